@@ -0,0 +1,237 @@
+//! Derive macros for `turing`, re-exported by it with the `derive` feature.
+
+use std::collections::BTreeSet;
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{Data, DeriveInput, Error, Fields, Ident, LitChar, LitStr, Token, Variant};
+
+/// Implements `Symbol` for an enum of unit variants.
+///
+/// Every variant takes the character it's written as, as in
+/// `#[symbol('1')]`, and at most one can be the blank, with
+/// `#[symbol('_', blank)]`, which also implements `Default` to return it.
+/// Ordinals are the variants' positions in the declaration.
+#[proc_macro_derive(Symbol, attributes(symbol))]
+pub fn derive_symbol(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+
+    symbol(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// Implements `TuringState` and `Display` for an enum of unit variants.
+///
+/// States are named after their variants unless renamed with
+/// `#[state(name = "q0")]`, and numbered in declaration order.
+#[proc_macro_derive(TuringState, attributes(state))]
+pub fn derive_turing_state(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+
+    turing_state(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+enum SymbolArgument {
+    Char(LitChar),
+    Blank,
+}
+
+impl Parse for SymbolArgument {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(LitChar) {
+            return Ok(Self::Char(input.parse()?));
+        }
+
+        let ident: Ident = input.parse()?;
+
+        match ident == "blank" {
+            true => Ok(Self::Blank),
+            false => Err(Error::new(ident.span(), "expected a char or `blank`")),
+        }
+    }
+}
+
+fn symbol(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    let mut variants = Vec::new();
+    let mut chars = BTreeSet::new();
+    let mut blank = None;
+
+    for variant in unit_variants(input, "Symbol")? {
+        let mut c = None;
+
+        for attribute in variant.attrs.iter().filter(|a| a.path().is_ident("symbol")) {
+            let arguments = attribute
+                .parse_args_with(Punctuated::<SymbolArgument, Token![,]>::parse_terminated)?;
+
+            for argument in arguments {
+                match argument {
+                    SymbolArgument::Char(lit) if c.is_some() => {
+                        return Err(Error::new(lit.span(), "the variant already has a char"));
+                    }
+                    SymbolArgument::Char(lit) => {
+                        if !chars.insert(lit.value()) {
+                            return Err(Error::new(lit.span(), "another variant has this char"));
+                        }
+
+                        c = Some(lit);
+                    }
+                    SymbolArgument::Blank if blank.is_some() => {
+                        return Err(Error::new_spanned(
+                            attribute,
+                            "only one variant can be blank",
+                        ));
+                    }
+                    SymbolArgument::Blank => blank = Some(&variant.ident),
+                }
+            }
+        }
+
+        match c {
+            Some(c) => variants.push((&variant.ident, c)),
+            None => {
+                return Err(Error::new_spanned(
+                    variant,
+                    "missing `#[symbol('c')]` for the variant's char",
+                ))
+            }
+        }
+    }
+
+    let from_char = variants.iter().map(|(v, c)| quote!(#c => Some(Self::#v)));
+    let to_char = variants.iter().map(|(v, c)| quote!(Self::#v => #c));
+    let ordinal = variants
+        .iter()
+        .zip(0u32..)
+        .map(|((v, _), i)| quote!(Self::#v => #i));
+    let from_ordinal = variants
+        .iter()
+        .zip(0u32..)
+        .map(|((v, _), i)| quote!(#i => Some(Self::#v)));
+    let default = blank.map(|blank| {
+        quote! {
+            impl #impl_generics ::core::default::Default for #name #type_generics #where_clause {
+                fn default() -> Self {
+                    Self::#blank
+                }
+            }
+        }
+    });
+
+    Ok(quote! {
+        impl #impl_generics ::turing::Symbol for #name #type_generics #where_clause {
+            fn from_char(c: char) -> ::core::option::Option<Self> {
+                match c {
+                    #(#from_char,)*
+                    _ => None,
+                }
+            }
+
+            fn to_char(&self) -> char {
+                match self {
+                    #(#to_char,)*
+                }
+            }
+
+            fn ordinal(&self) -> u32 {
+                match self {
+                    #(#ordinal,)*
+                }
+            }
+
+            fn from_ordinal(ordinal: u32) -> ::core::option::Option<Self> {
+                match ordinal {
+                    #(#from_ordinal,)*
+                    _ => None,
+                }
+            }
+        }
+
+        #default
+    })
+}
+
+fn turing_state(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    let mut variants = Vec::new();
+    let mut names = BTreeSet::new();
+
+    for variant in unit_variants(input, "TuringState")? {
+        let mut state_name = LitStr::new(&variant.ident.to_string(), variant.ident.span());
+
+        for attribute in variant.attrs.iter().filter(|a| a.path().is_ident("state")) {
+            attribute.parse_nested_meta(|meta| match meta.path.is_ident("name") {
+                true => {
+                    state_name = meta.value()?.parse()?;
+                    Ok(())
+                }
+                false => Err(meta.error("expected `name`")),
+            })?;
+        }
+
+        if !names.insert(state_name.value()) {
+            return Err(Error::new(state_name.span(), "another state has this name"));
+        }
+
+        variants.push((&variant.ident, state_name));
+    }
+
+    let states = variants.iter().map(|(v, _)| quote!(Self::#v));
+    let names = variants.iter().map(|(v, n)| quote!(Self::#v => #n));
+    let ordinals = variants
+        .iter()
+        .zip(0usize..)
+        .map(|((v, _), i)| quote!(Self::#v => #i));
+
+    Ok(quote! {
+        impl #impl_generics ::turing::TuringState for #name #type_generics #where_clause {
+            const STATES: &'static [Self] = &[#(#states),*];
+
+            fn name(&self) -> &'static str {
+                match self {
+                    #(#names,)*
+                }
+            }
+
+            fn ordinal(&self) -> usize {
+                match self {
+                    #(#ordinals,)*
+                }
+            }
+        }
+
+        impl #impl_generics ::core::fmt::Display for #name #type_generics #where_clause {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                f.write_str(::turing::TuringState::name(self))
+            }
+        }
+    })
+}
+
+fn unit_variants<'a>(input: &'a DeriveInput, derive: &str) -> syn::Result<Vec<&'a Variant>> {
+    let Data::Enum(data) = &input.data else {
+        return Err(Error::new(
+            Span::call_site(),
+            format!("`{derive}` can only be derived for enums"),
+        ));
+    };
+
+    data.variants
+        .iter()
+        .map(|variant| match variant.fields {
+            Fields::Unit => Ok(variant),
+            _ => Err(Error::new_spanned(
+                variant,
+                format!("`{derive}` needs variants without fields"),
+            )),
+        })
+        .collect()
+}
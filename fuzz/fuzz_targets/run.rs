@@ -0,0 +1,6 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use turing::fuzz::FuzzMachine;
+
+fuzz_target!(|machine: FuzzMachine| turing::fuzz::check_run(&machine));
@@ -0,0 +1,19 @@
+#ifndef TURING_H
+#define TURING_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+typedef struct TuringMachine TuringMachine;
+
+TuringMachine *turing_machine_new(const char *table, const char *state,
+                                  const uint8_t *tape, size_t tape_len);
+uint64_t turing_machine_step(TuringMachine *machine, uint64_t steps);
+bool turing_machine_halted(const TuringMachine *machine);
+uint64_t turing_machine_steps(const TuringMachine *machine);
+size_t turing_machine_head(const TuringMachine *machine);
+const uint8_t *turing_machine_tape(TuringMachine *machine, size_t *len);
+void turing_machine_free(TuringMachine *machine);
+
+#endif
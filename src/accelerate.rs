@@ -0,0 +1,692 @@
+//! Running machines from a blank tape faster than step by step, on tapes
+//! stored as runs of equal blocks of cells.
+//!
+//! The head moves over a whole run at once when running through one block
+//! of it leaves it in the state and direction it came in with. Beyond that,
+//! configurations that come back with only the lengths of their runs changed
+//! become candidates for an [`InferredRule`]: simulating with the lengths
+//! left symbolic proves that the same macro steps always lead back, adding
+//! the same amount to each run. A proven rule is then applied as many times
+//! as the runs allow in one go, skipping over sweeps whose number of steps
+//! grows with the runs.
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::{Move, State, TransitionTable};
+
+/// A rule found by [`Accelerator`]: from a configuration in `state` with the
+/// head facing `facing` and runs of the blocks `left` and `right` (nearest
+/// the head last), if every run is at least as long as its entry in
+/// `minimums`, the machine comes back with each run longer by its entry in
+/// `deltas`, taking `base_steps + Σ steps_per_count[i] * (count_i -
+/// minimums[i])` steps.
+///
+/// Runs are listed left side first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InferredRule<S, Sym> {
+    pub state: S,
+    pub facing: Move,
+    pub left: Vec<Vec<Sym>>,
+    pub right: Vec<Vec<Sym>>,
+    pub minimums: Vec<u64>,
+    pub deltas: Vec<i64>,
+    pub base_steps: u64,
+    pub steps_per_count: Vec<u64>,
+    /// How many times the rule was applied.
+    pub applications: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Halted,
+    /// The machine provably runs forever.
+    Forever,
+    StepLimit,
+    /// A run or the step count no longer fits a `u64`.
+    Overflow,
+    /// A [`Move::By`] jumped past the next block, which runs of blocks can't
+    /// follow.
+    Unsupported,
+}
+
+/// Runs a table from a blank tape with chain steps and inferred rules.
+#[derive(Debug, Clone)]
+pub struct Accelerator<'a, S, Sym> {
+    table: &'a TransitionTable<S, Sym>,
+    block_size: usize,
+    config: Config<S, Sym, u64>,
+    halted: bool,
+    steps: u64,
+    macro_steps: u64,
+    // the counts and macro step of the last visit to each skeleton since the
+    // last rule application
+    history: BTreeMap<Skeleton<S, Sym>, (Vec<u64>, u64)>,
+    rules: Vec<InferredRule<S, Sym>>,
+}
+
+type Skeleton<S, Sym> = (S, Move, Vec<Vec<Sym>>, Vec<Vec<Sym>>);
+
+// configurations with more runs than this are neither remembered nor matched
+// against rules; tapes that never settle into long runs would otherwise make
+// the history grow quadratically
+const MAX_RUNS: usize = 64;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Config<S, Sym, C> {
+    state: S,
+    facing: Move,
+    // runs of equal blocks with blanks beyond both ends, nearest the head
+    // last; the head enters the last block of the side it faces
+    left: Vec<(Vec<Sym>, C)>,
+    right: Vec<(Vec<Sym>, C)>,
+}
+
+// what running inside one block does
+enum Transition<S, Sym> {
+    Exit {
+        block: Vec<Sym>,
+        state: S,
+        side: Move,
+        steps: u64,
+    },
+    Halt {
+        block: Vec<Sym>,
+        steps: u64,
+    },
+    Loop,
+    // a jump past the neighbouring block
+    Jump,
+}
+
+enum Step<C> {
+    Moved(C),
+    Halted(C),
+    Forever,
+    // overflowing, or a symbolic run that might have ended
+    Unknown,
+    Jump,
+}
+
+trait Count: Clone {
+    fn constant(n: u64) -> Self;
+
+    fn add(&self, other: &Self) -> Option<Self>;
+
+    fn scale(&self, by: u64) -> Option<Self>;
+
+    /// The count less one, `Some(None)` for an emptied run and `None` if it
+    /// can't be told whether the run is empty.
+    fn decrement(&self) -> Option<Option<Self>>;
+}
+
+impl Count for u64 {
+    fn constant(n: u64) -> Self {
+        n
+    }
+
+    fn add(&self, other: &Self) -> Option<Self> {
+        self.checked_add(*other)
+    }
+
+    fn scale(&self, by: u64) -> Option<Self> {
+        self.checked_mul(by)
+    }
+
+    fn decrement(&self) -> Option<Option<Self>> {
+        Some(self.checked_sub(1).filter(|&rest| rest > 0))
+    }
+}
+
+// `constant + Σ coefficients[i] * x_i` for non-negative variables `x_i`
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Affine {
+    constant: u64,
+    coefficients: BTreeMap<usize, u64>,
+}
+
+impl Count for Affine {
+    fn constant(constant: u64) -> Self {
+        Self {
+            constant,
+            coefficients: BTreeMap::new(),
+        }
+    }
+
+    fn add(&self, other: &Self) -> Option<Self> {
+        let mut sum = self.clone();
+        sum.constant = sum.constant.checked_add(other.constant)?;
+
+        for (&variable, &coefficient) in &other.coefficients {
+            let entry = sum.coefficients.entry(variable).or_insert(0);
+            *entry = entry.checked_add(coefficient)?;
+        }
+
+        Some(sum)
+    }
+
+    fn scale(&self, by: u64) -> Option<Self> {
+        let mut coefficients = self.coefficients.clone();
+
+        for coefficient in coefficients.values_mut() {
+            *coefficient = coefficient.checked_mul(by)?;
+        }
+
+        Some(Self {
+            constant: self.constant.checked_mul(by)?,
+            coefficients,
+        })
+    }
+
+    fn decrement(&self) -> Option<Option<Self>> {
+        match (self.constant, self.coefficients.is_empty()) {
+            (1, true) => Some(None),
+            (0 | 1, false) => None,
+            (constant, _) => Some(Some(Self {
+                constant: constant - 1,
+                coefficients: self.coefficients.clone(),
+            })),
+        }
+    }
+}
+
+// runs the machine inside `block`, entered from the side opposite `facing`,
+// until the head leaves it
+fn transition<S, Sym>(
+    table: &TransitionTable<S, Sym>,
+    state: &S,
+    facing: Move,
+    block: &[Sym],
+) -> Transition<S, Sym>
+where
+    S: Ord + Clone,
+    Sym: Ord + Clone,
+{
+    let mut block = block.to_vec();
+    let mut state = state.clone();
+    let mut head = match facing {
+        Move::Left => block.len() - 1,
+        Move::Right => 0,
+        Move::By(_) => unreachable!("the head faces left or right"),
+    };
+    let mut seen = BTreeSet::new();
+    let mut steps = 0;
+
+    loop {
+        if !seen.insert((state.clone(), head, block.clone())) {
+            return Transition::Loop;
+        }
+
+        steps += 1;
+
+        let Some(rule) = table.get(&state, &block[head]) else {
+            return Transition::Halt { block, steps };
+        };
+
+        if let Some(write) = &rule.write {
+            block[head] = write.clone();
+        }
+
+        match &rule.new_state {
+            None => {}
+            Some(State::State(next)) => state = next.clone(),
+            Some(State::Halt) => return Transition::Halt { block, steps },
+        }
+
+        let offset = rule.head_move.map_or(0, Move::offset);
+        let side = match head.checked_add_signed(offset) {
+            Some(to) if to < block.len() => {
+                head = to;
+                continue;
+            }
+            Some(to) if to == block.len() => Move::Right,
+            None if offset == -1 - head as isize => Move::Left,
+            _ => return Transition::Jump,
+        };
+
+        return Transition::Exit {
+            block,
+            state,
+            side,
+            steps,
+        };
+    }
+}
+
+impl<S, Sym, C> Config<S, Sym, C>
+where
+    S: Ord + Clone,
+    Sym: Ord + Clone + Default,
+    C: Count,
+{
+    fn skeleton(&self) -> Skeleton<S, Sym> {
+        let blocks = |runs: &[(Vec<Sym>, C)]| runs.iter().map(|(block, _)| block.clone()).collect();
+
+        (
+            self.state.clone(),
+            self.facing,
+            blocks(&self.left),
+            blocks(&self.right),
+        )
+    }
+
+    fn runs(&self) -> usize {
+        self.left.len() + self.right.len()
+    }
+
+    fn counts(&self) -> Vec<C> {
+        self.left
+            .iter()
+            .chain(&self.right)
+            .map(|(_, count)| count.clone())
+            .collect()
+    }
+
+    fn step(&mut self, table: &TransitionTable<S, Sym>, block_size: usize) -> Step<C> {
+        let blank = vec![Sym::default(); block_size];
+        let facing = self.facing;
+        let (ahead, behind) = match facing {
+            Move::Left => (&mut self.left, &mut self.right),
+            Move::Right => (&mut self.right, &mut self.left),
+            Move::By(_) => unreachable!("the head faces left or right"),
+        };
+        let read = ahead.last().map_or(&blank, |(block, _)| block);
+
+        let (block, next, side, steps) = match transition(table, &self.state, facing, read) {
+            Transition::Exit {
+                block,
+                state,
+                side,
+                steps,
+            } => (block, Some(state), side, steps),
+            Transition::Halt { block, steps } => (block, None, facing, steps),
+            Transition::Loop => return Step::Forever,
+            Transition::Jump => return Step::Jump,
+        };
+
+        if next.as_ref() == Some(&self.state) && side == facing {
+            let Some((_, count)) = ahead.pop() else {
+                return Step::Forever;
+            };
+
+            return match (count.scale(steps), push(behind, block, count, &blank)) {
+                (Some(steps), Some(())) => Step::Moved(steps),
+                _ => Step::Unknown,
+            };
+        }
+
+        if let Some((read, count)) = ahead.pop() {
+            match count.decrement() {
+                None => return Step::Unknown,
+                Some(Some(rest)) => ahead.push((read, rest)),
+                Some(None) => {}
+            }
+        }
+
+        let pushed = match side == facing {
+            true => push(behind, block, C::constant(1), &blank),
+            false => push(ahead, block, C::constant(1), &blank),
+        };
+
+        if pushed.is_none() {
+            return Step::Unknown;
+        }
+
+        self.facing = side;
+
+        match next {
+            Some(next) => {
+                self.state = next;
+                Step::Moved(C::constant(steps))
+            }
+            None => Step::Halted(C::constant(steps)),
+        }
+    }
+}
+
+// pushes a run, merging it into an equal one and leaving out blanks at the
+// end of the tape
+fn push<Sym, C>(
+    runs: &mut Vec<(Vec<Sym>, C)>,
+    block: Vec<Sym>,
+    count: C,
+    blank: &[Sym],
+) -> Option<()>
+where
+    Sym: PartialEq,
+    C: Count,
+{
+    match runs.last_mut() {
+        Some((last, total)) if *last == block => *total = total.add(&count)?,
+        None if block == blank => {}
+        _ => runs.push((block, count)),
+    }
+
+    Some(())
+}
+
+impl<'a, S, Sym> Accelerator<'a, S, Sym>
+where
+    S: Ord + Clone,
+    Sym: Ord + Clone + Default,
+{
+    /// An accelerator over single cells.
+    pub fn new(table: &'a TransitionTable<S, Sym>, start: S) -> Self {
+        Self::with_block_size(table, start, 1)
+    }
+
+    /// An accelerator over blocks of `block_size` cells, which finds runs of
+    /// repeated patterns that single cells miss.
+    ///
+    /// # Panics
+    ///
+    /// If `block_size` is 0.
+    pub fn with_block_size(
+        table: &'a TransitionTable<S, Sym>,
+        start: S,
+        block_size: usize,
+    ) -> Self {
+        assert!(block_size > 0, "blocks need cells");
+
+        Self {
+            table,
+            block_size,
+            config: Config {
+                state: start,
+                facing: Move::Right,
+                left: Vec::new(),
+                right: Vec::new(),
+            },
+            halted: false,
+            steps: 0,
+            macro_steps: 0,
+            history: BTreeMap::new(),
+            rules: Vec::new(),
+        }
+    }
+
+    /// The number of steps the machine has taken.
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    pub fn halted(&self) -> bool {
+        self.halted
+    }
+
+    /// The number of non-blank symbols on the tape.
+    pub fn sigma(&self) -> u64 {
+        let blank = Sym::default();
+
+        self.config
+            .left
+            .iter()
+            .chain(&self.config.right)
+            .map(|(block, count)| block.iter().filter(|&sym| *sym != blank).count() as u64 * count)
+            .sum()
+    }
+
+    /// The rules proven so far, in the order they were found.
+    pub fn rules(&self) -> &[InferredRule<S, Sym>] {
+        &self.rules
+    }
+
+    /// Runs until the machine halts, is shown to run forever, or has taken at
+    /// least `max_steps` steps; a single chain step or rule application can
+    /// go past the limit.
+    pub fn run(&mut self, max_steps: u64) -> Outcome {
+        while !self.halted {
+            if self.steps >= max_steps {
+                return Outcome::StepLimit;
+            }
+
+            match self.apply_rule() {
+                Some(Ok(())) => continue,
+                Some(Err(outcome)) => return outcome,
+                None => {}
+            }
+
+            let taken = match self.config.step(self.table, self.block_size) {
+                Step::Moved(taken) => taken,
+                Step::Halted(taken) => {
+                    self.halted = true;
+                    taken
+                }
+                Step::Forever => return Outcome::Forever,
+                Step::Unknown => return Outcome::Overflow,
+                Step::Jump => return Outcome::Unsupported,
+            };
+
+            let Some(steps) = self.steps.checked_add(taken) else {
+                return Outcome::Overflow;
+            };
+
+            self.steps = steps;
+            self.macro_steps += 1;
+            self.infer();
+        }
+
+        Outcome::Halted
+    }
+
+    // applies the first rule matching the configuration as often as it can,
+    // if any does
+    fn apply_rule(&mut self) -> Option<Result<(), Outcome>> {
+        if self.config.runs() > MAX_RUNS {
+            return None;
+        }
+
+        let (state, facing, left, right) = self.config.skeleton();
+        let counts = self.config.counts();
+        let rule = self.rules.iter_mut().find(|rule| {
+            (&rule.state, rule.facing, &rule.left, &rule.right) == (&state, facing, &left, &right)
+                && counts
+                    .iter()
+                    .zip(&rule.minimums)
+                    .all(|(count, min)| count >= min)
+        })?;
+
+        let applications = counts
+            .iter()
+            .zip(&rule.minimums)
+            .zip(&rule.deltas)
+            .filter(|&(_, &delta)| delta < 0)
+            .map(|((count, min), delta)| (count - min) / delta.unsigned_abs() + 1)
+            .min();
+
+        let Some(k) = applications else {
+            return Some(Err(Outcome::Forever));
+        };
+
+        // the steps of application j are affine in the counts, which
+        // change by j * delta
+        let k = i128::from(k);
+        let mut steps = k * i128::from(rule.base_steps);
+
+        for (i, count) in counts.iter().enumerate() {
+            let x = i128::from(count - rule.minimums[i]);
+            let delta = i128::from(rule.deltas[i]);
+            steps += i128::from(rule.steps_per_count[i]) * (k * x + delta * k * (k - 1) / 2);
+        }
+
+        let steps = u64::try_from(steps)
+            .ok()
+            .and_then(|steps| self.steps.checked_add(steps));
+        let Some(steps) = steps else {
+            return Some(Err(Outcome::Overflow));
+        };
+
+        let runs = self.config.left.iter_mut().chain(&mut self.config.right);
+
+        for ((_, count), &delta) in runs.zip(&rule.deltas) {
+            let changed = i128::from(*count) + k * i128::from(delta);
+
+            match u64::try_from(changed) {
+                Ok(changed) => *count = changed,
+                Err(_) => return Some(Err(Outcome::Overflow)),
+            }
+        }
+
+        rule.applications += k as u64;
+        self.steps = steps;
+        self.macro_steps += 1;
+        self.history.clear();
+        Some(Ok(()))
+    }
+
+    // looks for an earlier visit to the current skeleton and tries to prove
+    // a rule from it
+    fn infer(&mut self) {
+        if self.halted || self.config.runs() > MAX_RUNS {
+            return;
+        }
+
+        let skeleton = self.config.skeleton();
+        let counts = self.config.counts();
+        let earlier = self
+            .history
+            .insert(skeleton.clone(), (counts.clone(), self.macro_steps));
+
+        let Some((before, at)) = earlier else {
+            return;
+        };
+
+        let deltas: Option<Vec<i64>> = counts
+            .iter()
+            .zip(&before)
+            .map(|(&after, &before)| i64::try_from(i128::from(after) - i128::from(before)).ok())
+            .collect();
+
+        if let Some(rule) =
+            deltas.and_then(|deltas| self.prove(skeleton, deltas, self.macro_steps - at))
+        {
+            self.rules.push(rule);
+        }
+    }
+
+    // simulates `macro_steps` macro steps from the skeleton with each run
+    // `minimum + x_i` long, checking that it ends with each run `delta` longer
+    fn prove(
+        &self,
+        (state, facing, left, right): Skeleton<S, Sym>,
+        deltas: Vec<i64>,
+        macro_steps: u64,
+    ) -> Option<InferredRule<S, Sym>> {
+        let minimums: Vec<u64> = deltas
+            .iter()
+            .map(|&delta| (1 - delta).max(1) as u64)
+            .collect();
+        let run = |i: usize| Affine {
+            constant: minimums[i],
+            coefficients: BTreeMap::from([(i, 1)]),
+        };
+        let mut config = Config {
+            state: state.clone(),
+            facing,
+            left: left
+                .iter()
+                .cloned()
+                .enumerate()
+                .map(|(i, block)| (block, run(i)))
+                .collect(),
+            right: right
+                .iter()
+                .cloned()
+                .enumerate()
+                .map(|(i, block)| (block, run(left.len() + i)))
+                .collect(),
+        };
+        let mut steps = Affine::constant(0);
+
+        for _ in 0..macro_steps {
+            match config.step(self.table, self.block_size) {
+                Step::Moved(taken) => steps = steps.add(&taken)?,
+                _ => return None,
+            }
+        }
+
+        if config.skeleton() != (state.clone(), facing, left.clone(), right.clone()) {
+            return None;
+        }
+
+        for (i, count) in config.counts().into_iter().enumerate() {
+            let expected = Affine {
+                constant: u64::try_from(minimums[i] as i64 + deltas[i]).ok()?,
+                coefficients: BTreeMap::from([(i, 1)]),
+            };
+
+            if count != expected {
+                return None;
+            }
+        }
+
+        let variables = minimums.len();
+
+        Some(InferredRule {
+            state,
+            facing,
+            left,
+            right,
+            minimums,
+            deltas,
+            base_steps: steps.constant,
+            steps_per_count: (0..variables)
+                .map(|i| steps.coefficients.get(&i).copied().unwrap_or(0))
+                .collect(),
+            applications: 0,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::bbchallenge;
+
+    fn run(notation: &str, block_size: usize, max_steps: u64) -> (Outcome, u64, u64, usize) {
+        let definition = bbchallenge::parse::<u8>(notation).unwrap();
+        let mut accelerator =
+            Accelerator::with_block_size(&definition.table, definition.start.clone(), block_size);
+        let outcome = accelerator.run(max_steps);
+
+        (
+            outcome,
+            accelerator.steps(),
+            accelerator.sigma(),
+            accelerator.rules().len(),
+        )
+    }
+
+    #[test]
+    fn champions_test() {
+        for (notation, block_size, steps, sigma) in [
+            ("1RB1LB_1LA1RZ", 1, 6, 4),
+            ("1RB1LB_1LA0LC_1RZ1LD_1RD0RA", 1, 107, 13),
+            ("1RB2LA1RA1RA_1LB1LA3RB1RZ", 1, 3_932_964, 2050),
+            ("1RB1LC_1RC1RB_1RD0LE_1LA1LD_1RZ0LA", 3, 47_176_870, 4098),
+        ] {
+            let (outcome, taken, ones, _) = run(notation, block_size, u64::MAX);
+
+            assert_eq!(outcome, Outcome::Halted, "{notation}");
+            assert_eq!((taken, ones), (steps, sigma), "{notation}");
+        }
+
+        // the 5-state champion's runs repeat in blocks of three cells
+        let (_, _, _, rules) = run("1RB1LC_1RC1RB_1RD0LE_1LA1LD_1RZ0LA", 3, u64::MAX);
+
+        assert!(rules > 0);
+    }
+
+    #[test]
+    fn forever_test() {
+        // sweeps back and forth over a run of ones that grows on both sides
+        let (outcome, _, _, rules) = run("1RB1RA_1LA1LB", 1, 1_000_000);
+
+        assert_eq!(outcome, Outcome::Forever);
+        assert!(rules > 0);
+
+        // runs off into the blanks
+        assert_eq!(run("1RA---", 1, 100).0, Outcome::Forever);
+    }
+}
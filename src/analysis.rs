@@ -0,0 +1,512 @@
+//! Static checks over transition tables.
+
+use alloc::collections::{BTreeMap, BTreeSet, VecDeque};
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::{Move, Rule, State, TransitionTable};
+
+/// States reachable from `start` by following rules, including `start`.
+pub fn reachable_states<'a, S, Sym>(
+    table: &'a TransitionTable<S, Sym>,
+    start: &'a S,
+) -> BTreeSet<&'a S>
+where
+    S: Ord,
+    Sym: Ord,
+{
+    let mut reachable = BTreeSet::from([start]);
+    let mut pending = vec![start];
+
+    while let Some(state) = pending.pop() {
+        let next = table
+            .rules(state)
+            .filter_map(|(_, rule)| match &rule.new_state {
+                Some(State::State(next)) => Some(next),
+                _ => None,
+            });
+
+        for next in next {
+            if reachable.insert(next) {
+                pending.push(next);
+            }
+        }
+    }
+
+    reachable
+}
+
+/// States that have rules but can never be entered from `start`.
+pub fn unreachable_states<'a, S, Sym>(
+    table: &'a TransitionTable<S, Sym>,
+    start: &'a S,
+) -> Vec<&'a S>
+where
+    S: Ord,
+    Sym: Ord,
+{
+    let reachable = reachable_states(table, start);
+
+    table
+        .states()
+        .filter(|state| !reachable.contains(state))
+        .collect()
+}
+
+/// Every symbol read or written by some rule.
+pub fn alphabet<S, Sym>(table: &TransitionTable<S, Sym>) -> BTreeSet<&Sym>
+where
+    S: Ord,
+    Sym: Ord,
+{
+    table
+        .iter()
+        .flat_map(|(_, read, rule)| [Some(read), rule.write.as_ref()])
+        .flatten()
+        .collect()
+}
+
+/// `(state, symbol)` pairs over the reachable states and `alphabet` that
+/// have no rule, i.e. where a run would halt implicitly.
+pub fn missing_rules<'a, S, Sym>(
+    table: &'a TransitionTable<S, Sym>,
+    start: &'a S,
+    alphabet: &BTreeSet<&'a Sym>,
+) -> Vec<(&'a S, &'a Sym)>
+where
+    S: Ord,
+    Sym: Ord,
+{
+    reachable_states(table, start)
+        .into_iter()
+        .flat_map(|state| alphabet.iter().map(move |symbol| (state, *symbol)))
+        .filter(|(state, symbol)| table.get(state, symbol).is_none())
+        .collect()
+}
+
+/// The `(state, symbol)` pairs without a rule, split by whether a run on
+/// some input may read that symbol in that state; see
+/// [`undefined_transitions`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Gaps<S, Sym> {
+    /// Pairs a run may get stuck at, as far as the analysis can tell.
+    pub reachable: Vec<(S, Sym)>,
+    /// Pairs no run can reach, whose rules would never be used.
+    pub dead: Vec<(S, Sym)>,
+}
+
+/// Splits the gaps of `table` over its states and symbols into those a run
+/// from `start` on an input over `input` may reach and those it can't.
+///
+/// The analysis only tracks which symbols may be on the tape, not where:
+/// after a move the head may read any of them. So every dead gap is truly
+/// dead, but some reachable ones may not be.
+pub fn undefined_transitions<S, Sym>(
+    table: &TransitionTable<S, Sym>,
+    start: &S,
+    input: &BTreeSet<Sym>,
+) -> Gaps<S, Sym>
+where
+    S: Ord + Clone,
+    Sym: Ord + Clone + Default,
+{
+    let mut initial = input.clone();
+    initial.insert(Sym::default());
+
+    // the symbols on the tape grow with the writes of the reachable rules
+    let mut tape = initial.clone();
+    let reached = loop {
+        let reached = reached_pairs(table, start, &initial, &tape);
+        let before = tape.len();
+
+        tape.extend(
+            reached
+                .iter()
+                .filter_map(|(state, symbol)| table.get(state, symbol)?.write.clone()),
+        );
+
+        if tape.len() == before {
+            break reached;
+        }
+    };
+
+    let mut states: BTreeSet<&S> = BTreeSet::from([start]);
+    states.extend(
+        table
+            .iter()
+            .flat_map(|(state, _, rule)| match &rule.new_state {
+                Some(State::State(next)) => [Some(state), Some(next)],
+                _ => [Some(state), None],
+            })
+            .flatten(),
+    );
+
+    let symbols: BTreeSet<&Sym> = alphabet(table).into_iter().chain(&tape).collect();
+    let (reachable, dead) = states
+        .into_iter()
+        .flat_map(|state| symbols.iter().map(move |symbol| (state, *symbol)))
+        .filter(|(state, symbol)| table.get(state, symbol).is_none())
+        .map(|(state, symbol)| (state.clone(), symbol.clone()))
+        .partition(|pair| reached.contains(pair));
+
+    Gaps { reachable, dead }
+}
+
+// the `(state, symbol)` pairs reachable when the tape starts out with
+// symbols of `initial` and at most ever holds symbols of `tape`
+fn reached_pairs<S, Sym>(
+    table: &TransitionTable<S, Sym>,
+    start: &S,
+    initial: &BTreeSet<Sym>,
+    tape: &BTreeSet<Sym>,
+) -> BTreeSet<(S, Sym)>
+where
+    S: Ord + Clone,
+    Sym: Ord + Clone,
+{
+    let mut reached = BTreeSet::new();
+    let mut pending: Vec<(S, Sym)> = initial
+        .iter()
+        .map(|symbol| (start.clone(), symbol.clone()))
+        .collect();
+
+    while let Some(pair) = pending.pop() {
+        if !reached.insert(pair.clone()) {
+            continue;
+        }
+
+        let (state, read) = pair;
+        let Some(rule) = table.get(&state, &read) else {
+            continue;
+        };
+        let next = match &rule.new_state {
+            Some(State::Halt) => continue,
+            Some(State::State(next)) => next.clone(),
+            None => state,
+        };
+
+        match rule.head_move.map_or(0, Move::offset) {
+            0 => pending.push((next, rule.write.clone().unwrap_or(read))),
+            _ => pending.extend(tape.iter().map(|symbol| (next.clone(), symbol.clone()))),
+        }
+    }
+
+    reached
+}
+
+/// A strongly connected component of the state graph: states that can all
+/// be reached from each other.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Component<'a, S> {
+    pub states: Vec<&'a S>,
+    /// Whether a run can stay in the component forever, i.e. it has a cycle;
+    /// otherwise its single state is transient.
+    pub recurrent: bool,
+    /// Whether a halting rule can be reached from the component.
+    pub can_halt: bool,
+}
+
+impl<S> Component<'_, S> {
+    /// Whether a run entering the component can loop but never halt.
+    pub fn is_trap(&self) -> bool {
+        self.recurrent && !self.can_halt
+    }
+}
+
+/// The strongly connected components of the state graph, including states
+/// only entered by rules, ordered so that rules only lead to later ones.
+pub fn components<S, Sym>(table: &TransitionTable<S, Sym>) -> Vec<Component<'_, S>>
+where
+    S: Ord,
+    Sym: Ord,
+{
+    let states: Vec<&S> = table
+        .iter()
+        .flat_map(|(state, _, rule)| match &rule.new_state {
+            Some(State::State(next)) => [Some(state), Some(next)],
+            _ => [Some(state), None],
+        })
+        .flatten()
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    let numbers: BTreeMap<&S, usize> = states.iter().enumerate().map(|(i, s)| (*s, i)).collect();
+
+    let mut successors = vec![Vec::new(); states.len()];
+    let mut halts = vec![false; states.len()];
+
+    for (state, _, rule) in table.iter() {
+        let from = numbers[state];
+
+        match &rule.new_state {
+            None => successors[from].push(from),
+            Some(State::State(next)) => successors[from].push(numbers[next]),
+            Some(State::Halt) => halts[from] = true,
+        }
+    }
+
+    // Tarjan's algorithm with an explicit stack, so large tables can't
+    // overflow the call stack; components come out successors first
+    const UNVISITED: usize = usize::MAX;
+    let mut index = vec![UNVISITED; states.len()];
+    let mut low = vec![0; states.len()];
+    let mut on_stack = vec![false; states.len()];
+    let mut stack = Vec::new();
+    let mut next_index = 0;
+    let mut component_of = vec![0; states.len()];
+    let mut members: Vec<Vec<usize>> = Vec::new();
+
+    for root in 0..states.len() {
+        if index[root] != UNVISITED {
+            continue;
+        }
+
+        let mut work = vec![(root, 0)];
+        index[root] = next_index;
+        low[root] = next_index;
+        next_index += 1;
+        stack.push(root);
+        on_stack[root] = true;
+
+        while let Some(&(state, edge)) = work.last() {
+            if let Some(&next) = successors[state].get(edge) {
+                work.last_mut().unwrap().1 += 1;
+
+                if index[next] == UNVISITED {
+                    index[next] = next_index;
+                    low[next] = next_index;
+                    next_index += 1;
+                    stack.push(next);
+                    on_stack[next] = true;
+                    work.push((next, 0));
+                } else if on_stack[next] {
+                    low[state] = low[state].min(index[next]);
+                }
+
+                continue;
+            }
+
+            work.pop();
+
+            if let Some(&(parent, _)) = work.last() {
+                low[parent] = low[parent].min(low[state]);
+            }
+
+            if low[state] == index[state] {
+                let mut component = Vec::new();
+
+                loop {
+                    let member = stack.pop().unwrap();
+                    on_stack[member] = false;
+                    component_of[member] = members.len();
+                    component.push(member);
+
+                    if member == state {
+                        break;
+                    }
+                }
+
+                members.push(component);
+            }
+        }
+    }
+
+    let mut can_halt = vec![false; members.len()];
+
+    for (number, component) in members.iter().enumerate() {
+        can_halt[number] = component.iter().any(|&state| {
+            halts[state]
+                || successors[state]
+                    .iter()
+                    .any(|&next| component_of[next] != number && can_halt[component_of[next]])
+        });
+    }
+
+    members
+        .into_iter()
+        .zip(can_halt)
+        .rev()
+        .map(|(component, can_halt)| {
+            let recurrent = component.len() > 1 || successors[component[0]].contains(&component[0]);
+            let mut states: Vec<&S> = component.into_iter().map(|state| states[state]).collect();
+            states.sort();
+
+            Component {
+                states,
+                recurrent,
+                can_halt,
+            }
+        })
+        .collect()
+}
+
+/// Why two tables aren't bisimilar: after reading `path` from their start
+/// states they are in `left` and `right`, whose rules for `symbol` differ.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Distinction<'a, S, T, Sym> {
+    pub path: Vec<&'a Sym>,
+    pub left: &'a S,
+    pub right: &'a T,
+    pub symbol: &'a Sym,
+}
+
+type Pairs<'a, S, T> = Vec<(&'a S, &'a T)>;
+
+/// Checks that `left` started in `left_start` and `right` started in
+/// `right_start` behave the same on every tape, up to renaming states: each
+/// pair of corresponding states writes, moves and halts alike on every
+/// symbol, and leads to corresponding states. Returns the pairs of
+/// corresponding states reachable from the start states.
+///
+/// Leaving a cell untouched counts the same as writing back what was read,
+/// and a missing rule only matches a missing rule.
+pub fn bisimulation<'a, S, T, Sym>(
+    left: &'a TransitionTable<S, Sym>,
+    left_start: &'a S,
+    right: &'a TransitionTable<T, Sym>,
+    right_start: &'a T,
+) -> Result<Pairs<'a, S, T>, Distinction<'a, S, T, Sym>>
+where
+    S: Ord,
+    T: Ord,
+    Sym: Ord,
+{
+    let mut related = BTreeSet::from([(left_start, right_start)]);
+    let mut pending = VecDeque::from([(left_start, right_start, Vec::new())]);
+
+    while let Some((state, other, path)) = pending.pop_front() {
+        let symbols: BTreeSet<&Sym> = left
+            .rules(state)
+            .map(|(symbol, _)| symbol)
+            .chain(right.rules(other).map(|(symbol, _)| symbol))
+            .collect();
+
+        for symbol in symbols {
+            let next = match (left.get(state, symbol), right.get(other, symbol)) {
+                (None, None) => continue,
+                (Some(rule), Some(other_rule))
+                    if effect(rule, symbol) == effect(other_rule, symbol) =>
+                {
+                    match (&rule.new_state, &other_rule.new_state) {
+                        (Some(State::Halt), Some(State::Halt)) => continue,
+                        (Some(State::Halt), _) | (_, Some(State::Halt)) => None,
+                        (next, other_next) => {
+                            Some((continued(next, state), continued(other_next, other)))
+                        }
+                    }
+                }
+                _ => None,
+            };
+
+            let Some(pair) = next else {
+                return Err(Distinction {
+                    path,
+                    left: state,
+                    right: other,
+                    symbol,
+                });
+            };
+
+            if related.insert(pair) {
+                let mut path = path.clone();
+                path.push(symbol);
+                pending.push_back((pair.0, pair.1, path));
+            }
+        }
+    }
+
+    Ok(related.into_iter().collect())
+}
+
+// what a rule does to the tape when reading `read`
+fn effect<'a, S, Sym>(rule: &'a Rule<S, Sym>, read: &'a Sym) -> (&'a Sym, isize) {
+    (
+        rule.write.as_ref().unwrap_or(read),
+        rule.head_move.map_or(0, Move::offset),
+    )
+}
+
+// the state a rule not halting leads to from `state`
+fn continued<'a, S>(next: &'a Option<State<S>>, state: &'a S) -> &'a S {
+    match next {
+        Some(State::State(next)) => next,
+        _ => state,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Rule;
+
+    #[test]
+    fn analysis_test() {
+        let table: TransitionTable<u8, u8> = [
+            (0, 0, Rule::to(1).write(1).right()),
+            (0, 1, Rule::new().right()),
+            (1, 0, Rule::halt()),
+            (2, 0, Rule::to(0)),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(reachable_states(&table, &0), BTreeSet::from([&0, &1]));
+        assert_eq!(unreachable_states(&table, &0), [&2]);
+        assert_eq!(alphabet(&table), BTreeSet::from([&0, &1]));
+        assert_eq!(missing_rules(&table, &0, &alphabet(&table)), [(&1, &1)]);
+    }
+
+    #[test]
+    fn undefined_transitions_test() {
+        // marks the ones of the input as twos, left to right; state 1 only
+        // reads the two just written, while state 0 reading a two can't
+        // happen but can't be ruled out without tracking positions
+        let table: TransitionTable<u8, u8> = [
+            (0, 1, Rule::to(1).write(2)),
+            (1, 2, Rule::to(0).right()),
+            (0, 0, Rule::halt()),
+        ]
+        .into_iter()
+        .collect();
+        let gaps = undefined_transitions(&table, &0, &BTreeSet::from([1]));
+
+        assert_eq!(gaps.reachable, [(0, 2)]);
+        assert_eq!(gaps.dead, [(1, 0), (1, 1)]);
+    }
+
+    #[test]
+    fn components_test() {
+        // 0 and 1 loop until 1 halts, 2 and 3 loop forever, 4 stays put
+        let table: TransitionTable<u8, u8> = [
+            (0, 0, Rule::to(1).right()),
+            (0, 1, Rule::to(2)),
+            (1, 0, Rule::to(0).left()),
+            (1, 1, Rule::halt()),
+            (2, 0, Rule::to(3)),
+            (3, 0, Rule::to(2).right()),
+            (3, 1, Rule::to(4)),
+            (4, 0, Rule::new().right()),
+        ]
+        .into_iter()
+        .collect();
+        let sccs = components(&table);
+        let traps: Vec<_> = sccs
+            .iter()
+            .filter(|component| component.is_trap())
+            .map(|component| component.states.clone())
+            .collect();
+
+        assert_eq!(sccs.len(), 3);
+        assert_eq!(sccs[0].states, [&0, &1]);
+        assert!(sccs[0].can_halt);
+        assert_eq!(traps, [vec![&2, &3], vec![&4]]);
+
+        let table: TransitionTable<u8, u8> = [(0, 0, Rule::to(1)), (1, 0, Rule::halt())]
+            .into_iter()
+            .collect();
+
+        assert!(components(&table)
+            .iter()
+            .all(|c| !c.recurrent && c.can_halt));
+    }
+}
@@ -0,0 +1,453 @@
+//! Generators of fixed-width arithmetic machines over [`Char`].
+//!
+//! The machines keep the cell the head is on in their state, so they only
+//! ever touch the cells of their [`Layout`] and their size grows with the
+//! width of the operands.
+
+use alloc::collections::{BTreeSet, VecDeque};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::ops::Range;
+
+use crate::{Char, Decode, Machine, Move, Rule, State, Symbol, TransitionTable};
+
+/// Where a generated machine expects its operands and leaves its result, as
+/// cells relative to cell 0. Numbers are in binary, least significant bit
+/// first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Layout {
+    pub operands: Vec<Range<usize>>,
+    pub result: Range<usize>,
+}
+
+/// A state of a generated machine: a phase of the computation along with the
+/// cell the head is on.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Cursor<P> {
+    pub phase: P,
+    pub cell: usize,
+}
+
+/// A generated machine, started in `start` on a tape laid out as `layout`
+/// says.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Generated<P> {
+    pub table: TransitionTable<Cursor<P>, Char>,
+    pub start: Cursor<P>,
+    pub layout: Layout,
+}
+
+impl<P: Clone> Generated<P> {
+    /// A machine with `operands` written on its tape, or `None` if there are
+    /// too few or too many of them or one does not fit its cells.
+    pub fn machine(&self, operands: &[u64]) -> Option<Machine<Cursor<P>, Char>> {
+        if operands.len() != self.layout.operands.len() {
+            return None;
+        }
+
+        let len = self.layout.operands.iter().map(|cells| cells.end).max();
+        let mut tape = VecDeque::from(alloc::vec![Char('0'); len.unwrap_or(1).max(1)]);
+
+        for (&operand, cells) in operands.iter().zip(&self.layout.operands) {
+            if cells.len() < 64 && operand >> cells.len() != 0 {
+                return None;
+            }
+
+            for (bit, cell) in cells.clone().enumerate() {
+                if bit < 64 && (operand >> bit) & 1 == 1 {
+                    tape[cell] = Char('1');
+                }
+            }
+        }
+
+        Some(Machine::new(self.start.clone(), tape))
+    }
+
+    /// The characters in the result cells of `machine`.
+    pub fn output(&self, machine: &Machine<Cursor<P>, Char>) -> String {
+        let tape = machine.tape();
+
+        self.layout
+            .result
+            .clone()
+            .map(|cell| {
+                tape.get(machine.origin() + cell)
+                    .map_or(Char::default().to_char(), Symbol::to_char)
+            })
+            .collect()
+    }
+
+    /// The result of `machine` as a number, with blank cells read as zeros.
+    pub fn result(&self, machine: &Machine<Cursor<P>, Char>) -> Option<u64> {
+        let output = self.output(machine).replace(Char::default().to_char(), "0");
+
+        u64::decode(&output)
+    }
+}
+
+/// The phases of an [`adder`].
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AdderPhase {
+    /// Reading the first operand.
+    Read {
+        bit: usize,
+        carry: bool,
+    },
+    /// Adding into the second operand.
+    Add {
+        bit: usize,
+        carry: bool,
+        a: bool,
+    },
+    Carry(bool),
+}
+
+/// An adder of two `width`-bit numbers, replacing the second one with their
+/// `width + 1`-bit sum.
+///
+/// # Panics
+///
+/// If `width` is 0.
+pub fn adder(width: usize) -> Generated<AdderPhase> {
+    assert!(width > 0, "zero-width adder");
+
+    let target = |phase: &AdderPhase| match *phase {
+        AdderPhase::Read { bit, .. } => bit,
+        AdderPhase::Add { bit, .. } => width + bit,
+        AdderPhase::Carry(_) => 2 * width,
+    };
+    let act = |phase: &AdderPhase, read: Char| match *phase {
+        AdderPhase::Read { bit, carry } => (
+            None,
+            State::State(AdderPhase::Add {
+                bit,
+                carry,
+                a: is_one(read),
+            }),
+        ),
+        AdderPhase::Add { bit, carry, a } => {
+            let (sum, carry) = full_add(a, is_one(read), carry);
+            let next = match bit + 1 < width {
+                true => AdderPhase::Read {
+                    bit: bit + 1,
+                    carry,
+                },
+                false => AdderPhase::Carry(carry),
+            };
+
+            (Some(sum), State::State(next))
+        }
+        AdderPhase::Carry(carry) => (Some(digit(carry)), State::Halt),
+    };
+
+    generate(
+        AdderPhase::Read {
+            bit: 0,
+            carry: false,
+        },
+        target,
+        act,
+        Layout {
+            operands: Vec::from([0..width, width..2 * width]),
+            result: width..2 * width + 1,
+        },
+    )
+}
+
+/// The phases of a [`comparator`].
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ComparatorPhase {
+    Read { bit: usize },
+    Compare { bit: usize, a: bool },
+    Write(Ordering),
+}
+
+/// A comparator of two `width`-bit numbers `a` and `b`, writing `<`, `=` or
+/// `>` as `a` is less than, equal to or greater than `b`.
+///
+/// # Panics
+///
+/// If `width` is 0.
+pub fn comparator(width: usize) -> Generated<ComparatorPhase> {
+    assert!(width > 0, "zero-width comparator");
+
+    let target = |phase: &ComparatorPhase| match *phase {
+        ComparatorPhase::Read { bit } => bit,
+        ComparatorPhase::Compare { bit, .. } => width + bit,
+        ComparatorPhase::Write(_) => 2 * width,
+    };
+    let act = |phase: &ComparatorPhase, read: Char| match *phase {
+        ComparatorPhase::Read { bit } => (
+            None,
+            State::State(ComparatorPhase::Compare {
+                bit,
+                a: is_one(read),
+            }),
+        ),
+        ComparatorPhase::Compare { bit, a } => {
+            let next = match (a.cmp(&is_one(read)), bit) {
+                (Ordering::Equal, 0) => ComparatorPhase::Write(Ordering::Equal),
+                (Ordering::Equal, bit) => ComparatorPhase::Read { bit: bit - 1 },
+                (ordering, _) => ComparatorPhase::Write(ordering),
+            };
+
+            (None, State::State(next))
+        }
+        ComparatorPhase::Write(ordering) => {
+            let symbol = match ordering {
+                Ordering::Less => '<',
+                Ordering::Equal => '=',
+                Ordering::Greater => '>',
+            };
+
+            (Some(Char(symbol)), State::Halt)
+        }
+    };
+
+    generate(
+        ComparatorPhase::Read { bit: width - 1 },
+        target,
+        act,
+        Layout {
+            operands: Vec::from([0..width, width..2 * width]),
+            result: 2 * width..2 * width + 1,
+        },
+    )
+}
+
+/// The phases of a [`multiplier`].
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MultiplierPhase {
+    /// Looking for a one in the second operand.
+    Check { bit: usize },
+    /// Decrementing the second operand.
+    Decrement { bit: usize },
+    /// Reading the first operand.
+    Read { bit: usize, carry: bool },
+    /// Adding into the product.
+    Add { bit: usize, carry: bool, a: bool },
+    /// Carrying into the upper half of the product.
+    Carry { bit: usize },
+}
+
+/// A multiplier of two `width`-bit numbers `a` and `b`, adding `a` to a
+/// `2 * width`-bit product `b` times while counting `b` down to 0.
+///
+/// # Panics
+///
+/// If `width` is 0.
+pub fn multiplier(width: usize) -> Generated<MultiplierPhase> {
+    assert!(width > 0, "zero-width multiplier");
+
+    let target = |phase: &MultiplierPhase| match *phase {
+        MultiplierPhase::Check { bit } | MultiplierPhase::Decrement { bit } => width + bit,
+        MultiplierPhase::Read { bit, .. } => bit,
+        MultiplierPhase::Add { bit, .. } | MultiplierPhase::Carry { bit } => 2 * width + bit,
+    };
+    let act = |phase: &MultiplierPhase, read: Char| match *phase {
+        MultiplierPhase::Check { bit } => match (is_one(read), bit + 1 < width) {
+            (true, _) => (None, State::State(MultiplierPhase::Decrement { bit: 0 })),
+            (false, true) => (None, State::State(MultiplierPhase::Check { bit: bit + 1 })),
+            (false, false) => (None, State::Halt),
+        },
+        // `b` is not 0 here, so there is a one to borrow from
+        MultiplierPhase::Decrement { bit } => match (is_one(read), bit + 1 < width) {
+            (true, _) => (
+                Some(Char('0')),
+                State::State(MultiplierPhase::Read {
+                    bit: 0,
+                    carry: false,
+                }),
+            ),
+            (false, true) => (
+                Some(Char('1')),
+                State::State(MultiplierPhase::Decrement { bit: bit + 1 }),
+            ),
+            (false, false) => (None, State::Halt),
+        },
+        MultiplierPhase::Read { bit, carry } => (
+            None,
+            State::State(MultiplierPhase::Add {
+                bit,
+                carry,
+                a: is_one(read),
+            }),
+        ),
+        MultiplierPhase::Add { bit, carry, a } => {
+            let (sum, carry) = full_add(a, is_one(read), carry);
+            let next = match (bit + 1 < width, carry) {
+                (true, _) => MultiplierPhase::Read {
+                    bit: bit + 1,
+                    carry,
+                },
+                (false, true) => MultiplierPhase::Carry { bit: bit + 1 },
+                (false, false) => MultiplierPhase::Check { bit: 0 },
+            };
+
+            (Some(sum), State::State(next))
+        }
+        // the product never overflows, so the carry stops within it
+        MultiplierPhase::Carry { bit } => match (is_one(read), bit + 1 < 2 * width) {
+            (true, true) => (
+                Some(Char('0')),
+                State::State(MultiplierPhase::Carry { bit: bit + 1 }),
+            ),
+            (true, false) => (None, State::Halt),
+            (false, _) => (
+                Some(Char('1')),
+                State::State(MultiplierPhase::Check { bit: 0 }),
+            ),
+        },
+    };
+
+    generate(
+        MultiplierPhase::Check { bit: 0 },
+        target,
+        act,
+        Layout {
+            operands: Vec::from([0..width, width..2 * width]),
+            result: 2 * width..4 * width,
+        },
+    )
+}
+
+fn is_one(symbol: Char) -> bool {
+    symbol == Char('1')
+}
+
+fn digit(bit: bool) -> Char {
+    match bit {
+        true => Char('1'),
+        false => Char('0'),
+    }
+}
+
+// the sum bit and the carry
+fn full_add(a: bool, b: bool, carry: bool) -> (Char, bool) {
+    (digit(a ^ b ^ carry), a & b | carry & (a ^ b))
+}
+
+// builds the table of the states reachable from `start` at cell 0. The head
+// walks to the `target` cell of each phase, where `act` says what to write and
+// which phase comes next
+fn generate<P>(
+    start: P,
+    target: impl Fn(&P) -> usize,
+    act: impl Fn(&P, Char) -> (Option<Char>, State<P>),
+    layout: Layout,
+) -> Generated<P>
+where
+    P: Ord + Clone,
+{
+    let alphabet = [Char('0'), Char('1'), Char::default()];
+    let start = Cursor {
+        phase: start,
+        cell: 0,
+    };
+    let mut table = TransitionTable::new();
+    let mut seen = BTreeSet::from([start.clone()]);
+    let mut pending = Vec::from([start.clone()]);
+
+    let toward = |cell: usize, target: usize| match cell.cmp(&target) {
+        Ordering::Less => (Some(Move::Right), cell + 1),
+        Ordering::Equal => (None, cell),
+        Ordering::Greater => (Some(Move::Left), cell - 1),
+    };
+
+    while let Some(cursor) = pending.pop() {
+        for read in alphabet {
+            let (write, phase) = match cursor.cell == target(&cursor.phase) {
+                true => act(&cursor.phase, read),
+                false => (None, State::State(cursor.phase.clone())),
+            };
+
+            let rule = match phase {
+                State::State(phase) => {
+                    let (head_move, cell) = toward(cursor.cell, target(&phase));
+                    let next = Cursor { phase, cell };
+
+                    if seen.insert(next.clone()) {
+                        pending.push(next.clone());
+                    }
+
+                    Rule {
+                        new_state: Some(State::State(next)),
+                        write,
+                        head_move,
+                    }
+                }
+                State::Halt => Rule {
+                    new_state: Some(State::Halt),
+                    write,
+                    head_move: None,
+                },
+            };
+
+            table.insert(cursor.clone(), read, rule);
+        }
+    }
+
+    Generated {
+        table,
+        start,
+        layout,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run<P: Ord + Clone>(generated: &Generated<P>, operands: &[u64]) -> Machine<Cursor<P>, Char> {
+        let mut machine = generated.machine(operands).unwrap();
+        machine.run_table(&generated.table, 100_000);
+
+        assert!(machine.halted());
+
+        machine
+    }
+
+    #[test]
+    fn adder_test() {
+        let adder = adder(4);
+
+        for (a, b) in [(0, 0), (3, 5), (15, 1), (15, 15), (9, 6)] {
+            let machine = run(&adder, &[a, b]);
+
+            assert_eq!(adder.result(&machine), Some(a + b));
+        }
+
+        assert!(adder.machine(&[16, 0]).is_none());
+        assert!(adder.machine(&[1]).is_none());
+    }
+
+    #[test]
+    fn comparator_test() {
+        let comparator = comparator(3);
+
+        for a in 0..8 {
+            for b in 0..8 {
+                let machine = run(&comparator, &[a, b]);
+                let expected = match a.cmp(&b) {
+                    Ordering::Less => "<",
+                    Ordering::Equal => "=",
+                    Ordering::Greater => ">",
+                };
+
+                assert_eq!(comparator.output(&machine), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn multiplier_test() {
+        let multiplier = multiplier(3);
+
+        for (a, b) in [(0, 0), (0, 5), (5, 0), (3, 3), (7, 7), (6, 5)] {
+            let machine = run(&multiplier, &[a, b]);
+
+            assert_eq!(multiplier.result(&machine), Some(a * b));
+        }
+    }
+}
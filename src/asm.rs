@@ -0,0 +1,494 @@
+//! A structured assembly language for writing machines, compiled to flat
+//! transition tables.
+//!
+//! A program is a list of instructions, one per line, optionally preceded by
+//! `label:` lines and followed by subroutines, each starting with a
+//! `sub name` line:
+//!
+//! ```text
+//! # adds two unary numbers separated by a blank
+//!     move right until _
+//!     write 1
+//!     move right until _
+//!     move left
+//!     write _
+//!     move left
+//!     call rewind
+//!     halt
+//!
+//! sub rewind
+//!     move left until _
+//!     move right
+//! ```
+//!
+//! The instructions are
+//!
+//! - `write x` writes `x` under the head,
+//! - `move left` or `move right`, optionally followed by a number of cells,
+//! - `move left until x y ...` moves until the head is on one of the symbols,
+//!   not moving at all if it already is,
+//! - `goto label` and `if x y ... goto label`, which jumps if the head is on
+//!   one of the symbols,
+//! - `call name` and `return`,
+//! - `halt`.
+//!
+//! Symbols are single characters of `Sym`, or `blank` for [`Symbol::blank`].
+//! Everything after a `#` is a comment. Running off the end of the program
+//! halts and running off the end of a subroutine returns.
+//!
+//! Every instruction compiles to one state with a rule for each symbol of
+//! the alphabet, which is the blank, every symbol the program mentions and
+//! those listed on an `alphabet x y ...` line. A state is named after the
+//! label of its instruction, or the nearest label before it as `label+1`,
+//! `label+2` and so on; each routine has an implicit label of its name at
+//! its start, `main` for the program itself. Machines have no call stack, so
+//! every `call` inlines its own copy of the subroutine with states prefixed
+//! by the calling state and a `/`, and subroutines may not be recursive.
+
+use alloc::borrow::ToOwned;
+use alloc::collections::BTreeSet;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::dsl::{Definition, ParseError};
+use crate::{Move, Rule, State, Symbol, TransitionTable};
+
+enum Instruction<Sym> {
+    Write(Sym),
+    Move(Move, usize),
+    Until(Move, Vec<Sym>),
+    Goto(String),
+    If(Vec<Sym>, String),
+    Call(String),
+    Return,
+    Halt,
+}
+
+struct Routine<Sym> {
+    name: String,
+    instructions: Vec<(usize, Instruction<Sym>)>,
+    labels: Vec<(String, usize)>,
+}
+
+impl<Sym> Routine<Sym> {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            instructions: Vec::new(),
+            labels: Vec::from([(name.to_owned(), 0)]),
+        }
+    }
+
+    fn label(&self, label: &str) -> Option<usize> {
+        self.labels
+            .iter()
+            .find(|(name, _)| name == label)
+            .map(|(_, index)| *index)
+    }
+
+    // the name of the state of instruction `index`, preferring the label
+    // declared last
+    fn state_name(&self, index: usize) -> String {
+        let (label, start) = self
+            .labels
+            .iter()
+            .filter(|(_, start)| *start <= index)
+            .max_by_key(|(_, start)| *start)
+            .unwrap();
+
+        match index - start {
+            0 => label.clone(),
+            offset => format!("{label}+{offset}"),
+        }
+    }
+}
+
+struct Compiler<'a, Sym> {
+    routines: &'a [Routine<Sym>],
+    alphabet: BTreeSet<Sym>,
+    table: TransitionTable<String, Sym>,
+    // indices of the routines being inlined, to reject recursion
+    active: Vec<usize>,
+}
+
+/// Compiles `src` into a transition table with string states, starting in
+/// the state of the first instruction.
+pub fn compile<Sym>(src: &str) -> Result<Definition<Sym>, ParseError>
+where
+    Sym: Symbol + Ord + Clone + Default,
+{
+    let (routines, alphabet) = parse(src)?;
+
+    if routines[0].instructions.is_empty() {
+        return Err(ParseError {
+            line: src.lines().count(),
+            message: "program has no instructions".to_owned(),
+        });
+    }
+
+    let mut compiler = Compiler {
+        routines: &routines,
+        alphabet,
+        table: TransitionTable::new(),
+        active: Vec::new(),
+    };
+    compiler.emit(0, "", State::Halt)?;
+
+    Ok(Definition {
+        start: routines[0].state_name(0),
+        blank: None,
+        input_alphabet: None,
+        tape_alphabet: None,
+        table: compiler.table,
+    })
+}
+
+fn parse<Sym>(src: &str) -> Result<(Vec<Routine<Sym>>, BTreeSet<Sym>), ParseError>
+where
+    Sym: Symbol + Ord + Clone + Default,
+{
+    let mut routines = Vec::from([Routine::new("main")]);
+    let mut alphabet = BTreeSet::from([Sym::blank()]);
+    let mut jumps = Vec::new();
+
+    for (index, line) in src.lines().enumerate() {
+        let line_number = index + 1;
+        let error = |message: String| ParseError {
+            line: line_number,
+            message,
+        };
+
+        let content = line.split('#').next().unwrap_or_default();
+        let fields: Vec<&str> = content.split_whitespace().collect();
+
+        let symbols = |fields: &[&str]| {
+            if fields.is_empty() {
+                return Err(error("expected symbols".to_owned()));
+            }
+
+            fields
+                .iter()
+                .map(|&field| {
+                    let mut chars = field.chars();
+
+                    match (field, chars.next(), chars.next()) {
+                        ("blank", _, _) => Some(Sym::blank()),
+                        (_, Some(c), None) => Sym::from_char(c),
+                        _ => None,
+                    }
+                    .ok_or_else(|| error(format!("invalid symbol {field:?}")))
+                })
+                .collect::<Result<Vec<Sym>, _>>()
+        };
+        let head_move = |field: &str| match field {
+            "left" => Ok(Move::Left),
+            "right" => Ok(Move::Right),
+            field => Err(error(format!("invalid direction {field:?}"))),
+        };
+
+        let current = routines.len() - 1;
+        let routine = &mut routines[current];
+        let instruction = match fields[..] {
+            [] => continue,
+            ["alphabet", ref rest @ ..] => {
+                alphabet.extend(symbols(rest)?);
+                continue;
+            }
+            ["sub", name] => {
+                if !is_identifier(name) {
+                    return Err(error(format!("invalid subroutine name {name:?}")));
+                }
+
+                if name == "main" || routines.iter().any(|routine| routine.name == name) {
+                    return Err(error(format!("duplicate subroutine {name:?}")));
+                }
+
+                routines.push(Routine::new(name));
+                continue;
+            }
+            [label] if label.ends_with(':') => {
+                let label = &label[..label.len() - 1];
+
+                if !is_identifier(label) {
+                    return Err(error(format!("invalid label {label:?}")));
+                }
+
+                if routine.label(label).is_some() {
+                    return Err(error(format!("duplicate label {label:?}")));
+                }
+
+                routine
+                    .labels
+                    .push((label.to_owned(), routine.instructions.len()));
+                continue;
+            }
+            ["write", symbol] => Instruction::Write(symbols(&[symbol])?.remove(0)),
+            ["move", direction] => Instruction::Move(head_move(direction)?, 1),
+            ["move", direction, "until", ref rest @ ..] => {
+                Instruction::Until(head_move(direction)?, symbols(rest)?)
+            }
+            ["move", direction, cells] => match cells.parse() {
+                Ok(cells) if cells > 0 => Instruction::Move(head_move(direction)?, cells),
+                _ => return Err(error(format!("invalid number of cells {cells:?}"))),
+            },
+            ["goto", label] => Instruction::Goto(label.to_owned()),
+            ["if", ref rest @ .., "goto", label] => {
+                Instruction::If(symbols(rest)?, label.to_owned())
+            }
+            ["call", name] => Instruction::Call(name.to_owned()),
+            ["return"] if current == 0 => {
+                return Err(error("return outside of a subroutine".to_owned()))
+            }
+            ["return"] => Instruction::Return,
+            ["halt"] => Instruction::Halt,
+            _ => return Err(error(format!("invalid instruction {:?}", content.trim()))),
+        };
+
+        match &instruction {
+            Instruction::Write(symbol) => {
+                alphabet.insert(symbol.clone());
+            }
+            Instruction::Until(_, targets) | Instruction::If(targets, _) => {
+                alphabet.extend(targets.iter().cloned());
+            }
+            _ => {}
+        }
+
+        if let Instruction::Goto(_) | Instruction::If(..) | Instruction::Call(_) = instruction {
+            jumps.push((current, routine.instructions.len()));
+        }
+
+        routine.instructions.push((line_number, instruction));
+    }
+
+    // labels and subroutines may be used before they are declared
+    for (routine, index) in jumps {
+        let (line, instruction) = &routines[routine].instructions[index];
+        let error = |message: String| ParseError {
+            line: *line,
+            message,
+        };
+
+        match instruction {
+            Instruction::Goto(label) | Instruction::If(_, label)
+                if routines[routine].label(label).is_none() =>
+            {
+                return Err(error(format!("undefined label {label:?}")));
+            }
+            Instruction::Call(name) if !routines[1..].iter().any(|sub| sub.name == *name) => {
+                return Err(error(format!("undefined subroutine {name:?}")));
+            }
+            _ => {}
+        }
+    }
+
+    Ok((routines, alphabet))
+}
+
+fn is_identifier(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+impl<Sym> Compiler<'_, Sym>
+where
+    Sym: Ord + Clone,
+{
+    // emits a copy of `routine` with states prefixed by `prefix` that
+    // continues in `ret` when it ends, returning the state it starts in
+    fn emit(
+        &mut self,
+        routine: usize,
+        prefix: &str,
+        ret: State<String>,
+    ) -> Result<State<String>, ParseError> {
+        let routines = self.routines;
+        let current = &routines[routine];
+        let state = |index: usize| match index < current.instructions.len() {
+            true => State::State(format!("{prefix}{}", current.state_name(index))),
+            false => ret.clone(),
+        };
+
+        self.active.push(routine);
+
+        for (index, (line, instruction)) in current.instructions.iter().enumerate() {
+            let name = format!("{prefix}{}", current.state_name(index));
+            let next = state(index + 1);
+
+            match instruction {
+                Instruction::Write(symbol) => self.emit_all(&name, |_| Rule {
+                    new_state: Some(next.clone()),
+                    write: Some(symbol.clone()),
+                    head_move: None,
+                }),
+                Instruction::Move(head_move, cells) => {
+                    for cell in 0..*cells {
+                        let from = match cell {
+                            0 => name.clone(),
+                            cell => format!("{name}~{cell}"),
+                        };
+                        let to = match cell + 1 == *cells {
+                            true => next.clone(),
+                            false => State::State(format!("{name}~{}", cell + 1)),
+                        };
+
+                        self.emit_all(&from, |_| Rule {
+                            new_state: Some(to.clone()),
+                            write: None,
+                            head_move: Some(*head_move),
+                        });
+                    }
+                }
+                Instruction::Until(head_move, targets) => {
+                    self.emit_all(&name, |symbol| match targets.contains(symbol) {
+                        true => jump(next.clone()),
+                        false => Rule {
+                            head_move: Some(*head_move),
+                            ..Rule::new()
+                        },
+                    })
+                }
+                Instruction::Goto(label) => {
+                    let target = state(current.label(label).unwrap());
+                    self.emit_all(&name, |_| jump(target.clone()));
+                }
+                Instruction::If(targets, label) => {
+                    let target = state(current.label(label).unwrap());
+                    self.emit_all(&name, |symbol| match targets.contains(symbol) {
+                        true => jump(target.clone()),
+                        false => jump(next.clone()),
+                    });
+                }
+                Instruction::Call(callee) => {
+                    let callee = (1..routines.len())
+                        .find(|&index| routines[index].name == *callee)
+                        .unwrap();
+
+                    if self.active.contains(&callee) {
+                        return Err(ParseError {
+                            line: *line,
+                            message: format!("recursive call of {:?}", routines[callee].name),
+                        });
+                    }
+
+                    let entry = self.emit(callee, &format!("{name}/"), next)?;
+                    self.emit_all(&name, |_| jump(entry.clone()));
+                }
+                Instruction::Return => self.emit_all(&name, |_| jump(ret.clone())),
+                Instruction::Halt => self.emit_all(&name, |_| Rule::halt()),
+            }
+        }
+
+        self.active.pop();
+
+        Ok(state(0))
+    }
+
+    fn emit_all(&mut self, state: &str, mut rule: impl FnMut(&Sym) -> Rule<String, Sym>) {
+        for symbol in &self.alphabet {
+            self.table
+                .insert(state.to_owned(), symbol.clone(), rule(symbol));
+        }
+    }
+}
+
+fn jump<Sym>(to: State<String>) -> Rule<String, Sym> {
+    Rule {
+        new_state: Some(to),
+        ..Rule::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Char, Machine};
+
+    const ADD: &str = "
+        # adds two unary numbers separated by a blank
+            move right until _
+            write 1
+            move right until _
+            move left
+            write _
+            move left
+            call rewind
+            halt
+
+        sub rewind
+            move left until _
+            move right
+    ";
+
+    fn run(definition: &Definition<Char>, input: &str) -> (Vec<Char>, isize) {
+        let mut machine: Machine<String, Char> =
+            Machine::with_input(definition.start.clone(), input).unwrap();
+        machine.run_table(&definition.table, 1_000);
+
+        assert!(machine.halted());
+
+        let head = machine.head() as isize - machine.origin() as isize;
+        let (tape, _, origin) = machine.finish_trimmed();
+
+        (tape, head + origin)
+    }
+
+    #[test]
+    fn compile_test() {
+        let definition = compile::<Char>(ADD).unwrap();
+
+        assert_eq!(definition.start, "main");
+        assert!(definition
+            .table
+            .get(&"main+6/rewind".to_owned(), &Char('1'))
+            .is_some());
+        assert_eq!(run(&definition, "11_111"), (Vec::from([Char('1'); 5]), 0));
+
+        // two calls inline two copies, and labels may be used before they
+        // are declared
+        let twice = "
+            alphabet 0 1
+            loop:
+                if _ goto done
+                call flip
+                call flip
+                move right
+                goto loop
+            done:
+
+            sub flip
+                if 0 goto zero
+                write 0
+                return
+            zero:
+                write 1
+        ";
+        let definition = compile::<Char>(twice).unwrap();
+        let states: BTreeSet<&String> = definition.table.states().collect();
+
+        assert!(states.contains(&"loop+1/flip".to_owned()));
+        assert!(states.contains(&"loop+2/zero".to_owned()));
+        assert_eq!(
+            run(&definition, "0110").0,
+            "0110".chars().map(Char).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn compile_error_test() {
+        let error = |src: &str| compile::<Char>(src).unwrap_err();
+
+        assert_eq!(error("goto nowhere").message, "undefined label \"nowhere\"");
+        assert_eq!(error("halt\n\ncall f").line, 3);
+        assert_eq!(error("return").message, "return outside of a subroutine");
+        assert_eq!(error("move up").message, "invalid direction \"up\"");
+        assert_eq!(
+            error("call f\nsub f\ncall g\nsub g\ncall f").message,
+            "recursive call of \"f\""
+        );
+        assert_eq!(error("# nothing").message, "program has no instructions");
+    }
+}
@@ -0,0 +1,130 @@
+use alloc::format;
+use alloc::string::String;
+use core::fmt::Debug;
+
+use crate::{Machine, Symbol, TransitionTable};
+
+/// Asserts that a table run from `start` on `input` halts within some number
+/// of steps, leaving `output` on the tape with the blanks at either end
+/// trimmed, and optionally that it halted from a given state:
+///
+/// ```
+/// use turing::{assert_machine, dsl, Char};
+///
+/// let table = dsl::parse::<Char>("inc 1 0 R *\ninc _ 1 N done").unwrap();
+///
+/// assert_machine!(&table, "inc", "11" => "001", within 10);
+/// assert_machine!(&table, "inc", "1" => "01", from "done", within 10);
+/// ```
+#[macro_export]
+macro_rules! assert_machine {
+    ($table:expr, $start:expr, $input:expr => $output:expr, within $max_steps:expr $(,)?) => {
+        if let Err(message) = $crate::__check_machine(
+            $table,
+            ::core::convert::Into::into($start),
+            $input,
+            $output,
+            None,
+            $max_steps,
+        ) {
+            panic!("{}", message);
+        }
+    };
+    (
+        $table:expr, $start:expr, $input:expr => $output:expr,
+        from $state:expr, within $max_steps:expr $(,)?
+    ) => {
+        if let Err(message) = $crate::__check_machine(
+            $table,
+            ::core::convert::Into::into($start),
+            $input,
+            $output,
+            Some(::core::convert::Into::into($state)),
+            $max_steps,
+        ) {
+            panic!("{}", message);
+        }
+    };
+}
+
+#[doc(hidden)]
+pub fn __check_machine<S, Sym>(
+    table: &TransitionTable<S, Sym>,
+    start: S,
+    input: &str,
+    output: &str,
+    state: Option<S>,
+    max_steps: u64,
+) -> Result<(), String>
+where
+    S: Ord + Clone + Debug,
+    Sym: Symbol + Ord + Clone + Default,
+{
+    let mut machine = Machine::with_input(start, input)
+        .ok_or_else(|| format!("input {input:?} has characters that aren't symbols"))?;
+    let mut last = None;
+    let mut steps = 0;
+
+    while !machine.halted() {
+        if steps == max_steps {
+            return Err(format!("{input:?} didn't halt within {max_steps} steps"));
+        }
+
+        last = machine.state().cloned();
+        machine.execute_table(table);
+        steps += 1;
+    }
+
+    if let Some(state) = state {
+        if last.as_ref() != Some(&state) {
+            return Err(format!(
+                "{input:?} halted from {last:?} instead of {state:?} after {steps} steps"
+            ));
+        }
+    }
+
+    let (tape, _, _) = machine.finish_trimmed();
+    let tape: String = tape.iter().map(Symbol::to_char).collect();
+
+    match tape == output {
+        true => Ok(()),
+        false => Err(format!(
+            "{input:?} left {tape:?} instead of {output:?} after {steps} steps"
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::borrow::ToOwned;
+
+    use super::*;
+    use crate::{dsl, Char};
+
+    #[test]
+    fn assert_machine_test() {
+        let table = dsl::parse::<Char>("inc 1 0 R *\ninc _ 1 N done").unwrap();
+
+        assert_machine!(&table, "inc", "" => "1", within 2);
+        assert_machine!(&table, "inc", "111" => "0001", from "done", within 10);
+
+        let check = |output, state: Option<&str>, max_steps| {
+            __check_machine(
+                &table,
+                "inc".to_owned(),
+                "11",
+                output,
+                state.map(str::to_owned),
+                max_steps,
+            )
+        };
+
+        assert_eq!(check("001", None, 10), Ok(()));
+        assert_eq!(
+            check("01", None, 10),
+            Err("\"11\" left \"001\" instead of \"01\" after 4 steps".to_owned())
+        );
+        assert!(check("001", Some("inc"), 10).is_err());
+        assert!(check("001", None, 2).is_err());
+    }
+}
@@ -0,0 +1,267 @@
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::vec;
+use alloc::vec::Vec;
+
+use super::Pda;
+
+/// A context-free grammar with variables `N` over terminals `T`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cfg<N, T> {
+    start: N,
+    productions: BTreeMap<N, BTreeSet<Vec<GrammarSymbol<N, T>>>>,
+}
+
+/// A symbol on the right of a production.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum GrammarSymbol<N, T> {
+    Variable(N),
+    Terminal(T),
+}
+
+// an Earley item: a production, how much of it has been matched and where
+// the match started
+type Item<'a, N, T> = (&'a N, &'a [GrammarSymbol<N, T>], usize, usize);
+
+impl<N, T> Cfg<N, T>
+where
+    N: Ord + Clone,
+    T: Ord + Clone,
+{
+    pub fn new(start: N) -> Self {
+        Self {
+            start,
+            productions: BTreeMap::new(),
+        }
+    }
+
+    /// Adds the production `variable → body`, an empty body deriving the
+    /// empty word.
+    pub fn insert(&mut self, variable: N, body: impl IntoIterator<Item = GrammarSymbol<N, T>>) {
+        self.productions
+            .entry(variable)
+            .or_default()
+            .insert(body.into_iter().collect());
+    }
+
+    pub fn start(&self) -> &N {
+        &self.start
+    }
+
+    /// All productions, ordered by variable.
+    pub fn productions(&self) -> impl Iterator<Item = (&N, &[GrammarSymbol<N, T>])> {
+        self.productions
+            .iter()
+            .flat_map(|(variable, bodies)| bodies.iter().map(move |body| (variable, &body[..])))
+    }
+
+    /// The terminals named in productions.
+    pub fn terminals(&self) -> BTreeSet<&T> {
+        self.productions()
+            .flat_map(|(_, body)| body)
+            .filter_map(|symbol| match symbol {
+                GrammarSymbol::Terminal(terminal) => Some(terminal),
+                GrammarSymbol::Variable(_) => None,
+            })
+            .collect()
+    }
+
+    /// The variables deriving the empty word.
+    pub fn nullable(&self) -> BTreeSet<&N> {
+        self.fixed_point(|nullable, symbol| match symbol {
+            GrammarSymbol::Variable(variable) => nullable.contains(variable),
+            GrammarSymbol::Terminal(_) => false,
+        })
+    }
+
+    /// Whether `input` derives from the start variable, by Earley's
+    /// algorithm, which takes any grammar.
+    pub fn generates(&self, input: &[T]) -> bool {
+        let nullable = self.nullable();
+        let mut columns: Vec<BTreeSet<Item<'_, N, T>>> = vec![BTreeSet::new(); input.len() + 1];
+        columns[0].extend(self.items(&self.start, 0));
+
+        for position in 0..=input.len() {
+            let mut pending: Vec<_> = columns[position].iter().copied().collect();
+
+            while let Some((variable, body, dot, origin)) = pending.pop() {
+                let advanced = (variable, body, dot + 1, origin);
+                let next: Vec<_> = match body.get(dot) {
+                    // completes the items that were waiting for `variable`
+                    None => columns[origin]
+                        .iter()
+                        .filter(|(_, body, dot, _)| {
+                            matches!(body.get(*dot), Some(GrammarSymbol::Variable(next)) if next == variable)
+                        })
+                        .map(|&(variable, body, dot, origin)| (variable, body, dot + 1, origin))
+                        .collect(),
+                    // predicts its productions, skipping it if it can be empty
+                    Some(GrammarSymbol::Variable(next)) => self
+                        .items(next, position)
+                        .chain(nullable.contains(next).then_some(advanced))
+                        .collect(),
+                    Some(GrammarSymbol::Terminal(terminal)) => {
+                        if input.get(position) == Some(terminal) {
+                            columns[position + 1].insert(advanced);
+                        }
+
+                        continue;
+                    }
+                };
+
+                for item in next {
+                    if columns[position].insert(item) {
+                        pending.push(item);
+                    }
+                }
+            }
+        }
+
+        columns[input.len()]
+            .iter()
+            .any(|&(variable, body, dot, origin)| {
+                *variable == self.start && dot == body.len() && origin == 0
+            })
+    }
+
+    /// An equivalent grammar without the variables that derive no word or
+    /// can't be reached from the start, and the productions naming them.
+    pub fn trim(&self) -> Self {
+        let generating = self.fixed_point(|generating, symbol| match symbol {
+            GrammarSymbol::Variable(variable) => generating.contains(variable),
+            GrammarSymbol::Terminal(_) => true,
+        });
+        let useful = |body: &[GrammarSymbol<N, T>]| {
+            body.iter().all(|symbol| match symbol {
+                GrammarSymbol::Variable(variable) => generating.contains(variable),
+                GrammarSymbol::Terminal(_) => true,
+            })
+        };
+
+        let mut trimmed = Self::new(self.start.clone());
+        let mut reached = BTreeSet::from([&self.start]);
+        let mut pending = vec![&self.start];
+
+        while let Some(variable) = pending.pop() {
+            for body in self.productions.get(variable).into_iter().flatten() {
+                if !useful(body) {
+                    continue;
+                }
+
+                for symbol in body {
+                    if let GrammarSymbol::Variable(next) = symbol {
+                        if reached.insert(next) {
+                            pending.push(next);
+                        }
+                    }
+                }
+
+                trimmed.insert(variable.clone(), body.iter().cloned());
+            }
+        }
+
+        trimmed
+    }
+
+    /// An equivalent pushdown automaton with a single state, accepting by
+    /// empty stack: it starts with the start variable on the stack, replaces
+    /// a variable on top by the body of one of its productions and pops a
+    /// terminal on top by reading it.
+    pub fn to_pda(&self) -> Pda<(), T, GrammarSymbol<N, T>> {
+        let mut pda = Pda::new((), GrammarSymbol::Variable(self.start.clone()));
+
+        for (variable, body) in self.productions() {
+            pda.insert(
+                (),
+                None,
+                GrammarSymbol::Variable(variable.clone()),
+                (),
+                body.iter().cloned(),
+            );
+        }
+
+        for terminal in self.terminals() {
+            pda.insert(
+                (),
+                Some(terminal.clone()),
+                GrammarSymbol::Terminal(terminal.clone()),
+                (),
+                [],
+            );
+        }
+
+        pda
+    }
+
+    // the productions of `variable` as items starting at `position`
+    fn items(&self, variable: &N, position: usize) -> impl Iterator<Item = Item<'_, N, T>> {
+        self.productions
+            .get_key_value(variable)
+            .into_iter()
+            .flat_map(move |(variable, bodies)| {
+                bodies
+                    .iter()
+                    .map(move |body| (variable, &body[..], 0, position))
+            })
+    }
+
+    // the least set of variables with a production whose every symbol is
+    // in it by `holds`
+    fn fixed_point(
+        &self,
+        holds: impl Fn(&BTreeSet<&N>, &GrammarSymbol<N, T>) -> bool,
+    ) -> BTreeSet<&N> {
+        let mut set = BTreeSet::new();
+
+        loop {
+            let added: Vec<_> = self
+                .productions()
+                .filter(|(variable, body)| {
+                    !set.contains(variable) && body.iter().all(|symbol| holds(&set, symbol))
+                })
+                .map(|(variable, _)| variable)
+                .collect();
+
+            if added.is_empty() {
+                return set;
+            }
+
+            set.extend(added);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use GrammarSymbol::{Terminal, Variable};
+
+    #[test]
+    fn generates_test() {
+        // left recursive sums of ones, with an optional sign
+        let mut cfg = Cfg::new('E');
+        cfg.insert('E', [Variable('E'), Terminal('+'), Variable('O')]);
+        cfg.insert('E', [Variable('S'), Variable('O')]);
+        cfg.insert('S', []);
+        cfg.insert('S', [Terminal('-')]);
+        cfg.insert('O', [Terminal('1')]);
+        cfg.insert('U', [Variable('U'), Terminal('1')]);
+
+        assert_eq!(cfg.nullable(), BTreeSet::from([&'S']));
+
+        for input in ["1", "-1", "1+1+1", "-1+1"] {
+            let input: Vec<char> = input.chars().collect();
+            assert!(cfg.generates(&input));
+        }
+
+        for input in ["", "+1", "1+", "1+-1", "11"] {
+            let input: Vec<char> = input.chars().collect();
+            assert!(!cfg.generates(&input));
+        }
+
+        let trimmed = cfg.trim();
+
+        assert_eq!(trimmed.productions().count(), 5);
+        assert!(trimmed.generates(&['1', '+', '1']));
+    }
+}
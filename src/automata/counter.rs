@@ -0,0 +1,666 @@
+use alloc::collections::{BTreeMap, BTreeSet, VecDeque};
+use alloc::vec::Vec;
+
+use crate::{Machine, Move, State, TransitionTable};
+
+/// An instruction of a [`CounterMachine`], jumping to others by their index
+/// in the program.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Instruction {
+    Inc {
+        counter: usize,
+        next: usize,
+    },
+    /// Decrements `counter` and jumps to `next`, or jumps to `zero` if it
+    /// already is.
+    Dec {
+        counter: usize,
+        next: usize,
+        zero: usize,
+    },
+    Halt,
+}
+
+/// A Minsky machine: a program of increments and decrements with zero tests
+/// over a number of counters, starting at instruction 0 and halting on
+/// [`Instruction::Halt`] or a jump past the end of the program.
+///
+/// Two counters are enough to simulate any Turing machine, through
+/// [`CounterMachine::from_table`] and [`CounterMachine::to_two_counters`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CounterMachine {
+    counters: usize,
+    program: Vec<Instruction>,
+}
+
+/// The result of [`CounterMachine::to_two_counters`], which keeps the
+/// counters of the original machine as the exponents of the primes in the
+/// first counter, `2^c0 * 3^c1 * 5^c2 * ...`. The second is a scratch counter
+/// left at zero between simulated instructions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TwoCounters {
+    pub machine: CounterMachine,
+    /// The instruction starting the simulation of each instruction of the
+    /// original machine, also jumped back to with the second counter above
+    /// zero.
+    pub labels: Vec<usize>,
+    primes: Vec<u64>,
+}
+
+/// The result of [`CounterMachine::from_table`], which keeps the tape left
+/// of the head in counter 0 and the rest in counter 1, as numbers whose
+/// digits are the symbols nearest to the head first. Counter 2 is scratch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableCounters<S, Sym> {
+    pub machine: CounterMachine,
+    /// The instruction at which each state of the table starts a step,
+    /// which is also jumped back to mid-step but then with counter 2 above
+    /// zero.
+    pub entries: BTreeMap<S, usize>,
+    /// The symbols by digit, the blank first as 0.
+    pub symbols: Vec<Sym>,
+    start: S,
+}
+
+impl CounterMachine {
+    pub fn new(counters: usize) -> Self {
+        Self {
+            counters,
+            program: Vec::new(),
+        }
+    }
+
+    /// Appends `instruction` to the program, returning its index.
+    pub fn push(&mut self, instruction: Instruction) -> usize {
+        self.program.push(instruction);
+        self.program.len() - 1
+    }
+
+    pub fn counters(&self) -> usize {
+        self.counters
+    }
+
+    pub fn program(&self) -> &[Instruction] {
+        &self.program
+    }
+
+    /// Executes the instruction at `pc`, or returns `false` if the machine
+    /// halts there.
+    ///
+    /// # Panics
+    ///
+    /// If an instruction names a counter past the end of `counters`, or a
+    /// counter overflows.
+    pub fn step(&self, pc: &mut usize, counters: &mut [u64]) -> bool {
+        match self.program.get(*pc) {
+            None | Some(Instruction::Halt) => false,
+            Some(&Instruction::Inc { counter, next }) => {
+                counters[counter] = counters[counter].checked_add(1).expect("counter overflow");
+                *pc = next;
+                true
+            }
+            Some(&Instruction::Dec {
+                counter,
+                next,
+                zero,
+            }) => {
+                match counters[counter].checked_sub(1) {
+                    Some(value) => {
+                        counters[counter] = value;
+                        *pc = next;
+                    }
+                    None => *pc = zero,
+                }
+
+                true
+            }
+        }
+    }
+
+    /// Steps until halting or for at most `max_steps` steps, returning the
+    /// number of steps taken.
+    pub fn run(&self, pc: &mut usize, counters: &mut [u64], max_steps: u64) -> u64 {
+        let mut steps = 0;
+
+        while steps < max_steps && self.step(pc, counters) {
+            steps += 1;
+        }
+
+        steps
+    }
+
+    /// An equivalent machine with two counters, by the exponent encoding:
+    /// incrementing a counter multiplies the first by its prime, and
+    /// decrementing it divides by the prime if that leaves no remainder.
+    ///
+    /// Each of those loops over the whole of the first counter, so the
+    /// machine is exponentially slower and its counter overflows quickly.
+    pub fn to_two_counters(&self) -> TwoCounters {
+        let primes = primes(self.counters);
+        let mut code = Assembler::default();
+        let labels: Vec<usize> = (0..self.program.len()).map(|_| code.label()).collect();
+        let halt = code.label();
+        let target = |index: usize| labels.get(index).copied().unwrap_or(halt);
+
+        for (index, instruction) in self.program.iter().enumerate() {
+            code.place(labels[index]);
+
+            match *instruction {
+                Instruction::Halt => code.halt(),
+                Instruction::Inc { counter, next } => {
+                    let multiplied = code.label();
+                    code.transfer(0, 1, primes[counter], multiplied);
+                    code.place(multiplied);
+                    code.transfer(1, 0, 1, target(next));
+                }
+                Instruction::Dec {
+                    counter,
+                    next,
+                    zero,
+                } => {
+                    let p = primes[counter] as usize;
+                    // counting down the first counter in rounds of `p` into
+                    // the second, stopping on the remainder
+                    let rounds: Vec<usize> = (0..p).map(|_| code.label()).collect();
+                    let remainders: Vec<usize> = (0..p).map(|_| code.label()).collect();
+                    let (round, exact) = (code.label(), code.label());
+
+                    for (k, &label) in rounds.iter().enumerate() {
+                        code.place(label);
+                        code.dec(
+                            0,
+                            rounds.get(k + 1).copied().unwrap_or(round),
+                            match k {
+                                0 => exact,
+                                k => remainders[k],
+                            },
+                        );
+                    }
+
+                    code.place(round);
+                    code.inc(1, rounds[0]);
+                    code.place(exact);
+                    code.transfer(1, 0, 1, target(next));
+
+                    // not divisible: adds back the remainder and the rounds
+                    for k in (1..p).rev() {
+                        code.place(remainders[k]);
+                        code.inc(0, remainders[k - 1]);
+                    }
+
+                    code.place(remainders[0]);
+                    code.transfer(1, 0, p as u64, target(zero));
+                }
+            }
+        }
+
+        code.place(halt);
+        code.halt();
+
+        TwoCounters {
+            labels: labels
+                .into_iter()
+                .map(|label| code.address(label))
+                .collect(),
+            machine: code.finish(2),
+            primes,
+        }
+    }
+
+    /// A machine simulating `table` from `start`, a step of the table taking
+    /// the symbol under the head off counter 1, looking up its rule and
+    /// pushing the symbols it moves over from one counter onto the other. It
+    /// halts when the table does, or with the tape as it was when the table
+    /// has no rule.
+    pub fn from_table<S, Sym>(table: &TransitionTable<S, Sym>, start: S) -> TableCounters<S, Sym>
+    where
+        S: Ord + Clone,
+        Sym: Ord + Clone + Default,
+    {
+        let blank = Sym::default();
+        let mut states = BTreeSet::from([&start]);
+        let mut others = BTreeSet::new();
+
+        for (state, read, rule) in table.iter() {
+            states.insert(state);
+            others.insert(read);
+            others.extend(&rule.write);
+
+            if let Some(State::State(next)) = &rule.new_state {
+                states.insert(next);
+            }
+        }
+
+        others.remove(&blank);
+
+        let symbols: Vec<Sym> = [&blank].into_iter().chain(others).cloned().collect();
+        let digit = |symbol: &Sym| symbols.iter().position(|other| other == symbol).unwrap();
+        let base = symbols.len() as u64;
+
+        let mut code = Assembler::default();
+        let labels: BTreeMap<&S, usize> =
+            states.iter().map(|&state| (state, code.label())).collect();
+        let halt = code.label();
+        states.remove(&start);
+
+        // the start state's step comes first, at instruction 0
+        for state in [&start].into_iter().chain(states) {
+            let label = labels[state];
+            code.place(label);
+
+            let reads: Vec<usize> = symbols.iter().map(|_| code.label()).collect();
+            code.pop(1, &reads);
+
+            for (symbol, &read) in symbols.iter().zip(&reads) {
+                code.place(read);
+
+                let Some(rule) = table.lookup(state, symbol) else {
+                    code.push(1, base, digit(symbol) as u64, halt);
+                    continue;
+                };
+
+                let next = match &rule.new_state {
+                    None => label,
+                    Some(State::State(next)) => labels[next],
+                    Some(State::Halt) => halt,
+                };
+                let written = rule.write.as_ref().unwrap_or(symbol);
+                let offset = rule.head_move.map_or(0, Move::offset);
+                let moves: Vec<usize> = (0..offset.unsigned_abs()).map(|_| code.label()).collect();
+
+                code.push(
+                    1,
+                    base,
+                    digit(written) as u64,
+                    moves.first().copied().unwrap_or(next),
+                );
+
+                for (index, &label) in moves.iter().enumerate() {
+                    let then = moves.get(index + 1).copied().unwrap_or(next);
+                    let (from, to) = if offset > 0 { (1, 0) } else { (0, 1) };
+
+                    code.place(label);
+                    code.shift(from, to, base, then);
+                }
+            }
+        }
+
+        code.place(halt);
+        code.halt();
+
+        let entries = labels
+            .into_iter()
+            .map(|(state, label)| (state.clone(), code.address(label)))
+            .collect();
+
+        TableCounters {
+            machine: code.finish(3),
+            entries,
+            symbols,
+            start,
+        }
+    }
+}
+
+impl TwoCounters {
+    /// The first counter for the counters of the original machine, or
+    /// `None` if it overflows.
+    pub fn encode(&self, counters: &[u64]) -> Option<u64> {
+        self.primes
+            .iter()
+            .zip(counters)
+            .try_fold(1u64, |value, (&prime, &counter)| {
+                value.checked_mul(prime.checked_pow(counter.try_into().ok()?)?)
+            })
+    }
+
+    /// The counters of the original machine for the first counter.
+    pub fn decode(&self, mut value: u64) -> Vec<u64> {
+        self.primes
+            .iter()
+            .map(|&prime| {
+                let mut counter = 0;
+
+                while value > 0 && value.is_multiple_of(prime) {
+                    value /= prime;
+                    counter += 1;
+                }
+
+                counter
+            })
+            .collect()
+    }
+}
+
+impl<S, Sym> TableCounters<S, Sym>
+where
+    S: Clone,
+    Sym: Clone + Default + PartialEq,
+{
+    /// The counters for the tape of `machine`, or `None` if it has a symbol
+    /// the table doesn't name or a counter overflows.
+    pub fn encode(&self, machine: &Machine<S, Sym>) -> Option<[u64; 3]> {
+        let tape = machine.tape();
+        let head = machine.head();
+
+        Some([
+            self.number(tape.range(..head).rev())?,
+            self.number(tape.range(head..))?,
+            0,
+        ])
+    }
+
+    /// The configuration of the table at an instruction starting a step or
+    /// halting, with the head on the first cell of counter 1.
+    pub fn decode(&self, pc: usize, counters: &[u64]) -> Option<Machine<S, Sym>> {
+        let state = match self.machine.program.get(pc) {
+            None | Some(Instruction::Halt) => State::Halt,
+            _ => State::State(
+                self.entries
+                    .iter()
+                    .find(|&(_, &entry)| entry == pc)?
+                    .0
+                    .clone(),
+            ),
+        };
+
+        let base = self.symbols.len() as u64;
+        let digits = |mut value: u64| {
+            let mut digits = Vec::new();
+
+            while value > 0 {
+                digits.push(self.symbols[(value % base) as usize].clone());
+                value /= base;
+            }
+
+            digits
+        };
+
+        let left = digits(counters[0]);
+        let mut tape: VecDeque<Sym> = left.iter().rev().cloned().collect();
+        tape.extend(digits(counters[1]));
+
+        if tape.len() == left.len() {
+            tape.push_back(Sym::default());
+        }
+
+        let mut machine = Machine::new(self.start.clone(), tape);
+        machine.set_head(left.len());
+        machine.set_state(state);
+        Some(machine)
+    }
+
+    fn number<'a>(&self, symbols: impl DoubleEndedIterator<Item = &'a Sym>) -> Option<u64>
+    where
+        Sym: 'a,
+    {
+        let base = self.symbols.len() as u64;
+
+        symbols.rev().try_fold(0u64, |value, symbol| {
+            let digit = self.symbols.iter().position(|other| other == symbol)?;
+            value.checked_mul(base)?.checked_add(digit as u64)
+        })
+    }
+}
+
+fn primes(count: usize) -> Vec<u64> {
+    let mut primes = Vec::new();
+
+    for candidate in 2.. {
+        if primes.len() == count {
+            break;
+        }
+
+        if primes.iter().all(|prime| candidate % prime != 0) {
+            primes.push(candidate);
+        }
+    }
+
+    primes
+}
+
+// builds programs out of instructions jumping to labels, placed at the
+// instruction emitted next
+#[derive(Default)]
+struct Assembler {
+    program: Vec<Instruction>,
+    addresses: Vec<Option<usize>>,
+}
+
+impl Assembler {
+    fn label(&mut self) -> usize {
+        self.addresses.push(None);
+        self.addresses.len() - 1
+    }
+
+    fn place(&mut self, label: usize) {
+        self.addresses[label] = Some(self.program.len());
+    }
+
+    fn address(&self, label: usize) -> usize {
+        self.addresses[label].expect("label never placed")
+    }
+
+    fn inc(&mut self, counter: usize, next: usize) {
+        self.program.push(Instruction::Inc { counter, next });
+    }
+
+    fn dec(&mut self, counter: usize, next: usize, zero: usize) {
+        self.program.push(Instruction::Dec {
+            counter,
+            next,
+            zero,
+        });
+    }
+
+    fn halt(&mut self) {
+        self.program.push(Instruction::Halt);
+    }
+
+    // adds `from` times `factor` to `to`, emptying `from`
+    fn transfer(&mut self, from: usize, to: usize, factor: u64, next: usize) {
+        let (top, add) = (self.label(), self.label());
+
+        self.place(top);
+        self.dec(from, add, next);
+        self.place(add);
+
+        for _ in 1..factor {
+            let following = self.label();
+            self.inc(to, following);
+            self.place(following);
+        }
+
+        self.inc(to, top);
+    }
+
+    // divides `counter` by the number of branches, jumping to the one for
+    // the remainder, with counter 2 as scratch
+    fn pop(&mut self, counter: usize, branches: &[usize]) {
+        let digits: Vec<usize> = branches.iter().map(|_| self.label()).collect();
+        let done: Vec<usize> = branches.iter().map(|_| self.label()).collect();
+        let round = self.label();
+
+        for (digit, &label) in digits.iter().enumerate() {
+            self.place(label);
+            self.dec(
+                counter,
+                digits.get(digit + 1).copied().unwrap_or(round),
+                done[digit],
+            );
+        }
+
+        self.place(round);
+        self.inc(2, digits[0]);
+
+        for (&label, &branch) in done.iter().zip(branches) {
+            self.place(label);
+            self.transfer(2, counter, 1, branch);
+        }
+    }
+
+    // appends `digit` to `counter` in base `base`
+    fn push(&mut self, counter: usize, base: u64, digit: u64, next: usize) {
+        let shifted = self.label();
+        self.transfer(counter, 2, base, shifted);
+        self.place(shifted);
+
+        for _ in 0..digit {
+            let following = self.label();
+            self.inc(counter, following);
+            self.place(following);
+        }
+
+        self.transfer(2, counter, 1, next);
+    }
+
+    // moves the last digit of `from` onto `to`
+    fn shift(&mut self, from: usize, to: usize, base: u64, next: usize) {
+        let branches: Vec<usize> = (0..base).map(|_| self.label()).collect();
+        self.pop(from, &branches);
+
+        for (digit, &branch) in branches.iter().enumerate() {
+            self.place(branch);
+            self.push(to, base, digit as u64, next);
+        }
+    }
+
+    fn finish(self, counters: usize) -> CounterMachine {
+        let address = |label: usize| self.addresses[label].expect("label never placed");
+        let program = self
+            .program
+            .iter()
+            .map(|&instruction| match instruction {
+                Instruction::Inc { counter, next } => Instruction::Inc {
+                    counter,
+                    next: address(next),
+                },
+                Instruction::Dec {
+                    counter,
+                    next,
+                    zero,
+                } => Instruction::Dec {
+                    counter,
+                    next: address(next),
+                    zero: address(zero),
+                },
+                Instruction::Halt => Instruction::Halt,
+            })
+            .collect();
+
+        CounterMachine { counters, program }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+    use crate::Rule;
+
+    // the 2-state busy beaver
+    fn busy_beaver() -> TransitionTable<char, bool> {
+        [
+            ('A', false, Rule::to('B').write(true).right()),
+            ('A', true, Rule::to('B').write(true).left()),
+            ('B', false, Rule::to('A').write(true).left()),
+            ('B', true, Rule::halt().write(true).right()),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    #[test]
+    fn two_counters_test() {
+        // adds counter 1 to counter 0, then counter 2 twice
+        let mut adder = CounterMachine::new(3);
+        adder.push(Instruction::Dec {
+            counter: 1,
+            next: 1,
+            zero: 2,
+        });
+        adder.push(Instruction::Inc {
+            counter: 0,
+            next: 0,
+        });
+        adder.push(Instruction::Dec {
+            counter: 2,
+            next: 3,
+            zero: 5,
+        });
+        adder.push(Instruction::Inc {
+            counter: 0,
+            next: 4,
+        });
+        adder.push(Instruction::Inc {
+            counter: 0,
+            next: 2,
+        });
+
+        let two = adder.to_two_counters();
+        let (mut pc, mut counters) = (0, vec![1, 2, 1]);
+        let (mut simulated_pc, mut simulated) = (0, vec![two.encode(&counters).unwrap(), 0]);
+
+        assert_eq!(simulated[0], 2 * 9 * 5);
+
+        // instruction by instruction
+        while adder.step(&mut pc, &mut counters) {
+            two.machine.step(&mut simulated_pc, &mut simulated);
+
+            while two.labels.get(pc) != Some(&simulated_pc)
+                && two.machine.step(&mut simulated_pc, &mut simulated)
+            {}
+
+            assert_eq!(two.decode(simulated[0]), counters);
+            assert_eq!(simulated[1], 0);
+        }
+
+        assert_eq!(counters, [5, 0, 0]);
+        assert_eq!(two.machine.run(&mut simulated_pc, &mut simulated, 1_000), 0);
+    }
+
+    #[test]
+    fn from_table_test() {
+        let table = busy_beaver();
+        let counters = CounterMachine::from_table(&table, 'A');
+        let mut machine: Machine<char, bool> = Machine::new('A', [false].into());
+        let mut pc = 0;
+        let mut values = counters.encode(&machine).unwrap();
+
+        // step by step
+        while !machine.halted() {
+            machine.execute_table(&table);
+            counters.machine.step(&mut pc, &mut values);
+
+            while !(values[2] == 0 && counters.entries.values().any(|&entry| entry == pc))
+                && counters.machine.step(&mut pc, &mut values)
+            {}
+
+            assert_eq!(counters.decode(pc, &values), Some(machine.clone()));
+        }
+
+        assert_eq!(machine.tape().iter().filter(|&&cell| cell).count(), 4);
+
+        // and through two counters, writing two ones
+        let table: TransitionTable<char, bool> = [
+            ('A', false, Rule::to('B').write(true).right()),
+            ('B', false, Rule::halt().write(true)),
+        ]
+        .into_iter()
+        .collect();
+        let counters = CounterMachine::from_table(&table, 'A');
+        let two = counters.machine.to_two_counters();
+        let start = counters.encode(&Machine::new('A', [false].into())).unwrap();
+        let mut values = [two.encode(&start).unwrap(), 0];
+        let mut pc = 0;
+
+        two.machine.run(&mut pc, &mut values, 1_000_000);
+
+        let end = counters.decode(counters.machine.program().len() - 1, &two.decode(values[0]));
+        let (tape, state, _) = end.unwrap().finish_trimmed();
+
+        assert_eq!(tape, [true, true]);
+        assert_eq!(state, State::Halt);
+    }
+}
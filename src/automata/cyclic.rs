@@ -0,0 +1,198 @@
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+
+use super::TagSystem;
+
+/// A cyclic tag system: its words are bits, and its productions are applied
+/// in turn, one per step. A step deletes the first bit of the word, appending
+/// the current production first if that bit was set. It halts on the empty
+/// word.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CyclicTag {
+    productions: Vec<Vec<bool>>,
+}
+
+/// A word of a [`CyclicTag`] with the index of the production applied next.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CyclicWord {
+    pub word: VecDeque<bool>,
+    pub next: usize,
+}
+
+impl CyclicTag {
+    /// # Panics
+    ///
+    /// If there are no productions.
+    pub fn new(productions: Vec<Vec<bool>>) -> Self {
+        assert!(
+            !productions.is_empty(),
+            "a cyclic tag system needs productions"
+        );
+
+        Self { productions }
+    }
+
+    pub fn productions(&self) -> &[Vec<bool>] {
+        &self.productions
+    }
+
+    /// Cook's translation of a tag system over the symbols of `alphabet`,
+    /// each of which is encoded by [`CyclicTag::encode`] as a run of bits with
+    /// only the bit at its index set. One step of `tag` is `k * m` steps of
+    /// the cyclic system, with `k` symbols and `m` the deletion number.
+    ///
+    /// The cyclic system halts only on the empty word, so symbols without a
+    /// production get the empty one and the translation follows `tag` until
+    /// it halts.
+    ///
+    /// # Panics
+    ///
+    /// If `alphabet` is empty.
+    pub fn from_tag<A: Ord + Clone>(tag: &TagSystem<A>, alphabet: &[A]) -> Self {
+        let empty = alphabet.len() * (tag.deletion() - 1);
+        let productions = alphabet
+            .iter()
+            .map(|symbol| {
+                let production = tag.production(symbol).unwrap_or_default();
+                Self::encode(production, alphabet)
+            })
+            .chain((0..empty).map(|_| Vec::new()))
+            .collect();
+
+        Self::new(productions)
+    }
+
+    /// The bits of `word` in the encoding of [`CyclicTag::from_tag`], or an
+    /// empty run for symbols missing from `alphabet`.
+    pub fn encode<A: PartialEq>(word: &[A], alphabet: &[A]) -> Vec<bool> {
+        word.iter()
+            .flat_map(|symbol| alphabet.iter().map(move |other| other == symbol))
+            .collect()
+    }
+
+    /// The word `bits` encodes in the encoding of [`CyclicTag::from_tag`], or
+    /// `None` if they aren't a run of `alphabet.len()` bits with one set for
+    /// each symbol.
+    pub fn decode<'a, A>(bits: impl IntoIterator<Item = &'a bool>, alphabet: &[A]) -> Option<Vec<A>>
+    where
+        A: Clone,
+    {
+        let bits: Vec<bool> = bits.into_iter().copied().collect();
+
+        if alphabet.is_empty() || !bits.len().is_multiple_of(alphabet.len()) {
+            return None;
+        }
+
+        bits.chunks(alphabet.len())
+            .map(|run| match run.iter().filter(|&&bit| bit).count() {
+                1 => Some(alphabet[run.iter().position(|&bit| bit)?].clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Takes one step on `word`, or returns `false` if it is empty.
+    pub fn step(&self, word: &mut CyclicWord) -> bool {
+        let Some(bit) = word.word.pop_front() else {
+            return false;
+        };
+
+        if bit {
+            word.word.extend(&self.productions[word.next]);
+        }
+
+        word.next = (word.next + 1) % self.productions.len();
+        true
+    }
+
+    /// Steps on `word` until halting or for at most `max_steps` steps,
+    /// returning the number of steps taken.
+    pub fn run(&self, word: &mut CyclicWord, max_steps: u64) -> u64 {
+        let mut steps = 0;
+
+        while steps < max_steps && self.step(word) {
+            steps += 1;
+        }
+
+        steps
+    }
+}
+
+impl CyclicWord {
+    /// `word` with the first production applied next.
+    pub fn new(word: impl IntoIterator<Item = bool>) -> Self {
+        Self {
+            word: word.into_iter().collect(),
+            next: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_tag_test() {
+        // De Mol's 2-tag system for the Collatz function
+        let mut tag = TagSystem::new(2);
+        tag.insert('a', Vec::from(['b', 'c']));
+        tag.insert('b', Vec::from(['a']));
+        tag.insert('c', Vec::from(['a', 'a', 'a']));
+
+        let alphabet = ['a', 'b', 'c'];
+        let cyclic = CyclicTag::from_tag(&tag, &alphabet);
+        let mut word: VecDeque<char> = ['a'; 7].into();
+        let mut bits = CyclicWord::new(CyclicTag::encode(&['a'; 7], &alphabet));
+
+        assert_eq!(cyclic.productions().len(), 6);
+
+        while tag.step(&mut word) {
+            assert_eq!(cyclic.run(&mut bits, 6), 6);
+            assert_eq!(bits.next, 0);
+            assert_eq!(
+                Vec::from(bits.word.clone()),
+                CyclicTag::encode(&Vec::from(word.clone()), &alphabet)
+            );
+        }
+
+        assert_eq!(word, ['a']);
+    }
+
+    #[test]
+    fn from_table_test() {
+        use crate::{Machine, Rule, State, TransitionTable};
+
+        // the 2-state busy beaver, through a 2-tag system
+        let table: TransitionTable<char, bool> = [
+            ('A', false, Rule::to('B').write(true).right()),
+            ('A', true, Rule::to('B').write(true).left()),
+            ('B', false, Rule::to('A').write(true).left()),
+            ('B', true, Rule::halt().write(true).right()),
+        ]
+        .into_iter()
+        .collect();
+
+        let tag = TagSystem::from_table(&table, 'A');
+        let cyclic = CyclicTag::from_tag(&tag.system, &tag.alphabet);
+        let start = tag.encode(&Machine::new('A', [false].into())).unwrap();
+        let mut bits = CyclicWord::new(CyclicTag::encode(&start, &tag.alphabet));
+        let pass = cyclic.productions().len() as u64;
+
+        // a step of the tag system at a time, until it halts
+        let word = loop {
+            let word = CyclicTag::decode(&bits.word, &tag.alphabet).unwrap();
+
+            if tag.system.production(&word[0]).is_none() {
+                break word;
+            }
+
+            assert_eq!(cyclic.run(&mut bits, pass), pass);
+        };
+
+        let (tape, state, _) = tag.decode(&word).unwrap().finish_trimmed();
+
+        assert_eq!(tape, [true; 4]);
+        assert_eq!(state, State::Halt);
+    }
+}
@@ -0,0 +1,432 @@
+use alloc::collections::{BTreeMap, BTreeSet, VecDeque};
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::{Acceptance, Rule, TransitionTable};
+
+/// A deterministic finite automaton with states `Q` over symbols `A`.
+///
+/// Transitions may be missing; reading a symbol without one rejects.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dfa<Q, A> {
+    start: Q,
+    accepting: BTreeSet<Q>,
+    transitions: BTreeMap<(Q, A), Q>,
+}
+
+/// The result of [`Dfa::minimize`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Minimized<Q, A> {
+    pub dfa: Dfa<usize, A>,
+    /// The state of `dfa` each state of the original automaton was merged
+    /// into.
+    pub classes: BTreeMap<Q, usize>,
+}
+
+/// The states of the Turing machine built by [`Dfa::to_machine`].
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DfaState<Q> {
+    State(Q),
+    Accept,
+    Reject,
+}
+
+impl<Q, A> Dfa<Q, A>
+where
+    Q: Ord + Clone,
+    A: Ord + Clone,
+{
+    pub fn new(start: Q) -> Self {
+        Self {
+            start,
+            accepting: BTreeSet::new(),
+            transitions: BTreeMap::new(),
+        }
+    }
+
+    /// Builds the part of the automaton reachable from `start` by feeding
+    /// every symbol of `alphabet` to `next`, with `accepting` telling the
+    /// accepting states.
+    pub fn from_fn(
+        start: Q,
+        alphabet: &[A],
+        next: impl Fn(&Q, &A) -> Option<Q>,
+        accepting: impl Fn(&Q) -> bool,
+    ) -> Self {
+        let mut dfa = Self::new(start.clone());
+        let mut seen = BTreeSet::from([start.clone()]);
+        let mut queue = VecDeque::from([start]);
+
+        while let Some(state) = queue.pop_front() {
+            if accepting(&state) {
+                dfa.accept(state.clone());
+            }
+
+            for symbol in alphabet {
+                let Some(to) = next(&state, symbol) else {
+                    continue;
+                };
+
+                if seen.insert(to.clone()) {
+                    queue.push_back(to.clone());
+                }
+
+                dfa.insert(state.clone(), symbol.clone(), to);
+            }
+        }
+
+        dfa
+    }
+
+    /// Sets the transition from `from` on `symbol`, returning the state it
+    /// used to lead to, if any.
+    pub fn insert(&mut self, from: Q, symbol: A, to: Q) -> Option<Q> {
+        self.transitions.insert((from, symbol), to)
+    }
+
+    pub fn accept(&mut self, state: Q) {
+        self.accepting.insert(state);
+    }
+
+    pub fn start(&self) -> &Q {
+        &self.start
+    }
+
+    pub fn is_accepting(&self, state: &Q) -> bool {
+        self.accepting.contains(state)
+    }
+
+    pub fn next(&self, state: &Q, symbol: &A) -> Option<&Q> {
+        self.transitions.get(&(state.clone(), symbol.clone()))
+    }
+
+    /// All `(from, symbol, to)` transitions, ordered by state and symbol.
+    pub fn transitions(&self) -> impl Iterator<Item = (&Q, &A, &Q)> {
+        self.transitions
+            .iter()
+            .map(|((from, symbol), to)| (from, symbol, to))
+    }
+
+    /// The start state and every state named in a transition.
+    pub fn states(&self) -> BTreeSet<&Q> {
+        let mut states = BTreeSet::from([&self.start]);
+
+        for (from, _, to) in self.transitions() {
+            states.insert(from);
+            states.insert(to);
+        }
+
+        states.extend(&self.accepting);
+        states
+    }
+
+    /// The symbols named in transitions.
+    pub fn alphabet(&self) -> BTreeSet<&A> {
+        self.transitions().map(|(_, symbol, _)| symbol).collect()
+    }
+
+    pub fn accepts<'a>(&self, input: impl IntoIterator<Item = &'a A>) -> bool
+    where
+        A: 'a,
+    {
+        let mut state = &self.start;
+
+        for symbol in input {
+            match self.next(state, symbol) {
+                Some(next) => state = next,
+                None => return false,
+            }
+        }
+
+        self.is_accepting(state)
+    }
+
+    /// The smallest equivalent automaton, by Hopcroft's algorithm, with its
+    /// states numbered from 0 for the start state in breadth-first order.
+    ///
+    /// States that can't be reached, or that can't reach an accepting state
+    /// and so only reject, are dropped along with the transitions to them,
+    /// as missing transitions reject anyway.
+    pub fn minimize(&self) -> Minimized<Q, A> {
+        let alphabet: Vec<&A> = self.alphabet().into_iter().collect();
+
+        // the reachable states, numbered by discovery, and a sink standing
+        // in for missing transitions
+        let mut states = Vec::from([&self.start]);
+        let mut numbers = BTreeMap::from([(&self.start, 0)]);
+        let mut next: Vec<Vec<usize>> = Vec::new();
+
+        while next.len() < states.len() {
+            let state = states[next.len()];
+            let row = alphabet
+                .iter()
+                .map(|&symbol| match self.next(state, symbol) {
+                    Some(to) => *numbers.entry(to).or_insert_with(|| {
+                        states.push(to);
+                        states.len() - 1
+                    }),
+                    None => usize::MAX,
+                })
+                .collect();
+
+            next.push(row);
+        }
+
+        let sink = states.len();
+        let count = sink + 1;
+
+        for row in &mut next {
+            for to in row.iter_mut().filter(|to| **to == usize::MAX) {
+                *to = sink;
+            }
+        }
+
+        next.push(Vec::from_iter(core::iter::repeat_n(sink, alphabet.len())));
+
+        let mut previous = alphabet
+            .iter()
+            .map(|_| Vec::from_iter(core::iter::repeat_n(Vec::new(), count)))
+            .collect::<Vec<Vec<Vec<usize>>>>();
+
+        for (from, row) in next.iter().enumerate() {
+            for (symbol, &to) in row.iter().enumerate() {
+                previous[symbol][to].push(from);
+            }
+        }
+
+        // Hopcroft's refinement, starting from accepting and rejecting
+        let accepting = |state: usize| state < sink && self.is_accepting(states[state]);
+        let (accept, reject): (BTreeSet<usize>, BTreeSet<usize>) =
+            (0..count).partition(|&state| accepting(state));
+        let mut classes: Vec<BTreeSet<usize>> = [accept, reject]
+            .into_iter()
+            .filter(|class| !class.is_empty())
+            .collect();
+        let mut class_of = vec![0; count];
+
+        for (class, members) in classes.iter().enumerate() {
+            for &state in members {
+                class_of[state] = class;
+            }
+        }
+
+        let mut pending: Vec<usize> = (0..classes.len()).collect();
+
+        while let Some(splitter) = pending.pop() {
+            for sources in &previous {
+                let into: BTreeSet<usize> = classes[splitter]
+                    .iter()
+                    .flat_map(|&to| sources[to].iter().copied())
+                    .collect();
+                let touched: BTreeSet<usize> = into.iter().map(|&state| class_of[state]).collect();
+
+                for class in touched {
+                    let (inside, outside): (BTreeSet<usize>, BTreeSet<usize>) = classes[class]
+                        .iter()
+                        .partition(|state| into.contains(state));
+
+                    if outside.is_empty() {
+                        continue;
+                    }
+
+                    let (kept, split) = match inside.len() <= outside.len() {
+                        true => (outside, inside),
+                        false => (inside, outside),
+                    };
+                    let new = classes.len();
+
+                    for &state in &split {
+                        class_of[state] = new;
+                    }
+
+                    // a pending class stays pending with what's kept, and
+                    // otherwise the smaller half is enough to split by
+                    classes[class] = kept;
+                    classes.push(split);
+                    pending.push(new);
+                }
+            }
+        }
+
+        // number the live classes breadth first; the sink's class only
+        // rejects, so it goes, unless the start itself is in it
+        let dead = class_of[sink];
+        let mut numbering = BTreeMap::from([(class_of[0], 0)]);
+        let mut order = VecDeque::from([class_of[0]]);
+        let mut dfa = Dfa::new(0);
+
+        while let Some(class) = order.pop_front() {
+            let number = numbering[&class];
+            let member = *classes[class].first().unwrap();
+
+            if accepting(member) {
+                dfa.accept(number);
+            }
+
+            for (symbol, &to) in next[member].iter().enumerate() {
+                let to = class_of[to];
+
+                if to == dead {
+                    continue;
+                }
+
+                let count = numbering.len();
+                let to = *numbering.entry(to).or_insert_with(|| {
+                    order.push_back(to);
+                    count
+                });
+
+                dfa.insert(number, alphabet[symbol].clone(), to);
+            }
+        }
+
+        let classes = states
+            .iter()
+            .enumerate()
+            .filter_map(|(state, &original)| {
+                let number = numbering.get(&class_of[state])?;
+                Some((original.clone(), *number))
+            })
+            .collect();
+
+        Minimized { dfa, classes }
+    }
+
+    /// An equivalent Turing machine that only reads its input, moving right
+    /// once per symbol, and decides on the first blank. It starts in
+    /// `DfaState::State(start)` and halts from [`DfaState::Accept`] or
+    /// [`DfaState::Reject`]; run it with [`Dfa::acceptance`].
+    ///
+    /// The blank, `A::default()`, must not be in the alphabet. Reading any
+    /// other symbol outside of it halts and rejects.
+    pub fn to_machine(&self) -> TransitionTable<DfaState<Q>, A>
+    where
+        A: Default,
+    {
+        let blank = A::default();
+        let alphabet = self.alphabet();
+        let mut table = TransitionTable::new();
+
+        for state in self.states() {
+            let verdict = match self.is_accepting(state) {
+                true => DfaState::Accept,
+                false => DfaState::Reject,
+            };
+
+            table.insert(
+                DfaState::State(state.clone()),
+                blank.clone(),
+                Rule::to(verdict),
+            );
+
+            for &symbol in &alphabet {
+                let next = match self.next(state, symbol) {
+                    Some(next) => DfaState::State(next.clone()),
+                    None => DfaState::Reject,
+                };
+
+                table.insert(
+                    DfaState::State(state.clone()),
+                    symbol.clone(),
+                    Rule::to(next).right(),
+                );
+            }
+        }
+
+        table
+    }
+
+    /// The acceptance criterion of the machines built by [`Dfa::to_machine`].
+    pub fn acceptance<'a>() -> Acceptance<'a, DfaState<Q>, A> {
+        Acceptance::FinalState(Vec::from([DfaState::Accept]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{format, vec};
+
+    use super::*;
+    use crate::{Char, Machine, Verdict};
+
+    // binary numbers divisible by three, most significant bit first
+    fn divisible_by_three() -> Dfa<u8, Char> {
+        Dfa::from_fn(
+            0,
+            &[Char('0'), Char('1')],
+            |rest, bit| Some((rest * 2 + (bit.0 == '1') as u8) % 3),
+            |rest| *rest == 0,
+        )
+    }
+
+    #[test]
+    fn dfa_test() {
+        let dfa = divisible_by_three();
+
+        assert_eq!(dfa.states().len(), 3);
+        assert!(dfa.accepts(&[Char('1'), Char('1')]));
+        assert!(!dfa.accepts(&[Char('1'), Char('0')]));
+        assert!(!dfa.accepts(&[Char('2')]));
+    }
+
+    #[test]
+    fn minimize_test() {
+        // remainders modulo six, accepting multiples of three, plus a state
+        // stuck rejecting after a 2
+        let dfa = Dfa::from_fn(
+            0,
+            &[Char('0'), Char('1'), Char('2')],
+            |rest: &u8, bit| match bit.0 {
+                '2' => Some(6),
+                _ if *rest == 6 => Some(6),
+                _ => Some((rest * 2 + (bit.0 == '1') as u8) % 6),
+            },
+            |rest| *rest % 3 == 0 && *rest < 6,
+        );
+        let minimized = dfa.minimize();
+
+        assert_eq!(dfa.states().len(), 7);
+        assert_eq!(minimized.dfa.states().len(), 3);
+        assert_eq!(minimized.classes[&0], minimized.classes[&3]);
+        assert!(!minimized.classes.contains_key(&6));
+
+        for n in 0..64u32 {
+            let input: Vec<Char> = format!("{n:b}2").chars().map(Char).collect();
+
+            assert!(!minimized.dfa.accepts(&input));
+            assert_eq!(
+                minimized.dfa.accepts(&input[..input.len() - 1]),
+                dfa.accepts(&input[..input.len() - 1])
+            );
+        }
+    }
+
+    #[test]
+    fn to_machine_test() {
+        let dfa = divisible_by_three();
+        let table = dfa.to_machine();
+
+        for n in 0..64u32 {
+            let input: Vec<Char> = format!("{n:b}").chars().map(Char).collect();
+            let mut machine: Machine<_, Char> =
+                Machine::new(DfaState::State(0), input.iter().copied().collect());
+            let verdict = machine.run_table_accepting(&table, 100, &Dfa::acceptance());
+
+            let expected = match dfa.accepts(&input) {
+                true => Verdict::Accept,
+                false => Verdict::Reject,
+            };
+
+            assert_eq!(verdict, Ok(expected));
+            assert_eq!(machine.head(), input.len());
+        }
+
+        let mut machine: Machine<_, Char> =
+            Machine::new(DfaState::State(0), vec![Char('2')].into());
+
+        assert_eq!(
+            machine.run_table_accepting(&table, 100, &Dfa::acceptance()),
+            Ok(Verdict::Reject)
+        );
+    }
+}
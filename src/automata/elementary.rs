@@ -0,0 +1,190 @@
+use alloc::vec::Vec;
+
+/// Rule 110's ether, the background it settles into: these 14 cells
+/// repeated, and again the same every 7 generations.
+const ETHER: &[u8; 14] = b"00010011011111";
+
+/// Cook's `C2` glider, which stands still in the ether with period 7. It
+/// moves the ether after it 3 cells along.
+const GLIDER: &[u8; 57] = b"100010011011111000100110111000110001001101111100010011011";
+
+/// The cells of ether before the first glider of a tape.
+const LEAD: usize = 13;
+
+/// The distance between the two gliders of a set bit.
+const SET: usize = 59;
+
+/// The distance between the two gliders of a clear bit.
+const CLEAR: usize = 73;
+
+/// The distance from the last glider of a bit to the next glider.
+const APART: usize = 101;
+
+/// An elementary cellular automaton: a row of bits where each cell becomes
+/// the bit of the rule number indexed by itself and its two neighbours, read
+/// as a 3-bit number with the left neighbour most significant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Elementary(pub u8);
+
+impl Elementary {
+    /// Cook's universal rule.
+    pub const RULE_110: Self = Self(110);
+
+    pub fn cell(self, left: bool, cell: bool, right: bool) -> bool {
+        let index = u8::from(left) << 2 | u8::from(cell) << 1 | u8::from(right);
+        self.0 >> index & 1 == 1
+    }
+
+    /// The next generation of `row`, with its ends wrapping around.
+    pub fn step(self, row: &[bool]) -> Vec<bool> {
+        let len = row.len();
+
+        (0..len)
+            .map(|i| self.cell(row[(i + len - 1) % len], row[i], row[(i + 1) % len]))
+            .collect()
+    }
+
+    /// The first `generations` generations from `row`, starting with it.
+    pub fn evolve(self, row: Vec<bool>, generations: usize) -> Vec<Vec<bool>> {
+        let mut rows = Vec::from([row]);
+
+        while rows.len() < generations {
+            let next = self.step(&rows[rows.len() - 1]);
+            rows.push(next);
+        }
+
+        rows
+    }
+}
+
+/// `word` written as still gliders on Rule 110's ether, the tape data of
+/// Cook's construction: two `C2` gliders for each bit, closer together for a
+/// set one, followed by lone ones until there is a multiple of 14 so the row
+/// wraps around onto the same ether. Every 7 generations of
+/// [`Elementary::RULE_110`] give back the same row.
+pub fn write_tape(word: &[bool]) -> Vec<bool> {
+    let mut gliders = Vec::new();
+    let mut at = LEAD;
+
+    for &bit in word {
+        gliders.extend([at, at + if bit { SET } else { CLEAR }]);
+        at = gliders[gliders.len() - 1] + APART;
+    }
+
+    for _ in 0..14 - gliders.len() % 14 {
+        gliders.push(at);
+        at += APART;
+    }
+
+    let mut row = Vec::with_capacity(at - LEAD);
+    let mut shift = 0;
+
+    for glider in gliders {
+        row.extend((row.len()..glider).map(|i| ETHER[(i + 14 - shift) % 14] == b'1'));
+        row.extend(GLIDER.iter().map(|&c| c == b'1'));
+        shift = (shift + 3) % 14;
+    }
+
+    row.extend((row.len()..at - LEAD).map(|i| ETHER[(i + 14 - shift) % 14] == b'1'));
+    row
+}
+
+/// The word [`write_tape`] wrote on `row`, or `None` if it isn't one of its
+/// rows.
+pub fn read_tape(row: &[bool]) -> Option<Vec<bool>> {
+    let glider: Vec<bool> = GLIDER.iter().map(|&c| c == b'1').collect();
+    let gliders: Vec<usize> = (0..row.len().saturating_sub(GLIDER.len() - 1))
+        .filter(|&i| row[i..i + GLIDER.len()] == glider[..])
+        .collect();
+    let mut word = Vec::new();
+
+    for pair in gliders.chunks(2) {
+        match pair {
+            [first, second] if second - first == SET => word.push(true),
+            [first, second] if second - first == CLEAR => word.push(false),
+            _ => break,
+        }
+    }
+
+    (write_tape(&word) == row).then_some(word)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rule_110_test() {
+        let rows = Elementary::RULE_110.evolve("00000001".chars().map(|c| c == '1').collect(), 4);
+        let rows: Vec<alloc::string::String> = rows
+            .iter()
+            .map(|row| row.iter().map(|&c| if c { '1' } else { '0' }).collect())
+            .collect();
+
+        assert_eq!(rows, ["00000001", "00000011", "00000111", "00001101"]);
+    }
+
+    #[test]
+    fn tape_test() {
+        for word in [
+            &[][..],
+            &[true],
+            &[false, true, true, false, true, false, false, true],
+        ] {
+            let row = write_tape(word);
+            let rows = Elementary::RULE_110.evolve(row.clone(), 15);
+
+            assert_eq!(read_tape(&row).as_deref(), Some(word));
+            assert_eq!(rows[7], row);
+            assert_eq!(rows[14], row);
+        }
+
+        let mut row = write_tape(&[true, false]);
+        row[0] = !row[0];
+
+        assert_eq!(read_tape(&row), None);
+        assert_eq!(read_tape(&[false; 14]), None);
+    }
+
+    #[test]
+    fn from_table_test() {
+        use crate::automata::{CyclicTag, CyclicWord, TagSystem};
+        use crate::{Machine, Rule, State, TransitionTable};
+
+        // the 2-state busy beaver, through a 2-tag system and a cyclic one
+        // whose words are kept on Rule 110's tape between passes
+        let table: TransitionTable<char, bool> = [
+            ('A', false, Rule::to('B').write(true).right()),
+            ('A', true, Rule::to('B').write(true).left()),
+            ('B', false, Rule::to('A').write(true).left()),
+            ('B', true, Rule::halt().write(true).right()),
+        ]
+        .into_iter()
+        .collect();
+
+        let tag = TagSystem::from_table(&table, 'A');
+        let cyclic = CyclicTag::from_tag(&tag.system, &tag.alphabet);
+        let start = tag.encode(&Machine::new('A', [false].into())).unwrap();
+        let mut row = write_tape(&CyclicTag::encode(&start, &tag.alphabet));
+        let pass = cyclic.productions().len() as u64;
+
+        let word = loop {
+            row = Elementary::RULE_110.evolve(row, 8).pop().unwrap();
+
+            let mut bits = CyclicWord::new(read_tape(&row).unwrap());
+            let word = CyclicTag::decode(&bits.word, &tag.alphabet).unwrap();
+
+            if tag.system.production(&word[0]).is_none() {
+                break word;
+            }
+
+            assert_eq!(cyclic.run(&mut bits, pass), pass);
+            row = write_tape(&Vec::from(bits.word));
+        };
+
+        let (tape, state, _) = tag.decode(&word).unwrap().finish_trimmed();
+
+        assert_eq!(tape, [true; 4]);
+        assert_eq!(state, State::Halt);
+    }
+}
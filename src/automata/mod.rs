@@ -0,0 +1,27 @@
+//! Other models of computation and their translations to Turing machines.
+
+pub mod cfg;
+pub mod counter;
+pub mod cyclic;
+pub mod dfa;
+pub mod elementary;
+pub mod nfa;
+pub mod pda;
+pub mod ram;
+pub mod regex;
+pub mod subleq;
+pub mod tag;
+
+pub use cfg::{Cfg, GrammarSymbol};
+pub use counter::{CounterMachine, Instruction, TableCounters, TwoCounters};
+pub use cyclic::{CyclicTag, CyclicWord};
+pub use dfa::{Dfa, DfaState, Minimized};
+pub use elementary::Elementary;
+pub use nfa::{LazyDfa, Nfa};
+pub use pda::{Pda, PdaVariable};
+pub use ram::{Ram, RamInstruction, RamMachine, RamPeek, RamStep};
+pub use regex::RegexError;
+pub use subleq::{Subleq, SubleqError, SubleqStep};
+pub use tag::{
+    Divergence, TableTag, TagControl, TagPart, TagStage, TagState, TagSymbol, TagSystem,
+};
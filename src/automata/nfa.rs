@@ -0,0 +1,236 @@
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::vec::Vec;
+
+use super::Dfa;
+
+/// A nondeterministic finite automaton with states `Q` over symbols `A`,
+/// with ε-transitions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Nfa<Q, A> {
+    start: Q,
+    accepting: BTreeSet<Q>,
+    // `None` for ε
+    transitions: BTreeMap<(Q, Option<A>), BTreeSet<Q>>,
+}
+
+impl<Q, A> Nfa<Q, A>
+where
+    Q: Ord + Clone,
+    A: Ord + Clone,
+{
+    pub fn new(start: Q) -> Self {
+        Self {
+            start,
+            accepting: BTreeSet::new(),
+            transitions: BTreeMap::new(),
+        }
+    }
+
+    /// Adds a transition from `from` to `to` on `symbol`, or an
+    /// ε-transition for `None`.
+    pub fn insert(&mut self, from: Q, symbol: Option<A>, to: Q) {
+        self.transitions
+            .entry((from, symbol))
+            .or_default()
+            .insert(to);
+    }
+
+    pub fn accept(&mut self, state: Q) {
+        self.accepting.insert(state);
+    }
+
+    pub fn start(&self) -> &Q {
+        &self.start
+    }
+
+    pub fn is_accepting(&self, state: &Q) -> bool {
+        self.accepting.contains(state)
+    }
+
+    /// All `(from, symbol, to)` transitions, `None` standing for ε.
+    pub fn transitions(&self) -> impl Iterator<Item = (&Q, Option<&A>, &Q)> {
+        self.transitions
+            .iter()
+            .flat_map(|((from, symbol), to)| to.iter().map(move |to| (from, symbol.as_ref(), to)))
+    }
+
+    /// The start state and every state named in a transition.
+    pub fn states(&self) -> BTreeSet<&Q> {
+        let mut states = BTreeSet::from([&self.start]);
+
+        for (from, _, to) in self.transitions() {
+            states.insert(from);
+            states.insert(to);
+        }
+
+        states.extend(&self.accepting);
+        states
+    }
+
+    /// The symbols named in transitions, not counting ε.
+    pub fn alphabet(&self) -> BTreeSet<&A> {
+        self.transitions()
+            .filter_map(|(_, symbol, _)| symbol)
+            .collect()
+    }
+
+    /// `states` and every state reachable from them by ε-transitions.
+    pub fn closure(&self, states: &BTreeSet<Q>) -> BTreeSet<Q> {
+        let mut closure = states.clone();
+        let mut pending: Vec<&Q> = states.iter().collect();
+
+        while let Some(state) = pending.pop() {
+            let Some(next) = self.transitions.get(&(state.clone(), None)) else {
+                continue;
+            };
+
+            for to in next {
+                if closure.insert(to.clone()) {
+                    pending.push(to);
+                }
+            }
+        }
+
+        closure
+    }
+
+    /// The closed set of states reached from the closed set `states` by
+    /// reading `symbol`.
+    pub fn step(&self, states: &BTreeSet<Q>, symbol: &A) -> BTreeSet<Q> {
+        let next = states
+            .iter()
+            .filter_map(|state| self.transitions.get(&(state.clone(), Some(symbol.clone()))))
+            .flatten()
+            .cloned()
+            .collect();
+
+        self.closure(&next)
+    }
+
+    pub fn accepts<'a>(&self, input: impl IntoIterator<Item = &'a A>) -> bool
+    where
+        A: 'a,
+    {
+        let mut states = self.closure(&BTreeSet::from([self.start.clone()]));
+
+        for symbol in input {
+            states = self.step(&states, symbol);
+        }
+
+        states.iter().any(|state| self.is_accepting(state))
+    }
+
+    /// An equivalent DFA by the subset construction, whose states are the
+    /// sets of states the NFA can be in. Only the sets reachable from the
+    /// start are built, and the empty set is left out.
+    pub fn determinize(&self) -> Dfa<BTreeSet<Q>, A> {
+        let alphabet: Vec<A> = self.alphabet().into_iter().cloned().collect();
+
+        Dfa::from_fn(
+            self.closure(&BTreeSet::from([self.start.clone()])),
+            &alphabet,
+            |states, symbol| Some(self.step(states, symbol)).filter(|next| !next.is_empty()),
+            |states| states.iter().any(|state| self.is_accepting(state)),
+        )
+    }
+
+    /// A DFA building its states on the fly as inputs need them, for NFAs
+    /// whose full subset construction would be too large.
+    pub fn lazy(&self) -> LazyDfa<'_, Q, A> {
+        LazyDfa {
+            start: self.closure(&BTreeSet::from([self.start.clone()])),
+            nfa: self,
+            transitions: BTreeMap::new(),
+        }
+    }
+}
+
+/// The subset construction of an [`Nfa`], built one transition at a time as
+/// [`LazyDfa::accepts`] follows it and cached for later inputs.
+#[derive(Debug, Clone)]
+pub struct LazyDfa<'a, Q, A> {
+    nfa: &'a Nfa<Q, A>,
+    start: BTreeSet<Q>,
+    transitions: BTreeMap<(BTreeSet<Q>, A), BTreeSet<Q>>,
+}
+
+impl<Q, A> LazyDfa<'_, Q, A>
+where
+    Q: Ord + Clone,
+    A: Ord + Clone,
+{
+    pub fn accepts<'a>(&mut self, input: impl IntoIterator<Item = &'a A>) -> bool
+    where
+        A: 'a,
+    {
+        let mut states = self.start.clone();
+
+        for symbol in input {
+            if states.is_empty() {
+                return false;
+            }
+
+            let key = (states, symbol.clone());
+            states = match self.transitions.get(&key) {
+                Some(next) => next.clone(),
+                None => {
+                    let next = self.nfa.step(&key.0, symbol);
+                    self.transitions.insert(key, next.clone());
+                    next
+                }
+            };
+        }
+
+        states.iter().any(|state| self.nfa.is_accepting(state))
+    }
+
+    /// The number of transitions built so far.
+    pub fn built(&self) -> usize {
+        self.transitions.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::format;
+
+    use super::*;
+    use crate::Char;
+
+    // binary strings whose third symbol from the end is a one, the classic
+    // NFA whose DFA needs every combination of the last three symbols
+    fn third_from_end() -> Nfa<u8, Char> {
+        let mut nfa = Nfa::new(0);
+
+        for bit in ['0', '1'] {
+            nfa.insert(0, Some(Char(bit)), 0);
+            nfa.insert(2, Some(Char(bit)), 3);
+            nfa.insert(3, Some(Char(bit)), 4);
+        }
+
+        nfa.insert(0, Some(Char('1')), 1);
+        nfa.insert(1, None, 2);
+        nfa.accept(4);
+        nfa
+    }
+
+    #[test]
+    fn determinize_test() {
+        let nfa = third_from_end();
+        let dfa = nfa.determinize();
+        let mut lazy = nfa.lazy();
+
+        assert_eq!(dfa.states().len(), 8);
+
+        for n in 0..128u32 {
+            let input: Vec<Char> = format!("{n:b}").chars().map(Char).collect();
+            let expected = input.len() >= 3 && input[input.len() - 3] == Char('1');
+
+            assert_eq!(nfa.accepts(&input), expected);
+            assert_eq!(dfa.accepts(&input), expected);
+            assert_eq!(lazy.accepts(&input), expected);
+        }
+
+        assert_eq!(lazy.built(), 16);
+    }
+}
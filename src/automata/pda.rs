@@ -0,0 +1,284 @@
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::vec;
+use alloc::vec::Vec;
+
+use super::{Cfg, GrammarSymbol};
+
+/// A nondeterministic pushdown automaton with states `Q` over input symbols
+/// `A` and stack symbols `G`, accepting by empty stack: an input is accepted
+/// if some run reads all of it and pops the last symbol off the stack.
+///
+/// Each transition pops the symbol on top of the stack, possibly reading an
+/// input symbol, and pushes a word whose first symbol ends up on top.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pda<Q, A, G> {
+    start: Q,
+    bottom: G,
+    // `None` for ε
+    transitions: BTreeMap<(Q, Option<A>, G), Targets<Q, G>>,
+}
+
+// the states a transition leads to, with the word it pushes
+type Targets<Q, G> = BTreeSet<(Q, Vec<G>)>;
+
+/// The variables of the grammar built by [`Pda::to_cfg`].
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PdaVariable<Q, G> {
+    Start,
+    /// The words on which the automaton can go from the first state to the
+    /// second, popping the stack symbol and leaving the rest of the stack
+    /// untouched.
+    Pop(Q, G, Q),
+}
+
+// a state, how much of the input has been read and the stack, top last
+type Configuration<Q, G> = (Q, usize, Vec<G>);
+
+impl<Q, A, G> Pda<Q, A, G>
+where
+    Q: Ord + Clone,
+    A: Ord + Clone,
+    G: Ord + Clone,
+{
+    /// An automaton starting in `start` with only `bottom` on the stack.
+    pub fn new(start: Q, bottom: G) -> Self {
+        Self {
+            start,
+            bottom,
+            transitions: BTreeMap::new(),
+        }
+    }
+
+    /// Adds a transition from `from` popping `pop` to `to` pushing `push`,
+    /// reading `symbol`, or nothing for `None`.
+    pub fn insert(
+        &mut self,
+        from: Q,
+        symbol: Option<A>,
+        pop: G,
+        to: Q,
+        push: impl IntoIterator<Item = G>,
+    ) {
+        self.transitions
+            .entry((from, symbol, pop))
+            .or_default()
+            .insert((to, push.into_iter().collect()));
+    }
+
+    pub fn start(&self) -> &Q {
+        &self.start
+    }
+
+    pub fn bottom(&self) -> &G {
+        &self.bottom
+    }
+
+    /// All `(from, symbol, pop, to, push)` transitions, `None` standing for
+    /// not reading.
+    pub fn transitions(&self) -> impl Iterator<Item = (&Q, Option<&A>, &G, &Q, &[G])> {
+        self.transitions
+            .iter()
+            .flat_map(|((from, symbol, pop), targets)| {
+                targets
+                    .iter()
+                    .map(move |(to, push)| (from, symbol.as_ref(), pop, to, &push[..]))
+            })
+    }
+
+    /// The start state and every state named in a transition.
+    pub fn states(&self) -> BTreeSet<&Q> {
+        let mut states = BTreeSet::from([&self.start]);
+
+        for (from, _, _, to, _) in self.transitions() {
+            states.insert(from);
+            states.insert(to);
+        }
+
+        states
+    }
+
+    /// Whether some run accepts `input`, searching the runs whose stack
+    /// never grows past `max_stack` symbols, as runs that don't read can
+    /// grow it without end.
+    pub fn accepts(&self, input: &[A], max_stack: usize) -> bool {
+        let start = (self.start.clone(), 0, vec![self.bottom.clone()]);
+        let mut seen: BTreeSet<Configuration<Q, G>> = BTreeSet::from([start.clone()]);
+        let mut pending = vec![start];
+
+        while let Some((state, position, mut stack)) = pending.pop() {
+            let Some(top) = stack.pop() else {
+                if position == input.len() {
+                    return true;
+                }
+
+                continue;
+            };
+
+            let reads = input.get(position).map(|symbol| (Some(symbol.clone()), 1));
+
+            for (symbol, read) in [(None, 0)].into_iter().chain(reads) {
+                let key = (state.clone(), symbol, top.clone());
+
+                for (to, push) in self.transitions.get(&key).into_iter().flatten() {
+                    let mut next = stack.clone();
+                    next.extend(push.iter().rev().cloned());
+
+                    let configuration = (to.clone(), position + read, next);
+
+                    if configuration.2.len() <= max_stack && seen.insert(configuration.clone()) {
+                        pending.push(configuration);
+                    }
+                }
+            }
+        }
+
+        false
+    }
+
+    /// An equivalent grammar by the triple construction: a variable
+    /// [`PdaVariable::Pop`] for every two states and stack symbol, and a
+    /// production for every transition and choice of the states the
+    /// automaton passes through popping what it pushed.
+    ///
+    /// That's a production per state for every symbol pushed, so most are
+    /// useless; [`Cfg::trim`] drops them.
+    pub fn to_cfg(&self) -> Cfg<PdaVariable<Q, G>, A> {
+        let states: Vec<&Q> = self.states().into_iter().collect();
+        let mut cfg = Cfg::new(PdaVariable::Start);
+
+        for &state in &states {
+            cfg.insert(
+                PdaVariable::Start,
+                [GrammarSymbol::Variable(PdaVariable::Pop(
+                    self.start.clone(),
+                    self.bottom.clone(),
+                    state.clone(),
+                ))],
+            );
+        }
+
+        for (from, symbol, pop, to, push) in self.transitions() {
+            // the state after popping each pushed symbol
+            let mut sequences: Vec<Vec<&Q>> = vec![Vec::new()];
+
+            for _ in push {
+                sequences = sequences
+                    .into_iter()
+                    .flat_map(|sequence| {
+                        states.iter().map(move |&state| {
+                            let mut sequence = sequence.clone();
+                            sequence.push(state);
+                            sequence
+                        })
+                    })
+                    .collect();
+            }
+
+            for sequence in sequences {
+                let mut body: Vec<_> = symbol
+                    .map(|symbol| GrammarSymbol::Terminal(symbol.clone()))
+                    .into_iter()
+                    .collect();
+                let mut current = to;
+
+                for (pushed, &next) in push.iter().zip(&sequence) {
+                    body.push(GrammarSymbol::Variable(PdaVariable::Pop(
+                        current.clone(),
+                        pushed.clone(),
+                        next.clone(),
+                    )));
+                    current = next;
+                }
+
+                cfg.insert(
+                    PdaVariable::Pop(from.clone(), pop.clone(), current.clone()),
+                    body,
+                );
+            }
+        }
+
+        cfg
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::String;
+
+    use super::*;
+
+    // every word over the parentheses up to `length`
+    fn words(length: usize) -> Vec<Vec<char>> {
+        (0..=length)
+            .flat_map(|length| {
+                (0..1u32 << length).map(move |bits| {
+                    (0..length)
+                        .map(|i| if bits >> i & 1 == 0 { '(' } else { ')' })
+                        .collect()
+                })
+            })
+            .collect()
+    }
+
+    fn balanced(word: &[char]) -> bool {
+        let mut depth = 0i32;
+
+        word.iter().all(|&c| {
+            depth += if c == '(' { 1 } else { -1 };
+            depth >= 0
+        }) && depth == 0
+    }
+
+    #[test]
+    fn pda_test() {
+        // pushes an X for every opening parenthesis and pops one for every
+        // closing one, then pops the bottom
+        let mut pda = Pda::new(0, 'Z');
+        pda.insert(0, Some('('), 'Z', 0, ['X', 'Z']);
+        pda.insert(0, Some('('), 'X', 0, ['X', 'X']);
+        pda.insert(0, Some(')'), 'X', 0, []);
+        pda.insert(0, None, 'Z', 0, []);
+
+        let cfg = pda.to_cfg().trim();
+
+        for word in words(8) {
+            let expected = balanced(&word);
+
+            assert_eq!(
+                pda.accepts(&word, 16),
+                expected,
+                "{}",
+                String::from_iter(&word)
+            );
+            assert_eq!(cfg.generates(&word), expected);
+        }
+    }
+
+    #[test]
+    fn round_trip_test() {
+        // S → ( S ) S | ε
+        let mut cfg = Cfg::new('S');
+        cfg.insert(
+            'S',
+            [
+                GrammarSymbol::Terminal('('),
+                GrammarSymbol::Variable('S'),
+                GrammarSymbol::Terminal(')'),
+                GrammarSymbol::Variable('S'),
+            ],
+        );
+        cfg.insert('S', []);
+
+        let pda = cfg.to_pda();
+        let back = pda.to_cfg().trim();
+
+        assert_eq!(pda.states().len(), 1);
+
+        for word in words(8) {
+            let expected = balanced(&word);
+
+            assert_eq!(pda.accepts(&word, 32), expected);
+            assert_eq!(back.generates(&word), expected);
+        }
+    }
+}
@@ -0,0 +1,403 @@
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use crate::{RunStats, StopReason};
+
+/// An instruction of a [`Ram`] program over registers `r`, jumping to
+/// others by their index in the program.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RamInstruction {
+    /// `r[dst] = value`
+    Set {
+        dst: usize,
+        value: u64,
+    },
+    /// `r[dst] = r[a] + r[b]`
+    Add {
+        dst: usize,
+        a: usize,
+        b: usize,
+    },
+    /// `r[dst] = r[a] - r[b]`, or 0 if `r[b]` is larger.
+    Sub {
+        dst: usize,
+        a: usize,
+        b: usize,
+    },
+    /// `r[dst] = r[src] / 2`
+    Half {
+        dst: usize,
+        src: usize,
+    },
+    /// `r[dst] = r[r[src]]`
+    Load {
+        dst: usize,
+        src: usize,
+    },
+    /// `r[r[dst]] = r[src]`
+    Store {
+        dst: usize,
+        src: usize,
+    },
+    Jump {
+        target: usize,
+    },
+    /// Jumps to `target` if `r[register]` is 0.
+    JumpZero {
+        register: usize,
+        target: usize,
+    },
+    Halt,
+}
+
+/// A random-access machine program: instructions over unboundedly many
+/// registers holding natural numbers, all 0 to begin with, with loads and
+/// stores through registers holding addresses. It starts at instruction 0
+/// and halts on [`RamInstruction::Halt`] or a jump past the end of the
+/// program.
+///
+/// Every instruction counts as one step whatever the size of its numbers,
+/// the unit-cost measure; [`RamMachine::log_cost`] gives the logarithmic
+/// one.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Ram {
+    program: Vec<RamInstruction>,
+}
+
+/// A [`Ram`] program running, with its registers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RamMachine<'a> {
+    ram: &'a Ram,
+    pc: usize,
+    registers: BTreeMap<usize, u64>,
+    log_cost: u64,
+}
+
+/// The configuration of a [`RamMachine`], by reference.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RamPeek<'a> {
+    pub pc: usize,
+    /// The registers that aren't 0.
+    pub registers: &'a BTreeMap<usize, u64>,
+}
+
+/// An instruction [`RamMachine::run_traced`] is about to execute.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RamStep {
+    pub step: u64,
+    pub pc: usize,
+    pub instruction: RamInstruction,
+}
+
+impl Ram {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends `instruction` to the program, returning its index.
+    pub fn push(&mut self, instruction: RamInstruction) -> usize {
+        self.program.push(instruction);
+        self.program.len() - 1
+    }
+
+    pub fn program(&self) -> &[RamInstruction] {
+        &self.program
+    }
+
+    /// A machine at instruction 0 with every register 0.
+    pub fn machine(&self) -> RamMachine<'_> {
+        RamMachine {
+            ram: self,
+            pc: 0,
+            registers: BTreeMap::new(),
+            log_cost: 0,
+        }
+    }
+}
+
+impl FromIterator<RamInstruction> for Ram {
+    fn from_iter<I: IntoIterator<Item = RamInstruction>>(iter: I) -> Self {
+        Self {
+            program: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl<'a> RamMachine<'a> {
+    pub fn with_registers(mut self, registers: impl IntoIterator<Item = (usize, u64)>) -> Self {
+        for (register, value) in registers {
+            self.set(register, value);
+        }
+
+        self
+    }
+
+    pub fn register(&self, register: usize) -> u64 {
+        self.registers.get(&register).copied().unwrap_or(0)
+    }
+
+    pub fn set(&mut self, register: usize, value: u64) {
+        match value {
+            0 => self.registers.remove(&register),
+            value => self.registers.insert(register, value),
+        };
+    }
+
+    pub fn pc(&self) -> usize {
+        self.pc
+    }
+
+    pub fn halted(&self) -> bool {
+        matches!(
+            self.ram.program.get(self.pc),
+            None | Some(RamInstruction::Halt)
+        )
+    }
+
+    pub fn peek(&self) -> RamPeek<'_> {
+        RamPeek {
+            pc: self.pc,
+            registers: &self.registers,
+        }
+    }
+
+    /// The cost of the instructions executed so far when each costs the
+    /// bit lengths of the registers and values it touches.
+    pub fn log_cost(&self) -> u64 {
+        self.log_cost
+    }
+
+    /// Executes the instruction at the program counter, or returns `false`
+    /// if the machine halts there.
+    ///
+    /// # Panics
+    ///
+    /// If a register overflows, or a load or store goes through a register
+    /// holding more than `usize::MAX`.
+    pub fn step(&mut self) -> bool {
+        let Some(&instruction) = self.ram.program.get(self.pc) else {
+            return false;
+        };
+
+        let mut next = self.pc + 1;
+
+        match instruction {
+            RamInstruction::Halt => return false,
+            RamInstruction::Set { dst, value } => {
+                self.log_cost += log_cost(&[dst as u64, value]);
+                self.set(dst, value);
+            }
+            RamInstruction::Add { dst, a, b } | RamInstruction::Sub { dst, a, b } => {
+                let (x, y) = (self.register(a), self.register(b));
+                self.log_cost += log_cost(&[dst as u64, a as u64, b as u64, x, y]);
+
+                let value = match instruction {
+                    RamInstruction::Add { .. } => x.checked_add(y).expect("register overflow"),
+                    _ => x.saturating_sub(y),
+                };
+                self.set(dst, value);
+            }
+            RamInstruction::Half { dst, src } => {
+                let value = self.register(src);
+                self.log_cost += log_cost(&[dst as u64, src as u64, value]);
+                self.set(dst, value / 2);
+            }
+            RamInstruction::Load { dst, src } => {
+                let address = address(self.register(src));
+                let value = self.register(address);
+                self.log_cost += log_cost(&[dst as u64, src as u64, address as u64, value]);
+                self.set(dst, value);
+            }
+            RamInstruction::Store { dst, src } => {
+                let address = address(self.register(dst));
+                let value = self.register(src);
+                self.log_cost += log_cost(&[dst as u64, src as u64, address as u64, value]);
+                self.set(address, value);
+            }
+            RamInstruction::Jump { target } => {
+                self.log_cost += log_cost(&[target as u64]);
+                next = target;
+            }
+            RamInstruction::JumpZero { register, target } => {
+                let value = self.register(register);
+                self.log_cost += log_cost(&[register as u64, target as u64, value]);
+
+                if value == 0 {
+                    next = target;
+                }
+            }
+        }
+
+        self.pc = next;
+        true
+    }
+
+    /// Steps until halting or for at most `max_steps` steps. The stats'
+    /// `tape_len` is the number of registers that aren't 0.
+    pub fn run(&mut self, max_steps: u64) -> RunStats {
+        self.run_traced(max_steps, |_| true)
+    }
+
+    /// Like [`RamMachine::run`], passing every instruction to `sink` before
+    /// executing it. The run stops as cancelled if the sink returns `false`.
+    pub fn run_traced(
+        &mut self,
+        max_steps: u64,
+        mut sink: impl FnMut(&RamStep) -> bool,
+    ) -> RunStats {
+        let mut steps = 0;
+
+        let stop = loop {
+            if self.halted() {
+                break StopReason::Halted;
+            }
+
+            if steps >= max_steps {
+                break StopReason::StepLimit;
+            }
+
+            let step = RamStep {
+                step: steps,
+                pc: self.pc,
+                instruction: self.ram.program[self.pc],
+            };
+
+            if !sink(&step) {
+                break StopReason::Cancelled;
+            }
+
+            self.step();
+            steps += 1;
+        };
+
+        RunStats {
+            steps,
+            tape_len: self.registers.len(),
+            stop,
+        }
+    }
+}
+
+// the bit lengths of `values`, counting 0 as one bit
+fn log_cost(values: &[u64]) -> u64 {
+    values
+        .iter()
+        .map(|value| u64::from(u64::BITS - value.leading_zeros()).max(1))
+        .sum()
+}
+
+fn address(value: u64) -> usize {
+    usize::try_from(value).expect("address overflow")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Char, Machine, Rule, TransitionTable};
+
+    use RamInstruction::*;
+
+    // adds r1 into r0 one at a time, with r2 as the constant 1
+    fn adder() -> Ram {
+        [
+            Set { dst: 2, value: 1 },
+            JumpZero {
+                register: 1,
+                target: 5,
+            },
+            Sub { dst: 1, a: 1, b: 2 },
+            Add { dst: 0, a: 0, b: 2 },
+            Jump { target: 1 },
+            Halt,
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    #[test]
+    fn run_test() {
+        let ram = adder();
+        let mut machine = ram.machine().with_registers([(0, 3), (1, 4)]);
+        let mut pcs = Vec::new();
+
+        let stats = machine.run_traced(100, |step| {
+            pcs.push(step.pc);
+            true
+        });
+
+        assert_eq!(stats.stop, StopReason::Halted);
+        assert_eq!(stats.steps, 1 + 4 * 4 + 1);
+        assert_eq!(machine.register(0), 7);
+        assert_eq!(machine.peek().registers, &BTreeMap::from([(0, 7), (2, 1)]));
+        assert_eq!(pcs[..6], [0, 1, 2, 3, 4, 1]);
+        assert!(machine.log_cost() > stats.steps);
+
+        let mut machine = ram.machine().with_registers([(1, 1_000)]);
+        assert_eq!(machine.run(10).stop, StopReason::StepLimit);
+        assert_eq!(
+            machine.run_traced(10, |_| false).stop,
+            StopReason::Cancelled
+        );
+    }
+
+    #[test]
+    fn indirect_test() {
+        // sums the r1 registers from r10 on into r0 through the pointer r3,
+        // and stores the sum after them
+        let ram: Ram = [
+            Set { dst: 3, value: 10 },
+            Set { dst: 4, value: 1 },
+            JumpZero {
+                register: 1,
+                target: 8,
+            },
+            Load { dst: 5, src: 3 },
+            Add { dst: 0, a: 0, b: 5 },
+            Add { dst: 3, a: 3, b: 4 },
+            Sub { dst: 1, a: 1, b: 4 },
+            Jump { target: 2 },
+            Store { dst: 3, src: 0 },
+        ]
+        .into_iter()
+        .collect();
+
+        let mut machine = ram
+            .machine()
+            .with_registers([(1, 3), (10, 5), (11, 6), (12, 7)]);
+
+        assert_eq!(machine.run(1_000).stop, StopReason::Halted);
+        assert_eq!(machine.register(0), 18);
+        assert_eq!(machine.register(13), 18);
+        assert_eq!(machine.pc(), 9);
+    }
+
+    #[test]
+    fn compare_test() {
+        // unary addition on a tape, `1110_1111` to `1111111`
+        let table: TransitionTable<char, Char> = [
+            ('A', Char('1'), Rule::new().right()),
+            ('A', Char('0'), Rule::to('B').write(Char('1')).right()),
+            ('B', Char('1'), Rule::new().right()),
+            ('B', Char('_'), Rule::to('C').left()),
+            ('C', Char('1'), Rule::halt().write(Char('_'))),
+        ]
+        .into_iter()
+        .collect();
+        let mut tm: Machine<char, Char> = Machine::with_input('A', "11101111").unwrap();
+        let tm_stats = tm.run_table(&table, 1_000);
+
+        let ram = adder();
+        let mut machine = ram.machine().with_registers([(0, 3), (1, 4)]);
+        let ram_stats = machine.run(1_000);
+
+        assert_eq!(
+            tm.tape_to_vec()
+                .iter()
+                .filter(|&&cell| cell == Char('1'))
+                .count(),
+            7
+        );
+        assert_eq!(machine.register(0), 7);
+        assert_eq!((tm_stats.steps, ram_stats.steps), (10, 18));
+    }
+}
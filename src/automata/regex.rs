@@ -0,0 +1,269 @@
+//! Regular expressions, compiled to [`Nfa`]s by Thompson's construction.
+//!
+//! Characters stand for the symbols they parse as, and `\` takes the next
+//! character literally. `|` separates alternatives, `*`, `+` and `?` repeat
+//! what precedes them and parentheses group. An empty pattern, group or
+//! alternative matches the empty input.
+
+use alloc::borrow::ToOwned;
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt::{self, Display};
+use core::iter::Peekable;
+use core::str::CharIndices;
+
+use super::Nfa;
+use crate::Symbol;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegexError {
+    /// The byte offset in the pattern.
+    pub position: usize,
+    pub message: String,
+}
+
+impl Display for RegexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "at {}: {}", self.position, self.message)
+    }
+}
+
+impl Error for RegexError {}
+
+enum Regex<A> {
+    Empty,
+    Symbol(A),
+    Concat(Vec<Regex<A>>),
+    Alternatives(Vec<Regex<A>>),
+    Star(Box<Regex<A>>),
+    Plus(Box<Regex<A>>),
+    Optional(Box<Regex<A>>),
+}
+
+/// Compiles `pattern` to an NFA accepting exactly the inputs it matches as a
+/// whole, with states numbered from 0 for the start.
+pub fn compile<A>(pattern: &str) -> Result<Nfa<usize, A>, RegexError>
+where
+    A: Symbol + Ord + Clone,
+{
+    let mut parser = Parser {
+        chars: pattern.char_indices().peekable(),
+        end: pattern.len(),
+    };
+    let regex = parser.alternatives()?;
+
+    if let Some((position, c)) = parser.chars.next() {
+        return Err(RegexError {
+            position,
+            message: format!("unmatched {c:?}"),
+        });
+    }
+
+    let mut nfa = Nfa::new(0);
+    let mut states = 1;
+    let end = build(&regex, 0, &mut nfa, &mut states);
+    nfa.accept(end);
+
+    Ok(nfa)
+}
+
+struct Parser<'a> {
+    chars: Peekable<CharIndices<'a>>,
+    end: usize,
+}
+
+impl Parser<'_> {
+    fn alternatives<A: Symbol>(&mut self) -> Result<Regex<A>, RegexError> {
+        let mut alternatives = Vec::from([self.concat()?]);
+
+        while self.chars.next_if(|&(_, c)| c == '|').is_some() {
+            alternatives.push(self.concat()?);
+        }
+
+        Ok(match alternatives.len() {
+            1 => alternatives.pop().unwrap(),
+            _ => Regex::Alternatives(alternatives),
+        })
+    }
+
+    fn concat<A: Symbol>(&mut self) -> Result<Regex<A>, RegexError> {
+        let mut parts = Vec::new();
+
+        while let Some(&(position, c)) = self.chars.peek() {
+            let atom = match c {
+                '|' | ')' => break,
+                '(' => {
+                    self.chars.next();
+                    let group = self.alternatives()?;
+
+                    if self.chars.next_if(|&(_, c)| c == ')').is_none() {
+                        return Err(self.error(position, "unclosed group"));
+                    }
+
+                    group
+                }
+                '*' | '+' | '?' => {
+                    return Err(self.error(position, &format!("nothing to repeat with {c:?}")))
+                }
+                _ => {
+                    self.chars.next();
+
+                    let c = match c {
+                        '\\' => match self.chars.next() {
+                            Some((_, c)) => c,
+                            None => return Err(self.error(self.end, "trailing escape")),
+                        },
+                        c => c,
+                    };
+
+                    match A::from_char(c) {
+                        Some(symbol) => Regex::Symbol(symbol),
+                        None => return Err(self.error(position, &format!("invalid symbol {c:?}"))),
+                    }
+                }
+            };
+
+            parts.push(self.repeats(atom));
+        }
+
+        Ok(match parts.len() {
+            0 => Regex::Empty,
+            1 => parts.pop().unwrap(),
+            _ => Regex::Concat(parts),
+        })
+    }
+
+    fn repeats<A>(&mut self, mut atom: Regex<A>) -> Regex<A> {
+        while let Some((_, c)) = self.chars.next_if(|&(_, c)| matches!(c, '*' | '+' | '?')) {
+            atom = match c {
+                '*' => Regex::Star(Box::new(atom)),
+                '+' => Regex::Plus(Box::new(atom)),
+                _ => Regex::Optional(Box::new(atom)),
+            };
+        }
+
+        atom
+    }
+
+    fn error(&self, position: usize, message: &str) -> RegexError {
+        RegexError {
+            position,
+            message: message.to_owned(),
+        }
+    }
+}
+
+// adds the states and transitions of `regex` starting from `start`,
+// returning the state it ends in
+fn build<A>(regex: &Regex<A>, start: usize, nfa: &mut Nfa<usize, A>, states: &mut usize) -> usize
+where
+    A: Ord + Clone,
+{
+    match regex {
+        Regex::Empty => start,
+        Regex::Symbol(symbol) => {
+            let end = fresh(states);
+            nfa.insert(start, Some(symbol.clone()), end);
+            end
+        }
+        Regex::Concat(parts) => parts
+            .iter()
+            .fold(start, |state, part| build(part, state, nfa, states)),
+        Regex::Alternatives(alternatives) => {
+            let end = fresh(states);
+
+            for alternative in alternatives {
+                let branch = fresh(states);
+                nfa.insert(start, None, branch);
+                let branch_end = build(alternative, branch, nfa, states);
+                nfa.insert(branch_end, None, end);
+            }
+
+            end
+        }
+        Regex::Star(inner) | Regex::Plus(inner) | Regex::Optional(inner) => {
+            let (body, end) = (fresh(states), fresh(states));
+            nfa.insert(start, None, body);
+            let body_end = build(inner, body, nfa, states);
+            nfa.insert(body_end, None, end);
+
+            if !matches!(regex, Regex::Plus(_)) {
+                nfa.insert(start, None, end);
+            }
+
+            if !matches!(regex, Regex::Optional(_)) {
+                nfa.insert(body_end, None, body);
+            }
+
+            end
+        }
+    }
+}
+
+fn fresh(states: &mut usize) -> usize {
+    *states += 1;
+    *states - 1
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::*;
+    use crate::automata::{Dfa, DfaState};
+    use crate::{Char, Machine, Verdict};
+
+    fn chars(input: &str) -> Vec<Char> {
+        input.chars().map(Char).collect()
+    }
+
+    #[test]
+    fn compile_test() {
+        let nfa = compile::<Char>("a(b|c)*d?|\\*+").unwrap();
+
+        for input in ["a", "abcbd", "ad", "*", "***"] {
+            assert!(nfa.accepts(&chars(input)), "{input}");
+        }
+
+        for input in ["", "d", "abdd", "a*"] {
+            assert!(!nfa.accepts(&chars(input)), "{input}");
+        }
+
+        assert!(compile::<Char>("").unwrap().accepts(&[]));
+        assert_eq!(compile::<Char>("a|*").unwrap_err().position, 2);
+        assert_eq!(
+            compile::<Char>("(ab").unwrap_err().message,
+            "unclosed group"
+        );
+        assert!(compile::<Char>("a)").is_err());
+        assert!(compile::<bool>("012").is_err());
+    }
+
+    #[test]
+    fn pipeline_test() {
+        // regex, NFA, DFA, minimal DFA and Turing machine agree
+        let nfa = compile::<Char>("(0|1)*1(0|1)").unwrap();
+        let dfa = nfa.determinize().minimize().dfa;
+        let table = dfa.to_machine();
+
+        for n in 0..64u32 {
+            let input = chars(&format!("{n:b}"));
+            let mut machine: Machine<_, Char> =
+                Machine::new(DfaState::State(0), input.iter().copied().collect());
+            let expected = match nfa.accepts(&input) {
+                true => Verdict::Accept,
+                false => Verdict::Reject,
+            };
+
+            assert_eq!(
+                machine.run_table_accepting(&table, 100, &Dfa::acceptance()),
+                Ok(expected)
+            );
+        }
+
+        assert_eq!(dfa.states().len(), 4);
+    }
+}
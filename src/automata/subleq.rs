@@ -0,0 +1,372 @@
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt::{self, Display};
+use core::num::ParseIntError;
+
+use crate::{Machine, Symbol};
+
+/// A one-instruction computer: memory is a row of words, and the
+/// instruction at `pc` is the three words `a b c` there. It subtracts the
+/// word at address `a` from the one at `b` and jumps to `c` if the result
+/// is zero or less, or goes on to `pc + 3`.
+///
+/// Jumping to a negative address or one without a whole instruction halts.
+/// Address `-1` is input and output: an `a` of `-1` reads the next input
+/// word into `b` (`-1` once input runs out), and a `b` of `-1` outputs the
+/// word at `a`; neither jumps. Arithmetic wraps around at the word width,
+/// 64 bits unless set with [`Subleq::with_width`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Subleq {
+    memory: Vec<i64>,
+    pc: Option<usize>,
+    width: u32,
+    pub input: VecDeque<i64>,
+    pub output: Vec<i64>,
+}
+
+/// An instruction [`Subleq::step`] executed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SubleqStep {
+    pub pc: usize,
+    pub a: i64,
+    pub b: i64,
+    pub c: i64,
+    /// The word written to `b`, read or output.
+    pub value: i64,
+    pub jumped: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubleqError {
+    /// The instruction at `pc` names an address outside memory.
+    Address { pc: usize, address: i64 },
+    /// A cell of a tape holds neither a 0 nor a 1.
+    Symbol { cell: usize },
+}
+
+impl Display for SubleqError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SubleqError::Address { pc, address } => {
+                write!(f, "instruction at {pc} addresses {address}, outside memory")
+            }
+            SubleqError::Symbol { cell } => write!(f, "cell {cell} isn't a binary digit"),
+        }
+    }
+}
+
+impl Error for SubleqError {}
+
+impl Subleq {
+    /// A computer with `memory`, at instruction 0.
+    pub fn new(memory: Vec<i64>) -> Self {
+        let mut subleq = Self {
+            memory,
+            pc: None,
+            width: 64,
+            input: VecDeque::new(),
+            output: Vec::new(),
+        };
+        subleq.jump(0);
+        subleq
+    }
+
+    /// A computer with the whitespace-separated words of `src` as memory,
+    /// ignoring `#` to the end of a line.
+    pub fn parse(src: &str) -> Result<Self, ParseIntError> {
+        let memory = src
+            .lines()
+            .flat_map(|line| line.split('#').next().unwrap_or("").split_whitespace())
+            .map(str::parse)
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self::new(memory))
+    }
+
+    /// Makes words `width` bits wide, wrapping the memory to them.
+    ///
+    /// # Panics
+    ///
+    /// If `width` isn't between 1 and 64.
+    pub fn with_width(mut self, width: u32) -> Self {
+        assert!((1..=64).contains(&width), "words are 1 to 64 bits wide");
+
+        self.width = width;
+        for word in 0..self.memory.len() {
+            self.memory[word] = self.wrap(self.memory[word]);
+        }
+
+        self
+    }
+
+    pub fn with_input(mut self, input: impl IntoIterator<Item = i64>) -> Self {
+        self.input.extend(input);
+        self
+    }
+
+    pub fn memory(&self) -> &[i64] {
+        &self.memory
+    }
+
+    pub fn memory_mut(&mut self) -> &mut [i64] {
+        &mut self.memory
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// The address of the next instruction, `None` once halted.
+    pub fn pc(&self) -> Option<usize> {
+        self.pc
+    }
+
+    pub fn halted(&self) -> bool {
+        self.pc.is_none()
+    }
+
+    /// Executes the next instruction, or returns `None` if halted.
+    pub fn step(&mut self) -> Result<Option<SubleqStep>, SubleqError> {
+        let Some(pc) = self.pc else {
+            return Ok(None);
+        };
+
+        let [a, b, c] = [self.memory[pc], self.memory[pc + 1], self.memory[pc + 2]];
+        let address = |address: i64| {
+            usize::try_from(address)
+                .ok()
+                .filter(|&address| address < self.memory.len())
+                .ok_or(SubleqError::Address { pc, address })
+        };
+
+        let (value, jumped) = match (a, b) {
+            (-1, -1) => return Err(SubleqError::Address { pc, address: -1 }),
+            (-1, b) => {
+                let b = address(b)?;
+                let read = self.input.pop_front().unwrap_or(-1);
+                let value = self.wrap(read);
+                self.memory[b] = value;
+                (value, false)
+            }
+            (a, -1) => {
+                let value = self.memory[address(a)?];
+                self.output.push(value);
+                (value, false)
+            }
+            (a, b) => {
+                let (a, b) = (address(a)?, address(b)?);
+                let value = self.wrap(self.memory[b].wrapping_sub(self.memory[a]));
+                self.memory[b] = value;
+                (value, value <= 0)
+            }
+        };
+
+        match jumped {
+            true => self.jump(c),
+            false => self.jump(pc as i64 + 3),
+        }
+
+        Ok(Some(SubleqStep {
+            pc,
+            a,
+            b,
+            c,
+            value,
+            jumped,
+        }))
+    }
+
+    /// Steps until halting or for at most `max_steps` steps, returning the
+    /// number of steps taken.
+    pub fn run(&mut self, max_steps: u64) -> Result<u64, SubleqError> {
+        self.run_traced(max_steps, |_| {})
+    }
+
+    /// Like [`Subleq::run`], calling `trace` with every instruction executed.
+    pub fn run_traced(
+        &mut self,
+        max_steps: u64,
+        mut trace: impl FnMut(&SubleqStep),
+    ) -> Result<u64, SubleqError> {
+        let mut steps = 0;
+
+        while steps < max_steps {
+            let Some(step) = self.step()? else {
+                break;
+            };
+
+            trace(&step);
+            steps += 1;
+        }
+
+        Ok(steps)
+    }
+
+    /// A computer whose memory is the tape of `machine` from cell 0 on, as
+    /// `width`-bit two's complement words, least significant bit first; a
+    /// partial word at the end is left out. Blanks count as zeros.
+    ///
+    /// # Panics
+    ///
+    /// If `width` isn't between 1 and 64.
+    pub fn from_tape<S, Sym>(machine: &Machine<S, Sym>, width: u32) -> Result<Self, SubleqError>
+    where
+        Sym: Symbol + Default + Clone,
+    {
+        assert!((1..=64).contains(&width), "words are 1 to 64 bits wide");
+
+        let blank = Sym::blank().to_char();
+        let cells: Vec<&Sym> = machine.tape().iter().skip(machine.origin()).collect();
+        let mut memory = Vec::new();
+
+        for (word, bits) in cells.chunks_exact(width as usize).enumerate() {
+            let mut value = 0u64;
+
+            for (bit, cell) in bits.iter().enumerate() {
+                match cell.to_char() {
+                    '1' => value |= 1 << bit,
+                    '0' => {}
+                    c if c == blank => {}
+                    _ => {
+                        return Err(SubleqError::Symbol {
+                            cell: word * width as usize + bit,
+                        })
+                    }
+                }
+            }
+
+            // sign-extends the top bit
+            let shift = 64 - width;
+            memory.push(((value << shift) as i64) >> shift);
+        }
+
+        Ok(Self::new(memory).with_width(width))
+    }
+
+    /// Writes the memory back onto the tape of `machine` as
+    /// [`Subleq::from_tape`] reads it.
+    ///
+    /// # Panics
+    ///
+    /// If the tape is too short for the memory, or `Sym` has no `0` or `1`.
+    pub fn write_tape<S, Sym>(&self, machine: &mut Machine<S, Sym>)
+    where
+        Sym: Symbol + Default + Clone,
+    {
+        let zero = Sym::from_char('0').expect("a symbol for 0");
+        let one = Sym::from_char('1').expect("a symbol for 1");
+        let origin = machine.origin();
+
+        for (word, &value) in self.memory.iter().enumerate() {
+            for bit in 0..self.width as usize {
+                let symbol = match (value >> bit) & 1 {
+                    1 => one.clone(),
+                    _ => zero.clone(),
+                };
+
+                machine.write_at(origin + word * self.width as usize + bit, symbol);
+            }
+        }
+    }
+
+    /// Runs the program stored on the tape of `machine`, as
+    /// [`Subleq::from_tape`] reads it, for at most `max_steps` steps, and
+    /// writes the memory back. Returns the computer, halted or not.
+    pub fn run_tape<S, Sym>(
+        machine: &mut Machine<S, Sym>,
+        width: u32,
+        max_steps: u64,
+    ) -> Result<Self, SubleqError>
+    where
+        Sym: Symbol + Default + Clone,
+    {
+        let mut subleq = Self::from_tape(machine, width)?;
+        let result = subleq.run(max_steps);
+
+        subleq.write_tape(machine);
+        result.map(|_| subleq)
+    }
+
+    fn jump(&mut self, target: i64) {
+        self.pc = usize::try_from(target)
+            .ok()
+            .filter(|&target| target.saturating_add(3) <= self.memory.len());
+    }
+
+    // the word `value` wraps to at `width` bits
+    fn wrap(&self, value: i64) -> i64 {
+        let shift = 64 - self.width;
+        (value << shift) >> shift
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    // adds the words at 12 and 13 into 13, through the scratch word 14,
+    // and outputs it
+    const ADD: &str = "
+        12 14 3   # z -= a
+        14 13 6   # b -= z
+        13 -1 9   # output b
+        14 14 -1  # z = 0, halt
+        7 5 0     # a b z
+    ";
+
+    #[test]
+    fn run_test() {
+        let mut subleq = Subleq::parse(ADD).unwrap();
+        let mut pcs = Vec::new();
+
+        assert_eq!(subleq.run_traced(100, |step| pcs.push(step.pc)), Ok(4));
+        assert!(subleq.halted());
+        assert_eq!(pcs, [0, 3, 6, 9]);
+        assert_eq!(subleq.output, [12]);
+        assert_eq!(subleq.memory()[12..], [7, 12, 0]);
+
+        // an endless loop, then a bad address
+        let mut looping = Subleq::new(vec![3, 3, 0, 0]);
+        assert_eq!(looping.run(1_000), Ok(1_000));
+        assert_eq!(looping.pc(), Some(0));
+        assert_eq!(
+            Subleq::new(vec![0, 7, 0]).step(),
+            Err(SubleqError::Address { pc: 0, address: 7 })
+        );
+
+        // echoes input, wrapping at 8 bits
+        let mut echo = Subleq::new(vec![-1, 6, 3, 6, -1, -1, 0])
+            .with_width(8)
+            .with_input([200]);
+        echo.run(10).unwrap();
+        assert_eq!(echo.output, [-56]);
+    }
+
+    #[test]
+    fn tape_test() {
+        // `4 5 -1 0 3 2` in four bit words, then a partial one: subtracts 3
+        // from 2 and halts
+        let mut machine: Machine<char, bool> =
+            Machine::with_input('A', "001010101111000011000100101").unwrap();
+
+        let subleq = Subleq::run_tape(&mut machine, 4, 10).unwrap();
+
+        assert!(subleq.halted());
+        assert_eq!(subleq.memory(), [4, 5, -1, 0, 3, -1]);
+        assert_eq!(
+            machine.tape_to_vec(),
+            Machine::<char, bool>::with_input('A', "001010101111000011001111101")
+                .unwrap()
+                .tape_to_vec()
+        );
+
+        let machine: Machine<char, char> = Machine::with_input('A', "01x1").unwrap();
+        assert_eq!(
+            Subleq::from_tape(&machine, 2),
+            Err(SubleqError::Symbol { cell: 2 })
+        );
+    }
+}
@@ -0,0 +1,817 @@
+use alloc::collections::{BTreeMap, BTreeSet, VecDeque};
+use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt::{self, Debug, Display};
+
+use crate::{Machine, Move, Rule, State, TransitionTable};
+
+/// An `m`-tag system over symbols `A`: while the word is at least `m`
+/// symbols long and starts with a symbol that has a production, that
+/// production is appended and the first `m` symbols are deleted.
+///
+/// Symbols without a production halt the system.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagSystem<A> {
+    deletion: usize,
+    productions: BTreeMap<A, Vec<A>>,
+}
+
+/// The states of the Turing machine built by [`TagSystem::to_machine`].
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TagState<A> {
+    /// On the first symbol of the word, between steps of the tag system.
+    Read,
+    /// Making sure the word is long enough for a step starting with the
+    /// symbol, with `remaining` more symbols to see.
+    Check { symbol: A, remaining: usize },
+    /// Moving right to the end of the word.
+    ToEnd { symbol: A },
+    /// Writing the production of the symbol from its `index`th symbol on.
+    Append { symbol: A, index: usize },
+    /// Moving left to the start of the word.
+    Return,
+    /// Deleting `remaining` more symbols from the start of the word.
+    Delete { remaining: usize },
+}
+
+impl<A> TagSystem<A>
+where
+    A: Ord + Clone,
+{
+    /// # Panics
+    ///
+    /// If `deletion` is 0.
+    pub fn new(deletion: usize) -> Self {
+        assert!(deletion > 0, "a tag system has to delete symbols");
+
+        Self {
+            deletion,
+            productions: BTreeMap::new(),
+        }
+    }
+
+    /// Sets the word appended for `symbol`, returning the one it replaced,
+    /// if any.
+    pub fn insert(&mut self, symbol: A, production: Vec<A>) -> Option<Vec<A>> {
+        self.productions.insert(symbol, production)
+    }
+
+    pub fn deletion(&self) -> usize {
+        self.deletion
+    }
+
+    pub fn production(&self, symbol: &A) -> Option<&[A]> {
+        self.productions.get(symbol).map(Vec::as_slice)
+    }
+
+    /// Takes one step on `word`, or returns `false` if the system halts on
+    /// it.
+    pub fn step(&self, word: &mut VecDeque<A>) -> bool {
+        if word.len() < self.deletion {
+            return false;
+        }
+
+        let Some(production) = self.productions.get(&word[0]) else {
+            return false;
+        };
+
+        word.extend(production.iter().cloned());
+        word.drain(..self.deletion);
+        true
+    }
+
+    /// Steps on `word` until halting or for at most `max_steps` steps,
+    /// returning the number of steps taken.
+    pub fn run(&self, word: &mut VecDeque<A>, max_steps: u64) -> u64 {
+        let mut steps = 0;
+
+        while steps < max_steps && self.step(word) {
+            steps += 1;
+        }
+
+        steps
+    }
+
+    /// A Turing machine simulating the system on a word written from cell 0
+    /// on, with the blank, `A::default()`, after it. It starts in
+    /// [`TagState::Read`], enters it again after every step of the system
+    /// and halts when the system does, leaving the word on the tape.
+    ///
+    /// Each step checks that the word is long enough, appends the
+    /// production at its end and deletes symbols from its start, so the word
+    /// drifts right along the tape. The blank must not be in the alphabet.
+    pub fn to_machine(&self) -> TransitionTable<TagState<A>, A>
+    where
+        A: Default,
+    {
+        let blank = A::default();
+        let mut alphabet: Vec<&A> = self.productions.keys().collect();
+
+        for production in self.productions.values() {
+            alphabet.extend(production);
+        }
+
+        alphabet.sort();
+        alphabet.dedup();
+
+        let mut table = TransitionTable::new();
+        let after_check = |symbol: &A| match self.deletion {
+            1 => TagState::ToEnd {
+                symbol: symbol.clone(),
+            },
+            deletion => TagState::Check {
+                symbol: symbol.clone(),
+                remaining: deletion - 1,
+            },
+        };
+
+        table.insert(TagState::Read, blank.clone(), Rule::halt());
+        table.insert(
+            TagState::Return,
+            blank.clone(),
+            Rule::to(TagState::Delete {
+                remaining: self.deletion,
+            })
+            .right(),
+        );
+
+        for &symbol in &alphabet {
+            let rule = match self.productions.contains_key(symbol) {
+                true => Rule::to(after_check(symbol)).right(),
+                false => Rule::halt(),
+            };
+
+            table.insert(TagState::Read, symbol.clone(), rule);
+            table.insert(TagState::Return, symbol.clone(), Rule::new().left());
+        }
+
+        for (symbol, production) in &self.productions {
+            for remaining in 1..self.deletion {
+                let check = TagState::Check {
+                    symbol: symbol.clone(),
+                    remaining,
+                };
+                let next = match remaining {
+                    1 => TagState::ToEnd {
+                        symbol: symbol.clone(),
+                    },
+                    remaining => TagState::Check {
+                        symbol: symbol.clone(),
+                        remaining: remaining - 1,
+                    },
+                };
+
+                // too short: halt with the word as it is
+                table.insert(check.clone(), blank.clone(), Rule::halt());
+
+                for &other in &alphabet {
+                    table.insert(check.clone(), other.clone(), Rule::to(next.clone()).right());
+                }
+            }
+
+            let to_end = TagState::ToEnd {
+                symbol: symbol.clone(),
+            };
+
+            for &other in &alphabet {
+                table.insert(to_end.clone(), other.clone(), Rule::new().right());
+            }
+
+            for (index, written) in production.iter().enumerate() {
+                let from = match index {
+                    0 => to_end.clone(),
+                    index => TagState::Append {
+                        symbol: symbol.clone(),
+                        index,
+                    },
+                };
+                let rule = match index + 1 == production.len() {
+                    true => Rule::to(TagState::Return).write(written.clone()).left(),
+                    false => Rule::to(TagState::Append {
+                        symbol: symbol.clone(),
+                        index: index + 1,
+                    })
+                    .write(written.clone())
+                    .right(),
+                };
+
+                table.insert(from, blank.clone(), rule);
+            }
+
+            if production.is_empty() {
+                table.insert(to_end, blank.clone(), Rule::to(TagState::Return).left());
+            }
+        }
+
+        for remaining in 1..=self.deletion {
+            let next = match remaining {
+                1 => TagState::Read,
+                remaining => TagState::Delete {
+                    remaining: remaining - 1,
+                },
+            };
+
+            for &symbol in &alphabet {
+                table.insert(
+                    TagState::Delete { remaining },
+                    symbol.clone(),
+                    Rule::to(next.clone()).write(blank.clone()).right(),
+                );
+            }
+        }
+
+        table
+    }
+
+    /// Runs the system on `word` and its [`TagSystem::to_machine`] side by
+    /// side for at most `max_steps` steps of the system, comparing the word
+    /// with the tape after each one. Returns the number of steps taken.
+    pub fn cosimulate(&self, word: &[A], max_steps: u64) -> Result<u64, Divergence<A>>
+    where
+        A: Default,
+    {
+        let blank = A::default();
+        let table = self.to_machine();
+        let mut expected: VecDeque<A> = word.iter().cloned().collect();
+        let mut machine = Machine::new(TagState::Read, expected.clone());
+
+        for step in 0..max_steps {
+            let halts = !self.step(&mut expected);
+
+            loop {
+                machine.execute_table(&table);
+
+                if machine.halted() || machine.state() == Some(&TagState::Read) {
+                    break;
+                }
+            }
+
+            let found: Vec<A> = machine
+                .tape_to_vec()
+                .into_iter()
+                .filter(|symbol| *symbol != blank)
+                .collect();
+
+            if found.iter().ne(&expected) || halts != machine.halted() {
+                return Err(Divergence {
+                    step,
+                    expected: expected.into(),
+                    found,
+                    halted: machine.halted(),
+                });
+            }
+
+            if halts {
+                return Ok(step);
+            }
+        }
+
+        Ok(max_steps)
+    }
+}
+
+/// Returned by [`TagSystem::cosimulate`] when the machine and the system
+/// disagree after `step` steps.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence<A> {
+    pub step: u64,
+    pub expected: Vec<A>,
+    pub found: Vec<A>,
+    /// Whether the machine had halted.
+    pub halted: bool,
+}
+
+impl<A: Debug> Display for Divergence<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "after {} steps expected {:?}, found {:?}",
+            self.step, self.expected, self.found
+        )
+    }
+}
+
+impl<A: Debug> Error for Divergence<A> {}
+
+/// The result of [`TagSystem::from_table`], the Cocke–Minsky simulation of a
+/// table over bits by a 2-tag system.
+///
+/// With the tape left of the head read as a number `m` and the rest as `n`,
+/// both with the bit nearest the head lowest, a configuration in `control` is
+/// the word `Start Pad (Left Pad)^m Middle Pad (Right Pad)^n` of
+/// [`TagStage::Config`] symbols. The system only ever reads every other
+/// symbol, and takes the symbol under the head, the parity of `n`, from
+/// whether halving `n` leaves it reading the pads instead: a step of the
+/// table is three passes over the word, or four moving left, where the
+/// parity of `m` is needed too.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableTag<S> {
+    pub system: TagSystem<TagSymbol<S>>,
+    /// Every symbol of the system, as [`CyclicTag::from_tag`] needs them.
+    ///
+    /// [`CyclicTag::from_tag`]: super::CyclicTag::from_tag
+    pub alphabet: Vec<TagSymbol<S>>,
+    start: S,
+}
+
+/// A symbol of a [`TableTag`].
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TagSymbol<S> {
+    /// Between the symbols the system reads, never read itself.
+    Pad,
+    Part {
+        control: TagControl<S>,
+        stage: TagStage,
+        part: TagPart,
+    },
+}
+
+/// What a word of a [`TableTag`] is simulating a step of.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TagControl<S> {
+    State(S),
+    /// The rest of a rule's move by more than one cell, `remaining` cells
+    /// towards the `right` before entering `next`, or halting.
+    Moving {
+        right: bool,
+        remaining: usize,
+        next: Option<S>,
+    },
+    /// Has no productions, so the system halts on the configuration.
+    Halt,
+}
+
+/// How far into simulating a step a [`TagSymbol`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TagStage {
+    /// Between steps.
+    Config,
+    /// With `n` halved, but its parity not yet read.
+    Halved,
+    /// With the symbol under the head read.
+    Read(bool),
+    /// Moving left, with `m` halved too.
+    LeftHalved(bool),
+    /// Moving left, with the parity of `m`, the symbol left of the head,
+    /// read as `low`.
+    LeftRead { read: bool, low: bool },
+}
+
+/// The part of the word for a configuration a [`TagSymbol`] is in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TagPart {
+    Start,
+    /// One for each unit of `m`, the tape left of the head.
+    Left,
+    Middle,
+    /// One for each unit of `n`, the rest of the tape.
+    Right,
+}
+
+impl<S> TagSystem<TagSymbol<S>>
+where
+    S: Ord + Clone,
+{
+    /// A 2-tag system simulating `table` from `start`, halting on
+    /// [`TagControl::Halt`] when the table halts or has no rule, with the
+    /// tape as the table leaves it. Moves by more than one cell take a step
+    /// for each.
+    pub fn from_table(table: &TransitionTable<S, bool>, start: S) -> TableTag<S> {
+        use TagPart::{Left, Middle, Right, Start};
+
+        let symbol = |control: &TagControl<S>, stage, part| TagSymbol::Part {
+            control: control.clone(),
+            stage,
+            part,
+        };
+
+        let mut system = TagSystem::new(2);
+        let mut seen: BTreeSet<TagControl<S>> = [&start]
+            .into_iter()
+            .chain(table.states())
+            .map(|state| TagControl::State(state.clone()))
+            .collect();
+        let mut controls: Vec<TagControl<S>> = seen.iter().cloned().collect();
+
+        while let Some(control) = controls.pop() {
+            if control == TagControl::Halt {
+                continue;
+            }
+
+            let config = |part| symbol(&control, TagStage::Config, part);
+            let halved = |part| symbol(&control, TagStage::Halved, part);
+
+            // halving `n` puts the pass after next a symbol off when it was
+            // even, reading the second of the pairs the next pass appends
+            system.insert(config(Start), Vec::from([halved(Start), TagSymbol::Pad]));
+            system.insert(config(Left), Vec::from([halved(Left), TagSymbol::Pad]));
+            system.insert(config(Middle), Vec::from([halved(Middle)]));
+            system.insert(config(Right), Vec::from([halved(Right)]));
+
+            for part in [Start, Left, Middle, Right] {
+                let read = |bit| symbol(&control, TagStage::Read(bit), part);
+                system.insert(halved(part), Vec::from([read(true), read(false)]));
+            }
+
+            for read in [false, true] {
+                let (write, offset, next) = step(table, &control, read);
+                let from = |part| symbol(&control, TagStage::Read(read), part);
+                let to = |part| symbol(&next, TagStage::Config, part);
+                let writes = usize::from(write);
+
+                let productions = match offset {
+                    // m = 2m + write, n = n / 2
+                    1 => [
+                        spaced(repeated(to(Start), to(Left), writes), !read, false),
+                        spaced(Vec::from([to(Left), to(Left)]), false, false),
+                        spaced(Vec::from([to(Middle)]), false, false),
+                        spaced(Vec::from([to(Right)]), false, false),
+                    ],
+                    // n = 2 (n / 2) + write
+                    0 => [
+                        spaced(Vec::from([to(Start)]), !read, false),
+                        spaced(Vec::from([to(Left)]), false, false),
+                        spaced(repeated(to(Middle), to(Right), writes), false, false),
+                        spaced(Vec::from([to(Right), to(Right)]), false, false),
+                    ],
+                    // halving `m` too, to move its lowest bit over
+                    _ => {
+                        let lower = |part| symbol(&control, TagStage::LeftHalved(read), part);
+                        let low =
+                            |low, part| symbol(&control, TagStage::LeftRead { read, low }, part);
+
+                        system.insert(lower(Start), Vec::from([to(Start), to(Start)]));
+                        system.insert(lower(Left), Vec::from([to(Left), to(Left)]));
+
+                        // m = m / 2, n = 4 (n / 2) + 2 write + low
+                        for bit in [false, true] {
+                            let middle =
+                                repeated(to(Middle), to(Right), 2 * writes + usize::from(bit));
+                            let right = Vec::from([to(Right), to(Right), to(Right), to(Right)]);
+
+                            system.insert(low(bit, Middle), spaced(middle, !bit, false));
+                            system.insert(low(bit, Right), spaced(right, false, false));
+                        }
+
+                        [
+                            spaced(Vec::from([lower(Start)]), !read, true),
+                            Vec::from([lower(Left)]),
+                            Vec::from([low(true, Middle), low(false, Middle)]),
+                            Vec::from([low(true, Right), low(false, Right)]),
+                        ]
+                    }
+                };
+
+                for (part, production) in [Start, Left, Middle, Right].into_iter().zip(productions)
+                {
+                    system.insert(from(part), production);
+                }
+
+                if seen.insert(next.clone()) {
+                    controls.push(next);
+                }
+            }
+        }
+
+        let mut alphabet = BTreeSet::from([TagSymbol::Pad]);
+
+        for control in seen {
+            for part in [Start, Left, Middle, Right] {
+                alphabet.insert(symbol(&control, TagStage::Config, part));
+            }
+        }
+
+        for (symbol, production) in &system.productions {
+            alphabet.insert(symbol.clone());
+            alphabet.extend(production.iter().cloned());
+        }
+
+        TableTag {
+            system,
+            alphabet: alphabet.into_iter().collect(),
+            start,
+        }
+    }
+}
+
+impl<S> TableTag<S>
+where
+    S: Clone,
+{
+    /// The word for the configuration of `machine`, or `None` if the
+    /// numbers for its tape overflow.
+    pub fn encode(&self, machine: &Machine<S, bool>) -> Option<Vec<TagSymbol<S>>> {
+        let tape = machine.tape();
+        let head = machine.head();
+        let number = |bits: Vec<&bool>| {
+            bits.into_iter().rev().try_fold(0usize, |value, &bit| {
+                value.checked_mul(2)?.checked_add(usize::from(bit))
+            })
+        };
+
+        let control = match machine.state() {
+            Some(state) => TagControl::State(state.clone()),
+            None => TagControl::Halt,
+        };
+        let counts = [
+            (TagPart::Start, 1),
+            (TagPart::Left, number(tape.range(..head).rev().collect())?),
+            (TagPart::Middle, 1),
+            (TagPart::Right, number(tape.range(head..).collect())?),
+        ];
+
+        let mut word = Vec::new();
+
+        for (part, count) in counts {
+            let symbol = TagSymbol::Part {
+                control: control.clone(),
+                stage: TagStage::Config,
+                part,
+            };
+
+            for _ in 0..count {
+                word.extend([symbol.clone(), TagSymbol::Pad]);
+            }
+        }
+
+        Some(word)
+    }
+
+    /// The configuration of the table for a word between steps, from the
+    /// symbols the system reads of it, or `None` if the word isn't one or is
+    /// in the middle of a move.
+    pub fn decode<'a>(
+        &self,
+        word: impl IntoIterator<Item = &'a TagSymbol<S>>,
+    ) -> Option<Machine<S, bool>>
+    where
+        S: 'a,
+    {
+        let mut word = word.into_iter().step_by(2);
+        let Some(TagSymbol::Part {
+            control,
+            stage: TagStage::Config,
+            part: TagPart::Start,
+        }) = word.next()
+        else {
+            return None;
+        };
+
+        let state = match control {
+            TagControl::State(state) => State::State(state.clone()),
+            TagControl::Moving { .. } => return None,
+            TagControl::Halt => State::Halt,
+        };
+
+        let (mut left, mut right) = (0, 0);
+
+        for symbol in word {
+            if let TagSymbol::Part {
+                stage: TagStage::Config,
+                part,
+                ..
+            } = symbol
+            {
+                match part {
+                    TagPart::Left => left += 1,
+                    TagPart::Right => right += 1,
+                    _ => {}
+                }
+            }
+        }
+
+        let bits = |mut value: usize| {
+            let mut bits = Vec::new();
+
+            while value > 0 {
+                bits.push(value % 2 == 1);
+                value /= 2;
+            }
+
+            bits
+        };
+
+        let left = bits(left);
+        let mut tape: VecDeque<bool> = left.iter().rev().copied().collect();
+        tape.extend(bits(right));
+
+        if tape.len() == left.len() {
+            tape.push_back(false);
+        }
+
+        let mut machine = Machine::new(self.start.clone(), tape);
+        machine.set_head(left.len());
+        machine.set_state(state);
+        Some(machine)
+    }
+}
+
+// the bit the rule for `control` reading `read` writes, the way it moves
+// the head by at most a cell, and what comes next
+fn step<S>(
+    table: &TransitionTable<S, bool>,
+    control: &TagControl<S>,
+    read: bool,
+) -> (bool, isize, TagControl<S>)
+where
+    S: Ord + Clone,
+{
+    let TagControl::State(state) = control else {
+        let TagControl::Moving {
+            right,
+            remaining,
+            next,
+        } = control
+        else {
+            return (read, 0, TagControl::Halt);
+        };
+
+        let control = match remaining {
+            1 => next.clone().map_or(TagControl::Halt, TagControl::State),
+            remaining => TagControl::Moving {
+                right: *right,
+                remaining: remaining - 1,
+                next: next.clone(),
+            },
+        };
+
+        return (read, if *right { 1 } else { -1 }, control);
+    };
+
+    let Some(rule) = table.lookup(state, &read) else {
+        return (read, 0, TagControl::Halt);
+    };
+
+    let write = rule.write.unwrap_or(read);
+    let offset = rule.head_move.map_or(0, Move::offset);
+    let next = match rule.new_state {
+        None => Some(state.clone()),
+        Some(State::State(next)) => Some(next),
+        Some(State::Halt) => None,
+    };
+
+    if offset.unsigned_abs() > 1 {
+        let control = TagControl::Moving {
+            right: offset > 0,
+            remaining: offset.unsigned_abs() - 1,
+            next,
+        };
+
+        return (write, offset.signum(), control);
+    }
+
+    (
+        write,
+        offset,
+        next.map_or(TagControl::Halt, TagControl::State),
+    )
+}
+
+// `first`, then `count` copies of `then`
+fn repeated<A: Clone>(first: A, then: A, count: usize) -> Vec<A> {
+    let mut symbols = Vec::from([first]);
+    symbols.extend(core::iter::repeat_n(then, count));
+    symbols
+}
+
+// a production the system reads `symbols` of, one after the other, starting
+// from its second symbol if `odd` and leaving it to read the one after next
+// if `next_odd`
+fn spaced<S>(symbols: Vec<TagSymbol<S>>, odd: bool, next_odd: bool) -> Vec<TagSymbol<S>> {
+    let mut production = Vec::new();
+
+    if odd {
+        production.push(TagSymbol::Pad);
+    }
+
+    for (index, symbol) in symbols.into_iter().enumerate() {
+        if index > 0 {
+            production.push(TagSymbol::Pad);
+        }
+
+        production.push(symbol);
+    }
+
+    if !next_odd {
+        production.push(TagSymbol::Pad);
+    }
+
+    production
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Char;
+
+    // the tape around the head without blanks at either end, with the head
+    // relative to its first cell
+    fn configuration(machine: &Machine<char, bool>) -> (Vec<bool>, Option<char>, isize) {
+        let tape: Vec<bool> = machine.tape_to_vec();
+        let head = machine.head();
+        let start = tape
+            .iter()
+            .position(|&bit| bit)
+            .map_or(head, |start| start.min(head));
+        let end = tape
+            .iter()
+            .rposition(|&bit| bit)
+            .map_or(start, |end| end + 1);
+
+        (
+            tape[start..end.max(start)].to_vec(),
+            machine.state().copied(),
+            (head - start) as isize,
+        )
+    }
+
+    // De Mol's 2-tag system for the Collatz function on unary `a`s
+    fn collatz() -> TagSystem<Char> {
+        let mut tag = TagSystem::new(2);
+        tag.insert(Char('a'), Vec::from([Char('b'), Char('c')]));
+        tag.insert(Char('b'), Vec::from([Char('a')]));
+        tag.insert(Char('c'), Vec::from([Char('a'), Char('a'), Char('a')]));
+        tag
+    }
+
+    #[test]
+    fn tag_system_test() {
+        let tag = collatz();
+        let mut word: VecDeque<Char> = [Char('a'); 3].into();
+
+        // 3 -> 10 -> 5 -> 16 -> 8 -> 4 -> 2 -> 1
+        assert_eq!(tag.run(&mut word, 1_000), 24);
+        assert_eq!(word, [Char('a')]);
+    }
+
+    #[test]
+    fn cosimulate_test() {
+        let tag = collatz();
+
+        assert_eq!(tag.cosimulate(&[Char('a'); 3], 1_000), Ok(24));
+        assert_eq!(tag.cosimulate(&[Char('a'); 5], 10), Ok(10));
+
+        // halting on a symbol without a production
+        let mut halting = TagSystem::new(1);
+        halting.insert(Char('a'), Vec::from([Char('h'), Char('b')]));
+        halting.insert(Char('b'), Vec::new());
+
+        assert_eq!(halting.cosimulate(&[Char('a'), Char('b')], 100), Ok(2));
+    }
+
+    #[test]
+    fn from_table_test() {
+        let table: TransitionTable<char, bool> = [
+            ('A', false, Rule::to('B').write(true).right()),
+            ('A', true, Rule::to('B').write(true).left()),
+            ('B', false, Rule::to('A').write(true).left()),
+            ('B', true, Rule::halt().write(true).right()),
+        ]
+        .into_iter()
+        .collect();
+
+        let tag = TagSystem::from_table(&table, 'A');
+        let mut machine: Machine<char, bool> = Machine::new('A', [false].into());
+        let mut word: VecDeque<_> = tag.encode(&machine).unwrap().into();
+
+        // step by step
+        while !machine.halted() {
+            machine.execute_table(&table);
+            tag.system.step(&mut word);
+
+            while tag.decode(&word).is_none() && tag.system.step(&mut word) {}
+
+            let decoded = tag.decode(&word).unwrap();
+            assert_eq!(configuration(&decoded), configuration(&machine));
+        }
+
+        assert!(!tag.system.step(&mut word));
+        assert_eq!(machine.tape().iter().filter(|&&cell| cell).count(), 4);
+    }
+
+    #[test]
+    fn from_table_moves_test() {
+        // staying, moving by more than a cell and running out of rules
+        let table: TransitionTable<char, bool> = [
+            ('A', false, Rule::to('B').write(true).by(2)),
+            ('B', false, Rule::new().write(true)),
+            ('B', true, Rule::to('C').by(-3)),
+        ]
+        .into_iter()
+        .collect();
+
+        let tag = TagSystem::from_table(&table, 'A');
+        let mut machine: Machine<char, bool> = Machine::new('A', [false].into());
+        let mut word: VecDeque<_> = tag.encode(&machine).unwrap().into();
+
+        machine.run_table(&table, 100);
+        tag.system.run(&mut word, 10_000);
+
+        let decoded = tag.decode(&word).unwrap();
+        assert_eq!(
+            configuration(&decoded),
+            (Vec::from([false, true, false, true]), None, 0)
+        );
+        assert_eq!(configuration(&decoded), configuration(&machine));
+    }
+}
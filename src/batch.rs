@@ -0,0 +1,161 @@
+use std::borrow::Borrow;
+use std::mem;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use crate::{Machine, Metrics, RunStats, Tape, TransitionTable};
+
+/// The outcome of one job of a [`run_batch`] call.
+#[derive(Debug, Clone)]
+pub struct BatchResult<S, Sym: Default, T> {
+    pub stats: RunStats,
+    pub machine: Machine<S, Sym, T>,
+}
+
+/// Runs every `(table, machine)` pair for at most `max_steps` steps, spread
+/// over `threads` worker threads (all available cores if zero). Results are
+/// returned in job order.
+pub fn run_batch<S, Sym, T, P>(
+    jobs: Vec<(P, Machine<S, Sym, T>)>,
+    max_steps: u64,
+    threads: usize,
+) -> Vec<BatchResult<S, Sym, T>>
+where
+    S: Ord + Clone + Send,
+    Sym: Default + Ord + Clone + Send,
+    T: Tape<Sym> + Send,
+    P: Borrow<TransitionTable<S, Sym>> + Send,
+{
+    run_batch_metered(jobs, max_steps, threads, &())
+}
+
+/// Like [`run_batch`], reporting every run and the memory the tapes take to
+/// `metrics` as the batch goes.
+pub fn run_batch_metered<S, Sym, T, P>(
+    jobs: Vec<(P, Machine<S, Sym, T>)>,
+    max_steps: u64,
+    threads: usize,
+    metrics: &impl Metrics,
+) -> Vec<BatchResult<S, Sym, T>>
+where
+    S: Ord + Clone + Send,
+    Sym: Default + Ord + Clone + Send,
+    T: Tape<Sym> + Send,
+    P: Borrow<TransitionTable<S, Sym>> + Send,
+{
+    let cell = mem::size_of::<Sym>();
+    let memory = AtomicUsize::new(
+        jobs.iter()
+            .map(|(_, machine)| machine.tape.len() * cell)
+            .sum(),
+    );
+    metrics.record_memory(memory.load(Ordering::Relaxed));
+
+    let threads = match threads {
+        0 => thread::available_parallelism().map_or(1, NonZeroUsize::get),
+        threads => threads,
+    }
+    .min(jobs.len());
+
+    // jobs are handed out one at a time so that slow runs don't hold up a
+    // whole chunk of fast ones
+    let jobs: Vec<_> = jobs.into_iter().map(Mutex::new).collect();
+    let results: Vec<_> = jobs.iter().map(|_| Mutex::new(None)).collect();
+    let next = AtomicUsize::new(0);
+
+    thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+
+                let Some(job) = jobs.get(index) else {
+                    break;
+                };
+
+                let (table, machine) = &mut *job.lock().unwrap();
+                let len = machine.tape.len();
+                let stats = machine.run_table(P::borrow(table), max_steps);
+
+                let grown = stats.tape_len.saturating_sub(len) * cell;
+                let total = memory.fetch_add(grown, Ordering::Relaxed) + grown;
+                metrics.record_run(&stats);
+                metrics.record_memory(total);
+
+                *results[index].lock().unwrap() = Some(stats);
+            });
+        }
+    });
+
+    jobs.into_iter()
+        .zip(results)
+        .map(|(job, stats)| BatchResult {
+            stats: stats.into_inner().unwrap().unwrap(),
+            machine: job.into_inner().unwrap().1,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{dsl, Counters, StopReason};
+
+    #[test]
+    fn run_batch_test() {
+        let inc: TransitionTable<String, bool> = dsl::parse("inc 1 0 R *\ninc 0 1 N halt").unwrap();
+        let forever: TransitionTable<String, bool> = dsl::parse("inc 0 0 R *").unwrap();
+
+        let jobs = vec![
+            (&inc, Machine::with_input("inc".to_owned(), "111").unwrap()),
+            (
+                &forever,
+                Machine::with_input("inc".to_owned(), "0").unwrap(),
+            ),
+            (&inc, Machine::with_input("inc".to_owned(), "0").unwrap()),
+        ];
+
+        let results = run_batch(jobs, 100, 2);
+        let stops: Vec<_> = results.iter().map(|result| result.stats.stop).collect();
+
+        assert_eq!(
+            stops,
+            [
+                StopReason::Halted,
+                StopReason::StepLimit,
+                StopReason::Halted
+            ]
+        );
+        assert_eq!(results[0].stats.steps, 4);
+        assert_eq!(
+            results[0].machine.tape_to_vec(),
+            [false, false, false, true]
+        );
+        assert_eq!(results[1].stats.steps, 100);
+        assert_eq!(results[2].machine.tape_to_vec(), [true]);
+    }
+
+    #[test]
+    fn run_batch_metered_test() {
+        let inc: TransitionTable<String, bool> = dsl::parse("inc 1 0 R *\ninc 0 1 N halt").unwrap();
+        let forever: TransitionTable<String, bool> = dsl::parse("inc 0 0 R *").unwrap();
+
+        let jobs = vec![
+            (&inc, Machine::with_input("inc".to_owned(), "111").unwrap()),
+            (
+                &forever,
+                Machine::with_input("inc".to_owned(), "0").unwrap(),
+            ),
+        ];
+
+        let counters = Counters::new();
+        run_batch_metered(jobs, 100, 2, &counters);
+        let snapshot = counters.snapshot();
+
+        assert_eq!(snapshot.machines, 2);
+        assert_eq!(snapshot.steps, 104);
+        assert_eq!(snapshot.undecided, 1);
+        assert_eq!(snapshot.memory, 4 + 101);
+    }
+}
@@ -0,0 +1,118 @@
+//! The busy beaver champions for the sizes whose values are proven: the
+//! halting machines that run longest from a blank tape among those with
+//! their number of states and symbols.
+//!
+//! Steps count the halting transition, and the machines are written in
+//! [`bbchallenge`] notation.
+
+use crate::dsl::Definition;
+use crate::format::bbchallenge;
+use crate::Symbol;
+
+/// A champion with its number of steps and of non-blank symbols left on the
+/// tape when it halts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Champion {
+    pub states: usize,
+    pub symbols: usize,
+    pub machine: &'static str,
+    pub steps: u64,
+    pub sigma: u64,
+}
+
+const CHAMPIONS: &[Champion] = &[
+    Champion {
+        states: 2,
+        symbols: 2,
+        machine: "1RB1LB_1LA1RZ",
+        steps: 6,
+        sigma: 4,
+    },
+    Champion {
+        states: 3,
+        symbols: 2,
+        machine: "1RB1RZ_1LB0RC_1LC1LA",
+        steps: 21,
+        sigma: 5,
+    },
+    Champion {
+        states: 4,
+        symbols: 2,
+        machine: "1RB1LB_1LA0LC_1RZ1LD_1RD0RA",
+        steps: 107,
+        sigma: 13,
+    },
+    Champion {
+        states: 5,
+        symbols: 2,
+        machine: "1RB1LC_1RC1RB_1RD0LE_1LA1LD_1RZ0LA",
+        steps: 47_176_870,
+        sigma: 4098,
+    },
+    Champion {
+        states: 2,
+        symbols: 3,
+        machine: "1RB2LB1RZ_2LA2RB1LB",
+        steps: 38,
+        sigma: 9,
+    },
+    Champion {
+        states: 2,
+        symbols: 4,
+        machine: "1RB2LA1RA1RA_1LB1LA3RB1RZ",
+        steps: 3_932_964,
+        sigma: 2050,
+    },
+];
+
+impl Champion {
+    /// The champion's table, with states named as [`bbchallenge::parse`]
+    /// names them.
+    pub fn definition<Sym>(&self) -> Definition<Sym>
+    where
+        Sym: Symbol + Ord + Default,
+    {
+        bbchallenge::parse(self.machine).unwrap()
+    }
+}
+
+/// All the champions in the database.
+pub fn champions() -> &'static [Champion] {
+    CHAMPIONS
+}
+
+/// The champion with `states` states and `symbols` symbols, if its value is
+/// known.
+pub fn champion(states: usize, symbols: usize) -> Option<&'static Champion> {
+    CHAMPIONS
+        .iter()
+        .find(|champion| champion.states == states && champion.symbols == symbols)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::collections::VecDeque;
+    use alloc::string::String;
+
+    use super::*;
+    use crate::{Machine, StopReason};
+
+    #[test]
+    fn champion_test() {
+        assert_eq!(champion(5, 2).map(|champion| champion.sigma), Some(4098));
+        assert_eq!(champion(6, 2), None);
+
+        for champion in champions().iter().filter(|champion| champion.steps < 1_000) {
+            let definition = champion.definition::<u8>();
+            let mut machine: Machine<String, u8> =
+                Machine::new(definition.start.clone(), VecDeque::from([0]));
+            let stats = machine.run_table(&definition.table, 1_000);
+            let sigma = machine.tape_to_vec().iter().filter(|&&s| s != 0).count();
+
+            assert_eq!(stats.stop, StopReason::Halted);
+            assert_eq!(stats.steps, champion.steps, "{}", champion.machine);
+            assert_eq!(sigma as u64, champion.sigma, "{}", champion.machine);
+            assert_eq!(definition.table.states().count(), champion.states);
+        }
+    }
+}
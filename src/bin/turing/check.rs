@@ -0,0 +1,91 @@
+use std::process::ExitCode;
+
+use turing::dsl::Definition;
+use turing::{analysis, Char, MissingRule, Symbol};
+
+use crate::{convert, Args};
+
+/// Exits with 0 if the definition is clean, 1 if there are diagnostics and 2
+/// if it could not be read or parsed (including nondeterministic tables).
+pub fn main(args: &[String]) -> Result<ExitCode, String> {
+    let args = Args::parse(args, &["--from"], &[])?;
+
+    let [path] = args.positional[..] else {
+        return Err("usage: turing check <file> [--from <format>]".to_owned());
+    };
+
+    let definition = convert::load(path, args.flag("--from"))?;
+    let diagnostics = diagnostics(&definition);
+
+    for diagnostic in &diagnostics {
+        println!("{path}: warning: {diagnostic}");
+    }
+
+    match diagnostics.len() {
+        0 => Ok(ExitCode::SUCCESS),
+        count => {
+            println!("{path}: {count} warning(s)");
+            Ok(ExitCode::from(1))
+        }
+    }
+}
+
+fn diagnostics(definition: &Definition<Char>) -> Vec<String> {
+    let table = &definition.table;
+    let start = &definition.start;
+
+    let blank = definition.blank.unwrap_or_default();
+    let mut alphabet = analysis::alphabet(table);
+    alphabet.insert(&blank);
+
+    let mut diagnostics = Vec::new();
+
+    if table.rules(start).next().is_none() {
+        diagnostics.push(format!("start state {start:?} has no rules"));
+    }
+
+    for state in analysis::unreachable_states(table, start) {
+        diagnostics.push(format!("state {state:?} is unreachable"));
+    }
+
+    let consequence = match table.missing() {
+        MissingRule::Halt => "the machine halts there",
+        MissingRule::Reject => "the machine halts there, rejecting",
+        MissingRule::Error => "the run stops there with an error",
+        MissingRule::Default(_) => "the default rule applies there",
+    };
+
+    for (state, symbol) in analysis::missing_rules(table, start, &alphabet) {
+        diagnostics.push(format!(
+            "no rule for state {state:?} reading {:?}; {consequence}",
+            symbol.to_char()
+        ));
+    }
+
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use turing::dsl;
+
+    use super::*;
+
+    #[test]
+    fn diagnostics_test() {
+        // the blank is `0`, so `_` never turns up
+        let mut definition = dsl::parse_definition("blank 0\nq 1 1 R *").unwrap();
+
+        assert_eq!(
+            diagnostics(&definition),
+            ["no rule for state \"q\" reading '0'; the machine halts there"]
+        );
+
+        definition.table.set_missing(MissingRule::Error);
+
+        assert_eq!(
+            diagnostics(&definition),
+            ["no rule for state \"q\" reading '0'; the run stops there with an error"]
+        );
+    }
+}
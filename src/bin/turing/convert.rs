@@ -0,0 +1,97 @@
+use std::fs;
+use std::path::Path;
+use std::process::ExitCode;
+
+use turing::dsl::{self, Definition, ParseError};
+use turing::format::{bbchallenge, dot, jflap, yaml};
+use turing::Char;
+
+use crate::Args;
+
+const FORMATS: &str = "dsl, yaml, jflap, bbchallenge or dot";
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Dsl,
+    Yaml,
+    Jflap,
+    Bbchallenge,
+    Dot,
+}
+
+impl Format {
+    fn from_name(name: &str) -> Result<Self, String> {
+        match name {
+            "dsl" => Ok(Self::Dsl),
+            "yaml" => Ok(Self::Yaml),
+            "jflap" => Ok(Self::Jflap),
+            "bbchallenge" => Ok(Self::Bbchallenge),
+            "dot" => Ok(Self::Dot),
+            _ => Err(format!("unknown format {name:?}; expected {FORMATS}")),
+        }
+    }
+
+    pub fn from_path(path: &str) -> Self {
+        match Path::new(path).extension().and_then(|e| e.to_str()) {
+            Some("yaml" | "yml") => Self::Yaml,
+            Some("jff") => Self::Jflap,
+            Some("bb") => Self::Bbchallenge,
+            Some("dot" | "gv") => Self::Dot,
+            _ => Self::Dsl,
+        }
+    }
+
+    fn parse(self, src: &str) -> Result<Definition<Char>, String> {
+        let result: Result<_, ParseError> = match self {
+            Self::Dsl => dsl::parse_definition(src),
+            Self::Yaml => yaml::parse(src),
+            Self::Jflap => jflap::parse(src),
+            Self::Bbchallenge => bbchallenge::parse(src),
+            Self::Dot => return Err("dot is an export-only format".to_owned()),
+        };
+
+        result.map_err(|e| e.to_string())
+    }
+
+    fn write(self, definition: &Definition<Char>) -> Result<String, String> {
+        match self {
+            Self::Dsl => Ok(dsl::write_definition(definition)),
+            Self::Yaml => yaml::write(definition).map_err(|e| e.to_string()),
+            Self::Jflap => jflap::write(definition).map_err(|e| e.to_string()),
+            Self::Bbchallenge => bbchallenge::write(definition)
+                .map(|notation| notation + "\n")
+                .map_err(|e| e.to_string()),
+            Self::Dot => Ok(dot::write(definition)),
+        }
+    }
+}
+
+/// Reads a machine definition in any supported format, guessing it from
+/// the file extension unless `from` is given.
+pub fn load(path: &str, from: Option<&str>) -> Result<Definition<Char>, String> {
+    let format = match from {
+        Some(name) => Format::from_name(name)?,
+        None => Format::from_path(path),
+    };
+
+    let src = fs::read_to_string(path).map_err(|e| format!("{path}: {e}"))?;
+
+    format.parse(&src).map_err(|e| format!("{path}: {e}"))
+}
+
+pub fn main(args: &[String]) -> Result<ExitCode, String> {
+    let args = Args::parse(args, &["--from", "--to"], &[])?;
+
+    let [path] = args.positional[..] else {
+        return Err(format!(
+            "usage: turing convert <file> --to <format> [--from <format>]\nformats: {FORMATS}"
+        ));
+    };
+
+    let to = Format::from_name(args.flag("--to").ok_or("missing --to <format>")?)?;
+    let definition = load(path, args.flag("--from"))?;
+
+    print!("{}", to.write(&definition)?);
+
+    Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,270 @@
+use std::collections::{BTreeSet, VecDeque};
+use std::io::{self, BufRead, Write};
+use std::process::ExitCode;
+
+use turing::{Char, Machine, State, Symbol, TransitionTable};
+
+use crate::{convert, Args};
+
+/// How many configurations `back` can return to.
+const HISTORY_LIMIT: usize = 10_000;
+
+const HELP: &str = "\
+commands:
+    step [N]              take one or N steps
+    continue [N]          run until a breakpoint, halting or N steps
+    back [N]              undo one or N steps
+    break [state]         stop on entering <state>; lists breakpoints
+                          without an argument
+    delete <state>        remove the breakpoint on <state>
+    print tape|state|head|rule
+    set cell <i> <sym>    write <sym> to cell <i>, counted from the first
+                          input cell
+    set head <i>          move the head to cell <i>
+    set state <state>     switch to <state> (`halt` halts)
+    reset                 go back to the initial configuration
+    help
+    quit";
+
+pub fn main(args: &[String]) -> Result<ExitCode, String> {
+    let args = Args::parse(args, &["--from"], &[])?;
+
+    let (path, input) = match args.positional[..] {
+        [path] => (path, ""),
+        [path, input] => (path, input),
+        _ => return Err("usage: turing debug <file> [input] [--from <format>]".to_owned()),
+    };
+
+    let definition = convert::load(path, args.flag("--from"))?;
+    definition.check_input(input).map_err(|e| e.to_string())?;
+
+    let blank = definition.blank.unwrap_or_default();
+    let machine = Machine::with_input_blank(definition.start, input, blank)
+        .ok_or("invalid input")?
+        .resettable();
+    let mut debugger = Debugger {
+        table: definition.table,
+        machine,
+        history: VecDeque::new(),
+        breakpoints: BTreeSet::new(),
+        steps: 0,
+    };
+
+    println!("{}", debugger.position());
+
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+
+    loop {
+        print!("(turing) ");
+        io::stdout().flush().map_err(|e| format!("stdout: {e}"))?;
+
+        let Some(line) = lines.next() else {
+            break;
+        };
+        let line = line.map_err(|e| format!("stdin: {e}"))?;
+        let words: Vec<&str> = line.split_whitespace().collect();
+
+        match words[..] {
+            [] => continue,
+            ["quit" | "q"] => break,
+            _ => match debugger.command(&words) {
+                Ok(output) => println!("{output}"),
+                Err(message) => println!("error: {message}"),
+            },
+        }
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+struct Debugger {
+    table: TransitionTable<String, Char>,
+    machine: Machine<String, Char>,
+    history: VecDeque<(Machine<String, Char>, u64)>,
+    breakpoints: BTreeSet<String>,
+    steps: u64,
+}
+
+impl Debugger {
+    fn command(&mut self, words: &[&str]) -> Result<String, String> {
+        match *words {
+            ["step" | "s"] => self.step(1, false),
+            ["step" | "s", n] => self.step(count(n)?, false),
+            ["continue" | "c"] => self.step(u64::MAX, true),
+            ["continue" | "c", n] => self.step(count(n)?, true),
+            ["back" | "b"] => self.back(1),
+            ["back" | "b", n] => self.back(count(n)?),
+            ["break"] if self.breakpoints.is_empty() => Ok("no breakpoints".to_owned()),
+            ["break"] => Ok(self
+                .breakpoints
+                .iter()
+                .map(|state| format!("break {state}"))
+                .collect::<Vec<_>>()
+                .join("\n")),
+            ["break", state] => {
+                self.breakpoints.insert(state.to_owned());
+                Ok(format!("breakpoint on {state}"))
+            }
+            ["delete", state] => match self.breakpoints.remove(state) {
+                true => Ok(format!("deleted breakpoint on {state}")),
+                false => Err(format!("no breakpoint on {state}")),
+            },
+            ["print" | "p", "tape"] => Ok(self.tape()),
+            ["print" | "p", "state"] => Ok(self.state().to_owned()),
+            ["print" | "p", "head"] => {
+                Ok((self.machine.head() as isize - self.machine.origin() as isize).to_string())
+            }
+            ["print" | "p", "rule"] => Ok(self.rule()),
+            ["set", "cell", index, symbol] => {
+                let index = self.index(index)?;
+                let symbol = parse_symbol(symbol)?;
+
+                self.save();
+                self.machine.write_at(index, symbol);
+
+                Ok(self.tape())
+            }
+            ["set", "head", index] => {
+                let index = self.index(index)?;
+
+                self.save();
+                self.machine.set_head(index);
+
+                Ok(self.tape())
+            }
+            ["set", "state", state] => {
+                self.save();
+                self.machine.set_state(match state {
+                    "halt" => State::Halt,
+                    state => State::State(state.to_owned()),
+                });
+
+                Ok(self.position())
+            }
+            ["reset"] => {
+                self.save();
+                self.machine.reset();
+                self.steps = 0;
+
+                Ok(self.position())
+            }
+            ["help" | "h"] => Ok(HELP.to_owned()),
+            _ => Err("unknown command, try `help`".to_owned()),
+        }
+    }
+
+    // takes up to `limit` steps; if `stop_at_breakpoints`, stops on
+    // entering a state with a breakpoint
+    fn step(&mut self, limit: u64, stop_at_breakpoints: bool) -> Result<String, String> {
+        if self.machine.halted() {
+            return Err("the machine has halted".to_owned());
+        }
+
+        for _ in 0..limit {
+            if self.machine.halted() {
+                break;
+            }
+
+            self.save();
+            self.machine.execute_table(&self.table);
+            self.steps += 1;
+
+            if stop_at_breakpoints
+                && self
+                    .machine
+                    .state()
+                    .is_some_and(|state| self.breakpoints.contains(state))
+            {
+                return Ok(format!("breakpoint\n{}", self.position()));
+            }
+        }
+
+        Ok(self.position())
+    }
+
+    fn back(&mut self, count: u64) -> Result<String, String> {
+        for _ in 0..count {
+            let (machine, steps) = self
+                .history
+                .pop_back()
+                .ok_or("no earlier configuration recorded")?;
+
+            self.machine = machine;
+            self.steps = steps;
+        }
+
+        Ok(self.position())
+    }
+
+    fn save(&mut self) {
+        if self.history.len() == HISTORY_LIMIT {
+            self.history.pop_front();
+        }
+
+        self.history.push_back((self.machine.clone(), self.steps));
+    }
+
+    // converts a cell number relative to the first input cell to a tape index
+    fn index(&self, index: &str) -> Result<usize, String> {
+        let cell: isize = index
+            .parse()
+            .map_err(|_| format!("invalid cell {index:?}"))?;
+
+        cell.checked_add(self.machine.origin() as isize)
+            .and_then(|index| usize::try_from(index).ok())
+            .filter(|&index| index < self.machine.tape().len())
+            .ok_or_else(|| format!("cell {cell} has not been visited"))
+    }
+
+    fn state(&self) -> &str {
+        self.machine.state().map_or("halt", String::as_str)
+    }
+
+    fn position(&self) -> String {
+        format!("step {}: {}\n{}", self.steps, self.state(), self.tape())
+    }
+
+    // the tape with a caret under the head
+    fn tape(&self) -> String {
+        let tape: String = self.machine.peek().iter().map(Symbol::to_char).collect();
+
+        format!("{tape}\n{}^", " ".repeat(self.machine.head()))
+    }
+
+    fn rule(&self) -> String {
+        let Some(state) = self.machine.state() else {
+            return "the machine has halted".to_owned();
+        };
+        let read = self.machine.current_symbol();
+
+        match self.table.get(state, read) {
+            Some(rule) => {
+                let rule = TransitionTable::from_iter([(state.clone(), *read, rule.clone())]);
+                turing::dsl::write(&rule).trim_end().to_owned()
+            }
+            None => format!(
+                "no rule for {state} reading {:?}; the machine halts",
+                read.to_char()
+            ),
+        }
+    }
+}
+
+fn count(n: &str) -> Result<u64, String> {
+    n.parse().map_err(|_| format!("invalid count {n:?}"))
+}
+
+// accepts both `1` and `'1'`
+pub fn parse_symbol(symbol: &str) -> Result<Char, String> {
+    let unquoted = symbol
+        .strip_prefix('\'')
+        .and_then(|symbol| symbol.strip_suffix('\''))
+        .unwrap_or(symbol);
+    let mut chars = unquoted.chars();
+
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Char::from_char(c).ok_or_else(|| format!("invalid symbol {symbol:?}")),
+        _ => Err(format!("invalid symbol {symbol:?}")),
+    }
+}
@@ -0,0 +1,395 @@
+use std::collections::BTreeSet;
+use std::process::ExitCode;
+
+use eframe::egui::{self, Color32, ColorImage, RichText, TextureHandle, TextureOptions};
+use turing::render::SpaceTime;
+use turing::{Char, Machine, Symbol, TransitionTable};
+
+use crate::debug::parse_symbol;
+use crate::{convert, Args};
+
+/// How many cells on each side of the head the tape view shows.
+const RADIUS: usize = 15;
+
+/// How many configurations the space-time view records.
+const SPACE_TIME_LIMIT: usize = 2_000;
+
+pub fn main(args: &[String]) -> Result<ExitCode, String> {
+    let args = Args::parse(args, &["--from"], &[])?;
+
+    let mut app = App {
+        from: args.flag("--from").map(str::to_owned),
+        ..App::default()
+    };
+
+    match args.positional[..] {
+        [] => {}
+        [path] => app.path = path.to_owned(),
+        [path, input] => {
+            app.path = path.to_owned();
+            app.input = input.to_owned();
+        }
+        _ => return Err("usage: turing gui [file] [input] [--from <format>]".to_owned()),
+    }
+
+    if !app.path.is_empty() {
+        app.load();
+    }
+
+    eframe::run_native(
+        "turing",
+        eframe::NativeOptions::default(),
+        Box::new(|_| Ok(Box::new(app))),
+    )
+    .map_err(|e| format!("gui: {e}"))?;
+
+    Ok(ExitCode::SUCCESS)
+}
+
+#[derive(Default)]
+struct App {
+    path: String,
+    input: String,
+    from: Option<String>,
+    session: Option<Session>,
+    breakpoints: BTreeSet<String>,
+    running: bool,
+    steps_per_frame: u32,
+    // the cell being edited, counted from the first input cell
+    selected: Option<isize>,
+    symbol: String,
+    message: String,
+    space_time: Option<TextureHandle>,
+}
+
+struct Session {
+    table: TransitionTable<String, Char>,
+    machine: Machine<String, Char>,
+    steps: u64,
+    space_time: SpaceTime<Char>,
+    // whether `space_time` changed since its texture was made
+    dirty: bool,
+}
+
+impl Session {
+    fn record(&mut self) {
+        if self.space_time.len() < SPACE_TIME_LIMIT {
+            self.space_time.record(&self.machine.snapshot());
+            self.dirty = true;
+        }
+    }
+}
+
+impl App {
+    fn load(&mut self) {
+        self.running = false;
+        self.selected = None;
+        self.steps_per_frame = self.steps_per_frame.max(1);
+
+        match self.session() {
+            Ok(session) => {
+                self.message = format!("loaded {}", self.path);
+                self.session = Some(session);
+            }
+            Err(message) => self.message = message,
+        }
+    }
+
+    fn session(&self) -> Result<Session, String> {
+        let definition = convert::load(&self.path, self.from.as_deref())?;
+        definition
+            .check_input(&self.input)
+            .map_err(|e| e.to_string())?;
+
+        let blank = definition.blank.unwrap_or_default();
+        let machine = Machine::with_input_blank(definition.start, &self.input, blank)
+            .ok_or("invalid input")?;
+
+        let mut session = Session {
+            table: definition.table,
+            machine: machine.resettable(),
+            steps: 0,
+            space_time: SpaceTime::new(),
+            dirty: true,
+        };
+        session.record();
+
+        Ok(session)
+    }
+
+    // takes up to `limit` steps, stopping on entering a state with a
+    // breakpoint
+    fn step(&mut self, limit: u32) {
+        let Some(session) = &mut self.session else {
+            return;
+        };
+
+        for _ in 0..limit {
+            if session.machine.halted() {
+                self.running = false;
+                self.message = "halted".to_owned();
+                return;
+            }
+
+            session.machine.execute_table(&session.table);
+            session.steps += 1;
+            session.record();
+
+            if let Some(state) = session.machine.state() {
+                if self.breakpoints.contains(state) {
+                    self.running = false;
+                    self.message = format!("breakpoint on {state}");
+                    return;
+                }
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        let Some(session) = &mut self.session else {
+            return;
+        };
+
+        session.machine.reset();
+        session.steps = 0;
+        session.space_time = SpaceTime::new();
+        session.record();
+
+        self.running = false;
+        self.message = "reset".to_owned();
+    }
+
+    // writes the edited symbol to the selected cell, growing the tape to it
+    fn write_selected(&mut self) {
+        let (Some(session), Some(position)) = (&mut self.session, self.selected) else {
+            return;
+        };
+        let symbol = match parse_symbol(&self.symbol) {
+            Ok(symbol) => symbol,
+            Err(message) => {
+                self.message = message;
+                return;
+            }
+        };
+
+        let machine = &mut session.machine;
+        let index = machine.origin() as isize + position;
+
+        if index < 0 {
+            machine.pad_left(index.unsigned_abs());
+        }
+
+        let index = (machine.origin() as isize + position) as usize;
+
+        if index >= machine.tape().len() {
+            machine.pad_right(index + 1 - machine.tape().len());
+        }
+
+        machine.write_at(index, symbol);
+        self.message = format!("wrote {} to cell {position}", symbol.to_char());
+    }
+
+    fn controls(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("file");
+            ui.text_edit_singleline(&mut self.path);
+            ui.label("input");
+            ui.text_edit_singleline(&mut self.input);
+
+            if ui.button("load").clicked() {
+                self.load();
+            }
+        });
+
+        ui.horizontal(|ui| {
+            let Some(session) = &self.session else {
+                ui.label(&self.message);
+                return;
+            };
+            let halted = session.machine.halted();
+
+            if ui.add_enabled(!halted, egui::Button::new("step")).clicked() {
+                self.step(1);
+            }
+
+            let run = match self.running {
+                true => "pause",
+                false => "run",
+            };
+
+            if ui.add_enabled(!halted, egui::Button::new(run)).clicked() {
+                self.running = !self.running;
+            }
+
+            if ui.button("reset").clicked() {
+                self.reset();
+            }
+
+            ui.add(egui::Slider::new(&mut self.steps_per_frame, 1..=1_000).text("steps/frame"));
+
+            if let Some(session) = &self.session {
+                let state = session.machine.state().map_or("halt", String::as_str);
+                ui.label(format!("step {}, state {state}", session.steps));
+            }
+
+            ui.label(&self.message);
+        });
+    }
+
+    // the rules with a breakpoint toggle per state
+    fn table(&mut self, ui: &mut egui::Ui) {
+        let Some(session) = &self.session else {
+            return;
+        };
+
+        ui.heading("rules");
+        ui.label("check a state to break on entering it");
+
+        let current = session.machine.state();
+        let read = session.machine.current_symbol();
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            egui::Grid::new("rules").striped(true).show(ui, |ui| {
+                let mut last = None;
+
+                for (state, symbol, rule) in session.table.iter() {
+                    if last != Some(state) {
+                        let mut checked = self.breakpoints.contains(state);
+
+                        if ui.checkbox(&mut checked, state.as_str()).changed() {
+                            match checked {
+                                true => self.breakpoints.insert(state.clone()),
+                                false => self.breakpoints.remove(state),
+                            };
+                        }
+
+                        last = Some(state);
+                    } else {
+                        ui.label("");
+                    }
+
+                    let single =
+                        TransitionTable::from_iter([(state.clone(), *symbol, rule.clone())]);
+                    let text = turing::dsl::write(&single).trim_end().to_owned();
+
+                    match current == Some(state) && read == symbol {
+                        true => ui.label(RichText::new(text).strong().color(Color32::ORANGE)),
+                        false => ui.label(text),
+                    };
+
+                    ui.end_row();
+                }
+            });
+        });
+    }
+
+    // the cells around the head; clicking one selects it for editing
+    fn tape(&mut self, ui: &mut egui::Ui) {
+        let Some(session) = &mut self.session else {
+            return;
+        };
+
+        let machine = &mut session.machine;
+        let head = machine.head() as isize - machine.origin() as isize;
+        let cells = machine.around_head(RADIUS);
+
+        ui.heading("tape");
+        ui.horizontal(|ui| {
+            for (offset, cell) in cells.iter().enumerate() {
+                let position = head - RADIUS as isize + offset as isize;
+                let mut text = RichText::new(cell.to_char().to_string())
+                    .monospace()
+                    .size(20.0);
+
+                if position == head {
+                    text = text.background_color(Color32::from_rgb(0xff, 0xdd, 0x66));
+                }
+
+                if ui
+                    .selectable_label(self.selected == Some(position), text)
+                    .clicked()
+                {
+                    self.selected = Some(position);
+                    self.symbol = cell.to_char().to_string();
+                }
+            }
+        });
+
+        let Some(position) = self.selected else {
+            ui.label("click a cell to edit it");
+            return;
+        };
+
+        let mut write = false;
+
+        ui.horizontal(|ui| {
+            ui.label(format!("cell {position}"));
+            ui.add(egui::TextEdit::singleline(&mut self.symbol).desired_width(24.0));
+            write = ui.button("write").clicked();
+
+            if ui.button("move head here").clicked() && !machine.seek(position) {
+                self.message = format!("cell {position} has not been visited");
+            }
+        });
+
+        if write {
+            self.write_selected();
+        }
+    }
+
+    // one row per recorded configuration, aligned on cell 0
+    fn space_time(&mut self, ui: &mut egui::Ui) {
+        let Some(session) = &mut self.session else {
+            return;
+        };
+
+        ui.heading("space-time");
+
+        if session.dirty {
+            session.dirty = false;
+            self.space_time = pgm_image(&session.space_time.to_pgm()).map(|image| {
+                ui.ctx()
+                    .load_texture("space-time", image, TextureOptions::NEAREST)
+            });
+        }
+
+        if let Some(texture) = &self.space_time {
+            let scale = (ui.available_width() / texture.size_vec2().x).clamp(1.0, 8.0);
+
+            egui::ScrollArea::vertical()
+                .stick_to_bottom(true)
+                .show(ui, |ui| {
+                    ui.image((texture.id(), texture.size_vec2() * scale))
+                });
+        }
+    }
+}
+
+impl eframe::App for App {
+    fn ui(&mut self, ui: &mut egui::Ui, _frame: &mut eframe::Frame) {
+        if self.running {
+            self.step(self.steps_per_frame);
+            ui.ctx().request_repaint();
+        }
+
+        egui::Panel::top("controls").show(ui, |ui| self.controls(ui));
+        egui::Panel::left("rules").show(ui, |ui| self.table(ui));
+        egui::CentralPanel::default().show(ui, |ui| {
+            self.tape(ui);
+            ui.separator();
+            self.space_time(ui);
+        });
+    }
+}
+
+// the gray levels of a binary PGM image, as produced by `SpaceTime::to_pgm`
+fn pgm_image(pgm: &[u8]) -> Option<ColorImage> {
+    let mut fields = pgm.splitn(4, u8::is_ascii_whitespace);
+    let _magic = fields.next()?;
+    let width: usize = std::str::from_utf8(fields.next()?).ok()?.parse().ok()?;
+    let height: usize = std::str::from_utf8(fields.next()?).ok()?.parse().ok()?;
+    let pixels = fields.next()?.strip_prefix(b"255\n")?;
+
+    (width > 0 && height > 0 && pixels.len() == width * height)
+        .then(|| ColorImage::from_gray([width, height], pixels))
+}
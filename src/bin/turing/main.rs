@@ -0,0 +1,135 @@
+//! Command-line front end for running machine definitions written in the
+//! `turing::dsl` format.
+
+mod check;
+mod convert;
+mod debug;
+#[cfg(feature = "gui")]
+mod gui;
+mod run;
+mod search;
+#[cfg(feature = "server")]
+mod serve;
+mod watch;
+
+use std::env;
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+usage: turing <command> [args]
+
+commands:
+    run <file> [input] [--max-steps N] [--from <format>]
+            [--watch [--delay MS] [--image <file.pgm>]]
+            [--certificate <file.lean|file.v>]
+        run the machine in <file> on [input] (read from stdin if omitted or
+        `-`) and print the final tape, state and step count. --watch animates
+        the run in the terminal (space: pause, s: step while paused, e: edit
+        the tape while paused, +/-: speed, q: quit) and --image saves a
+        space-time diagram of it. --certificate writes a Lean or Coq proof
+        that the machine halts, or loops if it repeats a configuration
+        within the step limit
+    check <file> [--from <format>]
+        report unreachable states and missing rules in <file>; exits with 1
+        if there are any and with 2 if <file> is invalid
+    convert <file> --to <format> [--from <format>]
+        print the machine in <file> in another format: dsl, yaml, jflap,
+        bbchallenge (.bb) or dot (export only). the input format is guessed
+        from the extension unless given
+    debug <file> [input] [--from <format>]
+        step through the machine in <file> interactively; type `help` at the
+        prompt for the list of commands
+    gui [file] [input] [--from <format>]
+        open the machine in <file> in a graphical debugger with breakpoints,
+        tape editing and a space-time view (needs the `gui` feature)
+    serve [--addr <host:port>]
+        serve the HTTP API of `turing::server` (needs the `server` feature)
+    search <states> <symbols> [--max-steps N] [--unit-size N]
+            [--addr <host:port>] [--lease SECONDS] [--log <file>]
+        run every table of a size from a blank tape on the workers connecting
+        to <host:port> (0.0.0.0:4000 by default) and print the champion and
+        how many tables halt, run forever or are undecided. units not done
+        within the lease are handed out again, and --log keeps the results so
+        that a search started again with the same log resumes
+    work <host:port> [--name <name>]
+        run units for the search coordinated at <host:port>
+
+machine files of every command may be in any supported input format";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let result = match args.first().map(String::as_str) {
+        Some("run") => run::main(&args[1..]),
+        Some("check") => check::main(&args[1..]),
+        Some("convert") => convert::main(&args[1..]),
+        Some("debug") => debug::main(&args[1..]),
+        #[cfg(feature = "gui")]
+        Some("gui") => gui::main(&args[1..]),
+        #[cfg(feature = "server")]
+        Some("serve") => serve::main(&args[1..]),
+        Some("search") => search::main(&args[1..]),
+        Some("work") => search::work(&args[1..]),
+        Some("help" | "--help" | "-h") => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        _ => Err(USAGE.to_owned()),
+    };
+
+    match result {
+        Ok(code) => code,
+        Err(message) => {
+            eprintln!("{message}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+/// Command arguments split into positional arguments, `--flag value` pairs
+/// and bare `--switch`es.
+struct Args<'a> {
+    positional: Vec<&'a str>,
+    flags: Vec<(&'a str, &'a str)>,
+    switches: Vec<&'a str>,
+}
+
+impl<'a> Args<'a> {
+    fn parse(args: &'a [String], flags: &[&str], switches: &[&str]) -> Result<Self, String> {
+        let mut parsed = Self {
+            positional: Vec::new(),
+            flags: Vec::new(),
+            switches: Vec::new(),
+        };
+        let mut args = args.iter();
+
+        while let Some(arg) = args.next() {
+            if !arg.starts_with("--") {
+                parsed.positional.push(arg.as_str());
+            } else if switches.contains(&arg.as_str()) {
+                parsed.switches.push(arg.as_str());
+            } else if flags.contains(&arg.as_str()) {
+                let value = args
+                    .next()
+                    .ok_or_else(|| format!("missing value for {arg}"))?;
+                parsed.flags.push((arg.as_str(), value.as_str()));
+            } else {
+                return Err(format!("unknown flag {arg}"));
+            }
+        }
+
+        Ok(parsed)
+    }
+
+    /// The value of the last occurrence of `flag`.
+    fn flag(&self, flag: &str) -> Option<&'a str> {
+        self.flags
+            .iter()
+            .rev()
+            .find_map(|&(name, value)| (name == flag).then_some(value))
+    }
+
+    fn switch(&self, switch: &str) -> bool {
+        self.switches.contains(&switch)
+    }
+}
@@ -0,0 +1,153 @@
+use std::fs;
+use std::io::{self, Read};
+use std::path::Path;
+use std::process::ExitCode;
+use std::time::Duration;
+
+use turing::dsl::Definition;
+use turing::proof::{Assistant, Certificate};
+use turing::{Char, Machine, StopReason, Symbol};
+
+use crate::{convert, watch, Args};
+
+const DEFAULT_MAX_STEPS: u64 = 1_000_000;
+
+pub fn main(args: &[String]) -> Result<ExitCode, String> {
+    let args = Args::parse(
+        args,
+        &[
+            "--max-steps",
+            "--from",
+            "--delay",
+            "--image",
+            "--certificate",
+        ],
+        &["--watch"],
+    )?;
+
+    let (path, input) = match args.positional[..] {
+        [path] => (path, None),
+        [path, input] => (path, Some(input)),
+        _ => return Err("usage: turing run <file> [input] [--max-steps N] [--from <format>] [--watch [--delay MS] [--image <file.pgm>]] [--certificate <file.lean|file.v>]".to_owned()),
+    };
+
+    let max_steps = match args.flag("--max-steps") {
+        Some(value) => value
+            .parse()
+            .map_err(|_| format!("invalid step limit {value:?}"))?,
+        None => DEFAULT_MAX_STEPS,
+    };
+
+    let definition = convert::load(path, args.flag("--from"))?;
+
+    let input = match input {
+        Some("-") | None => {
+            let mut input = String::new();
+            io::stdin()
+                .read_to_string(&mut input)
+                .map_err(|e| format!("stdin: {e}"))?;
+            input.trim_end_matches(['\n', '\r']).to_owned()
+        }
+        Some(input) => input.to_owned(),
+    };
+
+    let mut machine = start(&definition, &input)?;
+
+    let certificate = match args.flag("--certificate") {
+        Some(path) => {
+            let assistant = match Path::new(path).extension().and_then(|e| e.to_str()) {
+                Some("lean") => Assistant::Lean,
+                Some("v") => Assistant::Coq,
+                _ => return Err(format!("{path}: certificates are .lean or .v files")),
+            };
+            Some((path, assistant, machine.clone()))
+        }
+        None => None,
+    };
+
+    let stats = match args.switch("--watch") {
+        true => {
+            let delay = match args.flag("--delay") {
+                Some(value) => value
+                    .parse()
+                    .map_err(|_| format!("invalid delay {value:?}"))?,
+                None => watch::DEFAULT_DELAY_MS,
+            };
+
+            watch::run(
+                &mut machine,
+                &definition.table,
+                max_steps,
+                Duration::from_millis(delay),
+                args.flag("--image"),
+            )
+            .map_err(|e| format!("terminal: {e}"))?
+        }
+        false => machine.run_table(&definition.table, max_steps),
+    };
+
+    let state = machine
+        .state()
+        .cloned()
+        .unwrap_or_else(|| "halt".to_owned());
+    let (tape, _, _) = machine.finish_trimmed();
+    let tape: String = tape.iter().map(Symbol::to_char).collect();
+
+    println!("tape: {tape}");
+    println!("state: {state}");
+    println!("steps: {}", stats.steps);
+
+    if let Some((path, assistant, initial)) = certificate {
+        let certificate = Certificate::find(&definition.table, &initial, max_steps)
+            .ok_or("no certificate: neither halted nor repeated a configuration")?;
+
+        fs::write(path, certificate.script(assistant)).map_err(|e| format!("{path}: {e}"))?;
+    }
+
+    match stats.stop {
+        StopReason::Halted => Ok(ExitCode::SUCCESS),
+        StopReason::StepLimit => {
+            eprintln!("step limit of {max_steps} reached");
+            Ok(ExitCode::from(1))
+        }
+        StopReason::Cancelled => {
+            eprintln!("stopped");
+            Ok(ExitCode::from(1))
+        }
+        StopReason::NoRule => {
+            eprintln!("no rule for the configuration");
+            Ok(ExitCode::from(1))
+        }
+    }
+}
+
+// the machine running `definition` on `input`, its tape growing by the
+// definition's blank
+fn start(definition: &Definition<Char>, input: &str) -> Result<Machine<String, Char>, String> {
+    definition.check_input(input).map_err(|e| e.to_string())?;
+
+    let blank = definition.blank.unwrap_or_default();
+    Machine::with_input_blank(definition.start.clone(), input, blank)
+        .ok_or_else(|| "invalid input".to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use turing::dsl;
+
+    use super::*;
+
+    #[test]
+    fn start_test() {
+        // an empty input starts on the definition's blank
+        let definition = dsl::parse_definition("blank 0\na 0 1 R b\nb 0 1 N halt").unwrap();
+        let mut machine = start(&definition, "").unwrap();
+        let stats = machine.run_table(&definition.table, 10);
+
+        let (tape, _, _) = machine.finish_trimmed();
+        let tape: String = tape.iter().map(Symbol::to_char).collect();
+
+        assert_eq!(stats.steps, 2);
+        assert_eq!(tape, "11");
+    }
+}
@@ -0,0 +1,114 @@
+use std::net::TcpListener;
+use std::process::ExitCode;
+use std::str::FromStr;
+use std::time::Duration;
+
+use turing::distributed::{partition, Coordinator, Worker, DEFAULT_LEASE_TIMEOUT};
+use turing::format::bbchallenge;
+use turing::format::number::{self, Number};
+
+use crate::Args;
+
+const DEFAULT_ADDR: &str = "0.0.0.0:4000";
+const DEFAULT_MAX_STEPS: u64 = 10_000;
+const DEFAULT_UNIT_SIZE: u128 = 100_000;
+
+/// `turing search <states> <symbols>`: coordinates a search.
+pub fn main(args: &[String]) -> Result<ExitCode, String> {
+    let args = Args::parse(
+        args,
+        &["--max-steps", "--unit-size", "--addr", "--lease", "--log"],
+        &[],
+    )?;
+
+    let [states, symbols] = args.positional[..] else {
+        return Err("usage: turing search <states> <symbols> [--max-steps N] [--unit-size N] [--addr <host:port>] [--lease SECONDS] [--log <file>]".to_owned());
+    };
+
+    let states: usize = parse(states, "state count")?;
+    let symbols: usize = parse(symbols, "symbol count")?;
+    let max_steps = flag(&args, "--max-steps", "step limit")?.unwrap_or(DEFAULT_MAX_STEPS);
+    let unit_size = flag(&args, "--unit-size", "unit size")?.unwrap_or(DEFAULT_UNIT_SIZE);
+    let lease =
+        flag(&args, "--lease", "lease timeout")?.map_or(DEFAULT_LEASE_TIMEOUT, Duration::from_secs);
+
+    if !(1..=25).contains(&states) || !(1..=10).contains(&symbols) {
+        return Err("tables have 1 to 25 states and 1 to 10 symbols".to_owned());
+    }
+
+    if unit_size == 0 {
+        return Err("units need at least one table".to_owned());
+    }
+
+    let units = partition(states, symbols, max_steps, unit_size)
+        .ok_or_else(|| format!("too many tables with {states} states and {symbols} symbols"))?;
+    let coordinator = match args.flag("--log") {
+        Some(path) => Coordinator::open(units, path).map_err(|e| format!("{path}: {e}"))?,
+        None => Coordinator::new(units),
+    };
+    let mut coordinator = coordinator.with_lease_timeout(lease);
+
+    let addr = args.flag("--addr").unwrap_or(DEFAULT_ADDR);
+    let listener = TcpListener::bind(addr).map_err(|e| format!("{addr}: {e}"))?;
+
+    eprintln!("listening on {addr}; {}", coordinator.progress());
+    coordinator
+        .serve(&listener, |progress| eprintln!("{progress}"))
+        .map_err(|e| format!("search: {e}"))?;
+
+    let summary = coordinator.summary();
+    let certificates = summary.certificates.len();
+
+    println!("tables: {}", summary.machines);
+    println!("halting: {}", summary.halted);
+    println!("running forever: {certificates}");
+    println!("undecided: {}", summary.undecided.len());
+
+    if let Some(champion) = summary.champion {
+        let table = number::decode::<u8>(&Number::from_u128(states, symbols, champion.number))
+            .and_then(|definition| bbchallenge::write(&definition).ok())
+            .unwrap_or_else(|| champion.number.to_string());
+
+        println!(
+            "champion: {table} ({} steps, sigma {})",
+            champion.steps, champion.sigma
+        );
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// `turing work <host:port>`: runs units for a search.
+pub fn work(args: &[String]) -> Result<ExitCode, String> {
+    let args = Args::parse(args, &["--name"], &[])?;
+
+    let [addr] = args.positional[..] else {
+        return Err("usage: turing work <host:port> [--name <name>]".to_owned());
+    };
+
+    let name = match args.flag("--name") {
+        Some(name) if name.is_empty() || name.contains(char::is_whitespace) => {
+            return Err(format!("invalid worker name {name:?}"))
+        }
+        Some(name) => name.to_owned(),
+        None => format!("worker-{}", std::process::id()),
+    };
+
+    let units = Worker::new(addr, name)
+        .run()
+        .map_err(|e| format!("{addr}: {e}"))?;
+
+    eprintln!("ran {units} units");
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn parse<T: FromStr>(value: &str, what: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid {what} {value:?}"))
+}
+
+fn flag<T: FromStr>(args: &Args, flag: &str, what: &str) -> Result<Option<T>, String> {
+    args.flag(flag).map(|value| parse(value, what)).transpose()
+}
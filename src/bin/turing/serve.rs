@@ -0,0 +1,27 @@
+use std::net::SocketAddr;
+use std::process::ExitCode;
+
+use crate::Args;
+
+const DEFAULT_ADDR: &str = "127.0.0.1:8080";
+
+pub fn main(args: &[String]) -> Result<ExitCode, String> {
+    let args = Args::parse(args, &["--addr"], &[])?;
+
+    if !args.positional.is_empty() {
+        return Err("usage: turing serve [--addr <host:port>]".to_owned());
+    }
+
+    let addr = args.flag("--addr").unwrap_or(DEFAULT_ADDR);
+    let addr: SocketAddr = addr
+        .parse()
+        .map_err(|_| format!("invalid address {addr:?}"))?;
+    let runtime = tokio::runtime::Runtime::new().map_err(|e| format!("runtime: {e}"))?;
+
+    eprintln!("listening on http://{addr}");
+    runtime
+        .block_on(turing::server::serve(addr))
+        .map_err(|e| format!("{addr}: {e}"))?;
+
+    Ok(ExitCode::SUCCESS)
+}
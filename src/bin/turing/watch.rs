@@ -0,0 +1,150 @@
+use std::fs;
+use std::io::{self, Write};
+use std::time::Duration;
+
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::{cursor, execute, terminal};
+use turing::render::{self, SpaceTime};
+use turing::{Char, Machine, RunStats, StopReason, Symbol, TransitionTable};
+
+pub const DEFAULT_DELAY_MS: u64 = 100;
+
+/// Animates a run in the terminal until the machine halts, the step limit is
+/// reached or the user quits, optionally saving a space-time diagram. While
+/// paused, the tape can be edited before resuming.
+pub fn run(
+    machine: &mut Machine<String, Char>,
+    table: &TransitionTable<String, Char>,
+    max_steps: u64,
+    mut delay: Duration,
+    image: Option<&str>,
+) -> io::Result<RunStats> {
+    let mut stdout = io::stdout();
+    let mut diagram = image.map(|_| SpaceTime::new());
+    let mut paused = false;
+    // the cell being edited, counted from the first input cell
+    let mut cursor: Option<isize> = None;
+    let mut steps = 0;
+
+    terminal::enable_raw_mode()?;
+    execute!(stdout, terminal::EnterAlternateScreen, cursor::Hide)?;
+
+    let stop = loop {
+        let snapshot = machine.snapshot();
+
+        if let Some(diagram) = &mut diagram {
+            diagram.record(&snapshot);
+        }
+
+        let (width, _) = terminal::size()?;
+        let state = machine.state().map_or("halt", String::as_str);
+
+        execute!(
+            stdout,
+            cursor::MoveTo(0, 0),
+            terminal::Clear(terminal::ClearType::All)
+        )?;
+        write!(
+            stdout,
+            "{}\r\nstate: {state}\r\nstep: {steps}\r\ndelay: {}ms{}\r\n\r\n",
+            render::ansi(&snapshot, width as usize),
+            delay.as_millis(),
+            if paused { " (paused)" } else { "" },
+        )?;
+
+        match cursor.and_then(|position| index(machine, position)) {
+            Some(index) => write!(
+                stdout,
+                "editing cell {}: {}\r\n\
+                 left/right: move, a symbol: write it, enter: done\r\n",
+                cursor.unwrap_or_default(),
+                machine.tape()[index].to_char(),
+            )?,
+            None => write!(
+                stdout,
+                "space: pause, s: step, e: edit the tape while paused, +/-: speed, q: quit\r\n"
+            )?,
+        }
+        stdout.flush()?;
+
+        if machine.halted() {
+            break StopReason::Halted;
+        }
+
+        if steps == max_steps {
+            break StopReason::StepLimit;
+        }
+
+        // wait for a key press, or just for the delay while playing
+        let mut step = !paused;
+
+        if event::poll(if paused { Duration::MAX } else { delay })? {
+            if let Event::Key(key) = event::read()? {
+                if key.kind != KeyEventKind::Release {
+                    step = false;
+
+                    if let Some(position) = cursor {
+                        cursor = edit(machine, position, key.code);
+                        continue;
+                    }
+
+                    match key.code {
+                        KeyCode::Char('q') | KeyCode::Esc => break StopReason::Cancelled,
+                        KeyCode::Char(' ') => paused = !paused,
+                        KeyCode::Char('s') | KeyCode::Right => step = paused,
+                        KeyCode::Char('e') if paused => {
+                            cursor = Some(machine.head() as isize - machine.origin() as isize);
+                        }
+                        KeyCode::Char('+') => delay /= 2,
+                        KeyCode::Char('-') => delay = (delay * 2).max(Duration::from_millis(1)),
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        if step {
+            machine.execute_table(table);
+            steps += 1;
+        }
+    };
+
+    execute!(stdout, cursor::Show, terminal::LeaveAlternateScreen)?;
+    terminal::disable_raw_mode()?;
+
+    if let (Some(path), Some(diagram)) = (image, diagram) {
+        fs::write(path, diagram.to_pgm())?;
+    }
+
+    Ok(RunStats {
+        steps,
+        tape_len: machine.tape().len(),
+        stop,
+    })
+}
+
+// the tape index of the cell at `position`, if the tape reaches it
+fn index(machine: &Machine<String, Char>, position: isize) -> Option<usize> {
+    machine
+        .origin()
+        .checked_add_signed(position)
+        .filter(|&index| index < machine.tape().len())
+}
+
+// handles a key pressed while editing the cell at `position`, returning the
+// cell edited next or `None` when done
+fn edit(machine: &mut Machine<String, Char>, position: isize, key: KeyCode) -> Option<isize> {
+    match key {
+        KeyCode::Enter | KeyCode::Esc => None,
+        KeyCode::Left if index(machine, position - 1).is_some() => Some(position - 1),
+        KeyCode::Right if index(machine, position + 1).is_some() => Some(position + 1),
+        KeyCode::Char(c) => {
+            if let (Some(index), Some(symbol)) = (index(machine, position), Char::from_char(c)) {
+                machine.write_at(index, symbol);
+            }
+
+            Some(position)
+        }
+        _ => Some(position),
+    }
+}
@@ -0,0 +1,418 @@
+//! Bounded model checking: whether a bad configuration is reachable within a
+//! number of steps, over every choice of a nondeterministic machine and
+//! every one of a set of starting configurations.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use crate::{Machine, Rule, State};
+
+/// What [`check_bounded`] explored without finding a bad configuration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Explored {
+    /// The distinct configurations reachable within the bound.
+    pub configurations: usize,
+    /// Whether that's every reachable configuration, i.e. none is bad at any
+    /// depth.
+    pub exhaustive: bool,
+}
+
+/// A path to a bad configuration: the configuration it starts from and the
+/// rules applied to get from there to `end`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Witness<S, Sym: Default> {
+    pub start: Machine<S, Sym>,
+    pub rules: Vec<Rule<S, Sym>>,
+    pub end: Machine<S, Sym>,
+}
+
+// every configuration seen, with the one it was reached from and how
+type Seen<S, Sym> = Vec<(Machine<S, Sym>, Option<(usize, Rule<S, Sym>)>)>;
+
+/// Explores breadth first every configuration reachable from `starts` in at
+/// most `depth` steps, returning a shortest path to one for which `bad`
+/// holds, if any. `rules` gives the rules that may apply in a state reading
+/// a symbol: every rule of an [`NdExecutor`](crate::NdExecutor)'s, or the
+/// single one of a table's.
+///
+/// Configurations are told apart as machines are compared, ignoring blank
+/// cells and where on the tape they are, so the same configuration reached
+/// twice is explored once and `bad` should judge alike.
+pub fn check_bounded<S, Sym, R>(
+    starts: impl IntoIterator<Item = Machine<S, Sym>>,
+    depth: u64,
+    mut rules: impl FnMut(&S, &Sym) -> R,
+    bad: impl Fn(&Machine<S, Sym>) -> bool,
+) -> Result<Explored, Box<Witness<S, Sym>>>
+where
+    S: Clone + PartialEq,
+    Sym: Clone + Default + PartialEq,
+    R: IntoIterator<Item = Rule<S, Sym>>,
+{
+    let mut seen: Seen<S, Sym> = Vec::new();
+    let mut frontier = Vec::new();
+
+    for start in starts {
+        if !seen.iter().any(|(machine, _)| *machine == start) {
+            frontier.push(seen.len());
+            seen.push((start, None));
+        }
+    }
+
+    for level in 0.. {
+        if let Some(&index) = frontier.iter().find(|&&index| bad(&seen[index].0)) {
+            return Err(Box::new(witness(seen, index)));
+        }
+
+        let mut next = Vec::new();
+
+        for index in frontier {
+            let machine = seen[index].0.clone();
+            let State::State(state) = &machine.state else {
+                continue;
+            };
+
+            for rule in rules(state, machine.current_symbol()) {
+                let mut child = machine.clone();
+                child.apply(rule.clone());
+
+                if !seen.iter().any(|(machine, _)| *machine == child) {
+                    next.push(seen.len());
+                    seen.push((child, Some((index, rule))));
+                }
+            }
+        }
+
+        if next.is_empty() || level == depth {
+            return Ok(Explored {
+                configurations: seen.len() - next.len(),
+                exhaustive: next.is_empty(),
+            });
+        }
+
+        frontier = next;
+    }
+
+    unreachable!("the loop returns at the depth")
+}
+
+// a configuration explored by `check_bounded_parallel`, numbered by the
+// worker whose arena holds it and its index there
+#[cfg(feature = "std")]
+type Id = (usize, usize);
+// the state, the non-blank cells and the head relative to the first, telling
+// configurations apart like `Machine`'s `PartialEq`
+#[cfg(feature = "std")]
+type Key<S, Sym> = (Option<S>, Vec<Sym>, isize);
+// the configurations a worker explored, with the one each was reached from
+// and how
+#[cfg(feature = "std")]
+type Arena<S, Sym> = Vec<(Machine<S, Sym>, Option<(Id, Rule<S, Sym>)>)>;
+
+/// Like [`check_bounded`], spreading the search over `threads` worker threads
+/// (all available cores if zero). Each worker explores depth first from its
+/// own deque and steals from the others' when it runs dry, keeping what it
+/// explored in an arena of its own.
+///
+/// The configurations found are the same, but the path to a bad one needn't
+/// be a shortest one, and which one is found can vary from run to run.
+#[cfg(feature = "std")]
+pub fn check_bounded_parallel<S, Sym, R>(
+    starts: impl IntoIterator<Item = Machine<S, Sym>>,
+    depth: u64,
+    rules: impl Fn(&S, &Sym) -> R + Sync,
+    bad: impl Fn(&Machine<S, Sym>) -> bool + Sync,
+    threads: usize,
+) -> Result<Explored, Box<Witness<S, Sym>>>
+where
+    S: Ord + Clone + Send + Sync,
+    Sym: Ord + Clone + Default + Send + Sync,
+    R: IntoIterator<Item = Rule<S, Sym>>,
+{
+    use std::collections::{BTreeMap, VecDeque};
+    use std::num::NonZeroUsize;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::Mutex;
+    use std::thread;
+
+    // work is a configuration, its depth and where it came from
+    type Work<S, Sym> = (Machine<S, Sym>, u64, Option<(Id, Rule<S, Sym>)>);
+
+    let threads = match threads {
+        0 => thread::available_parallelism().map_or(1, NonZeroUsize::get),
+        threads => threads,
+    };
+
+    // the shallowest depth each configuration was reached at; one reached
+    // again by a shorter path is explored again from there
+    let seen: Mutex<BTreeMap<Key<S, Sym>, u64>> = Mutex::new(BTreeMap::new());
+    let deques: Vec<Mutex<VecDeque<Work<S, Sym>>>> =
+        (0..threads).map(|_| Mutex::new(VecDeque::new())).collect();
+    // work queued or being explored, so idle workers know when to stop
+    let pending = AtomicUsize::new(0);
+    let truncated = AtomicBool::new(false);
+    let found: Mutex<Option<Id>> = Mutex::new(None);
+
+    let reached = |machine: &Machine<S, Sym>, depth: u64| {
+        let mut seen = seen.lock().unwrap();
+        let key = key(machine);
+
+        match seen.get(&key) {
+            Some(&shallowest) if shallowest <= depth => false,
+            _ => {
+                seen.insert(key, depth);
+                true
+            }
+        }
+    };
+
+    for (index, start) in starts.into_iter().enumerate() {
+        if reached(&start, 0) {
+            pending.fetch_add(1, Ordering::SeqCst);
+            deques[index % threads]
+                .lock()
+                .unwrap()
+                .push_back((start, 0, None));
+        }
+    }
+
+    let arenas: Vec<Arena<S, Sym>> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|worker| {
+                let (deques, pending, truncated, found) = (&deques, &pending, &truncated, &found);
+                let (seen, rules, bad, reached) = (&seen, &rules, &bad, &reached);
+
+                scope.spawn(move || {
+                    let mut arena: Arena<S, Sym> = Vec::new();
+
+                    loop {
+                        if found.lock().unwrap().is_some() {
+                            break;
+                        }
+
+                        // the newest of its own work, or the oldest of another's
+                        let work = deques[worker].lock().unwrap().pop_back().or_else(|| {
+                            (1..threads).find_map(|offset| {
+                                deques[(worker + offset) % threads]
+                                    .lock()
+                                    .unwrap()
+                                    .pop_front()
+                            })
+                        });
+
+                        let Some((machine, level, parent)) = work else {
+                            if pending.load(Ordering::SeqCst) == 0 {
+                                break;
+                            }
+
+                            thread::yield_now();
+                            continue;
+                        };
+
+                        let id = (worker, arena.len());
+
+                        if bad(&machine) {
+                            arena.push((machine, parent));
+                            found.lock().unwrap().get_or_insert(id);
+                            pending.fetch_sub(1, Ordering::SeqCst);
+                            break;
+                        }
+
+                        if let State::State(state) = &machine.state {
+                            for rule in rules(state, machine.current_symbol()) {
+                                let mut child = machine.clone();
+                                child.apply(rule.clone());
+
+                                if level == depth {
+                                    if !seen.lock().unwrap().contains_key(&key(&child)) {
+                                        truncated.store(true, Ordering::SeqCst);
+                                    }
+                                } else if reached(&child, level + 1) {
+                                    pending.fetch_add(1, Ordering::SeqCst);
+                                    deques[worker].lock().unwrap().push_back((
+                                        child,
+                                        level + 1,
+                                        Some((id, rule)),
+                                    ));
+                                }
+                            }
+                        }
+
+                        arena.push((machine, parent));
+                        pending.fetch_sub(1, Ordering::SeqCst);
+                    }
+
+                    arena
+                })
+            })
+            .collect();
+
+        workers
+            .into_iter()
+            .map(|worker| worker.join().unwrap())
+            .collect()
+    });
+
+    let Some(found) = found.into_inner().unwrap() else {
+        return Ok(Explored {
+            configurations: seen.into_inner().unwrap().len(),
+            exhaustive: !truncated.into_inner(),
+        });
+    };
+
+    let mut arenas = arenas;
+    let mut rules = Vec::new();
+    let mut current = found;
+
+    while let Some((parent, rule)) = arenas[current.0][current.1].1.take() {
+        rules.push(rule);
+        current = parent;
+    }
+
+    rules.reverse();
+
+    let start = arenas[current.0][current.1].0.clone();
+    let end = arenas[found.0].swap_remove(found.1).0;
+
+    Err(Box::new(Witness { start, rules, end }))
+}
+
+#[cfg(feature = "std")]
+fn key<S, Sym>(machine: &Machine<S, Sym>) -> Key<S, Sym>
+where
+    S: Clone,
+    Sym: Clone + Default + PartialEq,
+{
+    let state = match &machine.state {
+        State::State(state) => Some(state.clone()),
+        State::Halt => None,
+    };
+    let blank = &machine.blank;
+
+    let Some(first) = machine.tape.iter().position(|cell| cell != blank) else {
+        return (state, Vec::new(), 0);
+    };
+    let last = machine.tape.iter().rposition(|cell| cell != blank).unwrap();
+    let cells = machine.tape.range(first..=last).cloned().collect();
+
+    (state, cells, machine.head as isize - first as isize)
+}
+
+fn witness<S, Sym>(mut seen: Seen<S, Sym>, index: usize) -> Witness<S, Sym>
+where
+    S: Clone,
+    Sym: Clone + Default,
+{
+    let mut rules = Vec::new();
+    let mut current = index;
+
+    while let Some((parent, rule)) = seen[current].1.take() {
+        rules.push(rule);
+        current = parent;
+    }
+
+    rules.reverse();
+
+    let start = seen[current].0.clone();
+    let end = seen.swap_remove(index).0;
+
+    Witness { start, rules, end }
+}
+
+#[cfg(test)]
+mod tests {
+    use smallvec::{smallvec, SmallVec};
+
+    use super::*;
+    use crate::{NdExecutor, TransitionTable};
+
+    struct Scribble;
+
+    // writes a zero or a one and moves right, forever
+    impl NdExecutor<u8, bool> for Scribble {
+        fn execute(_state: &u8, _symbol: &bool) -> SmallVec<[Rule<u8, bool>; 2]> {
+            smallvec![
+                Rule::new().write(false).right(),
+                Rule::new().write(true).right()
+            ]
+        }
+    }
+
+    #[test]
+    fn check_bounded_test() {
+        let start: Machine<u8, bool> = Machine::new(0, [false].into());
+        let three_ones =
+            |machine: &Machine<u8, bool>| machine.tape().iter().filter(|&&c| c).count() == 3;
+
+        // writing a zero just moves the head over blanks, so two steps
+        // reach four configurations
+        assert_eq!(
+            check_bounded([start.clone()], 2, Scribble::execute, three_ones),
+            Ok(Explored {
+                configurations: 4,
+                exhaustive: false
+            })
+        );
+
+        let witness = check_bounded([start], 3, Scribble::execute, three_ones).unwrap_err();
+
+        assert_eq!(witness.rules.len(), 3);
+        assert_eq!(witness.end.tape_to_vec(), [true, true, true, false]);
+
+        // over inputs of two cells with at most a single one: writes ones
+        // over zeros and halts on the first one, which must not leave two
+        let table: TransitionTable<u8, bool> = [
+            (0, false, Rule::new().write(true).right()),
+            (0, true, Rule::halt()),
+        ]
+        .into_iter()
+        .collect();
+        let inputs = [[false, false], [false, true], [true, false]]
+            .map(|input| Machine::new(0, input.into()));
+        let witness = check_bounded(
+            inputs,
+            10,
+            |state, symbol| table.lookup(state, symbol),
+            |machine| machine.halted() && machine.tape().iter().all(|&c| c),
+        )
+        .unwrap_err();
+
+        assert_eq!(witness.start.tape_to_vec(), [false, true]);
+        assert_eq!(
+            witness.rules,
+            [Rule::new().write(true).right(), Rule::halt()]
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn check_bounded_parallel_test() {
+        let start: Machine<u8, bool> = Machine::new(0, [false].into());
+        let ones = |count| {
+            move |machine: &Machine<u8, bool>| {
+                machine.tape().iter().filter(|&&c| c).count() == count
+            }
+        };
+
+        for depth in 0..6 {
+            assert_eq!(
+                check_bounded_parallel([start.clone()], depth, Scribble::execute, ones(9), 4),
+                check_bounded([start.clone()], depth, Scribble::execute, ones(9))
+            );
+        }
+
+        let witness =
+            check_bounded_parallel([start], 5, Scribble::execute, ones(4), 4).unwrap_err();
+
+        assert!(ones(4)(&witness.end));
+        assert!(witness.rules.len() <= 5);
+
+        let mut end = witness.start.clone();
+
+        for rule in witness.rules {
+            end.apply(rule);
+        }
+
+        assert_eq!(end, witness.end);
+    }
+}
@@ -0,0 +1,224 @@
+use alloc::collections::BTreeSet;
+use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt::{self, Debug, Display};
+
+use crate::analysis::alphabet;
+use crate::{MissingRule, Rule, State, TransitionTable};
+
+/// Why a [`TransitionTableBuilder`] refused a rule or a table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TableError<S, Sym> {
+    /// A rule is in, or goes to, a state that wasn't declared.
+    UnknownState(S),
+    /// A rule reads or writes a symbol that wasn't declared.
+    UnknownSymbol(Sym),
+    /// There already is a different rule for reading `symbol` in `state`.
+    Conflict { state: S, symbol: Sym },
+    /// The `(state, symbol)` pairs without a rule, when the table has to be
+    /// complete.
+    Incomplete(Vec<(S, Sym)>),
+}
+
+impl<S: Debug, Sym: Debug> Display for TableError<S, Sym> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TableError::UnknownState(state) => write!(f, "unknown state {state:?}"),
+            TableError::UnknownSymbol(symbol) => write!(f, "unknown symbol {symbol:?}"),
+            TableError::Conflict { state, symbol } => {
+                write!(f, "conflicting rules for {symbol:?} in state {state:?}")
+            }
+            TableError::Incomplete(missing) => {
+                write!(f, "no rules for {} state and symbol pairs", missing.len())?;
+
+                if let Some((state, symbol)) = missing.first() {
+                    write!(f, ", e.g. {symbol:?} in state {state:?}")?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+impl<S: Debug, Sym: Debug> Error for TableError<S, Sym> {}
+
+/// Builds a [`TransitionTable`], checking every rule as it's added rather
+/// than leaving mistakes to show up in the middle of a run.
+#[derive(Debug, Clone)]
+pub struct TransitionTableBuilder<S, Sym> {
+    states: Option<BTreeSet<S>>,
+    symbols: Option<BTreeSet<Sym>>,
+    complete: bool,
+    table: TransitionTable<S, Sym>,
+}
+
+impl<S, Sym> TransitionTableBuilder<S, Sym>
+where
+    S: Ord + Clone,
+    Sym: Ord + Clone,
+{
+    pub fn new() -> Self {
+        Self {
+            states: None,
+            symbols: None,
+            complete: false,
+            table: TransitionTable::new(),
+        }
+    }
+
+    /// Declares the states rules may use; any state goes if none are
+    /// declared.
+    pub fn states(self, states: impl IntoIterator<Item = S>) -> Self {
+        Self {
+            states: Some(states.into_iter().collect()),
+            ..self
+        }
+    }
+
+    /// Declares the symbols rules may read and write; any symbol goes if
+    /// none are declared.
+    pub fn symbols(self, symbols: impl IntoIterator<Item = Sym>) -> Self {
+        Self {
+            symbols: Some(symbols.into_iter().collect()),
+            ..self
+        }
+    }
+
+    /// Makes [`TransitionTableBuilder::build`] fail unless every declared
+    /// state has a rule for every declared symbol. Without declarations, the
+    /// states and symbols the rules use count instead.
+    pub fn complete(self) -> Self {
+        Self {
+            complete: true,
+            ..self
+        }
+    }
+
+    pub fn missing(mut self, missing: MissingRule<S, Sym>) -> Self {
+        self.table.set_missing(missing);
+        self
+    }
+
+    /// Adds the rule for reading `symbol` in `state`. Adding the same rule
+    /// twice is fine, a different one for the same pair is a conflict.
+    pub fn rule(
+        mut self,
+        state: S,
+        symbol: Sym,
+        rule: Rule<S, Sym>,
+    ) -> Result<Self, TableError<S, Sym>> {
+        let next = match &rule.new_state {
+            Some(State::State(next)) => Some(next),
+            _ => None,
+        };
+
+        for state in [Some(&state), next].into_iter().flatten() {
+            if self
+                .states
+                .as_ref()
+                .is_some_and(|states| !states.contains(state))
+            {
+                return Err(TableError::UnknownState(state.clone()));
+            }
+        }
+
+        for symbol in [Some(&symbol), rule.write.as_ref()].into_iter().flatten() {
+            if self
+                .symbols
+                .as_ref()
+                .is_some_and(|symbols| !symbols.contains(symbol))
+            {
+                return Err(TableError::UnknownSymbol(symbol.clone()));
+            }
+        }
+
+        match self.table.get(&state, &symbol) {
+            Some(existing) if *existing != rule => Err(TableError::Conflict { state, symbol }),
+            Some(_) => Ok(self),
+            None => {
+                self.table.insert(state, symbol, rule);
+                Ok(self)
+            }
+        }
+    }
+
+    /// The table, or the pairs it has no rules for if it has to be complete.
+    pub fn build(self) -> Result<TransitionTable<S, Sym>, TableError<S, Sym>> {
+        if !self.complete {
+            return Ok(self.table);
+        }
+
+        let states = self.states.unwrap_or_else(|| {
+            self.table
+                .iter()
+                .flat_map(|(state, _, rule)| match &rule.new_state {
+                    Some(State::State(next)) => [Some(state), Some(next)],
+                    _ => [Some(state), None],
+                })
+                .flatten()
+                .cloned()
+                .collect()
+        });
+        let symbols = self
+            .symbols
+            .unwrap_or_else(|| alphabet(&self.table).into_iter().cloned().collect());
+
+        let missing: Vec<(S, Sym)> = states
+            .iter()
+            .flat_map(|state| symbols.iter().map(move |symbol| (state, symbol)))
+            .filter(|(state, symbol)| self.table.get(state, symbol).is_none())
+            .map(|(state, symbol)| (state.clone(), symbol.clone()))
+            .collect();
+
+        match missing.is_empty() {
+            true => Ok(self.table),
+            false => Err(TableError::Incomplete(missing)),
+        }
+    }
+}
+
+impl<S, Sym> Default for TransitionTableBuilder<S, Sym>
+where
+    S: Ord + Clone,
+    Sym: Ord + Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    #[test]
+    fn builder_test() {
+        let builder = TransitionTableBuilder::new()
+            .states([0u8, 1])
+            .symbols([false, true])
+            .rule(0, false, Rule::to(1).write(true).right())
+            .unwrap()
+            .rule(0, false, Rule::to(1).write(true).right())
+            .unwrap();
+
+        assert_eq!(
+            builder.clone().rule(0, false, Rule::halt()).unwrap_err(),
+            TableError::Conflict {
+                state: 0,
+                symbol: false
+            }
+        );
+        assert_eq!(
+            builder.clone().rule(2, true, Rule::halt()).unwrap_err(),
+            TableError::UnknownState(2)
+        );
+        assert_eq!(builder.clone().build().unwrap().len(), 1);
+        assert_eq!(
+            builder.complete().build().unwrap_err(),
+            TableError::Incomplete(vec![(0, true), (1, false), (1, true)])
+        );
+    }
+}
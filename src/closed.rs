@@ -0,0 +1,299 @@
+//! Proving that a table runs forever from a blank tape by abstract
+//! interpretation: a run is followed on abstract configurations that only
+//! know the state, the head cell and the `window` cells either side of it,
+//! while the rest of each side of the tape is abstracted to a regular
+//! language, the sides all of whose windows of `window` cells are in a set
+//! collected along the way. The abstract step function over-approximates the
+//! real one, so once the abstract configurations and the sets are closed
+//! under it with no halting or missing rule among them, no run the
+//! abstraction covers, the real one included, ever halts.
+//!
+//! This proves tables that never repeat a configuration, counters and
+//! sweepers whose tapes keep a local structure, that cycle detection can't.
+
+use alloc::collections::BTreeSet;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::{Move, State, TransitionTable};
+
+/// A proof that a table runs forever from a blank tape: a set of abstract
+/// configurations and window sets closed under the abstract step function
+/// and without halting rules, see [`Closed::check`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Closed<S, Sym> {
+    window: usize,
+    start: S,
+    configs: BTreeSet<Local<S, Sym>>,
+    // windows of each side, nearest the head first
+    left: BTreeSet<Vec<Sym>>,
+    right: BTreeSet<Vec<Sym>>,
+}
+
+// the state, the head cell and the `window` cells either side of it, nearest
+// first
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct Local<S, Sym> {
+    state: S,
+    left: Vec<Sym>,
+    head: Sym,
+    right: Vec<Sym>,
+}
+
+// the abstraction a proof is built up in
+struct Abstraction<'t, S, Sym> {
+    table: &'t TransitionTable<S, Sym>,
+    left: BTreeSet<Vec<Sym>>,
+    right: BTreeSet<Vec<Sym>>,
+}
+
+impl<S, Sym> Closed<S, Sym>
+where
+    S: Ord + Clone,
+    Sym: Ord + Default + Clone,
+{
+    /// Looks for a proof that `table` runs forever from a blank tape in
+    /// `start`, keeping `window` cells either side of the head. `None` if the
+    /// abstraction reaches a halting or missing rule, which the real run
+    /// needn't, or more than `max_configs` abstract configurations.
+    ///
+    /// # Panics
+    ///
+    /// If `window` is 0.
+    pub fn prove(
+        table: &TransitionTable<S, Sym>,
+        start: S,
+        window: usize,
+        max_configs: usize,
+    ) -> Option<Self> {
+        assert!(window > 0, "windows need cells");
+
+        let blanks = vec![Sym::default(); window];
+        let mut abstraction = Abstraction {
+            table,
+            left: BTreeSet::from([blanks.clone()]),
+            right: BTreeSet::from([blanks.clone()]),
+        };
+        let mut configs = BTreeSet::from([Local {
+            state: start.clone(),
+            left: blanks.clone(),
+            head: Sym::default(),
+            right: blanks,
+        }]);
+        let mut pending: Vec<_> = configs.iter().cloned().collect();
+
+        // the sets only grow, so every configuration is stepped again once
+        // they did
+        loop {
+            let windows = abstraction.left.len() + abstraction.right.len();
+
+            while let Some(local) = pending.pop() {
+                for next in abstraction.step(&local)? {
+                    if configs.insert(next.clone()) {
+                        pending.push(next);
+                    }
+                }
+
+                if configs.len() > max_configs {
+                    return None;
+                }
+            }
+
+            if abstraction.left.len() + abstraction.right.len() == windows {
+                break;
+            }
+
+            pending.extend(configs.iter().cloned());
+        }
+
+        Some(Self {
+            window,
+            start,
+            configs,
+            left: abstraction.left,
+            right: abstraction.right,
+        })
+    }
+
+    /// Checks the proof against `table` independently of how it was found:
+    /// that it covers the blank tape, steps from each of its configurations
+    /// only to others of them without halting or a missing rule, and needs
+    /// no windows beyond its own.
+    pub fn check(&self, table: &TransitionTable<S, Sym>) -> bool {
+        let blanks = vec![Sym::default(); self.window];
+        let start = Local {
+            state: self.start.clone(),
+            left: blanks.clone(),
+            head: Sym::default(),
+            right: blanks.clone(),
+        };
+        let mut abstraction = Abstraction {
+            table,
+            left: self.left.clone(),
+            right: self.right.clone(),
+        };
+
+        let closed = self.configs.iter().all(|local| {
+            abstraction
+                .step(local)
+                .is_some_and(|next| next.iter().all(|next| self.configs.contains(next)))
+        });
+
+        closed
+            && self.configs.contains(&start)
+            && self.left.contains(&blanks)
+            && self.right.contains(&blanks)
+            && abstraction.left == self.left
+            && abstraction.right == self.right
+    }
+
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    /// The number of abstract configurations.
+    pub fn len(&self) -> usize {
+        self.configs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.configs.is_empty()
+    }
+}
+
+impl<S, Sym> Abstraction<'_, S, Sym>
+where
+    S: Ord + Clone,
+    Sym: Ord + Clone,
+{
+    // the configurations `local` can step to, adding the windows moving the
+    // head leaves behind, or `None` if it halts or has no rule
+    fn step(&mut self, local: &Local<S, Sym>) -> Option<Vec<Local<S, Sym>>> {
+        let rule = self.table.lookup(&local.state, &local.head)?;
+
+        let state = match rule.new_state {
+            Some(State::Halt) => return None,
+            Some(State::State(state)) => state,
+            None => local.state.clone(),
+        };
+
+        let mut configs = vec![Local {
+            state,
+            head: rule.write.unwrap_or_else(|| local.head.clone()),
+            ..local.clone()
+        }];
+
+        let offset = rule.head_move.map_or(0, Move::offset);
+
+        for _ in 0..offset.unsigned_abs() {
+            configs = configs
+                .iter()
+                .flat_map(|local| self.shift(local, offset > 0))
+                .collect();
+        }
+
+        Some(configs)
+    }
+
+    // moves the head one cell, onto the nearest cell of the side it moves
+    // towards, drawing a cell beyond the window from a window of that side
+    // overlapping it
+    fn shift(&mut self, local: &Local<S, Sym>, rightward: bool) -> Vec<Local<S, Sym>> {
+        let (behind, ahead, grown, drawn) = match rightward {
+            true => (&local.left, &local.right, &mut self.left, &self.right),
+            false => (&local.right, &local.left, &mut self.right, &self.left),
+        };
+
+        // the window behind now starts a cell further from the head
+        grown.insert(behind.clone());
+
+        let mut pushed = Vec::from([local.head.clone()]);
+        pushed.extend_from_slice(&behind[..behind.len() - 1]);
+
+        drawn
+            .iter()
+            .filter(|window| window[..window.len() - 1] == ahead[1..])
+            .map(|window| {
+                let (left, right) = match rightward {
+                    true => (pushed.clone(), window.clone()),
+                    false => (window.clone(), pushed.clone()),
+                };
+
+                Local {
+                    state: local.state.clone(),
+                    left,
+                    head: ahead[0].clone(),
+                    right,
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::String;
+
+    use super::*;
+    use crate::accelerate::{Accelerator, Outcome};
+    use crate::dsl::Definition;
+    use crate::format::bbchallenge;
+    use crate::format::number::{self, Number};
+    use crate::{Machine, StopReason};
+
+    fn definition(machine: &str) -> Definition<u8> {
+        bbchallenge::parse(machine).unwrap()
+    }
+
+    #[test]
+    fn prove_test() {
+        // sweeps right forever
+        let sweeper = definition("1RA---");
+        let closed = Closed::prove(&sweeper.table, sweeper.start.clone(), 1, 100).unwrap();
+
+        assert!(closed.check(&sweeper.table));
+        assert_eq!(closed.window(), 1);
+
+        // the 2-state champion halts
+        let champion = definition("1RB1LB_1LA1RZ");
+        assert!(Closed::prove(&champion.table, champion.start.clone(), 3, 1_000).is_none());
+
+        // a tampered proof doesn't check
+        let mut tampered = closed.clone();
+        tampered.right.clear();
+        assert!(!tampered.check(&sweeper.table));
+        assert!(!closed.check(&champion.table));
+    }
+
+    #[test]
+    fn sound_test() {
+        // no 2-state table proven to run forever halts, which they do within
+        // the 6 steps of the champion, and some that acceleration leaves
+        // undecided are proven
+        let mut beyond = 0;
+
+        for index in 0..Number::count(2, 2).unwrap() {
+            let definition: Definition<u8> =
+                number::decode(&Number::from_u128(2, 2, index)).unwrap();
+            let Some(closed) = Closed::prove(&definition.table, definition.start.clone(), 2, 500)
+            else {
+                continue;
+            };
+
+            let mut machine: Machine<String, u8> =
+                Machine::new(definition.start.clone(), [0].into());
+            assert!(closed.check(&definition.table));
+            assert_ne!(
+                machine.run_table(&definition.table, 100).stop,
+                StopReason::Halted
+            );
+
+            let mut accelerator = Accelerator::new(&definition.table, definition.start.clone());
+            if accelerator.run(100) != Outcome::Forever {
+                beyond += 1;
+            }
+        }
+
+        assert!(beyond > 0);
+    }
+}
@@ -0,0 +1,333 @@
+//! Traces and batch results as Arrow record batches and Parquet files, for
+//! analysis in DataFusion, Polars or pandas.
+//!
+//! Traces have the columns of [`CsvTrace`](crate::CsvTrace): `step`, `state`,
+//! `head`, `read`, `write` and `move`. Batch results have one row per job
+//! with its index `job`, the `steps`, `stop` reason and `tape_len` of its
+//! run, and the `state` (null once halted) and `head` it ended in.
+
+use std::fmt::Display;
+use std::io::Write;
+use std::sync::Arc;
+
+use arrow_array::builder::{Int64Builder, StringBuilder, UInt64Builder};
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use parquet::arrow::ArrowWriter;
+use parquet::errors::ParquetError;
+
+use crate::{move_name, BatchResult, Symbol, Tape, TraceSink, TraceStep};
+
+/// How many steps [`ArrowTrace`] and [`ParquetTrace`] put in a batch.
+pub const DEFAULT_BATCH_SIZE: usize = 65_536;
+
+pub fn trace_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("step", DataType::UInt64, false),
+        Field::new("state", DataType::Utf8, false),
+        Field::new("head", DataType::Int64, false),
+        Field::new("read", DataType::Utf8, false),
+        Field::new("write", DataType::Utf8, false),
+        Field::new("move", DataType::Utf8, false),
+    ]))
+}
+
+pub fn results_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("job", DataType::UInt64, false),
+        Field::new("steps", DataType::UInt64, false),
+        Field::new("stop", DataType::Utf8, false),
+        Field::new("tape_len", DataType::UInt64, false),
+        Field::new("state", DataType::Utf8, true),
+        Field::new("head", DataType::Int64, false),
+    ]))
+}
+
+#[derive(Debug, Default)]
+struct TraceColumns {
+    step: UInt64Builder,
+    state: StringBuilder,
+    head: Int64Builder,
+    read: StringBuilder,
+    write: StringBuilder,
+    head_move: StringBuilder,
+    len: usize,
+}
+
+impl TraceColumns {
+    fn push<S: Display, Sym: Symbol>(&mut self, step: &TraceStep<'_, S, Sym>) {
+        let write = step.rule.write.as_ref().unwrap_or(step.read);
+
+        self.step.append_value(step.step);
+        self.state.append_value(step.state.to_string());
+        self.head.append_value(step.head as i64);
+        self.read.append_value(step.read.to_char().to_string());
+        self.write.append_value(write.to_char().to_string());
+        self.head_move.append_value(move_name(step.rule.head_move));
+        self.len += 1;
+    }
+
+    fn finish(&mut self) -> RecordBatch {
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(self.step.finish()),
+            Arc::new(self.state.finish()),
+            Arc::new(self.head.finish()),
+            Arc::new(self.read.finish()),
+            Arc::new(self.write.finish()),
+            Arc::new(self.head_move.finish()),
+        ];
+        self.len = 0;
+
+        RecordBatch::try_new(trace_schema(), columns).expect("columns match the schema")
+    }
+}
+
+/// A [`TraceSink`] collecting steps into record batches.
+#[derive(Debug)]
+pub struct ArrowTrace {
+    columns: TraceColumns,
+    batches: Vec<RecordBatch>,
+    batch_size: usize,
+}
+
+impl ArrowTrace {
+    pub fn new() -> Self {
+        Self {
+            columns: TraceColumns::default(),
+            batches: Vec::new(),
+            batch_size: DEFAULT_BATCH_SIZE,
+        }
+    }
+
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// The batches of the steps recorded, in order.
+    pub fn finish(mut self) -> Vec<RecordBatch> {
+        if self.columns.len > 0 {
+            self.batches.push(self.columns.finish());
+        }
+
+        self.batches
+    }
+}
+
+impl Default for ArrowTrace {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: Display, Sym: Symbol> TraceSink<S, Sym> for ArrowTrace {
+    fn record(&mut self, step: &TraceStep<'_, S, Sym>) -> bool {
+        self.columns.push(step);
+
+        if self.columns.len >= self.batch_size {
+            self.batches.push(self.columns.finish());
+        }
+
+        true
+    }
+}
+
+/// A [`TraceSink`] writing steps to a Parquet file a batch at a time.
+///
+/// The file is only complete once [`ParquetTrace::finish`] is called.
+pub struct ParquetTrace<W: Write + Send> {
+    writer: ArrowWriter<W>,
+    columns: TraceColumns,
+    batch_size: usize,
+    error: Option<ParquetError>,
+}
+
+impl<W: Write + Send> ParquetTrace<W> {
+    pub fn new(writer: W) -> Result<Self, ParquetError> {
+        Ok(Self {
+            writer: ArrowWriter::try_new(writer, trace_schema(), None)?,
+            columns: TraceColumns::default(),
+            batch_size: DEFAULT_BATCH_SIZE,
+            error: None,
+        })
+    }
+
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Writes the last batch and the file footer and returns the writer, or
+    /// the first error encountered.
+    pub fn finish(mut self) -> Result<W, ParquetError> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+
+        if self.columns.len > 0 {
+            self.writer.write(&self.columns.finish())?;
+        }
+
+        self.writer.into_inner()
+    }
+}
+
+impl<S, Sym, W> TraceSink<S, Sym> for ParquetTrace<W>
+where
+    S: Display,
+    Sym: Symbol,
+    W: Write + Send,
+{
+    fn record(&mut self, step: &TraceStep<'_, S, Sym>) -> bool {
+        self.columns.push(step);
+
+        if self.columns.len >= self.batch_size {
+            if let Err(error) = self.writer.write(&self.columns.finish()) {
+                self.error = Some(error);
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+/// The results of a [`run_batch`](crate::run_batch) call as one batch, in
+/// job order.
+pub fn results_batch<S, Sym, T>(results: &[BatchResult<S, Sym, T>]) -> RecordBatch
+where
+    S: Display,
+    Sym: Default + Clone,
+    T: Tape<Sym>,
+{
+    let mut job = UInt64Builder::new();
+    let mut steps = UInt64Builder::new();
+    let mut stop = StringBuilder::new();
+    let mut tape_len = UInt64Builder::new();
+    let mut state = StringBuilder::new();
+    let mut head = Int64Builder::new();
+
+    for (index, result) in results.iter().enumerate() {
+        let machine = &result.machine;
+
+        job.append_value(index as u64);
+        steps.append_value(result.stats.steps);
+        stop.append_value(format!("{:?}", result.stats.stop));
+        tape_len.append_value(result.stats.tape_len as u64);
+        state.append_option(machine.state().map(ToString::to_string));
+        head.append_value(machine.head() as i64 - machine.origin() as i64);
+    }
+
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(job.finish()),
+        Arc::new(steps.finish()),
+        Arc::new(stop.finish()),
+        Arc::new(tape_len.finish()),
+        Arc::new(state.finish()),
+        Arc::new(head.finish()),
+    ];
+
+    RecordBatch::try_new(results_schema(), columns).expect("columns match the schema")
+}
+
+/// Writes `batches`, which have to share a schema, as a Parquet file.
+pub fn write_parquet<W: Write + Send>(
+    writer: W,
+    batches: &[RecordBatch],
+) -> Result<W, ParquetError> {
+    let schema = match batches.first() {
+        Some(batch) => batch.schema(),
+        None => Arc::new(Schema::empty()),
+    };
+    let mut writer = ArrowWriter::try_new(writer, schema, None)?;
+
+    for batch in batches {
+        writer.write(batch)?;
+    }
+
+    writer.into_inner()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::{Int64Type, UInt64Type};
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    use super::*;
+    use crate::{dsl, run_batch, Char, Machine};
+
+    const INC: &str = "inc 1 0 R *\ninc _ 1 N halt";
+
+    #[test]
+    fn arrow_trace_test() {
+        let table = dsl::parse(INC).unwrap();
+        let mut machine: Machine<String, Char> =
+            Machine::with_input("inc".to_owned(), "111").unwrap();
+        let mut sink = ArrowTrace::new().batch_size(3);
+
+        machine.run_table_traced(&table, 100, &mut sink);
+        let batches = sink.finish();
+
+        assert_eq!(
+            batches
+                .iter()
+                .map(RecordBatch::num_rows)
+                .collect::<Vec<_>>(),
+            [3, 1]
+        );
+        assert_eq!(batches[1].column(2).as_primitive::<Int64Type>().value(0), 3);
+        assert_eq!(batches[1].column(4).as_string::<i32>().value(0), "1");
+        assert_eq!(batches[0].column(5).as_string::<i32>().value(0), "R");
+    }
+
+    #[test]
+    fn parquet_test() {
+        let path =
+            std::env::temp_dir().join(format!("turing-trace-{}.parquet", std::process::id()));
+        let table = dsl::parse(INC).unwrap();
+        let mut machine: Machine<String, Char> =
+            Machine::with_input("inc".to_owned(), "11").unwrap();
+        let mut sink = ParquetTrace::new(File::create(&path).unwrap())
+            .unwrap()
+            .batch_size(2);
+
+        machine.run_table_traced(&table, 100, &mut sink);
+        sink.finish().unwrap();
+
+        let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(&path).unwrap())
+            .unwrap()
+            .build()
+            .unwrap();
+        let steps: Vec<u64> = reader
+            .flat_map(|batch| {
+                let batch = batch.unwrap();
+                batch
+                    .column(0)
+                    .as_primitive::<UInt64Type>()
+                    .values()
+                    .to_vec()
+            })
+            .collect();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(steps, [0, 1, 2]);
+
+        let jobs = ["1", "111"].map(|input| {
+            (
+                table.clone(),
+                Machine::with_input("inc".to_owned(), input).unwrap(),
+            )
+        });
+        let results: Vec<BatchResult<String, Char, _>> = run_batch(jobs.to_vec(), 2, 1);
+        let batch = results_batch(&results);
+        let bytes = write_parquet(Vec::new(), std::slice::from_ref(&batch)).unwrap();
+
+        assert!(!bytes.is_empty());
+        assert_eq!(batch.column(2).as_string::<i32>().value(1), "StepLimit");
+        assert!(batch.column(4).is_null(0));
+        assert_eq!(batch.column(4).as_string::<i32>().value(1), "inc");
+    }
+}
@@ -0,0 +1,119 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt::Debug;
+use core::hash::Hash;
+
+use crate::{Snapshot, State, Symbol};
+
+/// An unsigned integer type for the head, origin and step count of a
+/// [`CompactSnapshot`], typically `u32` or `u64`.
+pub trait Counter: Copy + Eq + Ord + Hash + Debug + TryFrom<u64> + Into<u64> {}
+
+impl<T> Counter for T where T: Copy + Eq + Ord + Hash + Debug + TryFrom<u64> + Into<u64> {}
+
+/// A [`Snapshot`] packed into a few bytes plus one byte per cell, for search
+/// frontiers and visited sets holding millions of configurations.
+///
+/// States are stored as their position in a list of at most 255 states and
+/// symbols as their [`Symbol::ordinal`], which must fit a `u8`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CompactSnapshot<C = u32> {
+    // 0 once halted, otherwise one more than the state's position
+    state: u8,
+    head: C,
+    origin: C,
+    steps: C,
+    cells: Box<[u8]>,
+}
+
+impl<S, Sym> Snapshot<S, Sym> {
+    /// Packs the snapshot, taken after `steps` steps, numbering states by
+    /// their position in `states`. Returns `None` if something doesn't fit.
+    pub fn compact<C: Counter>(&self, states: &[S], steps: u64) -> Option<CompactSnapshot<C>>
+    where
+        S: PartialEq,
+        Sym: Symbol,
+    {
+        let state = match &self.state {
+            State::Halt => 0,
+            State::State(state) => {
+                let position = states.iter().position(|other| other == state)?;
+                u8::try_from(position + 1).ok()?
+            }
+        };
+        let counter = |value: u64| C::try_from(value).ok();
+
+        Some(CompactSnapshot {
+            state,
+            head: counter(self.head as u64)?,
+            origin: counter(self.origin as u64)?,
+            steps: counter(steps)?,
+            cells: self
+                .tape
+                .iter()
+                .map(|symbol| u8::try_from(symbol.ordinal()).ok())
+                .collect::<Option<_>>()?,
+        })
+    }
+}
+
+impl<C: Counter> CompactSnapshot<C> {
+    /// Unpacks the snapshot and its step count, with states numbered as when
+    /// it was packed.
+    pub fn expand<S, Sym>(&self, states: &[S]) -> Option<(Snapshot<S, Sym>, u64)>
+    where
+        S: Clone,
+        Sym: Symbol,
+    {
+        let state = match self.state {
+            0 => State::Halt,
+            state => State::State(states.get(state as usize - 1)?.clone()),
+        };
+        let tape = self
+            .cells
+            .iter()
+            .map(|&cell| Sym::from_ordinal(cell.into()))
+            .collect::<Option<Vec<_>>>()?;
+        let snapshot = Snapshot {
+            state,
+            tape,
+            head: usize::try_from(self.head.into()).ok()?,
+            origin: usize::try_from(self.origin.into()).ok()?,
+        };
+
+        Some((snapshot, self.steps()))
+    }
+
+    pub fn steps(&self) -> u64 {
+        self.steps.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Char, Machine, Rule};
+
+    #[test]
+    fn compact_snapshot_test() {
+        let states = ["scan", "done"];
+        let mut machine: Machine<_, Char> = Machine::with_input("scan", "ab").unwrap();
+        machine.apply(Rule::to("done").left());
+        let snapshot = machine.snapshot();
+
+        let compact: CompactSnapshot = snapshot.compact(&states, 1).unwrap();
+
+        assert_eq!(compact.steps(), 1);
+        assert_eq!(compact.expand(&states), Some((snapshot.clone(), 1)));
+        assert_eq!(snapshot.compact::<u8>(&states, 300), None);
+        assert_eq!(snapshot.compact::<u32>(&["scan"], 1), None);
+        assert_eq!(
+            Snapshot::<_, Char> {
+                tape: Vec::from([Char('λ')]),
+                ..snapshot.clone()
+            }
+            .compact::<u32>(&states, 1),
+            None
+        );
+    }
+}
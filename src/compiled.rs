@@ -0,0 +1,458 @@
+use alloc::collections::BTreeSet;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use core::cmp::Reverse;
+
+#[cfg(feature = "std")]
+use crate::Coverage;
+use crate::{
+    analysis, Machine, MissingRule, Move, Rule, RunStats, State, StopReason, TransitionTable,
+};
+
+// `Entry::next` for halting and for missing rules under `MissingRule::Error`
+const HALT: u32 = u32::MAX;
+const NO_RULE: u32 = u32::MAX - 1;
+// `Entry::write` for rules leaving a symbol outside the table's alphabet
+const KEEP: u32 = u32::MAX;
+
+// a rule with every part spelled out: the state index it leads to, the
+// symbol index it writes and how far it moves the head
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Entry {
+    next: u32,
+    write: u32,
+    offset: isize,
+}
+
+/// A [`TransitionTable`] baked into a flat array indexed by dense state and
+/// symbol numbers, for running machines with [`Machine::run_compiled`] after
+/// building their table the flexible way.
+///
+/// There is a row for every state and a column for every symbol, plus one
+/// for states and one for symbols the table doesn't name, where its
+/// [`MissingRule`] policy applies.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompiledTable<S, Sym> {
+    // numbered by their position
+    states: Vec<S>,
+    symbols: Vec<Sym>,
+    // the numbers sorted by what they number, for looking them up
+    state_numbers: Vec<u32>,
+    symbol_numbers: Vec<u32>,
+    entries: Vec<Entry>,
+}
+
+impl<S, Sym> TransitionTable<S, Sym>
+where
+    S: Ord + Clone,
+    Sym: Ord + Clone + Default,
+{
+    /// Resolves the table into a [`CompiledTable`].
+    pub fn compile(&self) -> CompiledTable<S, Sym> {
+        let (states, symbols) = self.compiled_names();
+        self.compile_numbered(states, symbols)
+    }
+
+    /// Like [`TransitionTable::compile`], numbering states and symbols by how
+    /// often `profile` saw them, hottest first, so that the rules a machine
+    /// spends most of its time in share cache lines. Worth it for machines
+    /// run over and over, profiled on a typical run.
+    #[cfg(feature = "std")]
+    pub fn compile_with_profile(&self, profile: &Coverage<'_, S, Sym>) -> CompiledTable<S, Sym> {
+        let (mut states, mut symbols) = self.compiled_names();
+        let hits = |state: &S, symbol: &Sym| profile.hits(state, symbol);
+
+        // stable sorts, ties keep the order of the names
+        states.sort_by_cached_key(|state| {
+            Reverse(
+                symbols
+                    .iter()
+                    .map(|symbol| hits(state, symbol))
+                    .sum::<u64>(),
+            )
+        });
+        symbols.sort_by_cached_key(|symbol| {
+            Reverse(states.iter().map(|state| hits(state, symbol)).sum::<u64>())
+        });
+
+        self.compile_numbered(states, symbols)
+    }
+
+    // every state and symbol the table names, with the blank, in order
+    fn compiled_names(&self) -> (Vec<S>, Vec<Sym>) {
+        let mut states: BTreeSet<&S> = self.states().collect();
+        let mut symbols = analysis::alphabet(self);
+        let blank = Sym::default();
+        symbols.insert(&blank);
+
+        let rules = self
+            .iter()
+            .map(|(_, _, rule)| rule)
+            .chain(match self.missing() {
+                MissingRule::Default(rule) => Some(rule),
+                _ => None,
+            });
+
+        for rule in rules {
+            if let Some(State::State(next)) = &rule.new_state {
+                states.insert(next);
+            }
+
+            symbols.extend(&rule.write);
+        }
+
+        (
+            states.into_iter().cloned().collect(),
+            symbols.into_iter().cloned().collect(),
+        )
+    }
+
+    fn compile_numbered(&self, states: Vec<S>, symbols: Vec<Sym>) -> CompiledTable<S, Sym> {
+        let mut state_numbers: Vec<u32> = (0..states.len() as u32).collect();
+        state_numbers.sort_by(|&a, &b| states[a as usize].cmp(&states[b as usize]));
+        let mut symbol_numbers: Vec<u32> = (0..symbols.len() as u32).collect();
+        symbol_numbers.sort_by(|&a, &b| symbols[a as usize].cmp(&symbols[b as usize]));
+
+        let mut compiled = CompiledTable {
+            entries: Vec::with_capacity((states.len() + 1) * (symbols.len() + 1)),
+            states,
+            symbols,
+            state_numbers,
+            symbol_numbers,
+        };
+
+        for row in 0..=compiled.states.len() {
+            for column in 0..=compiled.symbols.len() {
+                let state = compiled.states.get(row);
+                let symbol = compiled.symbols.get(column);
+                let rule = match (state, symbol) {
+                    (Some(state), Some(symbol)) => self.get(state, symbol),
+                    _ => None,
+                };
+
+                let entry = match (rule, self.missing()) {
+                    (Some(rule), _) | (None, MissingRule::Default(rule)) => {
+                        compiled.entry(rule, row, column)
+                    }
+                    (None, MissingRule::Halt | MissingRule::Reject) => {
+                        compiled.entry(&Rule::halt(), row, column)
+                    }
+                    (None, MissingRule::Error) => Entry {
+                        next: NO_RULE,
+                        write: KEEP,
+                        offset: 0,
+                    },
+                };
+
+                compiled.entries.push(entry);
+            }
+        }
+
+        compiled
+    }
+}
+
+impl<S, Sym> CompiledTable<S, Sym>
+where
+    S: Ord + Clone,
+    Sym: Ord + Clone,
+{
+    /// The states the table names, numbered by their position.
+    pub fn states(&self) -> &[S] {
+        &self.states
+    }
+
+    /// The symbols the table names and the blank, numbered by their position.
+    pub fn symbols(&self) -> &[Sym] {
+        &self.symbols
+    }
+
+    fn entry(&self, rule: &Rule<S, Sym>, row: usize, column: usize) -> Entry {
+        let next = match &rule.new_state {
+            None => row as u32,
+            Some(State::Halt) => HALT,
+            Some(State::State(next)) => self.state_index(next).unwrap() as u32,
+        };
+        let write = match &rule.write {
+            Some(write) => self.symbol_index(write).unwrap() as u32,
+            None if column < self.symbols.len() => column as u32,
+            None => KEEP,
+        };
+
+        Entry {
+            next,
+            write,
+            offset: rule.head_move.map_or(0, Move::offset),
+        }
+    }
+
+    fn state_index(&self, state: &S) -> Option<usize> {
+        let position = self
+            .state_numbers
+            .binary_search_by(|&number| self.states[number as usize].cmp(state))
+            .ok()?;
+
+        Some(self.state_numbers[position] as usize)
+    }
+
+    fn symbol_index(&self, symbol: &Sym) -> Option<usize> {
+        let position = self
+            .symbol_numbers
+            .binary_search_by(|&number| self.symbols[number as usize].cmp(symbol))
+            .ok()?;
+
+        Some(self.symbol_numbers[position] as usize)
+    }
+
+    // the entry for a state and symbol index, or for an index past the ones
+    // the table names
+    fn lookup(&self, state: u32, symbol: u32) -> Entry {
+        let columns = self.symbols.len() + 1;
+        let symbol = (symbol as usize).min(self.symbols.len());
+        self.entries[state as usize * columns + symbol]
+    }
+}
+
+// the tape of a compiled run: symbol numbers padded with blanks on both
+// sides, so that moves only grow it once they run off the padding
+struct Cells {
+    cells: Vec<u32>,
+    head: usize,
+    // the cells the run has seen, and where the tape it started from began
+    low: usize,
+    high: usize,
+    start: usize,
+    blank: u32,
+}
+
+impl Cells {
+    const PADDING: usize = 64;
+
+    fn new(tape: &[u32], head: usize, blank: u32) -> Self {
+        let mut cells = Vec::with_capacity(tape.len() + 2 * Self::PADDING);
+        cells.resize(Self::PADDING, blank);
+        cells.extend_from_slice(tape);
+        cells.resize(tape.len() + 2 * Self::PADDING, blank);
+
+        Self {
+            cells,
+            head: Self::PADDING + head,
+            low: Self::PADDING,
+            high: Self::PADDING + tape.len() - 1,
+            start: Self::PADDING,
+            blank,
+        }
+    }
+
+    fn shift(&mut self, offset: isize) {
+        let target = self.head as isize + offset;
+
+        if target < 0 || target >= self.cells.len() as isize {
+            return self.grow(offset);
+        }
+
+        self.head = target as usize;
+        self.low = self.low.min(self.head);
+        self.high = self.high.max(self.head);
+    }
+
+    // pads the tape on both sides by as much as it holds and the move
+    #[cold]
+    fn grow(&mut self, offset: isize) {
+        let padding = self.cells.len() + offset.unsigned_abs();
+        let mut cells = Vec::with_capacity(self.cells.len() + 2 * padding);
+        cells.resize(padding, self.blank);
+        cells.extend_from_slice(&self.cells);
+        cells.resize(self.cells.len() + 2 * padding, self.blank);
+
+        self.cells = cells;
+        self.head += padding;
+        self.low += padding;
+        self.high += padding;
+        self.start += padding;
+        self.shift(offset);
+    }
+}
+
+impl<S, Sym> Machine<S, Sym>
+where
+    S: Ord + Clone,
+    Sym: Ord + Clone + Default,
+{
+    /// Like [`Machine::run_table`] with the table `table` was compiled from,
+    /// running on symbol and state numbers and translating the tape back
+    /// when done.
+    pub fn run_compiled(&mut self, table: &CompiledTable<S, Sym>, max_steps: u64) -> RunStats {
+        let State::State(state) = &self.state else {
+            return RunStats {
+                steps: 0,
+                tape_len: self.tape.len(),
+                stop: StopReason::Halted,
+            };
+        };
+
+        // numbers past the table's symbols for the ones it doesn't name
+        let mut extra: Vec<Sym> = Vec::new();
+        let mut number = |symbol: &Sym| match table.symbol_index(symbol) {
+            Some(index) => index as u32,
+            None => {
+                let index = match extra.iter().position(|other| other == symbol) {
+                    Some(index) => index,
+                    None => {
+                        extra.push(symbol.clone());
+                        extra.len() - 1
+                    }
+                };
+
+                (table.symbols.len() + 1 + index) as u32
+            }
+        };
+
+        let tape: Vec<u32> = self.tape.iter().map(&mut number).collect();
+        let mut cells = Cells::new(&tape, self.head, number(&self.blank));
+        let unnamed = table.states.len() as u32;
+        let mut state = table
+            .state_index(state)
+            .map_or(unnamed, |state| state as u32);
+        let mut steps = 0;
+
+        // a step is a lookup, a write and a move, whatever the rule; the
+        // branches left are on the step limit, the end of the run and the
+        // tape growing, all rarely taken
+        let stop = loop {
+            if steps == max_steps {
+                break StopReason::StepLimit;
+            }
+
+            let read = cells.cells[cells.head];
+            let entry = table.lookup(state, read);
+
+            if entry.next == NO_RULE {
+                break StopReason::NoRule;
+            }
+
+            cells.cells[cells.head] = if entry.write == KEEP {
+                read
+            } else {
+                entry.write
+            };
+            cells.shift(entry.offset);
+            state = entry.next;
+            steps += 1;
+
+            if state == HALT {
+                break StopReason::Halted;
+            }
+        };
+
+        let symbol = |index: &u32| match table.symbols.get(*index as usize) {
+            Some(symbol) => symbol.clone(),
+            None => extra[*index as usize - table.symbols.len() - 1].clone(),
+        };
+
+        self.tape = cells.cells[cells.low..=cells.high]
+            .iter()
+            .map(symbol)
+            .collect();
+        self.head = cells.head - cells.low;
+        self.origin += cells.start - cells.low;
+        self.state = match state {
+            HALT => State::Halt,
+            state if state == unnamed => self.state.clone(),
+            state => State::State(table.states[state as usize].clone()),
+        };
+
+        RunStats {
+            steps,
+            tape_len: self.tape.len(),
+            stop,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(table: &TransitionTable<u8, u8>, start: u8, tape: &[u8]) {
+        let compiled = table.compile();
+        let mut machine: Machine<u8, u8> = Machine::new(start, tape.iter().copied().collect());
+        let mut expected = machine.clone();
+
+        for max_steps in [3, 100] {
+            assert_eq!(
+                machine.run_compiled(&compiled, max_steps),
+                expected.run_table(table, max_steps)
+            );
+            assert_eq!(machine, expected);
+            assert_eq!(machine.head(), expected.head());
+            assert_eq!(machine.state(), expected.state());
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn compile_with_profile_test() {
+        // runs right over the ones in state 1 and halts on the blank
+        let table: TransitionTable<u8, u8> = [
+            (0, 0, Rule::to(1).right()),
+            (1, 1, Rule::new().right()),
+            (1, 0, Rule::halt()),
+        ]
+        .into_iter()
+        .collect();
+
+        let mut coverage = Coverage::new(&table);
+        let mut machine: Machine<u8, u8> = Machine::new(0, [0, 1, 1, 1, 0].into());
+        machine.run_table_traced(&table, 100, &mut coverage);
+
+        let compiled = table.compile_with_profile(&coverage);
+
+        assert_eq!(compiled.states(), [1, 0]);
+        assert_eq!(compiled.symbols(), [1, 0]);
+
+        let mut profiled: Machine<u8, u8> = Machine::new(0, [0, 1, 1, 0].into());
+        let mut expected = profiled.clone();
+
+        assert_eq!(
+            profiled.run_compiled(&compiled, 100),
+            expected.run_table(&table, 100)
+        );
+        assert_eq!(profiled, expected);
+    }
+
+    #[test]
+    fn run_compiled_test() {
+        // increments a binary number written least significant digit first
+        let mut table: TransitionTable<u8, u8> = [
+            (0, 1, Rule::new().write(0).right()),
+            (0, 0, Rule::to(1).write(1).by(-2)),
+            (1, 0, Rule::new().left()),
+        ]
+        .into_iter()
+        .collect();
+
+        check(&table, 0, &[1, 1, 0, 1]);
+        check(&table, 0, &[1, 1, 1]);
+        // a symbol and a state the table doesn't name
+        check(&table, 0, &[1, 7, 1]);
+        check(&table, 9, &[1]);
+
+        table.set_missing(MissingRule::Error);
+        check(&table, 0, &[1, 7]);
+
+        table.set_missing(MissingRule::Default(Rule::to(0).right()));
+        check(&table, 0, &[7, 7, 1, 7]);
+        check(&table, 9, &[7, 1]);
+
+        // runs off the padding of the tape on both sides
+        let table: TransitionTable<u8, u8> = [
+            (0, 0, Rule::to(1).write(1).by(100)),
+            (1, 0, Rule::to(0).write(2).by(-130)),
+        ]
+        .into_iter()
+        .collect();
+
+        check(&table, 0, &[0]);
+    }
+}
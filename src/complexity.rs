@@ -0,0 +1,263 @@
+//! Measuring how the running time and tape use of a machine grow with the
+//! size of its input.
+
+use std::fmt::{self, Display};
+
+use crate::dsl::Definition;
+use crate::{Machine, StopReason, Symbol};
+
+/// One run of a [`measure`] call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sample {
+    pub size: usize,
+    pub steps: u64,
+    /// The number of cells the tape grew to, input included.
+    pub space: usize,
+    /// Whether the machine halted within the step limit.
+    pub halted: bool,
+}
+
+/// The growth functions [`Fit`] chooses from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Growth {
+    Constant,
+    Logarithmic,
+    Linear,
+    Linearithmic,
+    Quadratic,
+    Cubic,
+    Exponential,
+}
+
+/// The growth function that best fits a measure, scaled by `coefficient`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fit {
+    pub growth: Growth,
+    pub coefficient: f64,
+    /// The root mean square of the relative errors of the fit.
+    pub error: f64,
+    /// The slope of the measure against the size on a log-log scale, which
+    /// is the degree for polynomial growth.
+    pub exponent: f64,
+}
+
+/// The samples of a [`measure`] call, smallest input first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Measurement {
+    pub samples: Vec<Sample>,
+}
+
+const GROWTHS: [Growth; 7] = [
+    Growth::Constant,
+    Growth::Logarithmic,
+    Growth::Linear,
+    Growth::Linearithmic,
+    Growth::Quadratic,
+    Growth::Cubic,
+    Growth::Exponential,
+];
+
+impl Growth {
+    pub fn eval(self, n: f64) -> f64 {
+        match self {
+            Growth::Constant => 1.0,
+            Growth::Logarithmic => n.max(2.0).log2(),
+            Growth::Linear => n,
+            Growth::Linearithmic => n * n.max(2.0).log2(),
+            Growth::Quadratic => n * n,
+            Growth::Cubic => n * n * n,
+            Growth::Exponential => n.exp2(),
+        }
+    }
+}
+
+impl Display for Growth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Growth::Constant => "1",
+            Growth::Logarithmic => "log n",
+            Growth::Linear => "n",
+            Growth::Linearithmic => "n log n",
+            Growth::Quadratic => "n^2",
+            Growth::Cubic => "n^3",
+            Growth::Exponential => "2^n",
+        })
+    }
+}
+
+/// Runs `definition` on `input(size)` for every size in `sizes`, for at most
+/// `max_steps` steps each. Inputs the tape can't hold are skipped.
+pub fn measure<Sym>(
+    definition: &Definition<Sym>,
+    sizes: impl IntoIterator<Item = usize>,
+    mut input: impl FnMut(usize) -> String,
+    max_steps: u64,
+) -> Measurement
+where
+    Sym: Symbol + Ord + Clone + Default,
+{
+    let mut samples: Vec<Sample> = sizes
+        .into_iter()
+        .filter_map(|size| {
+            let mut machine = Machine::with_input(definition.start.clone(), &input(size))?;
+            let stats = machine.run_table(&definition.table, max_steps);
+
+            Some(Sample {
+                size,
+                steps: stats.steps,
+                space: stats.tape_len,
+                halted: stats.stop == StopReason::Halted,
+            })
+        })
+        .collect();
+
+    samples.sort_by_key(|sample| sample.size);
+    Measurement { samples }
+}
+
+impl Measurement {
+    /// The fit of the steps of halted runs, if any halted.
+    pub fn time(&self) -> Option<Fit> {
+        self.fit(|sample| sample.steps as f64)
+    }
+
+    /// The fit of the tape use of halted runs, if any halted.
+    pub fn space(&self) -> Option<Fit> {
+        self.fit(|sample| sample.space as f64)
+    }
+
+    fn fit(&self, measure: impl Fn(&Sample) -> f64) -> Option<Fit> {
+        let points: Vec<(f64, f64)> = self
+            .samples
+            .iter()
+            .filter(|sample| sample.halted)
+            .map(|sample| (sample.size as f64, measure(sample).max(1.0)))
+            .collect();
+
+        if points.is_empty() {
+            return None;
+        }
+
+        // least squares on the relative errors, so that large inputs don't
+        // drown out the rest
+        let fits = GROWTHS.iter().filter_map(|&growth| {
+            let ratios: Vec<f64> = points.iter().map(|&(n, y)| growth.eval(n) / y).collect();
+            let coefficient = ratios.len() as f64 / ratios.iter().sum::<f64>();
+            let error = ratios
+                .iter()
+                .map(|ratio| (coefficient * ratio - 1.0).powi(2))
+                .sum::<f64>()
+                / ratios.len() as f64;
+
+            error
+                .is_finite()
+                .then_some((growth, coefficient, error.sqrt()))
+        });
+
+        let (growth, coefficient, error) = fits.min_by(|a, b| a.2.total_cmp(&b.2))?;
+
+        Some(Fit {
+            growth,
+            coefficient,
+            error,
+            exponent: slope(&points),
+        })
+    }
+}
+
+// the least squares slope of `ln y` against `ln n`, for sizes above zero
+fn slope(points: &[(f64, f64)]) -> f64 {
+    let logs: Vec<(f64, f64)> = points
+        .iter()
+        .filter(|&&(n, _)| n > 0.0)
+        .map(|&(n, y)| (n.ln(), y.ln()))
+        .collect();
+    let count = logs.len() as f64;
+    let mean_x = logs.iter().map(|p| p.0).sum::<f64>() / count;
+    let mean_y = logs.iter().map(|p| p.1).sum::<f64>() / count;
+    let covariance: f64 = logs.iter().map(|p| (p.0 - mean_x) * (p.1 - mean_y)).sum();
+    let variance: f64 = logs.iter().map(|p| (p.0 - mean_x).powi(2)).sum();
+
+    match variance > 0.0 {
+        true => covariance / variance,
+        false => 0.0,
+    }
+}
+
+impl Display for Fit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:.3} * {} (error {:.1}%, exponent {:.2})",
+            self.coefficient,
+            self.growth,
+            self.error * 100.0,
+            self.exponent
+        )
+    }
+}
+
+impl Display for Measurement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{:>8} {:>12} {:>8}", "size", "steps", "space")?;
+
+        for sample in &self.samples {
+            let limit = if sample.halted { "" } else { " (limit)" };
+            writeln!(
+                f,
+                "{:>8} {:>12} {:>8}{limit}",
+                sample.size, sample.steps, sample.space
+            )?;
+        }
+
+        if let Some(time) = self.time() {
+            writeln!(f, "time:  {time}")?;
+        }
+
+        if let Some(space) = self.space() {
+            writeln!(f, "space: {space}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{dsl, Char};
+
+    #[test]
+    fn measure_test() {
+        // walks to the end of the input: linear time and space
+        let scan: Definition<Char> =
+            dsl::parse_definition("scan 1 * R *\nscan _ * N halt").unwrap();
+        let measurement = measure(&scan, [4, 1, 16, 64, 256], |n| "1".repeat(n), 10_000);
+
+        assert_eq!(measurement.samples[0].size, 1);
+        assert_eq!(measurement.samples[4].steps, 257);
+        assert_eq!(measurement.time().unwrap().growth, Growth::Linear);
+        assert_eq!(measurement.space().unwrap().growth, Growth::Linear);
+
+        // erases the input from both ends alternately: quadratic time
+        let shrink = dsl::parse_definition::<Char>(
+            "
+            start start
+            start 1 _ R right
+            start _ * N halt
+            right 1 * R *
+            right _ * L erase
+            erase 1 _ L left
+            erase _ * N halt
+            left 1 * L *
+            left _ * R start
+            ",
+        )
+        .unwrap();
+        let measurement = measure(&shrink, [8, 16, 32, 64, 128], |n| "1".repeat(n), 100_000);
+        let time = measurement.time().unwrap();
+
+        assert_eq!(time.growth, Growth::Quadratic);
+        assert!((time.exponent - 2.0).abs() < 0.2, "{time}");
+    }
+}
@@ -0,0 +1,229 @@
+use alloc::borrow::ToOwned;
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{self, Debug, Display};
+
+use crate::{Machine, StopReason, Symbol, TransitionTable};
+
+/// A machine's I/O convention: the start state, preconditions its inputs
+/// must meet and postconditions relating them to the outputs, both over
+/// tapes with the blanks at either end trimmed.
+pub struct Contract<'a, S, Sym> {
+    start: S,
+    requires: Vec<(String, Box<Precondition<'a, Sym>>)>,
+    ensures: Vec<(String, Box<Postcondition<'a, Sym>>)>,
+}
+
+type Precondition<'a, Sym> = dyn Fn(&[Sym]) -> bool + 'a;
+type Postcondition<'a, Sym> = dyn Fn(&[Sym], &[Sym]) -> bool + 'a;
+
+/// How an input broke a [`Contract`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Breach<Sym> {
+    /// The machine was still running after the given number of steps.
+    NoHalt(u64),
+    Postcondition {
+        name: String,
+        output: Vec<Sym>,
+    },
+}
+
+/// The result of [`Contract::check`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContractReport<Sym> {
+    /// The number of inputs that met the preconditions and were run.
+    pub checked: usize,
+    /// Inputs left out, with the precondition they failed, or `None` if they
+    /// had characters that aren't symbols.
+    pub skipped: Vec<(String, Option<String>)>,
+    pub breaches: Vec<(String, Breach<Sym>)>,
+}
+
+impl<Sym> ContractReport<Sym> {
+    pub fn passed(&self) -> bool {
+        self.breaches.is_empty()
+    }
+}
+
+impl<'a, S, Sym> Contract<'a, S, Sym>
+where
+    S: Ord + Clone,
+    Sym: Symbol + Ord + Clone + Default,
+{
+    pub fn new(start: S) -> Self {
+        Self {
+            start,
+            requires: Vec::new(),
+            ensures: Vec::new(),
+        }
+    }
+
+    /// Adds a precondition on inputs.
+    pub fn requires(
+        mut self,
+        name: impl Into<String>,
+        check: impl Fn(&[Sym]) -> bool + 'a,
+    ) -> Self {
+        self.requires.push((name.into(), Box::new(check)));
+        self
+    }
+
+    /// Adds a postcondition on an input and the output the machine left for
+    /// it.
+    pub fn ensures(
+        mut self,
+        name: impl Into<String>,
+        check: impl Fn(&[Sym], &[Sym]) -> bool + 'a,
+    ) -> Self {
+        self.ensures.push((name.into(), Box::new(check)));
+        self
+    }
+
+    /// Runs `table` on each of `inputs` meeting the preconditions for at
+    /// most `max_steps` steps, checking that it halts and meets the
+    /// postconditions.
+    pub fn check<'i>(
+        &self,
+        table: &TransitionTable<S, Sym>,
+        inputs: impl IntoIterator<Item = &'i str>,
+        max_steps: u64,
+    ) -> ContractReport<Sym> {
+        let mut report = ContractReport {
+            checked: 0,
+            skipped: Vec::new(),
+            breaches: Vec::new(),
+        };
+
+        for input in inputs {
+            let Some(mut machine) = Machine::with_input(self.start.clone(), input) else {
+                report.skipped.push((input.to_owned(), None));
+                continue;
+            };
+            let symbols = trim(machine.tape_to_vec());
+
+            if let Some((name, _)) = self.requires.iter().find(|(_, check)| !check(&symbols)) {
+                report.skipped.push((input.to_owned(), Some(name.clone())));
+                continue;
+            }
+
+            report.checked += 1;
+
+            let stats = machine.run_table(table, max_steps);
+
+            if stats.stop != StopReason::Halted {
+                report
+                    .breaches
+                    .push((input.to_owned(), Breach::NoHalt(stats.steps)));
+                continue;
+            }
+
+            let (output, _, _) = machine.finish_trimmed();
+            let failed = self
+                .ensures
+                .iter()
+                .find(|(_, check)| !check(&symbols, &output));
+
+            if let Some((name, _)) = failed {
+                let breach = Breach::Postcondition {
+                    name: name.clone(),
+                    output,
+                };
+                report.breaches.push((input.to_owned(), breach));
+            }
+        }
+
+        report
+    }
+}
+
+fn trim<Sym: PartialEq + Default>(mut symbols: Vec<Sym>) -> Vec<Sym> {
+    let blank = Sym::default();
+    let end = symbols
+        .iter()
+        .rposition(|sym| *sym != blank)
+        .map_or(0, |i| i + 1);
+    symbols.truncate(end);
+
+    let start = symbols.iter().position(|sym| *sym != blank).unwrap_or(0);
+    symbols.drain(..start);
+    symbols
+}
+
+impl<Sym: Symbol> Display for ContractReport<Sym> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{}/{} inputs met the contract, {} skipped",
+            self.checked - self.breaches.len(),
+            self.checked,
+            self.skipped.len()
+        )?;
+
+        for (input, breach) in &self.breaches {
+            match breach {
+                Breach::NoHalt(steps) => {
+                    writeln!(f, "{input:?}: still running after {steps} steps")?
+                }
+                Breach::Postcondition { name, output } => {
+                    let output: String = output.iter().map(Symbol::to_char).collect();
+                    writeln!(f, "{input:?}: left {output:?}, breaking {name:?}")?
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<S: Debug, Sym> Debug for Contract<'_, S, Sym> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn names<T>(checks: &[(String, T)]) -> Vec<&str> {
+            checks.iter().map(|(name, _)| name.as_str()).collect()
+        }
+
+        f.debug_struct("Contract")
+            .field("start", &self.start)
+            .field("requires", &names(&self.requires))
+            .field("ensures", &names(&self.ensures))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::*;
+    use crate::{dsl, Char, Decode};
+
+    fn value(symbols: &[Char]) -> Option<u32> {
+        let bits: String = symbols.iter().map(|c| c.0).collect();
+        u32::decode(&bits)
+    }
+
+    #[test]
+    fn contract_test() {
+        // forgets the carry into a new cell
+        let table = dsl::parse::<Char>("inc 1 0 R *\ninc 0 1 N halt").unwrap();
+        let contract = Contract::new("inc".to_owned())
+            .requires("binary", |input: &[Char]| value(input).is_some())
+            .ensures("adds one", |input, output| {
+                value(output) == value(input).map(|n| n + 1)
+            });
+
+        let report = contract.check(&table, ["0", "01", "1x", "10", "11"], 100);
+
+        assert_eq!(report.checked, 4);
+        assert_eq!(
+            report.skipped,
+            [("1x".to_owned(), Some("binary".to_owned()))]
+        );
+        assert_eq!(report.breaches.len(), 1);
+        assert_eq!(report.breaches[0].0, "11");
+        assert_eq!(
+            report.to_string(),
+            "3/4 inputs met the contract, 1 skipped\n\"11\": left \"00\", breaking \"adds one\"\n"
+        );
+    }
+}
@@ -0,0 +1,187 @@
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+
+use crate::{Executor, Machine, RunStats, Tape, TransitionTable};
+
+/// A handle for pausing, resuming and single-stepping a run from another
+/// thread, e.g. the play and pause buttons of a GUI.
+///
+/// The running thread passes the handle to [`Machine::run_controlled`],
+/// which waits before each step while the run is paused. Clones share the
+/// same run.
+#[derive(Debug, Clone, Default)]
+pub struct RunControl {
+    shared: Arc<(Mutex<Controls>, Condvar)>,
+}
+
+#[derive(Debug, Default)]
+struct Controls {
+    paused: bool,
+    // steps let through while paused
+    steps: u64,
+    stopped: bool,
+}
+
+impl RunControl {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pauses the run before its next step.
+    pub fn pause(&self) {
+        self.update(|controls| controls.paused = true);
+    }
+
+    pub fn resume(&self) {
+        self.update(|controls| {
+            controls.paused = false;
+            controls.steps = 0;
+        });
+    }
+
+    /// Lets one more step through and then pauses, whether the run was
+    /// paused or not.
+    pub fn step_once(&self) {
+        self.update(|controls| {
+            controls.paused = true;
+            controls.steps += 1;
+        });
+    }
+
+    /// Ends the run as cancelled once the steps let through with
+    /// [`RunControl::step_once`] were taken, even while paused.
+    pub fn stop(&self) {
+        self.update(|controls| controls.stopped = true);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.lock().paused
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Controls> {
+        self.shared
+            .0
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn update(&self, change: impl FnOnce(&mut Controls)) {
+        change(&mut self.lock());
+        self.shared.1.notify_all();
+    }
+
+    // blocks until the next step may be taken, returning `false` if the run
+    // was stopped instead
+    fn wait(&self) -> bool {
+        let mut controls = self.lock();
+
+        loop {
+            if controls.paused && controls.steps > 0 {
+                controls.steps -= 1;
+                return true;
+            }
+
+            if controls.stopped {
+                return false;
+            }
+
+            if !controls.paused {
+                return true;
+            }
+
+            controls = self
+                .shared
+                .1
+                .wait(controls)
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+        }
+    }
+}
+
+impl<S, Sym, T> Machine<S, Sym, T>
+where
+    Sym: Default + Clone,
+    T: Tape<Sym>,
+{
+    /// Like [`Machine::run`], waiting before each step while `control` is
+    /// paused and stopping as cancelled when it is stopped.
+    pub fn run_controlled<E>(&mut self, max_steps: u64, control: &RunControl) -> RunStats
+    where
+        E: Executor<S, Sym>,
+    {
+        self.run_loop(
+            max_steps,
+            &AtomicBool::new(false),
+            1,
+            Self::step::<E>,
+            |_, _| control.wait(),
+        )
+    }
+
+    /// Like [`Machine::run_controlled`], looking rules up in `table`.
+    pub fn run_table_controlled(
+        &mut self,
+        table: &TransitionTable<S, Sym>,
+        max_steps: u64,
+        control: &RunControl,
+    ) -> RunStats
+    where
+        S: Ord + Clone,
+        Sym: Ord + Clone,
+    {
+        self.run_loop(
+            max_steps,
+            &AtomicBool::new(false),
+            1,
+            |machine| machine.step_table(table),
+            |_, _| control.wait(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+    use crate::{Rule, StopReason};
+
+    #[test]
+    fn run_control_test() {
+        // runs right forever
+        let table: TransitionTable<u8, u8> = [(0, 0, Rule::new().right())].into_iter().collect();
+        let control = RunControl::new();
+        control.pause();
+
+        let runner = {
+            let (control, table) = (control.clone(), table.clone());
+
+            thread::spawn(move || {
+                let mut machine: Machine<u8, u8> = Machine::new(0, [0].into());
+                machine.run_table_controlled(&table, 1_000, &control)
+            })
+        };
+
+        for _ in 0..3 {
+            control.step_once();
+        }
+
+        assert!(control.is_paused());
+        control.stop();
+
+        let stats = runner.join().unwrap();
+
+        assert_eq!(stats.steps, 3);
+        assert_eq!(stats.stop, StopReason::Cancelled);
+
+        let mut machine: Machine<u8, u8> = Machine::new(0, [0].into());
+        let control = RunControl::new();
+        control.step_once();
+        control.resume();
+
+        assert_eq!(
+            machine.run_table_controlled(&table, 10, &control).stop,
+            StopReason::StepLimit
+        );
+    }
+}
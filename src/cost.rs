@@ -0,0 +1,142 @@
+use alloc::collections::BTreeMap;
+use core::sync::atomic::AtomicBool;
+
+use crate::run::CANCEL_CHECK_INTERVAL;
+use crate::{Machine, RunStats, State, Tape, TransitionTable};
+
+/// The cost of applying each rule of a table, keyed like the table by the
+/// state and the symbol read, for runs where operations weigh differently.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Costs<S, Sym> {
+    costs: BTreeMap<(S, Sym), u64>,
+    default: u64,
+}
+
+/// The stats of a run by [`Machine::run_table_weighted`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WeightedStats {
+    pub stats: RunStats,
+    /// The sum of the costs of the rules applied.
+    pub cost: u64,
+}
+
+impl<S, Sym> Costs<S, Sym>
+where
+    S: Ord,
+    Sym: Ord,
+{
+    /// Costs of `default` for every rule without its own.
+    pub fn new(default: u64) -> Self {
+        Self {
+            costs: BTreeMap::new(),
+            default,
+        }
+    }
+
+    /// Sets the cost of the rule reading `symbol` in `state`, which applies
+    /// to a rule standing in by the table's missing rule policy too.
+    pub fn set(&mut self, state: S, symbol: Sym, cost: u64) {
+        self.costs.insert((state, symbol), cost);
+    }
+
+    pub fn with(mut self, state: S, symbol: Sym, cost: u64) -> Self {
+        self.set(state, symbol, cost);
+        self
+    }
+
+    pub fn get(&self, state: &S, symbol: &Sym) -> u64
+    where
+        S: Clone,
+        Sym: Clone,
+    {
+        let key = (state.clone(), symbol.clone());
+        self.costs.get(&key).copied().unwrap_or(self.default)
+    }
+
+    pub fn default_cost(&self) -> u64 {
+        self.default
+    }
+}
+
+impl<S, Sym> Default for Costs<S, Sym>
+where
+    S: Ord,
+    Sym: Ord,
+{
+    /// A cost of 1 for every rule, making the cost the step count.
+    fn default() -> Self {
+        Self::new(1)
+    }
+}
+
+impl<S, Sym, T> Machine<S, Sym, T>
+where
+    S: Ord + Clone,
+    Sym: Ord + Default + Clone,
+    T: Tape<Sym>,
+{
+    /// Like [`Machine::run_table`], also adding up the cost of every rule
+    /// applied, saturating at `u64::MAX`.
+    pub fn run_table_weighted(
+        &mut self,
+        table: &TransitionTable<S, Sym>,
+        costs: &Costs<S, Sym>,
+        max_steps: u64,
+    ) -> WeightedStats {
+        let mut cost = 0u64;
+
+        let stats = self.run_loop(
+            max_steps,
+            &AtomicBool::new(false),
+            CANCEL_CHECK_INTERVAL,
+            |machine| {
+                let step = match &machine.state {
+                    State::State(state) => costs.get(state, machine.current_symbol()),
+                    State::Halt => 0,
+                };
+
+                let applied = machine.step_table(table);
+
+                if applied {
+                    cost = cost.saturating_add(step);
+                }
+
+                applied
+            },
+            |_, _| true,
+        );
+
+        WeightedStats { stats, cost }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Rule;
+
+    #[test]
+    fn run_table_weighted_test() {
+        // writes cost 5 and moves over them 1
+        let table: TransitionTable<u8, u8> = [
+            (0, 0, Rule::to(1).write(1).right()),
+            (1, 0, Rule::to(2).write(1).right()),
+            (2, 1, Rule::to(3).left()),
+            (3, 1, Rule::halt()),
+        ]
+        .into_iter()
+        .collect();
+        let costs = Costs::new(1).with(0, 0, 5).with(1, 0, 5);
+
+        let mut machine: Machine<u8, u8> = Machine::new(0, [0, 0, 1].into());
+        let weighted = machine.run_table_weighted(&table, &costs, 100);
+
+        assert_eq!(weighted.stats.steps, 4);
+        assert_eq!(weighted.cost, 12);
+
+        let mut machine: Machine<u8, u8> = Machine::new(0, [0, 0, 1].into());
+        let unweighted = machine.run_table_weighted(&table, &Costs::default(), 100);
+
+        assert_eq!(unweighted.cost, unweighted.stats.steps);
+    }
+}
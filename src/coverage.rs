@@ -0,0 +1,121 @@
+use std::collections::BTreeMap;
+use std::fmt::{self, Debug, Display};
+
+use crate::{TraceSink, TraceStep, TransitionTable};
+
+/// A [`TraceSink`] counting how often each rule of a table was applied,
+/// over any number of runs traced into it.
+#[derive(Debug, Clone)]
+pub struct Coverage<'a, S, Sym> {
+    table: &'a TransitionTable<S, Sym>,
+    hits: BTreeMap<(S, Sym), u64>,
+}
+
+impl<'a, S, Sym> Coverage<'a, S, Sym>
+where
+    S: Ord + Clone,
+    Sym: Ord + Clone,
+{
+    pub fn new(table: &'a TransitionTable<S, Sym>) -> Self {
+        Self {
+            table,
+            hits: BTreeMap::new(),
+        }
+    }
+
+    /// How often the machine read `symbol` in `state`.
+    pub fn hits(&self, state: &S, symbol: &Sym) -> u64 {
+        let key = (state.clone(), symbol.clone());
+        self.hits.get(&key).copied().unwrap_or(0)
+    }
+
+    /// The rules of the table that were never applied.
+    pub fn uncovered(&self) -> Vec<(&'a S, &'a Sym)> {
+        self.table
+            .iter()
+            .filter(|(state, symbol, _)| self.hits(state, symbol) == 0)
+            .map(|(state, symbol, _)| (state, symbol))
+            .collect()
+    }
+
+    /// The symbols read in states without a rule for them, which halted the
+    /// machine.
+    pub fn undefined(&self) -> Vec<&(S, Sym)> {
+        self.hits
+            .keys()
+            .filter(|(state, symbol)| self.table.get(state, symbol).is_none())
+            .collect()
+    }
+
+    /// The number of rules applied at least once, and of rules in the table.
+    pub fn covered(&self) -> (usize, usize) {
+        let total = self.table.len();
+        (total - self.uncovered().len(), total)
+    }
+}
+
+impl<S, Sym> TraceSink<S, Sym> for Coverage<'_, S, Sym>
+where
+    S: Ord + Clone,
+    Sym: Ord + Clone,
+{
+    fn record(&mut self, step: &TraceStep<'_, S, Sym>) -> bool {
+        *self
+            .hits
+            .entry((step.state.clone(), step.read.clone()))
+            .or_insert(0) += 1;
+        true
+    }
+}
+
+impl<S, Sym> Display for Coverage<'_, S, Sym>
+where
+    S: Ord + Clone + Debug,
+    Sym: Ord + Clone + Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (covered, total) = self.covered();
+        writeln!(f, "{covered}/{total} rules covered")?;
+
+        for (state, symbol) in self.uncovered() {
+            writeln!(f, "uncovered: {state:?} reading {symbol:?}")?;
+        }
+
+        for (state, symbol) in self.undefined() {
+            writeln!(f, "undefined: {state:?} reading {symbol:?}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{dsl, Char, Machine};
+
+    #[test]
+    fn coverage_test() {
+        let table = dsl::parse::<Char>("inc 1 0 R *\ninc 0 1 N halt\ninc _ 1 N halt").unwrap();
+        let mut coverage = Coverage::new(&table);
+
+        for input in ["11", "1"] {
+            let mut machine = Machine::with_input("inc".to_owned(), input).unwrap();
+            machine.run_table_traced(&table, 100, &mut coverage);
+        }
+
+        assert_eq!(coverage.hits(&"inc".to_owned(), &Char('1')), 3);
+        assert_eq!(coverage.covered(), (2, 3));
+        assert_eq!(coverage.uncovered(), [(&"inc".to_owned(), &Char('0'))]);
+        assert_eq!(
+            coverage.to_string(),
+            "2/3 rules covered\nuncovered: \"inc\" reading Char('0')\n"
+        );
+
+        // reading a symbol without a rule
+        let mut machine = Machine::with_input("inc".to_owned(), "1x").unwrap();
+        machine.run_table_traced(&table, 100, &mut coverage);
+
+        assert_eq!(coverage.undefined(), [&("inc".to_owned(), Char('x'))]);
+    }
+}
@@ -0,0 +1,568 @@
+use alloc::collections::VecDeque;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt::{self, Display};
+use core::iter;
+
+use core::sync::atomic::AtomicBool;
+
+use crate::run::CANCEL_CHECK_INTERVAL;
+use crate::{
+    Executor, FixedTape, Machine, MissingRule, Snapshot, State, StopReason, Symbol, Tape,
+    TransitionTable,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Accept,
+    Reject,
+}
+
+impl Verdict {
+    pub fn is_accept(self) -> bool {
+        self == Self::Accept
+    }
+}
+
+/// Returned when a decider reaches neither verdict within its step limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Undecided {
+    pub steps: u64,
+}
+
+impl Display for Undecided {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "no verdict after {} steps", self.steps)
+    }
+}
+
+impl Error for Undecided {}
+
+/// An executor with a start state and accept and reject states.
+///
+/// Entering a state with a verdict ends the run with that verdict; halting
+/// without one rejects.
+pub trait Decider<S, Sym: Default>: Executor<S, Sym> {
+    fn start() -> S;
+
+    fn verdict(state: &S) -> Option<Verdict>;
+}
+
+/// When a halted run counts as accepting. Runs that have not halted are
+/// undecided.
+pub enum Acceptance<'a, S, Sym> {
+    /// Every halting run accepts.
+    Halting,
+    /// The machine halts from one of these states, that is, it was in one of
+    /// them when it read a symbol it had no rule for or applied a halting
+    /// rule.
+    FinalState(Vec<S>),
+    /// The machine halts with nothing but blanks on the tape.
+    BlankTape,
+    /// A predicate over the final configuration, with the state the machine
+    /// halted from in place of [`State::Halt`].
+    Custom(&'a dyn Fn(&Snapshot<S, Sym>) -> bool),
+}
+
+impl<S, Sym, T> Machine<S, Sym, T>
+where
+    S: Clone + PartialEq,
+    Sym: Default + Clone + PartialEq,
+    T: Tape<Sym>,
+{
+    /// Runs the machine for at most `max_steps` steps and judges the halted
+    /// configuration by `acceptance`.
+    pub fn run_accepting<E>(
+        &mut self,
+        max_steps: u64,
+        acceptance: &Acceptance<'_, S, Sym>,
+    ) -> Result<Verdict, Undecided>
+    where
+        E: Executor<S, Sym>,
+    {
+        self.run_accepting_with(max_steps, acceptance, Self::step::<E>)
+    }
+
+    /// Like [`Machine::run_accepting`], looking rules up in `table`.
+    pub fn run_table_accepting(
+        &mut self,
+        table: &TransitionTable<S, Sym>,
+        max_steps: u64,
+        acceptance: &Acceptance<'_, S, Sym>,
+    ) -> Result<Verdict, Undecided>
+    where
+        S: Ord,
+        Sym: Ord,
+    {
+        let mut rejected = false;
+        let verdict = self.run_accepting_with(max_steps, acceptance, |machine| {
+            if let State::State(state) = &machine.state {
+                rejected = *table.missing() == MissingRule::Reject
+                    && table.get(state, machine.current_symbol()).is_none();
+            }
+
+            machine.step_table(table)
+        })?;
+
+        Ok(match rejected {
+            true => Verdict::Reject,
+            false => verdict,
+        })
+    }
+
+    fn run_accepting_with(
+        &mut self,
+        max_steps: u64,
+        acceptance: &Acceptance<'_, S, Sym>,
+        mut step: impl FnMut(&mut Self) -> bool,
+    ) -> Result<Verdict, Undecided> {
+        let mut last = None;
+        let stats = self.run_loop(
+            max_steps,
+            &AtomicBool::new(false),
+            CANCEL_CHECK_INTERVAL,
+            |machine| {
+                if let State::State(state) = &machine.state {
+                    last = Some(state.clone());
+                }
+
+                step(machine)
+            },
+            |_, _| true,
+        );
+
+        if stats.stop != StopReason::Halted {
+            return Err(Undecided { steps: stats.steps });
+        }
+
+        Ok(self.judge(acceptance, last))
+    }
+
+    // the verdict on a halted run that was in `last` before halting
+    fn judge(&self, acceptance: &Acceptance<'_, S, Sym>, last: Option<S>) -> Verdict {
+        let accepted = match acceptance {
+            Acceptance::Halting => true,
+            Acceptance::FinalState(states) => last.is_some_and(|last| states.contains(&last)),
+            Acceptance::BlankTape => {
+                (0..self.tape.len()).all(|index| self.tape.get(index) == Some(&self.blank))
+            }
+            Acceptance::Custom(accepts) => {
+                let mut snapshot = self.snapshot();
+
+                if let Some(last) = last {
+                    snapshot.state = State::State(last);
+                }
+
+                accepts(&snapshot)
+            }
+        };
+
+        match accepted {
+            true => Verdict::Accept,
+            false => Verdict::Reject,
+        }
+    }
+}
+
+/// Returned by [`Machine::decide_bounded`] and
+/// [`Machine::decide_table_bounded`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Decided {
+    pub verdict: Verdict,
+    pub steps: u64,
+    /// Whether the run repeated a configuration, so would never halt, and
+    /// was rejected for it.
+    pub looped: bool,
+}
+
+impl<S, Sym, const N: usize> Machine<S, Sym, FixedTape<Sym, N>>
+where
+    S: Clone + PartialEq,
+    Sym: Default + Clone + PartialEq,
+{
+    /// Runs a linear bounded automaton to a verdict by `acceptance`, without
+    /// a step limit: on a tape of at most `N` cells there are finitely many
+    /// configurations, so a run that doesn't halt repeats one, and rejects.
+    ///
+    /// Repeats are found by Brent's algorithm, keeping a single earlier
+    /// configuration, within about twice the steps to the end of the first
+    /// loop. A run stuck without a rule, under [`MissingRule::Error`],
+    /// rejects too.
+    pub fn decide_bounded<E>(&mut self, acceptance: &Acceptance<'_, S, Sym>) -> Decided
+    where
+        E: Executor<S, Sym>,
+    {
+        self.decide_bounded_with(acceptance, Self::step::<E>)
+    }
+
+    /// Like [`Machine::decide_bounded`], looking rules up in `table`.
+    pub fn decide_table_bounded(
+        &mut self,
+        table: &TransitionTable<S, Sym>,
+        acceptance: &Acceptance<'_, S, Sym>,
+    ) -> Decided
+    where
+        S: Ord,
+        Sym: Ord,
+    {
+        let mut rejected = false;
+        let mut decided = self.decide_bounded_with(acceptance, |machine| {
+            if let State::State(state) = &machine.state {
+                rejected = *table.missing() == MissingRule::Reject
+                    && table.get(state, machine.current_symbol()).is_none();
+            }
+
+            machine.step_table(table)
+        });
+
+        if rejected {
+            decided.verdict = Verdict::Reject;
+        }
+
+        decided
+    }
+
+    fn decide_bounded_with(
+        &mut self,
+        acceptance: &Acceptance<'_, S, Sym>,
+        mut step: impl FnMut(&mut Self) -> bool,
+    ) -> Decided {
+        let mut last = None;
+        let mut saved = (self.state.clone(), self.head, self.tape.clone());
+        let (mut power, mut length, mut steps) = (1, 0, 0);
+
+        while let State::State(state) = &self.state {
+            last = Some(state.clone());
+
+            if !step(self) {
+                return Decided {
+                    verdict: Verdict::Reject,
+                    steps,
+                    looped: false,
+                };
+            }
+
+            steps += 1;
+            length += 1;
+
+            let (state, head, tape) = &saved;
+            let repeated = self.state == *state
+                && self.head == *head
+                && self.tape.len() == tape.len()
+                && (0..tape.len()).all(|index| self.tape.get(index) == tape.get(index));
+
+            if repeated {
+                return Decided {
+                    verdict: Verdict::Reject,
+                    steps,
+                    looped: true,
+                };
+            }
+
+            if length == power {
+                saved = (self.state.clone(), self.head, self.tape.clone());
+                power *= 2;
+                length = 0;
+            }
+        }
+
+        Decided {
+            verdict: self.judge(acceptance, last),
+            steps,
+            looped: false,
+        }
+    }
+}
+
+impl<S, Sym> Machine<S, Sym, VecDeque<Sym>>
+where
+    Sym: Symbol + Default + Clone,
+{
+    /// Runs `D` on `input` for at most `max_steps` steps. Inputs with
+    /// characters outside the alphabet of `Sym` are rejected.
+    pub fn accepts<D>(input: &str, max_steps: u64) -> Result<Verdict, Undecided>
+    where
+        D: Decider<S, Sym>,
+    {
+        let Some(mut machine) = Self::with_input(D::start(), input) else {
+            return Ok(Verdict::Reject);
+        };
+
+        for _ in 0..max_steps {
+            let State::State(state) = &machine.state else {
+                return Ok(Verdict::Reject);
+            };
+
+            if let Some(verdict) = D::verdict(state) {
+                return Ok(verdict);
+            }
+
+            machine.step::<D>();
+        }
+
+        match &machine.state {
+            State::State(state) => D::verdict(state).ok_or(Undecided { steps: max_steps }),
+            State::Halt => Ok(Verdict::Reject),
+        }
+    }
+
+    /// Whether `D` reaches a verdict on every one of `inputs` within
+    /// `max_steps` steps each.
+    pub fn decides<'a, D>(inputs: impl IntoIterator<Item = &'a str>, max_steps: u64) -> bool
+    where
+        D: Decider<S, Sym>,
+    {
+        inputs
+            .into_iter()
+            .all(|input| Self::accepts::<D>(input, max_steps).is_ok())
+    }
+
+    /// The strings over `alphabet` of at most `max_len` characters that `D`
+    /// accepts within `max_steps` steps, shortest first and in alphabet order
+    /// within each length. Strings without a verdict are skipped.
+    pub fn accepted<'a, D>(
+        alphabet: &'a [char],
+        max_len: usize,
+        max_steps: u64,
+    ) -> impl Iterator<Item = String> + 'a
+    where
+        D: Decider<S, Sym>,
+    {
+        // the digits of the next string, in base `alphabet.len()`
+        let mut digits = Some(Vec::new());
+
+        iter::from_fn(move || {
+            let current: &mut Vec<usize> = digits.as_mut()?;
+            let input: String = current.iter().map(|&digit| alphabet[digit]).collect();
+
+            match current
+                .iter()
+                .rposition(|&digit| digit + 1 < alphabet.len())
+            {
+                Some(position) => {
+                    current[position] += 1;
+                    current[position + 1..].fill(0);
+                }
+                None if current.len() < max_len && !alphabet.is_empty() => {
+                    current.fill(0);
+                    current.push(0);
+                }
+                None => digits = None,
+            }
+
+            Some(input)
+        })
+        .filter(move |input| Self::accepts::<D>(input, max_steps) == Ok(Verdict::Accept))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+    use crate::{Overflow, Rule};
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Parity {
+        Even,
+        Odd,
+        Accept,
+        Reject,
+    }
+
+    struct EvenOnes;
+
+    // accepts binary strings with an even number of ones
+    impl Executor<Parity, bool> for EvenOnes {
+        fn execute(state: &Parity, symbol: &bool) -> Rule<Parity, bool> {
+            match (state, symbol) {
+                (Parity::Even, true) => Rule::to(Parity::Odd).right(),
+                (Parity::Odd, true) => Rule::to(Parity::Even).right(),
+                (Parity::Even, false) => Rule::to(Parity::Accept),
+                (Parity::Odd, false) => Rule::to(Parity::Reject),
+                _ => Rule::halt(),
+            }
+        }
+    }
+
+    impl Decider<Parity, bool> for EvenOnes {
+        fn start() -> Parity {
+            Parity::Even
+        }
+
+        fn verdict(state: &Parity) -> Option<Verdict> {
+            match state {
+                Parity::Accept => Some(Verdict::Accept),
+                Parity::Reject => Some(Verdict::Reject),
+                _ => None,
+            }
+        }
+    }
+
+    struct Forever;
+
+    impl Executor<Parity, bool> for Forever {
+        fn execute(_state: &Parity, _symbol: &bool) -> Rule<Parity, bool> {
+            Rule::new().right()
+        }
+    }
+
+    impl Decider<Parity, bool> for Forever {
+        fn start() -> Parity {
+            Parity::Even
+        }
+
+        fn verdict(_state: &Parity) -> Option<Verdict> {
+            None
+        }
+    }
+
+    type M = Machine<Parity, bool>;
+
+    #[test]
+    fn accepts_test() {
+        // the input is followed by a blank 0, ending the scan
+        assert_eq!(M::accepts::<EvenOnes>("11", 100), Ok(Verdict::Accept));
+        assert_eq!(M::accepts::<EvenOnes>("1", 100), Ok(Verdict::Reject));
+        assert_eq!(M::accepts::<EvenOnes>("", 100), Ok(Verdict::Accept));
+        assert_eq!(M::accepts::<EvenOnes>("12", 100), Ok(Verdict::Reject));
+        assert_eq!(
+            M::accepts::<EvenOnes>("1111", 2),
+            Err(Undecided { steps: 2 })
+        );
+        assert!(M::accepts::<EvenOnes>("11", 3).unwrap().is_accept());
+    }
+
+    #[test]
+    fn accepted_test() {
+        let language: Vec<String> = M::accepted::<EvenOnes>(&['0', '1'], 3, 100).collect();
+
+        // the scan stops at the first 0, which doubles as the blank
+        assert_eq!(
+            language,
+            ["", "0", "00", "01", "11", "000", "001", "010", "011", "110"]
+        );
+        assert_eq!(M::accepted::<EvenOnes>(&[], 3, 100).count(), 1);
+    }
+
+    #[test]
+    fn acceptance_test() {
+        let table: TransitionTable<u8, bool> = [
+            (0, true, Rule::new().write(false).right()),
+            (0, false, Rule::to(1)),
+        ]
+        .into_iter()
+        .collect();
+        let run = |input: &[bool], acceptance: &Acceptance<'_, u8, bool>| {
+            let mut machine: Machine<u8, bool> = Machine::new(0, input.iter().copied().collect());
+            machine.run_table_accepting(&table, 100, acceptance)
+        };
+
+        // clears ones until the first zero, then halts from state 1
+        assert_eq!(run(&[true], &Acceptance::Halting), Ok(Verdict::Accept));
+        assert_eq!(
+            run(&[true], &Acceptance::FinalState(vec![1])),
+            Ok(Verdict::Accept)
+        );
+        assert_eq!(
+            run(&[true], &Acceptance::FinalState(vec![0])),
+            Ok(Verdict::Reject)
+        );
+        assert_eq!(
+            run(&[true, true], &Acceptance::BlankTape),
+            Ok(Verdict::Accept)
+        );
+        assert_eq!(
+            run(&[true, false, true], &Acceptance::BlankTape),
+            Ok(Verdict::Reject)
+        );
+
+        // with `true` as the blank, a tape of only `true`s is blank
+        let fill: TransitionTable<u8, bool> = [
+            (0, false, Rule::new().write(true).right()),
+            (0, true, Rule::halt()),
+        ]
+        .into_iter()
+        .collect();
+        let mut machine: Machine<u8, bool> =
+            Machine::new(0, [false, false].into()).with_blank(true);
+
+        assert_eq!(
+            machine.run_table_accepting(&fill, 100, &Acceptance::BlankTape),
+            Ok(Verdict::Accept)
+        );
+
+        let head_at_two = |snapshot: &Snapshot<u8, bool>| snapshot.head == 2;
+
+        assert_eq!(
+            run(&[true, true], &Acceptance::Custom(&head_at_two)),
+            Ok(Verdict::Accept)
+        );
+
+        // halting from state 1 by a missing rule rejects under any acceptance
+        let table = table.with_missing(MissingRule::Reject);
+        let mut machine: Machine<u8, bool> = Machine::new(0, [true].into());
+
+        assert_eq!(
+            machine.run_table_accepting(&table, 100, &Acceptance::Halting),
+            Ok(Verdict::Reject)
+        );
+
+        let mut machine: Machine<Parity, bool> = Machine::new(Parity::Even, [false].into());
+
+        assert_eq!(
+            machine.run_accepting::<Forever>(10, &Acceptance::Halting),
+            Err(Undecided { steps: 10 })
+        );
+    }
+
+    #[test]
+    fn decides_test() {
+        assert!(M::decides::<EvenOnes>(["", "1", "101", "111"], 100));
+        assert!(!M::decides::<Forever>(["", "1"], 100));
+    }
+
+    #[test]
+    fn decide_bounded_test() {
+        let tape = FixedTape::<bool, 4>::from_slice(&[true, true], Overflow::Stay).unwrap();
+        let mut machine = Machine::new(Parity::Even, tape);
+        let accept = Acceptance::FinalState(vec![Parity::Accept]);
+
+        assert_eq!(
+            machine.decide_bounded::<EvenOnes>(&accept),
+            Decided {
+                verdict: Verdict::Accept,
+                steps: 4,
+                looped: false
+            }
+        );
+
+        // stuck moving right against the end of the tape
+        let mut machine = Machine::new(Parity::Even, FixedTape::<bool, 2>::new(Overflow::Stay));
+        let decided = machine.decide_bounded::<Forever>(&Acceptance::Halting);
+
+        assert_eq!(decided.verdict, Verdict::Reject);
+        assert!(decided.looped);
+
+        // bouncing between the ends of the input without a limit on steps
+        let table: TransitionTable<u8, bool> = [
+            (0, true, Rule::new().right()),
+            (0, false, Rule::to(1).left()),
+            (1, true, Rule::new().left()),
+            (1, false, Rule::to(0).right()),
+        ]
+        .into_iter()
+        .collect();
+        let tape = FixedTape::<bool, 4>::from_slice(&[false, true, true, false], Overflow::Stay);
+        let mut machine = Machine::new(0, tape.unwrap());
+        machine.set_head(1);
+        let decided = machine.decide_table_bounded(&table, &Acceptance::Halting);
+
+        // a loop of 6 steps, seen again 6 steps after the configuration
+        // saved at step 7
+        assert!(decided.looped);
+        assert_eq!(decided.steps, 13);
+    }
+}
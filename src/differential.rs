@@ -0,0 +1,140 @@
+//! Differential testing: the same machine stepped by two implementations,
+//! say an [`Executor`](crate::Executor) and the table it was translated to,
+//! in lockstep until their configurations first differ.
+
+use core::fmt::{self, Debug, Display};
+
+use crate::{Machine, Snapshot, Tape};
+
+/// Returned by [`lockstep`] when the two machines disagree after `step`
+/// steps.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch<S, Sym> {
+    pub step: u64,
+    pub left: Snapshot<S, Sym>,
+    pub right: Snapshot<S, Sym>,
+}
+
+/// Steps `left` with `step_left` and `right` with `step_right` for at most
+/// `max_steps` steps, comparing their states, heads and tapes after each
+/// step. Returns the number of steps until both halted, or `max_steps`.
+///
+/// Tapes are lined up on their initial cell 0, with cells past either end
+/// read as blanks, so a tape that has grown further doesn't count as a
+/// difference by itself.
+pub fn lockstep<S, Sym, T, U>(
+    left: &mut Machine<S, Sym, T>,
+    right: &mut Machine<S, Sym, U>,
+    mut step_left: impl FnMut(&mut Machine<S, Sym, T>),
+    mut step_right: impl FnMut(&mut Machine<S, Sym, U>),
+    max_steps: u64,
+) -> Result<u64, Mismatch<S, Sym>>
+where
+    S: Clone + PartialEq,
+    Sym: Clone + PartialEq + Default,
+    T: Tape<Sym>,
+    U: Tape<Sym>,
+{
+    let compare = |step, left: &Machine<S, Sym, T>, right: &Machine<S, Sym, U>| {
+        let (left, right) = (left.snapshot(), right.snapshot());
+
+        match left.diff(&right).is_empty() {
+            true => Ok(()),
+            false => Err(Mismatch { step, left, right }),
+        }
+    };
+
+    compare(0, left, right)?;
+
+    for step in 1..=max_steps {
+        if left.halted() && right.halted() {
+            return Ok(step - 1);
+        }
+
+        step_left(left);
+        step_right(right);
+        compare(step, left, right)?;
+    }
+
+    Ok(max_steps)
+}
+
+impl<S: Debug, Sym: Debug> Display for Mismatch<S, Sym> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "after {} steps the machines differ: {:?} and {:?}",
+            self.step, self.left, self.right
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Char, Executor, Rule, State, TransitionTable};
+
+    struct Increment;
+
+    impl Executor<&'static str, Char> for Increment {
+        fn execute(_state: &&'static str, symbol: &Char) -> Rule<&'static str, Char> {
+            match symbol.0 {
+                '1' => Rule::new().write(Char('0')).right(),
+                _ => Rule::halt().write(Char('1')),
+            }
+        }
+    }
+
+    // forgets to carry past the second bit
+    struct Broken;
+
+    impl Executor<&'static str, Char> for Broken {
+        fn execute(state: &&'static str, symbol: &Char) -> Rule<&'static str, Char> {
+            match (*state, symbol.0) {
+                ("inc", '1') => Rule::to("carried").write(Char('0')).right(),
+                _ => Rule::halt().write(Char('1')),
+            }
+        }
+    }
+
+    #[test]
+    fn lockstep_test() {
+        let table: TransitionTable<_, _> = [
+            ("inc", Char('1'), Rule::new().write(Char('0')).right()),
+            ("inc", Char('0'), Rule::halt().write(Char('1'))),
+            ("inc", Char('_'), Rule::halt().write(Char('1'))),
+        ]
+        .into_iter()
+        .collect();
+        let machine = || Machine::with_input("inc", "110").unwrap();
+
+        let steps = lockstep(
+            &mut machine(),
+            &mut machine(),
+            |machine| {
+                machine.execute::<Increment>();
+            },
+            |machine| {
+                machine.execute_table(&table);
+            },
+            100,
+        );
+        assert_eq!(steps, Ok(3));
+
+        let mismatch = lockstep(
+            &mut machine(),
+            &mut machine(),
+            |machine| {
+                machine.execute::<Increment>();
+            },
+            |machine| {
+                machine.execute::<Broken>();
+            },
+            100,
+        )
+        .unwrap_err();
+
+        assert_eq!(mismatch.step, 1);
+        assert_eq!(mismatch.right.state, State::State("carried"));
+    }
+}
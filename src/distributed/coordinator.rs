@@ -0,0 +1,355 @@
+use std::collections::BTreeMap;
+use std::fmt::{self, Display};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufReader};
+use std::net::TcpListener;
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::{Message, Summary, UnitResult, WorkUnit};
+
+/// How long a worker has for a unit before it's handed out again.
+pub const DEFAULT_LEASE_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// The longest a worker is asked to wait before asking for a unit again.
+const MAX_WAIT: u64 = 10;
+
+/// How long a connection has to send its request.
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Hands out units, leasing each to one worker at a time, and merges their
+/// results.
+///
+/// A unit whose lease runs out goes back to the queue; whichever result for
+/// it arrives first is merged. With a log file, every merged result is
+/// appended to it, and a coordinator opened on the same file skips the units
+/// it has results for.
+#[derive(Debug)]
+pub struct Coordinator {
+    pending: BTreeMap<u64, WorkUnit>,
+    leases: BTreeMap<u64, Lease>,
+    lease_timeout: Duration,
+    summary: Summary,
+    units: u64,
+    log: Option<File>,
+}
+
+#[derive(Debug, Clone)]
+struct Lease {
+    unit: WorkUnit,
+    worker: String,
+    expires: Instant,
+}
+
+/// How far a search has come.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    pub units: u64,
+    pub done: u64,
+    pub leased: u64,
+    pub machines: u64,
+}
+
+impl Display for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}/{} units done, {} leased, {} tables run",
+            self.done, self.units, self.leased, self.machines
+        )
+    }
+}
+
+impl Coordinator {
+    pub fn new(units: impl IntoIterator<Item = WorkUnit>) -> Self {
+        let pending: BTreeMap<u64, WorkUnit> =
+            units.into_iter().map(|unit| (unit.id, unit)).collect();
+
+        Self {
+            units: pending.len() as u64,
+            pending,
+            leases: BTreeMap::new(),
+            lease_timeout: DEFAULT_LEASE_TIMEOUT,
+            summary: Summary::new(),
+            log: None,
+        }
+    }
+
+    /// A coordinator for `units` logging results to `path`, resuming from the
+    /// results already there. A truncated last result, left by a crash while
+    /// writing it, is dropped; results for units not in `units` are an
+    /// error.
+    pub fn open(
+        units: impl IntoIterator<Item = WorkUnit>,
+        path: impl AsRef<Path>,
+    ) -> io::Result<Self> {
+        let mut coordinator = Self::new(units);
+        let path = path.as_ref();
+
+        if path.exists() {
+            let bytes = fs::read(path)?;
+            let mut rest = &bytes[..];
+            // the length of the complete results
+            let mut valid = 0;
+
+            while !rest.is_empty() {
+                match Message::read(&mut rest) {
+                    Ok(Message::Result(result)) if coordinator.pending.contains_key(&result.id) => {
+                        coordinator.merge(result)
+                    }
+                    Ok(Message::Result(result)) => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!(
+                                "result in the log for unit {}, which isn't part of the search",
+                                result.id
+                            ),
+                        ))
+                    }
+                    Ok(message) => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!("not a result in the log: {message:?}"),
+                        ))
+                    }
+                    Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => break,
+                    Err(error) => return Err(error),
+                }
+
+                valid = bytes.len() - rest.len();
+            }
+
+            OpenOptions::new()
+                .write(true)
+                .open(path)?
+                .set_len(valid as u64)?;
+        }
+
+        coordinator.log = Some(OpenOptions::new().create(true).append(true).open(path)?);
+
+        Ok(coordinator)
+    }
+
+    pub fn with_lease_timeout(self, lease_timeout: Duration) -> Self {
+        Self {
+            lease_timeout,
+            ..self
+        }
+    }
+
+    pub fn summary(&self) -> &Summary {
+        &self.summary
+    }
+
+    pub fn progress(&self) -> Progress {
+        Progress {
+            units: self.units,
+            done: self.summary.units.len() as u64,
+            leased: self.leases.len() as u64,
+            machines: self.summary.machines,
+        }
+    }
+
+    /// Whether every unit has a result.
+    pub fn is_done(&self) -> bool {
+        self.pending.is_empty() && self.leases.is_empty()
+    }
+
+    /// The answer to `request` at time `now`.
+    pub fn handle(&mut self, request: Message, now: Instant) -> io::Result<Message> {
+        match request {
+            Message::Lease { worker } => Ok(self.lease(worker, now)),
+            Message::Result(result) => {
+                let id = result.id;
+
+                if self.pending.contains_key(&id) || self.leases.contains_key(&id) {
+                    if let Some(log) = &mut self.log {
+                        Message::Result(result.clone()).write(log)?;
+                        log.sync_data()?;
+                    }
+
+                    self.merge(result);
+                }
+
+                Ok(Message::Ok)
+            }
+            request => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unexpected request {request:?}"),
+            )),
+        }
+    }
+
+    /// The worker holding the lease on each leased unit.
+    pub fn leases(&self) -> impl Iterator<Item = (&WorkUnit, &str)> {
+        self.leases
+            .values()
+            .map(|lease| (&lease.unit, lease.worker.as_str()))
+    }
+
+    fn lease(&mut self, worker: String, now: Instant) -> Message {
+        let expired: Vec<u64> = self
+            .leases
+            .iter()
+            .filter(|(_, lease)| lease.expires <= now)
+            .map(|(&id, _)| id)
+            .collect();
+
+        for id in expired {
+            let lease = self.leases.remove(&id).unwrap();
+            self.pending.insert(id, lease.unit);
+        }
+
+        if let Some((id, unit)) = self.pending.pop_first() {
+            self.leases.insert(
+                id,
+                Lease {
+                    unit,
+                    worker,
+                    expires: now + self.lease_timeout,
+                },
+            );
+
+            return Message::Unit(unit);
+        }
+
+        match self.leases.values().map(|lease| lease.expires).min() {
+            Some(expires) => Message::Wait {
+                seconds: (expires - now).as_secs().clamp(1, MAX_WAIT),
+            },
+            None => Message::Done,
+        }
+    }
+
+    fn merge(&mut self, result: UnitResult) {
+        self.pending.remove(&result.id);
+        self.leases.remove(&result.id);
+        self.summary.merge(result);
+    }
+
+    /// Answers workers connecting to `listener`, calling `progress` after
+    /// every result, until every unit has a result and no worker
+    /// has asked for a unit for a lease timeout. Failed connections are
+    /// dropped; failing to write the log ends serving.
+    pub fn serve(
+        &mut self,
+        listener: &TcpListener,
+        mut progress: impl FnMut(Progress),
+    ) -> io::Result<()> {
+        listener.set_nonblocking(true)?;
+        let mut last_request = Instant::now();
+
+        loop {
+            let stream = match listener.accept() {
+                Ok((stream, _)) => stream,
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => {
+                    if self.is_done() && last_request.elapsed() >= self.lease_timeout {
+                        return Ok(());
+                    }
+
+                    thread::sleep(Duration::from_millis(10));
+                    continue;
+                }
+                Err(error) => return Err(error),
+            };
+
+            last_request = Instant::now();
+
+            let request = stream
+                .set_nonblocking(false)
+                .and_then(|()| stream.set_read_timeout(Some(READ_TIMEOUT)))
+                .and_then(|()| Message::read(&mut BufReader::new(&stream)));
+            let Ok(request) = request else {
+                continue;
+            };
+
+            let merged = matches!(request, Message::Result(_));
+            let answer = self.handle(request, Instant::now())?;
+            let _ = answer.write(&mut &stream);
+
+            if merged {
+                progress(self.progress());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::io::Write;
+
+    use super::*;
+    use crate::distributed::{partition, Worker};
+
+    fn lease(coordinator: &mut Coordinator, now: Instant) -> Message {
+        let request = Message::Lease {
+            worker: "w".to_owned(),
+        };
+        coordinator.handle(request, now).unwrap()
+    }
+
+    #[test]
+    fn lease_test() {
+        let units: Vec<WorkUnit> = partition(1, 2, 100, 50).unwrap().collect();
+        let mut coordinator =
+            Coordinator::new(units.clone()).with_lease_timeout(Duration::from_secs(30));
+        let now = Instant::now();
+
+        assert_eq!(lease(&mut coordinator, now), Message::Unit(units[0]));
+        assert_eq!(lease(&mut coordinator, now), Message::Unit(units[1]));
+        assert_eq!(lease(&mut coordinator, now), Message::Wait { seconds: 10 });
+
+        // the first lease runs out and its unit goes to the next worker
+        let later = now + Duration::from_secs(31);
+        assert_eq!(lease(&mut coordinator, later), Message::Unit(units[0]));
+
+        for unit in &units {
+            let result = Message::Result(unit.run());
+            assert_eq!(coordinator.handle(result, later).unwrap(), Message::Ok);
+        }
+
+        assert!(coordinator.is_done());
+        assert_eq!(lease(&mut coordinator, later), Message::Done);
+        assert_eq!(coordinator.progress().machines, 81);
+    }
+
+    #[test]
+    fn log_test() {
+        let path = env::temp_dir().join(format!("turing-coordinator-{}.log", std::process::id()));
+        let units: Vec<WorkUnit> = partition(1, 2, 100, 30).unwrap().collect();
+        let _ = std::fs::remove_file(&path);
+
+        let mut coordinator = Coordinator::open(units.clone(), &path).unwrap();
+        for unit in &units[..2] {
+            coordinator
+                .handle(Message::Result(unit.run()), Instant::now())
+                .unwrap();
+        }
+        drop(coordinator);
+
+        // a crash in the middle of writing a result
+        let mut log = OpenOptions::new().append(true).open(&path).unwrap();
+        writeln!(log, "result 2 21 3").unwrap();
+        drop(log);
+
+        let mut coordinator = Coordinator::open(units.clone(), &path)
+            .unwrap()
+            .with_lease_timeout(Duration::from_millis(500));
+        assert_eq!(coordinator.progress().done, 2);
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let worker = thread::spawn(move || Worker::new(address, "w").run().unwrap());
+
+        coordinator.serve(&listener, |_| {}).unwrap();
+
+        assert_eq!(worker.join().unwrap(), 1);
+        assert_eq!(coordinator.summary().machines, 81);
+
+        let coordinator = Coordinator::open(units, &path).unwrap();
+        assert!(coordinator.is_done());
+        let _ = std::fs::remove_file(&path);
+    }
+}
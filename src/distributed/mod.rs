@@ -0,0 +1,465 @@
+//! Busy beaver style searches split across machines: a coordinator hands
+//! out [`WorkUnit`]s, ranges of the [`number`](crate::format::number)s of
+//! every table of a size, to workers that run each table from a blank tape
+//! and send back a [`UnitResult`].
+//!
+//! Workers talk to the coordinator over TCP, one request and its answer per
+//! connection. A message is a line of space-separated fields, possibly
+//! followed by more lines, and ends with a line `end`:
+//!
+//! | request                       | answer                                    |
+//! |-------------------------------|-------------------------------------------|
+//! | `lease <worker>`              | `unit <id> <states> <symbols> <start> <end> <max steps>`, `wait <seconds>` or `done` |
+//! | `result <id> <machines> <halted>` | `ok`                                  |
+//!
+//! A result is followed by a line `champion <number> <steps> <sigma>` for
+//! its longest running halting table if it has one, a line
+//! `forever <number> <steps>` for every table shown to run forever and a
+//! line `undecided <number>` for every other one. Results for units already
+//! merged are answered `ok` and dropped, so a [`Coordinator`] can hand a
+//! unit out again when its worker seems lost.
+
+mod coordinator;
+mod worker;
+
+pub use coordinator::{Coordinator, Progress, DEFAULT_LEASE_TIMEOUT};
+pub use worker::Worker;
+
+use std::collections::BTreeSet;
+use std::io::{self, BufRead, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+
+use crate::accelerate::{Accelerator, Outcome};
+use crate::dsl::Definition;
+use crate::format::number::{self, Number};
+
+/// The tables numbered `start..end` with `states` states and `symbols`
+/// symbols, each run for up to `max_steps` steps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WorkUnit {
+    pub id: u64,
+    pub states: usize,
+    pub symbols: usize,
+    pub start: u128,
+    pub end: u128,
+    pub max_steps: u64,
+}
+
+/// Splits the tables with `states` states and `symbols` symbols into units
+/// of `size` tables, numbered from 0. `None` if their number doesn't fit a
+/// `u128`.
+///
+/// # Panics
+///
+/// If `size` is 0.
+pub fn partition(
+    states: usize,
+    symbols: usize,
+    max_steps: u64,
+    size: u128,
+) -> Option<impl Iterator<Item = WorkUnit>> {
+    assert!(size > 0, "units need tables");
+
+    let count = Number::count(states, symbols)?;
+
+    Some((0..count.div_ceil(size)).map(move |index| WorkUnit {
+        id: index as u64,
+        states,
+        symbols,
+        start: index * size,
+        end: (index * size).saturating_add(size).min(count),
+        max_steps,
+    }))
+}
+
+/// How running a table from a blank tape ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    Halted {
+        steps: u64,
+        sigma: u64,
+    },
+    /// Shown to run forever within `steps` steps, see [`Certificate`].
+    Forever {
+        steps: u64,
+    },
+    /// Neither after `steps` steps.
+    Undecided {
+        steps: u64,
+    },
+}
+
+impl WorkUnit {
+    /// The decision on every table of the unit, in order.
+    pub fn decisions(&self) -> impl Iterator<Item = (u128, Decision)> + '_ {
+        (self.start..self.end).map(|number| {
+            (
+                number,
+                decide(self.states, self.symbols, number, self.max_steps),
+            )
+        })
+    }
+
+    /// Runs every table of the unit.
+    pub fn run(&self) -> UnitResult {
+        self.collect(self.decisions())
+    }
+
+    /// The result of the unit with `decisions` on its tables.
+    pub fn collect(&self, decisions: impl IntoIterator<Item = (u128, Decision)>) -> UnitResult {
+        let mut result = UnitResult {
+            id: self.id,
+            machines: 0,
+            halted: 0,
+            champion: None,
+            certificates: Vec::new(),
+            undecided: Vec::new(),
+        };
+
+        for (number, decision) in decisions {
+            result.machines += 1;
+
+            match decision {
+                Decision::Halted { steps, sigma } => {
+                    result.halted += 1;
+                    result.offer(Halter {
+                        number,
+                        steps,
+                        sigma,
+                    });
+                }
+                Decision::Forever { steps } => {
+                    result.certificates.push(Certificate { number, steps })
+                }
+                Decision::Undecided { .. } => result.undecided.push(number),
+            }
+        }
+
+        result
+    }
+}
+
+/// The decision on table `number` with `states` states and `symbols` symbols,
+/// run for up to `max_steps` steps.
+pub(crate) fn decide(states: usize, symbols: usize, number: u128, max_steps: u64) -> Decision {
+    match run_number(states, symbols, number, max_steps) {
+        Some((Outcome::Halted, steps, sigma)) => Decision::Halted { steps, sigma },
+        Some((Outcome::Forever, steps, _)) => Decision::Forever { steps },
+        Some((_, steps, _)) => Decision::Undecided { steps },
+        None => Decision::Undecided { steps: 0 },
+    }
+}
+
+// the outcome, steps and sigma of running table `number` from a blank tape
+fn run_number(
+    states: usize,
+    symbols: usize,
+    number: u128,
+    max_steps: u64,
+) -> Option<(Outcome, u64, u64)> {
+    let definition: Definition<u8> = number::decode(&Number::from_u128(states, symbols, number))?;
+    let mut accelerator = Accelerator::new(&definition.table, definition.start.clone());
+    let outcome = accelerator.run(max_steps);
+
+    Some((outcome, accelerator.steps(), accelerator.sigma()))
+}
+
+/// A table that halts from a blank tape.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Halter {
+    pub number: u128,
+    pub steps: u64,
+    pub sigma: u64,
+}
+
+/// Proof that a table runs forever from a blank tape: [`Accelerator`] shows
+/// it within `steps` steps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Certificate {
+    pub number: u128,
+    pub steps: u64,
+}
+
+impl Certificate {
+    /// Checks the certificate by running the table again, for tables with
+    /// `states` states and `symbols` symbols.
+    pub fn verify(&self, states: usize, symbols: usize) -> bool {
+        // the run stops before proving anything once it reaches its limit
+        run_number(states, symbols, self.number, self.steps.saturating_add(1))
+            .is_some_and(|(outcome, steps, _)| outcome == Outcome::Forever && steps <= self.steps)
+    }
+}
+
+/// What running a [`WorkUnit`] found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnitResult {
+    pub id: u64,
+    pub machines: u64,
+    pub halted: u64,
+    /// The halting table taking the most steps, then leaving the most
+    /// non-blank symbols, then with the lowest number.
+    pub champion: Option<Halter>,
+    pub certificates: Vec<Certificate>,
+    /// The tables neither shown to halt nor to run forever.
+    pub undecided: Vec<u128>,
+}
+
+impl UnitResult {
+    fn offer(&mut self, halter: Halter) {
+        self.champion = Some(match self.champion {
+            Some(champion) => better(champion, halter),
+            None => halter,
+        });
+    }
+}
+
+fn better(a: Halter, b: Halter) -> Halter {
+    match (b.steps, b.sigma, a.number) > (a.steps, a.sigma, b.number) {
+        true => b,
+        false => a,
+    }
+}
+
+/// The merged results of a search.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Summary {
+    /// The ids of the units merged.
+    pub units: BTreeSet<u64>,
+    pub machines: u64,
+    pub halted: u64,
+    pub champion: Option<Halter>,
+    pub certificates: Vec<Certificate>,
+    pub undecided: Vec<u128>,
+}
+
+impl Summary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Merges `result`, unless a result for its unit already was. Returns
+    /// whether it was merged.
+    pub fn merge(&mut self, result: UnitResult) -> bool {
+        if !self.units.insert(result.id) {
+            return false;
+        }
+
+        self.machines += result.machines;
+        self.halted += result.halted;
+        self.champion = match (self.champion, result.champion) {
+            (Some(a), Some(b)) => Some(better(a, b)),
+            (a, b) => a.or(b),
+        };
+        self.certificates.extend(result.certificates);
+        self.undecided.extend(result.undecided);
+
+        true
+    }
+}
+
+/// A message of the protocol.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Message {
+    Lease {
+        worker: String,
+    },
+    Unit(WorkUnit),
+    /// Every unit is out; ask again after this many seconds.
+    Wait {
+        seconds: u64,
+    },
+    Done,
+    Result(UnitResult),
+    Ok,
+}
+
+impl Message {
+    pub fn write(&self, out: &mut impl Write) -> io::Result<()> {
+        match self {
+            Message::Lease { worker } => writeln!(out, "lease {worker}")?,
+            Message::Unit(unit) => writeln!(
+                out,
+                "unit {} {} {} {} {} {}",
+                unit.id, unit.states, unit.symbols, unit.start, unit.end, unit.max_steps
+            )?,
+            Message::Wait { seconds } => writeln!(out, "wait {seconds}")?,
+            Message::Done => writeln!(out, "done")?,
+            Message::Result(result) => {
+                writeln!(
+                    out,
+                    "result {} {} {}",
+                    result.id, result.machines, result.halted
+                )?;
+
+                if let Some(champion) = &result.champion {
+                    writeln!(
+                        out,
+                        "champion {} {} {}",
+                        champion.number, champion.steps, champion.sigma
+                    )?;
+                }
+
+                for certificate in &result.certificates {
+                    writeln!(out, "forever {} {}", certificate.number, certificate.steps)?;
+                }
+
+                for number in &result.undecided {
+                    writeln!(out, "undecided {number}")?;
+                }
+            }
+            Message::Ok => writeln!(out, "ok")?,
+        }
+
+        writeln!(out, "end")?;
+        out.flush()
+    }
+
+    pub fn read(input: &mut impl BufRead) -> io::Result<Self> {
+        let mut lines = Vec::new();
+
+        loop {
+            let mut line = String::new();
+
+            if input.read_line(&mut line)? == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "message without an end",
+                ));
+            }
+
+            match line.trim_end() {
+                "end" => break,
+                line => lines.push(line.to_owned()),
+            }
+        }
+
+        Self::parse(&lines).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "invalid message {:?}",
+                    lines.first().map_or("", |line| line)
+                ),
+            )
+        })
+    }
+
+    fn parse(lines: &[String]) -> Option<Self> {
+        let (first, rest) = lines.split_first()?;
+        let fields: Vec<&str> = first.split(' ').collect();
+
+        let message = match fields[..] {
+            ["lease", worker] => Message::Lease {
+                worker: worker.to_owned(),
+            },
+            ["unit", id, states, symbols, start, end, max_steps] => Message::Unit(WorkUnit {
+                id: id.parse().ok()?,
+                states: states.parse().ok()?,
+                symbols: symbols.parse().ok()?,
+                start: start.parse().ok()?,
+                end: end.parse().ok()?,
+                max_steps: max_steps.parse().ok()?,
+            }),
+            ["wait", seconds] => Message::Wait {
+                seconds: seconds.parse().ok()?,
+            },
+            ["done"] => Message::Done,
+            ["result", id, machines, halted] => {
+                let mut result = UnitResult {
+                    id: id.parse().ok()?,
+                    machines: machines.parse().ok()?,
+                    halted: halted.parse().ok()?,
+                    champion: None,
+                    certificates: Vec::new(),
+                    undecided: Vec::new(),
+                };
+
+                for line in rest {
+                    let fields: Vec<&str> = line.split(' ').collect();
+
+                    match fields[..] {
+                        ["champion", number, steps, sigma] if result.champion.is_none() => {
+                            result.champion = Some(Halter {
+                                number: number.parse().ok()?,
+                                steps: steps.parse().ok()?,
+                                sigma: sigma.parse().ok()?,
+                            })
+                        }
+                        ["forever", number, steps] => result.certificates.push(Certificate {
+                            number: number.parse().ok()?,
+                            steps: steps.parse().ok()?,
+                        }),
+                        ["undecided", number] => result.undecided.push(number.parse().ok()?),
+                        _ => return None,
+                    }
+                }
+
+                return Some(Message::Result(result));
+            }
+            ["ok"] => Message::Ok,
+            _ => return None,
+        };
+
+        rest.is_empty().then_some(message)
+    }
+}
+
+/// Sends `message` to the coordinator at `address` and reads its answer.
+pub fn request(address: impl ToSocketAddrs, message: &Message) -> io::Result<Message> {
+    let mut stream = TcpStream::connect(address)?;
+    message.write(&mut stream)?;
+
+    Message::read(&mut io::BufReader::new(stream))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn search_test() {
+        let mut summary = Summary::new();
+
+        for unit in partition(2, 2, 100, 5_000).unwrap() {
+            assert!(summary.merge(unit.run()));
+        }
+
+        let champion = summary.champion.unwrap();
+
+        assert_eq!(summary.units.len(), 6);
+        assert_eq!(summary.machines, Number::count(2, 2).unwrap() as u64);
+        assert_eq!((champion.steps, champion.sigma), (6, 4));
+        assert_eq!(
+            summary.halted + summary.certificates.len() as u64 + summary.undecided.len() as u64,
+            summary.machines
+        );
+        assert!(summary.certificates[..10].iter().all(|c| c.verify(2, 2)));
+        assert!(!Certificate {
+            number: champion.number,
+            steps: 50
+        }
+        .verify(2, 2));
+    }
+
+    #[test]
+    fn message_test() {
+        let result = partition(1, 2, 100, 81).unwrap().next().unwrap().run();
+        assert!(result.champion.is_some() && !result.certificates.is_empty());
+
+        for message in [
+            Message::Lease {
+                worker: "a".to_owned(),
+            },
+            Message::Wait { seconds: 3 },
+            Message::Result(result),
+        ] {
+            let mut bytes = Vec::new();
+            message.write(&mut bytes).unwrap();
+
+            assert_eq!(Message::read(&mut &bytes[..]).unwrap(), message);
+        }
+
+        assert!(Message::read(&mut &b"unit 1 2\nend\n"[..]).is_err());
+        assert!(Message::read(&mut &b"done\n"[..]).is_err());
+    }
+}
@@ -0,0 +1,138 @@
+use std::io;
+use std::thread;
+use std::time::Duration;
+
+use super::{request, Message};
+
+/// Leases units from a coordinator and runs them until the coordinator has
+/// none left.
+#[derive(Debug, Clone)]
+pub struct Worker {
+    coordinator: String,
+    name: String,
+    retries: u32,
+    retry_delay: Duration,
+}
+
+impl Worker {
+    /// A worker for the coordinator at `coordinator`, e.g.
+    /// `"10.0.0.1:4000"`, naming itself `name`, a single word.
+    pub fn new(coordinator: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            coordinator: coordinator.into(),
+            name: name.into(),
+            retries: 5,
+            retry_delay: Duration::from_secs(1),
+        }
+    }
+
+    /// Makes a request that fails be tried `retries` more times, `delay`
+    /// apart, doubling the delay every time.
+    pub fn with_retries(self, retries: u32, delay: Duration) -> Self {
+        Self {
+            retries,
+            retry_delay: delay,
+            ..self
+        }
+    }
+
+    /// Runs units until the coordinator is done, returning how many it ran;
+    /// fails once a request fails after all retries.
+    pub fn run(&self) -> io::Result<u64> {
+        let mut units = 0;
+
+        loop {
+            let lease = Message::Lease {
+                worker: self.name.clone(),
+            };
+
+            match self.request(&lease)? {
+                Message::Unit(unit) => {
+                    let result = unit.run();
+
+                    match self.request(&Message::Result(result))? {
+                        Message::Ok => units += 1,
+                        answer => return Err(unexpected(&answer)),
+                    }
+                }
+                Message::Wait { seconds } => thread::sleep(Duration::from_secs(seconds)),
+                Message::Done => return Ok(units),
+                answer => return Err(unexpected(&answer)),
+            }
+        }
+    }
+
+    fn request(&self, message: &Message) -> io::Result<Message> {
+        let mut delay = self.retry_delay;
+
+        for _ in 0..self.retries {
+            match request(self.coordinator.as_str(), message) {
+                Ok(answer) => return Ok(answer),
+                Err(_) => {
+                    thread::sleep(delay);
+                    delay *= 2;
+                }
+            }
+        }
+
+        request(self.coordinator.as_str(), message)
+    }
+}
+
+fn unexpected(answer: &Message) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("unexpected answer {answer:?}"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufReader;
+    use std::net::TcpListener;
+
+    use super::*;
+    use crate::distributed::{partition, Summary};
+
+    #[test]
+    fn worker_test() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let mut units: Vec<_> = partition(1, 2, 100, 30).unwrap().collect();
+
+        let coordinator = thread::spawn(move || {
+            let mut summary = Summary::new();
+            let mut incoming = listener.incoming();
+
+            // drops the first connection, which the worker retries
+            drop(incoming.next());
+
+            for stream in incoming {
+                let mut stream = stream.unwrap();
+                let answer = match Message::read(&mut BufReader::new(&stream)).unwrap() {
+                    Message::Lease { .. } => units.pop().map_or(Message::Done, Message::Unit),
+                    Message::Result(result) => {
+                        summary.merge(result);
+                        Message::Ok
+                    }
+                    message => panic!("unexpected {message:?}"),
+                };
+
+                answer.write(&mut stream).unwrap();
+
+                if answer == Message::Done {
+                    return summary;
+                }
+            }
+
+            unreachable!()
+        });
+
+        let worker = Worker::new(address, "test").with_retries(3, Duration::from_millis(10));
+        assert_eq!(worker.run().unwrap(), 3);
+
+        let summary = coordinator.join().unwrap();
+        assert_eq!(summary.machines, 81);
+        assert_eq!(summary.units.len(), 3);
+    }
+}
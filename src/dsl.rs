@@ -0,0 +1,459 @@
+//! A plain-text format for transition tables.
+//!
+//! Each non-empty line holds one rule as five whitespace-separated fields:
+//!
+//! ```text
+//! # state read write move next
+//! inc     1    0     R    inc
+//! inc     0    1     N    halt
+//! ```
+//!
+//! `write` and `next` may be `*` to leave the cell or state unchanged, `move`
+//! is one of `L`, `R` or `N`, or `L` or `R` followed by a distance such as
+//! `R3`, and a `next` of `halt` halts the machine.
+//! Everything after a `#` is a comment.
+//!
+//! A machine definition may also name its initial state with a `start inc`
+//! line; without one, the machine starts in the state of the first rule.
+//! A `blank _` line sets the symbol the tape grows by, otherwise
+//! `Sym::default()`.
+//!
+//! To catch typos in large tables, a definition may declare its alphabets
+//! with `input 01` and `tape 01_X` lines listing their symbols. Rules and the
+//! blank may then only use symbols of the tape alphabet, which must include
+//! the input alphabet; [`Definition::check_input`] checks an input against
+//! the input alphabet.
+
+use alloc::borrow::ToOwned;
+use alloc::collections::BTreeSet;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt::{self, Display, Write};
+
+use crate::{move_name, parse_move, Rule, State, Symbol, TransitionTable, TuringState};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl Error for ParseError {}
+
+/// A transition table together with the state a machine using it starts in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Definition<Sym> {
+    pub start: String,
+    /// The blank symbol, if not `Sym::default()`; see [`Machine::with_blank`].
+    ///
+    /// [`Machine::with_blank`]: crate::Machine::with_blank
+    pub blank: Option<Sym>,
+    /// The symbols an input may consist of, if declared.
+    pub input_alphabet: Option<BTreeSet<Sym>>,
+    /// The symbols the rules may read and write, if declared.
+    pub tape_alphabet: Option<BTreeSet<Sym>>,
+    pub table: TransitionTable<String, Sym>,
+}
+
+/// Returned by [`Definition::check_input`] for an input symbol outside the
+/// input alphabet, at `position` counting from 0.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputError {
+    pub position: usize,
+    pub symbol: char,
+}
+
+impl Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "input symbol {:?} at position {} is not in the input alphabet",
+            self.symbol, self.position
+        )
+    }
+}
+
+impl Error for InputError {}
+
+impl<Sym: Ord + Clone> Definition<Sym> {
+    /// A definition of `table` starting in `start`, referring to states by
+    /// their [`TuringState::name`], so that formats and renderers taking
+    /// definitions show the names.
+    pub fn named<S>(start: &S, table: &TransitionTable<S, Sym>) -> Self
+    where
+        S: TuringState + Ord,
+    {
+        Self {
+            start: start.name().to_owned(),
+            blank: None,
+            input_alphabet: None,
+            tape_alphabet: None,
+            table: table.map_states(|state| state.name().to_owned()),
+        }
+    }
+
+    /// The start state and the table with the states named back as `S`, or
+    /// the first name that isn't one of them.
+    pub fn typed<S>(&self) -> Result<(S, TransitionTable<S, Sym>), String>
+    where
+        S: TuringState + Ord + Clone,
+    {
+        let state = |name: &String| S::from_name(name).ok_or_else(|| name.clone());
+
+        Ok((state(&self.start)?, self.table.try_map_states(state)?))
+    }
+}
+
+impl<Sym: Symbol + Ord> Definition<Sym> {
+    /// Checks that `input` only uses symbols of the input alphabet, if one
+    /// was declared.
+    pub fn check_input(&self, input: &str) -> Result<(), InputError> {
+        let Some(alphabet) = &self.input_alphabet else {
+            return Ok(());
+        };
+
+        match input
+            .chars()
+            .enumerate()
+            .find(|&(_, c)| !Sym::from_char(c).is_some_and(|symbol| alphabet.contains(&symbol)))
+        {
+            Some((position, symbol)) => Err(InputError { position, symbol }),
+            None => Ok(()),
+        }
+    }
+}
+
+// what the lines of a definition declare
+struct Lines<Sym> {
+    start: Option<String>,
+    blank: Option<Sym>,
+    input_alphabet: Option<BTreeSet<Sym>>,
+    tape_alphabet: Option<BTreeSet<Sym>>,
+    table: TransitionTable<String, Sym>,
+}
+
+pub fn parse<Sym>(src: &str) -> Result<TransitionTable<String, Sym>, ParseError>
+where
+    Sym: Symbol + Ord,
+{
+    parse_lines(src).map(|lines| lines.table)
+}
+
+pub fn parse_definition<Sym>(src: &str) -> Result<Definition<Sym>, ParseError>
+where
+    Sym: Symbol + Ord,
+{
+    let Lines {
+        start,
+        blank,
+        input_alphabet,
+        tape_alphabet,
+        table,
+    } = parse_lines(src)?;
+
+    let start = start.ok_or_else(|| ParseError {
+        line: src.lines().count(),
+        message: "missing start state".to_owned(),
+    })?;
+
+    Ok(Definition {
+        start,
+        blank,
+        input_alphabet,
+        tape_alphabet,
+        table,
+    })
+}
+
+// returns the explicit start state if there is one, otherwise the state of
+// the first rule
+fn parse_lines<Sym>(src: &str) -> Result<Lines<Sym>, ParseError>
+where
+    Sym: Symbol + Ord,
+{
+    let mut table = TransitionTable::new();
+    let mut start = None;
+    let mut blank = None;
+    let mut input_alphabet = None;
+    let mut tape_alphabet = None;
+    let mut first_state = None;
+    // the line of every symbol to check against the tape alphabet
+    let mut uses = Vec::new();
+
+    for (index, line) in src.lines().enumerate() {
+        let line_number = index + 1;
+        let error = |message: String| ParseError {
+            line: line_number,
+            message,
+        };
+
+        let content = line.split('#').next().unwrap_or_default();
+        let fields: Vec<&str> = content.split_whitespace().collect();
+
+        let symbol = |field: &str| {
+            let mut chars = field.chars();
+
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Sym::from_char(c),
+                _ => None,
+            }
+            .ok_or_else(|| error(format!("invalid symbol {field:?}")))
+        };
+
+        let [state, read, write, head_move, next] = fields[..] else {
+            match fields[..] {
+                [] => continue,
+                ["start", state] if start.is_none() => {
+                    start = Some(state.to_owned());
+                    continue;
+                }
+                ["start", _] => return Err(error("duplicate start state".to_owned())),
+                ["blank", field] if blank.is_none() => {
+                    blank = Some(symbol(field)?);
+                    uses.push((line_number, field.chars().next().unwrap()));
+                    continue;
+                }
+                ["blank", _] => return Err(error("duplicate blank symbol".to_owned())),
+                ["input", field] if input_alphabet.is_none() => {
+                    input_alphabet = Some(alphabet(field, &error)?);
+                    uses.extend(field.chars().map(|c| (line_number, c)));
+                    continue;
+                }
+                ["input", _] => return Err(error("duplicate input alphabet".to_owned())),
+                ["tape", field] if tape_alphabet.is_none() => {
+                    tape_alphabet = Some(alphabet(field, &error)?);
+                    continue;
+                }
+                ["tape", _] => return Err(error("duplicate tape alphabet".to_owned())),
+                _ => return Err(error(format!("expected 5 fields, found {}", fields.len()))),
+            }
+        };
+
+        let read = symbol(read)?;
+        let write = match write {
+            "*" => None,
+            write => Some(symbol(write)?),
+        };
+
+        uses.push((line_number, read.to_char()));
+        uses.extend(write.as_ref().map(|write| (line_number, write.to_char())));
+        let head_move =
+            parse_move(head_move).ok_or_else(|| error(format!("invalid move {head_move:?}")))?;
+        let new_state = match next {
+            "*" => None,
+            "halt" => Some(State::Halt),
+            next => Some(State::State(next.to_owned())),
+        };
+
+        let rule = Rule {
+            new_state,
+            write,
+            head_move,
+        };
+
+        first_state.get_or_insert_with(|| state.to_owned());
+
+        let read_char = read.to_char();
+
+        if table.insert(state.to_owned(), read, rule).is_some() {
+            return Err(error(format!(
+                "duplicate rule for state {state:?} reading {read_char:?}"
+            )));
+        }
+    }
+
+    if let Some(tape) = &tape_alphabet {
+        let undeclared = uses
+            .into_iter()
+            .find(|&(_, c)| !Sym::from_char(c).is_some_and(|symbol| tape.contains(&symbol)));
+
+        if let Some((line, c)) = undeclared {
+            return Err(ParseError {
+                line,
+                message: format!("symbol {c:?} is not in the tape alphabet"),
+            });
+        }
+    }
+
+    Ok(Lines {
+        start: start.or(first_state),
+        blank,
+        input_alphabet,
+        tape_alphabet,
+        table,
+    })
+}
+
+fn alphabet<Sym>(
+    field: &str,
+    error: &impl Fn(String) -> ParseError,
+) -> Result<BTreeSet<Sym>, ParseError>
+where
+    Sym: Symbol + Ord,
+{
+    field
+        .chars()
+        .map(|c| Sym::from_char(c).ok_or_else(|| error(format!("invalid symbol {c:?}"))))
+        .collect()
+}
+
+/// Serializes `table` in the format accepted by [`parse`].
+pub fn write<Sym>(table: &TransitionTable<String, Sym>) -> String
+where
+    Sym: Symbol + Ord,
+{
+    let mut out = String::new();
+
+    for (state, read, rule) in table.iter() {
+        let write = rule.write.as_ref().map_or('*', Symbol::to_char);
+        let head_move = move_name(rule.head_move);
+        let next = match &rule.new_state {
+            None => "*",
+            Some(State::Halt) => "halt",
+            Some(State::State(next)) => next,
+        };
+
+        writeln!(out, "{state} {} {write} {head_move} {next}", read.to_char()).unwrap();
+    }
+
+    out
+}
+
+/// Serializes `definition` in the format accepted by [`parse_definition`].
+pub fn write_definition<Sym>(definition: &Definition<Sym>) -> String
+where
+    Sym: Symbol + Ord,
+{
+    let mut out = format!("start {}\n", definition.start);
+
+    if let Some(blank) = &definition.blank {
+        writeln!(out, "blank {}", blank.to_char()).unwrap();
+    }
+
+    for (keyword, alphabet) in [
+        ("input", &definition.input_alphabet),
+        ("tape", &definition.tape_alphabet),
+    ] {
+        if let Some(alphabet) = alphabet {
+            let symbols: String = alphabet.iter().map(Symbol::to_char).collect();
+            writeln!(out, "{keyword} {symbols}").unwrap();
+        }
+    }
+
+    out + write(&definition.table).as_str()
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::*;
+    use crate::Char;
+
+    const INC: &str = "
+        # binary increment, least significant bit first
+        inc 1 0 R *
+        inc 0 1 N halt
+    ";
+
+    #[test]
+    fn parse_write_round_trip_test() {
+        let table: TransitionTable<String, bool> = parse(INC).unwrap();
+
+        assert_eq!(table.len(), 2);
+        assert_eq!(
+            table.get(&"inc".to_owned(), &true),
+            Some(&Rule::new().write(false).right())
+        );
+        assert_eq!(write(&table), "inc 0 1 N halt\ninc 1 0 R *\n");
+        assert_eq!(parse::<bool>(&write(&table)).unwrap(), table);
+    }
+
+    #[test]
+    fn parse_error_test() {
+        let error = parse::<bool>("inc 1 0 R *\ninc 2 0 R *").unwrap_err();
+
+        assert_eq!(error.line, 2);
+
+        let error = parse::<bool>("inc 1 0 R\n").unwrap_err();
+
+        assert_eq!(error.to_string(), "line 1: expected 5 fields, found 4");
+        assert!(parse::<bool>("inc 1 0 R *\ninc 1 1 L *").is_err());
+    }
+
+    #[test]
+    fn distance_move_test() {
+        let table: TransitionTable<String, bool> =
+            parse("a 1 * R3 *\na 0 * L1 *\nb 1 * L12 halt").unwrap();
+
+        assert_eq!(table.get(&"a".to_owned(), &true), Some(&Rule::new().by(3)));
+        assert_eq!(
+            table.get(&"a".to_owned(), &false),
+            Some(&Rule::new().left())
+        );
+        assert_eq!(write(&table), "a 0 * L *\na 1 * R3 *\nb 1 * L12 halt\n");
+        assert!(parse::<bool>("a 1 * R-2 *").is_err());
+    }
+
+    #[test]
+    fn parse_definition_test() {
+        let definition: Definition<bool> = parse_definition(INC).unwrap();
+
+        assert_eq!(definition.start, "inc");
+        assert_eq!(definition.blank, None);
+
+        let definition: Definition<bool> = parse_definition(&format!("start done\n{INC}")).unwrap();
+
+        assert_eq!(definition.start, "done");
+        assert_eq!(
+            parse_definition(&write_definition(&definition)).unwrap(),
+            definition
+        );
+        assert!(parse_definition::<bool>("# nothing here\n").is_err());
+        assert!(parse_definition::<bool>("start a\nstart b\n").is_err());
+
+        let definition: Definition<bool> = parse_definition(&format!("blank 1\n{INC}")).unwrap();
+
+        assert_eq!(definition.blank, Some(true));
+        assert_eq!(
+            parse_definition(&write_definition(&definition)).unwrap(),
+            definition
+        );
+        assert!(parse_definition::<bool>("blank 1\nblank 0\n").is_err());
+    }
+
+    #[test]
+    fn alphabet_test() {
+        let src = "input 1\ntape 10_\nright 1 * R *\nright _ 0 N halt";
+        let definition: Definition<Char> = parse_definition(src).unwrap();
+
+        assert_eq!(definition.input_alphabet, Some(BTreeSet::from([Char('1')])));
+        assert_eq!(definition.check_input("11"), Ok(()));
+        assert_eq!(
+            definition.check_input("101"),
+            Err(InputError {
+                position: 1,
+                symbol: '0'
+            })
+        );
+        assert_eq!(
+            parse_definition(&write_definition(&definition)).unwrap(),
+            definition
+        );
+
+        // a typo in the last rule
+        let error = parse_definition::<Char>("tape 01_\na 1 * R *\na - 0 N halt").unwrap_err();
+
+        assert_eq!(error.line, 3);
+        assert_eq!(error.message, "symbol '-' is not in the tape alphabet");
+        assert!(parse_definition::<Char>("input 2\ntape 01\na 1 * R *").is_err());
+    }
+}
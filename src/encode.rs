@@ -0,0 +1,144 @@
+use alloc::collections::VecDeque;
+use alloc::string::String;
+
+use crate::{Machine, Symbol};
+
+/// Values that can be written on a tape, as the characters of its symbols.
+pub trait Encode {
+    fn encode(&self) -> String;
+}
+
+/// Values that can be read back from a tape.
+pub trait Decode: Sized {
+    /// Decodes the characters of the cells from cell 0 onwards, with the
+    /// trailing blanks removed.
+    fn decode(output: &str) -> Option<Self>;
+}
+
+/// An unsigned integer `n` encoded as `n` ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Unary<T>(pub T);
+
+// unsigned integers are encoded in binary, least significant bit first, so
+// that trailing zeros can double as blanks
+macro_rules! impl_unsigned {
+    ($($int:ty),*) => {$(
+        impl Encode for $int {
+            fn encode(&self) -> String {
+                let mut n = *self;
+                let mut out = String::new();
+
+                loop {
+                    out.push(if n & 1 == 1 { '1' } else { '0' });
+                    n >>= 1;
+
+                    if n == 0 {
+                        return out;
+                    }
+                }
+            }
+        }
+
+        impl Decode for $int {
+            fn decode(output: &str) -> Option<Self> {
+                output.chars().rev().try_fold(0 as $int, |n, bit| {
+                    let bit = match bit {
+                        '0' => 0,
+                        '1' => 1,
+                        _ => return None,
+                    };
+
+                    n.checked_mul(2)?.checked_add(bit)
+                })
+            }
+        }
+
+        impl Encode for Unary<$int> {
+            fn encode(&self) -> String {
+                (0..self.0).map(|_| '1').collect()
+            }
+        }
+
+        impl Decode for Unary<$int> {
+            fn decode(output: &str) -> Option<Self> {
+                if !output.chars().all(|c| c == '1') {
+                    return None;
+                }
+
+                <$int>::try_from(output.len()).ok().map(Unary)
+            }
+        }
+    )*};
+}
+
+impl_unsigned!(u8, u16, u32, u64, u128, usize);
+
+impl<S, Sym> Machine<S, Sym, VecDeque<Sym>>
+where
+    Sym: Symbol + Default + Clone,
+{
+    /// Like [`Machine::with_input`], with `input` encoded on the tape.
+    pub fn with_encoded_input<E: Encode>(state: S, input: E) -> Option<Self> {
+        Self::with_input(state, &input.encode())
+    }
+
+    /// Decodes the tape from cell 0 onwards; cells left of it are ignored.
+    pub fn decode_output<D: Decode>(&self) -> Option<D> {
+        let blank = Sym::blank().to_char();
+        let output: String = self
+            .tape
+            .iter()
+            .skip(self.origin)
+            .map(Symbol::to_char)
+            .collect();
+
+        D::decode(output.trim_end_matches(blank))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::borrow::ToOwned;
+
+    use super::*;
+    use crate::{dsl, Char};
+
+    #[test]
+    fn encode_decode_test() {
+        assert_eq!(6u8.encode(), "011");
+        assert_eq!(0u32.encode(), "0");
+        assert_eq!(Unary(3u16).encode(), "111");
+        assert_eq!(u8::decode("011"), Some(6));
+        assert_eq!(u8::decode(""), Some(0));
+        assert_eq!(u8::decode("000000001"), None);
+        assert_eq!(u8::decode("0_1"), None);
+        assert_eq!(Unary::<u8>::decode("1111"), Some(Unary(4)));
+        assert_eq!(Unary::<u8>::decode("11_1"), None);
+
+        for n in [0, 1, 2, 41, 42, u64::MAX] {
+            assert_eq!(u64::decode(&n.encode()), Some(n));
+        }
+    }
+
+    #[test]
+    fn encoded_input_test() {
+        // the trailing zeros of a bool tape are blanks
+        let inc = dsl::parse::<bool>("inc 1 0 R *\ninc 0 1 N halt").unwrap();
+        let mut machine = Machine::with_encoded_input("inc".to_owned(), 42u32).unwrap();
+        machine.run_table(&inc, 100);
+
+        assert_eq!(machine.decode_output(), Some(43u32));
+
+        // unary successor ending left of cell 0, which decoding ignores
+        let succ = dsl::parse::<Char>("s _ 1 L halt").unwrap();
+        let mut machine: Machine<_, Char> =
+            Machine::with_encoded_input("s".to_owned(), Unary(0u8)).unwrap();
+        machine.run_table(&succ, 100);
+
+        assert_eq!(machine.decode_output(), Some(Unary(1u8)));
+
+        machine.write_at(machine.head(), Char('1'));
+
+        assert_eq!(machine.decode_output(), Some(Unary(1u8)));
+    }
+}
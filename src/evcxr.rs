@@ -0,0 +1,34 @@
+//! Rich output in Rust Jupyter notebooks: evcxr calls an `evcxr_display`
+//! method, if there is one, to show the value of an expression.
+
+use core::fmt::Display;
+
+use crate::{render, Machine, RunStats, Symbol, Tape};
+
+fn display_html(html: &str) {
+    println!("EVCXR_BEGIN_CONTENT text/html\n{html}\nEVCXR_END_CONTENT");
+}
+
+impl<S, Sym, T> Machine<S, Sym, T>
+where
+    Sym: Default,
+    T: Tape<Sym>,
+{
+    pub fn evcxr_display(&self)
+    where
+        S: Display + Clone,
+        Sym: Symbol + PartialEq + Clone,
+    {
+        display_html(&render::html(&self.snapshot()));
+    }
+}
+
+impl RunStats {
+    pub fn evcxr_display(&self) {
+        display_html(&format!(
+            "<div style=\"font-family: monospace\">{:?} after <b>{}</b> steps, \
+             {} cells of tape</div>",
+            self.stop, self.steps, self.tape_len
+        ));
+    }
+}
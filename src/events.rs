@@ -0,0 +1,227 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::sync::atomic::AtomicBool;
+
+use crate::run::CANCEL_CHECK_INTERVAL;
+use crate::{Executor, Machine, Rule, RunStats, State, Tape, TransitionTable};
+
+type StateChange<'a, S> = dyn FnMut(u64, &S, &State<S>) + 'a;
+type Halt<'a> = dyn FnMut(u64) + 'a;
+type TapeGrowth<'a> = dyn FnMut(u64, usize) + 'a;
+type Write<'a, Sym> = dyn FnMut(u64, isize, &Sym) + 'a;
+type Predicate<'a, Sym> = dyn Fn(&Sym) -> bool + 'a;
+type WriteSubscription<'a, Sym> = (Box<Predicate<'a, Sym>>, Box<Write<'a, Sym>>);
+
+/// Callbacks for the events of a run that one cares about, passed to
+/// [`Machine::run_subscribed`]. Events without a subscriber aren't even
+/// looked for.
+///
+/// Every callback gets the number of the step the event happened at first.
+pub struct Subscriptions<'a, S, Sym> {
+    state_change: Option<Box<StateChange<'a, S>>>,
+    halt: Option<Box<Halt<'a>>>,
+    tape_growth: Option<Box<TapeGrowth<'a>>>,
+    writes: Vec<WriteSubscription<'a, Sym>>,
+}
+
+impl<'a, S, Sym> Subscriptions<'a, S, Sym> {
+    pub fn new() -> Self {
+        Self {
+            state_change: None,
+            halt: None,
+            tape_growth: None,
+            writes: Vec::new(),
+        }
+    }
+
+    /// Calls `callback` with the old and the new state on every step moving
+    /// to another state, halting included.
+    pub fn on_state_change(mut self, callback: impl FnMut(u64, &S, &State<S>) + 'a) -> Self {
+        self.state_change = Some(Box::new(callback));
+        self
+    }
+
+    /// Calls `callback` on the step the machine halts.
+    pub fn on_halt(mut self, callback: impl FnMut(u64) + 'a) -> Self {
+        self.halt = Some(Box::new(callback));
+        self
+    }
+
+    /// Calls `callback` with the new length on every step growing the tape.
+    pub fn on_tape_growth(mut self, callback: impl FnMut(u64, usize) + 'a) -> Self {
+        self.tape_growth = Some(Box::new(callback));
+        self
+    }
+
+    /// Calls `callback` with the head position, relative to the initial cell
+    /// 0, and the symbol on every write of a symbol matching `matches`.
+    pub fn on_write(
+        mut self,
+        matches: impl Fn(&Sym) -> bool + 'a,
+        callback: impl FnMut(u64, isize, &Sym) + 'a,
+    ) -> Self {
+        self.writes.push((Box::new(matches), Box::new(callback)));
+        self
+    }
+
+    fn before(&mut self, step: u64, state: &S, head: isize, rule: &Rule<S, Sym>)
+    where
+        S: PartialEq,
+    {
+        if let Some(new_state) = &rule.new_state {
+            if let Some(state_change) = &mut self.state_change {
+                if !matches!(new_state, State::State(new_state) if new_state == state) {
+                    state_change(step, state, new_state);
+                }
+            }
+
+            if let (Some(halt), State::Halt) = (&mut self.halt, new_state) {
+                halt(step);
+            }
+        }
+
+        if let Some(symbol) = &rule.write {
+            for (matches, write) in &mut self.writes {
+                if matches(symbol) {
+                    write(step, head, symbol);
+                }
+            }
+        }
+    }
+}
+
+impl<S, Sym> Default for Subscriptions<'_, S, Sym> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S, Sym, T> Machine<S, Sym, T>
+where
+    S: PartialEq,
+    Sym: Default + Clone,
+    T: Tape<Sym>,
+{
+    /// Like [`Machine::run`], calling the callbacks in `subscriptions` for
+    /// the events they subscribe to.
+    pub fn run_subscribed<E>(
+        &mut self,
+        max_steps: u64,
+        subscriptions: &mut Subscriptions<'_, S, Sym>,
+    ) -> RunStats
+    where
+        E: Executor<S, Sym>,
+    {
+        self.run_subscribed_with(
+            max_steps,
+            &AtomicBool::new(false),
+            0,
+            subscriptions,
+            Self::next_rule::<E>,
+        )
+    }
+
+    /// Like [`Machine::run_subscribed`], looking rules up in `table`.
+    pub fn run_table_subscribed(
+        &mut self,
+        table: &TransitionTable<S, Sym>,
+        max_steps: u64,
+        subscriptions: &mut Subscriptions<'_, S, Sym>,
+    ) -> RunStats
+    where
+        S: Ord + Clone,
+        Sym: Ord + Clone,
+    {
+        self.run_subscribed_with(
+            max_steps,
+            &AtomicBool::new(false),
+            0,
+            subscriptions,
+            |machine| machine.next_table_rule(table),
+        )
+    }
+
+    // `first` is the number of the first step, for runs going on from
+    // earlier ones
+    pub(crate) fn run_subscribed_with(
+        &mut self,
+        max_steps: u64,
+        cancel: &AtomicBool,
+        first: u64,
+        subscriptions: &mut Subscriptions<'_, S, Sym>,
+        rule: impl Fn(&Self) -> Option<Rule<S, Sym>>,
+    ) -> RunStats {
+        let mut steps = first;
+
+        self.run_loop(
+            max_steps,
+            cancel,
+            CANCEL_CHECK_INTERVAL,
+            |machine| {
+                let Some(rule) = rule(machine) else {
+                    return false;
+                };
+
+                if let State::State(state) = &machine.state {
+                    let head = machine.head as isize - machine.origin as isize;
+                    subscriptions.before(steps, state, head, &rule);
+                }
+
+                let len = machine.tape.len();
+                machine.apply(rule);
+
+                if let Some(tape_growth) = &mut subscriptions.tape_growth {
+                    if machine.tape.len() > len {
+                        tape_growth(steps, machine.tape.len());
+                    }
+                }
+
+                steps += 1;
+                true
+            },
+            |_, _| true,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    #[test]
+    fn run_subscribed_test() {
+        // the 2-state busy beaver
+        let table: TransitionTable<u8, bool> = [
+            (0, false, Rule::to(1).write(true).right()),
+            (0, true, Rule::to(1).write(true).left()),
+            (1, false, Rule::to(0).write(true).left()),
+            (1, true, Rule::halt().write(true).right()),
+        ]
+        .into_iter()
+        .collect();
+
+        let mut changes = Vec::new();
+        let mut halts = Vec::new();
+        let mut growth = Vec::new();
+        let mut writes = 0;
+
+        let mut subscriptions = Subscriptions::new()
+            .on_state_change(|step, &from, to| changes.push((step, from, to.clone())))
+            .on_halt(|step| halts.push(step))
+            .on_tape_growth(|step, len| growth.push((step, len)))
+            .on_write(|&symbol| symbol, |_, _, _| writes += 1);
+
+        let mut machine: Machine<u8, bool> = Machine::new(0, [false].into());
+        let stats = machine.run_table_subscribed(&table, 100, &mut subscriptions);
+        drop(subscriptions);
+
+        assert_eq!(stats.steps, 6);
+        assert_eq!(changes.len(), 6);
+        assert_eq!(changes[5], (5, 1, State::Halt));
+        assert_eq!(halts, vec![5]);
+        assert_eq!(growth, vec![(0, 2), (2, 3), (3, 4)]);
+        assert_eq!(writes, 6);
+    }
+}
@@ -0,0 +1,411 @@
+//! Halting-time statistics over ensembles of tables: every table of a size,
+//! or a random sample of them, run from a blank tape with a step cap.
+//!
+//! An [`Experiment`] gives a [`Distribution`] of how its tables ended, which
+//! breaks down into [`Histogram`]s of halting times and sigmas and a
+//! [`Survival`] curve, each of which can be written as CSV for plotting.
+
+use std::io::{self, Write};
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use crate::distributed::{decide, Decision};
+use crate::format::number::Number;
+
+/// How many tables a thread takes at a time.
+const CHUNK: u64 = 4096;
+
+/// Which tables of a size an [`Experiment`] runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sample {
+    /// Every table, in [`number`](crate::format::number) order.
+    All,
+    /// `count` tables picked uniformly at random, with repetition, from
+    /// `seed`.
+    Random { count: u64, seed: u64 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Experiment {
+    pub states: usize,
+    pub symbols: usize,
+    pub max_steps: u64,
+    pub sample: Sample,
+    threads: usize,
+}
+
+impl Experiment {
+    /// Runs every table with `states` states and `symbols` symbols.
+    pub fn all(states: usize, symbols: usize, max_steps: u64) -> Self {
+        Self {
+            states,
+            symbols,
+            max_steps,
+            sample: Sample::All,
+            threads: 0,
+        }
+    }
+
+    /// Runs `count` random tables with `states` states and `symbols`
+    /// symbols. The same `seed` picks the same tables.
+    pub fn random(states: usize, symbols: usize, max_steps: u64, count: u64, seed: u64) -> Self {
+        Self {
+            sample: Sample::Random { count, seed },
+            ..Self::all(states, symbols, max_steps)
+        }
+    }
+
+    /// Spreads the runs over `threads` threads (all available cores if
+    /// zero, the default). The distribution doesn't depend on it.
+    pub fn threads(self, threads: usize) -> Self {
+        Self { threads, ..self }
+    }
+
+    /// Runs the tables. `None` if their number doesn't fit a `u128`.
+    pub fn run(&self) -> Option<Distribution> {
+        let count = Number::count(self.states, self.symbols)?;
+        let runs = match self.sample {
+            Sample::All => u64::try_from(count).ok()?,
+            Sample::Random { count, .. } => count,
+        };
+
+        let threads = match self.threads {
+            0 => thread::available_parallelism().map_or(1, NonZeroUsize::get),
+            threads => threads,
+        }
+        .min(runs.div_ceil(CHUNK).max(1) as usize);
+
+        let next = AtomicU64::new(0);
+        let distribution = Mutex::new(Distribution::new(self.max_steps));
+
+        thread::scope(|scope| {
+            for _ in 0..threads {
+                scope.spawn(|| {
+                    let mut partial = Distribution::new(self.max_steps);
+
+                    loop {
+                        let start = next.fetch_add(CHUNK, Ordering::Relaxed);
+
+                        if start >= runs {
+                            break;
+                        }
+
+                        for index in start..(start + CHUNK).min(runs) {
+                            let number = match self.sample {
+                                Sample::All => u128::from(index),
+                                Sample::Random { seed, .. } => random(seed, index) % count,
+                            };
+
+                            partial.add(decide(self.states, self.symbols, number, self.max_steps));
+                        }
+                    }
+
+                    distribution.lock().unwrap().merge(partial);
+                });
+            }
+        });
+
+        let mut distribution = distribution.into_inner().unwrap();
+        distribution.halted.sort_unstable();
+
+        Some(distribution)
+    }
+}
+
+// the `index`th 128-bit draw from SplitMix64 seeded with `seed`, so that
+// draws don't depend on which thread makes them
+fn random(seed: u64, index: u64) -> u128 {
+    let draw = |n: u64| {
+        let mut z = seed.wrapping_add(n.wrapping_add(1).wrapping_mul(0x9e37_79b9_7f4a_7c15));
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    };
+
+    u128::from(draw(2 * index)) << 64 | u128::from(draw(2 * index + 1))
+}
+
+/// How the tables of an [`Experiment`] ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Distribution {
+    pub machines: u64,
+    /// The steps and sigma of every halting run, by steps then sigma.
+    pub halted: Vec<(u64, u64)>,
+    /// How many were shown to run forever.
+    pub forever: u64,
+    /// How many did neither within `max_steps` steps.
+    pub undecided: u64,
+    pub max_steps: u64,
+}
+
+impl Distribution {
+    fn new(max_steps: u64) -> Self {
+        Self {
+            machines: 0,
+            halted: Vec::new(),
+            forever: 0,
+            undecided: 0,
+            max_steps,
+        }
+    }
+
+    fn add(&mut self, decision: Decision) {
+        self.machines += 1;
+
+        match decision {
+            Decision::Halted { steps, sigma } => self.halted.push((steps, sigma)),
+            Decision::Forever { .. } => self.forever += 1,
+            Decision::Undecided { .. } => self.undecided += 1,
+        }
+    }
+
+    fn merge(&mut self, other: Distribution) {
+        self.machines += other.machines;
+        self.halted.extend(other.halted);
+        self.forever += other.forever;
+        self.undecided += other.undecided;
+    }
+
+    /// The fraction of the tables that halted.
+    pub fn halting_fraction(&self) -> f64 {
+        match self.machines {
+            0 => 0.0,
+            machines => self.halted.len() as f64 / machines as f64,
+        }
+    }
+
+    /// The halting time below which a fraction `q` of the halting runs
+    /// fall, `None` if none halted.
+    pub fn quantile(&self, q: f64) -> Option<u64> {
+        let last = self.halted.len().checked_sub(1)?;
+        let index = (q.clamp(0.0, 1.0) * last as f64).round() as usize;
+
+        Some(self.halted[index].0)
+    }
+
+    pub fn halting_histogram(&self, bins: usize, scale: Scale) -> Histogram {
+        Histogram::new(self.halted.iter().map(|&(steps, _)| steps), bins, scale)
+    }
+
+    pub fn sigma_histogram(&self, bins: usize, scale: Scale) -> Histogram {
+        Histogram::new(self.halted.iter().map(|&(_, sigma)| sigma), bins, scale)
+    }
+
+    /// The fraction of the tables still running after each halting time.
+    pub fn survival(&self) -> Survival {
+        let mut points = vec![(0, 1.0)];
+        let mut halted = 0;
+
+        for (index, &(steps, _)) in self.halted.iter().enumerate() {
+            halted += 1;
+
+            if self
+                .halted
+                .get(index + 1)
+                .is_some_and(|next| next.0 == steps)
+            {
+                continue;
+            }
+
+            let running = 1.0 - halted as f64 / self.machines as f64;
+
+            match points.last_mut() {
+                Some(last) if last.0 == steps => last.1 = running,
+                _ => points.push((steps, running)),
+            }
+        }
+
+        Survival { points }
+    }
+}
+
+/// How [`Histogram`] bins are spaced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scale {
+    Linear,
+    /// Growing geometrically from the smallest value, for heavy tails.
+    Log,
+}
+
+/// The values in `start..end`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bin {
+    pub start: u64,
+    pub end: u64,
+    pub count: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Histogram {
+    pub bins: Vec<Bin>,
+}
+
+impl Histogram {
+    /// Puts `values` into at most `bins` bins from the smallest to the
+    /// largest.
+    pub fn new(values: impl IntoIterator<Item = u64>, bins: usize, scale: Scale) -> Self {
+        let values: Vec<u64> = values.into_iter().collect();
+
+        let (Some(&min), Some(&max)) = (values.iter().min(), values.iter().max()) else {
+            return Self { bins: Vec::new() };
+        };
+
+        let bins = bins.max(1) as u64;
+        let range = max - min + 1;
+        let mut edges: Vec<u64> = match scale {
+            Scale::Linear => {
+                let width = range.div_ceil(bins);
+                (0..=range.div_ceil(width))
+                    .map(|i| min + i * width)
+                    .collect()
+            }
+            Scale::Log => (0..=bins)
+                .map(|i| match i {
+                    0 => min,
+                    i if i == bins => max + 1,
+                    i => {
+                        let edge = ((range + 1) as f64).powf(i as f64 / bins as f64).ceil();
+                        min + (edge as u64 - 1).min(range)
+                    }
+                })
+                .collect(),
+        };
+        // on a log scale the first bins can be narrower than one value
+        edges.dedup();
+
+        let mut bins: Vec<Bin> = edges
+            .windows(2)
+            .map(|edge| Bin {
+                start: edge[0],
+                end: edge[1],
+                count: 0,
+            })
+            .collect();
+
+        for value in values {
+            let index = bins.partition_point(|bin| bin.end <= value);
+            bins[index].count += 1;
+        }
+
+        Self { bins }
+    }
+
+    /// Writes a `start,end,count` line for every bin, after a header.
+    pub fn write_csv(&self, mut writer: impl Write) -> io::Result<()> {
+        writeln!(writer, "start,end,count")?;
+
+        for bin in &self.bins {
+            writeln!(writer, "{},{},{}", bin.start, bin.end, bin.count)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// `(steps, running)` points, `running` being the fraction of the tables
+/// that hadn't halted within `steps` steps; it stays the same up to the next
+/// point.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Survival {
+    pub points: Vec<(u64, f64)>,
+}
+
+impl Survival {
+    /// Writes a `steps,running` line for every point, after a header.
+    pub fn write_csv(&self, mut writer: impl Write) -> io::Result<()> {
+        writeln!(writer, "steps,running")?;
+
+        for &(steps, running) in &self.points {
+            writeln!(writer, "{steps},{running}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn all_test() {
+        let distribution = Experiment::all(2, 2, 100).threads(3).run().unwrap();
+        let halted = distribution.halted.len() as u64;
+
+        assert_eq!(distribution.machines, 28_561);
+        assert_eq!(
+            halted + distribution.forever + distribution.undecided,
+            distribution.machines
+        );
+        assert_eq!(distribution.halted.last(), Some(&(6, 4)));
+        assert_eq!(distribution.quantile(1.0), Some(6));
+
+        let histogram = distribution.halting_histogram(3, Scale::Linear);
+        assert_eq!(
+            histogram.bins.iter().map(|bin| bin.count).sum::<u64>(),
+            halted
+        );
+        assert_eq!(histogram.bins.last().unwrap().end, 7);
+
+        let survival = distribution.survival();
+        assert_eq!(survival.points[0], (0, 1.0));
+        assert!(survival
+            .points
+            .windows(2)
+            .all(|p| p[0].0 < p[1].0 && p[0].1 >= p[1].1));
+        assert_eq!(
+            survival.points.last().unwrap().1,
+            1.0 - distribution.halting_fraction()
+        );
+
+        let mut csv = Vec::new();
+        survival.write_csv(&mut csv).unwrap();
+        assert!(String::from_utf8(csv)
+            .unwrap()
+            .starts_with("steps,running\n0,1\n"));
+    }
+
+    #[test]
+    fn random_test() {
+        let one = Experiment::random(3, 2, 200, 2000, 7)
+            .threads(1)
+            .run()
+            .unwrap();
+        let many = Experiment::random(3, 2, 200, 2000, 7)
+            .threads(4)
+            .run()
+            .unwrap();
+
+        assert_eq!(one, many);
+        assert_eq!(one.machines, 2000);
+        assert_ne!(one, Experiment::random(3, 2, 200, 2000, 8).run().unwrap());
+    }
+
+    #[test]
+    fn histogram_test() {
+        let linear = Histogram::new([0, 1, 5, 9], 2, Scale::Linear);
+        assert_eq!(
+            linear.bins,
+            [
+                Bin {
+                    start: 0,
+                    end: 5,
+                    count: 2
+                },
+                Bin {
+                    start: 5,
+                    end: 10,
+                    count: 2
+                },
+            ]
+        );
+
+        let log = Histogram::new([1, 2, 3, 100, 1000], 3, Scale::Log);
+        assert!(log.bins.windows(2).all(|b| b[0].end == b[1].start));
+        assert!(log.bins[0].end - log.bins[0].start < log.bins[2].end - log.bins[2].start);
+        assert_eq!(log.bins.iter().map(|bin| bin.count).sum::<u64>(), 5);
+        assert!(Histogram::new([], 4, Scale::Log).bins.is_empty());
+    }
+}
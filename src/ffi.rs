@@ -0,0 +1,167 @@
+//! A C ABI for embedding the simulator; see `include/turing.h`. Build a
+//! linkable library with e.g.
+//! `cargo rustc --lib --release --features ffi --crate-type cdylib`.
+//!
+//! Machines are created from a table in the [`dsl`](crate::dsl) format over
+//! `u8` symbols (written as base-36 digits, `0` being the blank).
+
+use std::collections::VecDeque;
+use std::ffi::{c_char, CStr};
+use std::{ptr, slice};
+
+use crate::{dsl, Machine, TransitionTable};
+
+pub struct TuringMachine {
+    machine: Machine<String, u8>,
+    table: TransitionTable<String, u8>,
+    steps: u64,
+}
+
+/// Creates a machine from a serialized table, an initial state and an input
+/// tape. Returns null if any argument is invalid.
+///
+/// # Safety
+///
+/// `table` and `state` must be null-terminated strings, and `tape` must point
+/// to `tape_len` readable bytes (it may be null if `tape_len` is zero).
+#[no_mangle]
+pub unsafe extern "C" fn turing_machine_new(
+    table: *const c_char,
+    state: *const c_char,
+    tape: *const u8,
+    tape_len: usize,
+) -> *mut TuringMachine {
+    if table.is_null() || state.is_null() || (tape.is_null() && tape_len != 0) {
+        return ptr::null_mut();
+    }
+
+    let (Ok(table), Ok(state)) = (
+        CStr::from_ptr(table).to_str(),
+        CStr::from_ptr(state).to_str(),
+    ) else {
+        return ptr::null_mut();
+    };
+
+    let Ok(table) = dsl::parse(table) else {
+        return ptr::null_mut();
+    };
+
+    let mut tape: VecDeque<u8> = match tape_len {
+        0 => VecDeque::new(),
+        _ => slice::from_raw_parts(tape, tape_len)
+            .iter()
+            .copied()
+            .collect(),
+    };
+
+    if tape.is_empty() {
+        tape.push_back(0);
+    }
+
+    Box::into_raw(Box::new(TuringMachine {
+        machine: Machine::new(state.to_owned(), tape),
+        table,
+        steps: 0,
+    }))
+}
+
+/// Performs up to `steps` steps, stopping early on halt. Returns the number
+/// of steps taken.
+///
+/// # Safety
+///
+/// `machine` must come from [`turing_machine_new`] and not be freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn turing_machine_step(machine: *mut TuringMachine, steps: u64) -> u64 {
+    let machine = &mut *machine;
+    let taken = machine.machine.step_n_table(&machine.table, steps).steps;
+
+    machine.steps += taken;
+
+    taken
+}
+
+/// # Safety
+///
+/// `machine` must come from [`turing_machine_new`] and not be freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn turing_machine_halted(machine: *const TuringMachine) -> bool {
+    (*machine).machine.halted()
+}
+
+/// The total number of steps taken so far.
+///
+/// # Safety
+///
+/// `machine` must come from [`turing_machine_new`] and not be freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn turing_machine_steps(machine: *const TuringMachine) -> u64 {
+    (*machine).steps
+}
+
+/// The index of the head within the tape buffer.
+///
+/// # Safety
+///
+/// `machine` must come from [`turing_machine_new`] and not be freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn turing_machine_head(machine: *const TuringMachine) -> usize {
+    (*machine).machine.head()
+}
+
+/// Returns the tape as a contiguous buffer and stores its length in `len`.
+/// The buffer stays valid until the machine is stepped or freed.
+///
+/// # Safety
+///
+/// `machine` must come from [`turing_machine_new`] and not be freed yet, and
+/// `len` must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn turing_machine_tape(
+    machine: *mut TuringMachine,
+    len: *mut usize,
+) -> *const u8 {
+    let tape = (*machine).machine.tape.make_contiguous();
+    *len = tape.len();
+
+    tape.as_ptr()
+}
+
+/// # Safety
+///
+/// `machine` must come from [`turing_machine_new`] or be null, and must not
+/// be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn turing_machine_free(machine: *mut TuringMachine) {
+    if !machine.is_null() {
+        drop(Box::from_raw(machine));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ffi_round_trip_test() {
+        let table = c"inc 1 0 R *\ninc 0 1 N halt\ninc 0 1 N halt\n";
+        let state = c"inc";
+
+        unsafe {
+            assert!(turing_machine_new(table.as_ptr(), state.as_ptr(), ptr::null(), 0).is_null());
+
+            let table = c"inc 1 0 R *\ninc 0 1 N halt\n";
+            let machine = turing_machine_new(table.as_ptr(), state.as_ptr(), [1, 1].as_ptr(), 2);
+
+            assert_eq!(turing_machine_step(machine, 100), 3);
+            assert!(turing_machine_halted(machine));
+
+            let mut len = 0;
+            let tape = turing_machine_tape(machine, &mut len);
+
+            assert_eq!(slice::from_raw_parts(tape, len), [0, 0, 1]);
+
+            turing_machine_free(machine);
+        }
+    }
+}
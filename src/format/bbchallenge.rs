@@ -0,0 +1,325 @@
+//! The compact notation used by bbchallenge.org, e.g. `1RB1LB_1LA0LZ`.
+//!
+//! States are the letters `A`, `B`, ... with `A` as the start state, and each
+//! `_`-separated group lists the transitions for symbols `0`, `1`, ... as
+//! `write move next`. `---` leaves a transition undefined and a next state
+//! beyond the last one (conventionally `Z`) halts. Symbol `0` is the blank;
+//! other symbols are the digits `1` to `9`.
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use super::{default_blank, unit_move, Unsupported};
+use crate::dsl::{Definition, ParseError};
+use crate::{Move, Rule, State, Symbol, TransitionTable};
+
+const HALT: char = 'Z';
+
+pub fn parse<Sym>(src: &str) -> Result<Definition<Sym>, ParseError>
+where
+    Sym: Symbol + Ord + Default,
+{
+    let error = |message: String| ParseError { line: 1, message };
+    let groups: Vec<&str> = src.trim().split('_').collect();
+    let state_count = groups.len();
+    let name = |index: usize| char::from(b'A' + index as u8).to_string();
+
+    if state_count > 25 {
+        return Err(error(format!("too many states ({state_count})")));
+    }
+
+    let mut table = TransitionTable::new();
+
+    for (index, group) in groups.iter().enumerate() {
+        let chars: Vec<char> = group.chars().collect();
+
+        if chars.is_empty() || !chars.len().is_multiple_of(3) || chars.len() > 30 {
+            return Err(error(format!("malformed state {group:?}")));
+        }
+
+        for (read, transition) in chars.chunks(3).enumerate() {
+            let [write, head_move, next] = [transition[0], transition[1], transition[2]];
+
+            if [write, head_move, next] == ['-'; 3] {
+                continue;
+            }
+
+            let write = symbol(write).ok_or_else(|| error(format!("invalid symbol {write:?}")))?;
+            let head_move = match head_move {
+                'L' => Move::Left,
+                'R' => Move::Right,
+                _ => return Err(error(format!("invalid move {head_move:?}"))),
+            };
+            let new_state = match next {
+                'A'..='Z' if ((next as u8 - b'A') as usize) < state_count => {
+                    State::State(name((next as u8 - b'A') as usize))
+                }
+                'A'..='Z' => State::Halt,
+                _ => return Err(error(format!("invalid state {next:?}"))),
+            };
+
+            let rule = Rule {
+                new_state: Some(new_state),
+                write: Some(write),
+                head_move: Some(head_move),
+            };
+
+            let read = symbol(char::from(b'0' + read as u8)).unwrap();
+            table.insert(name(index), read, rule);
+        }
+    }
+
+    Ok(Definition {
+        start: name(0),
+        blank: None,
+        input_alphabet: None,
+        tape_alphabet: None,
+        table,
+    })
+}
+
+/// A normal form of the table `src`, the same for tables that differ only in
+/// the names of their states or by swapping left and right: states are
+/// renamed in the order a breadth-first walk from `A` reaches them, trying
+/// symbols in order, with unreachable states last, and moves are mirrored
+/// if the first transition goes left. Halting transitions all go to `Z`.
+pub fn normalize(src: &str) -> Result<String, ParseError> {
+    parse::<u8>(src)?;
+
+    let groups: Vec<Vec<char>> = src.trim().split('_').map(|g| g.chars().collect()).collect();
+    let next = |transition: &[char]| match transition[2] {
+        '-' => None,
+        next => Some((next as u8 - b'A') as usize).filter(|&next| next < groups.len()),
+    };
+
+    let mut order = Vec::from([0]);
+    let mut index = 0;
+
+    while let Some(&state) = order.get(index) {
+        for transition in groups[state].chunks(3) {
+            if let Some(next) = next(transition).filter(|next| !order.contains(next)) {
+                order.push(next);
+            }
+        }
+
+        index += 1;
+    }
+
+    for state in 0..groups.len() {
+        if !order.contains(&state) {
+            order.push(state);
+        }
+    }
+
+    let mirror = groups[0][1] == 'L';
+    let mut normal = Vec::new();
+
+    for &state in &order {
+        let mut group = String::new();
+
+        for transition in groups[state].chunks(3) {
+            if transition[0] == '-' {
+                group.push_str("---");
+                continue;
+            }
+
+            group.push(transition[0]);
+            group.push(match (transition[1], mirror) {
+                ('L', true) => 'R',
+                ('R', true) => 'L',
+                (head_move, _) => head_move,
+            });
+            group.push(match next(transition) {
+                Some(next) => {
+                    let index = order.iter().position(|&state| state == next).unwrap();
+                    char::from(b'A' + index as u8)
+                }
+                None => HALT,
+            });
+        }
+
+        normal.push(group);
+    }
+
+    Ok(normal.join("_"))
+}
+
+fn symbol<Sym>(digit: char) -> Option<Sym>
+where
+    Sym: Symbol + Default,
+{
+    match digit {
+        '0' => Some(Sym::blank()),
+        '1'..='9' => Sym::from_char(digit),
+        _ => None,
+    }
+}
+
+pub fn write<Sym>(definition: &Definition<Sym>) -> Result<String, Unsupported>
+where
+    Sym: Symbol + Ord + Default,
+{
+    default_blank(definition)?;
+
+    let table = &definition.table;
+
+    // the start state must come first; the rest keep their table order
+    let mut states: Vec<&String> = Vec::from([&definition.start]);
+
+    for state in table.states().chain(table.iter().filter_map(
+        |(_, _, rule)| match &rule.new_state {
+            Some(State::State(next)) => Some(next),
+            _ => None,
+        },
+    )) {
+        if !states.contains(&state) {
+            states.push(state);
+        }
+    }
+
+    if states.len() > 25 {
+        return Err(Unsupported(format!(
+            "{} states, at most 25 fit",
+            states.len()
+        )));
+    }
+
+    let letter = |state: &String| {
+        let index = states.iter().position(|other| *other == state).unwrap();
+        char::from(b'A' + index as u8)
+    };
+
+    // the blank is 0, and the other symbols their own digits if they all
+    // are one, or else numbered from 1 in order
+    let blank = Sym::blank();
+    let mut others: BTreeSet<&Sym> = BTreeSet::new();
+
+    for (_, read, rule) in table.iter() {
+        others.insert(read);
+        others.extend(&rule.write);
+    }
+
+    others.remove(&blank);
+
+    if others.len() > 9 {
+        return Err(Unsupported(format!(
+            "{} symbols, at most 10 fit",
+            others.len() + 1
+        )));
+    }
+
+    let own = others
+        .iter()
+        .all(|symbol| matches!(symbol.to_char(), '1'..='9'));
+    let digits: BTreeMap<&Sym, char> = others
+        .iter()
+        .enumerate()
+        .map(|(index, &symbol)| match own {
+            true => (symbol, symbol.to_char()),
+            false => (symbol, char::from(b'1' + index as u8)),
+        })
+        .collect();
+    let digit = |symbol: &Sym| match *symbol == blank {
+        true => '0',
+        false => digits[symbol],
+    };
+
+    let mut symbols = BTreeSet::from(['0', '1']);
+    symbols.extend(digits.values());
+
+    let symbol_count = (*symbols.last().unwrap() as u8 - b'0' + 1) as usize;
+    let mut groups = Vec::new();
+
+    for state in &states {
+        let mut group = String::new();
+
+        for read in 0..symbol_count {
+            let read_digit = char::from(b'0' + read as u8);
+            let rule = table
+                .rules(state)
+                .find(|(symbol, _)| digit(symbol) == read_digit);
+
+            let Some((_, rule)) = rule else {
+                group.push_str("---");
+                continue;
+            };
+
+            group.push(match &rule.write {
+                Some(write) => digit(write),
+                None => read_digit,
+            });
+            group.push(match unit_move(state, rule.head_move)? {
+                Some(head_move) => head_move,
+                None => return Err(Unsupported(format!("rule in state {state:?} doesn't move"))),
+            });
+            group.push(match &rule.new_state {
+                None => letter(state),
+                Some(State::State(next)) => letter(next),
+                Some(State::Halt) => HALT,
+            });
+        }
+
+        groups.push(group);
+    }
+
+    Ok(groups.join("_"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{dsl, Char, Machine, StopReason};
+
+    #[test]
+    fn bbchallenge_round_trip_test() {
+        // the 2-state busy beaver champion: 6 steps, four ones
+        let definition: Definition<Char> = parse("1RB1LB_1LA1RZ").unwrap();
+        let mut machine: Machine<String, Char> =
+            Machine::new(definition.start.clone(), [Char::default()].into());
+        let stats = machine.run_table(&definition.table, 100);
+
+        assert_eq!(stats.steps, 6);
+        assert_eq!(stats.stop, StopReason::Halted);
+        assert_eq!(machine.finish_trimmed().0, [Char('1'); 4]);
+        assert_eq!(write(&definition).unwrap(), "1RB1LB_1LA1RZ");
+    }
+
+    #[test]
+    fn bbchallenge_undefined_test() {
+        let definition: Definition<Char> = parse("1RB---_0LA1RA").unwrap();
+
+        assert_eq!(definition.table.len(), 3);
+        assert_eq!(write(&definition).unwrap(), "1RB---_0LA1RA");
+        assert!(parse::<Char>("1RB1L").is_err());
+        assert!(parse::<Char>("1XB1LB").is_err());
+    }
+
+    #[test]
+    fn bbchallenge_ordinal_test() {
+        // digits that aren't 1 to 9 are numbered in order after the blank
+        let definition: Definition<Char> =
+            dsl::parse_definition("a _ 1 R b\nb _ 0 L a\nb 1 1 R halt").unwrap();
+
+        assert_eq!(write(&definition).unwrap(), "2RB------_1LA---2RZ");
+    }
+
+    #[test]
+    fn normalize_test() {
+        // the 3-state champion with B and C swapped and mirrored
+        assert_eq!(
+            normalize("1LC1LZ_1RB1RA_1RC0LB").unwrap(),
+            "1RB1RZ_1LB0RC_1LC1LA"
+        );
+        assert_eq!(
+            normalize("1RB1RZ_1LB0RC_1LC1LA").unwrap(),
+            "1RB1RZ_1LB0RC_1LC1LA"
+        );
+        assert_eq!(
+            normalize("1RC---_0LA1RY_1LA0RA").unwrap(),
+            "1RB---_1LA0RA_0LA1RZ"
+        );
+        assert!(normalize("1RB1L").is_err());
+    }
+}
@@ -0,0 +1,109 @@
+//! Graphviz export of the state graph. Edges are labelled `read/write move`,
+//! with `*` for a cell left untouched.
+
+use alloc::collections::BTreeSet;
+use alloc::format;
+use alloc::string::String;
+use core::fmt::Write;
+
+use crate::dsl::Definition;
+use crate::{move_name, State, Symbol};
+
+pub fn write<Sym>(definition: &Definition<Sym>) -> String
+where
+    Sym: Symbol + Ord,
+{
+    let mut out = String::new();
+    let mut halts = false;
+    let mut states = BTreeSet::new();
+
+    out.push_str("digraph turing {\n    rankdir=LR;\n");
+    out.push_str("    start [shape=point];\n");
+
+    writeln!(out, "    start -> {};", quote(&definition.start)).unwrap();
+
+    for (state, read, rule) in definition.table.iter() {
+        let next = match &rule.new_state {
+            None => quote(state),
+            Some(State::State(next)) => {
+                states.insert(next);
+                quote(next)
+            }
+            Some(State::Halt) => {
+                halts = true;
+                String::from("halt")
+            }
+        };
+        let write = rule.write.as_ref().map_or('*', Symbol::to_char);
+
+        states.insert(state);
+
+        writeln!(
+            out,
+            "    {} -> {next} [label={}];",
+            quote(state),
+            quote(&format!(
+                "{}/{write} {}",
+                read.to_char(),
+                move_name(rule.head_move)
+            ))
+        )
+        .unwrap();
+    }
+
+    states.insert(&definition.start);
+
+    for state in states {
+        writeln!(out, "    {} [shape=circle];", quote(state)).unwrap();
+    }
+
+    if halts {
+        out.push_str("    halt [shape=doublecircle];\n");
+    }
+
+    out.push_str("}\n");
+
+    out
+}
+
+fn quote(id: &str) -> String {
+    let mut quoted = String::from("\"");
+
+    for c in id.chars() {
+        if matches!(c, '"' | '\\') {
+            quoted.push('\\');
+        }
+
+        quoted.push(c);
+    }
+
+    quoted.push('"');
+
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{dsl, Char};
+
+    #[test]
+    fn dot_test() {
+        let definition: Definition<Char> =
+            dsl::parse_definition("inc 1 0 R *\ninc _ 1 N halt").unwrap();
+
+        assert_eq!(
+            write(&definition),
+            "digraph turing {
+    rankdir=LR;
+    start [shape=point];
+    start -> \"inc\";
+    \"inc\" -> \"inc\" [label=\"1/0 R\"];
+    \"inc\" -> halt [label=\"_/1 N\"];
+    \"inc\" [shape=circle];
+    halt [shape=doublecircle];
+}
+"
+        );
+    }
+}
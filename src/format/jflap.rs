@@ -0,0 +1,323 @@
+//! JFLAP's `.jff` files for single-tape Turing machines.
+//!
+//! An empty `<read/>` or `<write/>` is JFLAP's blank. Final states without
+//! outgoing transitions into which the machine moves are treated as halting
+//! when they are named `halt`; all other states keep their names.
+
+use alloc::borrow::ToOwned;
+use alloc::collections::BTreeSet;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+use super::{default_blank, single_char, unit_move, Unsupported};
+use crate::dsl::{Definition, ParseError};
+use crate::{Move, Rule, State, Symbol, TransitionTable};
+
+pub fn parse<Sym>(src: &str) -> Result<Definition<Sym>, ParseError>
+where
+    Sym: Symbol + Ord + Default,
+{
+    let error = |at: usize, message: String| ParseError {
+        line: src[..at].lines().count().max(1),
+        message,
+    };
+
+    if let Some((at, kind)) = elements(src, "type").next() {
+        if kind.trim() != "turing" {
+            return Err(error(
+                at,
+                format!("not a Turing machine but {:?}", kind.trim()),
+            ));
+        }
+    }
+
+    // id -> name, plus the initial state's id
+    let mut names = Vec::new();
+    let mut start = None;
+
+    for (at, tag, body) in elements_with_tag(src, "state") {
+        let id = attribute(tag, "id").ok_or_else(|| error(at, "state without id".to_owned()))?;
+        let name = attribute(tag, "name").unwrap_or_else(|| format!("q{id}"));
+
+        if body.contains("<initial") {
+            start = Some(name.clone());
+        }
+
+        names.push((id, name, body.contains("<final")));
+    }
+
+    let name = |at: usize, id: &str| {
+        names
+            .iter()
+            .find(|(other, _, _)| other == id)
+            .map(|(_, name, _)| name.clone())
+            .ok_or_else(|| error(at, format!("unknown state id {id:?}")))
+    };
+
+    let symbol = |at: usize, text: Option<String>| -> Result<Sym, ParseError> {
+        match text.as_deref().map(unescape) {
+            None => Ok(Sym::blank()),
+            Some(text) if text.is_empty() => Ok(Sym::blank()),
+            Some(text) => single_char(&text)
+                .and_then(Sym::from_char)
+                .ok_or_else(|| error(at, format!("invalid symbol {text:?}"))),
+        }
+    };
+
+    let transitions: Vec<_> = elements(src, "transition").collect();
+    let sources: BTreeSet<String> = transitions
+        .iter()
+        .filter_map(|(_, body)| text(body, "from"))
+        .collect();
+
+    let halting: Vec<String> = names
+        .iter()
+        .filter(|(id, name, is_final)| *is_final && name == "halt" && !sources.contains(id))
+        .map(|(_, name, _)| name.clone())
+        .collect();
+
+    let mut table = TransitionTable::new();
+
+    for (at, body) in transitions {
+        if body.contains("tape=") {
+            return Err(error(
+                at,
+                "multi-tape machines are not supported".to_owned(),
+            ));
+        }
+
+        let field = |tag: &str| {
+            text(body, tag).ok_or_else(|| error(at, format!("transition without <{tag}>")))
+        };
+
+        let from = name(at, &field("from")?)?;
+        let to = name(at, &field("to")?)?;
+        let read = symbol(at, text(body, "read"))?;
+        let write = symbol(at, text(body, "write"))?;
+        let head_move = match field("move")?.trim() {
+            "L" => Some(Move::Left),
+            "R" => Some(Move::Right),
+            "S" => None,
+            other => return Err(error(at, format!("invalid move {other:?}"))),
+        };
+
+        let rule = Rule {
+            new_state: Some(match halting.contains(&to) {
+                true => State::Halt,
+                false => State::State(to),
+            }),
+            write: Some(write),
+            head_move,
+        };
+
+        if table.insert(from.clone(), read, rule).is_some() {
+            return Err(error(
+                at,
+                format!("nondeterministic transitions from {from:?}"),
+            ));
+        }
+    }
+
+    let start = start.ok_or_else(|| error(src.len(), "missing initial state".to_owned()))?;
+
+    Ok(Definition {
+        start,
+        blank: None,
+        input_alphabet: None,
+        tape_alphabet: None,
+        table,
+    })
+}
+
+pub fn write<Sym>(definition: &Definition<Sym>) -> Result<String, Unsupported>
+where
+    Sym: Symbol + Ord + Default,
+{
+    default_blank(definition)?;
+
+    let table = &definition.table;
+    let mut states: Vec<&String> = Vec::from([&definition.start]);
+    let mut halts = false;
+
+    for (state, _, rule) in table.iter() {
+        let next = match &rule.new_state {
+            Some(State::State(next)) => Some(next),
+            Some(State::Halt) => {
+                halts = true;
+                None
+            }
+            None => None,
+        };
+
+        for state in [Some(state), next].into_iter().flatten() {
+            if !states.contains(&state) {
+                states.push(state);
+            }
+        }
+    }
+
+    let halt = "halt".to_owned();
+
+    if halts && !states.contains(&&halt) {
+        states.push(&halt);
+    }
+
+    let id = |state: &String| states.iter().position(|other| *other == state).unwrap();
+    let mut out = String::new();
+
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"no\"?>\n");
+    out.push_str("<structure>\n\t<type>turing</type>\n\t<automaton>\n");
+
+    for (index, state) in states.iter().enumerate() {
+        writeln!(out, "\t\t<state id=\"{index}\" name=\"{}\">", escape(state)).unwrap();
+        writeln!(
+            out,
+            "\t\t\t<x>{}</x>\n\t\t\t<y>{}</y>",
+            100 + 150 * (index % 6),
+            100 + 150 * (index / 6)
+        )
+        .unwrap();
+
+        if index == 0 {
+            out.push_str("\t\t\t<initial/>\n");
+        }
+
+        if halts && **state == halt {
+            out.push_str("\t\t\t<final/>\n");
+        }
+
+        out.push_str("\t\t</state>\n");
+    }
+
+    let symbol = |symbol: &Sym| match *symbol == Sym::blank() {
+        true => String::new(),
+        false => escape(&symbol.to_char().to_string()),
+    };
+
+    for (state, read, rule) in table.iter() {
+        let to = match &rule.new_state {
+            None => id(state),
+            Some(State::State(next)) => id(next),
+            Some(State::Halt) => id(&halt),
+        };
+        let head_move = unit_move(state, rule.head_move)?.unwrap_or('S');
+        let write = symbol(rule.write.as_ref().unwrap_or(read));
+
+        writeln!(
+            out,
+            "\t\t<transition>\n\t\t\t<from>{}</from>\n\t\t\t<to>{to}</to>\n\t\t\t<read>{}</read>\n\t\t\t<write>{write}</write>\n\t\t\t<move>{head_move}</move>\n\t\t</transition>",
+            id(state),
+            symbol(read),
+        )
+        .unwrap();
+    }
+
+    out.push_str("\t</automaton>\n</structure>\n");
+
+    Ok(out)
+}
+
+// the bodies of all `<tag>...</tag>` elements, with their byte offsets
+fn elements<'a>(src: &'a str, tag: &'a str) -> impl Iterator<Item = (usize, &'a str)> {
+    elements_with_tag(src, tag).map(|(at, _, body)| (at, body))
+}
+
+// like `elements`, but also returns the opening tag for reading attributes.
+// self-closing elements have an empty body
+fn elements_with_tag<'a>(
+    src: &'a str,
+    tag: &'a str,
+) -> impl Iterator<Item = (usize, &'a str, &'a str)> {
+    let open = format!("<{tag}");
+    let close = format!("</{tag}>");
+    let mut offset = 0;
+
+    core::iter::from_fn(move || loop {
+        let start = offset + src[offset..].find(&open)?;
+        let after = start + open.len();
+
+        // skip longer tag names sharing the prefix, e.g. <states>
+        if !src[after..].starts_with(['>', '/', ' ', '\t', '\n', '\r']) {
+            offset = after;
+            continue;
+        }
+
+        let tag_end = after + src[after..].find('>')?;
+        let opening = &src[start..=tag_end];
+
+        if opening.ends_with("/>") {
+            offset = tag_end + 1;
+            return Some((start, opening, ""));
+        }
+
+        let body_end = tag_end + 1 + src[tag_end + 1..].find(&close)?;
+        offset = body_end + close.len();
+
+        return Some((start, opening, &src[tag_end + 1..body_end]));
+    })
+}
+
+fn text(body: &str, tag: &str) -> Option<String> {
+    elements(body, tag).next().map(|(_, text)| text.to_owned())
+}
+
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    let pattern = format!(" {name}=\"");
+    let start = tag.find(&pattern)? + pattern.len();
+    let end = start + tag[start..].find('"')?;
+
+    Some(unescape(&tag[start..end]))
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{dsl, Char};
+
+    #[test]
+    fn jflap_round_trip_test() {
+        let definition: Definition<Char> =
+            dsl::parse_definition("inc 1 0 R inc\ninc 0 1 N halt\ninc _ 1 N halt").unwrap();
+        let jff = write(&definition).unwrap();
+
+        assert!(jff.contains("<read></read>"));
+        assert_eq!(parse::<Char>(&jff).unwrap(), definition);
+    }
+
+    #[test]
+    fn parse_jflap_test() {
+        let jff = r#"<?xml version="1.0" encoding="UTF-8" standalone="no"?>
+<structure>
+    <type>turing</type>
+    <automaton>
+        <state id="0" name="q0"><x>0</x><y>0</y><initial/></state>
+        <state id="1" name="q1"><x>0</x><y>0</y><final/></state>
+        <transition><from>0</from><to>0</to><read>a</read><write>b</write><move>R</move></transition>
+        <transition><from>0</from><to>1</to><read/><write/><move>S</move></transition>
+    </automaton>
+</structure>"#;
+
+        let definition: Definition<Char> = parse(jff).unwrap();
+        let expected = dsl::parse_definition("q0 a b R q0\nq0 _ _ N q1").unwrap();
+
+        assert_eq!(definition, expected);
+        assert!(parse::<Char>("<structure><type>fa</type></structure>").is_err());
+    }
+}
@@ -0,0 +1,67 @@
+//! Converters between [`Definition`](crate::dsl::Definition)s and the
+//! machine formats of other tools.
+//!
+//! Formats without an explicit halting state map the halting state to a
+//! state named `halt` without rules, the same convention [`dsl`](crate::dsl)
+//! uses.
+
+pub mod bbchallenge;
+pub mod dot;
+pub mod jflap;
+pub mod number;
+pub mod simulator;
+pub mod yaml;
+
+use alloc::format;
+use alloc::string::String;
+use core::error::Error;
+use core::fmt::{self, Display};
+
+use crate::dsl::Definition;
+use crate::Move;
+
+/// Returned when a definition uses features the target format lacks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Unsupported(pub String);
+
+impl Display for Unsupported {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unsupported by the target format: {}", self.0)
+    }
+}
+
+impl Error for Unsupported {}
+
+fn single_char(s: &str) -> Option<char> {
+    let mut chars = s.chars();
+
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(c),
+        _ => None,
+    }
+}
+
+// `L` or `R` for a move of one cell, for formats without longer moves
+fn unit_move(state: &str, head_move: Option<Move>) -> Result<Option<char>, Unsupported> {
+    match head_move.map_or(0, Move::offset) {
+        0 => Ok(None),
+        -1 => Ok(Some('L')),
+        1 => Ok(Some('R')),
+        _ => Err(Unsupported(format!(
+            "rule in state {state:?} moves more than one cell"
+        ))),
+    }
+}
+
+// formats whose blank is always `Sym::default()`
+fn default_blank<Sym>(definition: &Definition<Sym>) -> Result<(), Unsupported>
+where
+    Sym: Default + PartialEq,
+{
+    match &definition.blank {
+        Some(blank) if *blank != Sym::default() => {
+            Err(Unsupported("blank symbol other than the default".into()))
+        }
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,262 @@
+//! A numbering of machines: each table in [`bbchallenge`] form with `n`
+//! states and `k` symbols gets a distinct number below `(2k(n + 1) + 1)^(nk)`,
+//! and every such number is the description of a table.
+//!
+//! The transitions, in the order of the bbchallenge notation, are the digits
+//! of the number in base `2k(n + 1) + 1`, most significant first: 0 for an
+//! undefined transition, and otherwise 1 plus the write, move and next state
+//! (with `n` for halting) read as a number in bases `k`, 2 and `n + 1`.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use super::{bbchallenge, Unsupported};
+use crate::dsl::Definition;
+use crate::Symbol;
+
+/// The number of a table with `states` states and `symbols` symbols, stored
+/// as big-endian bytes.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Number {
+    states: usize,
+    symbols: usize,
+    magnitude: Vec<u8>,
+}
+
+impl Number {
+    /// The number with big-endian bytes `magnitude`.
+    pub fn new(states: usize, symbols: usize, magnitude: &[u8]) -> Self {
+        let start = magnitude.iter().position(|&byte| byte != 0);
+
+        Self {
+            states,
+            symbols,
+            magnitude: start.map_or(Vec::new(), |start| magnitude[start..].to_vec()),
+        }
+    }
+
+    pub fn from_u128(states: usize, symbols: usize, n: u128) -> Self {
+        Self::new(states, symbols, &n.to_be_bytes())
+    }
+
+    pub fn states(&self) -> usize {
+        self.states
+    }
+
+    pub fn symbols(&self) -> usize {
+        self.symbols
+    }
+
+    /// The big-endian bytes of the number, without leading zeros.
+    pub fn magnitude(&self) -> &[u8] {
+        &self.magnitude
+    }
+
+    pub fn to_u128(&self) -> Option<u128> {
+        if self.magnitude.len() > 16 {
+            return None;
+        }
+
+        Some(
+            self.magnitude
+                .iter()
+                .fold(0, |n, &byte| n << 8 | u128::from(byte)),
+        )
+    }
+
+    /// The number as a single byte string: the state and symbol counts
+    /// followed by the magnitude.
+    pub fn to_bytes(&self) -> Option<Vec<u8>> {
+        let header = [
+            u8::try_from(self.states).ok()?,
+            u8::try_from(self.symbols).ok()?,
+        ];
+        Some(header.iter().chain(&self.magnitude).copied().collect())
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        match bytes {
+            [states, symbols, magnitude @ ..] => Some(Self::new(
+                usize::from(*states),
+                usize::from(*symbols),
+                magnitude,
+            )),
+            _ => None,
+        }
+    }
+
+    /// How many tables have `states` states and `symbols` symbols, if that
+    /// fits.
+    pub fn count(states: usize, symbols: usize) -> Option<u128> {
+        let base = u128::try_from(base(states, symbols)).ok()?;
+        base.checked_pow(u32::try_from(states * symbols).ok()?)
+    }
+}
+
+fn base(states: usize, symbols: usize) -> usize {
+    2 * symbols * (states + 1) + 1
+}
+
+/// The number of `definition`. Tables keep only the states and symbols they
+/// use, so decoding a number whose last states or symbols are unused and
+/// encoding the result gives the number of the smaller table.
+pub fn encode<Sym>(definition: &Definition<Sym>) -> Result<Number, Unsupported>
+where
+    Sym: Symbol + Ord + Default,
+{
+    let notation = bbchallenge::write(definition)?;
+    let groups: Vec<&str> = notation.split('_').collect();
+    let states = groups.len();
+    let symbols = groups[0].len() / 3;
+    let base = base(states, symbols) as u32;
+    let mut magnitude = Vec::new();
+
+    for group in groups {
+        let chars: Vec<char> = group.chars().collect();
+
+        for transition in chars.chunks(3) {
+            let digit = match transition {
+                ['-', '-', '-'] => 0,
+                &[write, head_move, next] => {
+                    let write = write.to_digit(10).unwrap() as usize;
+                    let head_move = usize::from(head_move == 'R');
+                    let next = ((next as u8 - b'A') as usize).min(states);
+
+                    1 + (write * 2 + head_move) * (states + 1) + next
+                }
+                _ => unreachable!(),
+            };
+
+            mul_add(&mut magnitude, base, digit as u32);
+        }
+    }
+
+    Ok(Number::new(states, symbols, &magnitude))
+}
+
+/// The table numbered `number`, or `None` if the number is too large for
+/// its state and symbol counts.
+pub fn decode<Sym>(number: &Number) -> Option<Definition<Sym>>
+where
+    Sym: Symbol + Ord + Default,
+{
+    let (states, symbols) = (number.states, number.symbols);
+
+    if states == 0 || states > 25 || !(1..=10).contains(&symbols) {
+        return None;
+    }
+
+    let base = base(states, symbols) as u32;
+    let mut magnitude = number.magnitude.clone();
+    let mut digits: Vec<usize> = (0..states * symbols)
+        .map(|_| div_rem(&mut magnitude, base) as usize)
+        .collect();
+
+    if !magnitude.is_empty() {
+        return None;
+    }
+
+    digits.reverse();
+
+    let mut notation = String::new();
+
+    for (index, digit) in digits.into_iter().enumerate() {
+        if index > 0 && index % symbols == 0 {
+            notation.push('_');
+        }
+
+        if digit == 0 {
+            notation.push_str("---");
+            continue;
+        }
+
+        let (rest, next) = ((digit - 1) / (states + 1), (digit - 1) % (states + 1));
+        let next = match next {
+            next if next == states => 'Z',
+            next => char::from(b'A' + next as u8),
+        };
+
+        notation.push(char::from(b'0' + (rest / 2) as u8));
+        notation.push(if rest % 2 == 1 { 'R' } else { 'L' });
+        notation.push(next);
+    }
+
+    bbchallenge::parse(&notation).ok()
+}
+
+// `magnitude = magnitude * base + add`, big-endian
+fn mul_add(magnitude: &mut Vec<u8>, base: u32, add: u32) {
+    let mut carry = add;
+
+    for byte in magnitude.iter_mut().rev() {
+        let value = u32::from(*byte) * base + carry;
+        *byte = value as u8;
+        carry = value >> 8;
+    }
+
+    while carry > 0 {
+        magnitude.insert(0, carry as u8);
+        carry >>= 8;
+    }
+}
+
+// `magnitude /= base`, returning the remainder
+fn div_rem(magnitude: &mut Vec<u8>, base: u32) -> u32 {
+    let mut rem = 0;
+
+    for byte in magnitude.iter_mut() {
+        let value = rem << 8 | u32::from(*byte);
+        *byte = (value / base) as u8;
+        rem = value % base;
+    }
+
+    let start = magnitude
+        .iter()
+        .position(|&byte| byte != 0)
+        .unwrap_or(magnitude.len());
+    magnitude.drain(..start);
+    rem
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn number_round_trip_test() {
+        for notation in [
+            "1RB1LB_1LA1RZ",
+            "1RB---_0LA1RA",
+            "1RB2LA1RA1RA_1LB1LA3RB1RZ",
+        ] {
+            let definition: Definition<u8> = bbchallenge::parse(notation).unwrap();
+            let number = encode(&definition).unwrap();
+            let bytes = number.to_bytes().unwrap();
+
+            assert_eq!(Number::from_bytes(&bytes), Some(number.clone()));
+            assert_eq!(
+                bbchallenge::write(&decode::<u8>(&number).unwrap()).unwrap(),
+                notation
+            );
+        }
+    }
+
+    #[test]
+    fn enumeration_test() {
+        // every number below the count is a distinct table
+        let count = Number::count(1, 2).unwrap();
+
+        assert_eq!(count, 81);
+
+        let notations: alloc::collections::BTreeSet<String> = (0..count)
+            .map(|n| {
+                let definition = decode::<u8>(&Number::from_u128(1, 2, n)).unwrap();
+                assert_eq!(encode(&definition).unwrap().to_u128(), Some(n));
+                bbchallenge::write(&definition).unwrap()
+            })
+            .collect();
+
+        assert_eq!(notations.len(), 81);
+        assert_eq!(decode::<u8>(&Number::from_u128(1, 2, count)), None);
+    }
+}
@@ -0,0 +1,321 @@
+//! The program syntax of turingmachinesimulator.com, for machines with any
+//! number of tapes:
+//!
+//! ```text
+//! name: Binary numbers divisible by 3
+//! init: q0
+//! accept: q0
+//!
+//! // a rule: the state and the symbols read, then the next state, the
+//! // symbols written and the moves, one per tape
+//! q0,0
+//! q0,0,>
+//!
+//! q0,1
+//! q1,1,>
+//! ```
+//!
+//! `_` is the blank and moves are `<`, `>` or `-` for staying.
+
+use alloc::borrow::ToOwned;
+use alloc::collections::VecDeque;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::array;
+
+use super::single_char;
+use crate::dsl::{Definition, ParseError};
+use crate::multi::{MultiMachine, MultiRule, MultiTable};
+use crate::{Move, Rule, State, Symbol, TransitionTable, Undecided, Verdict};
+
+/// A program for `K` tapes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Program<Sym, const K: usize> {
+    pub name: Option<String>,
+    pub start: String,
+    /// The states in which the machine halts and accepts.
+    pub accept: Vec<String>,
+    pub table: MultiTable<String, Sym, K>,
+}
+
+impl<Sym, const K: usize> Program<Sym, K>
+where
+    Sym: Ord + Clone + Default,
+{
+    /// Runs the program on `input` written on the first tape, for at most
+    /// `max_steps` steps. Like on the site, the machine accepts as soon as
+    /// it's in an accepting state and rejects when it has no rule.
+    pub fn verdict(&self, input: &[Sym], max_steps: u64) -> Result<Verdict, Undecided> {
+        let tapes = array::from_fn(|tape| match tape {
+            0 => input.iter().cloned().collect(),
+            _ => VecDeque::new(),
+        });
+        let mut machine = MultiMachine::new(self.start.clone(), tapes);
+
+        for steps in 0..=max_steps {
+            let Some(state) = machine.state() else {
+                return Ok(Verdict::Reject);
+            };
+
+            if self.accept.contains(state) {
+                return Ok(Verdict::Accept);
+            }
+
+            if steps < max_steps {
+                machine.step(&self.table);
+            }
+        }
+
+        Err(Undecided { steps: max_steps })
+    }
+}
+
+impl<Sym> Program<Sym, 1>
+where
+    Sym: Ord + Clone,
+{
+    /// The single-tape program as a definition, without its accepting
+    /// states.
+    pub fn definition(&self) -> Definition<Sym> {
+        let table: TransitionTable<String, Sym> = self
+            .table
+            .iter()
+            .map(|(state, [read], rule)| {
+                let [write] = rule.write.clone();
+                let [head_move] = rule.head_move;
+
+                (
+                    state.clone(),
+                    read.clone(),
+                    Rule {
+                        new_state: rule.new_state.clone(),
+                        write,
+                        head_move,
+                    },
+                )
+            })
+            .collect();
+
+        Definition {
+            start: self.start.clone(),
+            blank: None,
+            input_alphabet: None,
+            tape_alphabet: None,
+            table,
+        }
+    }
+}
+
+pub fn parse<Sym, const K: usize>(src: &str) -> Result<Program<Sym, K>, ParseError>
+where
+    Sym: Symbol + Ord + Default,
+{
+    let mut name = None;
+    let mut start = None;
+    let mut accept = Vec::new();
+    let mut table = MultiTable::new();
+    // the line of a rule's state and symbols read, until its second line
+    let mut pending: Option<(usize, Vec<&str>)> = None;
+
+    for (index, line) in src.lines().enumerate() {
+        let line_number = index + 1;
+        let error = |message: String| ParseError {
+            line: line_number,
+            message,
+        };
+
+        let line = line.split_once("//").map_or(line, |(line, _)| line).trim();
+
+        if line.is_empty() {
+            continue;
+        }
+
+        if pending.is_none() {
+            if let Some((key, value)) = line.split_once(':') {
+                let value = value.trim();
+
+                match key.trim() {
+                    "name" => name = Some(value.to_owned()),
+                    "init" => start = Some(value.to_owned()),
+                    "accept" => accept.extend(
+                        value
+                            .split(',')
+                            .map(str::trim)
+                            .filter(|state| !state.is_empty())
+                            .map(str::to_owned),
+                    ),
+                    key => return Err(error(format!("unknown setting {key:?}"))),
+                }
+
+                continue;
+            }
+        }
+
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+
+        let Some((first, read)) = pending.take() else {
+            if fields.len() != 1 + K {
+                return Err(error(format!(
+                    "expected a state and {K} symbols, found {line:?}"
+                )));
+            }
+
+            pending = Some((line_number, fields));
+            continue;
+        };
+
+        if fields.len() != 1 + 2 * K {
+            return Err(error(format!(
+                "expected a state, {K} symbols and {K} moves, found {line:?}"
+            )));
+        }
+
+        let symbol = |symbol: &str| {
+            single_char(symbol)
+                .and_then(|c| match c {
+                    '_' => Some(Sym::blank()),
+                    c => Sym::from_char(c),
+                })
+                .ok_or_else(|| error(format!("invalid symbol {symbol:?}")))
+        };
+
+        let state = read[0].to_owned();
+        let read = read[1..]
+            .iter()
+            .map(|read| {
+                symbol(read).map_err(|error| ParseError {
+                    line: first,
+                    message: error.message,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let write = fields[1..=K]
+            .iter()
+            .map(|write| symbol(write).map(Some))
+            .collect::<Result<Vec<_>, _>>()?;
+        let head_move = fields[K + 1..]
+            .iter()
+            .map(|head_move| match *head_move {
+                "<" => Ok(Some(Move::Left)),
+                ">" => Ok(Some(Move::Right)),
+                "-" => Ok(None),
+                head_move => Err(error(format!("invalid move {head_move:?}"))),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let rule = MultiRule {
+            new_state: Some(State::State(fields[0].to_owned())),
+            write: to_array(write),
+            head_move: to_array(head_move),
+        };
+
+        if table.insert(state.clone(), to_array(read), rule).is_some() {
+            return Err(ParseError {
+                line: first,
+                message: format!("duplicate rule for state {state:?}"),
+            });
+        }
+    }
+
+    if let Some((line, _)) = pending {
+        return Err(ParseError {
+            line,
+            message: "rule without a second line".to_owned(),
+        });
+    }
+
+    let start = start.ok_or_else(|| ParseError {
+        line: src.lines().count(),
+        message: "missing init state".to_owned(),
+    })?;
+
+    Ok(Program {
+        name,
+        start,
+        accept,
+        table,
+    })
+}
+
+// `items` has exactly `K` items, as checked by the caller
+fn to_array<T, const K: usize>(items: Vec<T>) -> [T; K] {
+    let mut items = items.into_iter();
+    array::from_fn(|_| items.next().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Acceptance, Char, Machine};
+
+    const DIVISIBLE_BY_3: &str = "name: Binary numbers divisible by 3
+init: q0
+accept: q0
+
+q0,0
+q0,0,>
+
+q0,1
+q1,1,>
+
+q1,0
+q2,0,>
+
+q1,1
+q0,1,>
+
+q2,0
+q1,0,>
+
+q2,1
+q2,1,>
+";
+
+    #[test]
+    fn single_tape_test() {
+        let program = parse::<Char, 1>(DIVISIBLE_BY_3).unwrap();
+        let definition = program.definition();
+        let acceptance = Acceptance::FinalState(program.accept.clone());
+
+        assert_eq!(
+            program.name.as_deref(),
+            Some("Binary numbers divisible by 3")
+        );
+
+        for (input, accept) in [("110", true), ("111", false), ("1001", true)] {
+            let mut machine = Machine::with_input(definition.start.clone(), input).unwrap();
+            let verdict = machine
+                .run_table_accepting(&definition.table, 100, &acceptance)
+                .unwrap();
+
+            assert_eq!(verdict.is_accept(), accept);
+        }
+    }
+
+    #[test]
+    fn multi_tape_test() {
+        // copies the input onto the second tape, accepting on the blank
+        let program = parse::<Char, 2>(
+            "init: copy
+accept: done
+
+copy,1,_
+copy,1,1,>,>
+
+// the end of the input
+copy,_,_
+done,_,_,-,-
+",
+        )
+        .unwrap();
+        let input = [Char('1'), Char('1')];
+
+        assert_eq!(program.table.len(), 2);
+        assert_eq!(program.verdict(&input, 10), Ok(Verdict::Accept));
+        assert_eq!(program.verdict(&[Char('0')], 10), Ok(Verdict::Reject));
+        assert_eq!(program.verdict(&input, 1), Err(Undecided { steps: 1 }));
+
+        assert!(parse::<Char, 2>("init: copy\ncopy,1\ncopy,1,>").is_err());
+    }
+}
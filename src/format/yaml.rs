@@ -0,0 +1,396 @@
+//! The YAML dialect of turingmachine.io:
+//!
+//! ```yaml
+//! blank: '_'
+//! start state: inc
+//! table:
+//!   inc:
+//!     '1': {write: '0', R}
+//!     ['0', '_']: {write: '1', R: halt}
+//!   halt:
+//! ```
+//!
+//! Only this subset of YAML is understood: flow-style rule keys and actions,
+//! and block mappings indented with spaces. Every rule has to move the head.
+
+use alloc::borrow::ToOwned;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+use super::{default_blank, single_char, unit_move, Unsupported};
+use crate::dsl::{Definition, ParseError};
+use crate::{Move, Rule, State, Symbol, TransitionTable};
+
+pub fn parse<Sym>(src: &str) -> Result<Definition<Sym>, ParseError>
+where
+    Sym: Symbol + Ord + Default,
+{
+    let mut blank = None;
+    let mut start = None;
+    let mut in_table = false;
+    let mut state_indent = None;
+    let mut current: Option<String> = None;
+    let mut empty_states = Vec::new();
+
+    // rules are collected first since the blank may be declared after them
+    let mut rules: Vec<(usize, String, Vec<String>, Action)> = Vec::new();
+
+    for (index, line) in src.lines().enumerate() {
+        let line_number = index + 1;
+        let error = |message: String| ParseError {
+            line: line_number,
+            message,
+        };
+
+        let line = strip_comment(line);
+
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let indent = line.len() - line.trim_start().len();
+        let (key, value) = split_key(line.trim())
+            .ok_or_else(|| error(format!("expected a mapping entry, found {:?}", line.trim())))?;
+
+        if indent == 0 {
+            in_table = key == "table";
+            current = None;
+
+            match key {
+                "blank" => {
+                    blank = Some(
+                        scalar(value)
+                            .and_then(|blank| single_char(&blank))
+                            .ok_or_else(|| error("invalid blank".to_owned()))?,
+                    )
+                }
+                "start state" => {
+                    start =
+                        Some(scalar(value).ok_or_else(|| error("invalid start state".to_owned()))?)
+                }
+                _ => {}
+            }
+
+            continue;
+        }
+
+        if !in_table {
+            continue;
+        }
+
+        if *state_indent.get_or_insert(indent) == indent {
+            let state = scalar(key).ok_or_else(|| error(format!("invalid state {key:?}")))?;
+
+            if !value.is_empty() {
+                return Err(error(format!("expected rules for state {state:?}")));
+            }
+
+            empty_states.push(state.clone());
+            current = Some(state);
+            continue;
+        }
+
+        let Some(state) = &current else {
+            return Err(error("rule outside of a state".to_owned()));
+        };
+
+        let symbols = symbols(key).ok_or_else(|| error(format!("invalid symbols {key:?}")))?;
+        let action = action(value).ok_or_else(|| error(format!("invalid action {value:?}")))?;
+
+        empty_states.retain(|empty| empty != state);
+        rules.push((line_number, state.clone(), symbols, action));
+    }
+
+    let blank = blank.unwrap_or(' ');
+    let to_symbol = |symbol: &str, line: usize| {
+        single_char(symbol)
+            .and_then(|c| match c == blank {
+                true => Some(Sym::blank()),
+                false => Sym::from_char(c),
+            })
+            .ok_or_else(|| ParseError {
+                line,
+                message: format!("invalid symbol {symbol:?}"),
+            })
+    };
+
+    let mut table = TransitionTable::new();
+
+    for (line, state, symbols, action) in rules {
+        let next = match action.next {
+            None => None,
+            Some(next) if next == "halt" && empty_states.contains(&next) => Some(State::Halt),
+            Some(next) => Some(State::State(next)),
+        };
+
+        for symbol in symbols {
+            let rule = Rule {
+                new_state: next.clone(),
+                write: action
+                    .write
+                    .as_deref()
+                    .map(|write| to_symbol(write, line))
+                    .transpose()?,
+                head_move: Some(action.head_move),
+            };
+
+            if table
+                .insert(state.clone(), to_symbol(&symbol, line)?, rule)
+                .is_some()
+            {
+                return Err(ParseError {
+                    line,
+                    message: format!("duplicate rule for state {state:?} reading {symbol:?}"),
+                });
+            }
+        }
+    }
+
+    let start = start.ok_or_else(|| ParseError {
+        line: src.lines().count(),
+        message: "missing start state".to_owned(),
+    })?;
+
+    Ok(Definition {
+        start,
+        blank: None,
+        input_alphabet: None,
+        tape_alphabet: None,
+        table,
+    })
+}
+
+struct Action {
+    write: Option<String>,
+    head_move: Move,
+    next: Option<String>,
+}
+
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+
+    for (index, c) in line.char_indices() {
+        match (quote, c) {
+            (None, '\'' | '"') => quote = Some(c),
+            (Some(open), _) if open == c => quote = None,
+            (None, '#') if index == 0 || line[..index].ends_with(' ') => return &line[..index],
+            _ => {}
+        }
+    }
+
+    line
+}
+
+// splits `key: value` at the first colon outside of quotes and brackets
+fn split_key(entry: &str) -> Option<(&str, &str)> {
+    let mut quote = None;
+    let mut depth = 0;
+
+    for (index, c) in entry.char_indices() {
+        match (quote, c) {
+            (None, '\'' | '"') => quote = Some(c),
+            (Some(open), _) if open == c => quote = None,
+            (None, '[' | '{') => depth += 1,
+            (None, ']' | '}') => depth -= 1,
+            (None, ':') if depth == 0 => {
+                return Some((entry[..index].trim(), entry[index + 1..].trim()));
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+fn scalar(value: &str) -> Option<String> {
+    let value = value.trim();
+
+    if let Some(quoted) = value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')) {
+        return Some(quoted.replace("''", "'"));
+    }
+
+    if let Some(quoted) = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+        return Some(quoted.replace("\\\"", "\"").replace("\\\\", "\\"));
+    }
+
+    (!value.is_empty()).then(|| value.to_owned())
+}
+
+// splits a flow collection's items at top-level commas
+fn items(inner: &str) -> Vec<&str> {
+    let mut items = Vec::new();
+    let mut quote = None;
+    let mut begin = 0;
+
+    for (index, c) in inner.char_indices() {
+        match (quote, c) {
+            (None, '\'' | '"') => quote = Some(c),
+            (Some(open), _) if open == c => quote = None,
+            (None, ',') => {
+                items.push(inner[begin..index].trim());
+                begin = index + 1;
+            }
+            _ => {}
+        }
+    }
+
+    items.push(inner[begin..].trim());
+    items.retain(|item| !item.is_empty());
+
+    items
+}
+
+fn symbols(key: &str) -> Option<Vec<String>> {
+    match key.strip_prefix('[').and_then(|k| k.strip_suffix(']')) {
+        Some(inner) => items(inner).into_iter().map(scalar).collect(),
+        None => Some(Vec::from([scalar(key)?])),
+    }
+}
+
+fn head_move(key: &str) -> Option<Move> {
+    match key {
+        "L" => Some(Move::Left),
+        "R" => Some(Move::Right),
+        _ => None,
+    }
+}
+
+fn action(value: &str) -> Option<Action> {
+    let Some(inner) = value.strip_prefix('{').and_then(|v| v.strip_suffix('}')) else {
+        return Some(Action {
+            write: None,
+            head_move: head_move(value)?,
+            next: None,
+        });
+    };
+
+    let mut write = None;
+    let mut movement = None;
+
+    for item in items(inner) {
+        let (key, value) = split_key(item).unwrap_or((item, ""));
+
+        match key {
+            "write" => write = Some(scalar(value)?),
+            key => {
+                movement = Some((head_move(key)?, scalar(value)));
+            }
+        }
+    }
+
+    let (head_move, next) = movement?;
+
+    Some(Action {
+        write,
+        head_move,
+        next,
+    })
+}
+
+fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+pub fn write<Sym>(definition: &Definition<Sym>) -> Result<String, Unsupported>
+where
+    Sym: Symbol + Ord + Default,
+{
+    default_blank(definition)?;
+
+    let mut out = String::new();
+    let mut halts = false;
+
+    writeln!(out, "blank: {}", quote(&Sym::blank().to_char().to_string())).unwrap();
+    writeln!(out, "start state: {}", quote(&definition.start)).unwrap();
+    out.push_str("table:\n");
+
+    let mut states: Vec<&String> = definition.table.states().collect();
+
+    if !states.contains(&&definition.start) {
+        states.insert(0, &definition.start);
+    }
+
+    for state in states {
+        writeln!(out, "  {}:", quote(state)).unwrap();
+
+        for (read, rule) in definition.table.rules(state) {
+            let head_move = match unit_move(state, rule.head_move)? {
+                Some(head_move) => head_move,
+                None => return Err(Unsupported(format!("rule in state {state:?} doesn't move"))),
+            };
+            let next = match &rule.new_state {
+                None => None,
+                Some(State::State(next)) => Some(quote(next)),
+                Some(State::Halt) => {
+                    halts = true;
+                    Some(quote("halt"))
+                }
+            };
+            let mut action = String::new();
+
+            if let Some(write) = &rule.write {
+                write!(action, "write: {}, ", quote(&write.to_char().to_string())).unwrap();
+            }
+
+            action.push(head_move);
+
+            if let Some(next) = next {
+                write!(action, ": {next}").unwrap();
+            }
+
+            writeln!(
+                out,
+                "    {}: {{{action}}}",
+                quote(&read.to_char().to_string())
+            )
+            .unwrap();
+        }
+    }
+
+    if halts && definition.table.rules(&"halt".to_owned()).next().is_none() {
+        out.push_str("  'halt':\n");
+    }
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{dsl, Char};
+
+    const INC: &str = "
+input: '111'
+blank: ' '
+start state: inc
+table:
+  inc:
+    1: {write: 0, R}
+    [0, ' ']: {write: 1, L: halt}
+  halt:
+";
+
+    #[test]
+    fn parse_yaml_test() {
+        let definition: Definition<Char> = parse(INC).unwrap();
+        let expected =
+            dsl::parse_definition("inc 1 0 R *\ninc 0 1 L halt\ninc _ 1 L halt").unwrap();
+
+        assert_eq!(definition, expected);
+    }
+
+    #[test]
+    fn yaml_round_trip_test() {
+        let definition: Definition<Char> =
+            dsl::parse_definition("a 1 0 R b\nb _ * L halt\nb 0 x R *").unwrap();
+        let yaml = write(&definition).unwrap();
+
+        assert_eq!(parse::<Char>(&yaml).unwrap(), definition);
+
+        let definition: Definition<Char> = dsl::parse_definition("a 1 0 N b").unwrap();
+
+        assert!(write(&definition).is_err());
+    }
+}
@@ -0,0 +1,213 @@
+//! [`arbitrary`] constructors for machines and inputs, and the checks run by
+//! the targets in `fuzz/` (run with `cargo fuzz run <target>`):
+//!
+//! - `parse`: [`check_parsers`] on arbitrary text
+//! - `run`: [`check_run`] on an arbitrary [`FuzzMachine`]
+
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+use crate::accelerate::{Accelerator, Outcome};
+use crate::dsl::{self, Definition};
+use crate::format::{bbchallenge, jflap, yaml};
+use crate::{asm, Char, Machine, Move, Rule, State, StopReason, TransitionTable};
+
+impl<'a> Arbitrary<'a> for Move {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(match u.int_in_range(0..=3)? {
+            0 => Move::Left,
+            1 => Move::Right,
+            _ => Move::By(u.int_in_range(-3..=3)?),
+        })
+    }
+}
+
+impl<'a, S: Arbitrary<'a>> Arbitrary<'a> for State<S> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(match Option::<S>::arbitrary(u)? {
+            Some(state) => State::State(state),
+            None => State::Halt,
+        })
+    }
+}
+
+impl<'a, S: Arbitrary<'a>, Sym: Arbitrary<'a>> Arbitrary<'a> for Rule<S, Sym> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Rule {
+            new_state: u.arbitrary()?,
+            write: u.arbitrary()?,
+            head_move: u.arbitrary()?,
+        })
+    }
+}
+
+impl<'a, S, Sym> Arbitrary<'a> for TransitionTable<S, Sym>
+where
+    S: Arbitrary<'a> + Ord,
+    Sym: Arbitrary<'a> + Ord,
+{
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        u.arbitrary_iter::<(S, Sym, Rule<S, Sym>)>()?.collect()
+    }
+}
+
+/// The most states and symbols of a [`FuzzMachine`]; arbitrary `u8`s would
+/// almost never read a symbol that has a rule.
+pub const MAX_STATES: u8 = 5;
+pub const MAX_SYMBOLS: u8 = 3;
+
+/// A table over the states `0..states` and the symbols `0..symbols`, with
+/// `0` as the blank, and a tape for a machine starting in state 0.
+#[derive(Debug, Clone)]
+pub struct FuzzMachine {
+    pub states: u8,
+    pub symbols: u8,
+    pub table: TransitionTable<u8, u8>,
+    pub tape: Vec<u8>,
+    pub max_steps: u16,
+}
+
+impl<'a> Arbitrary<'a> for FuzzMachine {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let states = u.int_in_range(1..=MAX_STATES)?;
+        let symbols = u.int_in_range(1..=MAX_SYMBOLS)?;
+        let mut table = TransitionTable::new();
+
+        for state in 0..states {
+            for symbol in 0..symbols {
+                if !u.ratio(9, 10)? {
+                    continue;
+                }
+
+                let next = u.int_in_range(0..=states)?;
+                let rule = Rule {
+                    new_state: Some(match next == states {
+                        true => State::Halt,
+                        false => State::State(next),
+                    }),
+                    write: Some(u.int_in_range(0..=symbols - 1)?),
+                    head_move: u.arbitrary()?,
+                };
+                table.insert(state, symbol, rule);
+            }
+        }
+
+        let tape = u
+            .arbitrary_iter::<u8>()?
+            .map(|symbol| symbol.map(|symbol| symbol % symbols))
+            .collect::<Result<_>>()?;
+
+        Ok(FuzzMachine {
+            states,
+            symbols,
+            table,
+            tape,
+            max_steps: u.arbitrary()?,
+        })
+    }
+}
+
+impl FuzzMachine {
+    /// A machine in state 0 on the tape, or on a single blank cell if the
+    /// tape is empty.
+    pub fn machine(&self) -> Machine<u8, u8> {
+        match self.tape.is_empty() {
+            true => Machine::new(0, [0].into()),
+            false => Machine::new(0, self.tape.iter().copied().collect()),
+        }
+    }
+}
+
+/// Feeds `src` to every parser, which may reject it but must not panic, and
+/// checks that what the bbchallenge and DSL parsers accept survives being
+/// written and parsed again.
+pub fn check_parsers(src: &str) {
+    let _ = jflap::parse::<Char>(src);
+    let _ = yaml::parse::<Char>(src);
+    let _ = asm::compile::<Char>(src);
+
+    if let Ok(definition) = bbchallenge::parse::<u8>(src) {
+        let written = bbchallenge::write(&definition).unwrap();
+        assert_eq!(bbchallenge::parse(&written).as_ref(), Ok(&definition));
+    }
+
+    if let Ok(definition) = dsl::parse_definition::<Char>(src) {
+        roundtrip(&definition);
+    }
+}
+
+fn roundtrip(definition: &Definition<Char>) {
+    let written = dsl::write_definition(definition);
+    let parsed = dsl::parse_definition(&written);
+
+    assert_eq!(parsed.as_ref(), Ok(definition), "rewritten as {written:?}");
+}
+
+/// Runs `machine` on its tape for `max_steps` steps, which must not panic,
+/// and checks that the [`Accelerator`] agrees with a plain run from a blank
+/// tape on whether and when the table halts.
+pub fn check_run(machine: &FuzzMachine) {
+    let max_steps = u64::from(machine.max_steps);
+    machine.machine().run_table(&machine.table, max_steps);
+
+    let mut plain: Machine<u8, u8> = Machine::new(0, [0].into());
+    let stats = plain.run_table(&machine.table, max_steps);
+    let mut accelerator = Accelerator::new(&machine.table, 0);
+    let outcome = accelerator.run(max_steps);
+
+    if outcome == Outcome::Unsupported {
+        return;
+    }
+
+    if stats.stop == StopReason::Halted {
+        let sigma = plain.tape_to_vec().iter().filter(|&&sym| sym != 0).count();
+
+        assert_eq!(outcome, Outcome::Halted);
+        assert_eq!(accelerator.steps(), stats.steps);
+        assert_eq!(accelerator.sigma(), sigma as u64);
+    } else if outcome == Outcome::Halted {
+        // a chain step can go past the limit
+        assert!(accelerator.steps() > stats.steps);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a cheap stand-in for the fuzzer: deterministic pseudo-random bytes
+    fn bytes(seed: u64, len: usize) -> Vec<u8> {
+        let mut state = seed.wrapping_mul(6364136223846793005) | 1;
+
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn check_run_test() {
+        for seed in 0..200 {
+            let data = bytes(seed, 128);
+            let machine = FuzzMachine::arbitrary(&mut Unstructured::new(&data)).unwrap();
+
+            assert!(machine.table.states().all(|&state| state < machine.states));
+            assert!(machine.tape.iter().all(|&symbol| symbol < machine.symbols));
+            check_run(&machine);
+        }
+    }
+
+    #[test]
+    fn check_parsers_test() {
+        check_parsers("1RB1LB_1LA1RZ");
+        check_parsers("start b\na 0 1 R b\nb _ 1 L halt");
+
+        for seed in 0..200 {
+            let data = bytes(seed, 64);
+            check_parsers(&String::from_utf8_lossy(&data));
+        }
+    }
+}
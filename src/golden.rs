@@ -0,0 +1,268 @@
+//! Regression tests against golden traces: runs written out in a stable text
+//! form and compared with a checked-in file.
+//!
+//! Setting `TURING_UPDATE_GOLDEN` in the environment rewrites the golden
+//! files instead of comparing against them.
+
+use std::env;
+use std::error::Error;
+use std::fmt::{self, Display, Write as _};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::{
+    move_name, Machine, State, StopReason, Symbol, Tape, TraceSink, TraceStep, TransitionTable,
+};
+
+/// The environment variable that makes [`check_golden`] write files.
+pub const UPDATE_VAR: &str = "TURING_UPDATE_GOLDEN";
+
+/// Collects a trace as one `step state head read -> write move next` line
+/// per step, with `N` for not moving.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TextTrace {
+    text: String,
+}
+
+impl TextTrace {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn finish(self) -> String {
+        self.text
+    }
+}
+
+impl<S, Sym> TraceSink<S, Sym> for TextTrace
+where
+    S: Display,
+    Sym: Symbol,
+{
+    fn record(&mut self, step: &TraceStep<'_, S, Sym>) -> bool {
+        let rule = step.rule;
+        let head_move = move_name(rule.head_move);
+        let write = rule.write.as_ref().unwrap_or(step.read);
+        let _ = write!(
+            self.text,
+            "{} {} {} {} -> {} {head_move} ",
+            step.step,
+            step.state,
+            step.head,
+            step.read.to_char(),
+            write.to_char()
+        );
+        let _ = match &rule.new_state {
+            None => writeln!(self.text, "{}", step.state),
+            Some(State::State(next)) => writeln!(self.text, "{next}"),
+            Some(State::Halt) => writeln!(self.text, "halt"),
+        };
+
+        true
+    }
+}
+
+/// Runs `machine` on `table` for at most `max_steps` steps and returns its
+/// [`TextTrace`], followed by how the run stopped and the tape without the
+/// blanks at either end, as `tape <position of its first cell> <symbols>`.
+pub fn golden_trace<S, Sym, T>(
+    machine: &mut Machine<S, Sym, T>,
+    table: &TransitionTable<S, Sym>,
+    max_steps: u64,
+) -> String
+where
+    S: Ord + Clone + Display,
+    Sym: Symbol + Ord + Clone + Default,
+    T: Tape<Sym>,
+{
+    let mut trace = TextTrace::new();
+    let stats = machine.run_table_traced(table, max_steps, &mut trace);
+    let mut text = trace.finish();
+    let stop = match stats.stop {
+        StopReason::Halted => "halted",
+        StopReason::StepLimit => "step limit",
+        StopReason::Cancelled => "cancelled",
+        StopReason::NoRule => "no rule",
+    };
+
+    let snapshot = machine.snapshot();
+    let blank = Sym::blank();
+    let used = snapshot.tape.iter().position(|sym| *sym != blank);
+    let (start, tape) = match used {
+        Some(first) => {
+            let last = snapshot.tape.iter().rposition(|sym| *sym != blank).unwrap();
+            (first, &snapshot.tape[first..=last])
+        }
+        None => (snapshot.origin, &[][..]),
+    };
+    let tape: String = tape.iter().map(Symbol::to_char).collect();
+
+    let _ = writeln!(text, "{stop} after {} steps", stats.steps);
+    let _ = writeln!(
+        text,
+        "tape {} {tape}",
+        start as isize - snapshot.origin as isize
+    );
+    text
+}
+
+#[derive(Debug)]
+pub enum GoldenError {
+    Io(PathBuf, io::Error),
+    /// The file differs from the actual text; `diff` marks removed lines
+    /// with `-` and added ones with `+`.
+    Mismatch {
+        path: PathBuf,
+        diff: String,
+    },
+}
+
+impl Display for GoldenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GoldenError::Io(path, error) => write!(f, "{}: {error}", path.display()),
+            GoldenError::Mismatch { path, diff } => write!(
+                f,
+                "{} differs (set {UPDATE_VAR} to update it):\n{diff}",
+                path.display()
+            ),
+        }
+    }
+}
+
+impl Error for GoldenError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            GoldenError::Io(_, error) => Some(error),
+            GoldenError::Mismatch { .. } => None,
+        }
+    }
+}
+
+/// Compares `actual` with the file at `path`, or writes it there when
+/// [`UPDATE_VAR`] is set.
+pub fn check_golden(path: impl AsRef<Path>, actual: &str) -> Result<(), GoldenError> {
+    let path = path.as_ref();
+    let io_error = |error| GoldenError::Io(path.to_owned(), error);
+
+    if env::var_os(UPDATE_VAR).is_some() {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(io_error)?;
+        }
+
+        return fs::write(path, actual).map_err(io_error);
+    }
+
+    let expected = fs::read_to_string(path).map_err(io_error)?;
+
+    match expected == actual {
+        true => Ok(()),
+        false => Err(GoldenError::Mismatch {
+            path: path.to_owned(),
+            diff: diff(&expected, actual),
+        }),
+    }
+}
+
+/// Like [`check_golden`], panicking with the diff on a mismatch.
+#[track_caller]
+pub fn assert_golden(path: impl AsRef<Path>, actual: &str) {
+    if let Err(error) = check_golden(path, actual) {
+        panic!("{error}");
+    }
+}
+
+// unchanged lines kept around each change
+const CONTEXT: usize = 2;
+
+// a line diff over the longest common subsequence of lines, leaving out
+// unchanged lines away from the changes
+fn diff(expected: &str, actual: &str) -> String {
+    let old: Vec<&str> = expected.lines().collect();
+    let new: Vec<&str> = actual.lines().collect();
+
+    // common[i][j]: the length of the longest common subsequence of
+    // old[i..] and new[j..]
+    let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = match old[i] == new[j] {
+                true => common[i + 1][j + 1] + 1,
+                false => common[i + 1][j].max(common[i][j + 1]),
+            };
+        }
+    }
+
+    let mut lines = Vec::new();
+    let (mut i, mut j) = (0, 0);
+
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push((' ', old[i]));
+            (i, j) = (i + 1, j + 1);
+        } else if j < new.len() && (i == old.len() || common[i][j + 1] > common[i + 1][j]) {
+            lines.push(('+', new[j]));
+            j += 1;
+        } else {
+            lines.push(('-', old[i]));
+            i += 1;
+        }
+    }
+
+    let changed: Vec<usize> = (0..lines.len()).filter(|&k| lines[k].0 != ' ').collect();
+    let near = |k: usize| changed.iter().any(|&c| c.abs_diff(k) <= CONTEXT);
+    let mut out = String::new();
+    let mut skipped = false;
+
+    for (k, (mark, line)) in lines.iter().enumerate() {
+        if near(k) {
+            let _ = writeln!(out, "{mark} {line}");
+            skipped = false;
+        } else if !skipped {
+            out.push_str("  ...\n");
+            skipped = true;
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{dsl, Char};
+
+    #[test]
+    fn golden_trace_test() {
+        let table = dsl::parse("inc 1 0 R *\ninc _ 1 N halt").unwrap();
+        let mut machine: Machine<String, Char> =
+            Machine::with_input("inc".to_owned(), "11").unwrap();
+
+        assert_eq!(
+            golden_trace(&mut machine, &table, 100),
+            "0 inc 0 1 -> 0 R inc\n1 inc 1 1 -> 0 R inc\n2 inc 2 _ -> 1 N halt\n\
+             halted after 3 steps\ntape 0 001\n"
+        );
+    }
+
+    #[test]
+    fn check_golden_test() {
+        let path = env::temp_dir().join(format!("turing-golden-{}.txt", std::process::id()));
+        fs::write(&path, "a\nb\nc\nd\ne\nf\ng\n").unwrap();
+
+        assert!(check_golden(&path, "a\nb\nc\nd\ne\nf\ng\n").is_ok());
+
+        let Err(GoldenError::Mismatch { diff, .. }) = check_golden(&path, "a\nb\nc\nd\nE\nf\ng\n")
+        else {
+            panic!("expected a mismatch");
+        };
+
+        assert_eq!(diff, "  ...\n  c\n  d\n- e\n+ E\n  f\n  g\n");
+
+        fs::remove_file(&path).unwrap();
+
+        assert!(matches!(check_golden(&path, ""), Err(GoldenError::Io(..))));
+    }
+}
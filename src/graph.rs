@@ -0,0 +1,117 @@
+//! The state graph of a transition table as a [`petgraph`] graph, for
+//! running shortest paths, dominators and other graph algorithms on it.
+
+use std::collections::BTreeMap;
+
+use petgraph::graph::{DiGraph, NodeIndex};
+
+use crate::{Rule, State, TransitionTable};
+
+/// The rule behind an edge of a [`StateGraph`], with the symbol it reads.
+#[derive(Debug)]
+pub struct Edge<'a, S, Sym> {
+    pub read: &'a Sym,
+    pub rule: &'a Rule<S, Sym>,
+}
+
+// derived impls would require `S: Clone` and `Sym: Clone`
+impl<S, Sym> Clone for Edge<'_, S, Sym> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<S, Sym> Copy for Edge<'_, S, Sym> {}
+
+/// A node per state, including those only entered by rules, and one for
+/// halting if any rule halts, with an edge per rule from the state it applies
+/// in to the state it leads to.
+#[derive(Debug, Clone)]
+pub struct StateGraph<'a, S, Sym> {
+    pub graph: DiGraph<State<&'a S>, Edge<'a, S, Sym>>,
+    nodes: BTreeMap<&'a S, NodeIndex>,
+    halt: Option<NodeIndex>,
+}
+
+impl<'a, S: Ord, Sym: Ord> StateGraph<'a, S, Sym> {
+    pub fn new(table: &'a TransitionTable<S, Sym>) -> Self {
+        let mut graph = Self {
+            graph: DiGraph::new(),
+            nodes: BTreeMap::new(),
+            halt: None,
+        };
+
+        for (state, read, rule) in table.iter() {
+            let from = graph.add(State::State(state));
+            let to = match &rule.new_state {
+                None => from,
+                Some(State::State(next)) => graph.add(State::State(next)),
+                Some(State::Halt) => graph.add(State::Halt),
+            };
+
+            graph.graph.add_edge(from, to, Edge { read, rule });
+        }
+
+        graph
+    }
+
+    pub fn node(&self, state: &S) -> Option<NodeIndex> {
+        self.nodes.get(state).copied()
+    }
+
+    /// The halting node, if any rule halts.
+    pub fn halt(&self) -> Option<NodeIndex> {
+        self.halt
+    }
+
+    // the node of `state`, added if it's new
+    fn add(&mut self, state: State<&'a S>) -> NodeIndex {
+        let existing = match &state {
+            State::State(state) => self.nodes.get(*state),
+            State::Halt => self.halt.as_ref(),
+        };
+
+        if let Some(&node) = existing {
+            return node;
+        }
+
+        let node = self.graph.add_node(state.clone());
+
+        match state {
+            State::State(state) => {
+                self.nodes.insert(state, node);
+            }
+            State::Halt => self.halt = Some(node),
+        }
+
+        node
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use petgraph::algo::dijkstra;
+
+    use super::*;
+    use crate::{dsl, Char};
+
+    #[test]
+    fn state_graph_test() {
+        let table =
+            dsl::parse::<Char>("a 1 * R b\na _ * N *\nb 1 * R c\nb _ * N a\nc _ * N halt").unwrap();
+        let graph = StateGraph::new(&table);
+
+        assert_eq!(graph.graph.node_count(), 4);
+        assert_eq!(graph.graph.edge_count(), 5);
+
+        let a = graph.node(&"a".to_owned()).unwrap();
+        let halt = graph.halt().unwrap();
+        let distances = dijkstra(&graph.graph, a, Some(halt), |_| 1);
+
+        assert_eq!(distances[&halt], 3);
+
+        let self_loop = graph.graph.find_edge(a, a).unwrap();
+
+        assert_eq!(*graph.graph[self_loop].read, Char('_'));
+    }
+}
@@ -0,0 +1,278 @@
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::hash::{Hash, Hasher};
+
+use smallvec::SmallVec;
+
+use crate::{Machine, Rule, State, Tape};
+
+// the Mersenne prime 2^61 - 1 the tape hash is taken modulo, and the base
+// of its powers
+const PRIME: u64 = (1 << 61) - 1;
+const BASE: u64 = 0x1f3d_5b79_a2c4_e681 % PRIME;
+
+/// A hash of a machine's configuration that can be kept up to date step by
+/// step with [`Machine::apply_hashed`], instead of hashing the whole tape
+/// again.
+///
+/// Like machines' `PartialEq`, it ignores blank cells and where on the tape
+/// the head is: the tape is hashed as the sum of a code of every non-blank
+/// symbol times a power of a base by its distance from the head.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ConfigHash {
+    // the sum of the codes times the base to the power of their position,
+    // counted from the initial cell 0, and the base to the power of minus
+    // the head's position, which turns it into one relative to the head
+    tape: u64,
+    head: isize,
+    unshift: u64,
+    state: u64,
+}
+
+impl ConfigHash {
+    pub fn new<S, Sym, T>(machine: &Machine<S, Sym, T>) -> Self
+    where
+        S: Hash,
+        Sym: Default + Hash + PartialEq,
+        T: Tape<Sym>,
+    {
+        let mut tape = 0;
+
+        for index in 0..machine.tape.len() {
+            let symbol = machine.tape.get(index).unwrap();
+            let position = index as isize - machine.origin as isize;
+            tape = add(tape, mul(code(symbol, &machine.blank), power(position)));
+        }
+
+        let head = machine.head as isize - machine.origin as isize;
+
+        Self {
+            tape,
+            head,
+            unshift: power(-head),
+            state: state_code(&machine.state),
+        }
+    }
+
+    pub fn value(&self) -> u64 {
+        mul(self.tape, self.unshift) ^ self.state
+    }
+}
+
+impl<S, Sym, T> Machine<S, Sym, T>
+where
+    S: Hash,
+    Sym: Default + Clone + Hash + PartialEq,
+    T: Tape<Sym>,
+{
+    /// Like [`Machine::apply`], also updating `hash`, which must be this
+    /// machine's, in constant time for rules moving the head by a cell.
+    pub fn apply_hashed(&mut self, rule: Rule<S, Sym>, hash: &mut ConfigHash) {
+        let position = self.head as isize - self.origin as isize;
+        let before = code(self.current_symbol(), &self.blank);
+
+        self.apply(rule);
+
+        let cell = (position + self.origin as isize) as usize;
+        let after = code(self.tape.get(cell).unwrap(), &self.blank);
+
+        if after != before {
+            let change = add(after, PRIME - before);
+            hash.tape = add(hash.tape, mul(change, power(position)));
+        }
+
+        let head = self.head as isize - self.origin as isize;
+        hash.unshift = match head - hash.head {
+            0 => hash.unshift,
+            1 => mul(hash.unshift, power(-1)),
+            -1 => mul(hash.unshift, BASE),
+            _ => power(-head),
+        };
+        hash.head = head;
+        hash.state = state_code(&self.state);
+    }
+}
+
+/// The distinct configurations seen, each stored once and numbered in the
+/// order they were first seen, for searches and loop detectors that must not
+/// explore a configuration twice.
+#[derive(Debug, Clone)]
+pub struct Interner<S, Sym: Default> {
+    configurations: Vec<Machine<S, Sym>>,
+    // the configurations by hash, more than one on collisions
+    buckets: BTreeMap<u64, SmallVec<[usize; 1]>>,
+}
+
+impl<S, Sym> Interner<S, Sym>
+where
+    S: Clone + Hash + PartialEq,
+    Sym: Default + Clone + Hash + PartialEq,
+{
+    pub fn new() -> Self {
+        Self {
+            configurations: Vec::new(),
+            buckets: BTreeMap::new(),
+        }
+    }
+
+    /// The number of `machine`'s configuration, and whether it's new.
+    pub fn intern(&mut self, machine: &Machine<S, Sym>) -> (usize, bool) {
+        self.intern_hashed(machine, ConfigHash::new(machine).value())
+    }
+
+    /// Like [`Interner::intern`] with the hash of the configuration already
+    /// at hand, from a [`ConfigHash`].
+    pub fn intern_hashed(&mut self, machine: &Machine<S, Sym>, hash: u64) -> (usize, bool) {
+        let bucket = self.buckets.entry(hash).or_default();
+
+        if let Some(&id) = bucket
+            .iter()
+            .find(|&&id| self.configurations[id] == *machine)
+        {
+            return (id, false);
+        }
+
+        let id = self.configurations.len();
+        bucket.push(id);
+        self.configurations.push(machine.clone());
+
+        (id, true)
+    }
+
+    pub fn get(&self, id: usize) -> Option<&Machine<S, Sym>> {
+        self.configurations.get(id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.configurations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.configurations.is_empty()
+    }
+}
+
+impl<S, Sym> Default for Interner<S, Sym>
+where
+    S: Clone + Hash + PartialEq,
+    Sym: Default + Clone + Hash + PartialEq,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// FNV-1a, for hashes that are the same on every run and platform
+struct Fnv(u64);
+
+impl Hasher for Fnv {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ byte as u64).wrapping_mul(0x100_0000_01b3);
+        }
+    }
+}
+
+fn fnv(value: &impl Hash) -> u64 {
+    let mut hasher = Fnv(0xcbf2_9ce4_8422_2325);
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+// 0 for the blank, so that blank cells don't count
+fn code<Sym: Hash + PartialEq>(symbol: &Sym, blank: &Sym) -> u64 {
+    match symbol == blank {
+        true => 0,
+        false => (fnv(symbol) % (PRIME - 1)) + 1,
+    }
+}
+
+fn state_code<S: Hash>(state: &State<S>) -> u64 {
+    match state {
+        State::State(state) => fnv(state),
+        State::Halt => 0,
+    }
+}
+
+fn add(a: u64, b: u64) -> u64 {
+    (a + b) % PRIME
+}
+
+fn mul(a: u64, b: u64) -> u64 {
+    (a as u128 * b as u128 % PRIME as u128) as u64
+}
+
+// the base to the power of `exponent`, inverting it for negative ones
+fn power(exponent: isize) -> u64 {
+    let base = match exponent < 0 {
+        true => pow(BASE, PRIME - 2),
+        false => BASE,
+    };
+
+    pow(base, exponent.unsigned_abs() as u64)
+}
+
+fn pow(mut base: u64, mut exponent: u64) -> u64 {
+    let mut result = 1;
+
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = mul(result, base);
+        }
+
+        base = mul(base, base);
+        exponent >>= 1;
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TransitionTable;
+
+    #[test]
+    fn apply_hashed_test() {
+        // the 2-state busy beaver
+        let table: TransitionTable<u8, bool> = [
+            (0, false, Rule::to(1).write(true).right()),
+            (0, true, Rule::to(1).write(true).left()),
+            (1, false, Rule::to(0).write(true).left()),
+            (1, true, Rule::halt().write(true).right()),
+        ]
+        .into_iter()
+        .collect();
+
+        let mut machine: Machine<u8, bool> = Machine::new(0, [false].into());
+        let mut hash = ConfigHash::new(&machine);
+
+        while let Some(rule) = machine.next_table_rule(&table) {
+            machine.apply_hashed(rule, &mut hash);
+            assert_eq!(hash.value(), ConfigHash::new(&machine).value());
+        }
+
+        machine.apply_hashed(Rule::new().by(-5), &mut hash);
+        assert_eq!(hash, ConfigHash::new(&machine));
+    }
+
+    #[test]
+    fn interner_test() {
+        let mut interner = Interner::new();
+        let machine: Machine<u8, bool> = Machine::new(0, [true, false].into());
+
+        assert_eq!(interner.intern(&machine), (0, true));
+
+        // the same configuration elsewhere on a longer tape
+        let mut shifted: Machine<u8, bool> = Machine::new(0, [false, true, false, false].into());
+        shifted.set_head(1);
+
+        assert_eq!(interner.intern(&shifted), (0, false));
+        assert_eq!(interner.intern(&Machine::new(1, [true].into())), (1, true));
+        assert_eq!(interner.len(), 2);
+    }
+}
@@ -0,0 +1,183 @@
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cell::{Cell, RefCell};
+use core::error::Error;
+use core::fmt::{self, Debug, Display};
+use core::sync::atomic::AtomicBool;
+
+use crate::{Executor, Machine, RunStats, Snapshot, Tape, TransitionTable};
+
+/// Named checks over a machine's configuration, for catching a machine
+/// breaking its intended tape discipline in the step it happens.
+pub struct Invariants<'a, S, Sym: Default, T> {
+    checks: Vec<(String, Check<'a, S, Sym, T>)>,
+}
+
+type Check<'a, S, Sym, T> = Box<dyn Fn(&Machine<S, Sym, T>) -> bool + 'a>;
+
+impl<'a, S, Sym: Default, T> Invariants<'a, S, Sym, T> {
+    pub fn new() -> Self {
+        Self { checks: Vec::new() }
+    }
+
+    /// Adds a check that must hold after every step.
+    pub fn with(
+        mut self,
+        name: impl Into<String>,
+        check: impl Fn(&Machine<S, Sym, T>) -> bool + 'a,
+    ) -> Self {
+        self.checks.push((name.into(), Box::new(check)));
+        self
+    }
+
+    // the name of the first check `machine` fails
+    fn violated(&self, machine: &Machine<S, Sym, T>) -> Option<&str> {
+        self.checks
+            .iter()
+            .find(|(_, check)| !check(machine))
+            .map(|(name, _)| name.as_str())
+    }
+}
+
+impl<S, Sym: Default, T> Default for Invariants<'_, S, Sym, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returned by [`Machine::run_checked`] when `invariant` didn't hold after
+/// step `step`, the first step being 1, with the configuration it left.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation<S, Sym> {
+    pub invariant: String,
+    pub step: u64,
+    pub snapshot: Snapshot<S, Sym>,
+}
+
+impl<S: Debug, Sym: Debug> Display for Violation<S, Sym> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invariant {:?} violated after step {}: {:?}",
+            self.invariant, self.step, self.snapshot
+        )
+    }
+}
+
+impl<S: Debug, Sym: Debug> Error for Violation<S, Sym> {}
+
+impl<S, Sym, T> Machine<S, Sym, T>
+where
+    Sym: Default,
+    T: Tape<Sym>,
+{
+    /// Like [`Machine::run`], checking `invariants` after every step and
+    /// stopping at the first one that fails.
+    pub fn run_checked<E>(
+        &mut self,
+        max_steps: u64,
+        invariants: &Invariants<'_, S, Sym, T>,
+    ) -> Result<RunStats, Violation<S, Sym>>
+    where
+        E: Executor<S, Sym>,
+        S: Clone,
+        Sym: Clone,
+    {
+        self.run_checked_with(max_steps, invariants, Self::step::<E>)
+    }
+
+    /// Like [`Machine::run_checked`], looking rules up in `table`.
+    pub fn run_table_checked(
+        &mut self,
+        table: &TransitionTable<S, Sym>,
+        max_steps: u64,
+        invariants: &Invariants<'_, S, Sym, T>,
+    ) -> Result<RunStats, Violation<S, Sym>>
+    where
+        S: Ord + Clone,
+        Sym: Ord + Clone,
+    {
+        self.run_checked_with(max_steps, invariants, |machine| machine.step_table(table))
+    }
+
+    fn run_checked_with(
+        &mut self,
+        max_steps: u64,
+        invariants: &Invariants<'_, S, Sym, T>,
+        mut step: impl FnMut(&mut Self) -> bool,
+    ) -> Result<RunStats, Violation<S, Sym>>
+    where
+        S: Clone,
+        Sym: Clone,
+    {
+        let steps = Cell::new(0);
+        let violation = RefCell::new(None);
+
+        let stats = self.run_loop(
+            max_steps,
+            &AtomicBool::new(false),
+            1,
+            |machine| {
+                if !step(machine) {
+                    return false;
+                }
+
+                steps.set(steps.get() + 1);
+
+                if let Some(name) = invariants.violated(machine) {
+                    *violation.borrow_mut() = Some(Violation {
+                        invariant: name.into(),
+                        step: steps.get(),
+                        snapshot: machine.snapshot(),
+                    });
+                }
+
+                true
+            },
+            |_, _| violation.borrow().is_none(),
+        );
+
+        match violation.into_inner() {
+            Some(violation) => Err(violation),
+            None => Ok(stats),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::borrow::ToOwned;
+
+    use super::*;
+    use crate::{dsl, Char, State};
+
+    #[test]
+    fn run_checked_test() {
+        // moves right over the input and back, but overshoots its left end
+        let table =
+            dsl::parse::<Char>("right 1 * R *\nright _ * L left\nleft 1 * L *\nleft _ * L done")
+                .unwrap();
+        let invariants = Invariants::new()
+            .with("stays on the input", |machine: &Machine<_, _>| {
+                machine.head() >= machine.origin()
+            })
+            .with("never writes", |machine| {
+                machine.tape().iter().all(|&c| c != Char('0'))
+            });
+
+        let mut machine = Machine::with_input("right".to_owned(), "11").unwrap();
+        let violation = machine
+            .run_table_checked(&table, 100, &invariants)
+            .unwrap_err();
+
+        assert_eq!(violation.invariant, "stays on the input");
+        assert_eq!(violation.step, 5);
+        assert_eq!(violation.snapshot.state, State::State("left".to_owned()));
+
+        let mut machine = Machine::with_input("right".to_owned(), "11").unwrap();
+        let stats = machine.run_table_checked(&table, 4, &invariants).unwrap();
+
+        assert_eq!(stats.steps, 4);
+    }
+}
@@ -1,33 +1,414 @@
-use std::collections::VecDeque;
+#![cfg_attr(not(feature = "std"), no_std)]
 
-#[derive(Debug)]
+extern crate alloc;
+// lets the derive macros name the crate from inside it
+extern crate self as turing;
+
+pub mod accelerate;
+pub mod analysis;
+pub mod arithmetic;
+pub mod asm;
+mod assert;
+pub mod automata;
+#[cfg(feature = "std")]
+mod batch;
+#[cfg(feature = "bb-champions")]
+pub mod bb;
+pub mod bounded;
+mod builder;
+pub mod closed;
+#[cfg(feature = "arrow")]
+pub mod columnar;
+mod compact;
+mod compiled;
+#[cfg(feature = "std")]
+pub mod complexity;
+mod contract;
+#[cfg(feature = "std")]
+mod control;
+mod cost;
+#[cfg(feature = "std")]
+mod coverage;
+mod decide;
+pub mod differential;
+#[cfg(feature = "std")]
+pub mod distributed;
+pub mod dsl;
+mod encode;
+#[cfg(feature = "evcxr")]
+mod evcxr;
+mod events;
+#[cfg(feature = "std")]
+pub mod experiments;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod format;
+#[cfg(feature = "arbitrary")]
+pub mod fuzz;
+#[cfg(feature = "std")]
+pub mod golden;
+#[cfg(feature = "petgraph")]
+pub mod graph;
+mod intern;
+mod invariant;
+#[cfg(feature = "examples-lib")]
+pub mod library;
+#[cfg(feature = "std")]
+mod logged;
+mod memo;
+#[cfg(feature = "std")]
+mod metrics;
+mod middleware;
+#[cfg(feature = "mmap")]
+pub mod mmap;
+pub mod multi;
+pub mod mutate;
+mod nondeterministic;
+#[cfg(feature = "std")]
+mod observe;
+#[cfg(feature = "plugins")]
+pub mod plugin;
+#[cfg(feature = "std")]
+mod pool;
+pub mod predicate;
+pub mod probabilistic;
+pub mod proof;
+#[cfg(feature = "python")]
+mod python;
+pub mod render;
+mod report;
+#[cfg(feature = "sqlite")]
+pub mod results;
+mod run;
+mod runner;
+#[cfg(feature = "rhai")]
+pub mod script;
+#[cfg(feature = "server")]
+pub mod server;
+mod snapshot;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+#[cfg(feature = "proptest")]
+pub mod strategy;
+mod symbol;
+pub mod symbolic;
+mod table;
+mod tape;
+#[cfg(feature = "telemetry")]
+pub mod telemetry;
+#[cfg(feature = "std")]
+mod temporal;
+#[cfg(feature = "std")]
+mod trace;
+pub mod transducer;
+pub mod transform;
+pub mod universal;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+mod word;
+
+use alloc::boxed::Box;
+use alloc::collections::VecDeque;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{self, Display};
+use core::mem;
+
+use smallvec::SmallVec;
+use table::RuleCache;
+
+#[doc(hidden)]
+pub use assert::__check_machine;
+#[cfg(feature = "std")]
+pub use batch::{run_batch, run_batch_metered, BatchResult};
+pub use builder::{TableError, TransitionTableBuilder};
+pub use compact::{CompactSnapshot, Counter};
+pub use compiled::CompiledTable;
+pub use contract::{Breach, Contract, ContractReport};
+#[cfg(feature = "std")]
+pub use control::RunControl;
+pub use cost::{Costs, WeightedStats};
+#[cfg(feature = "std")]
+pub use coverage::Coverage;
+pub use decide::{Acceptance, Decided, Decider, Undecided, Verdict};
+pub use encode::{Decode, Encode, Unary};
+pub use events::Subscriptions;
+pub use intern::{ConfigHash, Interner};
+pub use invariant::{Invariants, Violation};
+#[cfg(feature = "std")]
+pub use logged::{replay_tape_log, LoggedTape};
+pub use memo::{Memo, PureExecutor};
+#[cfg(feature = "std")]
+pub use metrics::{Counters, CountersSnapshot, Metrics};
+pub use middleware::{Layers, Middleware};
+pub use nondeterministic::{NdRun, NdStop};
+#[cfg(feature = "std")]
+pub use observe::{LiveView, Progress, SnapshotSink};
+#[cfg(feature = "std")]
+pub use pool::{Completion, MachinePool, Schedule, DEFAULT_QUANTUM};
+pub use report::{Outcome, RunOptions, RunReport};
+pub use run::{RunStats, StopReason, Until};
+pub use runner::Runner;
+pub use snapshot::{Snapshot, TapeDiff};
+pub use symbol::{Char, Symbol, TuringState};
+pub use table::{MissingRule, TransitionTable};
+#[cfg(feature = "std")]
+pub use tape::ReadCells;
+pub use tape::{
+    FixedTape, MicroTape, Overflow, PackedTape, SemiInfiniteTape, SharedTape, SparseTape,
+    StreamTape, Tape, TapeFull,
+};
+#[cfg(feature = "std")]
+pub use temporal::{Counterexample, Properties, RecordedStep};
+#[cfg(feature = "std")]
+pub use trace::{CsvTrace, JsonlTrace, TraceSink, TraceStep};
+#[cfg(feature = "derive")]
+pub use turing_derive::{Symbol, TuringState};
+pub use word::WordRule;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Move {
     Left,
     Right,
+    /// Moves the head this many cells right, or left if negative.
+    By(isize),
+}
+
+impl Move {
+    /// How many cells the move takes the head right, negative for left.
+    pub fn offset(self) -> isize {
+        match self {
+            Move::Left => -1,
+            Move::Right => 1,
+            Move::By(offset) => offset,
+        }
+    }
+
+    /// The simplest move by `offset` cells: `None` for none and `Left` or
+    /// `Right` for a single cell.
+    pub fn by(offset: isize) -> Option<Move> {
+        match offset {
+            0 => None,
+            -1 => Some(Move::Left),
+            1 => Some(Move::Right),
+            offset => Some(Move::By(offset)),
+        }
+    }
+}
+
+/// `L` and `R`, followed by the distance for moves of more than one cell, or
+/// `N` for a move by 0.
+impl Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.offset() {
+            0 => write!(f, "N"),
+            -1 => write!(f, "L"),
+            1 => write!(f, "R"),
+            offset if offset < 0 => write!(f, "L{}", offset.unsigned_abs()),
+            offset => write!(f, "R{offset}"),
+        }
+    }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+// a move as written in text formats: `N` for none, the move's `Display`
+// otherwise
+pub(crate) fn move_name(head_move: Option<Move>) -> String {
+    head_move.map_or_else(|| "N".into(), |head_move| head_move.to_string())
+}
+
+// the inverse of `move_name`, also taking distances of 0 and 1
+pub(crate) fn parse_move(name: &str) -> Option<Option<Move>> {
+    let (sign, distance) = match name.split_at_checked(1)? {
+        ("N", "") => return Some(None),
+        ("L", distance) => (-1, distance),
+        ("R", distance) => (1, distance),
+        _ => return None,
+    };
+    let distance: isize = match distance {
+        "" => 1,
+        distance if distance.starts_with('+') => return None,
+        distance => distance.parse().ok().filter(|&distance| distance >= 0)?,
+    };
+
+    Some(Move::by(sign * distance))
+}
+
+// a JSON string literal of `string`
+pub(crate) fn json_string(string: &str) -> String {
+    let mut json = String::with_capacity(string.len() + 2);
+    json.push('"');
+
+    for c in string.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if c < ' ' => json.push_str(&alloc::format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+
+    json.push('"');
+    json
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum State<S> {
     State(S),
     Halt,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Rule<S, Sym> {
     pub new_state: Option<State<S>>,
     pub write: Option<Sym>,
     pub head_move: Option<Move>,
 }
 
+impl<S, Sym> Rule<S, Sym> {
+    /// A rule that leaves the state, tape and head untouched.
+    pub fn new() -> Self {
+        Self {
+            new_state: None,
+            write: None,
+            head_move: None,
+        }
+    }
+
+    pub fn to(state: S) -> Self {
+        Self {
+            new_state: Some(State::State(state)),
+            ..Self::new()
+        }
+    }
+
+    pub fn halt() -> Self {
+        Self {
+            new_state: Some(State::Halt),
+            ..Self::new()
+        }
+    }
+
+    pub fn write(self, symbol: Sym) -> Self {
+        Self {
+            write: Some(symbol),
+            ..self
+        }
+    }
+
+    pub fn left(self) -> Self {
+        Self {
+            head_move: Some(Move::Left),
+            ..self
+        }
+    }
+
+    pub fn right(self) -> Self {
+        Self {
+            head_move: Some(Move::Right),
+            ..self
+        }
+    }
+
+    pub fn by(self, offset: isize) -> Self {
+        Self {
+            head_move: Some(Move::By(offset)),
+            ..self
+        }
+    }
+}
+
+impl<S, Sym> Default for Rule<S, Sym> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 pub trait Executor<S, Sym: Default> {
     fn execute(state: &S, symbol: &Sym) -> Rule<S, Sym>;
 }
 
-#[derive(Debug)]
-pub struct Machine<S, Sym: Default> {
+/// An executor whose rule lookup can fail, e.g. because it consults a file,
+/// an oracle or the network.
+pub trait TryExecutor<S, Sym: Default> {
+    type Error;
+
+    fn try_execute(state: &S, symbol: &Sym) -> Result<Rule<S, Sym>, Self::Error>;
+}
+
+/// An executor for nondeterministic machines, giving every rule that may
+/// apply; see [`Machine::run_nondeterministic`]. No rules at all end the
+/// branch without halting.
+pub trait NdExecutor<S, Sym: Default> {
+    fn execute(state: &S, symbol: &Sym) -> SmallVec<[Rule<S, Sym>; 2]>;
+}
+
+/// What a single call to [`Machine::execute`] did.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StepOutcome<S, Sym> {
+    /// The rule applied, or `None` if the machine had already halted or
+    /// there was no rule, see [`StepOutcome::no_rule`].
+    pub rule: Option<Rule<S, Sym>>,
+    /// Whether the machine was running but there was no rule for its
+    /// configuration, under [`MissingRule::Error`].
+    pub no_rule: bool,
+    /// Whether the tape grew by a cell at either end.
+    pub grew: bool,
+    /// Whether this step halted the machine.
+    pub halted: bool,
+}
+
+/// What a call to [`Machine::step_n`] did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StepBatchOutcome {
+    pub steps: u64,
+    /// [`StopReason::Halted`] if the machine has halted, or
+    /// [`StopReason::NoRule`] if a table had no rule under
+    /// [`MissingRule::Error`]. `None` if it can go on.
+    pub stop: Option<StopReason>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Machine<S, Sym: Default, T = VecDeque<Sym>> {
+    state: State<S>,
+    tape: T,
+    head: usize,
+    // index of the initial cell 0; shifts as the tape grows to the left
+    origin: usize,
+    initial: Option<Box<Initial<S, T>>>,
+    // the symbol new cells start as when the tape grows
+    blank: Sym,
+}
+
+/// The memory a machine takes, from [`Machine::memory_usage`]. Memory that
+/// states and symbols own themselves, like the buffers of `String` states,
+/// isn't counted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryUsage {
+    pub cells: usize,
+    /// The cells the tape has room for before reallocating.
+    pub capacity: usize,
+    /// The size of the tape's buffer, used or not.
+    pub tape_bytes: usize,
+    /// The size of the configuration saved by [`Machine::resettable`].
+    pub saved_bytes: usize,
+}
+
+impl MemoryUsage {
+    /// The bytes allocated in all.
+    pub fn total(&self) -> usize {
+        self.tape_bytes + self.saved_bytes
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Initial<S, T> {
     state: State<S>,
-    tape: VecDeque<Sym>,
+    tape: T,
     head: usize,
+    origin: usize,
 }
 
 pub struct MachinePeek<'a, S, Sym: Default> {
@@ -36,31 +417,282 @@ pub struct MachinePeek<'a, S, Sym: Default> {
     pub head: usize,
 }
 
-impl<S, Sym> Machine<S, Sym>
+impl<'a, S, Sym> MachinePeek<'a, S, Sym>
 where
     Sym: Default,
 {
-    pub fn new(state: S, tape: VecDeque<Sym>) -> Self {
+    pub fn iter(&self) -> impl Iterator<Item = &'a Sym> {
+        self.tape.0.iter().chain(self.tape.1.iter())
+    }
+}
+
+impl<S, Sym, T> Machine<S, Sym, T>
+where
+    Sym: Default + Clone,
+    T: Tape<Sym>,
+{
+    pub fn new(state: S, tape: T) -> Self {
         Self {
             state: State::State(state),
             tape,
             head: 0,
+            origin: 0,
+            initial: None,
+            blank: Sym::default(),
         }
     }
 
-    pub fn execute<E>(&mut self)
+    /// Like [`Machine::new`] with the head starting on tape index `head`,
+    /// which becomes the initial cell 0. `None` if `head` is outside the
+    /// tape.
+    pub fn new_with_head(state: S, tape: T, head: usize) -> Option<Self> {
+        if head >= tape.len() {
+            return None;
+        }
+
+        Some(Self {
+            head,
+            origin: head,
+            ..Self::new(state, tape)
+        })
+    }
+
+    /// Grows the tape by cells of `blank` instead of `Sym::default()`.
+    /// Constructions that need to know the blank, such as
+    /// [`reduce_to_binary`](transform::reduce_to_binary) and
+    /// [`SparseTape::new`], take it as a parameter.
+    pub fn with_blank(mut self, blank: Sym) -> Self {
+        self.blank = blank;
+        self
+    }
+
+    pub fn blank(&self) -> &Sym {
+        &self.blank
+    }
+
+    /// Remembers the current configuration so that [`Machine::reset`] can
+    /// return to it later.
+    pub fn resettable(mut self) -> Self
+    where
+        S: Clone,
+        T: Clone,
+    {
+        self.initial = Some(Box::new(Initial {
+            state: self.state.clone(),
+            tape: self.tape.clone(),
+            head: self.head,
+            origin: self.origin,
+        }));
+
+        self
+    }
+
+    /// Restores the configuration saved by [`Machine::resettable`]. Returns
+    /// `false` and leaves the machine untouched if none was saved.
+    pub fn reset(&mut self) -> bool
+    where
+        S: Clone,
+        T: Clone,
+    {
+        let Some(initial) = &self.initial else {
+            return false;
+        };
+
+        self.state = initial.state.clone();
+        self.tape.clone_from(&initial.tape);
+        self.head = initial.head;
+        self.origin = initial.origin;
+
+        true
+    }
+
+    /// Steps the machine using the rule `E` gives for the current state and
+    /// symbol. Does nothing if the machine has halted.
+    pub fn execute<E>(&mut self) -> StepOutcome<S, Sym>
+    where
+        E: Executor<S, Sym>,
+        S: Clone,
+        Sym: Clone,
+    {
+        let rule = self.next_rule::<E>();
+        self.apply_reporting(rule)
+    }
+
+    /// Like [`Machine::execute`] with a [`TryExecutor`], leaving the machine
+    /// as it was if the executor fails.
+    pub fn try_execute<E>(&mut self) -> Result<StepOutcome<S, Sym>, E::Error>
+    where
+        E: TryExecutor<S, Sym>,
+        S: Clone,
+        Sym: Clone,
+    {
+        let rule = match &self.state {
+            State::State(state) => Some(E::try_execute(state, self.current_symbol())?),
+            State::Halt => None,
+        };
+
+        Ok(self.apply_reporting(rule))
+    }
+
+    // `execute` for run loops, which don't need the outcome. Returns
+    // whether a rule was applied
+    pub(crate) fn step<E>(&mut self) -> bool
+    where
+        E: Executor<S, Sym>,
+    {
+        let Some(rule) = self.next_rule::<E>() else {
+            return false;
+        };
+
+        self.apply(rule);
+        true
+    }
+
+    /// The rule [`Machine::execute`] would apply next, without applying it,
+    /// or `None` if the machine has halted.
+    pub fn next_rule<E>(&self) -> Option<Rule<S, Sym>>
     where
         E: Executor<S, Sym>,
     {
         let State::State(ref state) = self.state else {
-            return;
+            return None;
         };
 
+        Some(E::execute(state, self.current_symbol()))
+    }
+
+    /// Like [`Machine::next_rule`], for [`Machine::execute_table`], with a
+    /// missing rule handled by the table's [`MissingRule`] policy. Also
+    /// `None` if the policy is [`MissingRule::Error`].
+    pub fn next_table_rule(&self, table: &TransitionTable<S, Sym>) -> Option<Rule<S, Sym>>
+    where
+        S: Ord + Clone,
+        Sym: Ord + Clone,
+    {
+        let State::State(ref state) = self.state else {
+            return None;
+        };
+
+        table.lookup(state, self.current_symbol())
+    }
+
+    /// Steps the machine using a rule looked up in `table`. A missing rule
+    /// is handled by the table's [`MissingRule`] policy, halting the machine
+    /// without touching the tape by default.
+    pub fn execute_table(&mut self, table: &TransitionTable<S, Sym>) -> StepOutcome<S, Sym>
+    where
+        S: Ord + Clone,
+        Sym: Ord + Clone,
+    {
+        let rule = self.next_table_rule(table);
+        self.apply_reporting(rule)
+    }
+
+    // `execute_table` for run loops
+    pub(crate) fn step_table(&mut self, table: &TransitionTable<S, Sym>) -> bool
+    where
+        S: Ord + Clone,
+        Sym: Ord + Clone,
+    {
+        let Some(rule) = self.next_table_rule(table) else {
+            return false;
+        };
+
+        self.apply(rule);
+        true
+    }
+
+    /// Performs up to `n` steps in one go, for callers paying for every call,
+    /// like FFI and WASM bindings or a machine driven over a channel.
+    pub fn step_n<E>(&mut self, n: u64) -> StepBatchOutcome
+    where
+        E: Executor<S, Sym>,
+    {
+        self.step_batch(n, Self::step::<E>)
+    }
+
+    /// Like [`Machine::step_n`], looking rules up in `table`.
+    pub fn step_n_table(&mut self, table: &TransitionTable<S, Sym>, n: u64) -> StepBatchOutcome
+    where
+        S: Ord + Clone,
+        Sym: Ord + Clone,
+    {
+        let mut cache = RuleCache::default();
+        self.step_batch(n, |machine| machine.step_table_cached(table, &mut cache))
+    }
+
+    fn step_batch(&mut self, n: u64, mut step: impl FnMut(&mut Self) -> bool) -> StepBatchOutcome {
+        let mut steps = 0;
+
+        // a step only fails on a halted machine or a missing rule
+        while steps < n && step(self) {
+            steps += 1;
+        }
+
+        let stop = match (self.halted(), steps < n) {
+            (true, _) => Some(StopReason::Halted),
+            (false, true) => Some(StopReason::NoRule),
+            (false, false) => None,
+        };
+
+        StepBatchOutcome { steps, stop }
+    }
+
+    // `step_table` looking rules up through `cache`
+    pub(crate) fn step_table_cached<'t>(
+        &mut self,
+        table: &'t TransitionTable<S, Sym>,
+        cache: &mut RuleCache<'t, S, Sym>,
+    ) -> bool
+    where
+        S: Ord + Clone,
+        Sym: Ord + Clone,
+    {
+        let State::State(ref state) = self.state else {
+            return false;
+        };
+
+        let Some(rule) = table.lookup_cached(cache, state, self.current_symbol()) else {
+            return false;
+        };
+
+        self.apply(rule);
+        true
+    }
+
+    fn apply_reporting(&mut self, rule: Option<Rule<S, Sym>>) -> StepOutcome<S, Sym>
+    where
+        S: Clone,
+        Sym: Clone,
+    {
+        let Some(rule) = rule else {
+            return StepOutcome {
+                rule: None,
+                no_rule: !self.halted(),
+                grew: false,
+                halted: false,
+            };
+        };
+
+        let len = self.tape.len();
+        self.apply(rule.clone());
+
+        StepOutcome {
+            rule: Some(rule),
+            no_rule: false,
+            grew: self.tape.len() > len,
+            halted: self.halted(),
+        }
+    }
+
+    /// Applies `rule` to the current configuration, regardless of whether the
+    /// machine has halted.
+    pub fn apply(&mut self, rule: Rule<S, Sym>) {
         let Rule {
             new_state,
             write,
             head_move,
-        } = E::execute(state, self.tape.get(self.head).unwrap());
+        } = rule;
 
         if let Some(new_state) = new_state {
             self.state = new_state;
@@ -74,6 +706,14 @@ where
             match head_move {
                 Move::Left => self.head_move_left(),
                 Move::Right => self.head_move_right(),
+                Move::By(offset) => {
+                    for _ in 0..offset.unsigned_abs() {
+                        match offset < 0 {
+                            true => self.head_move_left(),
+                            false => self.head_move_right(),
+                        }
+                    }
+                }
             }
         }
     }
@@ -82,45 +722,477 @@ where
         matches!(&self.state, State::Halt)
     }
 
-    pub fn peek(&self) -> MachinePeek<'_, S, Sym> {
-        MachinePeek {
-            state: &self.state,
-            tape: self.tape.as_slices(),
-            head: self.head,
+    pub fn state(&self) -> Option<&S> {
+        match &self.state {
+            State::State(state) => Some(state),
+            State::Halt => None,
         }
     }
 
-    pub fn finish(self) -> (VecDeque<Sym>, State<S>) {
-        (self.tape, self.state)
+    pub fn head(&self) -> usize {
+        self.head
+    }
+
+    /// The tape index of the initial cell 0, which moves right whenever the
+    /// tape grows to the left.
+    pub fn origin(&self) -> usize {
+        self.origin
+    }
+
+    pub fn current_symbol(&self) -> &Sym {
+        self.tape.get(self.head).unwrap()
+    }
+
+    /// The `2 * radius + 1` cells centred on the head, with blanks for cells
+    /// past either end of the tape.
+    pub fn around_head(&self, radius: usize) -> Vec<Sym> {
+        self.window(self.head as isize - radius as isize, 2 * radius + 1)
+    }
+
+    // `width` cells from tape index `first` on, padded with blanks
+    pub(crate) fn window(&self, first: isize, width: usize) -> Vec<Sym> {
+        (first..first + width as isize)
+            .map(|index| {
+                usize::try_from(index)
+                    .ok()
+                    .and_then(|index| self.tape.get(index))
+                    .unwrap_or(&self.blank)
+                    .clone()
+            })
+            .collect()
+    }
+
+    /// Overwrites the current state. Meant for debuggers and teaching tools;
+    /// regular runs should only change state through rules.
+    pub fn set_state(&mut self, state: State<S>) {
+        self.state = state;
+    }
+
+    /// Moves the head to tape index `head`. Meant for debuggers and teaching
+    /// tools; regular runs should only move the head through rules.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `head` is outside the tape.
+    pub fn set_head(&mut self, head: usize) {
+        assert!(head < self.tape.len(), "head index out of tape bounds");
+
+        self.head = head;
+    }
+
+    /// Moves the head to `position`, counted from the initial cell 0, for
+    /// setting up and debugging without rules. Returns `false` and leaves the
+    /// head in place if `position` is outside the tape.
+    pub fn seek(&mut self, position: isize) -> bool {
+        match self.origin.checked_add_signed(position) {
+            Some(head) if head < self.tape.len() => {
+                self.head = head;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Overwrites the cell at tape index `index`. Meant for debuggers and
+    /// teaching tools; regular runs should only write through rules.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is outside the tape.
+    pub fn write_at(&mut self, index: usize, symbol: Sym) {
+        assert!(index < self.tape.len(), "write index out of tape bounds");
+
+        self.tape.set(index, symbol);
+    }
+
+    pub fn tape(&self) -> &T {
+        &self.tape
+    }
+
+    pub fn into_tape(self) -> T {
+        self.tape
     }
 
     fn write_tape(&mut self, write: Sym) {
-        *self.tape.get_mut(self.head).unwrap() = write;
+        self.tape.set(self.head, write);
     }
 
     fn head_move_left(&mut self) {
         match self.head {
             // if at the left end of tape expand the vec; don't change the index
-            // to avoid underflow
-            0 => self.tape.push_front(Sym::default()),
+            // to avoid underflow. a tape that can't grow keeps the head in place
+            0 => {
+                if self.tape.push_front(self.blank.clone()).is_ok() {
+                    self.origin += 1;
+                }
+            }
             // otherwise decrement head by one
             _ => self.head -= 1,
         }
     }
 
     fn head_move_right(&mut self) {
-        if self.head == self.tape.len() - 1 {
-            self.tape.push_back(Sym::default());
+        if self.head == self.tape.len() - 1 && self.tape.push_back(self.blank.clone()).is_err() {
+            return;
         }
 
         self.head += 1;
     }
 }
 
+impl<S, Sym> Machine<S, Sym>
+where
+    Sym: Default + Clone,
+{
+    /// Like [`Machine::new`], but makes sure the tape can hold at least
+    /// `capacity` cells before reallocating.
+    pub fn with_capacity(state: S, mut tape: VecDeque<Sym>, capacity: usize) -> Self {
+        tape.reserve(capacity.saturating_sub(tape.len()));
+
+        Self::new(state, tape)
+    }
+
+    /// A machine with `left` on the tape to the left of the head and `right`
+    /// from the head on, the head starting on the first cell of `right`, or
+    /// on a `blank` if it's empty. The tape grows by `blank` too.
+    pub fn from_sides(
+        state: S,
+        left: impl IntoIterator<Item = Sym>,
+        right: impl IntoIterator<Item = Sym>,
+        blank: Sym,
+    ) -> Self {
+        let mut tape: VecDeque<Sym> = left.into_iter().collect();
+        let head = tape.len();
+        tape.extend(right);
+
+        if tape.len() == head {
+            tape.push_back(blank.clone());
+        }
+
+        Self {
+            head,
+            origin: head,
+            ..Self::new(state, tape).with_blank(blank)
+        }
+    }
+
+    /// A machine whose tape holds the symbols of `tape`, or a single `blank`
+    /// if there are none, e.g. an input, a separator and blank scratch space
+    /// chained together. The tape grows by `blank` too.
+    pub fn with_tape_from_iter(state: S, tape: impl IntoIterator<Item = Sym>, blank: Sym) -> Self {
+        let mut tape: VecDeque<Sym> = tape.into_iter().collect();
+
+        if tape.is_empty() {
+            tape.push_back(blank.clone());
+        }
+
+        Self::new(state, tape).with_blank(blank)
+    }
+
+    /// Adds `cells` blanks to the left end of the tape.
+    pub fn pad_left(&mut self, cells: usize) {
+        self.tape.reserve(cells);
+
+        for _ in 0..cells {
+            self.tape.push_front(self.blank.clone());
+        }
+
+        self.head += cells;
+        self.origin += cells;
+    }
+
+    /// Adds `cells` blanks to the right end of the tape.
+    pub fn pad_right(&mut self, cells: usize) {
+        let blank = self.blank.clone();
+        self.tape.resize(self.tape.len() + cells, blank);
+    }
+
+    // the tape is a ring buffer, so spare capacity is shared between both
+    // ends; the two reserve methods only differ in intent
+
+    /// Reserves room for growing the tape by at least `additional` cells to
+    /// the left of its current start.
+    pub fn reserve_left(&mut self, additional: usize) {
+        self.tape.reserve(additional);
+    }
+
+    /// Reserves room for growing the tape by at least `additional` cells to
+    /// the right of its current end.
+    pub fn reserve_right(&mut self, additional: usize) {
+        self.tape.reserve(additional);
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.tape.capacity()
+    }
+
+    pub fn memory_usage(&self) -> MemoryUsage {
+        let cell = mem::size_of::<Sym>();
+        let saved_bytes = self.initial.as_ref().map_or(0, |initial| {
+            mem::size_of::<Initial<S, VecDeque<Sym>>>() + initial.tape.capacity() * cell
+        });
+
+        MemoryUsage {
+            cells: self.tape.len(),
+            capacity: self.tape.capacity(),
+            tape_bytes: self.tape.capacity() * cell,
+            saved_bytes,
+        }
+    }
+
+    /// Drops the blank cells at either end of the tape, keeping those under
+    /// the head and of the initial cell 0, and frees the memory they took.
+    pub fn compact(&mut self)
+    where
+        Sym: PartialEq,
+    {
+        let blank = &self.blank;
+        let (first, last) = (self.head.min(self.origin), self.head.max(self.origin));
+        let start = self
+            .tape
+            .iter()
+            .position(|sym| *sym != *blank)
+            .map_or(first, |start| start.min(first));
+        let end = self
+            .tape
+            .iter()
+            .rposition(|sym| *sym != *blank)
+            .map_or(last, |end| end.max(last));
+
+        self.tape.truncate(end + 1);
+        self.tape.drain(..start);
+        self.tape.shrink_to_fit();
+        self.head -= start;
+        self.origin -= start;
+    }
+
+    pub fn with_input(state: S, input: &str) -> Option<Self>
+    where
+        Sym: Symbol,
+    {
+        Self::with_input_blank(state, input, Sym::blank())
+    }
+
+    /// Like [`Machine::with_input`] with `blank` as the blank, so that an
+    /// empty input starts on a cell of `blank` too.
+    pub fn with_input_blank(state: S, input: &str, blank: Sym) -> Option<Self>
+    where
+        Sym: Symbol,
+    {
+        let mut tape = input
+            .chars()
+            .map(Sym::from_char)
+            .collect::<Option<VecDeque<_>>>()?;
+
+        if tape.is_empty() {
+            tape.push_back(blank.clone());
+        }
+
+        Some(Self::new(state, tape).with_blank(blank))
+    }
+
+    pub fn peek(&self) -> MachinePeek<'_, S, Sym> {
+        MachinePeek {
+            state: &self.state,
+            tape: self.tape.as_slices(),
+            head: self.head,
+        }
+    }
+
+    pub fn tape_to_vec(&self) -> Vec<Sym>
+    where
+        Sym: Clone,
+    {
+        self.tape.iter().cloned().collect()
+    }
+
+    pub fn finish(self) -> (VecDeque<Sym>, State<S>) {
+        (self.tape, self.state)
+    }
+
+    /// The non-blank cells of the tape with their positions relative to the
+    /// initial cell 0, from left to right.
+    pub fn iter_non_blank(&self) -> impl Iterator<Item = (isize, &Sym)>
+    where
+        Sym: PartialEq,
+    {
+        let origin = self.origin as isize;
+
+        self.tape
+            .iter()
+            .enumerate()
+            .filter(|(_, sym)| **sym != self.blank)
+            .map(move |(index, sym)| (index as isize - origin, sym))
+    }
+
+    pub fn finish_contiguous(self) -> (Vec<Sym>, State<S>) {
+        (self.tape.into(), self.state)
+    }
+
+    /// Returns the tape with blank cells stripped from both ends, along with
+    /// the position of the initial cell 0 relative to the start of the result.
+    pub fn finish_trimmed(self) -> (Vec<Sym>, State<S>, isize)
+    where
+        Sym: PartialEq,
+    {
+        let blank = self.blank;
+        let mut tape: Vec<Sym> = self.tape.into();
+
+        let end = tape
+            .iter()
+            .rposition(|sym| *sym != blank)
+            .map_or(0, |i| i + 1);
+        tape.truncate(end);
+
+        let start = tape.iter().position(|sym| *sym != blank).unwrap_or(end);
+        tape.drain(..start);
+
+        (tape, self.state, self.origin as isize - start as isize)
+    }
+}
+
+impl<S, Sym> Machine<S, Sym>
+where
+    Sym: Default + Clone,
+{
+    /// The machine with its tape behind a [`SharedTape`], for cheap
+    /// [`Machine::fork`]s.
+    pub fn into_shared(self) -> Machine<S, Sym, SharedTape<Sym>> {
+        Machine {
+            state: self.state,
+            tape: self.tape.into(),
+            head: self.head,
+            origin: self.origin,
+            initial: self.initial.map(|initial| {
+                Box::new(Initial {
+                    state: initial.state,
+                    tape: initial.tape.into(),
+                    head: initial.head,
+                    origin: initial.origin,
+                })
+            }),
+            blank: self.blank,
+        }
+    }
+}
+
+impl<S, Sym> Machine<S, Sym, SharedTape<Sym>>
+where
+    S: Clone,
+    Sym: Default + Clone,
+{
+    /// A copy of the machine sharing the cells of its tape with this one
+    /// until either changes them, e.g. to branch a search without copying
+    /// long tapes.
+    pub fn fork(&self) -> Self {
+        self.clone()
+    }
+
+    /// The machine with a tape of its own again.
+    pub fn into_unshared(self) -> Machine<S, Sym> {
+        Machine {
+            state: self.state,
+            tape: self.tape.into_cells(),
+            head: self.head,
+            origin: self.origin,
+            initial: self.initial.map(|initial| {
+                Box::new(Initial {
+                    state: initial.state,
+                    tape: initial.tape.into_cells(),
+                    head: initial.head,
+                    origin: initial.origin,
+                })
+            }),
+            blank: self.blank,
+        }
+    }
+}
+
+impl<S, Sym> Machine<S, Sym>
+where
+    Sym: Default + PartialEq,
+{
+    // the non-blank part of the tape as offsets relative to the head
+    fn non_blank_span(&self) -> (isize, &[Sym], &[Sym]) {
+        let blank = &self.blank;
+        let start = self.tape.iter().position(|sym| *sym != *blank);
+        let end = self.tape.iter().rposition(|sym| *sym != *blank);
+
+        let Some((start, end)) = start.zip(end) else {
+            return (0, &[], &[]);
+        };
+
+        let (left, right) = self.tape.as_slices();
+        let split = left.len();
+
+        let left = &left[start.min(split)..(end + 1).min(split)];
+        let right = &right[start.saturating_sub(split)..(end + 1).saturating_sub(split)];
+
+        (start as isize - self.head as isize, left, right)
+    }
+}
+
+// machines are equal when they are in the same state and the non-blank parts
+// of their tapes line up around the head; blank padding is irrelevant
+impl<S, Sym> PartialEq for Machine<S, Sym>
+where
+    S: PartialEq,
+    Sym: Default + PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        if self.state != other.state {
+            return false;
+        }
+
+        let (offset, left, right) = self.non_blank_span();
+        let (other_offset, other_left, other_right) = other.non_blank_span();
+
+        (left.is_empty() && right.is_empty() || offset == other_offset)
+            && left
+                .iter()
+                .chain(right)
+                .eq(other_left.iter().chain(other_right))
+    }
+}
+
+impl<S, Sym> Eq for Machine<S, Sym>
+where
+    S: Eq,
+    Sym: Default + Eq,
+{
+}
+
+// extending a machine appends to the right end of its tape
+impl<S, Sym: Default> Extend<Sym> for Machine<S, Sym> {
+    fn extend<I: IntoIterator<Item = Sym>>(&mut self, iter: I) {
+        self.tape.extend(iter);
+    }
+}
+
+// iterating over a machine iterates over its tape, e.g. to turn the output
+// into a string
+impl<S, Sym: Default> IntoIterator for Machine<S, Sym> {
+    type Item = Sym;
+    type IntoIter = alloc::collections::vec_deque::IntoIter<Sym>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.tape.into_iter()
+    }
+}
+
+impl<'a, S, Sym: Default> IntoIterator for &'a Machine<S, Sym> {
+    type Item = &'a Sym;
+    type IntoIter = alloc::collections::vec_deque::Iter<'a, Sym>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.tape.iter()
+    }
+}
+
 impl<S, Sym> Default for Machine<S, Sym>
 where
     S: Default,
-    Sym: Default,
+    Sym: Default + Clone,
 {
     fn default() -> Self {
         Self::new(S::default(), [Sym::default()].into())
@@ -129,26 +1201,20 @@ where
 
 #[cfg(test)]
 mod tests {
+    use alloc::vec;
+
     use super::*;
 
-    #[derive(Default, Debug, PartialEq, Eq)]
+    #[derive(Default, Debug, Clone, PartialEq, Eq)]
     struct Inc;
     struct IncExecutor;
 
     impl Executor<Inc, bool> for IncExecutor {
-        fn execute(state: &Inc, symbol: &bool) -> Rule<Inc, bool> {
+        fn execute(_state: &Inc, symbol: &bool) -> Rule<Inc, bool> {
             if *symbol {
-                Rule {
-                    new_state: None,
-                    write: Some(false),
-                    head_move: Some(Move::Right),
-                }
+                Rule::new().write(false).right()
             } else {
-                Rule {
-                    new_state: Some(State::Halt),
-                    write: Some(true),
-                    head_move: None,
-                }
+                Rule::halt().write(true)
             }
         }
     }
@@ -161,12 +1227,9 @@ mod tests {
             machine.execute::<IncExecutor>();
         }
 
-        let (mut vec, state) = machine.finish();
+        let (vec, state) = machine.finish_contiguous();
 
-        vec.make_contiguous();
-        let (vec_slice, _) = vec.as_slices();
-
-        assert_eq!(vec_slice, &[true, true]);
+        assert_eq!(vec, [true, true]);
         assert_eq!(state, State::Halt);
 
         let mut machine: Machine<Inc, bool> = Machine::new(Inc, [true, true].into());
@@ -175,14 +1238,429 @@ mod tests {
             machine.execute::<IncExecutor>();
         }
 
-        let (mut vec, state) = machine.finish();
-
-        vec.make_contiguous();
-        let (vec_slice, _) = vec.as_slices();
+        let (vec, state) = machine.finish_contiguous();
 
-        assert_eq!(vec_slice, &[false, false, true]);
+        assert_eq!(vec, [false, false, true]);
         assert_eq!(state, State::Halt);
     }
 
+    #[test]
+    fn step_outcome_test() {
+        let mut machine: Machine<Inc, bool> = Machine::new(Inc, [true].into());
+        let outcome = machine.execute::<IncExecutor>();
+
+        assert_eq!(outcome.rule, Some(Rule::new().write(false).right()));
+        assert!(outcome.grew);
+        assert!(!outcome.halted);
+
+        let outcome = machine.execute::<IncExecutor>();
+
+        assert!(!outcome.grew);
+        assert!(outcome.halted);
+
+        let outcome = machine.execute::<IncExecutor>();
+
+        assert_eq!(outcome.rule, None);
+        assert!(!outcome.halted);
+        assert!(!outcome.no_rule);
+
+        // a missing rule under `MissingRule::Error` isn't a halt
+        let table: TransitionTable<u8, bool> =
+            TransitionTable::new().with_missing(MissingRule::Error);
+        let mut machine: Machine<u8, bool> = Machine::new(0, [true].into());
+        let outcome = machine.execute_table(&table);
+
+        assert_eq!(outcome.rule, None);
+        assert!(outcome.no_rule);
+        assert!(!machine.halted());
+    }
+
+    #[test]
+    fn step_n_test() {
+        let mut machine: Machine<Inc, bool> = Machine::new(Inc, [true, true, true].into());
+        let outcome = machine.step_n::<IncExecutor>(2);
+
+        assert_eq!(
+            outcome,
+            StepBatchOutcome {
+                steps: 2,
+                stop: None
+            }
+        );
+
+        let outcome = machine.step_n::<IncExecutor>(10);
+
+        assert_eq!(outcome.steps, 2);
+        assert_eq!(outcome.stop, Some(StopReason::Halted));
+
+        let table: TransitionTable<u8, bool> = [(0, true, Rule::new().right())]
+            .into_iter()
+            .collect::<TransitionTable<_, _>>()
+            .with_missing(MissingRule::Error);
+        let mut machine: Machine<u8, bool> = Machine::new(0, [true, false].into());
+
+        assert_eq!(
+            machine.step_n_table(&table, 10),
+            StepBatchOutcome {
+                steps: 1,
+                stop: Some(StopReason::NoRule)
+            }
+        );
+    }
+
+    #[test]
+    fn next_rule_test() {
+        let mut machine: Machine<Inc, bool> = Machine::new(Inc, [true].into());
+        let copy = machine.clone();
+
+        assert_eq!(
+            machine.next_rule::<IncExecutor>(),
+            Some(Rule::new().write(false).right())
+        );
+        assert_eq!(machine, copy);
+
+        machine.execute::<IncExecutor>();
+        machine.execute::<IncExecutor>();
+
+        assert_eq!(machine.next_rule::<IncExecutor>(), None);
+
+        let table: TransitionTable<u8, bool> = [(0, true, Rule::to(1))].into_iter().collect();
+        let machine: Machine<u8, bool> = Machine::new(0, [true].into());
+
+        assert_eq!(machine.next_table_rule(&table), Some(Rule::to(1)));
+
+        let machine: Machine<u8, bool> = Machine::new(0, [false].into());
+
+        assert_eq!(machine.next_table_rule(&table), Some(Rule::halt()));
+    }
+
+    #[test]
+    fn contiguous_tape_test() {
+        let mut machine: Machine<Inc, bool> = Machine::new(Inc, [true, false].into());
+        machine.execute::<IncExecutor>();
+
+        assert_eq!(machine.tape_to_vec(), [false, false]);
+        assert!(machine.peek().iter().eq([false, false].iter()));
+    }
+
+    #[test]
+    fn with_input_test() {
+        let mut machine: Machine<Inc, bool> = Machine::with_input(Inc, "111").unwrap();
+
+        while !machine.halted() {
+            machine.execute::<IncExecutor>();
+        }
+
+        let (vec, _) = machine.finish_contiguous();
+        let output: alloc::string::String = vec.iter().map(Symbol::to_char).collect();
+
+        assert_eq!(output, "0001");
+        assert!(Machine::<Inc, bool>::with_input(Inc, "012").is_none());
+        assert_eq!(
+            Machine::<Inc, bool>::with_input(Inc, "")
+                .unwrap()
+                .tape_to_vec(),
+            [false]
+        );
+        assert_eq!(
+            Machine::<Inc, bool>::with_input_blank(Inc, "", true)
+                .unwrap()
+                .tape_to_vec(),
+            [true]
+        );
+    }
+
+    #[test]
+    fn finish_trimmed_test() {
+        let mut machine: Machine<Inc, bool> = Machine::new(Inc, [false, true, false].into());
+        machine.head_move_left();
+        machine.head_move_left();
+
+        let (vec, _, origin) = machine.finish_trimmed();
+
+        assert_eq!(vec, [true]);
+        assert_eq!(origin, -1);
+
+        let machine: Machine<Inc, bool> = Machine::new(Inc, [false, false].into());
+        let (vec, _, origin) = machine.finish_trimmed();
+
+        assert!(vec.is_empty());
+        assert_eq!(origin, 0);
+    }
+
+    #[test]
+    fn rule_builder_test() {
+        let rule: Rule<u8, bool> = Rule::to(3).write(true).left();
+
+        assert_eq!(rule.new_state, Some(State::State(3)));
+        assert_eq!(rule.write, Some(true));
+        assert!(matches!(rule.head_move, Some(Move::Left)));
+
+        let rule: Rule<u8, bool> = Rule::halt();
+
+        assert_eq!(rule.new_state, Some(State::Halt));
+        assert_eq!(rule.write, None);
+        assert!(rule.head_move.is_none());
+    }
+
+    #[test]
+    fn reset_test() {
+        let mut machine: Machine<Inc, bool> = Machine::new(Inc, [true, true].into()).resettable();
+
+        while !machine.halted() {
+            machine.execute::<IncExecutor>();
+        }
+
+        assert!(machine.reset());
+        assert_eq!(machine.state(), Some(&Inc));
+        assert_eq!(machine.head(), 0);
+        assert_eq!(machine.tape_to_vec(), [true, true]);
+
+        let mut machine: Machine<Inc, bool> = Machine::new(Inc, [true].into());
+
+        assert!(!machine.reset());
+    }
+
+    #[test]
+    fn clone_eq_test() {
+        let mut machine: Machine<Inc, bool> = Machine::new(Inc, [true, false].into());
+        let copy = machine.clone();
+
+        assert_eq!(machine, copy);
+
+        machine.execute::<IncExecutor>();
+
+        assert_ne!(machine, copy);
+
+        let padded: Machine<Inc, bool> = Machine::new(Inc, [true, false, false].into());
+
+        assert_eq!(padded, copy);
+
+        let mut shifted: Machine<Inc, bool> = Machine::new(Inc, [false, true].into());
+        shifted.head_move_right();
+
+        assert_eq!(shifted, copy);
+        assert_ne!(Machine::<Inc, bool>::new(Inc, [false, true].into()), copy);
+    }
+
+    #[test]
+    fn capacity_test() {
+        let mut machine: Machine<Inc, bool> = Machine::with_capacity(Inc, [true].into(), 64);
+
+        assert!(machine.capacity() >= 64);
+        assert_eq!(machine.tape_to_vec(), [true]);
+
+        machine.reserve_left(100);
+        machine.reserve_right(100);
+
+        assert!(machine.capacity() >= 101);
+    }
+
+    #[test]
+    fn memory_usage_test() {
+        let machine: Machine<Inc, u32> = Machine::with_capacity(Inc, [1, 2].into(), 64);
+        let usage = machine.memory_usage();
+
+        assert_eq!(usage.cells, 2);
+        assert!(usage.capacity >= 64);
+        assert_eq!(usage.tape_bytes, usage.capacity * 4);
+        assert_eq!(usage.saved_bytes, 0);
+
+        let usage = machine.resettable().memory_usage();
+
+        assert!(usage.saved_bytes >= 2 * 4);
+        assert_eq!(usage.total(), usage.tape_bytes + usage.saved_bytes);
+    }
+
+    #[test]
+    fn iteration_test() {
+        let mut machine: Machine<Inc, u8> = Machine::new(Inc, [0, 3, 0].into());
+        machine.apply(Rule::new().left());
+        machine.apply(Rule::new().write(5));
+
+        let non_blank: Vec<_> = machine.iter_non_blank().collect();
+
+        assert_eq!(non_blank, [(-1, &5), (1, &3)]);
+        assert_eq!((&machine).into_iter().count(), 4);
+        assert_eq!(machine.into_iter().collect::<Vec<_>>(), [5, 0, 3, 0]);
+    }
+
+    #[test]
+    fn new_with_head_test() {
+        let machine: Machine<Inc, bool> =
+            Machine::new_with_head(Inc, [true, false, true].into(), 1).unwrap();
+
+        assert_eq!(machine.head(), 1);
+        assert_eq!(machine.current_symbol(), &false);
+        assert_eq!(
+            machine,
+            Machine::from_sides(Inc, [true], [false, true], false)
+        );
+        assert!(Machine::<Inc, bool>::new_with_head(Inc, [true].into(), 1).is_none());
+
+        let machine: Machine<Inc, bool> = Machine::from_sides(Inc, [true], [], false);
+
+        assert_eq!(machine.tape_to_vec(), [true, false]);
+        assert_eq!(machine.origin(), 1);
+
+        let machine: Machine<Inc, bool> = Machine::from_sides(Inc, [false], [], true);
+
+        assert_eq!(machine.tape_to_vec(), [false, true]);
+        assert_eq!(machine.blank(), &true);
+    }
+
+    #[test]
+    fn with_tape_from_iter_test() {
+        let mut machine: Machine<Inc, u8> =
+            Machine::with_tape_from_iter(Inc, [1, 1].into_iter().chain([2]), 0);
+        machine.extend([3, 3]);
+        machine.pad_right(2);
+        machine.pad_left(1);
+
+        assert_eq!(machine.tape_to_vec(), [0, 1, 1, 2, 3, 3, 0, 0]);
+        assert_eq!(machine.current_symbol(), &1);
+        assert_eq!(machine.origin(), 1);
+        assert_eq!(
+            Machine::<Inc, u8>::with_tape_from_iter(Inc, [], 0).tape_to_vec(),
+            [0]
+        );
+
+        let mut machine: Machine<Inc, u8> = Machine::with_tape_from_iter(Inc, [], 7);
+        machine.pad_right(1);
+        assert_eq!(machine.tape_to_vec(), [7, 7]);
+    }
+
+    #[test]
+    fn around_head_test() {
+        let mut machine: Machine<Inc, u8> = Machine::new(Inc, [1, 2, 3].into());
+
+        assert_eq!(machine.around_head(0), [1]);
+        assert_eq!(machine.around_head(2), [0, 0, 1, 2, 3]);
+
+        machine.set_head(2);
+
+        assert_eq!(machine.around_head(1), [2, 3, 0]);
+    }
+
+    #[test]
+    fn compact_test() {
+        let mut machine: Machine<Inc, bool> =
+            Machine::with_capacity(Inc, [false, true, false, false].into(), 1_000);
+        machine.apply(Rule::new().left());
+        machine.apply(Rule::new().left());
+        let before = machine.clone();
+
+        machine.compact();
+
+        assert_eq!(machine, before);
+        assert_eq!(machine.tape_to_vec(), [false, false, false, true]);
+        assert_eq!((machine.head(), machine.origin()), (0, 2));
+        assert!(machine.capacity() < 1_000);
+    }
+
+    #[test]
+    fn blank_test() {
+        let mut machine: Machine<Inc, bool> = Machine::new(Inc, [false].into()).with_blank(true);
+        machine.apply(Rule::new().right());
+        machine.apply(Rule::new().by(-2));
+
+        assert!(*machine.blank());
+        assert_eq!(machine.tape_to_vec(), [true, false, true]);
+        assert_eq!(
+            machine.finish_trimmed(),
+            (vec![false], State::State(Inc), 0)
+        );
+    }
+
+    #[test]
+    fn edit_configuration_test() {
+        let mut machine: Machine<Inc, bool> = Machine::new(Inc, [true, true].into());
+
+        machine.write_at(1, false);
+        machine.set_head(1);
+        machine.execute::<IncExecutor>();
+
+        assert!(machine.halted());
+        assert_eq!(machine.tape_to_vec(), [true, true]);
+
+        machine.set_state(State::State(Inc));
+
+        assert!(!machine.halted());
+    }
+
+    #[test]
+    fn seek_test() {
+        let mut machine: Machine<Inc, bool> = Machine::new(Inc, [true, false].into());
+        machine.apply(Rule::new().left());
+
+        assert!(machine.seek(1));
+        assert_eq!(machine.head(), 2);
+        assert!(!*machine.current_symbol());
+        assert!(machine.seek(-1));
+        assert_eq!(machine.head(), 0);
+        assert!(!machine.seek(2));
+        assert!(!machine.seek(-2));
+        assert_eq!(machine.head(), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn set_head_out_of_bounds_test() {
+        let mut machine: Machine<Inc, bool> = Machine::new(Inc, [true].into());
+        machine.set_head(1);
+    }
+
+    #[test]
+    fn accessors_test() {
+        let mut machine: Machine<Inc, bool> = Machine::new(Inc, [true, false].into());
+
+        assert_eq!(machine.state(), Some(&Inc));
+        assert_eq!(machine.head(), 0);
+        assert!(*machine.current_symbol());
+
+        machine.execute::<IncExecutor>();
+        machine.execute::<IncExecutor>();
+
+        assert_eq!(machine.state(), None);
+        assert_eq!(machine.head(), 1);
+        assert!(*machine.current_symbol());
+    }
+
+    #[test]
+    fn relative_move_test() {
+        let mut machine: Machine<Inc, bool> = Machine::new(Inc, [true, false].into());
+
+        machine.apply(Rule::new().by(3));
+
+        assert_eq!(machine.head(), 3);
+        assert_eq!(machine.tape_to_vec(), [true, false, false, false]);
+
+        machine.apply(Rule::new().write(true).by(-5));
+
+        assert_eq!(machine.head(), 0);
+        assert_eq!(machine.origin(), 2);
+        assert_eq!(
+            machine.tape_to_vec(),
+            [false, false, true, false, false, true]
+        );
+        assert_eq!(Move::by(-1), Some(Move::Left));
+        assert_eq!(Move::By(-3).to_string(), "L3");
+    }
+
+    #[test]
+    fn fork_test() {
+        let machine: Machine<Inc, bool> = Machine::new(Inc, [true, true, false].into());
+        let machine = machine.into_shared();
+        let mut fork = machine.fork();
+
+        assert!(fork.tape().is_shared());
+
+        fork.execute::<IncExecutor>();
+
+        assert!(!fork.tape().is_shared());
+        assert_eq!(fork.tape().cells(), &[false, true, false]);
+        assert_eq!(machine.into_unshared().tape_to_vec(), [true, true, false]);
+    }
+
     // TODO: Test something that involves traversing the head backwards
 }
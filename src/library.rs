@@ -0,0 +1,226 @@
+//! Ready-made machines over [`Char`], with `_` as the blank.
+//!
+//! Numbers in binary are written least significant bit first, as
+//! [`Encode`](crate::Encode) does, and unary numbers as runs of `1`. Deciders
+//! halt from an `accept` or a `reject` state, to be judged with
+//! [`accepting`].
+
+use alloc::borrow::ToOwned;
+use alloc::string::String;
+use alloc::vec;
+
+use crate::dsl::{self, Definition};
+use crate::{Acceptance, Char};
+
+const BINARY_INCREMENT: &str = "
+    start inc
+    inc 1 0 R *
+    inc 0 1 N halt
+    inc _ 1 N halt
+";
+
+const BINARY_DECREMENT: &str = "
+    start dec
+    dec 0 1 R *
+    dec 1 0 N halt
+    # below zero: undo the borrows and leave 0
+    dec _ * L undo
+    undo 1 0 L *
+    undo _ * R halt
+";
+
+const UNARY_ADD: &str = "
+    start first
+    first 1 * R *
+    first _ 1 R second
+    second 1 * R *
+    second _ * L last
+    last 1 _ N halt
+";
+
+const PALINDROME: &str = "
+    start start
+    start 0 _ R have0
+    start 1 _ R have1
+    start _ * N accept
+    have0 0 * R *
+    have0 1 * R *
+    have0 _ * L check0
+    have1 0 * R *
+    have1 1 * R *
+    have1 _ * L check1
+    check0 0 _ L back
+    check0 1 * N reject
+    check0 _ * N accept
+    check1 1 _ L back
+    check1 0 * N reject
+    check1 _ * N accept
+    back 0 * L *
+    back 1 * L *
+    back _ * R start
+";
+
+const COPY: &str = "
+    start start
+    # marks the next symbol as x or y and carries it past the separator
+    start 0 x R carry0
+    start 1 y R carry1
+    start _ * N halt
+    carry0 0 * R *
+    carry0 1 * R *
+    carry0 _ * R put0
+    carry1 0 * R *
+    carry1 1 * R *
+    carry1 _ * R put1
+    put0 0 * R *
+    put0 1 * R *
+    put0 _ 0 L return
+    put1 0 * R *
+    put1 1 * R *
+    put1 _ 1 L return
+    return 0 * L *
+    return 1 * L *
+    return _ * L restore
+    restore 0 * L *
+    restore 1 * L *
+    restore x 0 R start
+    restore y 1 R start
+";
+
+const BUSY_BEAVER_3: &str = "
+    start A
+    A _ 1 R B
+    A 1 1 R halt
+    B _ _ R C
+    B 1 1 R B
+    C _ 1 L C
+    C 1 1 L A
+";
+
+fn definition(src: &str) -> Definition<Char> {
+    dsl::parse_definition(src).unwrap()
+}
+
+/// Adds one to a binary number.
+pub fn binary_increment() -> Definition<Char> {
+    definition(BINARY_INCREMENT)
+}
+
+/// Subtracts one from a binary number, leaving 0 as it is.
+pub fn binary_decrement() -> Definition<Char> {
+    definition(BINARY_DECREMENT)
+}
+
+/// Adds two unary numbers separated by a blank, as in `11_111`.
+pub fn unary_add() -> Definition<Char> {
+    definition(UNARY_ADD)
+}
+
+/// Decides whether a string of `0`s and `1`s is a palindrome, erasing it.
+pub fn palindrome() -> Definition<Char> {
+    definition(PALINDROME)
+}
+
+/// Copies a string `w` of `0`s and `1`s, leaving `w_w`.
+pub fn copy() -> Definition<Char> {
+    definition(COPY)
+}
+
+/// The 3-state, 2-symbol busy beaver: it halts after 14 steps with six
+/// ones on the tape.
+pub fn busy_beaver_3() -> Definition<Char> {
+    definition(BUSY_BEAVER_3)
+}
+
+/// Acceptance by halting from the `accept` state.
+pub fn accepting() -> Acceptance<'static, String, Char> {
+    Acceptance::FinalState(vec!["accept".to_owned()])
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::*;
+    use crate::{Decode, Encode, Machine, StopReason, Symbol, Unary, Verdict};
+
+    fn machine(definition: &Definition<Char>, input: &str) -> Machine<String, Char> {
+        Machine::with_input(definition.start.clone(), input).unwrap()
+    }
+
+    fn run(definition: &Definition<Char>, input: &str) -> String {
+        let mut machine = machine(definition, input);
+        machine.run_table(&definition.table, 10_000);
+
+        assert!(machine.halted());
+
+        let (tape, _, _) = machine.finish_trimmed();
+        tape.iter().map(Symbol::to_char).collect()
+    }
+
+    #[test]
+    fn arithmetic_test() {
+        let (inc, dec) = (binary_increment(), binary_decrement());
+
+        for n in 0..64u32 {
+            assert_eq!(u32::decode(&run(&inc, &n.encode())), Some(n + 1));
+            assert_eq!(
+                u32::decode(&run(&dec, &n.encode())),
+                Some(n.saturating_sub(1))
+            );
+        }
+
+        let add = unary_add();
+
+        for (a, b) in [(0u8, 0u8), (2, 3), (0, 4), (5, 0)] {
+            let input = alloc::format!("{}_{}", Unary(a).encode(), Unary(b).encode());
+
+            assert_eq!(Unary::<u8>::decode(&run(&add, &input)), Some(Unary(a + b)));
+        }
+    }
+
+    #[test]
+    fn palindrome_test() {
+        let palindrome = palindrome();
+        let acceptance = accepting();
+
+        for input in ["", "0", "11", "010", "0110", "10101", "0100", "01", "110"] {
+            let mut machine = machine(&palindrome, input);
+            let expected = match input.chars().rev().eq(input.chars()) {
+                true => Verdict::Accept,
+                false => Verdict::Reject,
+            };
+
+            assert_eq!(
+                machine.run_table_accepting(&palindrome.table, 10_000, &acceptance),
+                Ok(expected),
+                "{input:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn copy_test() {
+        let copy = copy();
+
+        for input in ["0", "1", "0110", "111"] {
+            assert_eq!(run(&copy, input), alloc::format!("{input}_{input}"));
+        }
+
+        assert_eq!(run(&copy, ""), "");
+    }
+
+    #[test]
+    fn busy_beaver_test() {
+        let beaver = busy_beaver_3();
+        let mut machine = machine(&beaver, "");
+        let stats = machine.run_table(&beaver.table, 100);
+
+        assert_eq!(stats.stop, StopReason::Halted);
+        assert_eq!(stats.steps, 14);
+
+        let ones: Vec<Char> = machine.tape_to_vec();
+
+        assert_eq!(ones.iter().filter(|&&c| c == Char('1')).count(), 6);
+    }
+}
@@ -0,0 +1,172 @@
+use std::collections::VecDeque;
+use std::fmt::Arguments;
+use std::io::{self, BufRead, Write};
+
+use crate::{Symbol, Tape, TapeFull};
+
+/// A tape appending every change to a log as it happens, so the tape of a
+/// crashed run can be rebuilt with [`replay_tape_log`] and that of a huge one
+/// inspected with external tools.
+///
+/// The log is text, one change per line: `set <index> <symbol>` to overwrite
+/// a cell, and `front <symbol>` or `back <symbol>` to grow the tape. It
+/// starts with a `back` line for every initial cell. Only what reached the
+/// writer survives a crash, so wrap files in a `BufWriter` only as far as
+/// losing the last buffer is acceptable.
+#[derive(Debug)]
+pub struct LoggedTape<T, W> {
+    tape: T,
+    log: W,
+    error: Option<io::Error>,
+}
+
+impl<T, W: Write> LoggedTape<T, W> {
+    /// Logs the cells of `tape`, which must be a [`Tape`] of [`Symbol`]s, and
+    /// then every change to it.
+    pub fn new<Sym>(tape: T, log: W) -> Self
+    where
+        T: Tape<Sym>,
+        Sym: Symbol,
+    {
+        let mut logged = Self {
+            tape,
+            log,
+            error: None,
+        };
+
+        for index in 0..logged.tape.len() {
+            let symbol = logged.tape.get(index).unwrap().to_char();
+            logged.record(format_args!("back {symbol}"));
+        }
+
+        logged
+    }
+
+    /// The first error writing the log, after which nothing more was logged.
+    pub fn error(&self) -> Option<&io::Error> {
+        self.error.as_ref()
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.log.flush()
+    }
+
+    pub fn into_inner(self) -> (T, W) {
+        (self.tape, self.log)
+    }
+
+    fn record(&mut self, line: Arguments<'_>) {
+        if self.error.is_none() {
+            if let Err(error) = writeln!(self.log, "{line}") {
+                self.error = Some(error);
+            }
+        }
+    }
+}
+
+impl<Sym, T, W> Tape<Sym> for LoggedTape<T, W>
+where
+    Sym: Symbol,
+    T: Tape<Sym>,
+    W: Write,
+{
+    fn len(&self) -> usize {
+        self.tape.len()
+    }
+
+    fn get(&self, index: usize) -> Option<&Sym> {
+        self.tape.get(index)
+    }
+
+    fn set(&mut self, index: usize, symbol: Sym) {
+        self.record(format_args!("set {index} {}", symbol.to_char()));
+        self.tape.set(index, symbol);
+    }
+
+    fn push_front(&mut self, symbol: Sym) -> Result<(), TapeFull> {
+        let c = symbol.to_char();
+        self.tape.push_front(symbol)?;
+        self.record(format_args!("front {c}"));
+
+        Ok(())
+    }
+
+    fn push_back(&mut self, symbol: Sym) -> Result<(), TapeFull> {
+        let c = symbol.to_char();
+        self.tape.push_back(symbol)?;
+        self.record(format_args!("back {c}"));
+
+        Ok(())
+    }
+}
+
+/// Rebuilds the tape described by a log written by a [`LoggedTape`]. A
+/// truncated last line, as a crash can leave, is ignored.
+pub fn replay_tape_log<Sym: Symbol>(log: impl BufRead) -> io::Result<VecDeque<Sym>> {
+    let mut tape = VecDeque::new();
+    let mut lines = log.lines().peekable();
+    let invalid = |line: &str| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("invalid tape log line {line:?}"),
+        )
+    };
+
+    while let Some(line) = lines.next() {
+        let line = line?;
+        let last = lines.peek().is_none();
+        let symbol = |field: &str| {
+            let mut chars = field.chars();
+
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Sym::from_char(c),
+                _ => None,
+            }
+        };
+
+        let applied = match line.split_once(' ') {
+            Some(("front", field)) => symbol(field).map(|symbol| tape.push_front(symbol)),
+            Some(("back", field)) => symbol(field).map(|symbol| tape.push_back(symbol)),
+            Some(("set", rest)) => rest.split_once(' ').and_then(|(index, field)| {
+                let index: usize = index.parse().ok().filter(|&index| index < tape.len())?;
+                tape[index] = symbol(field)?;
+                Some(())
+            }),
+            _ => None,
+        };
+
+        if applied.is_none() && !last {
+            return Err(invalid(&line));
+        }
+    }
+
+    Ok(tape)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{dsl, Char, Machine};
+
+    #[test]
+    fn replay_tape_log_test() {
+        let table = dsl::parse::<Char>("inc 1 0 L *\ninc _ 1 N halt\ninc 0 1 N halt").unwrap();
+        let tape: VecDeque<Char> = "11".chars().map(Char).collect();
+
+        let mut machine = Machine::new("inc".to_owned(), LoggedTape::new(tape, Vec::new()));
+        machine.set_head(1);
+        machine.run_table(&table, 100);
+
+        let (tape, log) = machine.into_tape().into_inner();
+        let log = String::from_utf8(log).unwrap();
+
+        assert_eq!(log, "back 1\nback 1\nset 1 0\nset 0 0\nfront _\nset 0 1\n");
+        assert_eq!(replay_tape_log::<Char>(log.as_bytes()).unwrap(), tape);
+
+        // a crash halfway through the last line
+        let crashed = replay_tape_log::<Char>("back 1\nset 0 0\nse".as_bytes()).unwrap();
+
+        assert_eq!(crashed, [Char('0')]);
+        assert!(replay_tape_log::<Char>("set 3 0\nback 1\n".as_bytes()).is_err());
+    }
+}
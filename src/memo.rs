@@ -0,0 +1,136 @@
+use alloc::collections::BTreeMap;
+use core::sync::atomic::AtomicBool;
+
+use crate::run::CANCEL_CHECK_INTERVAL;
+use crate::{Executor, Machine, Rule, RunStats, State, Tape};
+
+/// An executor whose rule depends on nothing but the state and the symbol
+/// read, so that its rules can be computed once and remembered in a
+/// [`Memo`].
+pub trait PureExecutor<S, Sym: Default>: Executor<S, Sym> {}
+
+/// The rules an executor gave, by state and symbol, to run it again without
+/// calling it; see [`Machine::run_memoized`]. One memo can serve any number
+/// of runs of the same executor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Memo<S, Sym> {
+    rules: BTreeMap<(S, Sym), Rule<S, Sym>>,
+}
+
+impl<S, Sym> Memo<S, Sym>
+where
+    S: Ord + Clone,
+    Sym: Ord + Clone + Default,
+{
+    pub fn new() -> Self {
+        Self {
+            rules: BTreeMap::new(),
+        }
+    }
+
+    /// The rule for reading `symbol` in `state`, computed by `E` the first
+    /// time.
+    pub fn rule<E>(&mut self, state: &S, symbol: &Sym) -> &Rule<S, Sym>
+    where
+        E: PureExecutor<S, Sym>,
+    {
+        self.rules
+            .entry((state.clone(), symbol.clone()))
+            .or_insert_with(|| E::execute(state, symbol))
+    }
+
+    /// The number of rules remembered.
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.rules.clear();
+    }
+}
+
+impl<S, Sym> Default for Memo<S, Sym>
+where
+    S: Ord + Clone,
+    Sym: Ord + Clone + Default,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S, Sym, T> Machine<S, Sym, T>
+where
+    S: Ord + Clone,
+    Sym: Ord + Clone + Default,
+    T: Tape<Sym>,
+{
+    /// Like [`Machine::run`], taking the rules from `memo` and calling `E`
+    /// only for configurations it doesn't have yet.
+    pub fn run_memoized<E>(&mut self, max_steps: u64, memo: &mut Memo<S, Sym>) -> RunStats
+    where
+        E: PureExecutor<S, Sym>,
+    {
+        self.run_loop(
+            max_steps,
+            &AtomicBool::new(false),
+            CANCEL_CHECK_INTERVAL,
+            |machine| {
+                let State::State(state) = &machine.state else {
+                    return false;
+                };
+
+                let rule = memo.rule::<E>(state, machine.current_symbol()).clone();
+                machine.apply(rule);
+                true
+            },
+            |_, _| true,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    struct Counting;
+
+    // flips every cell up to the first blank, counting its calls
+    impl Executor<u8, u8> for Counting {
+        fn execute(_state: &u8, symbol: &u8) -> Rule<u8, u8> {
+            CALLS.fetch_add(1, Ordering::Relaxed);
+
+            match symbol {
+                0 => Rule::halt(),
+                symbol => Rule::new().write(3 - symbol).right(),
+            }
+        }
+    }
+
+    impl PureExecutor<u8, u8> for Counting {}
+
+    #[test]
+    fn run_memoized_test() {
+        let mut memo = Memo::new();
+        let mut machine: Machine<u8, u8> = Machine::new(0, [1, 2, 1, 1, 2].into());
+        let stats = machine.run_memoized::<Counting>(100, &mut memo);
+
+        assert_eq!(stats.steps, 6);
+        assert_eq!(machine.tape(), &[2, 1, 2, 2, 1, 0]);
+        assert_eq!(CALLS.load(Ordering::Relaxed), 3);
+
+        let mut machine: Machine<u8, u8> = Machine::new(0, [2, 2].into());
+        machine.run_memoized::<Counting>(100, &mut memo);
+
+        assert_eq!(CALLS.load(Ordering::Relaxed), 3);
+        assert_eq!(memo.len(), 3);
+    }
+}
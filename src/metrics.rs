@@ -0,0 +1,90 @@
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::{RunStats, StopReason};
+
+/// A consumer of the counters of a long search, e.g. a Prometheus registry,
+/// told about every machine simulated; see [`run_batch_metered`].
+///
+/// [`run_batch_metered`]: crate::run_batch_metered
+pub trait Metrics: Sync {
+    /// A machine was run; it's undecided unless it halted.
+    fn record_run(&self, stats: &RunStats);
+
+    /// The tapes of the machines held take `bytes` bytes now.
+    fn record_memory(&self, _bytes: usize) {}
+}
+
+impl Metrics for () {
+    fn record_run(&self, _stats: &RunStats) {}
+}
+
+/// [`Metrics`] kept in atomic counters, which can be read from another
+/// thread while the search runs.
+#[derive(Debug)]
+pub struct Counters {
+    machines: AtomicU64,
+    steps: AtomicU64,
+    undecided: AtomicU64,
+    memory: AtomicUsize,
+    start: Instant,
+}
+
+/// The values of [`Counters`] at some point.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CountersSnapshot {
+    pub machines: u64,
+    pub steps: u64,
+    pub undecided: u64,
+    /// The bytes last reported in use.
+    pub memory: usize,
+    pub elapsed: Duration,
+    pub steps_per_second: f64,
+}
+
+impl Counters {
+    pub fn new() -> Self {
+        Self {
+            machines: AtomicU64::new(0),
+            steps: AtomicU64::new(0),
+            undecided: AtomicU64::new(0),
+            memory: AtomicUsize::new(0),
+            start: Instant::now(),
+        }
+    }
+
+    pub fn snapshot(&self) -> CountersSnapshot {
+        let steps = self.steps.load(Ordering::Relaxed);
+        let elapsed = self.start.elapsed();
+
+        CountersSnapshot {
+            machines: self.machines.load(Ordering::Relaxed),
+            steps,
+            undecided: self.undecided.load(Ordering::Relaxed),
+            memory: self.memory.load(Ordering::Relaxed),
+            elapsed,
+            steps_per_second: steps as f64 / elapsed.as_secs_f64().max(f64::MIN_POSITIVE),
+        }
+    }
+}
+
+impl Default for Counters {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Metrics for Counters {
+    fn record_run(&self, stats: &RunStats) {
+        self.machines.fetch_add(1, Ordering::Relaxed);
+        self.steps.fetch_add(stats.steps, Ordering::Relaxed);
+
+        if stats.stop != StopReason::Halted {
+            self.undecided.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn record_memory(&self, bytes: usize) {
+        self.memory.store(bytes, Ordering::Relaxed);
+    }
+}
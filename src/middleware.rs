@@ -0,0 +1,197 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::sync::atomic::AtomicBool;
+
+use crate::run::CANCEL_CHECK_INTERVAL;
+use crate::{Executor, Machine, Rule, RunStats, State, StopReason, Tape, TransitionTable};
+
+/// A layer around the steps of a run, seeing every rule before it's applied;
+/// see [`Machine::run_layered`].
+///
+/// Closures taking the same arguments are middleware too.
+pub trait Middleware<S, Sym> {
+    /// The rule to apply for reading `symbol` in `state` at `step` instead
+    /// of `rule`, or `None` to stop the run before this step.
+    fn step(
+        &mut self,
+        step: u64,
+        state: &S,
+        symbol: &Sym,
+        rule: Rule<S, Sym>,
+    ) -> Option<Rule<S, Sym>>;
+}
+
+impl<S, Sym, F> Middleware<S, Sym> for F
+where
+    F: FnMut(u64, &S, &Sym, Rule<S, Sym>) -> Option<Rule<S, Sym>>,
+{
+    fn step(
+        &mut self,
+        step: u64,
+        state: &S,
+        symbol: &Sym,
+        rule: Rule<S, Sym>,
+    ) -> Option<Rule<S, Sym>> {
+        self(step, state, symbol, rule)
+    }
+}
+
+/// Middleware layers, each passing the rule it returns on to the next.
+pub struct Layers<'a, S, Sym> {
+    layers: Vec<Box<dyn Middleware<S, Sym> + 'a>>,
+}
+
+impl<'a, S, Sym> Layers<'a, S, Sym> {
+    pub fn new() -> Self {
+        Self { layers: Vec::new() }
+    }
+
+    /// Adds `layer` after the ones already there.
+    pub fn layer(mut self, layer: impl Middleware<S, Sym> + 'a) -> Self {
+        self.layers.push(Box::new(layer));
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.layers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.layers.is_empty()
+    }
+}
+
+impl<S, Sym> Default for Layers<'_, S, Sym> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S, Sym> Middleware<S, Sym> for Layers<'_, S, Sym> {
+    fn step(
+        &mut self,
+        step: u64,
+        state: &S,
+        symbol: &Sym,
+        rule: Rule<S, Sym>,
+    ) -> Option<Rule<S, Sym>> {
+        self.layers
+            .iter_mut()
+            .try_fold(rule, |rule, layer| layer.step(step, state, symbol, rule))
+    }
+}
+
+impl<S, Sym, T> Machine<S, Sym, T>
+where
+    Sym: Default + Clone,
+    T: Tape<Sym>,
+{
+    /// Like [`Machine::run`], passing every rule through `middleware` before
+    /// applying it. A step the middleware stops isn't taken and the run
+    /// stops as cancelled.
+    pub fn run_layered<E, M>(&mut self, max_steps: u64, middleware: &mut M) -> RunStats
+    where
+        E: Executor<S, Sym>,
+        M: Middleware<S, Sym>,
+    {
+        self.run_layered_with(max_steps, middleware, Self::next_rule::<E>)
+    }
+
+    /// Like [`Machine::run_layered`], looking rules up in `table`.
+    pub fn run_table_layered<M>(
+        &mut self,
+        table: &TransitionTable<S, Sym>,
+        max_steps: u64,
+        middleware: &mut M,
+    ) -> RunStats
+    where
+        S: Ord + Clone,
+        Sym: Ord + Clone,
+        M: Middleware<S, Sym>,
+    {
+        self.run_layered_with(max_steps, middleware, |machine| {
+            machine.next_table_rule(table)
+        })
+    }
+
+    fn run_layered_with<M>(
+        &mut self,
+        max_steps: u64,
+        middleware: &mut M,
+        rule: impl Fn(&Self) -> Option<Rule<S, Sym>>,
+    ) -> RunStats
+    where
+        M: Middleware<S, Sym>,
+    {
+        let mut steps = 0;
+        let mut stopped = false;
+
+        let mut stats = self.run_loop(
+            max_steps,
+            &AtomicBool::new(false),
+            CANCEL_CHECK_INTERVAL,
+            |machine| {
+                let (Some(rule), State::State(state)) = (rule(machine), &machine.state) else {
+                    return false;
+                };
+
+                let Some(rule) = middleware.step(steps, state, machine.current_symbol(), rule)
+                else {
+                    stopped = true;
+                    return false;
+                };
+
+                machine.apply(rule);
+                steps += 1;
+                true
+            },
+            |_, _| true,
+        );
+
+        if stopped {
+            stats.stop = StopReason::Cancelled;
+        }
+
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_table_layered_test() {
+        // writes ones while walking right forever
+        let table: TransitionTable<u8, bool> = [(0, false, Rule::new().write(true).right())]
+            .into_iter()
+            .collect();
+
+        let mut log = Vec::new();
+        let mut layers = Layers::new()
+            .layer(|step, _: &u8, _: &bool, rule: Rule<u8, bool>| {
+                log.push(step);
+                Some(rule)
+            })
+            // a fault on every third step
+            .layer(
+                |step: u64, _: &u8, _: &bool, rule: Rule<u8, bool>| match step % 3 {
+                    2 => Some(rule.write(false)),
+                    _ => Some(rule),
+                },
+            )
+            .layer(|step, _: &u8, _: &bool, rule| (step < 5).then_some(rule));
+
+        let mut machine: Machine<u8, bool> = Machine::new(0, [false].into());
+        let stats = machine.run_table_layered(&table, 100, &mut layers);
+        drop(layers);
+
+        assert_eq!(stats.steps, 5);
+        assert_eq!(stats.stop, StopReason::Cancelled);
+        assert_eq!(
+            machine.tape_to_vec(),
+            [true, true, false, true, true, false]
+        );
+        assert_eq!(log, [0, 1, 2, 3, 4, 5]);
+    }
+}
@@ -0,0 +1,240 @@
+//! A [`Tape`] stored in a memory-mapped file, for tapes larger than memory.
+//!
+//! Cells are single bytes with 0 as the blank, as on a `Machine<S, u8>`. The
+//! file holds a 16-byte header, the index of the tape's first cell and the
+//! tape's length as little-endian `u64`s, followed by `capacity` cells, so a
+//! tape can be reopened with [`MmapTape::open`] and its cells read by other
+//! tools. The tape grows into the unused cells on either side of it, starting
+//! from the middle of the file.
+//!
+//! The file must not be truncated while it is mapped.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::ops::Range;
+use std::path::Path;
+
+use memmap2::MmapMut;
+
+use crate::{Overflow, Tape, TapeFull};
+
+const HEADER: usize = 16;
+
+/// How [`MmapTape::create`] lays out the unused cells of a new file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fill {
+    /// Leave them as a hole in a sparse file, taking disk space only as the
+    /// tape grows into them.
+    Sparse,
+    /// Write blanks over all of them up front, so the run can't run out of
+    /// disk space halfway.
+    Preallocate,
+}
+
+/// A tape of at most `capacity` cells in a memory-mapped file.
+#[derive(Debug)]
+pub struct MmapTape {
+    map: MmapMut,
+    start: usize,
+    len: usize,
+    overflow: Overflow,
+}
+
+impl MmapTape {
+    /// Creates or replaces the file at `path` with room for `capacity` cells
+    /// and holding `cells`, or a single blank if they are empty.
+    pub fn create(
+        path: impl AsRef<Path>,
+        capacity: usize,
+        cells: &[u8],
+        fill: Fill,
+        overflow: Overflow,
+    ) -> io::Result<Self> {
+        let len = cells.len().max(1);
+
+        if len > capacity {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{len} cells don't fit a capacity of {capacity}"),
+            ));
+        }
+
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+
+        match fill {
+            Fill::Sparse => file.set_len((HEADER + capacity) as u64)?,
+            Fill::Preallocate => {
+                let zeros = [0; 1 << 16];
+                let mut left = HEADER + capacity;
+
+                while left > 0 {
+                    let chunk = left.min(zeros.len());
+                    file.write_all(&zeros[..chunk])?;
+                    left -= chunk;
+                }
+            }
+        }
+
+        let mut tape = Self::map(&file, (capacity - len) / 2, len, overflow)?;
+        let start = HEADER + tape.start;
+        tape.map[start..start + cells.len()].copy_from_slice(cells);
+        tape.write_header();
+
+        Ok(tape)
+    }
+
+    /// Opens a tape created by [`MmapTape::create`], as last written.
+    pub fn open(path: impl AsRef<Path>, overflow: Overflow) -> io::Result<Self> {
+        let file = OpenOptions::new().read(true).write(true).open(path)?;
+        let mut header = [0; HEADER];
+        (&file).read_exact(&mut header)?;
+
+        let field = |range: Range<usize>| {
+            usize::try_from(u64::from_le_bytes(header[range].try_into().unwrap())).ok()
+        };
+        let (start, len) = match (field(0..8), field(8..16)) {
+            (Some(start), Some(len)) => (start, len),
+            _ => return Err(invalid()),
+        };
+        let capacity = usize::try_from(file.metadata()?.len())
+            .ok()
+            .and_then(|size| size.checked_sub(HEADER))
+            .ok_or_else(invalid)?;
+
+        if len == 0 || start.checked_add(len).is_none_or(|end| end > capacity) {
+            return Err(invalid());
+        }
+
+        Self::map(&file, start, len, overflow)
+    }
+
+    fn map(file: &File, start: usize, len: usize, overflow: Overflow) -> io::Result<Self> {
+        // SAFETY: the file was opened for writing by us, and the module
+        // documentation rules out truncating it while it's mapped
+        let map = unsafe { MmapMut::map_mut(file)? };
+
+        Ok(Self {
+            map,
+            start,
+            len,
+            overflow,
+        })
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.map.len() - HEADER
+    }
+
+    /// Writes the cells and the header out to the file, returning once they
+    /// are on disk.
+    pub fn flush(&self) -> io::Result<()> {
+        self.map.flush()
+    }
+
+    fn write_header(&mut self) {
+        self.map[0..8].copy_from_slice(&(self.start as u64).to_le_bytes());
+        self.map[8..16].copy_from_slice(&(self.len as u64).to_le_bytes());
+    }
+
+    fn full(&self) -> Result<(), TapeFull> {
+        match self.overflow {
+            Overflow::Stay => Err(TapeFull),
+            Overflow::Panic => panic!("mapped tape of {} cells overflowed", self.capacity()),
+        }
+    }
+}
+
+fn invalid() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "not a mapped tape file")
+}
+
+impl Tape<u8> for MmapTape {
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn get(&self, index: usize) -> Option<&u8> {
+        (index < self.len).then(|| &self.map[HEADER + self.start + index])
+    }
+
+    fn set(&mut self, index: usize, symbol: u8) {
+        assert!(index < self.len, "index out of tape bounds");
+
+        self.map[HEADER + self.start + index] = symbol;
+    }
+
+    fn push_front(&mut self, symbol: u8) -> Result<(), TapeFull> {
+        if self.start == 0 {
+            return self.full();
+        }
+
+        self.start -= 1;
+        self.len += 1;
+        self.map[HEADER + self.start] = symbol;
+        self.write_header();
+
+        Ok(())
+    }
+
+    fn push_back(&mut self, symbol: u8) -> Result<(), TapeFull> {
+        if self.start + self.len == self.capacity() {
+            return self.full();
+        }
+
+        self.map[HEADER + self.start + self.len] = symbol;
+        self.len += 1;
+        self.write_header();
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+
+    use super::*;
+    use crate::{Machine, Rule, TransitionTable};
+
+    #[test]
+    fn mmap_tape_test() {
+        let path = env::temp_dir().join(format!("turing-mmap-{}.tape", std::process::id()));
+        // writes ones leftwards over the input and one cell past it
+        let table: TransitionTable<u8, u8> = [
+            (0, 2, Rule::new().write(1).left()),
+            (0, 0, Rule::halt().write(1)),
+        ]
+        .into_iter()
+        .collect();
+
+        let tape = MmapTape::create(&path, 8, &[2, 2, 2], Fill::Sparse, Overflow::Stay).unwrap();
+        let mut machine = Machine::new(0, tape);
+        machine.set_head(2);
+        machine.run_table(&table, 100);
+
+        assert!(machine.halted());
+        machine.tape().flush().unwrap();
+        drop(machine);
+
+        let tape = MmapTape::open(&path, Overflow::Stay).unwrap();
+        let cells: Vec<u8> = (0..tape.len()).map(|i| *tape.get(i).unwrap()).collect();
+
+        assert_eq!(cells, [1, 1, 1, 1]);
+        assert_eq!(tape.capacity(), 8);
+
+        let mut tape = MmapTape::create(&path, 2, &[], Fill::Preallocate, Overflow::Stay).unwrap();
+
+        assert_eq!(fs::metadata(&path).unwrap().len(), 18);
+        assert_eq!(tape.push_back(1), Ok(()));
+        assert_eq!(tape.push_front(1), Err(TapeFull));
+        assert!(MmapTape::create(&path, 1, &[1, 1], Fill::Sparse, Overflow::Stay).is_err());
+
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,417 @@
+use alloc::collections::{BTreeMap, VecDeque};
+use core::array;
+
+use crate::{Move, RunStats, State, StopReason};
+
+/// A rule of a machine with `K` tapes: what to write on each tape and where
+/// to move each head.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultiRule<S, Sym, const K: usize> {
+    pub new_state: Option<State<S>>,
+    pub write: [Option<Sym>; K],
+    pub head_move: [Option<Move>; K],
+}
+
+impl<S, Sym, const K: usize> MultiRule<S, Sym, K> {
+    /// A rule that only changes the state to `new_state`.
+    pub fn new(new_state: Option<State<S>>) -> Self {
+        Self {
+            new_state,
+            write: array::from_fn(|_| None),
+            head_move: [None; K],
+        }
+    }
+}
+
+/// Rules of a `K`-tape machine, keyed by state and the symbols under the
+/// heads.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultiTable<S, Sym, const K: usize> {
+    rules: BTreeMap<(S, [Sym; K]), MultiRule<S, Sym, K>>,
+}
+
+impl<S, Sym, const K: usize> MultiTable<S, Sym, K>
+where
+    S: Ord,
+    Sym: Ord,
+{
+    pub fn new() -> Self {
+        Self {
+            rules: BTreeMap::new(),
+        }
+    }
+
+    pub fn insert(
+        &mut self,
+        state: S,
+        read: [Sym; K],
+        rule: MultiRule<S, Sym, K>,
+    ) -> Option<MultiRule<S, Sym, K>> {
+        self.rules.insert((state, read), rule)
+    }
+
+    pub fn get(&self, state: &S, read: &[Sym; K]) -> Option<&MultiRule<S, Sym, K>>
+    where
+        S: Clone,
+        Sym: Clone,
+    {
+        self.rules.get(&(state.clone(), read.clone()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// All `(state, read, rule)` entries, ordered by state and then symbols.
+    pub fn iter(&self) -> impl Iterator<Item = (&S, &[Sym; K], &MultiRule<S, Sym, K>)> {
+        self.rules
+            .iter()
+            .map(|((state, read), rule)| (state, read, rule))
+    }
+}
+
+impl<S, Sym, const K: usize> Default for MultiTable<S, Sym, K>
+where
+    S: Ord,
+    Sym: Ord,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A machine with `K` tapes, each with its own head.
+#[derive(Debug, Clone)]
+pub struct MultiMachine<S, Sym, const K: usize> {
+    state: State<S>,
+    tapes: [VecDeque<Sym>; K],
+    heads: [usize; K],
+    origins: [usize; K],
+}
+
+impl<S, Sym, const K: usize> MultiMachine<S, Sym, K>
+where
+    S: Ord + Clone,
+    Sym: Ord + Clone + Default,
+{
+    /// Starts with every head on cell 0 of its tape. Empty tapes get a
+    /// blank.
+    pub fn new(state: S, mut tapes: [VecDeque<Sym>; K]) -> Self {
+        for tape in &mut tapes {
+            if tape.is_empty() {
+                tape.push_back(Sym::default());
+            }
+        }
+
+        Self {
+            state: State::State(state),
+            tapes,
+            heads: [0; K],
+            origins: [0; K],
+        }
+    }
+
+    /// Steps the machine; a missing rule halts it without touching the tapes.
+    pub fn step(&mut self, table: &MultiTable<S, Sym, K>) {
+        let State::State(state) = &self.state else {
+            return;
+        };
+
+        let read = array::from_fn(|tape| self.tapes[tape][self.heads[tape]].clone());
+        let Some(rule) = table.get(state, &read) else {
+            self.state = State::Halt;
+            return;
+        };
+        let rule = rule.clone();
+
+        if let Some(new_state) = rule.new_state {
+            self.state = new_state;
+        }
+
+        for (tape, (write, head_move)) in rule.write.into_iter().zip(rule.head_move).enumerate() {
+            let cells = &mut self.tapes[tape];
+            let head = &mut self.heads[tape];
+
+            if let Some(write) = write {
+                cells[*head] = write;
+            }
+
+            let offset = head_move.map_or(0, Move::offset);
+
+            for _ in 0..offset.unsigned_abs() {
+                match offset < 0 {
+                    true if *head == 0 => {
+                        cells.push_front(Sym::default());
+                        self.origins[tape] += 1;
+                    }
+                    true => *head -= 1,
+                    false => {
+                        if *head == cells.len() - 1 {
+                            cells.push_back(Sym::default());
+                        }
+
+                        *head += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Steps until halting or until `max_steps` steps were taken. The tape
+    /// length in the returned stats is that of the longest tape.
+    pub fn run(&mut self, table: &MultiTable<S, Sym, K>, max_steps: u64) -> RunStats {
+        let mut steps = 0;
+
+        while !self.halted() && steps < max_steps {
+            self.step(table);
+            steps += 1;
+        }
+
+        RunStats {
+            steps,
+            tape_len: self.tapes.iter().map(VecDeque::len).max().unwrap_or(0),
+            stop: match self.halted() {
+                true => StopReason::Halted,
+                false => StopReason::StepLimit,
+            },
+        }
+    }
+
+    pub fn halted(&self) -> bool {
+        self.state == State::Halt
+    }
+
+    pub fn state(&self) -> Option<&S> {
+        match &self.state {
+            State::State(state) => Some(state),
+            State::Halt => None,
+        }
+    }
+
+    pub fn tapes(&self) -> &[VecDeque<Sym>; K] {
+        &self.tapes
+    }
+
+    /// The head positions as tape indices.
+    pub fn heads(&self) -> [usize; K] {
+        self.heads
+    }
+
+    /// The indices of the initial cells 0 within the tapes.
+    pub fn origins(&self) -> [usize; K] {
+        self.origins
+    }
+}
+
+/// A machine with `K` heads on a single tape, stepped by the same tables as
+/// a [`MultiMachine`]: rules read the symbol under every head and write and
+/// move each of them. Heads on the same cell write in order, so the last
+/// one's write wins.
+#[derive(Debug, Clone)]
+pub struct MultiHeadMachine<S, Sym, const K: usize> {
+    state: State<S>,
+    tape: VecDeque<Sym>,
+    heads: [usize; K],
+    origin: usize,
+}
+
+impl<S, Sym, const K: usize> MultiHeadMachine<S, Sym, K>
+where
+    S: Ord + Clone,
+    Sym: Ord + Clone + Default,
+{
+    /// Starts with the heads on the given tape indices. An empty tape gets a
+    /// blank.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a head is outside the tape.
+    pub fn new(state: S, mut tape: VecDeque<Sym>, heads: [usize; K]) -> Self {
+        if tape.is_empty() {
+            tape.push_back(Sym::default());
+        }
+
+        assert!(
+            heads.iter().all(|&head| head < tape.len()),
+            "head index out of tape bounds"
+        );
+
+        Self {
+            state: State::State(state),
+            tape,
+            heads,
+            origin: 0,
+        }
+    }
+
+    /// Steps the machine; a missing rule halts it without touching the tape.
+    pub fn step(&mut self, table: &MultiTable<S, Sym, K>) {
+        let State::State(state) = &self.state else {
+            return;
+        };
+
+        let read = array::from_fn(|head| self.tape[self.heads[head]].clone());
+        let Some(rule) = table.get(state, &read) else {
+            self.state = State::Halt;
+            return;
+        };
+        let rule = rule.clone();
+
+        if let Some(new_state) = rule.new_state {
+            self.state = new_state;
+        }
+
+        for (head, write) in rule.write.into_iter().enumerate() {
+            if let Some(write) = write {
+                self.tape[self.heads[head]] = write;
+            }
+        }
+
+        for (head, head_move) in rule.head_move.into_iter().enumerate() {
+            let offset = head_move.map_or(0, Move::offset);
+
+            for _ in 0..offset.unsigned_abs() {
+                match offset < 0 {
+                    true if self.heads[head] == 0 => {
+                        // every other head shifts along with the tape
+                        self.tape.push_front(Sym::default());
+                        self.origin += 1;
+
+                        for (other, index) in self.heads.iter_mut().enumerate() {
+                            if other != head {
+                                *index += 1;
+                            }
+                        }
+                    }
+                    true => self.heads[head] -= 1,
+                    false => {
+                        if self.heads[head] == self.tape.len() - 1 {
+                            self.tape.push_back(Sym::default());
+                        }
+
+                        self.heads[head] += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Steps until halting or until `max_steps` steps were taken.
+    pub fn run(&mut self, table: &MultiTable<S, Sym, K>, max_steps: u64) -> RunStats {
+        let mut steps = 0;
+
+        while !self.halted() && steps < max_steps {
+            self.step(table);
+            steps += 1;
+        }
+
+        RunStats {
+            steps,
+            tape_len: self.tape.len(),
+            stop: match self.halted() {
+                true => StopReason::Halted,
+                false => StopReason::StepLimit,
+            },
+        }
+    }
+
+    pub fn halted(&self) -> bool {
+        self.state == State::Halt
+    }
+
+    pub fn state(&self) -> Option<&S> {
+        match &self.state {
+            State::State(state) => Some(state),
+            State::Halt => None,
+        }
+    }
+
+    pub fn tape(&self) -> &VecDeque<Sym> {
+        &self.tape
+    }
+
+    /// The head positions as tape indices.
+    pub fn heads(&self) -> [usize; K] {
+        self.heads
+    }
+
+    /// The tape index of the initial cell 0.
+    pub fn origin(&self) -> usize {
+        self.origin
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn multi_machine_test() {
+        // copies tape 0 onto tape 1
+        let mut table: MultiTable<u8, u8, 2> = MultiTable::new();
+
+        for symbol in 1..=2 {
+            table.insert(
+                0,
+                [symbol, 0],
+                MultiRule {
+                    new_state: None,
+                    write: [None, Some(symbol)],
+                    head_move: [Some(Move::Right), Some(Move::Right)],
+                },
+            );
+        }
+
+        table.insert(
+            0,
+            [0, 0],
+            MultiRule {
+                head_move: [Some(Move::Left), Some(Move::Left)],
+                ..MultiRule::new(Some(State::Halt))
+            },
+        );
+
+        let mut machine = MultiMachine::new(0, [[1, 2, 2].into(), VecDeque::new()]);
+        let stats = machine.run(&table, 100);
+
+        assert_eq!(stats.steps, 4);
+        assert_eq!(stats.stop, StopReason::Halted);
+        assert_eq!(machine.tapes()[1], [1, 2, 2, 0]);
+        assert_eq!(machine.heads(), [2, 2]);
+    }
+
+    #[test]
+    fn multi_head_machine_test() {
+        // compares the input with its reverse, one head from each end, and
+        // marks the cells that differ with a 3
+        let mut table: MultiTable<u8, u8, 2> = MultiTable::new();
+
+        for (first, second) in (1..=3).flat_map(|first| (1..=3).map(move |second| (first, second)))
+        {
+            let mark = (first != second).then_some(3);
+
+            table.insert(
+                0,
+                [first, second],
+                MultiRule {
+                    new_state: None,
+                    write: [mark, mark],
+                    head_move: [Some(Move::Right), Some(Move::Left)],
+                },
+            );
+        }
+
+        let mut machine = MultiHeadMachine::new(0, [1, 2, 1, 1].into(), [0, 3]);
+        let stats = machine.run(&table, 100);
+
+        // the second head steps off the left end, shifting the first one
+        assert_eq!(stats.steps, 5);
+        assert_eq!(machine.tape(), &[0, 1, 3, 3, 1, 0]);
+        assert_eq!(machine.heads(), [5, 0]);
+        assert_eq!(machine.origin(), 1);
+    }
+}
@@ -0,0 +1,214 @@
+//! Mutation testing of tables: every rule is perturbed in turn and a test
+//! suite rerun against each perturbed table, or mutant. Mutants the suite
+//! still passes on point at rules the tests don't pin down.
+
+use alloc::collections::BTreeSet;
+use alloc::vec::Vec;
+use core::fmt::{self, Debug, Display};
+
+use crate::{analysis, Move, Rule, State, TransitionTable};
+
+/// A change to the rule for `state` reading `symbol`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mutation<S, Sym> {
+    pub state: S,
+    pub symbol: Sym,
+    pub kind: MutationKind<S, Sym>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MutationKind<S, Sym> {
+    /// Writes another symbol.
+    Write(Sym),
+    /// Moves another way, or stays for `None`.
+    Move(Option<Move>),
+    /// Goes to another state.
+    Redirect(State<S>),
+    /// Removes the rule, halting instead.
+    Remove,
+}
+
+/// The result of [`mutation_test`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MutationReport<S, Sym> {
+    pub killed: usize,
+    /// The mutations the suite passed on.
+    pub survivors: Vec<Mutation<S, Sym>>,
+}
+
+impl<S, Sym> Mutation<S, Sym>
+where
+    S: Ord + Clone,
+    Sym: Ord + Clone,
+{
+    /// A copy of `table` with the mutation applied.
+    pub fn apply(&self, table: &TransitionTable<S, Sym>) -> TransitionTable<S, Sym> {
+        let mut mutant = table.clone();
+        let Some(mut rule) = mutant.remove(&self.state, &self.symbol) else {
+            return mutant;
+        };
+
+        match &self.kind {
+            MutationKind::Write(write) => rule.write = Some(write.clone()),
+            MutationKind::Move(head_move) => rule.head_move = *head_move,
+            MutationKind::Redirect(state) => rule.new_state = Some(state.clone()),
+            MutationKind::Remove => return mutant,
+        }
+
+        mutant.insert(self.state.clone(), self.symbol.clone(), rule);
+        mutant
+    }
+}
+
+/// Every mutation of a rule of `table`: each other symbol of its alphabet to
+/// write, each other move and each other state among those with rules or
+/// entered by one, halting included, and removing the rule.
+pub fn mutations<S, Sym>(table: &TransitionTable<S, Sym>) -> Vec<Mutation<S, Sym>>
+where
+    S: Ord + Clone,
+    Sym: Ord + Clone,
+{
+    let alphabet = analysis::alphabet(table);
+    let mut states: BTreeSet<&S> = table.states().collect();
+
+    for (_, _, rule) in table.iter() {
+        if let Some(State::State(next)) = &rule.new_state {
+            states.insert(next);
+        }
+    }
+
+    let targets: Vec<State<S>> = states
+        .into_iter()
+        .map(|state| State::State(state.clone()))
+        .chain([State::Halt])
+        .collect();
+    let mut mutations = Vec::new();
+
+    for (state, symbol, rule) in table.iter() {
+        let Rule {
+            new_state,
+            write,
+            head_move,
+        } = rule;
+        let write = write.as_ref().unwrap_or(symbol);
+        let next = match new_state {
+            Some(next) => next.clone(),
+            None => State::State(state.clone()),
+        };
+        let mutation = |kind| Mutation {
+            state: state.clone(),
+            symbol: symbol.clone(),
+            kind,
+        };
+
+        for &other in alphabet.iter().filter(|&&other| other != write) {
+            mutations.push(mutation(MutationKind::Write(other.clone())));
+        }
+
+        for other in [Some(Move::Left), Some(Move::Right), None] {
+            if other != *head_move {
+                mutations.push(mutation(MutationKind::Move(other)));
+            }
+        }
+
+        for other in targets.iter().filter(|&other| *other != next) {
+            mutations.push(mutation(MutationKind::Redirect(other.clone())));
+        }
+
+        mutations.push(mutation(MutationKind::Remove));
+    }
+
+    mutations
+}
+
+/// Runs `suite` on every mutant of `table`, where `suite` returns whether
+/// its tests pass.
+pub fn mutation_test<S, Sym>(
+    table: &TransitionTable<S, Sym>,
+    mut suite: impl FnMut(&TransitionTable<S, Sym>) -> bool,
+) -> MutationReport<S, Sym>
+where
+    S: Ord + Clone,
+    Sym: Ord + Clone,
+{
+    let mut killed = 0;
+    let mut survivors = Vec::new();
+
+    for mutation in mutations(table) {
+        match suite(&mutation.apply(table)) {
+            true => survivors.push(mutation),
+            false => killed += 1,
+        }
+    }
+
+    MutationReport { killed, survivors }
+}
+
+impl<S: Debug, Sym: Debug> Display for Mutation<S, Sym> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} reading {:?}: ", self.state, self.symbol)?;
+
+        match &self.kind {
+            MutationKind::Write(write) => write!(f, "write {write:?}"),
+            MutationKind::Move(Some(head_move)) => write!(f, "move {head_move:?}"),
+            MutationKind::Move(None) => write!(f, "stay"),
+            MutationKind::Redirect(State::State(state)) => write!(f, "go to {state:?}"),
+            MutationKind::Redirect(State::Halt) => write!(f, "halt"),
+            MutationKind::Remove => write!(f, "remove the rule"),
+        }
+    }
+}
+
+impl<S: Debug, Sym: Debug> Display for MutationReport<S, Sym> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let total = self.killed + self.survivors.len();
+        writeln!(f, "{}/{total} mutants killed", self.killed)?;
+
+        for survivor in &self.survivors {
+            writeln!(f, "survived: {survivor}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::borrow::ToOwned;
+    use alloc::string::{String, ToString};
+
+    use super::*;
+    use crate::{dsl, Char, Machine, StopReason};
+
+    fn passes(table: &TransitionTable<String, Char>, inputs: &[u32]) -> bool {
+        inputs.iter().all(|&n| {
+            let mut machine = Machine::with_encoded_input("inc".to_owned(), n).unwrap();
+            let stats = machine.run_table(table, 1_000);
+
+            stats.stop == StopReason::Halted && machine.decode_output() == Some(n + 1)
+        })
+    }
+
+    #[test]
+    fn mutation_test_test() {
+        let table = dsl::parse::<Char>("inc 1 0 R *\ninc 0 1 N halt\ninc _ 1 N halt").unwrap();
+
+        // 3 rules, each with 2 other writes, 2 other moves, 1 other target
+        // and a removal
+        assert_eq!(mutations(&table).len(), 18);
+
+        let weak = mutation_test(&table, |mutant| passes(mutant, &[1]));
+        let strong = mutation_test(&table, |mutant| passes(mutant, &[0, 1, 2, 3, 6]));
+
+        assert!(strong.survivors.len() < weak.survivors.len());
+
+        // halting rules may move the head without changing the output
+        assert!(strong
+            .survivors
+            .iter()
+            .all(|mutation| matches!(mutation.kind, MutationKind::Move(_))));
+        assert!(strong
+            .to_string()
+            .contains("survived: \"inc\" reading Char('0'): move Left"));
+    }
+}
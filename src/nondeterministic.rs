@@ -0,0 +1,144 @@
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+
+use crate::{Machine, NdExecutor, State};
+
+/// How [`Machine::run_nondeterministic`] ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NdStop {
+    /// A branch halted.
+    Halted,
+    /// Every branch ended without halting.
+    Rejected,
+    StepLimit,
+    /// More branches were alive at once than allowed.
+    TooManyBranches,
+}
+
+/// The result of [`Machine::run_nondeterministic`].
+#[derive(Debug, Clone)]
+pub struct NdRun<S, Sym: Default> {
+    pub stop: NdStop,
+    /// The halted branch, if one halted.
+    pub halted: Option<Machine<S, Sym>>,
+    /// The number of steps every live branch had taken when the run ended.
+    pub steps: u64,
+    /// The most branches alive at once.
+    pub width: usize,
+}
+
+impl<S, Sym> Machine<S, Sym, VecDeque<Sym>>
+where
+    S: Clone + PartialEq,
+    Sym: Clone + Default + PartialEq,
+{
+    /// Runs every branch of a nondeterministic machine in lockstep, breadth
+    /// first, until one halts, all of them end, `max_steps` steps were taken
+    /// or more than `max_branches` branches are alive. Branches reaching the
+    /// same configuration are merged.
+    pub fn run_nondeterministic<E>(&self, max_steps: u64, max_branches: usize) -> NdRun<S, Sym>
+    where
+        E: NdExecutor<S, Sym>,
+    {
+        let mut branches = Vec::from([self.clone()]);
+        let mut width = 1;
+
+        for steps in 0..=max_steps {
+            if let Some(index) = branches.iter().position(Machine::halted) {
+                return NdRun {
+                    stop: NdStop::Halted,
+                    halted: Some(branches.swap_remove(index)),
+                    steps,
+                    width,
+                };
+            }
+
+            let stop = if branches.is_empty() {
+                Some(NdStop::Rejected)
+            } else if branches.len() > max_branches {
+                Some(NdStop::TooManyBranches)
+            } else if steps == max_steps {
+                Some(NdStop::StepLimit)
+            } else {
+                None
+            };
+
+            if let Some(stop) = stop {
+                return NdRun {
+                    stop,
+                    halted: None,
+                    steps,
+                    width,
+                };
+            }
+
+            let mut next: Vec<Self> = Vec::new();
+
+            for branch in &branches {
+                let State::State(state) = &branch.state else {
+                    continue;
+                };
+
+                for rule in E::execute(state, branch.current_symbol()) {
+                    let mut child = branch.clone();
+                    child.apply(rule);
+
+                    if !next.contains(&child) {
+                        next.push(child);
+                    }
+                }
+            }
+
+            branches = next;
+            width = width.max(branches.len());
+        }
+
+        unreachable!("the last iteration returns at the step limit")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use smallvec::{smallvec, SmallVec};
+
+    use super::*;
+    use crate::Rule;
+
+    struct Guess;
+
+    // guesses where the last one of the input is, halting if it guessed
+    // right; reading a zero or a blank in state 0 ends the branch
+    impl NdExecutor<u8, bool> for Guess {
+        fn execute(state: &u8, symbol: &bool) -> SmallVec<[Rule<u8, bool>; 2]> {
+            match (state, symbol) {
+                (0, true) => smallvec![Rule::new().right(), Rule::to(1).right()],
+                (1, false) => smallvec![Rule::halt()],
+                _ => smallvec![],
+            }
+        }
+    }
+
+    #[test]
+    fn run_nondeterministic_test() {
+        let machine: Machine<u8, bool> = Machine::new(0, [true, true, true, false].into());
+        let run = machine.run_nondeterministic::<Guess>(100, 10);
+
+        assert_eq!(run.stop, NdStop::Halted);
+        assert_eq!(run.steps, 4);
+        assert_eq!(run.halted.unwrap().head(), 3);
+        assert_eq!(run.width, 2);
+
+        let machine: Machine<u8, bool> = Machine::new(0, [false].into());
+
+        assert_eq!(
+            machine.run_nondeterministic::<Guess>(100, 10).stop,
+            NdStop::Rejected
+        );
+        assert_eq!(
+            Machine::new(0, [true; 4].into())
+                .run_nondeterministic::<Guess>(2, 10)
+                .stop,
+            NdStop::StepLimit
+        );
+    }
+}
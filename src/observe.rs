@@ -0,0 +1,397 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{Sender, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::{Executor, Machine, RunStats, Snapshot, StopReason, Tape, TransitionTable};
+
+type Slot<S, Sym> = Mutex<Option<(u64, Snapshot<S, Sym>)>>;
+
+/// A shared slot holding the most recent snapshot of a running machine.
+///
+/// The running thread publishes into the view with
+/// [`Machine::run_observed`], while any number of clones of the view can
+/// read it from other threads. Publishing never waits on readers: if the slot
+/// is busy, that snapshot is simply skipped.
+#[derive(Debug)]
+pub struct LiveView<S, Sym> {
+    latest: Arc<Slot<S, Sym>>,
+}
+
+impl<S, Sym> LiveView<S, Sym> {
+    pub fn new() -> Self {
+        Self {
+            latest: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// The last published snapshot together with the step it was taken at.
+    pub fn latest(&self) -> Option<(u64, Snapshot<S, Sym>)>
+    where
+        S: Clone,
+        Sym: Clone,
+    {
+        self.latest
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    fn try_publish(&self, step: u64, snapshot: impl FnOnce() -> Snapshot<S, Sym>) {
+        if let Ok(mut latest) = self.latest.try_lock() {
+            *latest = Some((step, snapshot()));
+        }
+    }
+
+    fn publish(&self, step: u64, snapshot: Snapshot<S, Sym>) {
+        *self
+            .latest
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some((step, snapshot));
+    }
+}
+
+impl<S, Sym> Clone for LiveView<S, Sym> {
+    fn clone(&self) -> Self {
+        Self {
+            latest: Arc::clone(&self.latest),
+        }
+    }
+}
+
+impl<S, Sym> Default for LiveView<S, Sym> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S, Sym, T> Machine<S, Sym, T>
+where
+    S: Clone,
+    Sym: Default + Clone,
+    T: Tape<Sym>,
+{
+    /// Like [`Machine::run_cancellable`], additionally publishing a snapshot
+    /// into `view` every `every` steps. The final configuration is always
+    /// published.
+    pub fn run_observed<E>(
+        &mut self,
+        max_steps: u64,
+        cancel: &AtomicBool,
+        view: &LiveView<S, Sym>,
+        every: u64,
+    ) -> RunStats
+    where
+        E: Executor<S, Sym>,
+    {
+        let stats = self.run_hooked::<E>(max_steps, cancel, every, |machine, steps| {
+            view.try_publish(steps, || machine.snapshot());
+            true
+        });
+
+        view.publish(stats.steps, self.snapshot());
+
+        stats
+    }
+
+    /// Like [`Machine::run_cancellable`], additionally sending a snapshot into
+    /// `sink` every `stride` steps, plus one of the final configuration. The
+    /// run stops as cancelled once the sink is disconnected.
+    pub fn run_streaming<E, K>(
+        &mut self,
+        max_steps: u64,
+        cancel: &AtomicBool,
+        sink: &mut K,
+        stride: u64,
+    ) -> RunStats
+    where
+        E: Executor<S, Sym>,
+        K: SnapshotSink<S, Sym>,
+    {
+        let mut stats = self.run_hooked::<E>(max_steps, cancel, stride, |machine, steps| {
+            sink.send(steps, machine.snapshot())
+        });
+
+        if !sink.send(stats.steps, self.snapshot()) {
+            stats.stop = StopReason::Cancelled;
+        }
+
+        stats
+    }
+}
+
+/// How far a run has come, as passed to progress callbacks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    pub steps: u64,
+    pub tape_len: usize,
+    pub elapsed: Duration,
+}
+
+impl<S, Sym, T> Machine<S, Sym, T>
+where
+    Sym: Default + Clone,
+    T: Tape<Sym>,
+{
+    /// Like [`Machine::run_cancellable`], additionally calling `progress`
+    /// every `every` steps and once more when the run stops, e.g. to advance
+    /// a progress bar.
+    pub fn run_with_progress<E>(
+        &mut self,
+        max_steps: u64,
+        cancel: &AtomicBool,
+        every: u64,
+        progress: impl FnMut(&Progress),
+    ) -> RunStats
+    where
+        E: Executor<S, Sym>,
+    {
+        self.run_progress_loop(max_steps, cancel, every, Self::step::<E>, progress)
+    }
+
+    /// Like [`Machine::run_with_progress`], looking rules up in `table`.
+    pub fn run_table_with_progress(
+        &mut self,
+        table: &TransitionTable<S, Sym>,
+        max_steps: u64,
+        cancel: &AtomicBool,
+        every: u64,
+        progress: impl FnMut(&Progress),
+    ) -> RunStats
+    where
+        S: Ord + Clone,
+        Sym: Ord + Clone,
+    {
+        self.run_progress_loop(
+            max_steps,
+            cancel,
+            every,
+            |machine| machine.step_table(table),
+            progress,
+        )
+    }
+
+    fn run_progress_loop(
+        &mut self,
+        max_steps: u64,
+        cancel: &AtomicBool,
+        every: u64,
+        step: impl FnMut(&mut Self) -> bool,
+        mut progress: impl FnMut(&Progress),
+    ) -> RunStats {
+        let start = Instant::now();
+        let stats = self.run_loop(max_steps, cancel, every, step, |machine, steps| {
+            progress(&Progress {
+                steps,
+                tape_len: machine.tape.len(),
+                elapsed: start.elapsed(),
+            });
+            true
+        });
+
+        progress(&Progress {
+            steps: stats.steps,
+            tape_len: stats.tape_len,
+            elapsed: start.elapsed(),
+        });
+
+        stats
+    }
+}
+
+impl<S, Sym, T> Machine<S, Sym, T>
+where
+    Sym: Default + Clone,
+    T: Tape<Sym>,
+{
+    /// Like [`Machine::run_cancellable`], taking `steps_per_second` steps a
+    /// second and calling `frame` before each one, e.g. to redraw a demo.
+    ///
+    /// Steps are paced against the time the run started rather than the
+    /// previous step, so a slow frame isn't lost: the steps it held up are
+    /// then taken without waiting until the run is back on schedule.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `steps_per_second` isn't positive.
+    pub fn run_throttled<E>(
+        &mut self,
+        max_steps: u64,
+        cancel: &AtomicBool,
+        steps_per_second: f64,
+        frame: impl FnMut(&Self, u64),
+    ) -> RunStats
+    where
+        E: Executor<S, Sym>,
+    {
+        self.run_throttled_loop(max_steps, cancel, steps_per_second, Self::step::<E>, frame)
+    }
+
+    /// Like [`Machine::run_throttled`], looking rules up in `table`.
+    pub fn run_table_throttled(
+        &mut self,
+        table: &TransitionTable<S, Sym>,
+        max_steps: u64,
+        cancel: &AtomicBool,
+        steps_per_second: f64,
+        frame: impl FnMut(&Self, u64),
+    ) -> RunStats
+    where
+        S: Ord + Clone,
+        Sym: Ord + Clone,
+    {
+        self.run_throttled_loop(
+            max_steps,
+            cancel,
+            steps_per_second,
+            |machine| machine.step_table(table),
+            frame,
+        )
+    }
+
+    fn run_throttled_loop(
+        &mut self,
+        max_steps: u64,
+        cancel: &AtomicBool,
+        steps_per_second: f64,
+        step: impl FnMut(&mut Self) -> bool,
+        mut frame: impl FnMut(&Self, u64),
+    ) -> RunStats {
+        let start = Instant::now();
+        let period = Duration::from_secs_f64(1.0 / steps_per_second);
+
+        self.run_loop(max_steps, cancel, 1, step, |machine, steps| {
+            frame(machine, steps);
+
+            let due = start + period.mul_f64(steps as f64);
+            let now = Instant::now();
+
+            if due > now {
+                thread::sleep(due - now);
+            }
+
+            !cancel.load(Ordering::Relaxed)
+        })
+    }
+}
+
+/// A consumer of `(step, snapshot)` pairs, such as a channel sender.
+pub trait SnapshotSink<S, Sym> {
+    /// Returns `false` once the consumer has gone away.
+    fn send(&mut self, step: u64, snapshot: Snapshot<S, Sym>) -> bool;
+}
+
+impl<S, Sym> SnapshotSink<S, Sym> for Sender<(u64, Snapshot<S, Sym>)> {
+    fn send(&mut self, step: u64, snapshot: Snapshot<S, Sym>) -> bool {
+        Sender::send(self, (step, snapshot)).is_ok()
+    }
+}
+
+impl<S, Sym> SnapshotSink<S, Sym> for SyncSender<(u64, Snapshot<S, Sym>)> {
+    fn send(&mut self, step: u64, snapshot: Snapshot<S, Sym>) -> bool {
+        SyncSender::send(self, (step, snapshot)).is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+    use std::thread;
+
+    use super::*;
+    use crate::{Move, Rule, State};
+
+    struct Walker;
+
+    // walks right forever, flipping every cell it passes
+    impl Executor<u8, bool> for Walker {
+        fn execute(_state: &u8, symbol: &bool) -> Rule<u8, bool> {
+            Rule {
+                new_state: None,
+                write: Some(!symbol),
+                head_move: Some(Move::Right),
+            }
+        }
+    }
+
+    #[test]
+    fn live_view_test() {
+        let view = LiveView::new();
+        let reader = view.clone();
+        let cancel = AtomicBool::new(false);
+        let mut machine: Machine<u8, bool> = Machine::new(0, [false].into());
+
+        let stats = thread::scope(|scope| {
+            let runner =
+                scope.spawn(|| machine.run_observed::<Walker>(10_000, &cancel, &view, 100));
+
+            // every snapshot a reader sees must be internally consistent
+            while !runner.is_finished() {
+                if let Some((step, snapshot)) = reader.latest() {
+                    assert_eq!(snapshot.head as u64, step);
+                    assert!(snapshot.tape[..snapshot.head].iter().all(|cell| *cell));
+                }
+            }
+
+            runner.join().unwrap()
+        });
+
+        let (step, snapshot) = reader.latest().unwrap();
+
+        assert_eq!(stats.steps, 10_000);
+        assert_eq!(step, 10_000);
+        assert_eq!(snapshot.state, State::State(0));
+        assert_eq!(snapshot.head, 10_000);
+    }
+
+    #[test]
+    fn run_with_progress_test() {
+        let mut machine: Machine<u8, bool> = Machine::new(0, [false].into());
+        let mut reports = Vec::new();
+        let stats =
+            machine.run_with_progress::<Walker>(10, &AtomicBool::new(false), 4, |progress| {
+                reports.push((progress.steps, progress.tape_len))
+            });
+
+        assert_eq!(stats.steps, 10);
+        assert_eq!(reports, [(0, 1), (4, 5), (8, 9), (10, 11)]);
+    }
+
+    #[test]
+    fn run_throttled_test() {
+        let mut machine: Machine<u8, bool> = Machine::new(0, [false].into());
+        let mut frames = Vec::new();
+        let start = Instant::now();
+        let stats =
+            machine.run_throttled::<Walker>(20, &AtomicBool::new(false), 400.0, |_, step| {
+                frames.push(step);
+
+                // a hiccup the following steps catch up on
+                if step == 5 {
+                    thread::sleep(Duration::from_millis(20));
+                }
+            });
+
+        assert_eq!(stats.steps, 20);
+        assert_eq!(frames, (0..20).collect::<Vec<_>>());
+        assert!(start.elapsed() >= Duration::from_millis(47));
+    }
+
+    #[test]
+    fn run_streaming_test() {
+        let (mut sender, receiver) = mpsc::channel();
+        let mut machine: Machine<u8, bool> = Machine::new(0, [false].into());
+        let stats = machine.run_streaming::<Walker, _>(10, &AtomicBool::new(false), &mut sender, 4);
+        let steps: Vec<u64> = receiver.try_iter().map(|(step, _)| step).collect();
+
+        assert_eq!(stats.stop, StopReason::StepLimit);
+        assert_eq!(steps, [0, 4, 8, 10]);
+
+        drop(receiver);
+        let stats = machine.run_streaming::<Walker, _>(10, &AtomicBool::new(false), &mut sender, 4);
+
+        assert_eq!(stats.steps, 0);
+        assert_eq!(stats.stop, StopReason::Cancelled);
+    }
+}
@@ -0,0 +1,357 @@
+//! A stable C ABI for executors compiled into shared libraries, so that
+//! native executors can live outside the application running them.
+//!
+//! A plugin is a `cdylib` exporting [`ENTRY_POINT`], usually through
+//! [`export_plugin!`](crate::export_plugin), which returns a
+//! [`PluginDescriptor`]. States and symbols cross the boundary as their
+//! [`TuringState::ordinal`]s and [`Symbol::ordinal`]s, so both sides have to
+//! agree on the state and symbol types. Plugin crates depend on this crate
+//! with the `plugins` feature too, for the macro.
+
+use std::error::Error;
+use std::ffi::{c_char, CStr, OsStr};
+use std::fmt::{self, Display};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+
+use libloading::Library;
+
+use crate::run::CANCEL_CHECK_INTERVAL;
+use crate::{Machine, Move, Rule, RunStats, State, Symbol, Tape, TuringState};
+
+/// The version of the ABI described here, checked when loading a plugin.
+pub const ABI_VERSION: u32 = 1;
+
+/// The name of the function a plugin exports, taking no arguments and
+/// returning a `*const PluginDescriptor`.
+pub const ENTRY_POINT: &str = "turing_plugin_v1";
+
+/// For [`RawRule::new_state`] and [`RawRule::write`]: no change.
+pub const KEEP: u32 = u32::MAX;
+
+/// For [`RawRule::new_state`]: halt.
+pub const HALT: u32 = u32::MAX - 1;
+
+/// A [`Rule`] over ordinals.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RawRule {
+    /// The ordinal of the next state, or [`KEEP`] or [`HALT`].
+    pub new_state: u32,
+    /// The ordinal of the symbol to write, or [`KEEP`].
+    pub write: u32,
+    /// The cells to move the head by, negative to the left.
+    pub head_move: i32,
+}
+
+impl RawRule {
+    pub fn from_rule<S: TuringState, Sym: Symbol>(rule: &Rule<S, Sym>) -> Self {
+        Self {
+            new_state: match &rule.new_state {
+                None => KEEP,
+                Some(State::Halt) => HALT,
+                Some(State::State(state)) => state.ordinal() as u32,
+            },
+            write: rule.write.as_ref().map_or(KEEP, Symbol::ordinal),
+            head_move: rule
+                .head_move
+                .map_or(0, |head_move| head_move.offset() as i32),
+        }
+    }
+
+    /// The rule, or `None` if an ordinal has no state or symbol.
+    pub fn to_rule<S, Sym>(self) -> Option<Rule<S, Sym>>
+    where
+        S: TuringState + Clone,
+        Sym: Symbol,
+    {
+        Some(Rule {
+            new_state: match self.new_state {
+                KEEP => None,
+                HALT => Some(State::Halt),
+                state => Some(State::State(S::from_ordinal(state as usize)?)),
+            },
+            write: match self.write {
+                KEEP => None,
+                write => Some(Sym::from_ordinal(write)?),
+            },
+            head_move: Move::by(self.head_move as isize),
+        })
+    }
+}
+
+/// What a plugin's [`ENTRY_POINT`] returns, valid for as long as the plugin
+/// stays loaded.
+#[repr(C)]
+#[derive(Debug)]
+pub struct PluginDescriptor {
+    /// [`ABI_VERSION`] when the plugin was built.
+    pub abi_version: u32,
+    /// A null-terminated UTF-8 name.
+    pub name: *const c_char,
+    /// Writes the rule for reading symbol `symbol` in state `state` to
+    /// `rule`, returning `false` if there is none.
+    pub execute: unsafe extern "C" fn(state: u32, symbol: u32, rule: *mut RawRule) -> bool,
+}
+
+// the name points to a static string
+unsafe impl Sync for PluginDescriptor {}
+
+/// Exports the [`Executor`](crate::Executor) `$executor` over the states
+/// `$state` and symbols `$symbol` as a plugin named `$name`, from a crate
+/// built as a `cdylib`.
+#[macro_export]
+macro_rules! export_plugin {
+    ($name:literal, $executor:ty, $state:ty, $symbol:ty) => {
+        #[no_mangle]
+        pub extern "C" fn turing_plugin_v1() -> *const $crate::plugin::PluginDescriptor {
+            unsafe extern "C" fn execute(
+                state: u32,
+                symbol: u32,
+                rule: *mut $crate::plugin::RawRule,
+            ) -> bool {
+                let (Some(state), Some(symbol)) = (
+                    <$state as $crate::TuringState>::from_ordinal(state as usize),
+                    <$symbol as $crate::Symbol>::from_ordinal(symbol),
+                ) else {
+                    return false;
+                };
+
+                let executed =
+                    <$executor as $crate::Executor<$state, $symbol>>::execute(&state, &symbol);
+                *rule = $crate::plugin::RawRule::from_rule(&executed);
+                true
+            }
+
+            static DESCRIPTOR: $crate::plugin::PluginDescriptor =
+                $crate::plugin::PluginDescriptor {
+                    abi_version: $crate::plugin::ABI_VERSION,
+                    name: concat!($name, "\0").as_ptr().cast(),
+                    execute,
+                };
+
+            &DESCRIPTOR
+        }
+    };
+}
+
+#[derive(Debug)]
+pub enum PluginError {
+    Load(libloading::Error),
+    /// The plugin was built for another [`ABI_VERSION`].
+    Version(u32),
+    /// The plugin's name isn't valid UTF-8.
+    Name,
+}
+
+impl Display for PluginError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PluginError::Load(error) => write!(f, "failed to load plugin: {error}"),
+            PluginError::Version(version) => write!(
+                f,
+                "plugin was built for ABI version {version}, not {ABI_VERSION}"
+            ),
+            PluginError::Name => f.write_str("plugin name is not valid UTF-8"),
+        }
+    }
+}
+
+impl Error for PluginError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PluginError::Load(error) => Some(error),
+            PluginError::Version(_) | PluginError::Name => None,
+        }
+    }
+}
+
+/// A loaded plugin.
+#[derive(Debug)]
+pub struct Plugin {
+    descriptor: &'static PluginDescriptor,
+    name: String,
+    // keeps the descriptor's code loaded; dropped last
+    _library: Option<Library>,
+}
+
+impl Plugin {
+    /// Loads the plugin at `path`.
+    ///
+    /// # Safety
+    ///
+    /// Loading runs the library's initialization code, and the library has
+    /// to be a plugin whose [`ENTRY_POINT`] behaves as documented.
+    pub unsafe fn load(path: impl AsRef<Path>) -> Result<Self, PluginError> {
+        let library = Library::new(path.as_ref()).map_err(PluginError::Load)?;
+        let entry = library
+            .get::<unsafe extern "C" fn() -> *const PluginDescriptor>(ENTRY_POINT.as_bytes())
+            .map_err(PluginError::Load)?;
+        let descriptor = &*entry();
+
+        Self::from_descriptor(descriptor, Some(library))
+    }
+
+    /// Loads every file in `dir` with the platform's shared library
+    /// extension, e.g. `.so`, as a plugin.
+    ///
+    /// # Safety
+    ///
+    /// As for [`Plugin::load`], for every such file.
+    pub unsafe fn discover(
+        dir: impl AsRef<Path>,
+    ) -> io::Result<Vec<(PathBuf, Result<Self, PluginError>)>> {
+        let mut plugins = Vec::new();
+
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+
+            if path.extension() == Some(OsStr::new(std::env::consts::DLL_EXTENSION)) {
+                let plugin = Self::load(&path);
+                plugins.push((path, plugin));
+            }
+        }
+
+        plugins.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        Ok(plugins)
+    }
+
+    // `descriptor` has to stay valid while `library` is loaded
+    unsafe fn from_descriptor(
+        descriptor: &'static PluginDescriptor,
+        library: Option<Library>,
+    ) -> Result<Self, PluginError> {
+        if descriptor.abi_version != ABI_VERSION {
+            return Err(PluginError::Version(descriptor.abi_version));
+        }
+
+        let name = CStr::from_ptr(descriptor.name)
+            .to_str()
+            .map_err(|_| PluginError::Name)?
+            .to_owned();
+
+        Ok(Self {
+            descriptor,
+            name,
+            _library: library,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The rule for reading the symbol numbered `symbol` in the state
+    /// numbered `state`.
+    pub fn execute(&self, state: u32, symbol: u32) -> Option<RawRule> {
+        let mut rule = RawRule {
+            new_state: KEEP,
+            write: KEEP,
+            head_move: 0,
+        };
+
+        // the descriptor is valid while the library is loaded
+        unsafe { (self.descriptor.execute)(state, symbol, &mut rule) }.then_some(rule)
+    }
+}
+
+impl<S, Sym, T> Machine<S, Sym, T>
+where
+    S: TuringState + Clone,
+    Sym: Symbol + Default + Clone,
+    T: Tape<Sym>,
+{
+    /// Runs the machine with the rules of `plugin` until it halts or
+    /// `max_steps` steps were taken. The run stops with
+    /// [`StopReason::NoRule`](crate::StopReason::NoRule) if the plugin has no
+    /// rule, or gives one with ordinals `S` or `Sym` don't have.
+    pub fn run_plugin(&mut self, plugin: &Plugin, max_steps: u64) -> RunStats {
+        self.run_loop(
+            max_steps,
+            &AtomicBool::new(false),
+            CANCEL_CHECK_INTERVAL,
+            |machine| {
+                let State::State(state) = &machine.state else {
+                    return false;
+                };
+
+                let rule = plugin
+                    .execute(state.ordinal() as u32, machine.current_symbol().ordinal())
+                    .and_then(RawRule::to_rule);
+
+                match rule {
+                    Some(rule) => {
+                        machine.apply(rule);
+                        true
+                    }
+                    None => false,
+                }
+            },
+            |_, _| true,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use turing_derive::TuringState;
+
+    use super::*;
+    use crate::{Executor, StopReason};
+
+    #[derive(Debug, Clone, PartialEq, Eq, TuringState)]
+    enum Walk {
+        Right,
+        Back,
+    }
+
+    struct Walker;
+
+    // walks right over ones, then back one cell and halts
+    impl Executor<Walk, bool> for Walker {
+        fn execute(state: &Walk, symbol: &bool) -> Rule<Walk, bool> {
+            match (state, symbol) {
+                (Walk::Right, true) => Rule::new().right(),
+                (Walk::Right, false) => Rule::to(Walk::Back).write(true).left(),
+                (Walk::Back, _) => Rule::halt(),
+            }
+        }
+    }
+
+    export_plugin!("walker", Walker, Walk, bool);
+
+    #[test]
+    fn plugin_test() {
+        let plugin = unsafe { Plugin::from_descriptor(&*turing_plugin_v1(), None) }.unwrap();
+
+        assert_eq!(plugin.name(), "walker");
+        assert_eq!(
+            plugin.execute(0, 0),
+            Some(RawRule {
+                new_state: 1,
+                write: 1,
+                head_move: -1,
+            })
+        );
+        assert_eq!(plugin.execute(7, 0), None);
+
+        let mut machine: Machine<Walk, bool> = Machine::new(Walk::Right, [true, true].into());
+        let stats = machine.run_plugin(&plugin, 100);
+
+        assert_eq!(stats.steps, 4);
+        assert_eq!(stats.stop, StopReason::Halted);
+        assert_eq!(machine.tape_to_vec(), [true, true, true]);
+        assert_eq!(machine.head(), 1);
+    }
+
+    #[test]
+    fn load_error_test() {
+        assert!(matches!(
+            unsafe { Plugin::load("/nonexistent/plugin.so") },
+            Err(PluginError::Load(_))
+        ));
+    }
+}
@@ -0,0 +1,320 @@
+use std::borrow::Borrow;
+use std::collections::VecDeque;
+use std::iter;
+use std::sync::mpsc;
+use std::thread;
+
+use crate::{Machine, RunStats, StopReason, Tape, TransitionTable};
+
+/// The steps a machine of a [`MachinePool`] takes in a turn unless set with
+/// [`MachinePool::with_quantum`].
+pub const DEFAULT_QUANTUM: u64 = 1_000;
+
+/// How a [`MachinePool`] picks the machine to take the next turn.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Schedule {
+    /// Every machine in turn, for the quantum times its priority, so that
+    /// machines get steps in proportion to their priorities.
+    #[default]
+    Fair,
+    /// The machines with the highest priority in turn, for the quantum; the
+    /// others wait until those finish.
+    Priority,
+}
+
+/// A machine of a [`MachinePool`] that finished, with how.
+#[derive(Debug, Clone)]
+pub struct Completion<S, Sym: Default, T> {
+    /// The id [`MachinePool::add`] gave it.
+    pub id: usize,
+    pub stats: RunStats,
+    pub machine: Machine<S, Sym, T>,
+}
+
+/// Many machines, each with its own `(table, machine)` pair and step budget,
+/// run interleaved a quantum of steps at a time, reporting every machine as
+/// it halts, runs out of rules or out of budget.
+#[derive(Debug, Clone)]
+pub struct MachinePool<S, Sym: Default, P, T = VecDeque<Sym>> {
+    schedule: Schedule,
+    quantum: u64,
+    next_id: usize,
+    live: VecDeque<Entry<S, Sym, P, T>>,
+}
+
+#[derive(Debug, Clone)]
+struct Entry<S, Sym: Default, P, T> {
+    id: usize,
+    table: P,
+    machine: Machine<S, Sym, T>,
+    priority: u32,
+    budget: u64,
+    steps: u64,
+}
+
+impl<S, Sym, P, T> MachinePool<S, Sym, P, T>
+where
+    S: Ord + Clone,
+    Sym: Default + Ord + Clone,
+    T: Tape<Sym>,
+    P: Borrow<TransitionTable<S, Sym>>,
+{
+    pub fn new(schedule: Schedule) -> Self {
+        Self {
+            schedule,
+            quantum: DEFAULT_QUANTUM,
+            next_id: 0,
+            live: VecDeque::new(),
+        }
+    }
+
+    /// Makes a turn `quantum` steps, at least 1.
+    pub fn with_quantum(mut self, quantum: u64) -> Self {
+        self.quantum = quantum.max(1);
+        self
+    }
+
+    /// Adds `machine` to run under `table` for at most `budget` steps, with
+    /// priority 1. Returns its id, counting from 0.
+    pub fn add(&mut self, table: P, machine: Machine<S, Sym, T>, budget: u64) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        self.live.push_back(Entry {
+            id,
+            table,
+            machine,
+            priority: 1,
+            budget,
+            steps: 0,
+        });
+
+        id
+    }
+
+    /// Sets the priority of machine `id`, returning `false` if it isn't in
+    /// the pool. Under [`Schedule::Fair`] a priority of 0 counts as 1.
+    pub fn set_priority(&mut self, id: usize, priority: u32) -> bool {
+        let Some(entry) = self.live.iter_mut().find(|entry| entry.id == id) else {
+            return false;
+        };
+
+        entry.priority = priority;
+        true
+    }
+
+    /// Machine `id` if it is still running.
+    pub fn machine(&self, id: usize) -> Option<&Machine<S, Sym, T>> {
+        let entry = self.live.iter().find(|entry| entry.id == id)?;
+        Some(&entry.machine)
+    }
+
+    /// Takes machine `id` out of the pool, as cancelled.
+    pub fn cancel(&mut self, id: usize) -> Option<Completion<S, Sym, T>> {
+        let index = self.live.iter().position(|entry| entry.id == id)?;
+        let entry = self.live.remove(index)?;
+
+        Some(entry.complete(StopReason::Cancelled))
+    }
+
+    /// The number of machines still running.
+    pub fn len(&self) -> usize {
+        self.live.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.live.is_empty()
+    }
+
+    /// Gives the next machine its turn, returning it if it finished, or
+    /// `None`, also if the pool is empty.
+    pub fn turn(&mut self) -> Option<Completion<S, Sym, T>> {
+        let index = match self.schedule {
+            Schedule::Fair => 0,
+            // the first of the highest, which goes to the back after its turn
+            Schedule::Priority => {
+                let highest = self.live.iter().map(|entry| entry.priority).max()?;
+                self.live
+                    .iter()
+                    .position(|entry| entry.priority == highest)?
+            }
+        };
+
+        let mut entry = self.live.remove(index)?;
+        let quantum = match self.schedule {
+            Schedule::Fair => self.quantum.saturating_mul(entry.priority.max(1).into()),
+            Schedule::Priority => self.quantum,
+        };
+
+        let outcome = entry.machine.step_n_table(
+            entry.table.borrow(),
+            quantum.min(entry.budget - entry.steps),
+        );
+        entry.steps += outcome.steps;
+
+        match outcome.stop {
+            Some(stop) => Some(entry.complete(stop)),
+            None if entry.steps >= entry.budget => Some(entry.complete(StopReason::StepLimit)),
+            None => {
+                self.live.push_back(entry);
+                None
+            }
+        }
+    }
+
+    /// Takes turns until every machine finished, passing each to
+    /// `on_complete` as it does.
+    pub fn run(&mut self, mut on_complete: impl FnMut(Completion<S, Sym, T>)) {
+        while !self.is_empty() {
+            if let Some(completion) = self.turn() {
+                on_complete(completion);
+            }
+        }
+    }
+
+    /// Like [`MachinePool::run`], with the machines split between `threads`
+    /// threads, each scheduling its own share. `on_complete` is called on
+    /// the calling thread.
+    pub fn run_threads(
+        &mut self,
+        threads: usize,
+        mut on_complete: impl FnMut(Completion<S, Sym, T>),
+    ) where
+        S: Send,
+        Sym: Send,
+        T: Send,
+        P: Send,
+    {
+        let mut shares: Vec<_> = iter::repeat_with(|| Self {
+            live: VecDeque::new(),
+            ..*self
+        })
+        .take(threads.max(1))
+        .collect();
+
+        for (index, entry) in self.live.drain(..).enumerate() {
+            let index = index % shares.len();
+            shares[index].live.push_back(entry);
+        }
+
+        let (sender, receiver) = mpsc::channel();
+
+        thread::scope(|scope| {
+            for mut share in shares {
+                let sender = sender.clone();
+                scope.spawn(move || {
+                    share.run(|completion| {
+                        let _ = sender.send(completion);
+                    })
+                });
+            }
+
+            // the loop ends once every thread dropped its sender
+            drop(sender);
+            receiver.into_iter().for_each(&mut on_complete);
+        });
+    }
+}
+
+impl<S, Sym, P, T> Entry<S, Sym, P, T>
+where
+    Sym: Default,
+    T: Tape<Sym>,
+{
+    fn complete(self, stop: StopReason) -> Completion<S, Sym, T> {
+        Completion {
+            id: self.id,
+            stats: RunStats {
+                steps: self.steps,
+                tape_len: self.machine.tape.len(),
+                stop,
+            },
+            machine: self.machine,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsl;
+
+    fn tables() -> (TransitionTable<String, bool>, TransitionTable<String, bool>) {
+        (
+            dsl::parse("inc 1 0 R *\ninc 0 1 N halt").unwrap(),
+            dsl::parse("inc 0 0 R *").unwrap(),
+        )
+    }
+
+    fn machine(input: &str) -> Machine<String, bool> {
+        Machine::with_input("inc".to_owned(), input).unwrap()
+    }
+
+    #[test]
+    fn fair_test() {
+        let (inc, forever) = tables();
+        let mut pool = MachinePool::new(Schedule::Fair).with_quantum(2);
+
+        let looping = pool.add(&forever, machine("0"), 100);
+        let slow = pool.add(&inc, machine("1111111"), 100);
+        let fast = pool.add(&inc, machine("1"), 100);
+        assert!(pool.set_priority(slow, 3));
+
+        let mut completions = Vec::new();
+        pool.run(|completion| completions.push((completion.id, completion.stats)));
+
+        // the fast one takes 2 steps, the slow one 8 in one turn of 6 and one
+        // of 2, after the fast one's
+        let ids: Vec<_> = completions.iter().map(|&(id, _)| id).collect();
+        assert_eq!(ids, [fast, slow, looping]);
+        assert_eq!(completions[0].1.steps, 2);
+        assert_eq!(completions[1].1.steps, 8);
+        assert_eq!(completions[2].1.stop, StopReason::StepLimit);
+        assert_eq!(completions[2].1.steps, 100);
+        assert!(pool.is_empty());
+    }
+
+    #[test]
+    fn priority_test() {
+        let (_, forever) = tables();
+        let mut pool = MachinePool::new(Schedule::Priority).with_quantum(3);
+
+        let low = pool.add(&forever, machine("0"), 10);
+        let high = pool.add(&forever, machine("0"), 10);
+        let cancelled = pool.add(&forever, machine("0"), 10);
+        pool.set_priority(high, 2);
+        pool.set_priority(cancelled, 2);
+
+        assert!(pool.turn().is_none());
+        assert_eq!(pool.machine(high).unwrap().head(), 3);
+        assert_eq!(pool.machine(low).unwrap().head(), 0);
+
+        let completion = pool.cancel(cancelled).unwrap();
+        assert_eq!(completion.stats.stop, StopReason::Cancelled);
+        assert!(pool.machine(cancelled).is_none());
+
+        let mut ids = Vec::new();
+        pool.run(|completion| ids.push(completion.id));
+        assert_eq!(ids, [high, low]);
+    }
+
+    #[test]
+    fn run_threads_test() {
+        let (inc, _) = tables();
+        let mut pool = MachinePool::new(Schedule::Fair).with_quantum(1);
+        let inputs: Vec<String> = (0..20).map(|len| "1".repeat(len)).collect();
+
+        for input in &inputs {
+            pool.add(&inc, machine(input), 100);
+        }
+
+        let mut steps = vec![0; inputs.len()];
+        pool.run_threads(3, |completion| {
+            assert_eq!(completion.stats.stop, StopReason::Halted);
+            steps[completion.id] = completion.stats.steps;
+        });
+
+        assert!(pool.is_empty());
+        assert_eq!(steps, (0..20).map(|len| len + 1).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,251 @@
+//! Rules matched by predicates on the symbol read and writing a function of
+//! it, for machines over alphabets too large to list, like the integers.
+
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::fmt::{self, Debug};
+use core::sync::atomic::AtomicBool;
+
+use crate::run::CANCEL_CHECK_INTERVAL;
+use crate::{Machine, Move, Rule, RunStats, State, Tape};
+
+type Predicate<'a, Sym> = dyn Fn(&Sym) -> bool + 'a;
+type Map<'a, Sym> = dyn Fn(&Sym) -> Sym + 'a;
+// a state's rules with their predicates, in the order they are tried
+type Rules<'a, S, Sym> = Vec<(Box<Predicate<'a, Sym>>, PredicateRule<'a, S, Sym>)>;
+
+/// A [`Rule`] writing a symbol computed from the one read.
+pub struct PredicateRule<'a, S, Sym> {
+    pub new_state: Option<State<S>>,
+    pub write: Option<Box<Map<'a, Sym>>>,
+    pub head_move: Option<Move>,
+}
+
+impl<'a, S, Sym> PredicateRule<'a, S, Sym> {
+    /// A rule that leaves the state, tape and head untouched.
+    pub fn new() -> Self {
+        Self {
+            new_state: None,
+            write: None,
+            head_move: None,
+        }
+    }
+
+    pub fn to(state: S) -> Self {
+        Self {
+            new_state: Some(State::State(state)),
+            ..Self::new()
+        }
+    }
+
+    pub fn halt() -> Self {
+        Self {
+            new_state: Some(State::Halt),
+            ..Self::new()
+        }
+    }
+
+    /// Writes `map` of the symbol read.
+    pub fn map(self, map: impl Fn(&Sym) -> Sym + 'a) -> Self {
+        Self {
+            write: Some(Box::new(map)),
+            ..self
+        }
+    }
+
+    pub fn left(self) -> Self {
+        Self {
+            head_move: Some(Move::Left),
+            ..self
+        }
+    }
+
+    pub fn right(self) -> Self {
+        Self {
+            head_move: Some(Move::Right),
+            ..self
+        }
+    }
+
+    pub fn by(self, offset: isize) -> Self {
+        Self {
+            head_move: Some(Move::By(offset)),
+            ..self
+        }
+    }
+
+    // the plain rule this one applies reading `symbol`
+    fn resolve(&self, symbol: &Sym) -> Rule<S, Sym>
+    where
+        S: Clone,
+    {
+        Rule {
+            new_state: self.new_state.clone(),
+            write: self.write.as_ref().map(|map| map(symbol)),
+            head_move: self.head_move,
+        }
+    }
+}
+
+impl<S, Sym> Default for PredicateRule<'_, S, Sym> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, S, Sym> From<Rule<S, Sym>> for PredicateRule<'a, S, Sym>
+where
+    Sym: Clone + 'a,
+{
+    fn from(rule: Rule<S, Sym>) -> Self {
+        Self {
+            new_state: rule.new_state,
+            write: rule
+                .write
+                .map(|symbol| Box::new(move |_: &Sym| symbol.clone()) as Box<Map<'a, Sym>>),
+            head_move: rule.head_move,
+        }
+    }
+}
+
+impl<S: Debug, Sym> Debug for PredicateRule<'_, S, Sym> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PredicateRule")
+            .field("new_state", &self.new_state)
+            .field("write", &self.write.as_ref().map(|_| ".."))
+            .field("head_move", &self.head_move)
+            .finish()
+    }
+}
+
+/// The rules of each state, tried in the order they were inserted until one
+/// whose predicate holds for the symbol read.
+pub struct PredicateTable<'a, S, Sym> {
+    rules: BTreeMap<S, Rules<'a, S, Sym>>,
+}
+
+impl<'a, S, Sym> PredicateTable<'a, S, Sym>
+where
+    S: Ord,
+{
+    pub fn new() -> Self {
+        Self {
+            rules: BTreeMap::new(),
+        }
+    }
+
+    /// Adds a rule for reading a symbol matching `matches` in `state`, after
+    /// the ones already there.
+    pub fn insert(
+        &mut self,
+        state: S,
+        matches: impl Fn(&Sym) -> bool + 'a,
+        rule: impl Into<PredicateRule<'a, S, Sym>>,
+    ) {
+        self.rules
+            .entry(state)
+            .or_default()
+            .push((Box::new(matches), rule.into()));
+    }
+
+    /// The first rule of `state` matching `symbol`.
+    pub fn get(&self, state: &S, symbol: &Sym) -> Option<&PredicateRule<'a, S, Sym>> {
+        self.rules
+            .get(state)?
+            .iter()
+            .find(|(matches, _)| matches(symbol))
+            .map(|(_, rule)| rule)
+    }
+
+    pub fn len(&self) -> usize {
+        self.rules.values().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+}
+
+impl<S: Ord, Sym> Default for PredicateTable<'_, S, Sym> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: Debug, Sym> Debug for PredicateTable<'_, S, Sym> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(self.rules.iter().map(|(state, rules)| {
+                let rules: Vec<_> = rules.iter().map(|(_, rule)| rule).collect();
+                (state, rules)
+            }))
+            .finish()
+    }
+}
+
+impl<S, Sym, T> Machine<S, Sym, T>
+where
+    S: Ord + Clone,
+    Sym: Default + Clone,
+    T: Tape<Sym>,
+{
+    /// Steps the machine by the first rule matching the symbol under the
+    /// head; no matching rule halts it without touching the tape.
+    pub fn step_predicate(&mut self, table: &PredicateTable<'_, S, Sym>) {
+        let State::State(state) = &self.state else {
+            return;
+        };
+
+        let rule = match table.get(state, self.current_symbol()) {
+            Some(rule) => rule.resolve(self.current_symbol()),
+            None => Rule::halt(),
+        };
+
+        self.apply(rule);
+    }
+
+    /// Runs the machine by [`Machine::step_predicate`] until it halts or
+    /// `max_steps` steps were taken.
+    pub fn run_predicate(
+        &mut self,
+        table: &PredicateTable<'_, S, Sym>,
+        max_steps: u64,
+    ) -> RunStats {
+        self.run_loop(
+            max_steps,
+            &AtomicBool::new(false),
+            CANCEL_CHECK_INTERVAL,
+            |machine| {
+                machine.step_predicate(table);
+                true
+            },
+            |_, _| true,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_predicate_test() {
+        // a Collatz step on every number up to the first 0
+        let mut table: PredicateTable<u8, u64> = PredicateTable::new();
+        table.insert(0, |&n| n == 0, Rule::halt());
+        table.insert(
+            0,
+            |n| n.is_multiple_of(2),
+            PredicateRule::new().map(|n| n / 2).right(),
+        );
+        table.insert(0, |_| true, PredicateRule::new().map(|n| 3 * n + 1).right());
+
+        let mut machine: Machine<u8, u64> = Machine::new(0, [6, 7, 1 << 40, 0, 5].into());
+        let stats = machine.run_predicate(&table, 100);
+
+        assert_eq!(stats.steps, 4);
+        assert_eq!(machine.tape(), &[3, 22, 1 << 39, 0, 5]);
+        assert_eq!(table.len(), 3);
+    }
+}
@@ -0,0 +1,340 @@
+//! Probabilistic machines, whose rules are picked at random among weighted
+//! alternatives, and exact replays of their runs.
+//!
+//! Every run starts from a seed and records the index of the alternative
+//! picked at each step that had more than one, as a [`Recording`]. Its text
+//! form, `seed <seed> choices <index>...`, can be stored next to a trace and
+//! parsed back to replay the run with [`Machine::replay`].
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::convert::Infallible;
+use core::error::Error;
+use core::fmt::{self, Display};
+use core::str::FromStr;
+
+use crate::{Machine, Rule, RunStats, State, StopReason, Tape};
+
+/// Weighted alternative rules for each state and symbol.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProbabilisticTable<S, Sym> {
+    rules: BTreeMap<(S, Sym), Vec<Weighted<S, Sym>>>,
+}
+
+/// A rule and its weight.
+pub type Weighted<S, Sym> = (u32, Rule<S, Sym>);
+
+impl<S, Sym> ProbabilisticTable<S, Sym>
+where
+    S: Ord,
+    Sym: Ord,
+{
+    pub fn new() -> Self {
+        Self {
+            rules: BTreeMap::new(),
+        }
+    }
+
+    /// Adds an alternative for reading `symbol` in `state`, picked with
+    /// probability proportional to `weight`.
+    pub fn insert(&mut self, state: S, symbol: Sym, weight: u32, rule: Rule<S, Sym>) {
+        self.rules
+            .entry((state, symbol))
+            .or_default()
+            .push((weight, rule));
+    }
+
+    /// The weighted alternatives for reading `symbol` in `state`, in the
+    /// order they were inserted.
+    pub fn get(&self, state: &S, symbol: &Sym) -> Option<&[Weighted<S, Sym>]>
+    where
+        S: Clone,
+        Sym: Clone,
+    {
+        let rules = self.rules.get(&(state.clone(), symbol.clone()))?;
+        Some(rules)
+    }
+}
+
+impl<S, Sym> Default for ProbabilisticTable<S, Sym>
+where
+    S: Ord,
+    Sym: Ord,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The seed of a run and the choices it made, enough to replay it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recording {
+    pub seed: u64,
+    /// The index of the alternative picked at each step with more than one.
+    pub choices: Vec<usize>,
+}
+
+/// The result of [`Machine::run_probabilistic`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProbabilisticRun {
+    pub stats: RunStats,
+    pub recording: Recording,
+}
+
+// SplitMix64, which is plenty for picking rules and the same on every
+// platform
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    // an index into `rules` picked by weight
+    fn pick<R>(&mut self, rules: &[(u32, R)]) -> usize {
+        let total: u64 = rules.iter().map(|&(weight, _)| u64::from(weight)).sum();
+
+        if total == 0 {
+            return 0;
+        }
+
+        let mut target = self.next() % total;
+
+        for (index, &(weight, _)) in rules.iter().enumerate() {
+            match target.checked_sub(u64::from(weight)) {
+                Some(rest) => target = rest,
+                None => return index,
+            }
+        }
+
+        unreachable!("the target is below the total weight")
+    }
+}
+
+/// Returned by [`Machine::replay`] when the recording doesn't fit the table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayError {
+    /// The run needed a choice at `step` after all recorded ones were used.
+    OutOfChoices { step: u64 },
+    /// The choice at `step` names an alternative the table doesn't have.
+    NoAlternative { step: u64, choice: usize },
+}
+
+impl Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::OutOfChoices { step } => {
+                write!(f, "out of recorded choices at step {step}")
+            }
+            ReplayError::NoAlternative { step, choice } => {
+                write!(f, "no alternative {choice} at step {step}")
+            }
+        }
+    }
+}
+
+impl Error for ReplayError {}
+
+impl<S, Sym, T> Machine<S, Sym, T>
+where
+    Sym: Default,
+    T: Tape<Sym>,
+{
+    /// Runs the machine until it halts or `max_steps` steps were taken,
+    /// picking among the alternatives at random from `seed`. A missing rule
+    /// halts the machine.
+    pub fn run_probabilistic(
+        &mut self,
+        table: &ProbabilisticTable<S, Sym>,
+        seed: u64,
+        max_steps: u64,
+    ) -> ProbabilisticRun
+    where
+        S: Ord + Clone,
+        Sym: Ord + Clone,
+    {
+        let mut rng = Rng(seed);
+        let mut choices = Vec::new();
+
+        let stats = self.run_choosing(table, max_steps, |_, rules| {
+            let choice = rng.pick(rules);
+            choices.push(choice);
+            Ok::<_, Infallible>(choice)
+        });
+        let Ok(stats) = stats;
+
+        ProbabilisticRun {
+            stats,
+            recording: Recording { seed, choices },
+        }
+    }
+
+    /// Runs the machine making the choices of `recording` instead of random
+    /// ones, repeating the recorded run if the machine starts from the same
+    /// configuration.
+    pub fn replay(
+        &mut self,
+        table: &ProbabilisticTable<S, Sym>,
+        recording: &Recording,
+        max_steps: u64,
+    ) -> Result<RunStats, ReplayError>
+    where
+        S: Ord + Clone,
+        Sym: Ord + Clone,
+    {
+        let mut choices = recording.choices.iter();
+
+        self.run_choosing(table, max_steps, |step, rules| {
+            let &choice = choices.next().ok_or(ReplayError::OutOfChoices { step })?;
+
+            match choice < rules.len() {
+                true => Ok(choice),
+                false => Err(ReplayError::NoAlternative { step, choice }),
+            }
+        })
+    }
+
+    // the run loop for both, asking `choose` for an index at each step with
+    // more than one alternative
+    fn run_choosing<E>(
+        &mut self,
+        table: &ProbabilisticTable<S, Sym>,
+        max_steps: u64,
+        mut choose: impl FnMut(u64, &[Weighted<S, Sym>]) -> Result<usize, E>,
+    ) -> Result<RunStats, E>
+    where
+        S: Ord + Clone,
+        Sym: Ord + Clone,
+    {
+        let mut steps = 0;
+
+        while steps < max_steps {
+            let State::State(state) = &self.state else {
+                break;
+            };
+
+            let rule = match table.get(state, self.current_symbol()) {
+                None | Some([]) => Rule::halt(),
+                Some([(_, rule)]) => rule.clone(),
+                Some(rules) => rules[choose(steps, rules)?].1.clone(),
+            };
+
+            self.apply(rule);
+            steps += 1;
+        }
+
+        Ok(RunStats {
+            steps,
+            tape_len: self.tape.len(),
+            stop: match self.halted() {
+                true => StopReason::Halted,
+                false => StopReason::StepLimit,
+            },
+        })
+    }
+}
+
+impl Display for Recording {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "seed {} choices", self.seed)?;
+
+        for choice in &self.choices {
+            write!(f, " {choice}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Returned when parsing a [`Recording`] fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseRecordingError;
+
+impl Display for ParseRecordingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "expected `seed <seed> choices <index>...`")
+    }
+}
+
+impl Error for ParseRecordingError {}
+
+impl FromStr for Recording {
+    type Err = ParseRecordingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut words = s.split_whitespace();
+
+        let (Some("seed"), Some(seed), Some("choices")) =
+            (words.next(), words.next(), words.next())
+        else {
+            return Err(ParseRecordingError);
+        };
+
+        Ok(Recording {
+            seed: seed.parse().map_err(|_| ParseRecordingError)?,
+            choices: words
+                .map(str::parse)
+                .collect::<Result<_, _>>()
+                .map_err(|_| ParseRecordingError)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::*;
+
+    // writes a random bit and moves right, halting on a one with weight 1
+    // in 4
+    fn coin() -> ProbabilisticTable<u8, u8> {
+        let mut table = ProbabilisticTable::new();
+        table.insert(0, 0, 3, Rule::new().write(2).right());
+        table.insert(0, 0, 1, Rule::halt().write(1));
+        table
+    }
+
+    #[test]
+    fn replay_test() {
+        let table = coin();
+        let mut machine: Machine<u8, u8> = Machine::new(0, [0].into()).resettable();
+        let run = machine.run_probabilistic(&table, 7, 1_000);
+        let tape = machine.tape_to_vec();
+
+        assert_eq!(run.stats.stop, StopReason::Halted);
+        assert_eq!(run.recording.choices.len() as u64, run.stats.steps);
+        assert_eq!(run.recording.choices.last(), Some(&1));
+
+        // the same seed and the recording both repeat the run
+        machine.reset();
+        assert_eq!(machine.run_probabilistic(&table, 7, 1_000), run);
+
+        let recording: Recording = run.recording.to_string().parse().unwrap();
+        machine.reset();
+
+        assert_eq!(recording, run.recording);
+        assert_eq!(machine.replay(&table, &recording, 1_000), Ok(run.stats));
+        assert_eq!(machine.tape_to_vec(), tape);
+
+        machine.reset();
+        let short = Recording {
+            seed: 7,
+            choices: Vec::from([0, 0]),
+        };
+
+        assert_eq!(
+            machine.replay(&table, &short, 1_000),
+            Err(ReplayError::OutOfChoices { step: 2 })
+        );
+        assert_eq!(
+            "seed x choices".parse::<Recording>(),
+            Err(ParseRecordingError)
+        );
+    }
+}
@@ -0,0 +1,703 @@
+//! Certificates that a run halts or never does, as scripts for the Lean 4
+//! and Coq proof assistants, so that a result can be checked without
+//! trusting the crate.
+//!
+//! A script defines the table, a step function over configurations and the
+//! run from the starting configuration, then proves the [`Claim`]: that the
+//! run halts after exactly its steps, or that it comes back to the
+//! configuration it was in `period` steps before, so runs forever. The runs
+//! themselves are replayed by the proof assistant's evaluator (`decide` and
+//! `vm_compute`), which for long runs may need raised limits or Lean's
+//! `native_decide`.
+//!
+//! Tapes are lists of the cells on either side of the head, nearest first,
+//! without blanks at their far ends, so that equal tapes are equal lists.
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{Display, Write};
+
+use crate::{Machine, State, Symbol, TransitionTable};
+
+/// What a [`Certificate`] proves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Claim {
+    /// The run halts after `steps` steps, and not before.
+    Halts { steps: u64 },
+    /// The configurations after `start` and `start + period` steps are the
+    /// same, and the run doesn't halt before the second.
+    Cycles { start: u64, period: u64 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Assistant {
+    Lean,
+    Coq,
+}
+
+/// A [`Claim`] about the run of a table from a configuration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Certificate<S, Sym> {
+    table: TransitionTable<S, Sym>,
+    start: Config<S, Sym>,
+    blank: Sym,
+    claim: Claim,
+}
+
+// a configuration as the scripts have it
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct Config<S, Sym> {
+    state: Option<S>,
+    left: Vec<Sym>,
+    head: Sym,
+    right: Vec<Sym>,
+}
+
+impl<S, Sym> Config<S, Sym>
+where
+    S: Clone,
+    Sym: Default + Clone + PartialEq,
+{
+    fn of(machine: &Machine<S, Sym>) -> Self {
+        let blank = machine.blank();
+        let tape = machine.tape();
+        let head = machine.head();
+        let trimmed = |cells: Vec<Sym>| {
+            let len = cells
+                .iter()
+                .rposition(|cell| cell != blank)
+                .map_or(0, |i| i + 1);
+            cells[..len].to_vec()
+        };
+
+        Self {
+            state: machine.state().cloned(),
+            left: trimmed(tape.range(..head).rev().cloned().collect()),
+            head: tape[head].clone(),
+            right: trimmed(tape.range(head + 1..).cloned().collect()),
+        }
+    }
+}
+
+impl<S, Sym> Certificate<S, Sym>
+where
+    S: Ord + Clone,
+    Sym: Ord + Default + Clone,
+{
+    /// A certificate that `machine` halts under `table` within `max_steps`
+    /// steps, or `None` if it doesn't.
+    pub fn halting(
+        table: &TransitionTable<S, Sym>,
+        machine: &Machine<S, Sym>,
+        max_steps: u64,
+    ) -> Option<Self> {
+        let mut run = machine.clone();
+        let mut steps = 0;
+
+        while steps < max_steps && run.step_table(table) {
+            steps += 1;
+        }
+
+        run.halted()
+            .then(|| Self::new(table, machine, Claim::Halts { steps }))
+    }
+
+    /// A certificate that `machine` halts under `table` or repeats a
+    /// configuration within `max_steps` steps, or `None` if it does neither.
+    /// Keeps every configuration seen, like
+    /// [`RunOptions::detect_loops`](crate::RunOptions::detect_loops).
+    pub fn find(
+        table: &TransitionTable<S, Sym>,
+        machine: &Machine<S, Sym>,
+        max_steps: u64,
+    ) -> Option<Self> {
+        let mut run = machine.clone();
+        let mut seen = BTreeMap::from([(Config::of(&run), 0)]);
+
+        for step in 1..=max_steps {
+            if !run.step_table(table) {
+                break;
+            }
+
+            if run.halted() {
+                return Some(Self::new(table, machine, Claim::Halts { steps: step }));
+            }
+
+            if let Some(&start) = seen.get(&Config::of(&run)) {
+                let period = step - start;
+                return Some(Self::new(table, machine, Claim::Cycles { start, period }));
+            }
+
+            seen.insert(Config::of(&run), step);
+        }
+
+        None
+    }
+
+    fn new(table: &TransitionTable<S, Sym>, machine: &Machine<S, Sym>, claim: Claim) -> Self {
+        Self {
+            table: table.clone(),
+            start: Config::of(machine),
+            blank: machine.blank().clone(),
+            claim,
+        }
+    }
+
+    pub fn claim(&self) -> Claim {
+        self.claim
+    }
+
+    /// The script proving the claim in `assistant`.
+    pub fn script(&self, assistant: Assistant) -> String
+    where
+        S: Display,
+        Sym: Symbol,
+    {
+        let model = Model::new(self);
+
+        match assistant {
+            Assistant::Lean => model.lean(self.claim),
+            Assistant::Coq => model.coq(self.claim),
+        }
+    }
+}
+
+// the table and starting configuration with states and symbols numbered,
+// the start state and the blank first
+struct Model {
+    states: Vec<String>,
+    symbols: Vec<char>,
+    // by state then symbol
+    delta: Vec<Vec<Option<Transition>>>,
+    start: (Option<usize>, Vec<usize>, usize, Vec<usize>),
+}
+
+// the new state if not halting, the symbol written, whether the head moves
+// right and by how many cells
+type Transition = (Option<usize>, usize, bool, usize);
+
+impl Model {
+    fn new<S, Sym>(certificate: &Certificate<S, Sym>) -> Self
+    where
+        S: Ord + Clone + Display,
+        Sym: Ord + Default + Clone + Symbol,
+    {
+        let Certificate {
+            table,
+            start,
+            blank,
+            ..
+        } = certificate;
+
+        let mut states = BTreeSet::new();
+        let mut symbols = BTreeSet::from([&start.head]);
+        symbols.extend(start.left.iter().chain(&start.right));
+        states.extend(&start.state);
+
+        for (state, read, rule) in table.iter() {
+            states.insert(state);
+            symbols.insert(read);
+            symbols.extend(&rule.write);
+
+            if let Some(State::State(next)) = &rule.new_state {
+                states.insert(next);
+            }
+        }
+
+        symbols.remove(blank);
+
+        let states: Vec<&S> = start
+            .state
+            .iter()
+            .chain(
+                states
+                    .into_iter()
+                    .filter(|&state| Some(state) != start.state.as_ref()),
+            )
+            .collect();
+        let symbols: Vec<&Sym> = [blank].into_iter().chain(symbols).collect();
+        let state_index = |state: &S| states.iter().position(|&other| other == state);
+        let symbol_index = |symbol: &Sym| symbols.iter().position(|&other| other == symbol);
+
+        let delta = states
+            .iter()
+            .map(|&state| {
+                symbols
+                    .iter()
+                    .map(|&read| {
+                        let rule = table.lookup(state, read)?;
+                        let next = match &rule.new_state {
+                            None => state_index(state),
+                            Some(State::State(next)) => state_index(next),
+                            Some(State::Halt) => None,
+                        };
+                        let written = symbol_index(rule.write.as_ref().unwrap_or(read))?;
+                        let offset = rule.head_move.map_or(0, |head_move| head_move.offset());
+
+                        Some((next, written, offset > 0, offset.unsigned_abs()))
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let cells = |cells: &[Sym]| cells.iter().filter_map(symbol_index).collect();
+
+        Self {
+            start: (
+                start.state.as_ref().and_then(state_index),
+                cells(&start.left),
+                symbol_index(&start.head).unwrap_or(0),
+                cells(&start.right),
+            ),
+            states: states.iter().map(ToString::to_string).collect(),
+            symbols: symbols.iter().map(|symbol| symbol.to_char()).collect(),
+            delta,
+        }
+    }
+
+    // `q0 = A, q1 = B`, `s0 = _, s1 = 1`
+    fn names(&self) -> (String, String) {
+        let states = self.states.iter().enumerate();
+        let symbols = self.symbols.iter().enumerate();
+
+        (
+            join(
+                states.map(|(i, state)| alloc::format!("q{i} = {state}")),
+                ", ",
+            ),
+            join(
+                symbols.map(|(i, symbol)| alloc::format!("s{i} = {symbol}")),
+                ", ",
+            ),
+        )
+    }
+
+    fn lean(&self, claim: Claim) -> String {
+        let mut out = String::new();
+        let (states, symbols) = self.names();
+        let list = |cells: &[usize]| {
+            let cells = cells.iter().map(|cell| alloc::format!(".s{cell}"));
+            alloc::format!("[{}]", join(cells, ", "))
+        };
+        let state = |state: Option<usize>| match state {
+            Some(state) => alloc::format!("some .q{state}"),
+            None => "none".to_string(),
+        };
+
+        let _ = writeln!(out, "-- {states}; {symbols}, the blank\n");
+        let _ = writeln!(out, "inductive St where");
+        let _ = writeln!(out, "  | {}", self.indexed('q', self.states.len(), " | "));
+        let _ = writeln!(out, "  deriving DecidableEq\n");
+        let _ = writeln!(out, "inductive Sym where");
+        let _ = writeln!(out, "  | {}", self.indexed('s', self.symbols.len(), " | "));
+        let _ = writeln!(out, "  deriving DecidableEq\n");
+        let _ = writeln!(out, "{LEAN_CONFIG}");
+
+        let _ = writeln!(out, "def isBlank : Sym → Bool");
+        for symbol in 0..self.symbols.len() {
+            let _ = writeln!(out, "  | .s{symbol} => {}", symbol == 0);
+        }
+
+        let _ = writeln!(
+            out,
+            "\ndef delta : St → Sym → Option (Option St × Sym × Bool × Nat)"
+        );
+        for (q, row) in self.delta.iter().enumerate() {
+            for (s, rule) in row.iter().enumerate() {
+                let rule = match rule {
+                    Some((next, written, right, cells)) => {
+                        alloc::format!("some ({}, .s{written}, {right}, {cells})", state(*next))
+                    }
+                    None => "none".to_string(),
+                };
+                let _ = writeln!(out, "  | .q{q}, .s{s} => {rule}");
+            }
+        }
+
+        let (start_state, left, head, right) = &self.start;
+        let _ = writeln!(out, "\n{LEAN_STEP}");
+        let _ = writeln!(
+            out,
+            "def start : Config :=\n  {{ state := {}, left := {}, head := .s{head}, right := {} }}\n",
+            state(*start_state),
+            list(left),
+            list(right)
+        );
+        let _ = writeln!(out, "{LEAN_RUN}");
+
+        let _ = match claim {
+            Claim::Halts { steps } => writeln!(
+                out,
+                "theorem halts : (run {steps}).running = false := by decide\n\n\
+                 theorem running_before : ∀ k, k < {steps} → (run k).running = true :=\n  \
+                 allRunning_spec {steps} (by decide)"
+            ),
+            Claim::Cycles { start, period } => {
+                writeln!(out, "{}", cycle(LEAN_CYCLE, start, period))
+            }
+        };
+
+        out
+    }
+
+    fn coq(&self, claim: Claim) -> String {
+        let mut out = String::new();
+        let (states, symbols) = self.names();
+        let list = |cells: &[usize]| {
+            let cells = cells.iter().map(|cell| alloc::format!("s{cell}"));
+            alloc::format!("[{}]", join(cells, "; "))
+        };
+        let state = |state: Option<usize>| match state {
+            Some(state) => alloc::format!("Some q{state}"),
+            None => "None".to_string(),
+        };
+
+        let _ = writeln!(out, "(* {states}; {symbols}, the blank *)\n");
+        let _ = writeln!(out, "Require Import List Lia.\nImport ListNotations.\n");
+        let _ = writeln!(
+            out,
+            "Inductive St := {}.",
+            self.indexed('q', self.states.len(), " | ")
+        );
+        let _ = writeln!(
+            out,
+            "Inductive Sym := {}.\n",
+            self.indexed('s', self.symbols.len(), " | ")
+        );
+        let _ = writeln!(out, "{COQ_CONFIG}");
+
+        let _ = writeln!(
+            out,
+            "Definition is_blank (a : Sym) : bool :=\n  match a with"
+        );
+        for symbol in 0..self.symbols.len() {
+            let _ = writeln!(out, "  | s{symbol} => {}", symbol == 0);
+        }
+        let _ = writeln!(out, "  end.\n");
+
+        let _ = writeln!(
+            out,
+            "Definition delta (q : St) (a : Sym) : option (option St * Sym * bool * nat) :=\n  \
+             match q, a with"
+        );
+        for (q, row) in self.delta.iter().enumerate() {
+            for (s, rule) in row.iter().enumerate() {
+                let rule = match rule {
+                    Some((next, written, right, cells)) => {
+                        alloc::format!("Some ({}, s{written}, {right}, {cells})", state(*next))
+                    }
+                    None => "None".to_string(),
+                };
+                let _ = writeln!(out, "  | q{q}, s{s} => {rule}");
+            }
+        }
+        let _ = writeln!(out, "  end.");
+
+        let (start_state, left, head, right) = &self.start;
+        let _ = writeln!(out, "\n{COQ_STEP}");
+        let _ = writeln!(
+            out,
+            "Definition start : Config := mkConfig ({}) {} s{head} {}.\n",
+            state(*start_state),
+            list(left),
+            list(right)
+        );
+        let _ = writeln!(out, "{COQ_RUN}");
+
+        let _ = match claim {
+            Claim::Halts { steps } => writeln!(
+                out,
+                "Lemma halts : running (run {steps}) = false.\n\
+                 Proof. vm_compute. reflexivity. Qed.\n\n\
+                 Lemma running_before : forall k, k < {steps} -> running (run k) = true.\n\
+                 Proof. apply all_running_spec. vm_compute. reflexivity. Qed."
+            ),
+            Claim::Cycles { start, period } => writeln!(out, "{}", cycle(COQ_CYCLE, start, period)),
+        };
+
+        out
+    }
+
+    // `q0 | q1 | q2`
+    fn indexed(&self, prefix: char, count: usize, separator: &str) -> String {
+        join((0..count).map(|i| alloc::format!("{prefix}{i}")), separator)
+    }
+}
+
+fn join(items: impl Iterator<Item = String>, separator: &str) -> String {
+    items.collect::<Vec<_>>().join(separator)
+}
+
+// the cycle proof with its start and period filled in
+fn cycle(template: &str, start: u64, period: u64) -> String {
+    template
+        .replace("START", &start.to_string())
+        .replace("PERIOD", &period.to_string())
+}
+
+const LEAN_CONFIG: &str = "\
+/-- The state, `none` once halted, and the cells left and right of the head,
+nearest first and never ending in a blank. -/
+structure Config where
+  state : Option St
+  left : List Sym
+  head : Sym
+  right : List Sym
+  deriving DecidableEq
+";
+
+const LEAN_STEP: &str = "\
+def push (b : Sym) : List Sym → List Sym
+  | [] => if isBlank b then [] else [b]
+  | l => b :: l
+
+def moveLeft (c : Config) : Config :=
+  match c.left with
+  | [] => { c with head := .s0, right := push c.head c.right }
+  | x :: l => { c with left := l, head := x, right := push c.head c.right }
+
+def moveRight (c : Config) : Config :=
+  match c.right with
+  | [] => { c with head := .s0, left := push c.head c.left }
+  | x :: r => { c with right := r, head := x, left := push c.head c.left }
+
+def moves (right : Bool) : Nat → Config → Config
+  | 0, c => c
+  | n + 1, c => moves right n (if right then moveRight c else moveLeft c)
+
+def step (c : Config) : Config :=
+  match c.state with
+  | none => c
+  | some q =>
+    match delta q c.head with
+    | none => { c with state := none }
+    | some (q', w, right, n) => moves right n { c with state := q', head := w }
+
+def Config.running (c : Config) : Bool := c.state.isSome
+";
+
+const LEAN_RUN: &str = "\
+def run : Nat → Config
+  | 0 => start
+  | n + 1 => step (run n)
+
+def allRunning : Nat → Bool
+  | 0 => true
+  | n + 1 => allRunning n && (run n).running
+
+theorem allRunning_spec : ∀ n, allRunning n = true → ∀ k, k < n → (run k).running = true := by
+  intro n
+  induction n with
+  | zero => intro _ k hk; exact absurd hk (Nat.not_lt_zero k)
+  | succ n ih =>
+    intro h k hk
+    simp only [allRunning, Bool.and_eq_true] at h
+    by_cases hkn : k < n
+    · exact ih h.1 k hkn
+    · rw [show k = n by omega]
+      exact h.2
+";
+
+const LEAN_CYCLE: &str = "\
+theorem cycle : run (START + PERIOD) = run START := by decide
+
+theorem period : ∀ m, ∃ r, r < PERIOD ∧ run (START + m) = run (START + r) := by
+  intro m
+  induction m with
+  | zero => exact ⟨0, by decide, rfl⟩
+  | succ m ih =>
+    obtain ⟨r, hr, h⟩ := ih
+    have hs : run (START + (m + 1)) = step (run (START + r)) := congrArg step h
+    by_cases hrp : r + 1 < PERIOD
+    · exact ⟨r + 1, hrp, hs⟩
+    · refine ⟨0, by decide, ?_⟩
+      have hp : START + r + 1 = START + PERIOD := by omega
+      rw [hs]
+      show run (START + r + 1) = run START
+      rw [hp]
+      exact cycle
+
+theorem never_halts (n : Nat) : (run n).running = true := by
+  by_cases hn : n < START + PERIOD
+  · exact allRunning_spec (START + PERIOD) (by decide) n hn
+  · obtain ⟨r, hr, h⟩ := period (n - START)
+    have hn' : START + (n - START) = n := by omega
+    rw [← hn', h]
+    exact allRunning_spec (START + PERIOD) (by decide) (START + r) (by omega)";
+
+const COQ_CONFIG: &str = "\
+(* The state, None once halted, and the cells left and right of the head,
+   nearest first and never ending in a blank. *)
+Record Config := mkConfig {
+  cstate : option St;
+  cleft : list Sym;
+  chead : Sym;
+  cright : list Sym
+}.
+";
+
+const COQ_STEP: &str = "\
+Definition push (b : Sym) (l : list Sym) : list Sym :=
+  match l with
+  | [] => if is_blank b then [] else [b]
+  | _ => b :: l
+  end.
+
+Definition move_left (c : Config) : Config :=
+  match cleft c with
+  | [] => mkConfig (cstate c) [] s0 (push (chead c) (cright c))
+  | x :: l => mkConfig (cstate c) l x (push (chead c) (cright c))
+  end.
+
+Definition move_right (c : Config) : Config :=
+  match cright c with
+  | [] => mkConfig (cstate c) (push (chead c) (cleft c)) s0 []
+  | x :: r => mkConfig (cstate c) (push (chead c) (cleft c)) x r
+  end.
+
+Fixpoint moves (rightward : bool) (n : nat) (c : Config) : Config :=
+  match n with
+  | 0 => c
+  | S n => moves rightward n (if rightward then move_right c else move_left c)
+  end.
+
+Definition step (c : Config) : Config :=
+  match cstate c with
+  | None => c
+  | Some q =>
+    match delta q (chead c) with
+    | None => mkConfig None (cleft c) (chead c) (cright c)
+    | Some (q', w, rightward, n) => moves rightward n (mkConfig q' (cleft c) w (cright c))
+    end
+  end.
+
+Definition running (c : Config) : bool :=
+  match cstate c with
+  | Some _ => true
+  | None => false
+  end.
+";
+
+const COQ_RUN: &str = "\
+Fixpoint run (n : nat) : Config :=
+  match n with
+  | 0 => start
+  | S n => step (run n)
+  end.
+
+Fixpoint all_running (n : nat) : bool :=
+  match n with
+  | 0 => true
+  | S n => all_running n && running (run n)
+  end.
+
+Lemma all_running_spec :
+  forall n, all_running n = true -> forall k, k < n -> running (run k) = true.
+Proof.
+  induction n as [|n IH]; intros H k Hk.
+  - exfalso. lia.
+  - simpl in H. apply andb_prop in H. destruct H as [H1 H2].
+    destruct (Nat.eq_dec k n) as [-> | Hne].
+    + exact H2.
+    + apply IH; [exact H1 | lia].
+Qed.
+";
+
+const COQ_CYCLE: &str = "\
+Lemma cycle : run (START + PERIOD) = run START.
+Proof. vm_compute. reflexivity. Qed.
+
+Lemma period : forall m, exists r, r < PERIOD /\\ run (START + m) = run (START + r).
+Proof.
+  induction m as [|m [r [Hr H]]].
+  - exists 0. split; [lia | reflexivity].
+  - assert (Hs : run (START + S m) = step (run (START + r))).
+    { rewrite <- H. rewrite <- plus_n_Sm. reflexivity. }
+    destruct (Nat.lt_ge_cases (S r) PERIOD) as [Hlt | Hge].
+    + exists (S r). split; [exact Hlt |].
+      rewrite Hs, <- plus_n_Sm. reflexivity.
+    + exists 0. split; [lia |].
+      rewrite Hs, Nat.add_0_r, <- cycle.
+      replace (START + PERIOD) with (S (START + r)) by lia.
+      reflexivity.
+Qed.
+
+Theorem never_halts : forall n, running (run n) = true.
+Proof.
+  intro n.
+  destruct (Nat.lt_ge_cases n (START + PERIOD)) as [Hn | Hn].
+  - apply (all_running_spec (START + PERIOD)); [vm_compute; reflexivity | exact Hn].
+  - destruct (period (n - START)) as [r [Hr H]].
+    replace n with (START + (n - START)) by lia.
+    rewrite H.
+    apply (all_running_spec (START + PERIOD)); [vm_compute; reflexivity | lia].
+Qed.";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsl::Definition;
+    use crate::format::bbchallenge;
+    use crate::{Char, Rule};
+
+    #[test]
+    fn halting_test() {
+        let definition: Definition<Char> = bbchallenge::parse("1RB1LB_1LA1RZ").unwrap();
+        let machine: Machine<String, Char> =
+            Machine::new(definition.start.clone(), [Char::default()].into());
+        let certificate = Certificate::halting(&definition.table, &machine, 100).unwrap();
+
+        assert_eq!(certificate.claim(), Claim::Halts { steps: 6 });
+        assert_eq!(
+            Certificate::find(&definition.table, &machine, 100),
+            Some(certificate.clone())
+        );
+
+        let lean = certificate.script(Assistant::Lean);
+        assert!(lean.contains("  | .q0, .s0 => some (some .q1, .s1, true, 1)\n"));
+        assert!(lean.contains("  | .q1, .s1 => some (none, .s1, true, 1)\n"));
+        assert!(lean.contains("theorem halts : (run 6).running = false := by decide"));
+
+        let coq = certificate.script(Assistant::Coq);
+        assert!(coq.contains("Inductive St := q0 | q1.\n"));
+        assert!(coq.contains("Definition start : Config := mkConfig (Some q0) [] s0 [].\n"));
+    }
+
+    #[test]
+    fn cycle_test() {
+        // sweeps back and forth over `1_1` forever, after writing it
+        let table: TransitionTable<char, Char> = [
+            ('A', Char('_'), Rule::to('B').write(Char('1')).right()),
+            ('B', Char('_'), Rule::to('C').right()),
+            ('C', Char('_'), Rule::to('D').write(Char('1')).left()),
+            ('D', Char('_'), Rule::to('E').left()),
+            ('E', Char('1'), Rule::to('D').right()),
+            ('D', Char('1'), Rule::to('E').right()),
+        ]
+        .into_iter()
+        .collect();
+        let machine: Machine<char, Char> = Machine::with_input('A', "").unwrap();
+        let certificate = Certificate::find(&table, &machine, 100).unwrap();
+
+        let Claim::Cycles { start, period } = certificate.claim() else {
+            panic!("expected a cycle, got {:?}", certificate.claim());
+        };
+
+        // the configuration after `start` steps comes back
+        let mut first = machine.clone();
+        first.run_table(&table, start);
+        let mut again = first.clone();
+        again.run_table(&table, period);
+
+        assert_eq!(Config::of(&first), Config::of(&again));
+        assert!(period > 0);
+        assert!(Certificate::halting(&table, &machine, 100).is_none());
+        assert!(certificate
+            .script(Assistant::Lean)
+            .contains(&alloc::format!(
+                "theorem cycle : run ({start} + {period}) = run {start}"
+            )));
+        assert!(certificate.script(Assistant::Coq).contains("[Hlt | Hge]"));
+    }
+}
@@ -0,0 +1,181 @@
+//! Python bindings, built as an extension module with e.g.
+//! `maturin develop --features python,pyo3/extension-module`.
+//!
+//! States are strings and symbols are one-character strings, with `_` as the
+//! blank. Moves are written as `"L"`, `"R"` or `"N"` (no move), or `"L"` or
+//! `"R"` followed by a distance such as `"R3"`.
+
+use std::collections::VecDeque;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use crate::{parse_move, Char, Machine, Move, Rule, State, TransitionTable};
+
+fn cell(symbol: &str) -> PyResult<Char> {
+    let mut chars = symbol.chars();
+
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Ok(Char(c)),
+        _ => Err(PyValueError::new_err(format!(
+            "symbols must be single characters, got {symbol:?}"
+        ))),
+    }
+}
+
+fn head_move(head_move: &str) -> PyResult<Option<Move>> {
+    parse_move(head_move).ok_or_else(|| {
+        PyValueError::new_err(format!(
+            "moves must be \"L\", \"R\" or \"N\", or \"L\" or \"R\" with a distance, got {head_move:?}"
+        ))
+    })
+}
+
+#[pyclass(name = "TransitionTable")]
+#[derive(Default)]
+struct PyTransitionTable {
+    table: TransitionTable<String, Char>,
+}
+
+#[pymethods]
+impl PyTransitionTable {
+    #[new]
+    fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the rule for reading `read` in `state`. A `next` of `None` halts
+    /// the machine; a `write` of `None` leaves the cell untouched.
+    #[pyo3(signature = (state, read, write, head_move, next))]
+    fn add_rule(
+        &mut self,
+        state: String,
+        read: &str,
+        write: Option<&str>,
+        head_move: &str,
+        next: Option<String>,
+    ) -> PyResult<()> {
+        let rule = Rule {
+            new_state: Some(next.map_or(State::Halt, State::State)),
+            write: write.map(cell).transpose()?,
+            head_move: self::head_move(head_move)?,
+        };
+
+        self.table.insert(state, cell(read)?, rule);
+
+        Ok(())
+    }
+
+    fn __len__(&self) -> usize {
+        self.table.len()
+    }
+}
+
+#[pyclass(name = "Machine")]
+struct PyMachine {
+    machine: Machine<String, Char>,
+    steps: u64,
+}
+
+#[pymethods]
+impl PyMachine {
+    #[new]
+    fn new(state: String, tape: &str) -> Self {
+        let mut tape: VecDeque<Char> = tape.chars().map(Char).collect();
+
+        if tape.is_empty() {
+            tape.push_back(Char::default());
+        }
+
+        Self {
+            machine: Machine::new(state, tape),
+            steps: 0,
+        }
+    }
+
+    /// Performs a single step; returns `False` if the machine had already
+    /// halted.
+    fn step(&mut self, table: &PyTransitionTable) -> bool {
+        if self.machine.halted() {
+            return false;
+        }
+
+        self.machine.execute_table(&table.table);
+        self.steps += 1;
+
+        true
+    }
+
+    /// Steps until halting or until `limit` steps were taken; returns the
+    /// number of steps taken.
+    fn run(&mut self, table: &PyTransitionTable, limit: u64) -> u64 {
+        let mut taken = 0;
+
+        while taken < limit && self.step(table) {
+            taken += 1;
+        }
+
+        taken
+    }
+
+    /// Like `run`, but returns a `(step, state, head, read)` tuple for every
+    /// step taken.
+    fn run_traced(
+        &mut self,
+        table: &PyTransitionTable,
+        limit: u64,
+    ) -> Vec<(u64, String, usize, String)> {
+        let mut trace = Vec::new();
+
+        while (trace.len() as u64) < limit {
+            let Some(state) = self.machine.state().cloned() else {
+                break;
+            };
+            let entry = (
+                self.steps,
+                state,
+                self.machine.head(),
+                self.machine.current_symbol().0.to_string(),
+            );
+
+            self.step(table);
+            trace.push(entry);
+        }
+
+        trace
+    }
+
+    #[getter]
+    fn halted(&self) -> bool {
+        self.machine.halted()
+    }
+
+    /// The current state, or `None` once halted.
+    #[getter]
+    fn state(&self) -> Option<String> {
+        self.machine.state().cloned()
+    }
+
+    #[getter]
+    fn head(&self) -> usize {
+        self.machine.head()
+    }
+
+    #[getter]
+    fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    #[getter]
+    fn tape(&self) -> String {
+        self.machine.peek().iter().map(|cell| cell.0).collect()
+    }
+}
+
+#[pymodule]
+fn turing(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyTransitionTable>()?;
+    module.add_class::<PyMachine>()?;
+
+    Ok(())
+}
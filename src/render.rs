@@ -0,0 +1,583 @@
+//! Text and image renderings of configurations.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Write};
+
+use crate::{json_string, Machine, Snapshot, State, Symbol, Tape, TransitionTable};
+
+const RESET: &str = "\x1b[0m";
+const HEAD: &str = "\x1b[7m";
+const BLANK: &str = "\x1b[2m";
+
+/// Renders `width` cells of the tape centred on the head as a single line,
+/// using ANSI escapes to highlight the head and dim blank cells. Cells past
+/// either end of the tape are drawn as blanks.
+pub fn ansi<S, Sym>(snapshot: &Snapshot<S, Sym>, width: usize) -> String
+where
+    Sym: Symbol + Default + PartialEq,
+{
+    let blank = Sym::blank();
+    let first = snapshot.head as isize - (width / 2) as isize;
+    let mut out = String::new();
+
+    for position in first..first + width as isize {
+        let symbol = usize::try_from(position)
+            .ok()
+            .and_then(|index| snapshot.tape.get(index))
+            .unwrap_or(&blank);
+
+        let style = match (position == snapshot.head as isize, *symbol == blank) {
+            (true, _) => HEAD,
+            (false, true) => BLANK,
+            (false, false) => "",
+        };
+
+        write!(out, "{style}{}", symbol.to_char()).unwrap();
+
+        if !style.is_empty() {
+            out.push_str(RESET);
+        }
+    }
+
+    out
+}
+
+/// Renders the state and the whole tape as an HTML table, with the head's
+/// cell highlighted.
+pub fn html<S, Sym>(snapshot: &Snapshot<S, Sym>) -> String
+where
+    S: Display,
+    Sym: Symbol + Default + PartialEq,
+{
+    let blank = Sym::blank();
+    let mut out = String::new();
+
+    out.push_str("<div style=\"font-family: monospace\"><div>state: <b>");
+
+    match &snapshot.state {
+        State::State(state) => escape_html(&mut out, &alloc::format!("{state}")),
+        State::Halt => out.push_str("halt"),
+    }
+
+    out.push_str("</b></div><table style=\"border-collapse: collapse\"><tr>");
+
+    for (index, symbol) in snapshot.tape.iter().enumerate() {
+        let background = match (index == snapshot.head, *symbol == blank) {
+            (true, _) => "#fd6",
+            (false, true) => "#eee",
+            (false, false) => "#fff",
+        };
+
+        write!(
+            out,
+            "<td style=\"border: 1px solid #999; padding: 2px 6px; background: {background}\">"
+        )
+        .unwrap();
+        escape_html(&mut out, symbol.to_char().encode_utf8(&mut [0; 4]));
+        out.push_str("</td>");
+    }
+
+    out.push_str("</tr></table></div>");
+    out
+}
+
+fn escape_html(out: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '&' => out.push_str("&amp;"),
+            '"' => out.push_str("&quot;"),
+            c => out.push(c),
+        }
+    }
+}
+
+/// The instantaneous description `α q β` of a configuration: the tape with
+/// blanks trimmed from both ends, and the state in front of the symbol under
+/// the head, e.g. `10 q1 011`. Blanks between the head and the rest of the
+/// tape are kept.
+pub fn description<S, Sym>(snapshot: &Snapshot<S, Sym>) -> String
+where
+    S: Display,
+    Sym: Symbol + Default + PartialEq,
+{
+    let blank = Sym::default();
+    let start = snapshot.tape.iter().position(|symbol| *symbol != blank);
+    let end = snapshot.tape.iter().rposition(|symbol| *symbol != blank);
+
+    let (start, end) = match start.zip(end) {
+        Some((start, end)) => (start.min(snapshot.head), end.max(snapshot.head)),
+        None => (snapshot.head, snapshot.head),
+    };
+
+    let mut out = String::new();
+
+    for symbol in &snapshot.tape[start..snapshot.head] {
+        out.push(symbol.to_char());
+    }
+
+    if !out.is_empty() {
+        out.push(' ');
+    }
+
+    match &snapshot.state {
+        State::State(state) => write!(out, "{state} ").unwrap(),
+        State::Halt => out.push_str("halt "),
+    }
+
+    for symbol in &snapshot.tape[snapshot.head..=end] {
+        out.push(symbol.to_char());
+    }
+
+    out
+}
+
+/// The computation history of a run as a sequence of [`description`]s,
+/// printed as `α q β ⊢ α' q' β' ⊢ ...`, or one per line with `{:#}`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct History {
+    descriptions: Vec<String>,
+}
+
+impl History {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record<S, Sym>(&mut self, snapshot: &Snapshot<S, Sym>)
+    where
+        S: Display,
+        Sym: Symbol + Default + PartialEq,
+    {
+        self.descriptions.push(description(snapshot));
+    }
+
+    pub fn descriptions(&self) -> &[String] {
+        &self.descriptions
+    }
+
+    pub fn len(&self) -> usize {
+        self.descriptions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.descriptions.is_empty()
+    }
+}
+
+impl Display for History {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, description) in self.descriptions.iter().enumerate() {
+            match (index, f.alternate()) {
+                (0, _) => {}
+                (_, true) => f.write_str("\n⊢ ")?,
+                (_, false) => f.write_str(" ⊢ ")?,
+            }
+
+            f.write_str(description)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// What a GUI draws for one configuration: a window of cells around the
+/// head, padded with blanks past the ends of the tape.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame<S, Sym> {
+    pub step: u64,
+    pub state: State<S>,
+    pub cells: Vec<Sym>,
+    /// The index of the head's cell within `cells`.
+    pub head: usize,
+    /// The head position relative to the initial cell 0.
+    pub position: isize,
+}
+
+impl<S, Sym, T> Machine<S, Sym, T>
+where
+    S: Clone,
+    Sym: Default + Clone,
+    T: Tape<Sym>,
+{
+    /// The frame of the current configuration at `step`, `width` cells wide
+    /// with the head in the middle.
+    pub fn frame(&self, step: u64, width: usize) -> Frame<S, Sym> {
+        let head = width / 2;
+        let cells = self.window(self.head as isize - head as isize, width);
+
+        Frame {
+            step,
+            state: self.state.clone(),
+            cells,
+            head,
+            position: self.head as isize - self.origin as isize,
+        }
+    }
+
+    /// An iterator stepping the machine by `table`, yielding the frame of
+    /// every configuration from the current one to the last.
+    pub fn frames<'a>(
+        &'a mut self,
+        table: &'a TransitionTable<S, Sym>,
+        width: usize,
+    ) -> Frames<'a, S, Sym, T> {
+        Frames {
+            machine: self,
+            table,
+            width,
+            step: 0,
+            done: false,
+        }
+    }
+}
+
+/// The frames of a run, from [`Machine::frames`].
+#[derive(Debug)]
+pub struct Frames<'a, S, Sym: Default, T> {
+    machine: &'a mut Machine<S, Sym, T>,
+    table: &'a TransitionTable<S, Sym>,
+    width: usize,
+    step: u64,
+    done: bool,
+}
+
+impl<S, Sym, T> Iterator for Frames<'_, S, Sym, T>
+where
+    S: Ord + Clone,
+    Sym: Default + Ord + Clone,
+    T: Tape<Sym>,
+{
+    type Item = Frame<S, Sym>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let frame = self.machine.frame(self.step, self.width);
+
+        self.done = !self.machine.step_table(self.table);
+        self.step += 1;
+
+        Some(frame)
+    }
+}
+
+/// A standalone HTML page titled `title` showing `table` and playing back
+/// `frames`, e.g. those of [`Machine::frames`], with a small script, so a
+/// run can be shared with anyone who has a browser.
+pub fn html_player<S, Sym>(
+    title: &str,
+    table: &TransitionTable<S, Sym>,
+    frames: &[Frame<S, Sym>],
+) -> String
+where
+    S: Ord + Display,
+    Sym: Ord + Symbol,
+{
+    let mut out = String::new();
+
+    out.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>");
+    escape_html(&mut out, title);
+    out.push_str("</title>\n");
+    out.push_str(PLAYER_STYLE);
+    out.push_str("</head>\n<body>\n<h1>");
+    escape_html(&mut out, title);
+    out.push_str("</h1>\n<pre>");
+    escape_html(&mut out, &alloc::format!("{table}"));
+    out.push_str("</pre>\n");
+    out.push_str(PLAYER_CONTROLS);
+    let mut json = String::new();
+
+    for frame in frames {
+        let state = match &frame.state {
+            State::State(state) => alloc::format!("{state}"),
+            State::Halt => "halt".into(),
+        };
+        let cells: String = frame.cells.iter().map(Symbol::to_char).collect();
+
+        writeln!(
+            json,
+            r#"{{"step":{},"state":{},"cells":{},"head":{},"position":{}}},"#,
+            frame.step,
+            json_string(&state),
+            json_string(&cells),
+            frame.head,
+            frame.position,
+        )
+        .unwrap();
+    }
+
+    // `<` only occurs inside strings, where escaping it keeps names like
+    // `</script>` from ending the script
+    out.push_str("<script>\nconst frames = [\n");
+    out.push_str(&json.replace('<', "\\u003c"));
+    out.push_str("];\n");
+    out.push_str(PLAYER_SCRIPT);
+    out.push_str("</script>\n</body>\n</html>\n");
+    out
+}
+
+const PLAYER_STYLE: &str = "<style>
+body { font-family: monospace; margin: 2em; }
+pre { background: #f6f6f6; padding: 1em; }
+#tape { border-collapse: collapse; margin: 1em 0; }
+#tape td { border: 1px solid #999; padding: 2px 6px; min-width: 1em; text-align: center; }
+#tape td.head { background: #fd6; }
+</style>
+";
+
+const PLAYER_CONTROLS: &str = "<div>step <span id=\"step\"></span>, \
+state <b id=\"state\"></b>, position <span id=\"position\"></span></div>
+<table id=\"tape\"><tr></tr></table>
+<div>
+<button id=\"back\">&lt;</button>
+<button id=\"play\">play</button>
+<button id=\"forward\">&gt;</button>
+<input id=\"seek\" type=\"range\" min=\"0\" value=\"0\">
+</div>
+";
+
+const PLAYER_SCRIPT: &str = "const $ = (id) => document.getElementById(id);
+let current = 0;
+let timer = null;
+
+function show(index) {
+  current = Math.max(0, Math.min(index, frames.length - 1));
+  const frame = frames[current];
+  if (!frame) return;
+  $('step').textContent = frame.step;
+  $('state').textContent = frame.state;
+  $('position').textContent = frame.position;
+  $('seek').value = current;
+  const row = $('tape').rows[0];
+  row.replaceChildren(...Array.from(frame.cells, (cell, index) => {
+    const td = document.createElement('td');
+    td.textContent = cell;
+    if (index === frame.head) td.className = 'head';
+    return td;
+  }));
+}
+
+function pause() {
+  clearInterval(timer);
+  timer = null;
+  $('play').textContent = 'play';
+}
+
+$('seek').max = Math.max(frames.length - 1, 0);
+$('seek').oninput = (event) => show(Number(event.target.value));
+$('back').onclick = () => { pause(); show(current - 1); };
+$('forward').onclick = () => { pause(); show(current + 1); };
+$('play').onclick = () => {
+  if (timer) return pause();
+  if (current === frames.length - 1) show(0);
+  $('play').textContent = 'pause';
+  timer = setInterval(() => {
+    show(current + 1);
+    if (current === frames.length - 1) pause();
+  }, 200);
+};
+show(0);
+";
+
+/// Collects one row of cells per recorded configuration, aligned on the
+/// initial cell 0, and turns them into a space-time diagram.
+#[derive(Debug, Clone)]
+pub struct SpaceTime<Sym> {
+    // (position of the row's first cell relative to cell 0, cells)
+    rows: Vec<(isize, Vec<Sym>)>,
+}
+
+impl<Sym> SpaceTime<Sym>
+where
+    Sym: Default + PartialEq + Clone,
+{
+    pub fn new() -> Self {
+        Self { rows: Vec::new() }
+    }
+
+    pub fn record<S>(&mut self, snapshot: &Snapshot<S, Sym>) {
+        self.rows
+            .push((-(snapshot.origin as isize), snapshot.tape.clone()));
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Encodes the diagram as a binary PGM image with one row per recorded
+    /// configuration. Blanks are white and every other symbol gets its own
+    /// shade of grey, in order of first appearance.
+    pub fn to_pgm(&self) -> Vec<u8> {
+        let blank = Sym::default();
+        let left = self.rows.iter().map(|(start, _)| *start).min().unwrap_or(0);
+        let right = self
+            .rows
+            .iter()
+            .map(|(start, cells)| start + cells.len() as isize)
+            .max()
+            .unwrap_or(0);
+        let width = (right - left) as usize;
+
+        let mut shades: Vec<&Sym> = Vec::new();
+
+        for (_, cells) in &self.rows {
+            for cell in cells {
+                if *cell != blank && !shades.contains(&cell) {
+                    shades.push(cell);
+                }
+            }
+        }
+
+        let shade = |cell: &Sym| match shades.iter().position(|other| *other == cell) {
+            None => 255,
+            Some(index) => (index * 200 / shades.len().max(1)) as u8,
+        };
+
+        let mut image = Vec::new();
+        let mut header = String::new();
+        write!(header, "P5\n{width} {}\n255\n", self.rows.len()).unwrap();
+        image.extend_from_slice(header.as_bytes());
+
+        for (start, cells) in &self.rows {
+            let offset = (start - left) as usize;
+
+            image.extend(core::iter::repeat_n(255, offset));
+            image.extend(cells.iter().map(shade));
+            image.extend(core::iter::repeat_n(255, width - offset - cells.len()));
+        }
+
+        image
+    }
+}
+
+impl<Sym> Default for SpaceTime<Sym>
+where
+    Sym: Default + PartialEq + Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::format;
+    use alloc::string::ToString;
+
+    use super::*;
+    use crate::{Char, Executor, Machine, Rule};
+
+    struct Grow;
+
+    // writes ones while walking left forever
+    impl Executor<u8, bool> for Grow {
+        fn execute(_state: &u8, _symbol: &bool) -> Rule<u8, bool> {
+            Rule::new().write(true).left()
+        }
+    }
+
+    #[test]
+    fn ansi_test() {
+        let machine: Machine<u8, bool> = Machine::new(0, [true, false].into());
+
+        assert_eq!(
+            ansi(&machine.snapshot(), 3),
+            "\x1b[2m0\x1b[0m\x1b[7m1\x1b[0m\x1b[2m0\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn html_test() {
+        let machine: Machine<&str, Char> = Machine::new("a<b", [Char('1'), Char('_')].into());
+        let html = html(&machine.snapshot());
+
+        assert!(html.contains("state: <b>a&lt;b</b>"));
+        assert_eq!(html.matches("<td").count(), 2);
+        assert!(html.contains("background: #fd6\">1</td>"));
+    }
+
+    #[test]
+    fn history_test() {
+        let table =
+            crate::dsl::parse("q0 1 1 R q0\nq0 _ 1 L q1\nq1 1 1 L q1\nq1 _ _ R halt").unwrap();
+        let mut machine: Machine<String, Char> =
+            Machine::with_input("q0".to_string(), "11").unwrap();
+        let mut history = History::new();
+
+        history.record(&machine.snapshot());
+
+        while machine.step_table(&table) && history.len() < 3 {
+            history.record(&machine.snapshot());
+        }
+
+        assert_eq!(history.to_string(), "q0 11 ⊢ 1 q0 1 ⊢ 11 q0 _");
+        assert_eq!(format!("{history:#}"), "q0 11\n⊢ 1 q0 1\n⊢ 11 q0 _");
+
+        while machine.step_table(&table) {}
+        let mut history = History::new();
+        history.record(&machine.snapshot());
+
+        assert_eq!(history.descriptions(), ["halt 111"]);
+    }
+
+    #[test]
+    fn frames_test() {
+        let table = crate::dsl::parse("a 1 1 R a\na _ 1 N halt").unwrap();
+        let mut machine: Machine<String, Char> =
+            Machine::with_input("a".to_string(), "11").unwrap();
+        let frames: Vec<_> = machine.frames(&table, 3).collect();
+
+        assert_eq!(frames.len(), 4);
+        assert_eq!(frames[0].cells, [Char('_'), Char('1'), Char('1')]);
+        assert_eq!(frames[2].position, 2);
+        assert_eq!(frames[2].cells, [Char('1'), Char('_'), Char('_')]);
+        assert_eq!(frames[3].state, State::Halt);
+        assert_eq!(frames[3].cells[frames[3].head], Char('1'));
+    }
+
+    #[test]
+    fn html_player_test() {
+        let table = crate::dsl::parse("a 1 1 R a\na _ 1 N halt").unwrap();
+        let mut machine: Machine<String, Char> =
+            Machine::with_input("a".to_string(), "11").unwrap();
+        let mut frames: Vec<_> = machine.frames(&table, 3).collect();
+        frames[0].state = State::State("</script>".to_string());
+
+        let page = html_player("a & b", &table, &frames);
+
+        assert!(page.contains("<title>a &amp; b</title>"));
+        assert_eq!(page.matches(r#""step":"#).count(), 4);
+        assert!(page.contains(r#""state":"halt","cells":"11_""#));
+        assert!(page.contains(r#""state":"\u003c/script>""#));
+        assert_eq!(page.matches("</script>").count(), 1);
+    }
+
+    #[test]
+    fn space_time_test() {
+        let mut machine: Machine<u8, bool> = Machine::new(0, [false].into());
+        let mut diagram = SpaceTime::new();
+
+        for _ in 0..3 {
+            diagram.record(&machine.snapshot());
+            machine.execute::<Grow>();
+        }
+
+        let image = diagram.to_pgm();
+        let header = b"P5\n3 3\n255\n";
+
+        assert_eq!(&image[..header.len()], header);
+        assert_eq!(
+            &image[header.len()..],
+            [255, 255, 255, 255, 255, 0, 255, 0, 0]
+        );
+    }
+}
@@ -0,0 +1,223 @@
+use alloc::vec::Vec;
+use core::cell::Cell;
+use core::hash::Hash;
+use core::sync::atomic::AtomicBool;
+
+use crate::{ConfigHash, Interner, Machine, State, StopReason, TransitionTable};
+
+/// Why a run from [`Machine::run_report`] stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Halted,
+    StepLimit,
+    /// The tape grew past [`RunOptions::max_tape_len`].
+    TapeLimit,
+    Cancelled,
+    /// The machine came back to a configuration it had been in, so it never
+    /// halts; only with [`RunOptions::detect_loops`].
+    LoopDetected,
+    /// There was no rule for the configuration, under
+    /// [`MissingRule::Error`](crate::MissingRule::Error).
+    NoRule,
+}
+
+/// The limits and extras of a [`Machine::run_report`] run.
+#[derive(Debug, Clone, Copy)]
+pub struct RunOptions<'a> {
+    pub max_steps: u64,
+    pub max_tape_len: Option<usize>,
+    pub detect_loops: bool,
+    /// Whether to put the trimmed tape into the report.
+    pub output: bool,
+    pub cancel: Option<&'a AtomicBool>,
+}
+
+impl<'a> RunOptions<'a> {
+    pub fn new(max_steps: u64) -> Self {
+        Self {
+            max_steps,
+            max_tape_len: None,
+            detect_loops: false,
+            output: false,
+            cancel: None,
+        }
+    }
+
+    pub fn max_tape_len(self, max_tape_len: usize) -> Self {
+        Self {
+            max_tape_len: Some(max_tape_len),
+            ..self
+        }
+    }
+
+    /// Remembers every configuration to stop on the first one repeated, at
+    /// the cost of memory proportional to the distinct configurations.
+    pub fn detect_loops(self) -> Self {
+        Self {
+            detect_loops: true,
+            ..self
+        }
+    }
+
+    pub fn output(self) -> Self {
+        Self {
+            output: true,
+            ..self
+        }
+    }
+
+    pub fn cancel(self, cancel: &'a AtomicBool) -> Self {
+        Self {
+            cancel: Some(cancel),
+            ..self
+        }
+    }
+}
+
+/// Everything about a finished run in one place.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunReport<S, Sym> {
+    pub outcome: Outcome,
+    pub state: State<S>,
+    pub steps: u64,
+    pub tape_len: usize,
+    /// The distinct configurations seen, with loop detection.
+    pub configurations: Option<usize>,
+    /// The tape with blanks trimmed from both ends and the position of the
+    /// initial cell 0 in it, like [`Machine::finish_trimmed`], if asked for.
+    pub output: Option<(Vec<Sym>, isize)>,
+}
+
+impl<S, Sym> Machine<S, Sym>
+where
+    S: Ord + Clone + Hash,
+    Sym: Default + Ord + Clone + Hash,
+{
+    /// Runs the machine by `table` within the limits of `options`, reporting
+    /// how the run went.
+    pub fn run_report(
+        &mut self,
+        table: &TransitionTable<S, Sym>,
+        options: &RunOptions<'_>,
+    ) -> RunReport<S, Sym> {
+        let mut seen = options.detect_loops.then(Interner::new);
+        let mut hash = ConfigHash::new(self);
+        let outcome = Cell::new(None);
+
+        if let Some(seen) = &mut seen {
+            seen.intern_hashed(self, hash.value());
+        }
+
+        let stats = self.run_loop(
+            options.max_steps,
+            options.cancel.unwrap_or(&AtomicBool::new(false)),
+            // stopping through the hook, so that the step ending the run is
+            // counted
+            1,
+            |machine| {
+                let Some(rule) = machine.next_table_rule(table) else {
+                    return false;
+                };
+
+                match &mut seen {
+                    Some(seen) => {
+                        machine.apply_hashed(rule, &mut hash);
+
+                        if !seen.intern_hashed(machine, hash.value()).1 {
+                            outcome.set(Some(Outcome::LoopDetected));
+                        }
+                    }
+                    None => machine.apply(rule),
+                }
+
+                if options
+                    .max_tape_len
+                    .is_some_and(|max| machine.tape.len() > max)
+                {
+                    outcome.set(Some(Outcome::TapeLimit));
+                }
+
+                true
+            },
+            |_, _| outcome.get().is_none(),
+        );
+
+        let outcome = outcome.get().unwrap_or(match stats.stop {
+            StopReason::Halted => Outcome::Halted,
+            StopReason::StepLimit => Outcome::StepLimit,
+            StopReason::Cancelled => Outcome::Cancelled,
+            StopReason::NoRule => Outcome::NoRule,
+        });
+
+        RunReport {
+            outcome,
+            state: self.state.clone(),
+            steps: stats.steps,
+            tape_len: stats.tape_len,
+            configurations: seen.map(|seen| seen.len()),
+            output: options.output.then(|| self.trimmed()),
+        }
+    }
+
+    fn trimmed(&self) -> (Vec<Sym>, isize) {
+        let blank = &self.blank;
+        let start = self.tape.iter().position(|sym| sym != blank);
+        let end = self.tape.iter().rposition(|sym| sym != blank);
+
+        match start.zip(end) {
+            Some((start, end)) => (
+                self.tape.range(start..=end).cloned().collect(),
+                self.origin as isize - start as isize,
+            ),
+            None => (Vec::new(), 0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Rule;
+
+    #[test]
+    fn run_report_test() {
+        // the 2-state busy beaver
+        let bb: TransitionTable<u8, bool> = [
+            (0, false, Rule::to(1).write(true).right()),
+            (0, true, Rule::to(1).write(true).left()),
+            (1, false, Rule::to(0).write(true).left()),
+            (1, true, Rule::halt().write(true).right()),
+        ]
+        .into_iter()
+        .collect();
+
+        let mut machine: Machine<u8, bool> = Machine::new(0, [false].into());
+        let report = machine.run_report(&bb, &RunOptions::new(100).output());
+
+        assert_eq!(report.outcome, Outcome::Halted);
+        assert_eq!(report.state, State::Halt);
+        assert_eq!(report.steps, 6);
+        assert_eq!(report.output, Some((alloc::vec![true; 4], 2)));
+
+        let mut machine: Machine<u8, bool> = Machine::new(0, [false].into());
+        let report = machine.run_report(&bb, &RunOptions::new(100).max_tape_len(2));
+
+        assert_eq!(report.outcome, Outcome::TapeLimit);
+        assert_eq!(report.tape_len, 3);
+
+        // bounces between two cells forever
+        let bounce: TransitionTable<u8, bool> = [
+            (0, false, Rule::to(1).right()),
+            (1, false, Rule::to(0).left()),
+        ]
+        .into_iter()
+        .collect();
+
+        let mut machine: Machine<u8, bool> = Machine::new(0, [false].into());
+        let report = machine.run_report(&bounce, &RunOptions::new(100).detect_loops());
+
+        assert_eq!(report.outcome, Outcome::LoopDetected);
+        assert_eq!(report.steps, 2);
+        assert_eq!(report.configurations, Some(2));
+    }
+}
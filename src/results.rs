@@ -0,0 +1,290 @@
+//! A database of search results in SQLite, with one row per table keyed by
+//! its [`normalize`]d bbchallenge notation, so that tables differing only in
+//! state names or direction share a row.
+//!
+//! Rows go into a `results` table of
+//! `(machine, states, symbols, outcome, steps, sigma)` rows, with `outcome`
+//! one of `halted`, `forever` and `undecided` and `sigma` only set for
+//! halting tables, indexed on `(states, symbols, outcome)`. A decided table
+//! keeps its row; an undecided one is replaced by a decision, or by a run
+//! that went further.
+
+use std::path::Path;
+
+use rusqlite::{params, Connection, Error, OptionalExtension, Result, Row};
+
+use crate::distributed::{Decision, UnitResult, WorkUnit};
+use crate::format::bbchallenge::{self, normalize};
+use crate::format::number::{self, Number};
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS results (
+        machine TEXT PRIMARY KEY,
+        states INTEGER NOT NULL,
+        symbols INTEGER NOT NULL,
+        outcome TEXT NOT NULL,
+        steps INTEGER NOT NULL,
+        sigma INTEGER
+    );
+    CREATE INDEX IF NOT EXISTS results_outcome ON results (states, symbols, outcome);
+";
+
+const UPSERT: &str = "
+    INSERT INTO results (machine, states, symbols, outcome, steps, sigma)
+    VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+    ON CONFLICT (machine) DO UPDATE SET
+        outcome = excluded.outcome, steps = excluded.steps, sigma = excluded.sigma
+    WHERE results.outcome = 'undecided'
+        AND (excluded.outcome != 'undecided' OR excluded.steps > results.steps)
+";
+
+/// The kinds of [`Decision`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Halted,
+    Forever,
+    Undecided,
+}
+
+impl Kind {
+    pub fn of(decision: &Decision) -> Self {
+        match decision {
+            Decision::Halted { .. } => Kind::Halted,
+            Decision::Forever { .. } => Kind::Forever,
+            Decision::Undecided { .. } => Kind::Undecided,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Kind::Halted => "halted",
+            Kind::Forever => "forever",
+            Kind::Undecided => "undecided",
+        }
+    }
+}
+
+/// A stored table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    /// The normalized notation.
+    pub machine: String,
+    pub states: usize,
+    pub symbols: usize,
+    pub decision: Decision,
+}
+
+impl Entry {
+    fn from_row(row: &Row<'_>) -> Result<Self> {
+        let outcome: String = row.get(3)?;
+        let steps = row.get::<_, i64>(4)? as u64;
+
+        let decision = match outcome.as_str() {
+            "halted" => Decision::Halted {
+                steps,
+                sigma: row.get::<_, i64>(5)? as u64,
+            },
+            "forever" => Decision::Forever { steps },
+            _ => Decision::Undecided { steps },
+        };
+
+        Ok(Self {
+            machine: row.get(0)?,
+            states: row.get::<_, i64>(1)? as usize,
+            symbols: row.get::<_, i64>(2)? as usize,
+            decision,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct ResultsDb {
+    connection: Connection,
+}
+
+impl ResultsDb {
+    /// Opens or creates the database at `path`.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    pub fn in_memory() -> Result<Self> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    pub fn with_connection(connection: Connection) -> Result<Self> {
+        connection.execute_batch(SCHEMA)?;
+
+        Ok(Self { connection })
+    }
+
+    /// Records `decision` for the table with bbchallenge notation `machine`.
+    /// An invalid notation fails with [`Error::ToSqlConversionFailure`].
+    pub fn record(&self, machine: &str, decision: Decision) -> Result<()> {
+        upsert(&self.connection, machine, decision)
+    }
+
+    /// Runs every table of `unit`, recording each in one transaction, and
+    /// returns the unit's result.
+    pub fn record_unit(&mut self, unit: &WorkUnit) -> Result<UnitResult> {
+        let transaction = self.connection.transaction()?;
+        let mut decisions = Vec::new();
+
+        for (number, decision) in unit.decisions() {
+            let notation =
+                number::decode::<u8>(&Number::from_u128(unit.states, unit.symbols, number))
+                    .and_then(|definition| bbchallenge::write(&definition).ok());
+
+            if let Some(notation) = notation {
+                upsert(&transaction, &notation, decision)?;
+            }
+
+            decisions.push((number, decision));
+        }
+
+        transaction.commit()?;
+
+        Ok(unit.collect(decisions))
+    }
+
+    /// The stored row for the table with bbchallenge notation `machine`.
+    pub fn get(&self, machine: &str) -> Result<Option<Entry>> {
+        let machine = normalize(machine).map_err(|e| Error::ToSqlConversionFailure(e.into()))?;
+
+        self.connection
+            .query_row(
+                "SELECT * FROM results WHERE machine = ?1",
+                params![machine],
+                Entry::from_row,
+            )
+            .optional()
+    }
+
+    /// The tables with a decision of kind `kind`, and `states` states and
+    /// `symbols` symbols where given, in notation order.
+    pub fn find(
+        &self,
+        kind: Kind,
+        states: Option<usize>,
+        symbols: Option<usize>,
+    ) -> Result<Vec<Entry>> {
+        let mut statement = self.connection.prepare_cached(
+            "SELECT * FROM results
+             WHERE outcome = ?1 AND (?2 IS NULL OR states = ?2) AND (?3 IS NULL OR symbols = ?3)
+             ORDER BY machine",
+        )?;
+
+        let entries = statement
+            .query_map(
+                params![kind.name(), size(states), size(symbols)],
+                Entry::from_row,
+            )?
+            .collect();
+
+        entries
+    }
+
+    /// How many of the tables with `states` states and `symbols` symbols
+    /// have a decision of kind `kind`.
+    pub fn count(&self, kind: Kind, states: usize, symbols: usize) -> Result<u64> {
+        self.connection
+            .query_row(
+                "SELECT COUNT(*) FROM results WHERE outcome = ?1 AND states = ?2 AND symbols = ?3",
+                params![kind.name(), states as i64, symbols as i64],
+                |row| row.get::<_, i64>(0),
+            )
+            .map(|count| count as u64)
+    }
+
+    /// The halting table with `states` states and `symbols` symbols taking
+    /// the most steps, then leaving the most non-blank symbols.
+    pub fn champion(&self, states: usize, symbols: usize) -> Result<Option<Entry>> {
+        self.connection
+            .query_row(
+                "SELECT * FROM results WHERE outcome = 'halted' AND states = ?1 AND symbols = ?2
+                 ORDER BY steps DESC, sigma DESC, machine LIMIT 1",
+                params![states as i64, symbols as i64],
+                Entry::from_row,
+            )
+            .optional()
+    }
+
+    /// The underlying connection, for queries not covered here.
+    pub fn connection(&self) -> &Connection {
+        &self.connection
+    }
+}
+
+fn size(size: Option<usize>) -> Option<i64> {
+    size.map(|size| size as i64)
+}
+
+fn upsert(connection: &Connection, machine: &str, decision: Decision) -> Result<()> {
+    let machine = normalize(machine).map_err(|e| Error::ToSqlConversionFailure(e.into()))?;
+    let states = machine.split('_').count();
+    let symbols = machine.len() / states / 3;
+
+    let (steps, sigma) = match decision {
+        Decision::Halted { steps, sigma } => (steps, Some(sigma as i64)),
+        Decision::Forever { steps } | Decision::Undecided { steps } => (steps, None),
+    };
+
+    connection.prepare_cached(UPSERT)?.execute(params![
+        machine,
+        states as i64,
+        symbols as i64,
+        Kind::of(&decision).name(),
+        steps as i64,
+        sigma,
+    ])?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::distributed::partition;
+
+    #[test]
+    fn results_test() {
+        let db = ResultsDb::in_memory().unwrap();
+        let halted = Decision::Halted { steps: 6, sigma: 4 };
+
+        db.record("1RB---_1LA0RA", Decision::Undecided { steps: 10 })
+            .unwrap();
+        db.record("1LB---_1RA0LA", Decision::Undecided { steps: 5 })
+            .unwrap();
+        db.record("1RB1LB_1LA1RZ", halted).unwrap();
+        db.record("1LB1RB_1RA1LZ", Decision::Undecided { steps: 100 })
+            .unwrap();
+
+        // the mirrored tables share the rows, and decisions stay
+        assert_eq!(
+            db.get("1LB---_1RA0LA").unwrap().unwrap().decision,
+            Decision::Undecided { steps: 10 }
+        );
+        assert_eq!(db.get("1RB1LB_1LA1RZ").unwrap().unwrap().decision, halted);
+        assert_eq!(db.find(Kind::Undecided, Some(2), None).unwrap().len(), 1);
+        assert!(db.find(Kind::Undecided, Some(5), None).unwrap().is_empty());
+        assert!(db.record("1RB1L", halted).is_err());
+    }
+
+    #[test]
+    fn record_unit_test() {
+        let mut db = ResultsDb::in_memory().unwrap();
+
+        for unit in partition(2, 2, 100, 10_000).unwrap() {
+            db.record_unit(&unit).unwrap();
+        }
+
+        let champion = db.champion(2, 2).unwrap().unwrap();
+
+        assert_eq!(champion.decision, Decision::Halted { steps: 6, sigma: 4 });
+        assert_eq!(
+            db.find(Kind::Undecided, Some(2), Some(2)).unwrap().len() as u64,
+            db.count(Kind::Undecided, 2, 2).unwrap()
+        );
+        assert!(db.count(Kind::Forever, 2, 2).unwrap() > 0);
+    }
+}
@@ -0,0 +1,418 @@
+use core::future::Future;
+use core::pin::Pin;
+use core::sync::atomic::{AtomicBool, Ordering};
+use core::task::{Context, Poll};
+
+use crate::table::RuleCache;
+use crate::{Executor, Machine, State, Tape, TransitionTable, TryExecutor};
+
+// how many steps run between checks of a cancellation flag
+pub(crate) const CANCEL_CHECK_INTERVAL: u64 = 4096;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+    Halted,
+    StepLimit,
+    Cancelled,
+    /// There was no rule for the configuration, under
+    /// [`MissingRule::Error`](crate::MissingRule::Error).
+    NoRule,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RunStats {
+    pub steps: u64,
+    pub tape_len: usize,
+    pub stop: StopReason,
+}
+
+/// Why a [`Machine::run_until`] run stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Until {
+    /// The predicate held after `steps` steps.
+    Holds { steps: u64 },
+    /// The run stopped before the predicate held.
+    Stopped(RunStats),
+}
+
+impl<S, Sym, T> Machine<S, Sym, T>
+where
+    Sym: Default + Clone,
+    T: Tape<Sym>,
+{
+    /// Runs the machine until it halts or `max_steps` steps were taken.
+    pub fn run<E>(&mut self, max_steps: u64) -> RunStats
+    where
+        E: Executor<S, Sym>,
+    {
+        self.run_cancellable::<E>(max_steps, &AtomicBool::new(false))
+    }
+
+    /// Like [`Machine::run`], but also stops soon after `cancel` is set, e.g.
+    /// from another thread holding the same `Arc<AtomicBool>`.
+    pub fn run_cancellable<E>(&mut self, max_steps: u64, cancel: &AtomicBool) -> RunStats
+    where
+        E: Executor<S, Sym>,
+    {
+        self.run_hooked::<E>(max_steps, cancel, CANCEL_CHECK_INTERVAL, |_, _| true)
+    }
+
+    /// Like [`Machine::run`] with a [`TryExecutor`], stopping at the first
+    /// error it returns. The failed step isn't counted and leaves the machine
+    /// as it was.
+    pub fn try_run<E>(&mut self, max_steps: u64) -> Result<RunStats, E::Error>
+    where
+        E: TryExecutor<S, Sym>,
+    {
+        let mut error = None;
+        let stats = self.run_loop(
+            max_steps,
+            &AtomicBool::new(false),
+            CANCEL_CHECK_INTERVAL,
+            |machine| {
+                let State::State(state) = &machine.state else {
+                    return false;
+                };
+
+                match E::try_execute(state, machine.current_symbol()) {
+                    Ok(rule) => {
+                        machine.apply(rule);
+                        true
+                    }
+                    Err(e) => {
+                        error = Some(e);
+                        false
+                    }
+                }
+            },
+            |_, _| true,
+        );
+
+        match error {
+            Some(error) => Err(error),
+            None => Ok(stats),
+        }
+    }
+
+    /// Like [`Machine::run`], looking rules up in `table`.
+    pub fn run_table(&mut self, table: &TransitionTable<S, Sym>, max_steps: u64) -> RunStats
+    where
+        S: Ord + Clone,
+        Sym: Ord + Clone,
+    {
+        let mut cache = RuleCache::default();
+
+        self.run_loop(
+            max_steps,
+            &AtomicBool::new(false),
+            CANCEL_CHECK_INTERVAL,
+            |machine| machine.step_table_cached(table, &mut cache),
+            |_, _| true,
+        )
+    }
+
+    /// Runs the machine until `predicate` holds for its configuration, which
+    /// is checked before every step and once more after the last, or until it
+    /// halts or `max_steps` steps were taken.
+    pub fn run_until<E>(&mut self, max_steps: u64, predicate: impl FnMut(&Self) -> bool) -> Until
+    where
+        E: Executor<S, Sym>,
+    {
+        self.run_until_with(max_steps, Self::step::<E>, predicate)
+    }
+
+    /// Like [`Machine::run_until`], looking rules up in `table`.
+    pub fn run_table_until(
+        &mut self,
+        table: &TransitionTable<S, Sym>,
+        max_steps: u64,
+        predicate: impl FnMut(&Self) -> bool,
+    ) -> Until
+    where
+        S: Ord + Clone,
+        Sym: Ord + Clone,
+    {
+        let mut cache = RuleCache::default();
+
+        self.run_until_with(
+            max_steps,
+            |machine| machine.step_table_cached(table, &mut cache),
+            predicate,
+        )
+    }
+
+    fn run_until_with(
+        &mut self,
+        max_steps: u64,
+        step: impl FnMut(&mut Self) -> bool,
+        mut predicate: impl FnMut(&Self) -> bool,
+    ) -> Until {
+        // the hook stops the run as cancelled once the predicate holds, but
+        // isn't called once the run stops otherwise
+        let stats = self.run_loop(max_steps, &AtomicBool::new(false), 1, step, |machine, _| {
+            !predicate(machine)
+        });
+
+        match stats.stop {
+            StopReason::Cancelled => Until::Holds { steps: stats.steps },
+            StopReason::Halted | StopReason::StepLimit if predicate(self) => {
+                Until::Holds { steps: stats.steps }
+            }
+            _ => Until::Stopped(stats),
+        }
+    }
+
+    pub(crate) fn run_hooked<E>(
+        &mut self,
+        max_steps: u64,
+        cancel: &AtomicBool,
+        every: u64,
+        hook: impl FnMut(&Self, u64) -> bool,
+    ) -> RunStats
+    where
+        E: Executor<S, Sym>,
+    {
+        self.run_loop(max_steps, cancel, every, Self::step::<E>, hook)
+    }
+
+    // the shared run loop: every `every` steps `hook` is called with the
+    // machine and the step count, and the cancellation flag is checked as
+    // often, or every `CANCEL_CHECK_INTERVAL` steps if that's sooner. a hook
+    // returning `false` stops the run as cancelled, a step returning `false`
+    // for not finding a rule stops it without counting the step
+    pub(crate) fn run_loop(
+        &mut self,
+        max_steps: u64,
+        cancel: &AtomicBool,
+        every: u64,
+        mut step: impl FnMut(&mut Self) -> bool,
+        mut hook: impl FnMut(&Self, u64) -> bool,
+    ) -> RunStats {
+        let every = every.max(1);
+        let check = every.min(CANCEL_CHECK_INTERVAL);
+        let mut steps = 0;
+
+        let stop = loop {
+            if self.halted() {
+                break StopReason::Halted;
+            }
+
+            if steps == max_steps {
+                break StopReason::StepLimit;
+            }
+
+            if steps % check == 0 && cancel.load(Ordering::Relaxed) {
+                break StopReason::Cancelled;
+            }
+
+            if steps % every == 0 && !hook(self, steps) {
+                break StopReason::Cancelled;
+            }
+
+            if !step(self) {
+                break StopReason::NoRule;
+            }
+
+            steps += 1;
+        };
+
+        RunStats {
+            steps,
+            tape_len: self.tape.len(),
+            stop,
+        }
+    }
+
+    /// Runs the machine until it halts, yielding back to the async runtime
+    /// after every `budget_per_poll` steps. Returns the number of steps taken.
+    ///
+    /// Dropping the future stops the run; the machine keeps whatever
+    /// configuration it had reached.
+    pub async fn run_async<E>(&mut self, budget_per_poll: u64) -> u64
+    where
+        E: Executor<S, Sym>,
+    {
+        let budget_per_poll = budget_per_poll.max(1);
+        let mut steps = 0;
+
+        while !self.halted() {
+            for _ in 0..budget_per_poll {
+                if self.halted() {
+                    break;
+                }
+
+                self.step::<E>();
+                steps += 1;
+            }
+
+            YieldNow(false).await;
+        }
+
+        steps
+    }
+}
+
+// a future that is pending exactly once, so the runtime gets to schedule
+// other tasks before the run continues
+struct YieldNow(bool);
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            return Poll::Ready(());
+        }
+
+        self.0 = true;
+        cx.waker().wake_by_ref();
+
+        Poll::Pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::pin::pin;
+    use core::task::Waker;
+
+    use super::*;
+    use crate::{Move, Rule};
+
+    struct Walker;
+
+    // walks right over ones and halts on the first blank
+    impl Executor<u8, bool> for Walker {
+        fn execute(_state: &u8, symbol: &bool) -> Rule<u8, bool> {
+            Rule {
+                new_state: (!symbol).then_some(State::Halt),
+                write: None,
+                head_move: Some(Move::Right),
+            }
+        }
+    }
+
+    #[test]
+    fn run_test() {
+        let mut machine: Machine<u8, bool> = Machine::new(0, [true; 10].into());
+        let stats = machine.run::<Walker>(5);
+
+        assert_eq!(stats.steps, 5);
+        assert_eq!(stats.stop, StopReason::StepLimit);
+
+        let stats = machine.run::<Walker>(100);
+
+        assert_eq!(stats.steps, 6);
+        assert_eq!(stats.tape_len, 12);
+        assert_eq!(stats.stop, StopReason::Halted);
+    }
+
+    struct Oracle;
+
+    // walks right like `Walker`, failing on the fifth cell
+    impl TryExecutor<u8, bool> for Oracle {
+        type Error = &'static str;
+
+        fn try_execute(state: &u8, symbol: &bool) -> Result<Rule<u8, bool>, Self::Error> {
+            match *state {
+                4 => Err("the oracle is down"),
+                _ => Ok(Rule {
+                    new_state: Some(State::State(state + 1)),
+                    ..Walker::execute(state, symbol)
+                }),
+            }
+        }
+    }
+
+    #[test]
+    fn try_run_test() {
+        let mut machine: Machine<u8, bool> = Machine::new(0, [true; 10].into());
+
+        assert_eq!(machine.try_run::<Oracle>(3).map(|stats| stats.steps), Ok(3));
+        assert_eq!(machine.try_run::<Oracle>(100), Err("the oracle is down"));
+        assert_eq!(machine.state(), Some(&4));
+        assert_eq!(machine.head(), 4);
+        assert!(machine.try_execute::<Oracle>().is_err());
+    }
+
+    #[test]
+    fn run_until_test() {
+        let mut machine: Machine<u8, bool> = Machine::new(0, [true; 10].into());
+
+        assert_eq!(
+            machine.run_until::<Walker>(100, |machine| machine.head() == 4),
+            Until::Holds { steps: 4 }
+        );
+        assert_eq!(
+            machine.run_until::<Walker>(100, |machine| machine.head() == 4),
+            Until::Holds { steps: 0 }
+        );
+        assert_eq!(
+            machine.run_until::<Walker>(100, |machine| machine.halted()),
+            Until::Holds { steps: 7 }
+        );
+
+        let mut machine: Machine<u8, bool> = Machine::new(0, [true; 10].into());
+
+        assert!(matches!(
+            machine.run_until::<Walker>(3, |_| false),
+            Until::Stopped(RunStats {
+                steps: 3,
+                stop: StopReason::StepLimit,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn run_cancellable_test() {
+        let mut machine: Machine<u8, bool> = Machine::new(0, [true; 10].into());
+        let stats = machine.run_cancellable::<Walker>(100, &AtomicBool::new(true));
+
+        assert_eq!(stats.steps, 0);
+        assert_eq!(stats.stop, StopReason::Cancelled);
+        assert!(!machine.halted());
+
+        // a hook called rarely doesn't hold up cancellation
+        let cancel = AtomicBool::new(false);
+        let mut steps = 0u64;
+        let stats = machine.run_loop(
+            u64::MAX,
+            &cancel,
+            1 << 40,
+            |_| {
+                steps += 1;
+                if steps == 10 {
+                    cancel.store(true, Ordering::Relaxed);
+                }
+                true
+            },
+            |_, _| true,
+        );
+
+        assert_eq!(stats.stop, StopReason::Cancelled);
+        assert_eq!(stats.steps, CANCEL_CHECK_INTERVAL);
+    }
+
+    #[test]
+    fn run_async_test() {
+        let mut machine: Machine<u8, bool> = Machine::new(0, [true; 10].into());
+        let mut cx = Context::from_waker(Waker::noop());
+        let mut polls = 1;
+
+        let steps = {
+            let mut future = pin!(machine.run_async::<Walker>(3));
+
+            loop {
+                match future.as_mut().poll(&mut cx) {
+                    Poll::Ready(steps) => break steps,
+                    Poll::Pending => polls += 1,
+                }
+            }
+        };
+
+        assert_eq!(steps, 11);
+        assert_eq!(polls, 5);
+        assert!(machine.halted());
+    }
+}
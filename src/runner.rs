@@ -0,0 +1,143 @@
+use alloc::collections::VecDeque;
+use core::fmt::{self, Debug};
+use core::marker::PhantomData;
+use core::sync::atomic::AtomicBool;
+
+use crate::{Executor, Machine, RunStats, Subscriptions, Tape};
+
+/// A machine together with the executor running it, chosen once instead of
+/// on every call.
+pub struct Runner<'a, E, S, Sym: Default, T = VecDeque<Sym>> {
+    machine: Machine<S, Sym, T>,
+    subscriptions: Option<Subscriptions<'a, S, Sym>>,
+    steps: u64,
+    executor: PhantomData<fn() -> E>,
+}
+
+impl<'a, E, S, Sym, T> Runner<'a, E, S, Sym, T>
+where
+    E: Executor<S, Sym>,
+    S: PartialEq,
+    Sym: Default + Clone,
+    T: Tape<Sym>,
+{
+    pub fn new(machine: Machine<S, Sym, T>) -> Self {
+        Self {
+            machine,
+            subscriptions: None,
+            steps: 0,
+            executor: PhantomData,
+        }
+    }
+
+    /// Calls the callbacks of `subscriptions` in all later steps and runs,
+    /// numbering steps from the first one of the runner.
+    pub fn observe(mut self, subscriptions: Subscriptions<'a, S, Sym>) -> Self {
+        self.subscriptions = Some(subscriptions);
+        self
+    }
+
+    /// Takes a step, returning `false` if the machine has halted.
+    pub fn step(&mut self) -> bool {
+        self.run(1).steps == 1
+    }
+
+    /// Runs the machine until it halts or `max_steps` more steps were taken.
+    pub fn run(&mut self, max_steps: u64) -> RunStats {
+        self.run_cancellable(max_steps, &AtomicBool::new(false))
+    }
+
+    /// Like [`Runner::run`], but also stops soon after `cancel` is set.
+    pub fn run_cancellable(&mut self, max_steps: u64, cancel: &AtomicBool) -> RunStats {
+        let stats = match &mut self.subscriptions {
+            Some(subscriptions) => self.machine.run_subscribed_with(
+                max_steps,
+                cancel,
+                self.steps,
+                subscriptions,
+                Machine::next_rule::<E>,
+            ),
+            None => self.machine.run_cancellable::<E>(max_steps, cancel),
+        };
+
+        self.steps += stats.steps;
+        stats
+    }
+
+    /// The steps taken by all runs so far.
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    pub fn machine(&self) -> &Machine<S, Sym, T> {
+        &self.machine
+    }
+
+    pub fn into_machine(self) -> Machine<S, Sym, T> {
+        self.machine
+    }
+}
+
+impl<E, S: Debug, Sym: Default + Debug, T: Debug> Debug for Runner<'_, E, S, Sym, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Runner")
+            .field("machine", &self.machine)
+            .field("observed", &self.subscriptions.is_some())
+            .field("steps", &self.steps)
+            .finish()
+    }
+}
+
+impl<S, Sym, T> Machine<S, Sym, T>
+where
+    S: PartialEq,
+    Sym: Default + Clone,
+    T: Tape<Sym>,
+{
+    /// A [`Runner`] running this machine with `E`.
+    pub fn runner<'a, E>(self) -> Runner<'a, E, S, Sym, T>
+    where
+        E: Executor<S, Sym>,
+    {
+        Runner::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::*;
+    use crate::{Rule, StopReason};
+
+    struct Walker;
+
+    // walks right over ones and halts on the first blank
+    impl Executor<u8, bool> for Walker {
+        fn execute(_state: &u8, symbol: &bool) -> Rule<u8, bool> {
+            match symbol {
+                true => Rule::new().right(),
+                false => Rule::halt(),
+            }
+        }
+    }
+
+    #[test]
+    fn runner_test() {
+        let mut growth = Vec::new();
+        let machine: Machine<u8, bool> = Machine::new(0, [true; 3].into());
+        let mut runner = machine
+            .runner::<Walker>()
+            .observe(Subscriptions::new().on_tape_growth(|step, _| growth.push(step)));
+
+        assert!(runner.step());
+        assert_eq!(runner.run(1).steps, 1);
+        assert_eq!(runner.run(100).stop, StopReason::Halted);
+        assert!(!runner.step());
+        assert_eq!(runner.steps(), 4);
+        assert_eq!(runner.machine().head(), 3);
+
+        drop(runner);
+        assert_eq!(growth, [2]);
+    }
+}
@@ -0,0 +1,220 @@
+//! Executors written as [rhai](https://rhai.rs) scripts, for machines
+//! defined at runtime.
+//!
+//! A script is an expression evaluated once per step with the current
+//! `state` and the `symbol` under the head in scope, both strings. It
+//! evaluates to the rule to apply as a map with any of the keys `write` (a
+//! one-character string), `move` (`"L"`, `"R"` or `"N"`, or `"L"` or `"R"`
+//! with a distance such as `"R3"`) and `next` (a state, or `"halt"`), e.g.
+//!
+//! ```text
+//! if symbol == "1" { #{ write: "0", move: "L" } } else { #{ write: "1", next: "halt" } }
+//! ```
+//!
+//! A script evaluating to `()` halts the machine without touching the
+//! tape.
+
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::sync::atomic::AtomicBool;
+
+use rhai::{Dynamic, Engine, EvalAltResult, Map, Scope, AST};
+
+use crate::run::CANCEL_CHECK_INTERVAL;
+use crate::{parse_move, Char, Machine, Rule, RunStats, State, Symbol, Tape};
+
+#[derive(Debug)]
+pub enum ScriptError {
+    Parse(rhai::ParseError),
+    Eval(Box<EvalAltResult>),
+    /// The script evaluated to something that isn't a rule.
+    Rule(String),
+}
+
+impl Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScriptError::Parse(error) => write!(f, "invalid script: {error}"),
+            ScriptError::Eval(error) => write!(f, "script failed: {error}"),
+            ScriptError::Rule(message) => write!(f, "invalid rule: {message}"),
+        }
+    }
+}
+
+impl Error for ScriptError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ScriptError::Parse(error) => Some(error),
+            ScriptError::Eval(error) => Some(error),
+            ScriptError::Rule(_) => None,
+        }
+    }
+}
+
+/// A compiled script giving the rule of every step.
+#[derive(Debug)]
+pub struct Script {
+    engine: Engine,
+    ast: AST,
+}
+
+impl Script {
+    pub fn new(script: &str) -> Result<Self, ScriptError> {
+        let engine = Engine::new();
+        let ast = engine
+            .compile_expression(script)
+            .map_err(ScriptError::Parse)?;
+
+        Ok(Self { engine, ast })
+    }
+
+    /// The rule for reading `symbol` in `state`.
+    pub fn rule(&self, state: &str, symbol: Char) -> Result<Rule<String, Char>, ScriptError> {
+        let mut scope = Scope::new();
+        scope.push("state", state.to_owned());
+        scope.push("symbol", symbol.to_char().to_string());
+
+        let value: Dynamic = self
+            .engine
+            .eval_ast_with_scope(&mut scope, &self.ast)
+            .map_err(ScriptError::Eval)?;
+
+        to_rule(value)
+    }
+}
+
+fn to_rule(value: Dynamic) -> Result<Rule<String, Char>, ScriptError> {
+    if value.is_unit() {
+        return Ok(Rule::halt());
+    }
+
+    let type_name = value.type_name();
+    let map = value
+        .try_cast::<Map>()
+        .ok_or_else(|| ScriptError::Rule(format!("expected a map, not {type_name}")))?;
+
+    if let Some(key) = map
+        .keys()
+        .find(|key| !["write", "move", "next"].contains(&key.as_str()))
+    {
+        return Err(ScriptError::Rule(format!("unknown key {key:?}")));
+    }
+
+    let text = |key: &str| match map.get(key) {
+        Some(value) => value.clone().into_string().map(Some).map_err(|type_name| {
+            ScriptError::Rule(format!("{key} must be a string, not {type_name}"))
+        }),
+        None => Ok(None),
+    };
+
+    let write = text("write")?
+        .map(|write| {
+            let mut chars = write.chars();
+
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Char::from_char(c),
+                _ => None,
+            }
+            .ok_or_else(|| ScriptError::Rule(format!("invalid symbol {write:?}")))
+        })
+        .transpose()?;
+    let head_move = match text("move")? {
+        Some(name) => {
+            parse_move(&name).ok_or_else(|| ScriptError::Rule(format!("invalid move {name:?}")))?
+        }
+        None => None,
+    };
+    let new_state = text("next")?.map(|next| match next.as_str() {
+        "halt" => State::Halt,
+        _ => State::State(next),
+    });
+
+    Ok(Rule {
+        new_state,
+        write,
+        head_move,
+    })
+}
+
+impl<T: Tape<Char>> Machine<String, Char, T> {
+    /// Runs the machine with the rules `script` gives until it halts or
+    /// `max_steps` steps were taken, or the script fails. A failed step
+    /// leaves the machine as it was before it.
+    pub fn run_script(&mut self, script: &Script, max_steps: u64) -> Result<RunStats, ScriptError> {
+        let mut error = None;
+
+        let stats = self.run_loop(
+            max_steps,
+            &AtomicBool::new(false),
+            CANCEL_CHECK_INTERVAL,
+            |machine| {
+                let State::State(state) = &machine.state else {
+                    return false;
+                };
+
+                match script.rule(state, *machine.current_symbol()) {
+                    Ok(rule) => {
+                        machine.apply(rule);
+                        true
+                    }
+                    Err(failure) => {
+                        error = Some(failure);
+                        false
+                    }
+                }
+            },
+            |_, _| true,
+        );
+
+        match error {
+            Some(error) => Err(error),
+            None => Ok(stats),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::StopReason;
+
+    #[test]
+    fn run_script_test() {
+        // walks to the end of a binary number and adds one to it
+        let script = Script::new(
+            r#"
+            if state == "right" {
+                if symbol == "_" { #{ move: "L", next: "inc" } } else { #{ move: "R" } }
+            } else if symbol == "1" {
+                #{ write: "0", move: "L" }
+            } else {
+                #{ write: "1", next: "halt" }
+            }
+            "#,
+        )
+        .unwrap();
+
+        let mut machine = Machine::with_input("right".to_owned(), "1011").unwrap();
+        let stats = machine.run_script(&script, 100).unwrap();
+        let tape: String = machine.tape_to_vec().iter().map(Symbol::to_char).collect();
+
+        assert_eq!(stats.stop, StopReason::Halted);
+        assert_eq!(tape.trim_matches('_'), "1100");
+    }
+
+    #[test]
+    fn script_error_test() {
+        let mut machine = Machine::with_input("a".to_owned(), "1").unwrap();
+
+        assert!(matches!(
+            machine.run_script(&Script::new("42").unwrap(), 10),
+            Err(ScriptError::Rule(_))
+        ));
+        assert!(matches!(
+            machine.run_script(&Script::new(r#"#{ move: "U" }"#).unwrap(), 10),
+            Err(ScriptError::Rule(_))
+        ));
+        assert!(matches!(Script::new("if {"), Err(ScriptError::Parse(_))));
+        assert_eq!(machine.state(), Some(&"a".to_owned()));
+    }
+}
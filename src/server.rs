@@ -0,0 +1,698 @@
+//! A JSON-over-HTTP API for driving machines remotely.
+//!
+//! | method   | path                   | body                                 |
+//! |----------|------------------------|--------------------------------------|
+//! | `POST`   | `/machines`            | `{"source", "format"?, "input"?}`    |
+//! | `GET`    | `/machines/{id}`       |                                      |
+//! | `DELETE` | `/machines/{id}`       |                                      |
+//! | `POST`   | `/machines/{id}/reset` | `{"input"?}`                         |
+//! | `POST`   | `/machines/{id}/step`  | `{"count"?}`                         |
+//! | `POST`   | `/machines/{id}/run`   | `{"max_steps"?}`                     |
+//! | `GET`    | `/machines/{id}/trace` |                                      |
+//! | `GET`    | `/machines/{id}/live`  | WebSocket, see below                 |
+//!
+//! `source` is a definition in `format` (`dsl`, `yaml`, `jflap` or
+//! `bbchallenge`; `dsl` by default) over [`Char`] symbols. Every endpoint but
+//! `DELETE`, `trace` and `live` answers with the machine's
+//! [`Configuration`]; errors are answered with `{"error"}` and a 4xx status.
+//! Step counts are capped at [`MAX_STEPS`].
+//!
+//! `live` streams [`Event`]s as JSON text messages: the configuration when
+//! connecting and after every change to the machine, however it was made,
+//! and `{"event": "removed"}` before closing when the machine is deleted.
+//! Clients may send `{"step": count}` and `{"run": max_steps, "every": n}`,
+//! which runs in the background, streaming the configuration every `n`
+//! steps (every [`DEFAULT_LIVE_EVERY`] by default).
+
+use std::collections::{HashMap, VecDeque};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use axum::extract::ws::{self, WebSocket, WebSocketUpgrade};
+use axum::extract::{Path, State as Extract};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use crate::dsl::{self, Definition};
+use crate::format::{bbchallenge, jflap, yaml};
+use crate::{Char, Machine, StopReason, Symbol};
+
+/// How many steps `run` takes without an explicit `max_steps`.
+pub const DEFAULT_MAX_STEPS: u64 = 1_000_000;
+
+/// The most steps a `step` or `run`, over HTTP or `live`, takes; larger
+/// counts are capped to it.
+pub const MAX_STEPS: u64 = 100_000_000;
+
+// how many steps a request takes while holding the lock on the sessions
+const CHUNK: u64 = 10_000;
+
+/// How many of the most recent steps `trace` returns.
+pub const TRACE_LIMIT: usize = 10_000;
+
+/// How many steps a `live` run takes between configurations.
+pub const DEFAULT_LIVE_EVERY: u64 = 10_000;
+
+// how many events a slow `live` client may fall behind before missing some
+const EVENT_CAPACITY: usize = 64;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Configuration {
+    pub id: u64,
+    /// `None` once halted.
+    pub state: Option<String>,
+    pub tape: String,
+    /// The head position relative to the first input cell.
+    pub head: isize,
+    pub steps: u64,
+    /// Why the last `run` stopped, if the last request was a `run`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TraceEntry {
+    pub step: u64,
+    pub state: String,
+    pub head: isize,
+    pub read: char,
+}
+
+/// A message streamed by `live`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    Configuration(Configuration),
+    Removed {
+        id: u64,
+    },
+    /// A message from the client that isn't a command.
+    Error {
+        error: String,
+    },
+}
+
+#[derive(Deserialize)]
+struct Upload {
+    source: String,
+    format: Option<String>,
+    #[serde(default)]
+    input: String,
+}
+
+#[derive(Deserialize)]
+struct Reset {
+    #[serde(default)]
+    input: String,
+}
+
+#[derive(Deserialize)]
+struct Step {
+    count: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct Run {
+    max_steps: Option<u64>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Command {
+    Step { step: u64 },
+    Run { run: u64, every: Option<u64> },
+}
+
+struct Session {
+    definition: Definition<Char>,
+    machine: Machine<String, Char>,
+    steps: u64,
+    // whether the table had no rule for the configuration
+    stuck: bool,
+    trace: VecDeque<TraceEntry>,
+    events: broadcast::Sender<Event>,
+}
+
+impl Session {
+    fn new(definition: Definition<Char>, input: &str) -> Result<Self, Error> {
+        definition
+            .check_input(input)
+            .map_err(|e| Error::bad_request(e.to_string()))?;
+
+        let blank = definition.blank.unwrap_or_default();
+        let machine = Machine::with_input_blank(definition.start.clone(), input, blank)
+            .ok_or_else(|| Error::bad_request("invalid input"))?;
+
+        Ok(Self {
+            definition,
+            machine,
+            steps: 0,
+            stuck: false,
+            trace: VecDeque::new(),
+            events: broadcast::channel(EVENT_CAPACITY).0,
+        })
+    }
+
+    // tells `live` clients about the configuration; no clients is fine
+    fn publish(&self, configuration: &Configuration) {
+        let _ = self
+            .events
+            .send(Event::Configuration(configuration.clone()));
+    }
+
+    fn step(&mut self, count: u64) {
+        for _ in 0..count {
+            let Some(state) = self.machine.state() else {
+                break;
+            };
+
+            let entry = TraceEntry {
+                step: self.steps,
+                state: state.clone(),
+                head: self.head(),
+                read: self.machine.current_symbol().to_char(),
+            };
+
+            // no rule under `MissingRule::Error` leaves the machine as it is
+            if self.machine.execute_table(&self.definition.table).no_rule {
+                self.stuck = true;
+                break;
+            }
+
+            if self.trace.len() == TRACE_LIMIT {
+                self.trace.pop_front();
+            }
+
+            self.trace.push_back(entry);
+            self.steps += 1;
+        }
+    }
+
+    fn finished(&self) -> bool {
+        self.machine.halted() || self.stuck
+    }
+
+    fn head(&self) -> isize {
+        self.machine.head() as isize - self.machine.origin() as isize
+    }
+
+    // the configuration at the end of a run
+    fn stopped(&self, id: u64) -> Configuration {
+        let stop = match (self.machine.halted(), self.stuck) {
+            (true, _) => StopReason::Halted,
+            (false, true) => StopReason::NoRule,
+            (false, false) => StopReason::StepLimit,
+        };
+
+        Configuration {
+            stop: Some(format!("{stop:?}")),
+            ..self.configuration(id)
+        }
+    }
+
+    fn configuration(&self, id: u64) -> Configuration {
+        Configuration {
+            id,
+            state: self.machine.state().cloned(),
+            tape: self.machine.peek().iter().map(Symbol::to_char).collect(),
+            head: self.head(),
+            steps: self.steps,
+            stop: None,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Sessions {
+    next_id: u64,
+    sessions: HashMap<u64, Session>,
+}
+
+type Shared = Arc<Mutex<Sessions>>;
+
+struct Error(StatusCode, String);
+
+impl Error {
+    fn bad_request(message: impl Into<String>) -> Self {
+        Self(StatusCode::BAD_REQUEST, message.into())
+    }
+
+    fn not_found(id: u64) -> Self {
+        Self(StatusCode::NOT_FOUND, format!("no machine {id}"))
+    }
+}
+
+impl IntoResponse for Error {
+    fn into_response(self) -> Response {
+        #[derive(Serialize)]
+        struct Body {
+            error: String,
+        }
+
+        (self.0, Json(Body { error: self.1 })).into_response()
+    }
+}
+
+/// The API's routes, with an empty set of machines.
+pub fn router() -> Router {
+    Router::new()
+        .route("/machines", post(upload))
+        .route("/machines/{id}", get(configuration).delete(remove))
+        .route("/machines/{id}/reset", post(reset))
+        .route("/machines/{id}/step", post(step))
+        .route("/machines/{id}/run", post(run))
+        .route("/machines/{id}/trace", get(trace))
+        .route("/machines/{id}/live", get(live))
+        .with_state(Shared::default())
+}
+
+/// Serves [`router`] on `addr` until the process is stopped.
+pub async fn serve(addr: SocketAddr) -> std::io::Result<()> {
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+
+    axum::serve(listener, router()).await
+}
+
+fn with_session<R>(
+    shared: &Shared,
+    id: u64,
+    f: impl FnOnce(&mut Session) -> R,
+) -> Result<R, Error> {
+    let mut sessions = shared.lock().unwrap();
+
+    sessions
+        .sessions
+        .get_mut(&id)
+        .map(f)
+        .ok_or(Error::not_found(id))
+}
+
+async fn upload(
+    Extract(shared): Extract<Shared>,
+    Json(upload): Json<Upload>,
+) -> Result<Json<Configuration>, Error> {
+    let definition = match upload.format.as_deref().unwrap_or("dsl") {
+        "dsl" => dsl::parse_definition(&upload.source),
+        "yaml" => yaml::parse(&upload.source),
+        "jflap" => jflap::parse(&upload.source),
+        "bbchallenge" => bbchallenge::parse(&upload.source),
+        format => return Err(Error::bad_request(format!("unknown format {format:?}"))),
+    }
+    .map_err(|e| Error::bad_request(e.to_string()))?;
+
+    let session = Session::new(definition, &upload.input)?;
+    let mut sessions = shared.lock().unwrap();
+    let id = sessions.next_id;
+    let configuration = session.configuration(id);
+
+    sessions.next_id += 1;
+    sessions.sessions.insert(id, session);
+
+    Ok(Json(configuration))
+}
+
+async fn configuration(
+    Extract(shared): Extract<Shared>,
+    Path(id): Path<u64>,
+) -> Result<Json<Configuration>, Error> {
+    with_session(&shared, id, |session| Json(session.configuration(id)))
+}
+
+async fn remove(
+    Extract(shared): Extract<Shared>,
+    Path(id): Path<u64>,
+) -> Result<StatusCode, Error> {
+    let mut sessions = shared.lock().unwrap();
+
+    match sessions.sessions.remove(&id) {
+        Some(session) => {
+            let _ = session.events.send(Event::Removed { id });
+            Ok(StatusCode::NO_CONTENT)
+        }
+        None => Err(Error::not_found(id)),
+    }
+}
+
+async fn reset(
+    Extract(shared): Extract<Shared>,
+    Path(id): Path<u64>,
+    Json(reset): Json<Reset>,
+) -> Result<Json<Configuration>, Error> {
+    with_session(&shared, id, |session| {
+        let events = session.events.clone();
+        *session = Session {
+            events,
+            ..Session::new(session.definition.clone(), &reset.input)?
+        };
+
+        let configuration = session.configuration(id);
+        session.publish(&configuration);
+
+        Ok(Json(configuration))
+    })?
+}
+
+async fn step(
+    Extract(shared): Extract<Shared>,
+    Path(id): Path<u64>,
+    Json(step): Json<Step>,
+) -> Result<Json<Configuration>, Error> {
+    let count = step.count.unwrap_or(1);
+
+    run_chunked(&shared, id, count, u64::MAX, false)
+        .await
+        .map(Json)
+}
+
+async fn run(
+    Extract(shared): Extract<Shared>,
+    Path(id): Path<u64>,
+    Json(run): Json<Run>,
+) -> Result<Json<Configuration>, Error> {
+    let max_steps = run.max_steps.unwrap_or(DEFAULT_MAX_STEPS);
+
+    run_chunked(&shared, id, max_steps, u64::MAX, true)
+        .await
+        .map(Json)
+}
+
+async fn trace(
+    Extract(shared): Extract<Shared>,
+    Path(id): Path<u64>,
+) -> Result<Json<Vec<TraceEntry>>, Error> {
+    with_session(&shared, id, |session| {
+        Json(session.trace.iter().cloned().collect())
+    })
+}
+
+async fn live(
+    Extract(shared): Extract<Shared>,
+    Path(id): Path<u64>,
+    upgrade: WebSocketUpgrade,
+) -> Result<Response, Error> {
+    let (configuration, events) = with_session(&shared, id, |session| {
+        (session.configuration(id), session.events.subscribe())
+    })?;
+
+    Ok(upgrade.on_upgrade(move |socket| stream(socket, shared, id, configuration, events)))
+}
+
+async fn stream(
+    mut socket: WebSocket,
+    shared: Shared,
+    id: u64,
+    configuration: Configuration,
+    mut events: broadcast::Receiver<Event>,
+) {
+    if send(&mut socket, &Event::Configuration(configuration))
+        .await
+        .is_err()
+    {
+        return;
+    }
+
+    loop {
+        tokio::select! {
+            event = events.recv() => {
+                let event = match event {
+                    Ok(event) => event,
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => return,
+                };
+
+                let removed = matches!(event, Event::Removed { .. });
+
+                if send(&mut socket, &event).await.is_err() || removed {
+                    return;
+                }
+            }
+            message = socket.recv() => {
+                let text = match message {
+                    Some(Ok(ws::Message::Text(text))) => text,
+                    Some(Ok(ws::Message::Close(_)) | Err(_)) | None => return,
+                    Some(Ok(_)) => continue,
+                };
+
+                let error = match serde_json::from_str(&text) {
+                    Ok(Command::Step { step }) => {
+                        run_chunked(&shared, id, step, u64::MAX, false).await.err()
+                    }
+                    Ok(Command::Run { run, every }) => {
+                        let every = every.unwrap_or(DEFAULT_LIVE_EVERY).max(1);
+                        tokio::spawn(run_live(shared.clone(), id, run, every));
+                        None
+                    }
+                    Err(error) => Some(Error::bad_request(error.to_string())),
+                };
+
+                if let Some(Error(_, error)) = error {
+                    if send(&mut socket, &Event::Error { error }).await.is_err() {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+}
+
+// runs in chunks of `every` steps, publishing the configuration after each
+async fn run_live(shared: Shared, id: u64, max_steps: u64, every: u64) {
+    let _ = run_chunked(&shared, id, max_steps, every, true).await;
+}
+
+// steps session `id` up to `count` steps, at most `MAX_STEPS`, taking the
+// lock for `CHUNK` steps at a time so that other requests get it in between.
+// Publishes the configuration every `every` steps and at the end, then
+// returns it, with why the run stopped if `stopped`
+async fn run_chunked(
+    shared: &Shared,
+    id: u64,
+    count: u64,
+    every: u64,
+    stopped: bool,
+) -> Result<Configuration, Error> {
+    let count = count.min(MAX_STEPS);
+    let mut steps = 0;
+
+    loop {
+        let done = with_session(shared, id, |session| {
+            let chunk = CHUNK.min(every - steps % every).min(count - steps);
+            session.step(chunk);
+            steps += chunk;
+
+            if steps == count || session.finished() {
+                let configuration = match stopped {
+                    true => session.stopped(id),
+                    false => session.configuration(id),
+                };
+                session.publish(&configuration);
+
+                return Some(configuration);
+            }
+
+            if steps % every == 0 {
+                session.publish(&session.configuration(id));
+            }
+
+            None
+        })?;
+
+        if let Some(configuration) = done {
+            return Ok(configuration);
+        }
+
+        tokio::task::yield_now().await;
+    }
+}
+
+async fn send(socket: &mut WebSocket, event: &Event) -> Result<(), axum::Error> {
+    let text = serde_json::to_string(event).expect("events serialize");
+
+    socket.send(ws::Message::Text(text.into())).await
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::{self, Body};
+    use axum::http::Request;
+    use futures_util::{SinkExt, StreamExt};
+    use tokio_tungstenite::tungstenite;
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::MissingRule;
+
+    async fn request(
+        router: &Router,
+        method: &str,
+        uri: &str,
+        body: &str,
+    ) -> (StatusCode, serde_json::Value) {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_owned()))
+            .unwrap();
+        let response = router.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+
+        (
+            status,
+            serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null),
+        )
+    }
+
+    #[tokio::test]
+    async fn upload_step_run_test() {
+        let router = router();
+        let source = r#"{"source": "inc 1 0 R *\ninc _ 1 N halt\ninc 0 1 N halt", "input": "11"}"#;
+
+        let (status, machine) = request(&router, "POST", "/machines", source).await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(machine["state"], "inc");
+        assert_eq!(machine["tape"], "11");
+
+        let (_, machine) = request(&router, "POST", "/machines/0/step", "{}").await;
+
+        assert_eq!(machine["tape"], "01");
+        assert_eq!(machine["head"], 1);
+
+        let (_, machine) = request(&router, "POST", "/machines/0/run", "{}").await;
+
+        assert_eq!(machine["state"], serde_json::Value::Null);
+        assert_eq!(machine["tape"], "001");
+        assert_eq!(machine["steps"], 3);
+        assert_eq!(machine["stop"], "Halted");
+
+        let (_, trace) = request(&router, "GET", "/machines/0/trace", "").await;
+
+        assert_eq!(trace.as_array().unwrap().len(), 3);
+        assert_eq!(trace[2]["read"], "_");
+
+        let (_, machine) = request(&router, "POST", "/machines/0/reset", r#"{"input": "0"}"#).await;
+
+        assert_eq!(machine["tape"], "0");
+        assert_eq!(machine["steps"], 0);
+    }
+
+    #[tokio::test]
+    async fn run_chunked_test() {
+        let router = router();
+        let source = r#"{"source": "a _ _ R *\na 1 1 R *", "input": "1"}"#;
+        request(&router, "POST", "/machines", source).await;
+
+        let (_, machine) = request(
+            &router,
+            "POST",
+            "/machines/0/run",
+            r#"{"max_steps": 25001}"#,
+        )
+        .await;
+
+        assert_eq!(machine["steps"], 25_001);
+        assert_eq!(machine["stop"], "StepLimit");
+
+        // no rule under `MissingRule::Error` is neither a step nor a step limit
+        let mut definition = dsl::parse_definition("a 1 1 R b").unwrap();
+        definition.table.set_missing(MissingRule::Error);
+        let Ok(mut session) = Session::new(definition, "1") else {
+            panic!("invalid input");
+        };
+        session.step(5);
+
+        assert_eq!(session.steps, 1);
+        assert_eq!(session.trace.len(), 1);
+        assert_eq!(session.stopped(0).stop.as_deref(), Some("NoRule"));
+    }
+
+    #[tokio::test]
+    async fn error_test() {
+        let router = router();
+
+        let (status, error) = request(&router, "GET", "/machines/7", "").await;
+
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(error["error"], "no machine 7");
+
+        let (status, _) = request(&router, "POST", "/machines", r#"{"source": "a b"}"#).await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (status, _) = request(&router, "DELETE", "/machines/0", "").await;
+
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn live_test() {
+        let router = router();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let served = router.clone();
+        tokio::spawn(async move { axum::serve(listener, served).await });
+
+        let source = r#"{"source": "inc 1 0 R *\ninc _ 1 N halt", "input": "1111"}"#;
+        request(&router, "POST", "/machines", source).await;
+
+        let (mut socket, _) =
+            tokio_tungstenite::connect_async(format!("ws://{addr}/machines/0/live"))
+                .await
+                .unwrap();
+        let mut next = async || loop {
+            if let tungstenite::Message::Text(text) = socket.next().await.unwrap().unwrap() {
+                return serde_json::from_str::<Event>(&text).unwrap();
+            }
+        };
+        let steps = |event: Event| match event {
+            Event::Configuration(configuration) => (configuration.steps, configuration.stop),
+            event => panic!("unexpected {event:?}"),
+        };
+
+        assert_eq!(steps(next().await), (0, None));
+
+        // changes made over HTTP are streamed too
+        request(&router, "POST", "/machines/0/step", "{}").await;
+        assert_eq!(steps(next().await), (1, None));
+
+        socket
+            .send(tungstenite::Message::text(r#"{"run": 10, "every": 2}"#))
+            .await
+            .unwrap();
+        socket.send(tungstenite::Message::text("{}")).await.unwrap();
+
+        let mut events = Vec::new();
+
+        while let Some(Ok(message)) = socket.next().await {
+            if let tungstenite::Message::Text(text) = message {
+                let event: Event = serde_json::from_str(&text).unwrap();
+
+                if let Event::Configuration(Configuration { stop: Some(_), .. }) = event {
+                    request(&router, "DELETE", "/machines/0", "").await;
+                }
+
+                events.push(event);
+            }
+        }
+
+        assert!(events.contains(&Event::Removed { id: 0 }));
+        assert!(events
+            .iter()
+            .any(|event| matches!(event, Event::Error { .. })));
+
+        let configurations: Vec<_> = events
+            .into_iter()
+            .filter(|event| matches!(event, Event::Configuration(_)))
+            .map(steps)
+            .collect();
+        assert_eq!(configurations, [(3, None), (5, Some("Halted".to_owned()))]);
+    }
+}
@@ -0,0 +1,135 @@
+use alloc::vec::Vec;
+use core::mem;
+
+use crate::{Machine, State, Tape};
+
+/// What changed between two [`Snapshot`]s, see [`Snapshot::diff`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TapeDiff<S, Sym> {
+    /// The cells that differ, as their position relative to the initial cell
+    /// 0 and their symbols before and after.
+    pub cells: Vec<(isize, Sym, Sym)>,
+    /// How far the head moved right, negative for left.
+    pub head_move: isize,
+    /// The states before and after, if they differ.
+    pub state: Option<(State<S>, State<S>)>,
+}
+
+impl<S, Sym> TapeDiff<S, Sym> {
+    /// Whether the configurations are the same.
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty() && self.head_move == 0 && self.state.is_none()
+    }
+}
+
+/// An owned copy of a machine's configuration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot<S, Sym> {
+    pub state: State<S>,
+    pub tape: Vec<Sym>,
+    pub head: usize,
+    /// Index of the initial cell 0 within `tape`.
+    pub origin: usize,
+}
+
+impl<S, Sym, T> Machine<S, Sym, T>
+where
+    Sym: Default,
+    T: Tape<Sym>,
+{
+    pub fn snapshot(&self) -> Snapshot<S, Sym>
+    where
+        S: Clone,
+        Sym: Clone,
+    {
+        Snapshot {
+            state: self.state.clone(),
+            tape: (0..self.tape.len())
+                .filter_map(|index| self.tape.get(index).cloned())
+                .collect(),
+            head: self.head,
+            origin: self.origin,
+        }
+    }
+}
+
+impl<S, Sym> Snapshot<S, Sym> {
+    /// The bytes this snapshot takes, its tape's buffer included.
+    pub fn memory_bytes(&self) -> usize {
+        mem::size_of::<Self>() + self.tape.capacity() * mem::size_of::<Sym>()
+    }
+
+    /// The head position relative to the initial cell 0.
+    pub fn position(&self) -> isize {
+        self.head as isize - self.origin as isize
+    }
+
+    /// The changes from this configuration to `other`. Tapes are lined up on
+    /// their initial cell 0, with cells past either end read as blanks.
+    pub fn diff(&self, other: &Self) -> TapeDiff<S, Sym>
+    where
+        S: Clone + PartialEq,
+        Sym: Clone + PartialEq + Default,
+    {
+        let blank = Sym::default();
+        let start = -(self.origin.max(other.origin) as isize);
+        let end = (self.tape.len() - self.origin).max(other.tape.len() - other.origin) as isize;
+        let cells = (start..end)
+            .filter_map(|position| {
+                let (before, after) = (self.cell(position, &blank), other.cell(position, &blank));
+                (before != after).then(|| (position, before.clone(), after.clone()))
+            })
+            .collect();
+
+        TapeDiff {
+            cells,
+            head_move: other.position() - self.position(),
+            state: (self.state != other.state).then(|| (self.state.clone(), other.state.clone())),
+        }
+    }
+
+    // the cell at `position`, or `blank` past either end
+    fn cell<'a>(&'a self, position: isize, blank: &'a Sym) -> &'a Sym {
+        usize::try_from(position + self.origin as isize)
+            .ok()
+            .and_then(|index| self.tape.get(index))
+            .unwrap_or(blank)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::borrow::ToOwned;
+
+    use super::*;
+    use crate::{dsl, Char};
+
+    #[test]
+    fn diff_test() {
+        let table = dsl::parse::<Char>(
+            "inc 1 0 L *
+inc _ 1 N halt",
+        )
+        .unwrap();
+        let mut machine = Machine::with_input("inc".to_owned(), "11").unwrap();
+        machine.set_head(1);
+        let before = machine.snapshot();
+
+        machine.run_table(&table, 2);
+        let diff = before.diff(&machine.snapshot());
+
+        assert_eq!(
+            diff.cells,
+            [(0, Char('1'), Char('0')), (1, Char('1'), Char('0'))]
+        );
+        assert_eq!(diff.head_move, -2);
+        assert_eq!(diff.state, None);
+
+        machine.run_table(&table, 1);
+        let diff = before.diff(&machine.snapshot());
+
+        assert_eq!(diff.cells[0], (-1, Char('_'), Char('1')));
+        assert!(diff.state.is_some());
+        assert!(before.diff(&before).is_empty());
+    }
+}
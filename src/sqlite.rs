@@ -0,0 +1,282 @@
+//! Trace storage in SQLite, for runs too long to keep their traces in
+//! memory.
+//!
+//! Steps go into a single `trace` table with one
+//! `(step, state, head, read, write, move)` row per step, the same columns
+//! as [`CsvTrace`](crate::CsvTrace), indexed on `step` and on
+//! `(state, step)`.
+
+use std::fmt::Display;
+use std::ops::Range;
+use std::path::Path;
+
+use rusqlite::{params, Connection, Result, Row};
+
+use crate::{move_name, parse_move, Move, Symbol, TraceSink, TraceStep};
+
+/// How many steps [`SqliteTrace`] buffers before writing them out.
+pub const DEFAULT_BATCH_SIZE: usize = 10_000;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS trace (
+        step INTEGER PRIMARY KEY,
+        state TEXT NOT NULL,
+        head INTEGER NOT NULL,
+        read TEXT NOT NULL,
+        write TEXT NOT NULL,
+        move TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS trace_state ON trace (state, step);
+";
+
+/// A stored step of a run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceRow {
+    pub step: u64,
+    pub state: String,
+    pub head: isize,
+    pub read: char,
+    /// The symbol left in the cell.
+    pub write: char,
+    pub head_move: Option<Move>,
+}
+
+impl TraceRow {
+    fn from_row(row: &Row<'_>) -> Result<Self> {
+        let single = |text: String| text.chars().next().unwrap_or_default();
+        let head_move: String = row.get(5)?;
+
+        Ok(Self {
+            step: row.get::<_, i64>(0)? as u64,
+            state: row.get(1)?,
+            head: row.get::<_, i64>(2)? as isize,
+            read: single(row.get(3)?),
+            write: single(row.get(4)?),
+            head_move: parse_move(&head_move).flatten(),
+        })
+    }
+}
+
+/// A [`TraceSink`] writing steps into SQLite in batches, one transaction per
+/// batch.
+///
+/// Any buffered steps are lost unless [`SqliteTrace::finish`] or
+/// [`SqliteTrace::flush`] is called after the run.
+#[derive(Debug)]
+pub struct SqliteTrace {
+    connection: Connection,
+    batch: Vec<TraceRow>,
+    batch_size: usize,
+    error: Option<rusqlite::Error>,
+}
+
+impl SqliteTrace {
+    /// Opens or creates the database at `path`, appending to any trace
+    /// already stored there.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    pub fn in_memory() -> Result<Self> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    pub fn with_connection(connection: Connection) -> Result<Self> {
+        connection.execute_batch(SCHEMA)?;
+
+        Ok(Self {
+            connection,
+            batch: Vec::new(),
+            batch_size: DEFAULT_BATCH_SIZE,
+            error: None,
+        })
+    }
+
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Writes out the buffered steps.
+    pub fn flush(&mut self) -> Result<()> {
+        if let Some(error) = self.error.take() {
+            return Err(error);
+        }
+
+        let transaction = self.connection.transaction()?;
+
+        {
+            let mut insert = transaction.prepare_cached(
+                "INSERT INTO trace (step, state, head, read, write, move)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            )?;
+
+            for row in self.batch.drain(..) {
+                let head_move = move_name(row.head_move);
+
+                insert.execute(params![
+                    row.step as i64,
+                    row.state,
+                    row.head as i64,
+                    row.read.to_string(),
+                    row.write.to_string(),
+                    head_move,
+                ])?;
+            }
+        }
+
+        transaction.commit()
+    }
+
+    /// Writes out the buffered steps and returns the stored trace for
+    /// querying.
+    pub fn finish(mut self) -> Result<TraceQuery> {
+        self.flush()?;
+
+        Ok(TraceQuery {
+            connection: self.connection,
+        })
+    }
+}
+
+impl<S, Sym> TraceSink<S, Sym> for SqliteTrace
+where
+    S: Display,
+    Sym: Symbol,
+{
+    fn record(&mut self, step: &TraceStep<'_, S, Sym>) -> bool {
+        self.batch.push(TraceRow {
+            step: step.step,
+            state: step.state.to_string(),
+            head: step.head,
+            read: step.read.to_char(),
+            write: step.rule.write.as_ref().unwrap_or(step.read).to_char(),
+            head_move: step.rule.head_move,
+        });
+
+        if self.batch.len() >= self.batch_size {
+            if let Err(error) = self.flush() {
+                self.error = Some(error);
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+/// Read access to a trace stored by [`SqliteTrace`].
+#[derive(Debug)]
+pub struct TraceQuery {
+    connection: Connection,
+}
+
+impl TraceQuery {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self {
+            connection: Connection::open(path)?,
+        })
+    }
+
+    /// The number of stored steps.
+    pub fn len(&self) -> Result<u64> {
+        self.connection
+            .query_row("SELECT COUNT(*) FROM trace", [], |row| row.get::<_, i64>(0))
+            .map(|count| count as u64)
+    }
+
+    pub fn is_empty(&self) -> Result<bool> {
+        self.len().map(|len| len == 0)
+    }
+
+    pub fn step(&self, step: u64) -> Result<Option<TraceRow>> {
+        let mut rows = self.query("SELECT * FROM trace WHERE step = ?1", params![step as i64])?;
+
+        Ok(rows.pop())
+    }
+
+    /// The stored steps within `steps`, in order.
+    pub fn range(&self, steps: Range<u64>) -> Result<Vec<TraceRow>> {
+        self.query(
+            "SELECT * FROM trace WHERE step >= ?1 AND step < ?2 ORDER BY step",
+            params![steps.start as i64, steps.end as i64],
+        )
+    }
+
+    /// The first `limit` steps taken in `state`, in order.
+    pub fn in_state(&self, state: &str, limit: usize) -> Result<Vec<TraceRow>> {
+        self.query(
+            "SELECT * FROM trace WHERE state = ?1 ORDER BY step LIMIT ?2",
+            params![state, limit as i64],
+        )
+    }
+
+    /// How many steps were taken in each state, most frequent first.
+    pub fn state_counts(&self) -> Result<Vec<(String, u64)>> {
+        let mut statement = self.connection.prepare(
+            "SELECT state, COUNT(*) AS count FROM trace GROUP BY state ORDER BY count DESC, state",
+        )?;
+
+        let counts = statement
+            .query_map([], |row| Ok((row.get(0)?, row.get::<_, i64>(1)? as u64)))?
+            .collect();
+
+        counts
+    }
+
+    /// The underlying connection, for queries not covered here.
+    pub fn connection(&self) -> &Connection {
+        &self.connection
+    }
+
+    fn query(&self, sql: &str, params: impl rusqlite::Params) -> Result<Vec<TraceRow>> {
+        let mut statement = self.connection.prepare_cached(sql)?;
+
+        let rows = statement.query_map(params, TraceRow::from_row)?.collect();
+
+        rows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{dsl, Char, Machine};
+
+    #[test]
+    fn sqlite_trace_test() {
+        let table = dsl::parse("inc 1 0 R *\ninc _ 1 N halt").unwrap();
+        let mut machine: Machine<String, Char> =
+            Machine::with_input("inc".to_owned(), "111").unwrap();
+        let mut sink = SqliteTrace::in_memory().unwrap().batch_size(2);
+
+        let stats = machine.run_table_traced(&table, 100, &mut sink);
+        let trace = sink.finish().unwrap();
+
+        assert_eq!(stats.steps, 4);
+        assert_eq!(trace.len().unwrap(), 4);
+        assert_eq!(
+            trace.step(3).unwrap(),
+            Some(TraceRow {
+                step: 3,
+                state: "inc".to_owned(),
+                head: 3,
+                read: '_',
+                write: '1',
+                head_move: None,
+            })
+        );
+        assert_eq!(trace.step(4).unwrap(), None);
+        assert_eq!(
+            trace
+                .range(1..3)
+                .unwrap()
+                .iter()
+                .map(|row| row.head)
+                .collect::<Vec<_>>(),
+            [1, 2]
+        );
+        assert_eq!(trace.in_state("inc", 2).unwrap().len(), 2);
+        assert_eq!(trace.state_counts().unwrap(), [("inc".to_owned(), 4)]);
+    }
+}
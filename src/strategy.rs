@@ -0,0 +1,137 @@
+//! [`proptest`] strategies for random machines, tapes and inputs.
+//!
+//! Tables have the states `0..states` and the symbols `0..symbols`, with `0`
+//! as the blank, and shrink towards fewer rules and smaller states, symbols
+//! and moves.
+
+use std::collections::VecDeque;
+
+use proptest::collection::{vec, SizeRange};
+use proptest::option;
+use proptest::prelude::*;
+use proptest::sample::select;
+
+use crate::{Machine, Move, Rule, State, TransitionTable};
+
+impl Arbitrary for Move {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        prop_oneof![Just(Move::Left), Just(Move::Right)].boxed()
+    }
+}
+
+/// Rules writing, moving and changing state, halting one time in
+/// `states + 1`. One in three rules keeps the head where it is.
+pub fn rule(states: usize, symbols: u8) -> impl Strategy<Value = Rule<usize, u8>> {
+    let next = (0..=states).prop_map(move |next| match next == states {
+        true => State::Halt,
+        false => State::State(next),
+    });
+    let head_move = prop_oneof![
+        2 => any::<Move>().prop_map(Some),
+        1 => Just(None),
+    ];
+
+    (next, 0..symbols, head_move).prop_map(|(next, write, head_move)| Rule {
+        new_state: Some(next),
+        write: Some(write),
+        head_move,
+    })
+}
+
+/// Tables where each state and symbol has a rule with probability
+/// `density`.
+///
+/// # Panics
+///
+/// If `density` is not between 0 and 1.
+pub fn table(
+    states: usize,
+    symbols: u8,
+    density: f64,
+) -> impl Strategy<Value = TransitionTable<usize, u8>> {
+    let symbols_len = usize::from(symbols);
+
+    vec(
+        option::weighted(density, rule(states, symbols)),
+        states * symbols_len,
+    )
+    .prop_map(move |rules| {
+        rules
+            .into_iter()
+            .enumerate()
+            .filter_map(|(index, rule)| {
+                let symbol = (index % symbols_len) as u8;
+                rule.map(|rule| (index / symbols_len, symbol, rule))
+            })
+            .collect()
+    })
+}
+
+/// Tapes of `len` symbols.
+pub fn tape(symbols: u8, len: impl Into<SizeRange>) -> impl Strategy<Value = VecDeque<u8>> {
+    vec(0..symbols, len).prop_map(VecDeque::from)
+}
+
+/// Inputs of `len` characters of `alphabet`, for [`Machine::with_input`].
+///
+/// # Panics
+///
+/// If `alphabet` is empty.
+pub fn input(alphabet: &str, len: impl Into<SizeRange>) -> impl Strategy<Value = String> {
+    let alphabet: Vec<char> = alphabet.chars().collect();
+
+    vec(select(alphabet), len).prop_map(String::from_iter)
+}
+
+/// Tables with a machine in state 0 on a tape for them.
+pub fn machine(
+    states: usize,
+    symbols: u8,
+    len: impl Into<SizeRange>,
+) -> impl Strategy<Value = (TransitionTable<usize, u8>, Machine<usize, u8>)> {
+    (table(states, symbols, 0.9), tape(symbols, len))
+        .prop_map(|(table, tape)| (table, Machine::new(0, tape)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accelerate::{Accelerator, Outcome};
+    use crate::StopReason;
+
+    proptest! {
+        // the accelerator agrees with plain runs from a blank tape
+        #[test]
+        fn accelerator_test(table in table(3, 3, 0.9)) {
+            let mut machine: Machine<usize, u8> = Machine::new(0, VecDeque::from([0]));
+            let stats = machine.run_table(&table, 2_000);
+            let mut accelerator = Accelerator::new(&table, 0);
+            let outcome = accelerator.run(2_000);
+
+            if stats.stop == StopReason::Halted {
+                let sigma = machine.tape_to_vec().iter().filter(|&&sym| sym != 0).count();
+
+                prop_assert_eq!(outcome, Outcome::Halted);
+                prop_assert_eq!(accelerator.steps(), stats.steps);
+                prop_assert_eq!(accelerator.sigma(), sigma as u64);
+            } else if outcome == Outcome::Halted {
+                // a chain step can go past the limit
+                prop_assert!(accelerator.steps() > stats.steps);
+            }
+        }
+
+        #[test]
+        fn strategies_test(
+            (table, machine) in machine(4, 2, 1..8),
+            input in input("ab", 0..5),
+        ) {
+            prop_assert!(table.len() <= 8);
+            prop_assert!(table.states().all(|&state| state < 4));
+            prop_assert!((1..8).contains(&machine.tape().len()));
+            prop_assert!(input.len() < 5 && input.chars().all(|c| "ab".contains(c)));
+        }
+    }
+}
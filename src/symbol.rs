@@ -0,0 +1,196 @@
+/// A tape symbol with a text form and a number, for parsing, rendering and
+/// packing machines generically.
+pub trait Symbol: Sized {
+    fn from_char(c: char) -> Option<Self>;
+    fn to_char(&self) -> char;
+
+    /// The symbol of empty cells, which machines made with
+    /// [`Machine::with_input`](crate::Machine::with_input) grow their tape by.
+    fn blank() -> Self
+    where
+        Self: Default,
+    {
+        Self::default()
+    }
+
+    /// A number for the symbol that stays the same across versions, by
+    /// default the code point of its `char`.
+    fn ordinal(&self) -> u32 {
+        self.to_char() as u32
+    }
+
+    /// The inverse of [`Symbol::ordinal`].
+    fn from_ordinal(ordinal: u32) -> Option<Self> {
+        char::from_u32(ordinal).and_then(Self::from_char)
+    }
+}
+
+impl Symbol for bool {
+    fn from_char(c: char) -> Option<Self> {
+        match c {
+            '0' => Some(false),
+            '1' => Some(true),
+            _ => None,
+        }
+    }
+
+    fn to_char(&self) -> char {
+        match self {
+            false => '0',
+            true => '1',
+        }
+    }
+
+    fn ordinal(&self) -> u32 {
+        *self as u32
+    }
+
+    fn from_ordinal(ordinal: u32) -> Option<Self> {
+        match ordinal {
+            0 => Some(false),
+            1 => Some(true),
+            _ => None,
+        }
+    }
+}
+
+impl Symbol for char {
+    fn from_char(c: char) -> Option<Self> {
+        Some(c)
+    }
+
+    fn to_char(&self) -> char {
+        *self
+    }
+}
+
+impl Symbol for u8 {
+    fn from_char(c: char) -> Option<Self> {
+        c.to_digit(36).map(|digit| digit as u8)
+    }
+
+    fn to_char(&self) -> char {
+        char::from_digit(*self as u32, 36).unwrap_or('?')
+    }
+
+    fn ordinal(&self) -> u32 {
+        *self as u32
+    }
+
+    fn from_ordinal(ordinal: u32) -> Option<Self> {
+        u8::try_from(ordinal).ok()
+    }
+}
+
+/// A state type with finitely many named states, for formats and renderers
+/// that refer to states by name.
+pub trait TuringState: Sized + 'static {
+    /// Every state, in declaration order.
+    const STATES: &'static [Self];
+
+    fn name(&self) -> &'static str;
+
+    /// The index of the state in [`TuringState::STATES`].
+    fn ordinal(&self) -> usize;
+
+    fn from_ordinal(ordinal: usize) -> Option<Self>
+    where
+        Self: Clone,
+    {
+        Self::STATES.get(ordinal).cloned()
+    }
+
+    /// The state named `name`.
+    fn from_name(name: &str) -> Option<Self>
+    where
+        Self: Clone,
+    {
+        Self::STATES
+            .iter()
+            .find(|state| state.name() == name)
+            .cloned()
+    }
+}
+
+/// A character symbol whose blank is `_`, for alphabets written down as text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Char(pub char);
+
+impl Default for Char {
+    fn default() -> Self {
+        Self('_')
+    }
+}
+
+impl Symbol for Char {
+    fn from_char(c: char) -> Option<Self> {
+        Some(Self(c))
+    }
+
+    fn to_char(&self) -> char {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ordinal_test() {
+        assert_eq!(true.ordinal(), 1);
+        assert_eq!(200u8.ordinal(), 200);
+        assert_eq!(u8::from_ordinal(200), Some(200));
+        assert_eq!(u8::from_ordinal(256), None);
+        assert_eq!(Char::from_ordinal(Char('x').ordinal()), Some(Char('x')));
+        assert_eq!(Char::blank(), Char('_'));
+    }
+
+    #[test]
+    fn derive_test() {
+        use alloc::string::ToString;
+
+        use turing_derive::{Symbol, TuringState};
+
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Symbol)]
+        enum Bit {
+            #[symbol('0', blank)]
+            Zero,
+            #[symbol('1')]
+            One,
+        }
+
+        #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, TuringState)]
+        enum Parity {
+            Even,
+            #[state(name = "odd")]
+            Odd,
+        }
+
+        assert_eq!(Bit::from_char('1'), Some(Bit::One));
+        assert_eq!(Bit::from_char('2'), None);
+        assert_eq!(Bit::One.to_char(), '1');
+        assert_eq!(Bit::from_ordinal(Bit::One.ordinal()), Some(Bit::One));
+        assert_eq!(Bit::blank(), Bit::Zero);
+
+        assert_eq!(Parity::STATES, [Parity::Even, Parity::Odd]);
+        assert_eq!(Parity::from_name("odd"), Some(Parity::Odd));
+        assert_eq!(Parity::Even.to_string(), "Even");
+        assert_eq!(Parity::Odd.ordinal(), 1);
+        assert_eq!(Parity::from_ordinal(1), Some(Parity::Odd));
+
+        let table: crate::TransitionTable<Parity, Bit> = [
+            (Parity::Even, Bit::One, crate::Rule::to(Parity::Odd).right()),
+            (Parity::Odd, Bit::One, crate::Rule::to(Parity::Even).right()),
+        ]
+        .into_iter()
+        .collect();
+        let definition = crate::dsl::Definition::named(&Parity::Even, &table);
+
+        assert_eq!(
+            crate::dsl::write(&definition.table),
+            "Even 1 * R odd\nodd 1 * R Even\n"
+        );
+        assert_eq!(definition.typed(), Ok((Parity::Even, table)));
+    }
+}
@@ -0,0 +1,357 @@
+//! Symbolic execution: running a table on a tape some of whose cells are
+//! unknown, forking whenever the head reads one, to find out which inputs
+//! take which paths, e.g. every input of some length that reaches an error
+//! state within a number of steps.
+
+use alloc::collections::{BTreeMap, BTreeSet, VecDeque};
+use alloc::vec::Vec;
+
+use crate::{Machine, Rule, TransitionTable};
+
+/// A tape cell of a [`Path`]: a symbol, or the unknown symbol of a variable.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Cell<Sym> {
+    Known(Sym),
+    Unknown(usize),
+}
+
+impl<Sym: Default> Default for Cell<Sym> {
+    fn default() -> Self {
+        Cell::Known(Sym::default())
+    }
+}
+
+/// How a [`Path`] ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathEnd {
+    Halted,
+    /// No rule for the configuration, under [`MissingRule::Error`](crate::MissingRule::Error).
+    NoRule,
+    StepLimit,
+    /// The predicate given to [`Symbolic::explore`] held.
+    Reached,
+}
+
+/// One way a run can go: the machine and the constraints on the variables
+/// under which it goes that way.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path<S, Sym: Default> {
+    machine: Machine<S, Cell<Sym>>,
+    constraints: BTreeMap<usize, BTreeSet<Sym>>,
+    steps: u64,
+    end: Option<PathEnd>,
+}
+
+/// The paths [`Symbolic::explore`] found.
+#[derive(Debug, Clone)]
+pub struct Exploration<S, Sym: Default> {
+    /// The paths that ended, in the order they did.
+    pub paths: Vec<Path<S, Sym>>,
+    /// Whether every path ended, rather than the exploration stopping at the
+    /// limit on paths.
+    pub complete: bool,
+}
+
+/// Runs a table symbolically, with unknown cells ranging over an alphabet.
+#[derive(Debug, Clone)]
+pub struct Symbolic<'t, S, Sym> {
+    table: &'t TransitionTable<S, Sym>,
+    alphabet: BTreeSet<Sym>,
+}
+
+impl<S, Sym> Path<S, Sym>
+where
+    S: Clone,
+    Sym: Ord + Default + Clone,
+{
+    /// A path starting in `state` on `cells`, the head on the first.
+    pub fn new(state: S, cells: impl IntoIterator<Item = Cell<Sym>>) -> Self {
+        let mut tape: VecDeque<_> = cells.into_iter().collect();
+
+        if tape.is_empty() {
+            tape.push_back(Cell::default());
+        }
+
+        Self::from_machine(Machine::new(state, tape))
+    }
+
+    /// A path starting in `state` on `len` unknown cells, variables `0` to
+    /// `len - 1` from left to right.
+    pub fn unknowns(state: S, len: usize) -> Self {
+        Self::new(state, (0..len).map(Cell::Unknown))
+    }
+
+    /// A path starting from `machine`, for a tape of known and unknown cells
+    /// with another blank or head position.
+    pub fn from_machine(machine: Machine<S, Cell<Sym>>) -> Self {
+        Self {
+            machine,
+            constraints: BTreeMap::new(),
+            steps: 0,
+            end: None,
+        }
+    }
+
+    pub fn machine(&self) -> &Machine<S, Cell<Sym>> {
+        &self.machine
+    }
+
+    /// The symbols each variable read so far can be for the run to take this
+    /// path. Variables that weren't read can be anything.
+    pub fn constraints(&self) -> &BTreeMap<usize, BTreeSet<Sym>> {
+        &self.constraints
+    }
+
+    /// A value for every constrained variable taking the run down this path.
+    pub fn witness(&self) -> BTreeMap<usize, Sym> {
+        self.constraints
+            .iter()
+            .filter_map(|(&variable, values)| Some((variable, values.first()?.clone())))
+            .collect()
+    }
+
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    /// How the path ended, `None` while it goes on.
+    pub fn end(&self) -> Option<PathEnd> {
+        self.end
+    }
+}
+
+impl<'t, S, Sym> Symbolic<'t, S, Sym>
+where
+    S: Ord + Clone,
+    Sym: Ord + Default + Clone,
+{
+    /// Runs `table` with unknown cells holding any symbol of `alphabet`.
+    pub fn new(
+        table: &'t TransitionTable<S, Sym>,
+        alphabet: impl IntoIterator<Item = Sym>,
+    ) -> Self {
+        Self {
+            table,
+            alphabet: alphabet.into_iter().collect(),
+        }
+    }
+
+    /// The paths `path` takes in one step: one unless the head is on an
+    /// unknown cell, then one for every group of its symbols with the same
+    /// rule, or none if it has no symbols left. A path that has ended stays
+    /// as it is.
+    pub fn step(&self, path: &Path<S, Sym>) -> Vec<Path<S, Sym>> {
+        if path.end.is_some() {
+            return Vec::from([path.clone()]);
+        }
+
+        let Some(state) = path.machine.state() else {
+            return Vec::from([Path {
+                end: Some(PathEnd::Halted),
+                ..path.clone()
+            }]);
+        };
+
+        let variable = match path.machine.current_symbol() {
+            Cell::Known(symbol) => {
+                let rule = self.table.lookup(state, symbol);
+                return Vec::from([self.follow(path.clone(), rule, None)]);
+            }
+            &Cell::Unknown(variable) => variable,
+        };
+
+        let values = path.constraints.get(&variable).unwrap_or(&self.alphabet);
+        let mut groups: Vec<(_, BTreeSet<Sym>)> = Vec::new();
+
+        for value in values {
+            let rule = self.table.lookup(state, value);
+
+            match groups.iter_mut().find(|(other, _)| *other == rule) {
+                Some((_, group)) => {
+                    group.insert(value.clone());
+                }
+                None => groups.push((rule, BTreeSet::from([value.clone()]))),
+            }
+        }
+
+        groups
+            .into_iter()
+            .map(|(rule, values)| {
+                let mut child = path.clone();
+                let known = match values.len() {
+                    1 => values.first().cloned(),
+                    _ => None,
+                };
+
+                child.constraints.insert(variable, values);
+                self.follow(child, rule, known)
+            })
+            .collect()
+    }
+
+    /// Steps every path from `start` until it halts, has no rule, takes
+    /// `max_steps` steps or satisfies `until`, forking on unknown cells. Stops
+    /// early, incomplete, once more than `max_paths` paths were found.
+    pub fn explore(
+        &self,
+        start: &Path<S, Sym>,
+        max_steps: u64,
+        max_paths: usize,
+        mut until: impl FnMut(&Path<S, Sym>) -> bool,
+    ) -> Exploration<S, Sym> {
+        let mut live = Vec::from([start.clone()]);
+        let mut paths = Vec::new();
+
+        while let Some(mut path) = live.pop() {
+            if path.end.is_none() && until(&path) {
+                path.end = Some(PathEnd::Reached);
+            } else if path.end.is_none() && path.steps >= max_steps {
+                path.end = Some(PathEnd::StepLimit);
+            }
+
+            if path.end.is_some() {
+                paths.push(path);
+                continue;
+            }
+
+            // reversed so that paths are explored in the order of the groups
+            live.extend(self.step(&path).into_iter().rev());
+
+            if paths.len() + live.len() > max_paths {
+                return Exploration {
+                    paths,
+                    complete: false,
+                };
+            }
+        }
+
+        Exploration {
+            paths,
+            complete: true,
+        }
+    }
+
+    /// The paths from `start` entering `state` within `max_steps` steps.
+    /// `None` if there were more than `max_paths` paths to explore.
+    pub fn reach(
+        &self,
+        start: &Path<S, Sym>,
+        state: &S,
+        max_steps: u64,
+        max_paths: usize,
+    ) -> Option<Vec<Path<S, Sym>>> {
+        let exploration = self.explore(start, max_steps, max_paths, |path| {
+            path.machine.state() == Some(state)
+        });
+
+        exploration.complete.then(|| {
+            exploration
+                .paths
+                .into_iter()
+                .filter(|path| path.end == Some(PathEnd::Reached))
+                .collect()
+        })
+    }
+
+    // applies `rule` to `path`, writing `known` to an unknown cell it leaves
+    // as it is
+    fn follow(
+        &self,
+        mut path: Path<S, Sym>,
+        rule: Option<Rule<S, Sym>>,
+        known: Option<Sym>,
+    ) -> Path<S, Sym> {
+        let Some(rule) = rule else {
+            path.end = Some(PathEnd::NoRule);
+            return path;
+        };
+
+        path.machine.apply(Rule {
+            new_state: rule.new_state,
+            write: rule.write.or(known).map(Cell::Known),
+            head_move: rule.head_move,
+        });
+        path.steps += 1;
+        path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Char, MissingRule};
+
+    // reaches E on `1` then `0`, reading the tape left to right
+    fn checker() -> TransitionTable<char, Char> {
+        let mut table: TransitionTable<char, Char> = [
+            ('A', Char('1'), Rule::to('B').right()),
+            ('A', Char('0'), Rule::halt()),
+            ('B', Char('0'), Rule::to('E').write(Char('x'))),
+            ('B', Char('1'), Rule::new().right()),
+            ('B', Char('_'), Rule::halt()),
+        ]
+        .into_iter()
+        .collect();
+        table.set_missing(MissingRule::Error);
+        table
+    }
+
+    #[test]
+    fn reach_test() {
+        let table = checker();
+        let symbolic = Symbolic::new(&table, [Char('0'), Char('1')]);
+        let start = Path::unknowns('A', 3);
+        let paths = symbolic.reach(&start, &'E', 10, 100).unwrap();
+
+        let witnesses: Vec<_> = paths.iter().map(Path::witness).collect();
+        assert_eq!(
+            witnesses,
+            [
+                BTreeMap::from([(0, Char('1')), (1, Char('0'))]),
+                BTreeMap::from([(0, Char('1')), (1, Char('1')), (2, Char('0'))]),
+            ]
+        );
+        assert_eq!(paths[0].steps(), 2);
+        assert_eq!(paths[0].machine().current_symbol(), &Cell::Known(Char('x')));
+        assert_eq!(paths[1].machine().tape()[0], Cell::Known(Char('1')));
+
+        assert!(symbolic.reach(&start, &'E', 10, 2).is_none());
+    }
+
+    #[test]
+    fn explore_test() {
+        let table = checker();
+        // blanks are in the alphabet too, sorting last, and `_` in state A has
+        // no rule
+        let symbolic = Symbolic::new(&table, [Char('_'), Char('0'), Char('1')]);
+        let start = Path::new('A', [Cell::Unknown(0), Cell::Known(Char('1'))]);
+        let exploration = symbolic.explore(&start, 2, 100, |_| false);
+
+        let ends: Vec<_> = exploration
+            .paths
+            .iter()
+            .map(|path| (path.end(), path.constraints()[&0].len()))
+            .collect();
+
+        assert!(exploration.complete);
+        assert_eq!(
+            ends,
+            [
+                (Some(PathEnd::Halted), 1),
+                (Some(PathEnd::StepLimit), 1),
+                (Some(PathEnd::NoRule), 1),
+            ]
+        );
+
+        // symbols with the same rule stay one path, and the cell unknown
+        let mut table = TransitionTable::new();
+        table.insert('A', Char('0'), Rule::to('B').right());
+        table.insert('A', Char('1'), Rule::to('B').right());
+        let symbolic = Symbolic::new(&table, [Char('0'), Char('1')]);
+
+        let paths = symbolic.step(&Path::unknowns('A', 1));
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].machine().tape()[0], Cell::Unknown(0));
+        assert_eq!(paths[0].constraints()[&0].len(), 2);
+    }
+}
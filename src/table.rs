@@ -0,0 +1,445 @@
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::convert::Infallible;
+use core::fmt::{self, Display};
+
+use crate::{move_name, Rule, State, Symbol};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransitionTable<S, Sym> {
+    rules: BTreeMap<S, BTreeMap<Sym, Rule<S, Sym>>>,
+    missing: MissingRule<S, Sym>,
+}
+
+/// What a machine run from a [`TransitionTable`] does when the table has no
+/// rule for its state and the symbol under the head.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum MissingRule<S, Sym> {
+    /// Halt without touching the tape.
+    #[default]
+    Halt,
+    /// Halt like [`MissingRule::Halt`], with the run rejecting under any
+    /// [`Acceptance`](crate::Acceptance).
+    Reject,
+    /// Leave the machine as it is and stop the run with
+    /// [`StopReason::NoRule`](crate::StopReason::NoRule).
+    Error,
+    /// Apply this rule instead.
+    Default(Rule<S, Sym>),
+}
+
+impl<S, Sym> TransitionTable<S, Sym>
+where
+    S: Ord,
+    Sym: Ord,
+{
+    pub fn new() -> Self {
+        Self {
+            rules: BTreeMap::new(),
+            missing: MissingRule::Halt,
+        }
+    }
+
+    /// Sets what happens when there is no rule; see [`MissingRule`].
+    pub fn with_missing(mut self, missing: MissingRule<S, Sym>) -> Self {
+        self.missing = missing;
+        self
+    }
+
+    pub fn set_missing(&mut self, missing: MissingRule<S, Sym>) {
+        self.missing = missing;
+    }
+
+    pub fn missing(&self) -> &MissingRule<S, Sym> {
+        &self.missing
+    }
+
+    /// The rule a machine applies reading `symbol` in `state`: its own rule,
+    /// or the one the [`MissingRule`] policy stands in with. `None` under
+    /// [`MissingRule::Error`].
+    pub fn lookup(&self, state: &S, symbol: &Sym) -> Option<Rule<S, Sym>>
+    where
+        S: Clone,
+        Sym: Clone,
+    {
+        if let Some(rule) = self.get(state, symbol) {
+            return Some(rule.clone());
+        }
+
+        match &self.missing {
+            MissingRule::Halt | MissingRule::Reject => Some(Rule::halt()),
+            MissingRule::Error => None,
+            MissingRule::Default(rule) => Some(rule.clone()),
+        }
+    }
+
+    /// Sets the rule for reading `symbol` in `state`, returning the rule it
+    /// replaced, if any.
+    pub fn insert(&mut self, state: S, symbol: Sym, rule: Rule<S, Sym>) -> Option<Rule<S, Sym>> {
+        self.rules.entry(state).or_default().insert(symbol, rule)
+    }
+
+    pub fn get(&self, state: &S, symbol: &Sym) -> Option<&Rule<S, Sym>> {
+        self.rules.get(state)?.get(symbol)
+    }
+
+    pub fn remove(&mut self, state: &S, symbol: &Sym) -> Option<Rule<S, Sym>> {
+        let rules = self.rules.get_mut(state)?;
+        let rule = rules.remove(symbol);
+
+        if rules.is_empty() {
+            self.rules.remove(state);
+        }
+
+        rule
+    }
+
+    pub fn len(&self) -> usize {
+        self.rules.values().map(BTreeMap::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// The rules of `state`, ordered by the symbol they read.
+    pub fn rules(&self, state: &S) -> impl Iterator<Item = (&Sym, &Rule<S, Sym>)> {
+        self.rules.get(state).into_iter().flatten()
+    }
+
+    /// States that have at least one rule, in ascending order.
+    pub fn states(&self) -> impl Iterator<Item = &S> {
+        self.rules.keys()
+    }
+
+    /// All `(state, symbol, rule)` entries, ordered by state and then symbol.
+    pub fn iter(&self) -> impl Iterator<Item = (&S, &Sym, &Rule<S, Sym>)> {
+        self.rules.iter().flat_map(|(state, rules)| {
+            rules
+                .iter()
+                .map(move |(symbol, rule)| (state, symbol, rule))
+        })
+    }
+}
+
+impl<S, Sym> TransitionTable<S, Sym>
+where
+    S: Ord,
+    Sym: Ord + Clone,
+{
+    /// The same table with every state replaced by `f` of it, or the first
+    /// error `f` returns.
+    pub fn try_map_states<T, E>(
+        &self,
+        mut f: impl FnMut(&S) -> Result<T, E>,
+    ) -> Result<TransitionTable<T, Sym>, E>
+    where
+        T: Ord,
+    {
+        let missing = match &self.missing {
+            MissingRule::Halt => MissingRule::Halt,
+            MissingRule::Reject => MissingRule::Reject,
+            MissingRule::Error => MissingRule::Error,
+            MissingRule::Default(rule) => MissingRule::Default(map_rule(rule, &mut f)?),
+        };
+
+        let mut table = TransitionTable::new().with_missing(missing);
+
+        for (state, symbol, rule) in self.iter() {
+            let rule = map_rule(rule, &mut f)?;
+            table.insert(f(state)?, symbol.clone(), rule);
+        }
+
+        Ok(table)
+    }
+
+    pub fn map_states<T: Ord>(&self, mut f: impl FnMut(&S) -> T) -> TransitionTable<T, Sym> {
+        match self.try_map_states(|state| Ok::<_, Infallible>(f(state))) {
+            Ok(table) => table,
+        }
+    }
+}
+
+fn map_rule<S, T, Sym: Clone, E>(
+    rule: &Rule<S, Sym>,
+    f: &mut impl FnMut(&S) -> Result<T, E>,
+) -> Result<Rule<T, Sym>, E> {
+    Ok(Rule {
+        new_state: match &rule.new_state {
+            Some(State::State(state)) => Some(State::State(f(state)?)),
+            Some(State::Halt) => Some(State::Halt),
+            None => None,
+        },
+        write: rule.write.clone(),
+        head_move: rule.head_move,
+    })
+}
+
+// the rules of a state by the symbol they read
+type Row<S, Sym> = BTreeMap<Sym, Rule<S, Sym>>;
+
+// the rules of the state looked up last and the one applied last among
+// them, so that runs staying in a state, or applying the same rule over and
+// over, skip the map lookups
+pub(crate) struct RuleCache<'t, S, Sym> {
+    state: Option<(&'t S, &'t Row<S, Sym>)>,
+    hot: Option<(&'t Sym, &'t Rule<S, Sym>)>,
+}
+
+impl<S, Sym> Default for RuleCache<'_, S, Sym> {
+    fn default() -> Self {
+        Self {
+            state: None,
+            hot: None,
+        }
+    }
+}
+
+impl<S, Sym> TransitionTable<S, Sym>
+where
+    S: Ord + Clone,
+    Sym: Ord + Clone,
+{
+    // `lookup`, trying `cache` first
+    pub(crate) fn lookup_cached<'t>(
+        &'t self,
+        cache: &mut RuleCache<'t, S, Sym>,
+        state: &S,
+        symbol: &Sym,
+    ) -> Option<Rule<S, Sym>> {
+        // the common case of the same rule again
+        if let (Some((cached, _)), Some((hot, rule))) = (cache.state, cache.hot) {
+            if cached == state && hot == symbol {
+                return Some(rule.clone());
+            }
+        }
+
+        let rules = match cache.state {
+            Some((cached, rules)) if cached == state => Some(rules),
+            _ => {
+                cache.hot = None;
+                cache.state = self.rules.get_key_value(state);
+                cache.state.map(|(_, rules)| rules)
+            }
+        };
+
+        match rules.and_then(|rules| rules.get_key_value(symbol)) {
+            Some((symbol, rule)) => {
+                cache.hot = Some((symbol, rule));
+                Some(rule.clone())
+            }
+            None => self.lookup(state, symbol),
+        }
+    }
+}
+
+impl<S, Sym> Default for TransitionTable<S, Sym>
+where
+    S: Ord,
+    Sym: Ord,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S, Sym> FromIterator<(S, Sym, Rule<S, Sym>)> for TransitionTable<S, Sym>
+where
+    S: Ord,
+    Sym: Ord,
+{
+    fn from_iter<T: IntoIterator<Item = (S, Sym, Rule<S, Sym>)>>(iter: T) -> Self {
+        let mut table = Self::new();
+
+        for (state, symbol, rule) in iter {
+            table.insert(state, symbol, rule);
+        }
+
+        table
+    }
+}
+
+/// The table as a grid with a row per state and a column per symbol read,
+/// each cell showing the symbol written, the move and the next state like
+/// `1 R b`, with `*` for leaving the symbol or state as it is and `-` for
+/// no rule.
+impl<S, Sym> Display for TransitionTable<S, Sym>
+where
+    S: Ord + Display,
+    Sym: Ord + Symbol,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbols: BTreeSet<&Sym> = self.rules.values().flat_map(BTreeMap::keys).collect();
+
+        let mut header = Vec::from([String::new()]);
+        header.extend(symbols.iter().map(|symbol| symbol.to_char().to_string()));
+
+        let mut grid = Vec::from([header]);
+
+        for (state, rules) in &self.rules {
+            let mut row = Vec::from([state.to_string()]);
+
+            row.extend(symbols.iter().map(|&symbol| match rules.get(symbol) {
+                Some(rule) => {
+                    let write = rule.write.as_ref().map_or('*', Symbol::to_char);
+                    let next = match &rule.new_state {
+                        None => "*".to_string(),
+                        Some(State::Halt) => "halt".to_string(),
+                        Some(State::State(next)) => next.to_string(),
+                    };
+
+                    alloc::format!("{write} {} {next}", move_name(rule.head_move))
+                }
+                None => "-".to_string(),
+            }));
+
+            grid.push(row);
+        }
+
+        let widths: Vec<usize> = (0..=symbols.len())
+            .map(|column| {
+                grid.iter()
+                    .map(|row| row[column].chars().count())
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+
+        for row in &grid {
+            let mut line = String::new();
+
+            for (column, (cell, width)) in row.iter().zip(&widths).enumerate() {
+                if column > 0 {
+                    line.push_str(" | ");
+                }
+
+                line.push_str(cell);
+                line.extend(core::iter::repeat_n(' ', width - cell.chars().count()));
+            }
+
+            writeln!(f, "{}", line.trim_end())?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<S, Sym> TransitionTable<S, Sym>
+where
+    S: Ord + Display,
+    Sym: Ord + Symbol,
+{
+    /// The table as the grid of its [`Display`] impl, for terminals and logs.
+    pub fn to_table_string(&self) -> String {
+        self.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Char, Machine, StopReason};
+
+    #[test]
+    fn binary_inc_table_test() {
+        let table: TransitionTable<u8, bool> = [
+            (0, true, Rule::new().write(false).right()),
+            (0, false, Rule::halt().write(true)),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(table.len(), 2);
+
+        let mut machine: Machine<u8, bool> = Machine::new(0, [true, true].into());
+
+        while !machine.halted() {
+            machine.execute_table(&table);
+        }
+
+        assert_eq!(machine.tape_to_vec(), [false, false, true]);
+    }
+
+    #[test]
+    fn lookup_cached_test() {
+        let table: TransitionTable<u8, u8> = [
+            (0, 0, Rule::new().right()),
+            (0, 1, Rule::to(1)),
+            (1, 1, Rule::halt()),
+        ]
+        .into_iter()
+        .collect();
+        let mut cache = RuleCache::default();
+
+        for (state, symbol) in [(0, 0), (0, 0), (0, 1), (1, 1), (1, 2), (0, 0), (2, 0)] {
+            assert_eq!(
+                table.lookup_cached(&mut cache, &state, &symbol),
+                table.lookup(&state, &symbol)
+            );
+        }
+    }
+
+    #[test]
+    fn missing_rule_halts_test() {
+        let mut table: TransitionTable<u8, bool> = TransitionTable::new();
+        table.insert(0, true, Rule::to(1).right());
+
+        let mut machine: Machine<u8, bool> = Machine::new(0, [true, true].into());
+        machine.execute_table(&table);
+        machine.execute_table(&table);
+
+        assert!(machine.halted());
+        assert_eq!(machine.head(), 1);
+        assert_eq!(table.remove(&0, &true), Some(Rule::to(1).right()));
+        assert!(table.is_empty());
+    }
+
+    #[test]
+    fn missing_rule_policy_test() {
+        let table: TransitionTable<u8, bool> = [(0, true, Rule::new().right())]
+            .into_iter()
+            .collect::<TransitionTable<_, _>>()
+            .with_missing(MissingRule::Error);
+
+        let mut machine: Machine<u8, bool> = Machine::new(0, [true, false].into());
+        let stats = machine.run_table(&table, 10);
+
+        assert_eq!(stats.stop, StopReason::NoRule);
+        assert_eq!(stats.steps, 1);
+        assert_eq!(machine.state(), Some(&0));
+
+        // a default rule writing and moving right past the blanks
+        let mut table = table;
+        table.set_missing(MissingRule::Default(Rule::new().write(true).right()));
+
+        let mut machine: Machine<u8, bool> = Machine::new(0, [true, false].into());
+        let stats = machine.run_table(&table, 3);
+
+        assert_eq!(stats.stop, StopReason::StepLimit);
+        assert_eq!(machine.tape_to_vec(), [true, true, true, false]);
+    }
+
+    #[test]
+    fn display_test() {
+        let table: TransitionTable<String, Char> = [
+            (
+                "a".into(),
+                Char('0'),
+                Rule::to("b".into()).write(Char('1')).right(),
+            ),
+            ("a".into(), Char('1'), Rule::halt().left()),
+            ("b".into(), Char('0'), Rule::new().write(Char('1'))),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(
+            table.to_string(),
+            "  | 0     | 1\n\
+             a | 1 R b | * L halt\n\
+             b | 1 N * | -\n"
+        );
+        assert_eq!(table.to_table_string(), table.to_string());
+    }
+}
@@ -0,0 +1,1113 @@
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::array;
+use core::fmt::{self, Display};
+#[cfg(feature = "std")]
+use std::io::{self, BufReader, Bytes, Read};
+
+use crate::{MissingRule, Symbol, TransitionTable};
+
+/// Storage for the cells of a machine's tape, indexed from its left end.
+pub trait Tape<Sym> {
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn get(&self, index: usize) -> Option<&Sym>;
+
+    /// Overwrites the cell at `index`, which must be within the tape.
+    fn set(&mut self, index: usize, symbol: Sym);
+
+    /// Grows the tape by one cell on the left, shifting all indices up by one.
+    fn push_front(&mut self, symbol: Sym) -> Result<(), TapeFull>;
+
+    /// Grows the tape by one cell on the right.
+    fn push_back(&mut self, symbol: Sym) -> Result<(), TapeFull>;
+}
+
+/// Returned by [`Tape`]s that cannot grow any further. A machine whose tape
+/// refuses to grow leaves its head on the edge cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TapeFull;
+
+impl Display for TapeFull {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("tape is full")
+    }
+}
+
+impl core::error::Error for TapeFull {}
+
+impl<Sym> Tape<Sym> for VecDeque<Sym> {
+    fn len(&self) -> usize {
+        VecDeque::len(self)
+    }
+
+    fn get(&self, index: usize) -> Option<&Sym> {
+        VecDeque::get(self, index)
+    }
+
+    fn set(&mut self, index: usize, symbol: Sym) {
+        self[index] = symbol;
+    }
+
+    fn push_front(&mut self, symbol: Sym) -> Result<(), TapeFull> {
+        VecDeque::push_front(self, symbol);
+
+        Ok(())
+    }
+
+    fn push_back(&mut self, symbol: Sym) -> Result<(), TapeFull> {
+        VecDeque::push_back(self, symbol);
+
+        Ok(())
+    }
+}
+
+/// What a bounded tape, e.g. a [`FixedTape`], does when asked to grow past
+/// its ends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Overflow {
+    /// Refuse to grow; the head stays on the edge cell.
+    Stay,
+    Panic,
+}
+
+/// A tape of at most `N` cells stored inline, for running machines without
+/// any heap allocation.
+#[derive(Debug, Clone)]
+pub struct FixedTape<Sym, const N: usize> {
+    // ring buffer; the tape starts at `start` and wraps around
+    cells: [Sym; N],
+    start: usize,
+    len: usize,
+    overflow: Overflow,
+}
+
+impl<Sym, const N: usize> FixedTape<Sym, N>
+where
+    Sym: Default,
+{
+    /// A tape holding a single blank cell.
+    pub fn new(overflow: Overflow) -> Self {
+        const { assert!(N > 0, "a tape needs at least one cell") };
+
+        Self {
+            cells: array::from_fn(|_| Sym::default()),
+            start: 0,
+            len: 1,
+            overflow,
+        }
+    }
+
+    /// A tape holding `symbols`, or `None` if they don't fit. An empty slice
+    /// gives a single blank cell.
+    pub fn from_slice(symbols: &[Sym], overflow: Overflow) -> Option<Self>
+    where
+        Sym: Clone,
+    {
+        if symbols.len() > N {
+            return None;
+        }
+
+        let mut tape = Self::new(overflow);
+        tape.len = symbols.len().max(1);
+        tape.cells[..symbols.len()].clone_from_slice(symbols);
+
+        Some(tape)
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    fn full(&self) -> Result<(), TapeFull> {
+        match self.overflow {
+            Overflow::Stay => Err(TapeFull),
+            Overflow::Panic => panic!("fixed tape of {N} cells overflowed"),
+        }
+    }
+}
+
+impl<Sym, const N: usize> Tape<Sym> for FixedTape<Sym, N>
+where
+    Sym: Default,
+{
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn get(&self, index: usize) -> Option<&Sym> {
+        (index < self.len).then(|| &self.cells[(self.start + index) % N])
+    }
+
+    fn set(&mut self, index: usize, symbol: Sym) {
+        assert!(index < self.len, "index out of tape bounds");
+
+        self.cells[(self.start + index) % N] = symbol;
+    }
+
+    fn push_front(&mut self, symbol: Sym) -> Result<(), TapeFull> {
+        if self.len == N {
+            return self.full();
+        }
+
+        self.start = (self.start + N - 1) % N;
+        self.cells[self.start] = symbol;
+        self.len += 1;
+
+        Ok(())
+    }
+
+    fn push_back(&mut self, symbol: Sym) -> Result<(), TapeFull> {
+        if self.len == N {
+            return self.full();
+        }
+
+        self.cells[(self.start + self.len) % N] = symbol;
+        self.len += 1;
+
+        Ok(())
+    }
+}
+
+/// A tape of at most 128 boolean cells held in a `u128`, copied and
+/// compared for free, for enumerating millions of short-lived machines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MicroTape {
+    // cell `index` is bit `index`
+    bits: u128,
+    len: u8,
+    overflow: Overflow,
+}
+
+impl MicroTape {
+    pub const CAPACITY: usize = 128;
+
+    /// A tape holding a single blank cell.
+    pub const fn new(overflow: Overflow) -> Self {
+        Self {
+            bits: 0,
+            len: 1,
+            overflow,
+        }
+    }
+
+    /// A tape holding the low `len` bits of `bits`, or a single blank cell
+    /// for a `len` of 0.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len` is more than 128.
+    pub fn from_bits(bits: u128, len: usize, overflow: Overflow) -> Self {
+        assert!(len <= Self::CAPACITY, "micro tapes have at most 128 cells");
+
+        let len = len.max(1);
+
+        Self {
+            bits: match len {
+                Self::CAPACITY => bits,
+                len => bits & ((1 << len) - 1),
+            },
+            len: len as u8,
+            overflow,
+        }
+    }
+
+    /// The cells as bits, cell 0 lowest.
+    pub const fn bits(&self) -> u128 {
+        self.bits
+    }
+
+    fn full(&self) -> Result<(), TapeFull> {
+        match self.overflow {
+            Overflow::Stay => Err(TapeFull),
+            Overflow::Panic => panic!("micro tape overflowed"),
+        }
+    }
+}
+
+impl Tape<bool> for MicroTape {
+    fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    fn get(&self, index: usize) -> Option<&bool> {
+        (index < self.len()).then_some(match self.bits >> index & 1 {
+            0 => &false,
+            _ => &true,
+        })
+    }
+
+    fn set(&mut self, index: usize, symbol: bool) {
+        assert!(index < self.len(), "index out of tape bounds");
+
+        self.bits = self.bits & !(1 << index) | (symbol as u128) << index;
+    }
+
+    fn push_front(&mut self, symbol: bool) -> Result<(), TapeFull> {
+        if self.len() == Self::CAPACITY {
+            return self.full();
+        }
+
+        self.bits = self.bits << 1 | symbol as u128;
+        self.len += 1;
+
+        Ok(())
+    }
+
+    fn push_back(&mut self, symbol: bool) -> Result<(), TapeFull> {
+        if self.len() == Self::CAPACITY {
+            return self.full();
+        }
+
+        self.len += 1;
+        self.set(self.len() - 1, symbol);
+
+        Ok(())
+    }
+}
+
+/// A tape infinite only to the right, as in most textbooks: cell 0 is its
+/// left end, and moving left from it fails with [`TapeFull`] under
+/// [`Overflow::Stay`], leaving the head in place, or panics under
+/// [`Overflow::Panic`]. Runs that treat the move as an error rather than a
+/// no-op check [`SemiInfiniteTape::bumped`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SemiInfiniteTape<Sym> {
+    cells: Vec<Sym>,
+    overflow: Overflow,
+    bumped: bool,
+}
+
+impl<Sym> SemiInfiniteTape<Sym>
+where
+    Sym: Default,
+{
+    /// A tape holding a single blank cell.
+    pub fn new(overflow: Overflow) -> Self {
+        Self {
+            cells: vec![Sym::default()],
+            overflow,
+            bumped: false,
+        }
+    }
+
+    /// A tape holding `symbols` from cell 0, or a single blank if they are
+    /// empty.
+    pub fn from_slice(symbols: &[Sym], overflow: Overflow) -> Self
+    where
+        Sym: Clone,
+    {
+        match symbols.is_empty() {
+            true => Self::new(overflow),
+            false => Self {
+                cells: symbols.to_vec(),
+                overflow,
+                bumped: false,
+            },
+        }
+    }
+
+    /// Whether a move left of cell 0 failed, e.g. to stop a
+    /// [`Machine::run_table_until`](crate::Machine::run_table_until) on it.
+    pub fn bumped(&self) -> bool {
+        self.bumped
+    }
+}
+
+impl<Sym> Tape<Sym> for SemiInfiniteTape<Sym> {
+    fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    fn get(&self, index: usize) -> Option<&Sym> {
+        self.cells.get(index)
+    }
+
+    fn set(&mut self, index: usize, symbol: Sym) {
+        self.cells[index] = symbol;
+    }
+
+    fn push_front(&mut self, _symbol: Sym) -> Result<(), TapeFull> {
+        self.bumped = true;
+
+        match self.overflow {
+            Overflow::Stay => Err(TapeFull),
+            Overflow::Panic => panic!("moved left of the end of a semi-infinite tape"),
+        }
+    }
+
+    fn push_back(&mut self, symbol: Sym) -> Result<(), TapeFull> {
+        self.cells.push(symbol);
+
+        Ok(())
+    }
+}
+
+/// A tape whose cells right of the initial ones are pulled from an iterator
+/// as the head reaches them, for inputs larger than memory, e.g. the bytes of
+/// a file through [`ReadCells`]. Once the input runs out, new cells are
+/// blank.
+///
+/// Only the `window` cells nearest the right end are kept; a machine going
+/// back further than that panics on the first cell that was dropped.
+#[derive(Debug, Clone)]
+pub struct StreamTape<Sym, I> {
+    cells: VecDeque<Sym>,
+    // how many cells were dropped from the left end
+    dropped: usize,
+    window: usize,
+    input: I,
+}
+
+impl<Sym, I> StreamTape<Sym, I>
+where
+    Sym: Default,
+    I: Iterator<Item = Sym>,
+{
+    /// A tape holding the first cell of `input`, or a blank if it's empty.
+    pub fn new(input: impl IntoIterator<IntoIter = I>, window: usize) -> Self {
+        let mut input = input.into_iter();
+
+        Self {
+            cells: VecDeque::from([input.next().unwrap_or_default()]),
+            dropped: 0,
+            window: window.max(1),
+            input,
+        }
+    }
+
+    /// The rest of the input, not pulled onto the tape yet.
+    pub fn input(&self) -> &I {
+        &self.input
+    }
+}
+
+impl<Sym, I> Tape<Sym> for StreamTape<Sym, I>
+where
+    I: Iterator<Item = Sym>,
+{
+    fn len(&self) -> usize {
+        self.dropped + self.cells.len()
+    }
+
+    fn get(&self, index: usize) -> Option<&Sym> {
+        self.cells.get(index.checked_sub(self.dropped)?)
+    }
+
+    fn set(&mut self, index: usize, symbol: Sym) {
+        let Some(kept) = index.checked_sub(self.dropped) else {
+            panic!("cell {index} was dropped from the stream window");
+        };
+
+        self.cells[kept] = symbol;
+    }
+
+    fn push_front(&mut self, symbol: Sym) -> Result<(), TapeFull> {
+        assert_eq!(
+            self.dropped, 0,
+            "a stream tape can't grow left of dropped cells"
+        );
+
+        self.cells.push_front(symbol);
+
+        Ok(())
+    }
+
+    fn push_back(&mut self, symbol: Sym) -> Result<(), TapeFull> {
+        self.cells.push_back(self.input.next().unwrap_or(symbol));
+
+        if self.cells.len() > self.window {
+            self.cells.pop_front();
+            self.dropped += 1;
+        }
+
+        Ok(())
+    }
+}
+
+/// The bytes of a reader as tape cells for a [`StreamTape`], ending at the
+/// first read error, which is kept.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct ReadCells<R> {
+    bytes: Bytes<BufReader<R>>,
+    error: Option<io::Error>,
+}
+
+#[cfg(feature = "std")]
+impl<R: Read> ReadCells<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            bytes: BufReader::new(reader).bytes(),
+            error: None,
+        }
+    }
+
+    /// The error that ended the input early, if any.
+    pub fn error(&self) -> Option<&io::Error> {
+        self.error.as_ref()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: Read> Iterator for ReadCells<R> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        if self.error.is_some() {
+            return None;
+        }
+
+        match self.bytes.next()? {
+            Ok(byte) => Some(byte),
+            Err(error) => {
+                self.error = Some(error);
+                None
+            }
+        }
+    }
+}
+
+// the cells per word of a `PackedTape`
+const PACKED: usize = 32;
+
+/// A tape of two bits per cell, for alphabets of at most four symbols, such
+/// as those of most busy beaver candidates. Cells hold the symbols'
+/// [`Symbol::ordinal`]s, which must be below 4: growing the tape with any
+/// other symbol fails with [`TapeFull`], and writing one panics, so tables
+/// are best checked up front with [`PackedTape::for_table`].
+#[derive(Debug, Clone)]
+pub struct PackedTape<Sym> {
+    // cell `index` is at bits `2 * (first + index)` on, counting from the
+    // low bits of the first word
+    words: VecDeque<u64>,
+    first: usize,
+    len: usize,
+    // the symbols by ordinal, for handing out references
+    symbols: [Sym; 4],
+}
+
+impl<Sym> PackedTape<Sym>
+where
+    Sym: Symbol + Default + Clone,
+{
+    /// A tape holding a single blank cell, or `None` if the blank's ordinal
+    /// isn't below 4.
+    pub fn new() -> Option<Self> {
+        let blank = Sym::default();
+
+        if blank.ordinal() >= 4 {
+            return None;
+        }
+
+        let mut tape = Self {
+            words: VecDeque::from([0]),
+            first: 0,
+            len: 1,
+            symbols: array::from_fn(|ordinal| {
+                Sym::from_ordinal(ordinal as u32).unwrap_or_default()
+            }),
+        };
+        tape.set(0, blank);
+
+        Some(tape)
+    }
+
+    /// A tape holding a single blank cell for running `table`, or `None` if
+    /// the blank or a symbol `table` reads or writes, its missing-rule
+    /// policy included, has an ordinal of 4 or more.
+    pub fn for_table<S: Ord>(table: &TransitionTable<S, Sym>) -> Option<Self>
+    where
+        Sym: Ord,
+    {
+        let fits = |write: &Option<Sym>| write.as_ref().is_none_or(|write| write.ordinal() < 4);
+        let missing = match table.missing() {
+            MissingRule::Default(rule) => fits(&rule.write),
+            _ => true,
+        };
+
+        match missing
+            && table
+                .iter()
+                .all(|(_, read, rule)| read.ordinal() < 4 && fits(&rule.write))
+        {
+            true => Self::new(),
+            false => None,
+        }
+    }
+
+    /// A tape holding `symbols`, or a single blank if they are empty. `None`
+    /// if the blank's or a symbol's ordinal isn't below 4.
+    pub fn from_slice(symbols: &[Sym]) -> Option<Self> {
+        if symbols.iter().any(|symbol| symbol.ordinal() >= 4) {
+            return None;
+        }
+
+        let mut tape = Self::new()?;
+
+        for symbol in symbols.iter().skip(1) {
+            let _ = tape.push_back(Sym::default());
+            tape.set(tape.len - 1, symbol.clone());
+        }
+
+        if let Some(symbol) = symbols.first() {
+            tape.set(0, symbol.clone());
+        }
+
+        Some(tape)
+    }
+
+    /// The ordinals of the `count` cells from `index`, at most 32 of them,
+    /// two bits each from the low bits up, for machines grouping cells into
+    /// blocks. Cells past the end read as 0.
+    pub fn block(&self, index: usize, count: usize) -> u64 {
+        assert!(count <= PACKED, "blocks have at most 32 cells");
+
+        let bit = 2 * (self.first + index);
+        let (word, offset) = (bit / 64, bit % 64);
+        let low = self.words.get(word).map_or(0, |word| word >> offset);
+        let high = match offset {
+            0 => 0,
+            _ => self
+                .words
+                .get(word + 1)
+                .map_or(0, |word| word << (64 - offset)),
+        };
+        let end = count.min(self.len.saturating_sub(index));
+        let mask = match end {
+            PACKED => u64::MAX,
+            end => (1 << (2 * end)) - 1,
+        };
+
+        (low | high) & mask
+    }
+
+    fn code(&self, index: usize) -> usize {
+        let bit = 2 * (self.first + index);
+        (self.words[bit / 64] >> (bit % 64) & 0b11) as usize
+    }
+}
+
+impl<Sym> Tape<Sym> for PackedTape<Sym>
+where
+    Sym: Symbol + Default + Clone,
+{
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn get(&self, index: usize) -> Option<&Sym> {
+        (index < self.len).then(|| &self.symbols[self.code(index)])
+    }
+
+    fn set(&mut self, index: usize, symbol: Sym) {
+        assert!(index < self.len, "index out of tape bounds");
+
+        let ordinal = symbol.ordinal() as u64;
+        assert!(ordinal < 4, "symbol ordinal {ordinal} doesn't fit two bits");
+
+        let bit = 2 * (self.first + index);
+        let word = &mut self.words[bit / 64];
+        *word = *word & !(0b11 << (bit % 64)) | ordinal << (bit % 64);
+    }
+
+    fn push_front(&mut self, symbol: Sym) -> Result<(), TapeFull> {
+        if symbol.ordinal() >= 4 {
+            return Err(TapeFull);
+        }
+
+        if self.first == 0 {
+            self.words.push_front(0);
+            self.first = PACKED;
+        }
+
+        self.first -= 1;
+        self.len += 1;
+        self.set(0, symbol);
+
+        Ok(())
+    }
+
+    fn push_back(&mut self, symbol: Sym) -> Result<(), TapeFull> {
+        if symbol.ordinal() >= 4 {
+            return Err(TapeFull);
+        }
+
+        if self.first + self.len == PACKED * self.words.len() {
+            self.words.push_back(0);
+        }
+
+        self.len += 1;
+        self.set(self.len - 1, symbol);
+
+        Ok(())
+    }
+}
+
+// the cells per chunk of a `SparseTape`
+const CHUNK: usize = 64;
+
+/// A tape storing only the chunks of cells holding a non-blank symbol, so
+/// long stretches of blanks, e.g. those a machine leaves behind, take no
+/// memory. Writing a blank over the last non-blank cell of a chunk frees it.
+#[derive(Debug, Clone)]
+pub struct SparseTape<Sym> {
+    // chunks by the position of their first cell, from which each has
+    // `CHUNK` cells
+    chunks: BTreeMap<isize, Vec<Sym>>,
+    // the position of index 0
+    first: isize,
+    len: usize,
+    blank: Sym,
+}
+
+impl<Sym> SparseTape<Sym>
+where
+    Sym: Clone + PartialEq,
+{
+    /// A tape holding a single `blank` cell, storing only chunks with other
+    /// symbols.
+    pub fn new(blank: Sym) -> Self {
+        Self {
+            chunks: BTreeMap::new(),
+            first: 0,
+            len: 1,
+            blank,
+        }
+    }
+
+    /// A tape holding `symbols`, or a single `blank` if they are empty.
+    pub fn from_slice(symbols: &[Sym], blank: Sym) -> Self {
+        let mut tape = Self::new(blank);
+        tape.len = symbols.len().max(1);
+
+        for (index, symbol) in symbols.iter().enumerate() {
+            tape.set(index, symbol.clone());
+        }
+
+        tape
+    }
+
+    /// How many cells are held in memory, blanks inside chunks included.
+    pub fn stored_cells(&self) -> usize {
+        self.chunks.len() * CHUNK
+    }
+
+    // the chunk holding `index` and the cell's offset in it
+    fn locate(&self, index: usize) -> (isize, usize) {
+        let position = self.first + index as isize;
+        let start = position.div_euclid(CHUNK as isize) * CHUNK as isize;
+
+        (start, (position - start) as usize)
+    }
+}
+
+impl<Sym> Default for SparseTape<Sym>
+where
+    Sym: Clone + PartialEq + Default,
+{
+    fn default() -> Self {
+        Self::new(Sym::default())
+    }
+}
+
+impl<Sym> Tape<Sym> for SparseTape<Sym>
+where
+    Sym: Clone + PartialEq,
+{
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn get(&self, index: usize) -> Option<&Sym> {
+        if index >= self.len {
+            return None;
+        }
+
+        let (start, offset) = self.locate(index);
+
+        Some(
+            self.chunks
+                .get(&start)
+                .map_or(&self.blank, |chunk| &chunk[offset]),
+        )
+    }
+
+    fn set(&mut self, index: usize, symbol: Sym) {
+        assert!(index < self.len, "index out of tape bounds");
+
+        let (start, offset) = self.locate(index);
+
+        if symbol == self.blank {
+            if let Some(chunk) = self.chunks.get_mut(&start) {
+                chunk[offset] = symbol;
+
+                if chunk.iter().all(|cell| *cell == self.blank) {
+                    self.chunks.remove(&start);
+                }
+            }
+
+            return;
+        }
+
+        let blank = &self.blank;
+        self.chunks
+            .entry(start)
+            .or_insert_with(|| vec![blank.clone(); CHUNK])[offset] = symbol;
+    }
+
+    fn push_front(&mut self, symbol: Sym) -> Result<(), TapeFull> {
+        self.first -= 1;
+        self.len += 1;
+        self.set(0, symbol);
+
+        Ok(())
+    }
+
+    fn push_back(&mut self, symbol: Sym) -> Result<(), TapeFull> {
+        self.len += 1;
+        self.set(self.len - 1, symbol);
+
+        Ok(())
+    }
+}
+
+/// A tape whose cells are shared between clones until one of them changes
+/// them, so that copying a machine, e.g. with [`Machine::fork`], doesn't copy
+/// its cells.
+///
+/// The first change to a shared tape copies it; writing a symbol a cell
+/// already holds isn't a change.
+///
+/// [`Machine::fork`]: crate::Machine::fork
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SharedTape<Sym> {
+    cells: Arc<VecDeque<Sym>>,
+}
+
+impl<Sym> SharedTape<Sym> {
+    pub fn new(cells: VecDeque<Sym>) -> Self {
+        Self {
+            cells: Arc::new(cells),
+        }
+    }
+
+    /// Whether other tapes still share these cells.
+    pub fn is_shared(&self) -> bool {
+        Arc::strong_count(&self.cells) > 1
+    }
+
+    pub fn cells(&self) -> &VecDeque<Sym> {
+        &self.cells
+    }
+
+    /// The cells, copied if they are still shared.
+    pub fn into_cells(self) -> VecDeque<Sym>
+    where
+        Sym: Clone,
+    {
+        Arc::unwrap_or_clone(self.cells)
+    }
+}
+
+impl<Sym> From<VecDeque<Sym>> for SharedTape<Sym> {
+    fn from(cells: VecDeque<Sym>) -> Self {
+        Self::new(cells)
+    }
+}
+
+impl<Sym> Tape<Sym> for SharedTape<Sym>
+where
+    Sym: Clone + PartialEq,
+{
+    fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    fn get(&self, index: usize) -> Option<&Sym> {
+        self.cells.get(index)
+    }
+
+    fn set(&mut self, index: usize, symbol: Sym) {
+        if self.cells[index] != symbol {
+            Arc::make_mut(&mut self.cells)[index] = symbol;
+        }
+    }
+
+    fn push_front(&mut self, symbol: Sym) -> Result<(), TapeFull> {
+        Arc::make_mut(&mut self.cells).push_front(symbol);
+
+        Ok(())
+    }
+
+    fn push_back(&mut self, symbol: Sym) -> Result<(), TapeFull> {
+        Arc::make_mut(&mut self.cells).push_back(symbol);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Char, Machine, Rule, TransitionTable, Until};
+
+    #[test]
+    fn fixed_tape_machine_test() {
+        // walk left over the input, then write a marker past its end
+        let table: TransitionTable<u8, u8> = [
+            (0, 1, Rule::new().left()),
+            (0, 0, Rule::to(1).write(2).right()),
+        ]
+        .into_iter()
+        .collect();
+
+        let tape = FixedTape::<u8, 4>::from_slice(&[1, 1], Overflow::Stay).unwrap();
+        let mut machine = Machine::new(0, tape);
+        machine.set_head(1);
+
+        while machine.state() == Some(&0) {
+            machine.execute_table(&table);
+        }
+
+        let tape = machine.into_tape();
+        let cells: Vec<u8> = (0..tape.len()).map(|i| *tape.get(i).unwrap()).collect();
+
+        assert_eq!(cells, [2, 1, 1]);
+    }
+
+    #[test]
+    fn fixed_tape_overflow_test() {
+        let mut tape = FixedTape::<bool, 2>::new(Overflow::Stay);
+
+        assert_eq!(tape.push_back(true), Ok(()));
+        assert_eq!(tape.push_front(true), Err(TapeFull));
+        assert!(FixedTape::<bool, 2>::from_slice(&[true; 3], Overflow::Stay).is_none());
+
+        let table: TransitionTable<u8, bool> =
+            [(0, false, Rule::new().left())].into_iter().collect();
+        let mut machine = Machine::new(0, FixedTape::<bool, 1>::new(Overflow::Stay));
+        machine.execute_table(&table);
+        machine.execute_table(&table);
+
+        assert_eq!(machine.head(), 0);
+        assert_eq!(machine.tape().len(), 1);
+    }
+
+    #[test]
+    fn stream_tape_test() {
+        // replaces every 1 with 2 until the first blank
+        let table: TransitionTable<u8, u8> =
+            [(0, 1, Rule::new().write(2).right()), (0, 0, Rule::halt())]
+                .into_iter()
+                .collect();
+
+        let input = core::iter::repeat_n(1, 100_000);
+        let mut machine = Machine::new(0, StreamTape::new(input, 4));
+        let stats = machine.run_table(&table, 1_000_000);
+
+        assert!(machine.halted());
+        assert_eq!(stats.steps, 100_001);
+        assert_eq!(machine.tape().len(), 100_001);
+        assert_eq!(machine.tape().get(99_999), Some(&2));
+        assert_eq!(machine.tape().get(0), None);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn read_cells_test() {
+        let mut tape = StreamTape::new(ReadCells::new(&b"ab"[..]), 8);
+        let _ = tape.push_back(0);
+        let _ = tape.push_back(0);
+
+        assert_eq!(
+            (0..3).map(|i| tape.get(i).copied()).collect::<Vec<_>>(),
+            [Some(b'a'), Some(b'b'), Some(0)]
+        );
+        assert!(tape.input().error().is_none());
+    }
+
+    #[test]
+    fn sparse_tape_test() {
+        // writes a 1 a million cells to the left and then to the right
+        let table: TransitionTable<u8, u8> = [
+            (0, 0, Rule::to(1).write(1).by(-1_000_000)),
+            (1, 0, Rule::to(2).write(1).by(2_000_000)),
+            (2, 0, Rule::halt().write(1)),
+        ]
+        .into_iter()
+        .collect();
+
+        let mut machine = Machine::new(0, SparseTape::from_slice(&[0, 2], 0));
+        machine.run_table(&table, 10);
+
+        let tape = machine.tape();
+
+        assert!(machine.halted());
+        assert_eq!(tape.len(), 2_000_001);
+        assert_eq!(tape.get(machine.origin()), Some(&1));
+        assert_eq!(tape.get(machine.origin() + 1), Some(&2));
+        assert_eq!(tape.get(machine.head()), Some(&1));
+        assert_eq!(tape.get(1), Some(&0));
+        assert_eq!(tape.stored_cells(), 3 * CHUNK);
+
+        let mut tape = SparseTape::from_slice(&[1], 0);
+        tape.set(0, 0);
+
+        assert_eq!(tape.stored_cells(), 0);
+
+        // another blank takes no memory either
+        let mut tape = SparseTape::new(9);
+        let _ = tape.push_back(9);
+        tape.set(0, 1);
+        tape.set(0, 9);
+
+        assert_eq!(tape.stored_cells(), 0);
+    }
+
+    #[test]
+    fn micro_tape_test() {
+        // the 2-state busy beaver
+        let table: TransitionTable<u8, bool> = [
+            (0, false, Rule::to(1).write(true).right()),
+            (0, true, Rule::to(1).write(true).left()),
+            (1, false, Rule::to(0).write(true).left()),
+            (1, true, Rule::halt().write(true).right()),
+        ]
+        .into_iter()
+        .collect();
+
+        let mut machine = Machine::new(0, MicroTape::new(Overflow::Stay));
+
+        assert_eq!(machine.run_table(&table, 100).steps, 6);
+        assert_eq!(machine.tape().bits(), 0b1111);
+        assert_eq!(machine.origin(), 2);
+
+        let mut tape = MicroTape::from_bits(u128::MAX, 128, Overflow::Stay);
+
+        assert_eq!(tape.push_back(false), Err(TapeFull));
+        assert_eq!(
+            MicroTape::from_bits(0b1101, 3, Overflow::Stay).bits(),
+            0b101
+        );
+    }
+
+    #[test]
+    fn packed_tape_test() {
+        let mut tape = PackedTape::from_slice(&[1u8, 2, 3]).unwrap();
+
+        for symbol in 0..40 {
+            tape.push_front(symbol % 4).unwrap();
+        }
+
+        tape.push_back(2).unwrap();
+
+        assert_eq!(tape.len(), 44);
+        assert_eq!(tape.get(0), Some(&3));
+        assert_eq!(tape.get(40), Some(&1));
+        assert_eq!(tape.get(43), Some(&2));
+        assert_eq!(tape.get(44), None);
+        // the cells 38 to 43 hold 1, 0, 1, 2, 3, 2 and straddle two words
+        assert_eq!(tape.block(38, 6), 0b10_11_10_01_00_01);
+        assert_eq!(tape.block(42, 32), 0b10_11);
+
+        // symbols, blanks included, need ordinals below 4
+        assert!(PackedTape::from_slice(&[1u8, 4]).is_none());
+        assert!(PackedTape::<Char>::new().is_none());
+        assert_eq!(tape.push_back(4), Err(TapeFull));
+        assert_eq!(tape.len(), 44);
+
+        // the same run on this tape as on a plain one
+        let table: TransitionTable<u8, u8> = [
+            (0, 0, Rule::new().write(3).left()),
+            (0, 3, Rule::to(1).write(2).by(3)),
+            (1, 0, Rule::to(0).write(1).right()),
+            (1, 1, Rule::halt()),
+        ]
+        .into_iter()
+        .collect();
+
+        let mut packed = Machine::new(0, PackedTape::for_table(&table).unwrap());
+        let mut plain: Machine<u8, u8> = Machine::new(0, [0].into());
+
+        assert_eq!(packed.run_table(&table, 100), plain.run_table(&table, 100));
+
+        let tape: Vec<u8> = (0..packed.tape().len())
+            .map(|index| *packed.tape().get(index).unwrap())
+            .collect();
+
+        assert_eq!(tape, plain.tape_to_vec());
+
+        // a table writing a symbol past 3 is refused before it runs
+        let mut wide = table.clone();
+        wide.insert(1, 2, Rule::halt().write(4));
+        assert!(PackedTape::for_table(&wide).is_none());
+    }
+
+    #[test]
+    fn semi_infinite_tape_test() {
+        // walks left to the end of the tape, bumps into it and marks it
+        let table: TransitionTable<u8, u8> = [
+            (0, 1, Rule::to(1).left()),
+            (1, 1, Rule::to(2).left()),
+            (2, 1, Rule::halt().write(3)),
+        ]
+        .into_iter()
+        .collect();
+
+        let mut machine = Machine::new(0, SemiInfiniteTape::from_slice(&[1, 1], Overflow::Stay));
+        machine.set_head(1);
+        let start = machine.clone();
+        machine.run_table(&table, 100);
+
+        assert!(machine.halted());
+        assert!(machine.tape().bumped());
+        assert_eq!((machine.head(), machine.origin()), (0, 0));
+        assert_eq!(machine.into_tape().cells, [3, 1]);
+
+        // or as an error, stopping the run on the move
+        let mut machine = start;
+        assert!(!machine.tape().bumped());
+        assert_eq!(
+            machine.run_table_until(&table, 100, |machine| machine.tape().bumped()),
+            Until::Holds { steps: 2 }
+        );
+        assert_eq!(SemiInfiniteTape::<u8>::new(Overflow::Stay).len(), 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn semi_infinite_tape_panic_test() {
+        let mut tape = SemiInfiniteTape::<bool>::new(Overflow::Panic);
+        let _ = tape.push_front(true);
+    }
+
+    #[test]
+    #[should_panic]
+    fn fixed_tape_panic_test() {
+        let mut tape = FixedTape::<bool, 1>::new(Overflow::Panic);
+        let _ = tape.push_back(true);
+    }
+
+    #[test]
+    fn shared_tape_test() {
+        let mut tape = SharedTape::new(VecDeque::from([1, 2]));
+        let copy = tape.clone();
+
+        assert!(tape.is_shared());
+
+        tape.set(0, 1);
+        assert!(tape.is_shared());
+
+        tape.set(0, 3);
+        assert!(!tape.is_shared());
+        assert_eq!(tape.cells(), &[3, 2]);
+        assert_eq!(copy.into_cells(), [1, 2]);
+    }
+}
@@ -0,0 +1,215 @@
+//! Runs reporting a `tracing` span each, with the fields OpenTelemetry
+//! exporters expect, so that services embedding the simulator can monitor it
+//! through `tracing-opentelemetry` like the rest of their code.
+//!
+//! Every run is a `turing.run` span recording its step count, final tape
+//! length and stop reason, and ends with an event carrying the
+//! `monotonic_counter.turing.steps` and `histogram.turing.run.duration_ms`
+//! fields the metrics layer turns into metrics.
+//!
+//! [`layer`] exports both through an OpenTelemetry pipeline; the tracer and
+//! meter provider, and so the exporter (OTLP, stdout, ...), are the caller's.
+
+use std::time::Instant;
+
+use opentelemetry::metrics::MeterProvider;
+use opentelemetry::trace::Tracer;
+use tracing::field::Empty;
+use tracing::Subscriber;
+use tracing_opentelemetry::MetricsLayer;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+use crate::{Executor, Machine, RunStats, StopReason, Tape, TransitionTable};
+
+impl<S, Sym, T> Machine<S, Sym, T>
+where
+    Sym: Default + Clone,
+    T: Tape<Sym>,
+{
+    /// Like [`Machine::run`], inside a `turing.run` span.
+    pub fn run_instrumented<E>(&mut self, max_steps: u64) -> RunStats
+    where
+        E: Executor<S, Sym>,
+    {
+        instrumented(|| self.run::<E>(max_steps))
+    }
+
+    /// Like [`Machine::run_table`], inside a `turing.run` span.
+    pub fn run_table_instrumented(
+        &mut self,
+        table: &TransitionTable<S, Sym>,
+        max_steps: u64,
+    ) -> RunStats
+    where
+        S: Ord + Clone,
+        Sym: Ord + Clone,
+    {
+        instrumented(|| self.run_table(table, max_steps))
+    }
+}
+
+/// A `tracing-subscriber` layer exporting the `turing.run` spans to `tracer`
+/// and the run metrics to `meter_provider`.
+pub fn layer<Reg, T, M>(tracer: T, meter_provider: M) -> impl Layer<Reg>
+where
+    Reg: Subscriber + for<'span> LookupSpan<'span>,
+    T: Tracer + Send + Sync + 'static,
+    T::Span: Send + Sync,
+    M: MeterProvider + Send + Sync + 'static,
+{
+    tracing_opentelemetry::layer()
+        .with_tracer(tracer)
+        .and_then(MetricsLayer::new(meter_provider))
+}
+
+fn instrumented(run: impl FnOnce() -> RunStats) -> RunStats {
+    let span = tracing::info_span!(
+        "turing.run",
+        otel.name = "turing.run",
+        turing.steps = Empty,
+        turing.tape_len = Empty,
+        turing.stop = Empty,
+    );
+    let _entered = span.enter();
+
+    let start = Instant::now();
+    let stats = run();
+    let duration = start.elapsed().as_secs_f64() * 1000.0;
+
+    span.record("turing.steps", stats.steps);
+    span.record("turing.tape_len", stats.tape_len as u64);
+    span.record("turing.stop", stop_name(stats.stop));
+
+    tracing::info!(
+        monotonic_counter.turing.steps = stats.steps,
+        histogram.turing.run.duration_ms = duration,
+        turing.stop = stop_name(stats.stop),
+        "run stopped",
+    );
+
+    stats
+}
+
+fn stop_name(stop: StopReason) -> &'static str {
+    match stop {
+        StopReason::Halted => "halted",
+        StopReason::StepLimit => "step_limit",
+        StopReason::Cancelled => "cancelled",
+        StopReason::NoRule => "no_rule",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Debug;
+    use std::sync::{Arc, Mutex};
+
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::subscriber::{self, Subscriber};
+    use tracing::{Event, Metadata};
+
+    use super::*;
+    use crate::Rule;
+
+    // collects every field recorded on spans and events as `name=value`
+    #[derive(Default)]
+    struct Fields(Mutex<Vec<String>>);
+
+    impl Visit for &Fields {
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            let field = format!("{}={value:?}", field.name());
+            self.0.lock().unwrap().push(field);
+        }
+    }
+
+    impl Subscriber for Fields {
+        fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, _span: &Attributes<'_>) -> Id {
+            Id::from_u64(1)
+        }
+
+        fn record(&self, _span: &Id, values: &Record<'_>) {
+            values.record(&mut &*self);
+        }
+
+        fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            event.record(&mut &*self);
+        }
+
+        fn enter(&self, _span: &Id) {}
+
+        fn exit(&self, _span: &Id) {}
+    }
+
+    #[test]
+    fn run_instrumented_test() {
+        let table: TransitionTable<u8, bool> = [(0, false, Rule::new().write(true).right())]
+            .into_iter()
+            .collect();
+
+        let fields = Arc::new(Fields::default());
+        let mut machine: Machine<u8, bool> = Machine::new(0, [false].into());
+        let stats = subscriber::with_default(fields.clone(), || {
+            machine.run_table_instrumented(&table, 10)
+        });
+
+        let fields = fields.0.lock().unwrap();
+
+        assert_eq!(stats.steps, 10);
+        assert!(fields.contains(&"turing.steps=10".to_owned()));
+        assert!(fields.contains(&"turing.stop=\"step_limit\"".to_owned()));
+        assert!(fields.contains(&"monotonic_counter.turing.steps=10".to_owned()));
+    }
+
+    #[test]
+    fn layer_test() {
+        use opentelemetry::trace::TracerProvider;
+        use opentelemetry::{KeyValue, Value};
+        use opentelemetry_sdk::metrics::{InMemoryMetricExporter, SdkMeterProvider};
+        use opentelemetry_sdk::trace::{InMemorySpanExporter, SdkTracerProvider};
+        use tracing_subscriber::layer::SubscriberExt;
+        use tracing_subscriber::Registry;
+
+        let table: TransitionTable<u8, bool> = [(0, false, Rule::new().write(true).right())]
+            .into_iter()
+            .collect();
+
+        let spans = InMemorySpanExporter::default();
+        let tracers = SdkTracerProvider::builder()
+            .with_simple_exporter(spans.clone())
+            .build();
+        let metrics = InMemoryMetricExporter::default();
+        let meters = SdkMeterProvider::builder()
+            .with_periodic_exporter(metrics.clone())
+            .build();
+
+        let subscriber = Registry::default().with(layer(tracers.tracer("turing"), meters.clone()));
+        let mut machine: Machine<u8, bool> = Machine::new(0, [false].into());
+        subscriber::with_default(subscriber, || machine.run_table_instrumented(&table, 10));
+        meters.force_flush().unwrap();
+
+        let spans = spans.get_finished_spans().unwrap();
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].name, "turing.run");
+        assert!(spans[0]
+            .attributes
+            .contains(&KeyValue::new("turing.stop", Value::from("step_limit"))));
+
+        let metrics = metrics.get_finished_metrics().unwrap();
+        let names: Vec<_> = metrics
+            .iter()
+            .flat_map(|resource| resource.scope_metrics())
+            .flat_map(|scope| scope.metrics())
+            .map(|metric| metric.name().to_owned())
+            .collect();
+        assert!(names.contains(&"turing.steps".to_owned()));
+        assert!(names.contains(&"turing.run.duration_ms".to_owned()));
+    }
+}
@@ -0,0 +1,238 @@
+use std::error::Error;
+use std::fmt::{self, Debug, Display};
+
+use crate::{Rule, TraceSink, TraceStep};
+
+/// Temporal properties of a run, checked step by step as a [`TraceSink`]:
+/// pass it to [`Machine::run_traced`](crate::Machine::run_traced) and call
+/// [`Properties::finish`] afterwards. The run stops at the first violation.
+///
+/// Properties are predicates over the steps of a run, e.g. whether a step
+/// enters a state or writes a symbol. Every step is kept for the
+/// counterexample, so bound long runs.
+pub struct Properties<'a, S, Sym> {
+    properties: Vec<(String, Property<'a, S, Sym>)>,
+    trace: Vec<RecordedStep<S, Sym>>,
+    violated: Option<usize>,
+}
+
+type Predicate<'a, S, Sym> = Box<dyn Fn(&TraceStep<'_, S, Sym>) -> bool + 'a>;
+
+enum Property<'a, S, Sym> {
+    Always(Predicate<'a, S, Sym>),
+    NeverAfter {
+        trigger: Predicate<'a, S, Sym>,
+        forbidden: Predicate<'a, S, Sym>,
+        triggered: bool,
+    },
+    Eventually {
+        wanted: Predicate<'a, S, Sym>,
+        seen: bool,
+    },
+}
+
+/// An owned copy of a [`TraceStep`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedStep<S, Sym> {
+    pub step: u64,
+    pub state: S,
+    pub head: isize,
+    pub read: Sym,
+    pub rule: Rule<S, Sym>,
+}
+
+/// Returned by [`Properties::finish`] for the first property the run
+/// violated, with the steps up to the violation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Counterexample<S, Sym> {
+    pub property: String,
+    pub trace: Vec<RecordedStep<S, Sym>>,
+}
+
+impl<'a, S, Sym> Properties<'a, S, Sym> {
+    pub fn new() -> Self {
+        Self {
+            properties: Vec::new(),
+            trace: Vec::new(),
+            violated: None,
+        }
+    }
+
+    /// Adds a property that must hold for every step.
+    pub fn always(
+        self,
+        name: impl Into<String>,
+        holds: impl Fn(&TraceStep<'_, S, Sym>) -> bool + 'a,
+    ) -> Self {
+        self.with(name, Property::Always(Box::new(holds)))
+    }
+
+    /// Adds a property that must hold for no step.
+    pub fn never(
+        self,
+        name: impl Into<String>,
+        forbidden: impl Fn(&TraceStep<'_, S, Sym>) -> bool + 'a,
+    ) -> Self {
+        self.always(name, move |step| !forbidden(step))
+    }
+
+    /// Adds a property that must hold for no step after one for which
+    /// `trigger` held, e.g. that a state is never entered after another.
+    pub fn never_after(
+        self,
+        name: impl Into<String>,
+        trigger: impl Fn(&TraceStep<'_, S, Sym>) -> bool + 'a,
+        forbidden: impl Fn(&TraceStep<'_, S, Sym>) -> bool + 'a,
+    ) -> Self {
+        self.with(
+            name,
+            Property::NeverAfter {
+                trigger: Box::new(trigger),
+                forbidden: Box::new(forbidden),
+                triggered: false,
+            },
+        )
+    }
+
+    /// Adds a property that must hold for some step before the run ends,
+    /// however it ends.
+    pub fn eventually(
+        self,
+        name: impl Into<String>,
+        wanted: impl Fn(&TraceStep<'_, S, Sym>) -> bool + 'a,
+    ) -> Self {
+        self.with(
+            name,
+            Property::Eventually {
+                wanted: Box::new(wanted),
+                seen: false,
+            },
+        )
+    }
+
+    fn with(mut self, name: impl Into<String>, property: Property<'a, S, Sym>) -> Self {
+        self.properties.push((name.into(), property));
+        self
+    }
+
+    /// The first violated property with its counterexample, counting
+    /// `eventually` properties that never held.
+    pub fn finish(self) -> Result<(), Counterexample<S, Sym>> {
+        let violated = self.violated.or_else(|| {
+            self.properties.iter().position(|(_, property)| {
+                matches!(property, Property::Eventually { seen: false, .. })
+            })
+        });
+
+        match violated {
+            Some(index) => Err(Counterexample {
+                property: self.properties.into_iter().nth(index).unwrap().0,
+                trace: self.trace,
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<S, Sym> Default for Properties<'_, S, Sym> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: Clone, Sym: Clone> TraceSink<S, Sym> for Properties<'_, S, Sym> {
+    fn record(&mut self, step: &TraceStep<'_, S, Sym>) -> bool {
+        self.trace.push(RecordedStep {
+            step: step.step,
+            state: step.state.clone(),
+            head: step.head,
+            read: step.read.clone(),
+            rule: step.rule.clone(),
+        });
+
+        self.violated = self
+            .properties
+            .iter_mut()
+            .position(|(_, property)| match property {
+                Property::Always(holds) => !holds(step),
+                Property::NeverAfter {
+                    trigger,
+                    forbidden,
+                    triggered,
+                } => {
+                    let violated = *triggered && forbidden(step);
+                    *triggered |= trigger(step);
+                    violated
+                }
+                Property::Eventually { wanted, seen } => {
+                    *seen |= !*seen && wanted(step);
+                    false
+                }
+            });
+
+        self.violated.is_none()
+    }
+}
+
+impl<S, Sym> Display for Counterexample<S, Sym> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "property {:?} violated after {} steps",
+            self.property,
+            self.trace.len()
+        )
+    }
+}
+
+impl<S: Debug, Sym: Debug> Error for Counterexample<S, Sym> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{dsl, Char, Machine, State};
+
+    fn enters(state: &'static str) -> impl Fn(&TraceStep<'_, String, Char>) -> bool {
+        move |step| matches!(&step.rule.new_state, Some(State::State(next)) if next == state)
+    }
+
+    #[test]
+    fn properties_test() {
+        // walks right over the input, then back left to its start
+        let table = dsl::parse::<Char>(
+            "right 1 * R *\nright _ * L left\nleft 1 X L *\nleft _ * R done\ndone X X N halt",
+        )
+        .unwrap();
+        let run = |properties: &mut Properties<'_, String, Char>| {
+            let mut machine = Machine::with_input("right".to_owned(), "11").unwrap();
+            machine.run_table_traced(&table, 100, properties).steps
+        };
+
+        let mut properties = Properties::new()
+            .never_after("no turning back", enters("left"), enters("right"))
+            .eventually("writes X", |step| step.rule.write == Some(Char('X')));
+
+        assert_eq!(run(&mut properties), 7);
+        assert_eq!(properties.finish(), Ok(()));
+
+        let mut properties = Properties::new().never_after(
+            "stays right",
+            |step| step.state == "right",
+            enters("left"),
+        );
+
+        assert_eq!(run(&mut properties), 3);
+
+        let counterexample = properties.finish().unwrap_err();
+
+        assert_eq!(counterexample.property, "stays right");
+        assert_eq!(counterexample.trace.len(), 3);
+        assert_eq!(counterexample.trace[2].read, Char('_'));
+
+        let mut properties =
+            Properties::new().eventually("writes 0", |step| step.rule.write == Some(Char('0')));
+        run(&mut properties);
+
+        assert_eq!(properties.finish().unwrap_err().trace.len(), 7);
+    }
+}
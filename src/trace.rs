@@ -0,0 +1,336 @@
+use std::cell::Cell;
+use std::fmt::Display;
+use std::io::{self, Write};
+use std::sync::atomic::AtomicBool;
+
+use crate::{
+    json_string, move_name, Executor, Machine, Rule, RunStats, State, Symbol, Tape, TransitionTable,
+};
+
+/// One step of a run: the configuration it started from and the rule that
+/// was applied to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceStep<'a, S, Sym> {
+    pub step: u64,
+    pub state: &'a S,
+    /// The head position relative to the initial cell 0.
+    pub head: isize,
+    pub read: &'a Sym,
+    pub rule: &'a Rule<S, Sym>,
+}
+
+/// A consumer of the steps of a run.
+pub trait TraceSink<S, Sym> {
+    /// Returns `false` to stop the run, e.g. after a write error.
+    fn record(&mut self, step: &TraceStep<'_, S, Sym>) -> bool;
+}
+
+impl<S, Sym, T> Machine<S, Sym, T>
+where
+    Sym: Default + Clone,
+    T: Tape<Sym>,
+{
+    /// Like [`Machine::run`], passing every step to `sink`. The run stops as
+    /// cancelled if the sink asks for it.
+    pub fn run_traced<E, K>(&mut self, max_steps: u64, sink: &mut K) -> RunStats
+    where
+        E: Executor<S, Sym>,
+        K: TraceSink<S, Sym>,
+    {
+        self.run_traced_with(max_steps, sink, |state, symbol| {
+            Some(E::execute(state, symbol))
+        })
+    }
+
+    /// Like [`Machine::run_traced`], looking rules up in `table`.
+    pub fn run_table_traced<K>(
+        &mut self,
+        table: &TransitionTable<S, Sym>,
+        max_steps: u64,
+        sink: &mut K,
+    ) -> RunStats
+    where
+        S: Ord + Clone,
+        Sym: Ord + Clone,
+        K: TraceSink<S, Sym>,
+    {
+        self.run_traced_with(max_steps, sink, |state, symbol| table.lookup(state, symbol))
+    }
+
+    fn run_traced_with<K>(
+        &mut self,
+        max_steps: u64,
+        sink: &mut K,
+        rule: impl Fn(&S, &Sym) -> Option<Rule<S, Sym>>,
+    ) -> RunStats
+    where
+        K: TraceSink<S, Sym>,
+    {
+        let mut steps = 0;
+        let stopped = Cell::new(false);
+
+        self.run_loop(
+            max_steps,
+            &AtomicBool::new(false),
+            1,
+            |machine| {
+                let State::State(state) = &machine.state else {
+                    return false;
+                };
+                let read = machine.current_symbol();
+                let Some(rule) = rule(state, read) else {
+                    return false;
+                };
+
+                stopped.set(!sink.record(&TraceStep {
+                    step: steps,
+                    state,
+                    head: machine.head as isize - machine.origin as isize,
+                    read,
+                    rule: &rule,
+                }));
+                steps += 1;
+
+                machine.apply(rule);
+                true
+            },
+            |_, _| !stopped.get(),
+        )
+    }
+}
+
+/// Writes a trace as CSV with a header and one
+/// `step,state,head,read,write,move` row per step.
+///
+/// `write` is the symbol left in the cell and `move` is `L`, `R` or `N`.
+#[derive(Debug)]
+pub struct CsvTrace<W> {
+    writer: W,
+    error: Option<io::Error>,
+    header: bool,
+}
+
+impl<W: Write> CsvTrace<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            error: None,
+            header: false,
+        }
+    }
+
+    /// Flushes the writer and returns it, or the first error encountered.
+    pub fn finish(mut self) -> io::Result<W> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+
+        if !self.header {
+            self.write_header()?;
+        }
+
+        self.writer.flush()?;
+
+        Ok(self.writer)
+    }
+
+    fn write_header(&mut self) -> io::Result<()> {
+        self.header = true;
+        writeln!(self.writer, "step,state,head,read,write,move")
+    }
+}
+
+impl<S, Sym, W> TraceSink<S, Sym> for CsvTrace<W>
+where
+    S: Display,
+    Sym: Symbol,
+    W: Write,
+{
+    fn record(&mut self, step: &TraceStep<'_, S, Sym>) -> bool {
+        let mut record = || {
+            if !self.header {
+                self.write_header()?;
+            }
+
+            let head_move = move_name(step.rule.head_move);
+            let write = step.rule.write.as_ref().unwrap_or(step.read);
+
+            writeln!(
+                self.writer,
+                "{},{},{},{},{},{head_move}",
+                step.step,
+                csv_field(&step.state.to_string()),
+                step.head,
+                csv_field(&step.read.to_char().to_string()),
+                csv_field(&write.to_char().to_string()),
+            )
+        };
+
+        match record() {
+            Ok(()) => true,
+            Err(error) => {
+                self.error = Some(error);
+                false
+            }
+        }
+    }
+}
+
+/// Writes a trace as JSON Lines, one
+/// `{"step":..,"state":..,"head":..,"read":..,"write":..,"move":..}` object
+/// per line, with the same fields as [`CsvTrace`].
+#[derive(Debug)]
+pub struct JsonlTrace<W> {
+    writer: W,
+    error: Option<io::Error>,
+    every: u64,
+}
+
+impl<W: Write> JsonlTrace<W> {
+    pub fn new(writer: W) -> Self {
+        Self::every(writer, 1)
+    }
+
+    /// Writes only every `every`-th step, starting with the first.
+    pub fn every(writer: W, every: u64) -> Self {
+        Self {
+            writer,
+            error: None,
+            every: every.max(1),
+        }
+    }
+
+    /// Flushes the writer and returns it, or the first error encountered.
+    pub fn finish(mut self) -> io::Result<W> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+
+        self.writer.flush()?;
+
+        Ok(self.writer)
+    }
+}
+
+impl<S, Sym, W> TraceSink<S, Sym> for JsonlTrace<W>
+where
+    S: Display,
+    Sym: Symbol,
+    W: Write,
+{
+    fn record(&mut self, step: &TraceStep<'_, S, Sym>) -> bool {
+        if !step.step.is_multiple_of(self.every) {
+            return true;
+        }
+
+        let head_move = move_name(step.rule.head_move);
+        let write = step.rule.write.as_ref().unwrap_or(step.read);
+
+        let record = writeln!(
+            self.writer,
+            r#"{{"step":{},"state":{},"head":{},"read":{},"write":{},"move":"{head_move}"}}"#,
+            step.step,
+            json_string(&step.state.to_string()),
+            step.head,
+            json_string(&step.read.to_char().to_string()),
+            json_string(&write.to_char().to_string()),
+        );
+
+        match record {
+            Ok(()) => true,
+            Err(error) => {
+                self.error = Some(error);
+                false
+            }
+        }
+    }
+}
+
+// quotes fields containing separators, quotes or line breaks
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{dsl, Char};
+
+    #[test]
+    fn csv_trace_test() {
+        let table = dsl::parse("inc 1 0 R *\ninc , 1 N halt").unwrap();
+        let mut machine: Machine<String, Char> =
+            Machine::new("inc".to_owned(), [Char('1'), Char(',')].into());
+        let mut csv = CsvTrace::new(Vec::new());
+
+        let stats = machine.run_table_traced(&table, 100, &mut csv);
+        let csv = String::from_utf8(csv.finish().unwrap()).unwrap();
+
+        assert_eq!(stats.steps, 2);
+        assert_eq!(
+            csv,
+            "step,state,head,read,write,move\n0,inc,0,1,0,R\n1,inc,1,\",\",1,N\n"
+        );
+    }
+
+    #[test]
+    fn jsonl_trace_test() {
+        let table = dsl::parse("inc 1 0 R *\ninc \" 1 N halt").unwrap();
+        let mut machine: Machine<String, Char> =
+            Machine::new("inc".to_owned(), [Char('1'), Char('"')].into());
+        let mut jsonl = JsonlTrace::new(Vec::new());
+
+        machine.run_table_traced(&table, 100, &mut jsonl);
+        let jsonl = String::from_utf8(jsonl.finish().unwrap()).unwrap();
+        let lines: Vec<serde_json::Value> = jsonl
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["state"], "inc");
+        assert_eq!(lines[1]["read"], "\"");
+        assert_eq!(lines[1]["move"], "N");
+
+        let mut machine: Machine<String, Char> =
+            Machine::new("inc".to_owned(), [Char('1'), Char('"')].into());
+        let mut jsonl = JsonlTrace::every(Vec::new(), 2);
+
+        machine.run_table_traced(&table, 100, &mut jsonl);
+        let jsonl = String::from_utf8(jsonl.finish().unwrap()).unwrap();
+
+        assert_eq!(jsonl.lines().count(), 1);
+    }
+
+    #[test]
+    fn sink_stops_run_test() {
+        struct First(Vec<u64>);
+
+        impl TraceSink<u8, bool> for First {
+            fn record(&mut self, step: &TraceStep<'_, u8, bool>) -> bool {
+                self.0.push(step.step);
+                false
+            }
+        }
+
+        struct Walker;
+
+        impl Executor<u8, bool> for Walker {
+            fn execute(_state: &u8, _symbol: &bool) -> Rule<u8, bool> {
+                Rule::new().right()
+            }
+        }
+
+        let mut machine: Machine<u8, bool> = Machine::new(0, [false].into());
+        let mut sink = First(Vec::new());
+        let stats = machine.run_traced::<Walker, _>(100, &mut sink);
+
+        assert_eq!(sink.0, [0]);
+        assert_eq!(stats.steps, 1);
+        assert_eq!(stats.stop, crate::StopReason::Cancelled);
+    }
+}
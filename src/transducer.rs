@@ -0,0 +1,286 @@
+//! Turing transducers: machines with a work tape and a write-only output
+//! tape whose head only moves right, computing functions from strings to
+//! strings, or enumerating the words of a set one after another.
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::sync::atomic::AtomicBool;
+
+use crate::run::CANCEL_CHECK_INTERVAL;
+use crate::{Machine, Rule, RunStats, State};
+
+/// A rule for the work tape, and a symbol to append to the output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransducerRule<S, Sym, Out> {
+    pub rule: Rule<S, Sym>,
+    pub emit: Option<Out>,
+}
+
+impl<S, Sym, Out> From<Rule<S, Sym>> for TransducerRule<S, Sym, Out> {
+    fn from(rule: Rule<S, Sym>) -> Self {
+        Self { rule, emit: None }
+    }
+}
+
+impl<S, Sym, Out> TransducerRule<S, Sym, Out> {
+    /// `rule`, also appending `emit` to the output.
+    pub fn emitting(rule: Rule<S, Sym>, emit: Out) -> Self {
+        Self {
+            rule,
+            emit: Some(emit),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransducerTable<S, Sym, Out> {
+    rules: BTreeMap<(S, Sym), TransducerRule<S, Sym, Out>>,
+}
+
+impl<S, Sym, Out> TransducerTable<S, Sym, Out>
+where
+    S: Ord,
+    Sym: Ord,
+{
+    pub fn new() -> Self {
+        Self {
+            rules: BTreeMap::new(),
+        }
+    }
+
+    /// Sets the rule for reading `symbol` in `state`, returning the rule it
+    /// replaced, if any.
+    pub fn insert(
+        &mut self,
+        state: S,
+        symbol: Sym,
+        rule: impl Into<TransducerRule<S, Sym, Out>>,
+    ) -> Option<TransducerRule<S, Sym, Out>> {
+        self.rules.insert((state, symbol), rule.into())
+    }
+
+    pub fn get(&self, state: &S, symbol: &Sym) -> Option<&TransducerRule<S, Sym, Out>>
+    where
+        S: Clone,
+        Sym: Clone,
+    {
+        self.rules.get(&(state.clone(), symbol.clone()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// All `(state, symbol, rule)` entries, ordered by state and then symbol.
+    pub fn iter(&self) -> impl Iterator<Item = (&S, &Sym, &TransducerRule<S, Sym, Out>)> {
+        self.rules
+            .iter()
+            .map(|((state, symbol), rule)| (state, symbol, rule))
+    }
+}
+
+impl<S, Sym, Out> Default for TransducerTable<S, Sym, Out>
+where
+    S: Ord,
+    Sym: Ord,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A machine on its work tape together with what it has written so far to
+/// its output tape.
+#[derive(Debug, Clone)]
+pub struct Transducer<S, Sym: Default, Out> {
+    machine: Machine<S, Sym>,
+    output: Vec<Out>,
+}
+
+impl<S, Sym, Out> Transducer<S, Sym, Out>
+where
+    S: Ord + Clone,
+    Sym: Ord + Clone + Default,
+    Out: Clone,
+{
+    /// Starts `machine` with an empty output.
+    pub fn new(machine: Machine<S, Sym>) -> Self {
+        Self {
+            machine,
+            output: Vec::new(),
+        }
+    }
+
+    /// Steps the machine, returning `false` if it had halted. A missing
+    /// rule halts it without touching either tape.
+    pub fn step(&mut self, table: &TransducerTable<S, Sym, Out>) -> bool {
+        step(&mut self.machine, &mut self.output, table)
+    }
+
+    /// Steps until halting or until `max_steps` steps were taken.
+    pub fn run(&mut self, table: &TransducerTable<S, Sym, Out>, max_steps: u64) -> RunStats {
+        let output = &mut self.output;
+
+        self.machine.run_loop(
+            max_steps,
+            &AtomicBool::new(false),
+            CANCEL_CHECK_INTERVAL,
+            |machine| step(machine, output, table),
+            |_, _| true,
+        )
+    }
+
+    pub fn machine(&self) -> &Machine<S, Sym> {
+        &self.machine
+    }
+
+    /// The output tape, from its first cell to the one before the head.
+    pub fn output(&self) -> &[Out] {
+        &self.output
+    }
+
+    pub fn into_output(self) -> Vec<Out> {
+        self.output
+    }
+
+    /// Runs the machine as an enumerator, iterating over the words it emits
+    /// ended by `separator`, without the separators. Iteration ends when the
+    /// machine halts, dropping an unfinished word, or takes more than
+    /// `max_steps` steps for a word.
+    pub fn enumerate<'a>(
+        self,
+        table: &'a TransducerTable<S, Sym, Out>,
+        separator: Out,
+        max_steps: u64,
+    ) -> Enumerator<'a, S, Sym, Out> {
+        Enumerator {
+            transducer: self,
+            table,
+            separator,
+            max_steps,
+        }
+    }
+}
+
+/// The iterator of [`Transducer::enumerate`].
+#[derive(Debug, Clone)]
+pub struct Enumerator<'a, S, Sym: Default, Out> {
+    transducer: Transducer<S, Sym, Out>,
+    table: &'a TransducerTable<S, Sym, Out>,
+    separator: Out,
+    max_steps: u64,
+}
+
+impl<S, Sym: Default, Out> Enumerator<'_, S, Sym, Out> {
+    /// The machine and whatever it emitted after the last word.
+    pub fn transducer(&self) -> &Transducer<S, Sym, Out> {
+        &self.transducer
+    }
+}
+
+impl<S, Sym, Out> Iterator for Enumerator<'_, S, Sym, Out>
+where
+    S: Ord + Clone,
+    Sym: Ord + Clone + Default,
+    Out: Clone + PartialEq,
+{
+    type Item = Vec<Out>;
+
+    fn next(&mut self) -> Option<Vec<Out>> {
+        for _ in 0..self.max_steps {
+            let emitted = self.transducer.output.len();
+
+            if !self.transducer.step(self.table) {
+                return None;
+            }
+
+            if self.transducer.output.len() > emitted
+                && self.transducer.output.last() == Some(&self.separator)
+            {
+                let mut word: Vec<Out> = self.transducer.output.drain(..).collect();
+                word.pop();
+                return Some(word);
+            }
+        }
+
+        None
+    }
+}
+
+fn step<S, Sym, Out>(
+    machine: &mut Machine<S, Sym>,
+    output: &mut Vec<Out>,
+    table: &TransducerTable<S, Sym, Out>,
+) -> bool
+where
+    S: Ord + Clone,
+    Sym: Ord + Clone + Default,
+    Out: Clone,
+{
+    let State::State(state) = &machine.state else {
+        return false;
+    };
+
+    let Some(rule) = table.get(state, machine.current_symbol()).cloned() else {
+        machine.state = State::Halt;
+        return true;
+    };
+
+    machine.apply(rule.rule);
+    output.extend(rule.emit);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::String;
+
+    use super::*;
+
+    #[test]
+    fn transducer_test() {
+        // outputs the sum of the input in unary, going over every 2 twice and
+        // skipping the 3s
+        let mut table: TransducerTable<u8, u8, char> = TransducerTable::new();
+        table.insert(0, 1, TransducerRule::emitting(Rule::new().right(), '1'));
+        table.insert(0, 2, TransducerRule::emitting(Rule::to(1), '1'));
+        table.insert(1, 2, TransducerRule::emitting(Rule::to(0).right(), '1'));
+        table.insert(0, 3, Rule::new().right());
+
+        let machine = Machine::new(0, [2, 3, 1, 2].into());
+        let mut transducer = Transducer::new(machine);
+        let stats = transducer.run(&table, 100);
+
+        assert_eq!(stats.steps, 7);
+        assert_eq!(String::from_iter(transducer.output()), "11111");
+        assert_eq!(transducer.machine().tape(), &[2, 3, 1, 2, 0]);
+    }
+
+    #[test]
+    fn enumerate_test() {
+        // enumerates 1, 11, 111, ... by writing a 1 on the work tape for
+        // every word and then copying the tape to the output
+        let mut table: TransducerTable<u8, u8, char> = TransducerTable::new();
+        table.insert(0, 0, Rule::to(1).write(1).left());
+        table.insert(1, 1, Rule::new().left());
+        table.insert(1, 0, Rule::to(2).right());
+        table.insert(2, 1, TransducerRule::emitting(Rule::new().right(), '1'));
+        table.insert(2, 0, TransducerRule::emitting(Rule::to(0), ','));
+
+        let transducer = Transducer::new(Machine::new(0, [0].into()));
+        let words: Vec<String> = transducer
+            .enumerate(&table, ',', 100)
+            .take(4)
+            .map(String::from_iter)
+            .collect();
+
+        assert_eq!(words, ["1", "11", "111", "1111"]);
+
+        let halting = Transducer::new(Machine::new(0, [1].into()));
+        assert_eq!(halting.enumerate(&table, ',', 100).next(), None);
+    }
+}
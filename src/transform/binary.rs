@@ -0,0 +1,447 @@
+use alloc::collections::BTreeSet;
+use alloc::vec::Vec;
+
+use super::{default_rule, rule_or_default, states};
+use crate::{analysis, MissingRule, Move, Rule, State, TransitionTable};
+
+/// The states of a machine built by [`reduce_to_binary`].
+///
+/// Every cell of the original tape becomes a block of `width` bits, the
+/// blank becoming all zeros. To simulate one step, the machine reads a block
+/// left to right, writes the new symbol back right to left and then moves a
+/// whole block.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Binary<S> {
+    /// Reading bit `read` of a block in the original `state`, with the bits
+    /// read so far in `bits`, most significant first.
+    Read { state: S, bits: usize, read: usize },
+    /// Writing bit `bit` of the code of the new symbol, counted from the
+    /// least significant, and moving left until the block's first bit.
+    /// `next` of `None` halts.
+    Write {
+        next: Option<S>,
+        head_move: Option<Move>,
+        code: usize,
+        bit: usize,
+    },
+    /// Moving `remaining` more cells to the block the original move lands on.
+    Shift {
+        next: Option<S>,
+        head_move: Move,
+        remaining: usize,
+    },
+}
+
+impl<S> Binary<S> {
+    /// The state to start the reduced machine in for original state `state`.
+    pub fn start(state: S) -> Self {
+        Self::Read {
+            state,
+            bits: 0,
+            read: 0,
+        }
+    }
+}
+
+/// A machine over `bool` equivalent to an original machine over `Sym`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BinaryReduction<S, Sym> {
+    pub table: TransitionTable<Binary<S>, bool>,
+    /// The original symbols, indexed by their code; the blank is code 0.
+    pub symbols: Vec<Sym>,
+    /// The number of bits per block.
+    pub width: usize,
+}
+
+impl<S, Sym> BinaryReduction<S, Sym>
+where
+    Sym: PartialEq,
+{
+    pub fn encode(&self, tape: &[Sym]) -> Option<Vec<bool>> {
+        let mut bits = Vec::with_capacity(tape.len() * self.width);
+
+        for symbol in tape {
+            let code = self.symbols.iter().position(|other| other == symbol)?;
+
+            bits.extend((0..self.width).rev().map(|bit| code >> bit & 1 == 1));
+        }
+
+        Some(bits)
+    }
+
+    /// Decodes whole blocks of `bits`; a trailing partial block is ignored.
+    /// Returns `None` if a block holds no symbol's code.
+    pub fn decode(&self, bits: &[bool]) -> Option<Vec<Sym>>
+    where
+        Sym: Clone,
+    {
+        bits.chunks_exact(self.width)
+            .map(|block| {
+                let code = block.iter().fold(0, |code, &bit| code << 1 | bit as usize);
+
+                self.symbols.get(code).cloned()
+            })
+            .collect()
+    }
+}
+
+/// Encodes the symbols of `table` in binary blocks, yielding an equivalent
+/// machine over `{0, 1}`, `blank` becoming the all-zero block.
+///
+/// The reduced machine starts in [`Binary::start`] of the original start
+/// state with its head on the first bit of the block of the original head.
+/// Its tape matches the original one block for block whenever the original
+/// machine halts; in between, blocks may be partially rewritten. A
+/// [`MissingRule::Default`] becomes a rule for every symbol without one,
+/// and the other policies carry over.
+pub fn reduce_to_binary<S, Sym>(
+    table: &TransitionTable<S, Sym>,
+    blank: Sym,
+) -> BinaryReduction<S, Sym>
+where
+    S: Ord + Clone,
+    Sym: Ord + Clone,
+{
+    let mut symbols = Vec::from([blank.clone()]);
+
+    let mut alphabet = analysis::alphabet(table);
+
+    if let Some(rule) = default_rule(table) {
+        alphabet.extend(&rule.write);
+    }
+
+    symbols.extend(
+        alphabet
+            .into_iter()
+            .filter(|symbol| **symbol != blank)
+            .cloned(),
+    );
+
+    let width = (usize::BITS - (symbols.len() - 1).leading_zeros()).max(1) as usize;
+    let missing = match table.missing() {
+        MissingRule::Default(_) | MissingRule::Halt => MissingRule::Halt,
+        MissingRule::Reject => MissingRule::Reject,
+        MissingRule::Error => MissingRule::Error,
+    };
+    let mut reduced = BinaryReduction {
+        table: TransitionTable::new().with_missing(missing),
+        symbols,
+        width,
+    };
+
+    let states = states(table);
+
+    // states are added to `pending` as the read transitions target them
+    let mut pending = BTreeSet::new();
+
+    for state in states {
+        for read in 0..width {
+            for bits in 0..1 << read {
+                for bit in [false, true] {
+                    let from = Binary::Read {
+                        state: state.clone(),
+                        bits,
+                        read,
+                    };
+                    let bits = bits << 1 | bit as usize;
+
+                    if read + 1 < width {
+                        let next = Binary::Read {
+                            state: state.clone(),
+                            bits,
+                            read: read + 1,
+                        };
+
+                        reduced.table.insert(from, bit, Rule::to(next).right());
+                        continue;
+                    }
+
+                    // the whole block is read: look up the original rule,
+                    // leaving a missing one to a rejecting or erroring policy
+                    let rule = reduced
+                        .symbols
+                        .get(bits)
+                        .and_then(|symbol| rule_or_default(table, state, symbol));
+                    let Some(rule) = rule else {
+                        if let MissingRule::Halt = reduced.table.missing() {
+                            reduced.table.insert(from, bit, Rule::halt());
+                        }
+                        continue;
+                    };
+
+                    let next = match &rule.new_state {
+                        None => Some(state.clone()),
+                        Some(State::State(next)) => Some(next.clone()),
+                        Some(State::Halt) => None,
+                    };
+                    let code = match &rule.write {
+                        Some(write) => reduced.symbols.iter().position(|s| s == write).unwrap(),
+                        None => bits,
+                    };
+                    let write = Binary::Write {
+                        next,
+                        head_move: rule.head_move,
+                        code,
+                        bit: 0,
+                    };
+
+                    // write the last bit in place, then walk back over the rest
+                    let rule = reduced.write_step(&write);
+                    reduced.table.insert(from, bit, rule);
+                    pending.insert(write);
+                }
+            }
+        }
+    }
+
+    for write in pending {
+        let Binary::Write {
+            next,
+            head_move,
+            code,
+            ..
+        } = write
+        else {
+            unreachable!()
+        };
+
+        for bit in 1..width {
+            let write = Binary::Write {
+                next: next.clone(),
+                head_move,
+                code,
+                bit,
+            };
+            let rule = reduced.write_step(&write);
+
+            for read in [false, true] {
+                reduced.table.insert(write.clone(), read, rule.clone());
+            }
+        }
+
+        if let Some(head_move) = head_move {
+            let (head_move, distance) = shift(head_move, width);
+
+            for remaining in 1..distance {
+                let shift = Binary::Shift {
+                    next: next.clone(),
+                    head_move,
+                    remaining,
+                };
+                let to = match remaining {
+                    1 => resume(next.clone()),
+                    _ => Rule::to(Binary::Shift {
+                        next: next.clone(),
+                        head_move,
+                        remaining: remaining - 1,
+                    }),
+                };
+                let to = Rule {
+                    head_move: Some(head_move),
+                    ..to
+                };
+
+                for read in [false, true] {
+                    reduced.table.insert(shift.clone(), read, to.clone());
+                }
+            }
+        }
+    }
+
+    reduced
+}
+
+impl<S, Sym> BinaryReduction<S, Sym>
+where
+    S: Clone,
+{
+    // the rule of a `Binary::Write` state
+    fn write_step(&self, write: &Binary<S>) -> Rule<Binary<S>, bool> {
+        let Binary::Write {
+            next,
+            head_move,
+            code,
+            bit,
+        } = write
+        else {
+            unreachable!()
+        };
+
+        let value = code >> bit & 1 == 1;
+
+        if bit + 1 < self.width {
+            return Rule::to(Binary::Write {
+                next: next.clone(),
+                head_move: *head_move,
+                code: *code,
+                bit: bit + 1,
+            })
+            .write(value)
+            .left();
+        }
+
+        // on the block's first bit: move on to the next block
+        let head_move = head_move.map(|head_move| shift(head_move, self.width));
+        let (to, head_move) = match head_move {
+            None | Some((_, 0)) => return resume(next.clone()).write(value),
+            Some((head_move, 1)) => (resume(next.clone()), head_move),
+            Some((head_move, distance)) => (
+                Rule::to(Binary::Shift {
+                    next: next.clone(),
+                    head_move,
+                    remaining: distance - 1,
+                }),
+                head_move,
+            ),
+        };
+
+        Rule {
+            head_move: Some(head_move),
+            ..to.write(value)
+        }
+    }
+}
+
+// the direction and number of bits of an original move
+fn shift(head_move: Move, width: usize) -> (Move, usize) {
+    let offset = head_move.offset();
+    let direction = match offset < 0 {
+        true => Move::Left,
+        false => Move::Right,
+    };
+
+    (direction, offset.unsigned_abs() * width)
+}
+
+// the rule entering `next` on the first bit of a block
+fn resume<S>(next: Option<S>) -> Rule<Binary<S>, bool> {
+    match next {
+        Some(next) => Rule::to(Binary::start(next)),
+        None => Rule::halt(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::collections::BTreeMap;
+    use alloc::string::String;
+
+    use super::*;
+    use crate::{dsl, Char, Machine, StopReason};
+
+    // the non-blank cells by position relative to cell 0
+    fn cells<Sym: Clone + PartialEq>(
+        tape: &[Sym],
+        origin: usize,
+        blank: &Sym,
+    ) -> BTreeMap<isize, Sym> {
+        tape.iter()
+            .enumerate()
+            .filter(|(_, symbol)| *symbol != blank)
+            .map(|(index, symbol)| (index as isize - origin as isize, symbol.clone()))
+            .collect()
+    }
+
+    fn co_simulate(src: &str, input: &str) {
+        let definition = dsl::parse_definition::<Char>(src).unwrap();
+        let blank = definition.blank.unwrap_or_default();
+        let reduced = reduce_to_binary(&definition.table, blank);
+
+        let mut original: Machine<String, Char> =
+            Machine::with_input_blank(definition.start.clone(), input, blank).unwrap();
+        let original_stats = original.run_table(&definition.table, 10_000);
+
+        let tape: Vec<Char> = input.chars().map(Char).collect();
+        let mut binary: Machine<Binary<String>, bool> = Machine::new(
+            Binary::start(definition.start),
+            reduced.encode(&tape).unwrap().into(),
+        );
+        let binary_stats = binary.run_table(&reduced.table, 1_000_000);
+
+        assert_eq!(original_stats.stop, StopReason::Halted);
+        assert_eq!(binary_stats.stop, StopReason::Halted);
+
+        let original = original.snapshot();
+        let binary = binary.snapshot();
+
+        // align the bits on block boundaries before decoding
+        let skip = binary.origin % reduced.width;
+        let bits = &binary.tape[skip..];
+        let decoded = reduced.decode(bits).unwrap();
+
+        assert_eq!(
+            cells(&decoded, (binary.origin - skip) / reduced.width, &blank),
+            cells(&original.tape, original.origin, &blank)
+        );
+    }
+
+    #[test]
+    fn binary_reduction_test() {
+        // binary increment over {0, 1, _}, least significant bit first
+        co_simulate("inc 1 0 R *\ninc 0 1 N halt\ninc _ 1 N halt", "1101");
+        // the same with 0 as the blank
+        co_simulate("blank 0\ninc 1 0 R *\ninc 0 1 N halt", "1101");
+        // copies a block of as to the left of a separator
+        co_simulate(
+            "
+            start seek
+            seek a x L back
+            seek b b R seek
+            seek _ _ L done
+            back b b L back
+            back _ a R forth
+            back a a L back
+            forth a a R forth
+            forth b b R seek
+            done b b L done
+            done x a L done
+            done a a L done
+            done _ _ R halt
+            ",
+            "baaab",
+        );
+        // jumps two cells at a time, then back past the start
+        co_simulate(
+            "skip 1 x R2 *\nskip 0 y R2 *\nskip _ z L5 back\nback _ x N halt",
+            "1011",
+        );
+    }
+
+    #[test]
+    fn binary_missing_test() {
+        // a default rule is spelled out, its symbol among the others
+        let mut table = dsl::parse::<Char>("a 1 1 R *").unwrap();
+        table.set_missing(MissingRule::Default(Rule::new().write(Char('x')).right()));
+        let reduced = reduce_to_binary(&table, Char::default());
+
+        assert_eq!(reduced.table.missing(), &MissingRule::Halt);
+        assert_eq!(reduced.symbols, [Char('_'), Char('1'), Char('x')]);
+
+        // and a missing rule past the input stops the run with an error
+        table.set_missing(MissingRule::Error);
+        let reduced = reduce_to_binary(&table, Char::default());
+        let mut binary: Machine<Binary<String>, bool> = Machine::new(
+            Binary::start("a".into()),
+            reduced.encode(&[Char('1')]).unwrap().into(),
+        );
+
+        assert_eq!(reduced.table.missing(), &MissingRule::Error);
+        assert_eq!(
+            binary.run_table(&reduced.table, 100).stop,
+            StopReason::NoRule
+        );
+    }
+
+    #[test]
+    fn width_test() {
+        let table = dsl::parse::<Char>("a 1 2 R *\na 2 3 R *\na 3 4 R *\na 4 5 R *").unwrap();
+        let reduced = reduce_to_binary(&table, Char::default());
+
+        assert_eq!(reduced.symbols.len(), 6);
+        assert_eq!(reduced.width, 3);
+        assert_eq!(
+            reduced.encode(&[Char('_'), Char('1')]),
+            Some(Vec::from([false, false, false, false, false, true]))
+        );
+    }
+}
@@ -0,0 +1,212 @@
+use alloc::collections::BTreeSet;
+
+use super::{default_rule, merge_missing, states};
+use crate::{analysis, MissingRule, Rule, State, TransitionTable};
+
+/// Where the second machine of a [`compose`]d pair starts on the first
+/// machine's output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Head {
+    /// Where the first machine halted.
+    Stay,
+    /// On the first cell of the output around the head: the head moves left
+    /// to the nearest blank and then one cell right.
+    Leftmost,
+    /// On the last cell of the output around the head: the head moves right
+    /// to the nearest blank and then one cell left.
+    Rightmost,
+}
+
+/// The states of a machine built by [`compose`].
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Composed<SA, SB> {
+    First(SA),
+    /// Moving the head for [`Head::Leftmost`] or [`Head::Rightmost`].
+    Seek,
+    Second(SB),
+}
+
+/// Builds a machine that runs `a` until it halts, moves the head as `head`
+/// says and then runs `b` from `b_start` on the tape `a` left behind.
+///
+/// Start it in `Composed::First` of `a`'s start state. `a` halts both by
+/// halting rules and by missing rules; either way its last rule is applied
+/// before handing over. A [`MissingRule::Default`] of either machine becomes
+/// a rule for every symbol without one, and a rejecting or erroring policy
+/// carries over, so `None` if one machine rejects and the other errors.
+pub fn compose<SA, SB, Sym>(
+    a: &TransitionTable<SA, Sym>,
+    b: &TransitionTable<SB, Sym>,
+    b_start: SB,
+    head: Head,
+) -> Option<TransitionTable<Composed<SA, SB>, Sym>>
+where
+    SA: Ord + Clone,
+    SB: Ord + Clone,
+    Sym: Ord + Clone + Default,
+{
+    let blank = Sym::default();
+    let handover = match head {
+        Head::Stay => Composed::Second(b_start.clone()),
+        Head::Leftmost | Head::Rightmost => Composed::Seek,
+    };
+    let first = |rule: &Rule<SA, Sym>| Rule {
+        new_state: match &rule.new_state {
+            None => None,
+            Some(State::State(next)) => Some(State::State(Composed::First(next.clone()))),
+            Some(State::Halt) => Some(State::State(handover.clone())),
+        },
+        write: rule.write.clone(),
+        head_move: rule.head_move,
+    };
+    let second = |rule: &Rule<SB, Sym>| Rule {
+        new_state: rule.new_state.as_ref().map(|new_state| match new_state {
+            State::State(next) => State::State(Composed::Second(next.clone())),
+            State::Halt => State::Halt,
+        }),
+        write: rule.write.clone(),
+        head_move: rule.head_move,
+    };
+
+    let mut alphabet: BTreeSet<Sym> = analysis::alphabet(a).into_iter().cloned().collect();
+    alphabet.extend(analysis::alphabet(b).into_iter().cloned());
+    alphabet.extend(default_rule(a).and_then(|rule| rule.write.clone()));
+    alphabet.extend(default_rule(b).and_then(|rule| rule.write.clone()));
+    alphabet.insert(blank.clone());
+
+    let mut table = TransitionTable::new().with_missing(merge_missing(a.missing(), b.missing())?);
+
+    for (state, read, rule) in a.iter() {
+        table.insert(Composed::First(state.clone()), read.clone(), first(rule));
+    }
+
+    // where `a` has no rule it halts, so hand over there too, unless its
+    // policy says otherwise
+    for state in states(a) {
+        for symbol in &alphabet {
+            if a.get(state, symbol).is_some() {
+                continue;
+            }
+
+            let rule = match a.missing() {
+                MissingRule::Halt => Rule::to(handover.clone()),
+                MissingRule::Default(rule) => first(rule),
+                MissingRule::Reject | MissingRule::Error => continue,
+            };
+
+            table.insert(Composed::First(state.clone()), symbol.clone(), rule);
+        }
+    }
+
+    if head != Head::Stay {
+        for symbol in &alphabet {
+            let rule = match (head, *symbol == blank) {
+                (Head::Leftmost, false) => Rule::new().left(),
+                (Head::Rightmost, false) => Rule::new().right(),
+                (Head::Leftmost, true) => Rule::to(Composed::Second(b_start.clone())).right(),
+                _ => Rule::to(Composed::Second(b_start.clone())).left(),
+            };
+
+            table.insert(Composed::Seek, symbol.clone(), rule);
+        }
+    }
+
+    for (state, read, rule) in b.iter() {
+        table.insert(Composed::Second(state.clone()), read.clone(), second(rule));
+    }
+
+    // `b`'s missing rules halt outside a halting policy
+    let halts = table.missing() != &MissingRule::Halt;
+
+    for state in states(b).into_iter().chain([&b_start]) {
+        for symbol in &alphabet {
+            let rule = match (b.get(state, symbol), b.missing()) {
+                (None, MissingRule::Default(rule)) => second(rule),
+                (None, MissingRule::Halt) if halts => Rule::halt(),
+                _ => continue,
+            };
+
+            table.insert(Composed::Second(state.clone()), symbol.clone(), rule);
+        }
+    }
+
+    Some(table)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::borrow::ToOwned;
+    use alloc::string::String;
+
+    use super::*;
+    use crate::{dsl, Char, Machine, StopReason, Symbol};
+
+    fn run(head: Head) -> String {
+        // appends a 1 to a unary number and halts on it
+        let append = dsl::parse::<Char>("a 1 * R *\na _ 1 N halt").unwrap();
+        // marks the 1 under the head
+        let mark = dsl::parse::<Char>("m 1 x N halt").unwrap();
+
+        let table = compose(&append, &mark, "m".to_owned(), head).unwrap();
+        let mut machine: Machine<_, Char> =
+            Machine::with_input(Composed::First("a".to_owned()), "111").unwrap();
+        machine.run_table(&table, 100);
+
+        assert!(machine.halted());
+
+        let (tape, _, _) = machine.finish_trimmed();
+        tape.iter().map(Symbol::to_char).collect()
+    }
+
+    #[test]
+    fn compose_test() {
+        assert_eq!(run(Head::Stay), "111x");
+        assert_eq!(run(Head::Leftmost), "x111");
+        assert_eq!(run(Head::Rightmost), "111x");
+    }
+
+    #[test]
+    fn missing_rule_handover_test() {
+        // halts by a missing rule on the blank after the input
+        let skip = dsl::parse::<Char>("s 1 * R *").unwrap();
+        let mark = dsl::parse::<Char>("m _ x N halt\nm 1 y N halt").unwrap();
+
+        let table = compose(&skip, &mark, "m".to_owned(), Head::Stay).unwrap();
+        let mut machine: Machine<_, Char> =
+            Machine::with_input(Composed::First("s".to_owned()), "11").unwrap();
+        machine.run_table(&table, 100);
+
+        let (tape, _, _) = machine.finish_trimmed();
+
+        assert_eq!(tape, [Char('1'), Char('1'), Char('x')]);
+    }
+
+    #[test]
+    fn compose_missing_test() {
+        // the default rule of the first machine writes a y on the blank after
+        // the input and hands over
+        let mut skip = dsl::parse::<Char>("s 1 * R *").unwrap();
+        skip.set_missing(MissingRule::Default(Rule::halt().write(Char('y'))));
+        let mark = dsl::parse::<Char>("m y x N halt").unwrap();
+
+        let table = compose(&skip, &mark, "m".to_owned(), Head::Stay).unwrap();
+        let mut machine: Machine<_, Char> =
+            Machine::with_input(Composed::First("s".to_owned()), "11").unwrap();
+        machine.run_table(&table, 100);
+
+        let (tape, _, _) = machine.finish_trimmed();
+        assert_eq!(tape, [Char('1'), Char('1'), Char('x')]);
+
+        // an erroring first machine stops the composed one there
+        skip.set_missing(MissingRule::Error);
+        let table = compose(&skip, &mark, "m".to_owned(), Head::Stay).unwrap();
+        let mut machine: Machine<_, Char> =
+            Machine::with_input(Composed::First("s".to_owned()), "11").unwrap();
+
+        assert_eq!(machine.run_table(&table, 100).stop, StopReason::NoRule);
+
+        let mut reject = mark.clone();
+        reject.set_missing(MissingRule::Reject);
+        assert!(compose(&skip, &reject, "m".to_owned(), Head::Stay).is_none());
+    }
+}
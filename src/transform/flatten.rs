@@ -0,0 +1,348 @@
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt::{self, Debug, Display};
+
+use crate::{analysis, Rule, State, TransitionTable};
+
+/// A machine that can be called from other machines, by name `N`.
+///
+/// It starts in `entry` and returns to its caller when it halts, by a
+/// halting rule or a missing one, or when it enters one of its exit states.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubMachine<N, S, Sym> {
+    pub table: TransitionTable<S, Sym>,
+    pub entry: S,
+    pub exits: BTreeSet<S>,
+    pub calls: BTreeMap<S, Call<N, S>>,
+}
+
+/// What entering a call site does: run `machine` and continue in `returns`,
+/// or in the state `exits` pairs with the exit state it returned from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Call<N, S> {
+    pub machine: N,
+    pub returns: S,
+    pub exits: BTreeMap<S, S>,
+}
+
+impl<N, S, Sym> SubMachine<N, S, Sym>
+where
+    S: Ord,
+{
+    pub fn new(table: TransitionTable<S, Sym>, entry: S) -> Self {
+        Self {
+            table,
+            entry,
+            exits: BTreeSet::new(),
+            calls: BTreeMap::new(),
+        }
+    }
+
+    /// Makes entering `state` return to the caller.
+    pub fn exit(mut self, state: S) -> Self {
+        self.exits.insert(state);
+        self
+    }
+
+    /// Makes entering `site` call `machine`, continuing in `returns` when it
+    /// returns. Rules of `site` itself are ignored.
+    pub fn call(self, site: S, machine: N, returns: S) -> Self {
+        self.call_with_exits(site, machine, returns, [])
+    }
+
+    /// Like [`SubMachine::call`], continuing in the state paired with the
+    /// exit state `machine` returns from, if any.
+    pub fn call_with_exits(
+        mut self,
+        site: S,
+        machine: N,
+        returns: S,
+        exits: impl IntoIterator<Item = (S, S)>,
+    ) -> Self {
+        let call = Call {
+            machine,
+            returns,
+            exits: exits.into_iter().collect(),
+        };
+
+        self.calls.insert(site, call);
+        self
+    }
+}
+
+/// A state of a [`flatten`]ed machine: a state of one of the sub-machines,
+/// along with the call sites on the way to it, outermost first.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Scoped<S> {
+    pub path: Vec<S>,
+    pub state: S,
+}
+
+impl<S> Scoped<S> {
+    /// A state of the outermost machine.
+    pub fn top(state: S) -> Self {
+        Self {
+            path: Vec::new(),
+            state,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FlattenError<N> {
+    /// A machine calls a name that `machines` has no machine for.
+    Undefined(N),
+    /// A machine calls itself, directly or through others.
+    Recursive(N),
+}
+
+impl<N: Debug> Display for FlattenError<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Undefined(name) => write!(f, "undefined machine {name:?}"),
+            Self::Recursive(name) => write!(f, "recursive call of {name:?}"),
+        }
+    }
+}
+
+impl<N: Debug> Error for FlattenError<N> {}
+
+/// Inlines every call of the machine `main` into one transition table, a
+/// copy of the callee for each call site, namespaced by [`Scoped::path`].
+///
+/// Start it in [`Scoped::top`] of `main`'s entry state; it halts when `main`
+/// returns. Entering a call site and returning take one step each.
+pub fn flatten<N, S, Sym>(
+    machines: &BTreeMap<N, SubMachine<N, S, Sym>>,
+    main: &N,
+) -> Result<TransitionTable<Scoped<S>, Sym>, FlattenError<N>>
+where
+    N: Ord + Clone,
+    S: Ord + Clone,
+    Sym: Ord + Clone + Default,
+{
+    let mut alphabet = BTreeSet::from([Sym::default()]);
+
+    for machine in machines.values() {
+        alphabet.extend(analysis::alphabet(&machine.table).into_iter().cloned());
+    }
+
+    let mut flattener = Flattener {
+        machines,
+        alphabet,
+        table: TransitionTable::new(),
+        active: Vec::new(),
+    };
+    flattener.emit(main, &[], None)?;
+
+    Ok(flattener.table)
+}
+
+struct Flattener<'a, N, S, Sym> {
+    machines: &'a BTreeMap<N, SubMachine<N, S, Sym>>,
+    alphabet: BTreeSet<Sym>,
+    table: TransitionTable<Scoped<S>, Sym>,
+    // names of the machines being inlined, to reject recursion
+    active: Vec<&'a N>,
+}
+
+impl<'a, N, S, Sym> Flattener<'a, N, S, Sym>
+where
+    N: Ord + Clone,
+    S: Ord + Clone,
+    Sym: Ord + Clone,
+{
+    // emits the copy of `name` at `path`, returning through `call` made from
+    // the states at `path` without its last element
+    fn emit(
+        &mut self,
+        name: &'a N,
+        path: &[S],
+        call: Option<&Call<N, S>>,
+    ) -> Result<(), FlattenError<N>> {
+        if self.active.contains(&name) {
+            return Err(FlattenError::Recursive(name.clone()));
+        }
+
+        let machine = self
+            .machines
+            .get(name)
+            .ok_or_else(|| FlattenError::Undefined(name.clone()))?;
+        let scoped = |state: &S| Scoped {
+            path: path.to_vec(),
+            state: state.clone(),
+        };
+        let returns = |exit: Option<&S>| match call {
+            None => State::Halt,
+            Some(call) => State::State(Scoped {
+                path: path[..path.len() - 1].to_vec(),
+                state: exit
+                    .and_then(|exit| call.exits.get(exit))
+                    .unwrap_or(&call.returns)
+                    .clone(),
+            }),
+        };
+
+        self.active.push(name);
+
+        let mut states: BTreeSet<&S> = machine.table.states().collect();
+        states.insert(&machine.entry);
+        states.extend(machine.exits.iter());
+
+        for (site, call) in &machine.calls {
+            states.insert(site);
+            states.insert(&call.returns);
+            states.extend(call.exits.values());
+        }
+
+        for (state, read, rule) in machine.table.iter() {
+            states.extend(match &rule.new_state {
+                Some(State::State(next)) => Some(next),
+                _ => None,
+            });
+
+            if machine.exits.contains(state) || machine.calls.contains_key(state) {
+                continue;
+            }
+
+            let new_state = match &rule.new_state {
+                None => None,
+                Some(State::State(next)) => Some(State::State(scoped(next))),
+                Some(State::Halt) => Some(returns(None)),
+            };
+
+            self.table.insert(
+                scoped(state),
+                read.clone(),
+                Rule {
+                    new_state,
+                    write: rule.write.clone(),
+                    head_move: rule.head_move,
+                },
+            );
+        }
+
+        for state in states {
+            let target = if let Some(call) = machine.calls.get(state) {
+                let mut inner = path.to_vec();
+                inner.push(state.clone());
+
+                self.emit(&call.machine, &inner, Some(call))?;
+
+                let entry = &self.machines[&call.machine].entry;
+                State::State(Scoped {
+                    path: inner,
+                    state: entry.clone(),
+                })
+            } else if machine.exits.contains(state) {
+                returns(Some(state))
+            } else {
+                // where there is no rule the machine halts, so return there
+                returns(None)
+            };
+
+            for symbol in &self.alphabet {
+                if self.table.get(&scoped(state), symbol).is_none() {
+                    self.table.insert(
+                        scoped(state),
+                        symbol.clone(),
+                        Rule {
+                            new_state: Some(target.clone()),
+                            ..Rule::new()
+                        },
+                    );
+                }
+            }
+        }
+
+        self.active.pop();
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::borrow::ToOwned;
+    use alloc::string::String;
+
+    use super::*;
+    use crate::{dsl, Char, Machine, Symbol};
+
+    type Machines = BTreeMap<&'static str, SubMachine<&'static str, String, Char>>;
+
+    fn sub(src: &str, entry: &str) -> SubMachine<&'static str, String, Char> {
+        SubMachine::new(dsl::parse(src).unwrap(), entry.to_owned())
+    }
+
+    fn run(machines: &Machines, input: &str) -> String {
+        let table = flatten(machines, &"main").unwrap();
+        let entry = machines["main"].entry.clone();
+        let mut machine: Machine<_, Char> = Machine::with_input(Scoped::top(entry), input).unwrap();
+        machine.run_table(&table, 1_000);
+
+        assert!(machine.halted());
+
+        let (tape, _, _) = machine.finish_trimmed();
+        tape.iter().map(Symbol::to_char).collect()
+    }
+
+    #[test]
+    fn flatten_test() {
+        // finds the next blank to the right
+        let find = sub("f 1 * R *", "f");
+        // appends a 1 twice
+        let main = sub("b _ 1 N c\nd _ 1 N halt", "a")
+            .call("a".to_owned(), "find", "b".to_owned())
+            .call("c".to_owned(), "find", "d".to_owned());
+        let machines = Machines::from([("find", find), ("main", main)]);
+
+        assert_eq!(run(&machines, "11"), "1111");
+
+        let table = flatten(&machines, &"main").unwrap();
+        let copies = table.states().filter(|scoped| scoped.state == "f").count();
+
+        assert_eq!(copies, 2);
+    }
+
+    #[test]
+    fn exit_test() {
+        // skips ones and exits through `x` on an x, or halts on a blank
+        let scan = sub("s 1 * R *\ns x * N x", "s").exit("x".to_owned());
+        let main = sub("y x y N halt\nz _ z N halt", "a").call_with_exits(
+            "a".to_owned(),
+            "scan",
+            "z".to_owned(),
+            [("x".to_owned(), "y".to_owned())],
+        );
+        // nested one level deeper
+        let outer = sub("", "m").call("m".to_owned(), "inner", "done".to_owned());
+        let machines = Machines::from([("scan", scan), ("inner", main), ("main", outer)]);
+
+        assert_eq!(run(&machines, "11x"), "11y");
+        assert_eq!(run(&machines, "11"), "11z");
+    }
+
+    #[test]
+    fn flatten_error_test() {
+        let a = sub("", "a").call("a".to_owned(), "b", "a".to_owned());
+        let b = sub("", "b").call("b".to_owned(), "main", "b".to_owned());
+        let machines = Machines::from([("main", a), ("b", b)]);
+
+        assert_eq!(
+            flatten(&machines, &"main"),
+            Err(FlattenError::Recursive("main"))
+        );
+
+        let machines = Machines::from([(
+            "main",
+            sub("", "a").call("a".to_owned(), "c", "a".to_owned()),
+        )]);
+
+        assert_eq!(
+            flatten(&machines, &"main"),
+            Err(FlattenError::Undefined("c"))
+        );
+    }
+}
@@ -0,0 +1,86 @@
+//! Constructions turning machines into equivalent machines of another form.
+
+pub mod binary;
+pub mod compose;
+pub mod flatten;
+pub mod product;
+pub mod single_tape;
+pub mod strict;
+
+pub use binary::{reduce_to_binary, Binary, BinaryReduction};
+pub use compose::{compose, Composed, Head};
+pub use flatten::{flatten, Call, FlattenError, Scoped, SubMachine};
+pub use product::{product, Sync};
+pub use single_tape::{to_single_tape, Sim};
+pub use strict::{to_strict_moves, Strict};
+
+use alloc::collections::BTreeSet;
+
+use crate::{MissingRule, Rule, State, TransitionTable};
+
+// the rule `table` has for `state` reading `symbol`, its
+// `MissingRule::Default` standing in for a missing one
+fn rule_or_default<'t, S, Sym>(
+    table: &'t TransitionTable<S, Sym>,
+    state: &S,
+    symbol: &Sym,
+) -> Option<&'t Rule<S, Sym>>
+where
+    S: Ord,
+    Sym: Ord,
+{
+    match (table.get(state, symbol), table.missing()) {
+        (Some(rule), _) | (None, MissingRule::Default(rule)) => Some(rule),
+        (None, _) => None,
+    }
+}
+
+// the default rule of `table`, if it has one
+fn default_rule<S, Sym>(table: &TransitionTable<S, Sym>) -> Option<&Rule<S, Sym>>
+where
+    S: Ord,
+    Sym: Ord,
+{
+    match table.missing() {
+        MissingRule::Default(rule) => Some(rule),
+        _ => None,
+    }
+}
+
+// the states of `table` and those its rules, default one included, enter
+fn states<S, Sym>(table: &TransitionTable<S, Sym>) -> BTreeSet<&S>
+where
+    S: Ord,
+    Sym: Ord,
+{
+    let mut states: BTreeSet<&S> = table.states().collect();
+
+    for rule in table
+        .iter()
+        .map(|(_, _, rule)| rule)
+        .chain(default_rule(table))
+    {
+        if let Some(State::State(next)) = &rule.new_state {
+            states.insert(next);
+        }
+    }
+
+    states
+}
+
+// the policy of a table built from tables with policies `a` and `b` once
+// their default and, where needed, halting rules are spelled out: the one
+// rejecting or erroring, or `None` if one rejects and the other errors
+fn merge_missing<SA, A, SB, B, S, Sym>(
+    a: &MissingRule<SA, A>,
+    b: &MissingRule<SB, B>,
+) -> Option<MissingRule<S, Sym>> {
+    match (a, b) {
+        (MissingRule::Reject, MissingRule::Error) | (MissingRule::Error, MissingRule::Reject) => {
+            None
+        }
+        (MissingRule::Reject, _) | (_, MissingRule::Reject) => Some(MissingRule::Reject),
+        (MissingRule::Error, _) | (_, MissingRule::Error) => Some(MissingRule::Error),
+        _ => Some(MissingRule::Halt),
+    }
+}
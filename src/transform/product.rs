@@ -0,0 +1,270 @@
+use alloc::collections::{BTreeSet, VecDeque};
+
+use super::{default_rule, merge_missing, rule_or_default, states};
+use crate::{analysis, MissingRule, Rule, State, TransitionTable};
+
+/// How the shared head of a [`product`] machine moves when the components
+/// want different moves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sync {
+    /// Halt, without writing, as soon as the components disagree.
+    Agree,
+    /// Follow the first machine; the second one's moves are ignored.
+    First,
+    /// Follow the second machine; the first one's moves are ignored.
+    Second,
+}
+
+/// Builds the product of `a` and `b`: a machine over pairs of states that
+/// runs both in lockstep, each on its own track of a shared tape with a
+/// shared head.
+///
+/// A state of the product has a rule exactly when both components have one
+/// for their track's symbol, so it stops as soon as either component does,
+/// as that component's [`MissingRule`] policy says. A
+/// [`MissingRule::Default`] becomes a rule for every symbol without one, and
+/// a rejecting or erroring policy carries over, so `None` if one component
+/// rejects and the other errors. With [`Sync::Agree`] on read-only machines,
+/// such as those built by
+/// [`Dfa::to_machine`](crate::automata::Dfa::to_machine) and given the same
+/// input on both tracks, the product decides the intersection of the
+/// languages.
+pub fn product<SA, SB, A, B>(
+    a: &TransitionTable<SA, A>,
+    b: &TransitionTable<SB, B>,
+    sync: Sync,
+) -> Option<TransitionTable<(SA, SB), (A, B)>>
+where
+    SA: Ord + Clone,
+    SB: Ord + Clone,
+    A: Ord + Clone + Default,
+    B: Ord + Clone + Default,
+{
+    let mut table = TransitionTable::new().with_missing(merge_missing(a.missing(), b.missing())?);
+    // missing rules of a halting component only halt the product under a
+    // halting policy, and are spelled out otherwise
+    let halts = table.missing() != &MissingRule::Halt;
+
+    let (states_a, states_b) = (states(a), states(b));
+    let (alphabet_a, alphabet_b) = (alphabet(a), alphabet(b));
+
+    for &state_a in &states_a {
+        for read_a in &alphabet_a {
+            for &state_b in &states_b {
+                for read_b in &alphabet_b {
+                    let from = (
+                        (state_a.clone(), state_b.clone()),
+                        (read_a.clone(), read_b.clone()),
+                    );
+                    let rules = (
+                        rule_or_default(a, state_a, read_a),
+                        rule_or_default(b, state_b, read_b),
+                    );
+
+                    let (rule_a, rule_b) = match rules {
+                        (Some(rule_a), Some(rule_b)) => (rule_a, rule_b),
+                        // the first component to miss a rule decides
+                        (None, _) if a.missing() != &MissingRule::Halt => continue,
+                        (Some(_), None) if b.missing() != &MissingRule::Halt => continue,
+                        _ => {
+                            if halts {
+                                table.insert(from.0, from.1, Rule::halt());
+                            }
+                            continue;
+                        }
+                    };
+
+                    let rule = match sync {
+                        Sync::Agree if rule_a.head_move != rule_b.head_move => Rule::halt(),
+                        _ => step(state_a, read_a, rule_a, state_b, read_b, rule_b, sync),
+                    };
+
+                    table.insert(from.0, from.1, rule);
+                }
+            }
+        }
+    }
+
+    Some(table)
+}
+
+// the rule of the product applying `rule_a` and `rule_b` together
+fn step<SA, SB, A, B>(
+    state_a: &SA,
+    read_a: &A,
+    rule_a: &Rule<SA, A>,
+    state_b: &SB,
+    read_b: &B,
+    rule_b: &Rule<SB, B>,
+    sync: Sync,
+) -> Rule<(SA, SB), (A, B)>
+where
+    SA: Clone,
+    SB: Clone,
+    A: Clone,
+    B: Clone,
+{
+    let head_move = match sync {
+        Sync::Agree | Sync::First => rule_a.head_move,
+        Sync::Second => rule_b.head_move,
+    };
+
+    let new_state = match (
+        target(state_a, &rule_a.new_state),
+        target(state_b, &rule_b.new_state),
+    ) {
+        (Some(next_a), Some(next_b)) => State::State((next_a, next_b)),
+        _ => State::Halt,
+    };
+
+    let write = match (&rule_a.write, &rule_b.write) {
+        (None, None) => None,
+        (write_a, write_b) => Some((
+            write_a.clone().unwrap_or_else(|| read_a.clone()),
+            write_b.clone().unwrap_or_else(|| read_b.clone()),
+        )),
+    };
+
+    Rule {
+        new_state: Some(new_state),
+        write,
+        head_move,
+    }
+}
+
+// the symbols a component reads or writes, its default rule's and the blank
+// included
+fn alphabet<S, Sym>(table: &TransitionTable<S, Sym>) -> BTreeSet<Sym>
+where
+    S: Ord,
+    Sym: Ord + Clone + Default,
+{
+    let mut alphabet: BTreeSet<Sym> = analysis::alphabet(table).into_iter().cloned().collect();
+    alphabet.extend(default_rule(table).and_then(|rule| rule.write.clone()));
+    alphabet.insert(Sym::default());
+    alphabet
+}
+
+/// A tape for a [`product`] machine with `a` and `b` on its tracks, the
+/// shorter one padded with blanks.
+pub fn zip_tracks<A, B>(a: &[A], b: &[B]) -> VecDeque<(A, B)>
+where
+    A: Clone + Default,
+    B: Clone + Default,
+{
+    (0..a.len().max(b.len()).max(1))
+        .map(|index| {
+            (
+                a.get(index).cloned().unwrap_or_default(),
+                b.get(index).cloned().unwrap_or_default(),
+            )
+        })
+        .collect()
+}
+
+// the state a component is in after a rule, or `None` if it halts
+fn target<S: Clone>(state: &S, new_state: &Option<State<S>>) -> Option<S> {
+    match new_state {
+        None => Some(state.clone()),
+        Some(State::State(next)) => Some(next.clone()),
+        Some(State::Halt) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::*;
+    use crate::automata::{Dfa, DfaState};
+    use crate::{Acceptance, Char, Machine, Verdict};
+
+    fn divisible_by_three() -> Dfa<u8, Char> {
+        Dfa::from_fn(
+            0,
+            &[Char('0'), Char('1')],
+            |rest, bit| Some((rest * 2 + (bit.0 == '1') as u8) % 3),
+            |rest| *rest == 0,
+        )
+    }
+
+    fn even_ones() -> Dfa<bool, Char> {
+        Dfa::from_fn(
+            false,
+            &[Char('0'), Char('1')],
+            |odd, bit| Some(*odd ^ (bit.0 == '1')),
+            |odd| !odd,
+        )
+    }
+
+    #[test]
+    fn intersection_test() {
+        let (a, b) = (divisible_by_three(), even_ones());
+        let table = product(&a.to_machine(), &b.to_machine(), Sync::Agree).unwrap();
+        let acceptance = Acceptance::FinalState(Vec::from([(DfaState::Accept, DfaState::Accept)]));
+
+        for n in 0..128u32 {
+            let input: Vec<Char> = alloc::format!("{n:b}").chars().map(Char).collect();
+            let mut machine = Machine::new(
+                (DfaState::State(0), DfaState::State(false)),
+                zip_tracks(&input, &input),
+            );
+            let expected = match a.accepts(&input) && b.accepts(&input) {
+                true => Verdict::Accept,
+                false => Verdict::Reject,
+            };
+
+            assert_eq!(
+                machine.run_table_accepting(&table, 1_000, &acceptance),
+                Ok(expected)
+            );
+        }
+    }
+
+    #[test]
+    fn sync_test() {
+        let right: TransitionTable<u8, bool> = [(0, false, Rule::new().write(true).right())]
+            .into_iter()
+            .collect();
+        let left: TransitionTable<u8, bool> =
+            [(0, false, Rule::new().left())].into_iter().collect();
+
+        let table = product(&right, &left, Sync::Agree).unwrap();
+        let mut machine = Machine::new((0, 0), zip_tracks(&[false], &[false]));
+        machine.run_table(&table, 10);
+
+        assert!(machine.halted());
+        assert_eq!(machine.tape_to_vec(), [(false, false)]);
+
+        let table = product(&right, &left, Sync::Second).unwrap();
+        let mut machine = Machine::new((0, 0), zip_tracks(&[false], &[false]));
+        let stats = machine.run_table(&table, 3);
+
+        assert_eq!(stats.steps, 3);
+        assert_eq!(machine.tape_to_vec()[1..], [(true, false); 3]);
+    }
+
+    #[test]
+    fn product_missing_test() {
+        // writes 1s rightwards by its default rule
+        let mut fill: TransitionTable<u8, bool> = [(0, true, Rule::halt())].into_iter().collect();
+        fill.set_missing(MissingRule::Default(Rule::new().write(true).right()));
+        // walks right over 0s, rejecting on a 1
+        let mut walk: TransitionTable<u8, bool> =
+            [(0, false, Rule::new().right())].into_iter().collect();
+        walk.set_missing(MissingRule::Reject);
+
+        let table = product(&fill, &walk, Sync::First).unwrap();
+        let mut machine = Machine::new((0, 0), zip_tracks(&[false, false], &[false, true]));
+        let stats = machine.run_table(&table, 10);
+
+        assert_eq!(table.missing(), &MissingRule::Reject);
+        // the second step halts on the 1, by the policy
+        assert_eq!(stats.steps, 2);
+        assert!(machine.halted());
+        assert_eq!(machine.tape_to_vec()[0], (true, false));
+
+        fill.set_missing(MissingRule::Error);
+        assert!(product(&fill, &walk, Sync::First).is_none());
+    }
+}
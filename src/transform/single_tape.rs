@@ -0,0 +1,478 @@
+use alloc::collections::{BTreeSet, VecDeque};
+use alloc::vec::Vec;
+use core::array;
+
+use crate::multi::{MultiRule, MultiTable};
+use crate::{Move, Rule, State, TransitionTable};
+
+/// A cell of the single tape simulating `K` tapes: either one of the end
+/// markers around the used region, or `K` tracks each holding a symbol and
+/// whether that tape's head is on it.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Cell<Sym, const K: usize> {
+    Start,
+    End,
+    Tracks([(Sym, bool); K]),
+}
+
+impl<Sym: Default, const K: usize> Default for Cell<Sym, K> {
+    fn default() -> Self {
+        Self::Tracks(array::from_fn(|_| (Sym::default(), false)))
+    }
+}
+
+/// The pending effects of a rule during the leftward update sweep.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Update<S, Sym, const K: usize> {
+    /// `None` halts once the sweep is over.
+    pub next: Option<S>,
+    pub write: [Option<Sym>; K],
+    pub head_move: [Option<Move>; K],
+    /// Tapes whose head has been updated.
+    pub done: [bool; K],
+    /// Tapes whose head moves onto the next cell to the left.
+    pub carry: [bool; K],
+}
+
+/// The states of a machine built by [`to_single_tape`].
+///
+/// Each step of the original machine is simulated by two sweeps between the
+/// end markers: a rightward [`Sim::Scan`] collecting the symbols under the
+/// heads, then a leftward [`Sim::Update`] writing them and moving the head
+/// markers, growing the region where a head leaves it.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Sim<S, Sym, const K: usize> {
+    Scan {
+        state: S,
+        seen: [Option<Sym>; K],
+    },
+    Update(Update<S, Sym, K>),
+    /// Stepped right to put down the head markers in `place`.
+    PlaceRight {
+        update: Update<S, Sym, K>,
+        place: [bool; K],
+    },
+    /// Writing the end marker one cell further right.
+    ExtendEnd(Update<S, Sym, K>),
+    /// Moving `cells` cells left before resuming the update sweep.
+    Back {
+        update: Update<S, Sym, K>,
+        cells: usize,
+    },
+    /// Writing the start marker one cell further left.
+    ExtendStart {
+        next: Option<S>,
+    },
+}
+
+impl<S, Sym, const K: usize> Sim<S, Sym, K> {
+    /// The state to start the single-tape machine in, on the start marker.
+    pub fn start(state: S) -> Self {
+        Self::Scan {
+            state,
+            seen: array::from_fn(|_| None),
+        }
+    }
+}
+
+/// Lays out tapes as tracks between end markers, with every head on cell
+/// 0. The single-tape machine starts on the start marker at index 0.
+pub fn encode<Sym, const K: usize>(tapes: [&[Sym]; K]) -> VecDeque<Cell<Sym, K>>
+where
+    Sym: Clone + Default,
+{
+    let len = tapes
+        .iter()
+        .map(|tape| tape.len())
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let mut cells = VecDeque::from([Cell::Start]);
+
+    cells.extend((0..len).map(|index| {
+        Cell::Tracks(array::from_fn(|tape| {
+            let symbol = tapes[tape].get(index).cloned().unwrap_or_default();
+
+            (symbol, index == 0)
+        }))
+    }));
+    cells.push_back(Cell::End);
+
+    cells
+}
+
+/// The tracks between the end markers with the index of each head, or
+/// `None` if a track has no head.
+pub fn decode<Sym, const K: usize>(cells: &[Cell<Sym, K>]) -> Option<[(Vec<Sym>, usize); K]>
+where
+    Sym: Clone,
+{
+    let first = cells.iter().position(|cell| matches!(cell, Cell::Start))?;
+    let last = cells.iter().position(|cell| matches!(cell, Cell::End))?;
+    let tracks = cells
+        .get(first + 1..last)?
+        .iter()
+        .map(|cell| match cell {
+            Cell::Tracks(tracks) => Some(tracks),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    let mut decoded = array::from_fn(|_| (Vec::new(), usize::MAX));
+
+    for (index, cell) in tracks.iter().enumerate() {
+        for (tape, (symbol, head)) in cell.iter().enumerate() {
+            decoded[tape].0.push(symbol.clone());
+
+            if *head {
+                decoded[tape].1 = index;
+            }
+        }
+    }
+
+    decoded
+        .iter()
+        .all(|(_, head)| *head != usize::MAX)
+        .then_some(decoded)
+}
+
+/// Builds a single-tape machine simulating the `K`-tape machine `table`,
+/// over the encoding produced by [`encode`].
+///
+/// Start it in [`Sim::start`] of the original start state. It halts exactly
+/// when the original machine does, with the same tracks, or when a rule
+/// moves a head more than one cell with [`Move::By`], which isn't simulated.
+pub fn to_single_tape<S, Sym, const K: usize>(
+    table: &MultiTable<S, Sym, K>,
+) -> TransitionTable<Sim<S, Sym, K>, Cell<Sym, K>>
+where
+    S: Ord + Clone,
+    Sym: Ord + Clone + Default,
+{
+    let mut alphabet = BTreeSet::from([Sym::default()]);
+    let mut states = BTreeSet::new();
+
+    for (state, read, rule) in table.iter() {
+        states.insert(state.clone());
+        alphabet.extend(read.iter().cloned());
+        alphabet.extend(rule.write.iter().flatten().cloned());
+
+        if let Some(State::State(next)) = &rule.new_state {
+            states.insert(next.clone());
+        }
+    }
+
+    // every cell the machine may read: the markers and all track contents
+    let mut cells = Vec::from([Cell::Start, Cell::End]);
+    let tracks: Vec<(Sym, bool)> = alphabet
+        .iter()
+        .flat_map(|symbol| [(symbol.clone(), false), (symbol.clone(), true)])
+        .collect();
+
+    for mut index in 0..tracks.len().pow(K as u32) {
+        cells.push(Cell::Tracks(array::from_fn(|_| {
+            let track = tracks[index % tracks.len()].clone();
+            index /= tracks.len();
+            track
+        })));
+    }
+
+    let mut single = TransitionTable::new();
+    let mut seen: BTreeSet<Sim<S, Sym, K>> = states.into_iter().map(Sim::start).collect();
+    let mut queue: Vec<Sim<S, Sym, K>> = seen.iter().cloned().collect();
+
+    while let Some(sim) = queue.pop() {
+        for cell in &cells {
+            let Some(rule) = step(table, &sim, cell) else {
+                continue;
+            };
+
+            if let Some(State::State(next)) = &rule.new_state {
+                if seen.insert(next.clone()) {
+                    queue.push(next.clone());
+                }
+            }
+
+            single.insert(sim.clone(), cell.clone(), rule);
+        }
+    }
+
+    single
+}
+
+// the rule of the single-tape machine in `sim` reading `cell`
+fn step<S, Sym, const K: usize>(
+    table: &MultiTable<S, Sym, K>,
+    sim: &Sim<S, Sym, K>,
+    cell: &Cell<Sym, K>,
+) -> Option<Rule<Sim<S, Sym, K>, Cell<Sym, K>>>
+where
+    S: Ord + Clone,
+    Sym: Ord + Clone + Default,
+{
+    let rule = match (sim, cell) {
+        (Sim::Scan { .. }, Cell::Start) => Rule::new().right(),
+        (Sim::Scan { state, seen }, Cell::Tracks(tracks)) => {
+            let seen = array::from_fn(|tape| match &tracks[tape] {
+                (symbol, true) => Some(symbol.clone()),
+                _ => seen[tape].clone(),
+            });
+
+            Rule::to(Sim::Scan {
+                state: state.clone(),
+                seen,
+            })
+            .right()
+        }
+        (Sim::Scan { state, seen }, Cell::End) => {
+            let read: [Sym; K] = array::from_fn(|tape| seen[tape].clone().unwrap_or_default());
+
+            match table.get(state, &read) {
+                Some(rule) if seen.iter().all(Option::is_some) && unit_moves(rule) => {
+                    let next = match &rule.new_state {
+                        None => Some(state.clone()),
+                        Some(State::State(next)) => Some(next.clone()),
+                        Some(State::Halt) => None,
+                    };
+
+                    Rule::to(Sim::Update(Update {
+                        next,
+                        write: rule.write.clone(),
+                        head_move: rule.head_move,
+                        done: [false; K],
+                        carry: [false; K],
+                    }))
+                    .left()
+                }
+                _ => Rule::halt(),
+            }
+        }
+        (Sim::Update(update), Cell::Start) if update.carry.contains(&true) => {
+            let mut tracks = Cell::default();
+
+            if let Cell::Tracks(tracks) = &mut tracks {
+                for (track, carry) in tracks.iter_mut().zip(update.carry) {
+                    track.1 = carry;
+                }
+            }
+
+            Rule::to(Sim::ExtendStart {
+                next: update.next.clone(),
+            })
+            .write(tracks)
+            .left()
+        }
+        (Sim::Update(update), Cell::Start) => resume(update.next.clone()),
+        (Sim::Update(update), Cell::Tracks(tracks)) => {
+            let mut update = update.clone();
+            let mut tracks = tracks.clone();
+            let mut place = [false; K];
+
+            for tape in 0..K {
+                if update.carry[tape] {
+                    // a head moving left from the cell to the right
+                    tracks[tape].1 = true;
+                    update.carry[tape] = false;
+                } else if tracks[tape].1 && !update.done[tape] {
+                    if let Some(write) = &update.write[tape] {
+                        tracks[tape].0 = write.clone();
+                    }
+
+                    update.done[tape] = true;
+
+                    match update.head_move[tape].map_or(0, Move::offset) {
+                        -1 => {
+                            tracks[tape].1 = false;
+                            update.carry[tape] = true;
+                        }
+                        1 => {
+                            tracks[tape].1 = false;
+                            place[tape] = true;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+
+            let rule = match place.contains(&true) {
+                true => Rule::to(Sim::PlaceRight { update, place }).right(),
+                false => Rule::to(Sim::Update(update)).left(),
+            };
+
+            rule.write(Cell::Tracks(tracks))
+        }
+        (Sim::PlaceRight { update, place }, Cell::Tracks(tracks)) => {
+            let mut tracks = tracks.clone();
+
+            for (track, place) in tracks.iter_mut().zip(place) {
+                track.1 |= place;
+            }
+
+            Rule::to(Sim::Back {
+                update: update.clone(),
+                cells: 1,
+            })
+            .write(Cell::Tracks(tracks))
+            .left()
+        }
+        (Sim::PlaceRight { update, place }, Cell::End) => {
+            let mut tracks = Cell::default();
+
+            if let Cell::Tracks(tracks) = &mut tracks {
+                for (track, place) in tracks.iter_mut().zip(place) {
+                    track.1 = *place;
+                }
+            }
+
+            Rule::to(Sim::ExtendEnd(update.clone()))
+                .write(tracks)
+                .right()
+        }
+        (Sim::ExtendEnd(update), _) => Rule::to(Sim::Back {
+            update: update.clone(),
+            cells: 2,
+        })
+        .write(Cell::End)
+        .left(),
+        (Sim::Back { update, cells }, _) => {
+            let next = match cells {
+                1 => Sim::Update(update.clone()),
+                _ => Sim::Back {
+                    update: update.clone(),
+                    cells: cells - 1,
+                },
+            };
+
+            Rule::to(next).left()
+        }
+        (Sim::ExtendStart { next }, _) => resume(next.clone()).write(Cell::Start),
+        (Sim::Update(_), Cell::End) | (Sim::PlaceRight { .. }, Cell::Start) => return None,
+    };
+
+    Some(rule)
+}
+
+// the rule starting the next simulated step from the start marker
+fn resume<S, Sym, const K: usize>(next: Option<S>) -> Rule<Sim<S, Sym, K>, Cell<Sym, K>> {
+    match next {
+        Some(next) => Rule::to(Sim::start(next)),
+        None => Rule::halt(),
+    }
+}
+
+// whether every head of `rule` moves at most one cell
+fn unit_moves<S, Sym, const K: usize>(rule: &MultiRule<S, Sym, K>) -> bool {
+    rule.head_move
+        .iter()
+        .all(|head_move| head_move.map_or(0, Move::offset).abs() <= 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::collections::BTreeMap;
+
+    use super::*;
+    use crate::multi::MultiMachine;
+    use crate::{Machine, StopReason};
+
+    // copies tape 0 onto tape 1, then appends tape 1 reversed to tape 0,
+    // walking the head of tape 1 off its left end
+    fn copy_reverse() -> MultiTable<u8, u8, 2> {
+        let mut table = MultiTable::new();
+        let rule = |next, write, head_move| MultiRule {
+            new_state: next,
+            write,
+            head_move,
+        };
+
+        for symbol in 1..=2 {
+            table.insert(
+                0,
+                [symbol, 0],
+                rule(None, [None, Some(symbol)], [Some(Move::Right); 2]),
+            );
+            table.insert(
+                1,
+                [0, symbol],
+                rule(
+                    None,
+                    [Some(symbol), None],
+                    [Some(Move::Right), Some(Move::Left)],
+                ),
+            );
+        }
+
+        table.insert(
+            0,
+            [0, 0],
+            rule(
+                Some(State::State(1)),
+                [None, None],
+                [None, Some(Move::Left)],
+            ),
+        );
+        table.insert(
+            1,
+            [0, 0],
+            rule(Some(State::Halt), [None, None], [None, Some(Move::Left)]),
+        );
+
+        table
+    }
+
+    // the non-blank cells and the head, relative to cell 0 at `zero`
+    fn relative(tape: &[u8], head: usize, zero: usize) -> (BTreeMap<isize, u8>, isize) {
+        let cells = tape
+            .iter()
+            .enumerate()
+            .filter(|(_, symbol)| **symbol != 0)
+            .map(|(index, symbol)| (index as isize - zero as isize, *symbol))
+            .collect();
+
+        (cells, head as isize - zero as isize)
+    }
+
+    #[test]
+    fn single_tape_test() {
+        let table = copy_reverse();
+        let single = to_single_tape(&table);
+
+        for input in [&[1, 2, 2][..], &[2], &[]] {
+            let mut multi =
+                MultiMachine::new(0, [input.iter().copied().collect(), VecDeque::new()]);
+            let multi_stats = multi.run(&table, 1_000);
+
+            let mut machine: Machine<Sim<u8, u8, 2>, Cell<u8, 2>> =
+                Machine::new(Sim::start(0), encode([input, &[]]));
+            let stats = machine.run_table(&single, 100_000);
+
+            assert_eq!(multi_stats.stop, StopReason::Halted);
+            assert_eq!(stats.stop, StopReason::Halted);
+
+            let tracks = decode(&machine.tape_to_vec()).unwrap();
+
+            for (tape, (cells, head)) in tracks.iter().enumerate() {
+                let expected: Vec<u8> = multi.tapes()[tape].iter().copied().collect();
+
+                // the start marker precedes cell 0 of every track
+                assert_eq!(
+                    relative(cells, *head, machine.origin()),
+                    relative(&expected, multi.heads()[tape], multi.origins()[tape]),
+                    "tape {tape} on input {input:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn encode_decode_test() {
+        let cells = encode::<u8, 2>([&[1, 2], &[3]]);
+
+        assert_eq!(cells.len(), 4);
+        assert_eq!(cells[1], Cell::Tracks([(1, true), (3, true)]));
+        assert_eq!(
+            decode(&Vec::from(cells)),
+            Some([(Vec::from([1, 2]), 0), (Vec::from([3, 0]), 0)])
+        );
+    }
+}
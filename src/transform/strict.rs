@@ -0,0 +1,149 @@
+use alloc::collections::BTreeSet;
+
+use crate::{analysis, MissingRule, Move, Rule, State, TransitionTable};
+
+/// The states of a machine built by [`to_strict_moves`].
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Strict<S> {
+    State(S),
+    /// Moving `remaining` more cells towards `head_move` before entering
+    /// `next`, or halting for `None`.
+    Moving {
+        next: Option<S>,
+        head_move: Move,
+        remaining: usize,
+    },
+}
+
+/// Builds a machine equivalent to `table` whose rules all move the head one
+/// cell left or right, for formats without stays or longer moves. A rule
+/// that stays moves right and then back left, and a move of `n` cells takes
+/// `n` steps.
+///
+/// Start it in `Strict::State` of the original start state. A
+/// [`MissingRule::Default`] becomes a rule for every symbol of the alphabet
+/// without one.
+pub fn to_strict_moves<S, Sym>(table: &TransitionTable<S, Sym>) -> TransitionTable<Strict<S>, Sym>
+where
+    S: Ord + Clone,
+    Sym: Ord + Clone + Default,
+{
+    let mut alphabet: BTreeSet<Sym> = analysis::alphabet(table).into_iter().cloned().collect();
+    alphabet.insert(Sym::default());
+
+    let missing = match table.missing() {
+        MissingRule::Default(_) | MissingRule::Halt => MissingRule::Halt,
+        MissingRule::Reject => MissingRule::Reject,
+        MissingRule::Error => MissingRule::Error,
+    };
+    let mut strict = TransitionTable::new().with_missing(missing);
+    let mut states: BTreeSet<&S> = table.states().collect();
+
+    for (_, _, rule) in table.iter() {
+        if let Some(State::State(next)) = &rule.new_state {
+            states.insert(next);
+        }
+    }
+
+    for state in states {
+        for symbol in &alphabet {
+            let rule = match (table.get(state, symbol), table.missing()) {
+                (Some(rule), _) | (None, MissingRule::Default(rule)) => rule.clone(),
+                (None, _) => continue,
+            };
+
+            let next = match rule.new_state {
+                None => Some(state.clone()),
+                Some(State::State(next)) => Some(next),
+                Some(State::Halt) => None,
+            };
+            let offset = rule.head_move.map_or(0, Move::offset);
+            // stays go right first, then take the remaining move back
+            let (first, head_move, remaining) = match offset {
+                0 => (Move::Right, Move::Left, 1),
+                offset if offset < 0 => (Move::Left, Move::Left, offset.unsigned_abs() - 1),
+                offset => (Move::Right, Move::Right, offset as usize - 1),
+            };
+
+            strict.insert(
+                Strict::State(state.clone()),
+                symbol.clone(),
+                Rule {
+                    new_state: Some(moving(next.clone(), head_move, remaining)),
+                    write: rule.write,
+                    head_move: Some(first),
+                },
+            );
+
+            for remaining in 1..=remaining {
+                for symbol in &alphabet {
+                    strict.insert(
+                        Strict::Moving {
+                            next: next.clone(),
+                            head_move,
+                            remaining,
+                        },
+                        symbol.clone(),
+                        Rule {
+                            new_state: Some(moving(next.clone(), head_move, remaining - 1)),
+                            write: None,
+                            head_move: Some(head_move),
+                        },
+                    );
+                }
+            }
+        }
+    }
+
+    strict
+}
+
+// the state with `remaining` more cells to move before entering `next`
+fn moving<S>(next: Option<S>, head_move: Move, remaining: usize) -> State<Strict<S>> {
+    match (remaining, next) {
+        (0, Some(next)) => State::State(Strict::State(next)),
+        (0, None) => State::Halt,
+        (remaining, next) => State::State(Strict::Moving {
+            next,
+            head_move,
+            remaining,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Machine;
+
+    #[test]
+    fn to_strict_moves_test() {
+        // marks every other cell of the input, staying on each mark, and
+        // jumps back three cells from the blank after it
+        let table: TransitionTable<u8, u8> = [
+            (0, 1, Rule::to(1).write(2)),
+            (1, 2, Rule::to(0).by(2)),
+            (0, 0, Rule::halt().write(3).by(-3)),
+        ]
+        .into_iter()
+        .collect();
+        let strict = to_strict_moves(&table);
+
+        assert!(strict
+            .iter()
+            .all(|(_, _, rule)| matches!(rule.head_move, Some(Move::Left | Move::Right))));
+
+        let mut machine: Machine<u8, u8> = Machine::new(0, [1, 1, 1, 1].into());
+        let mut strict_machine = Machine::new(Strict::State(0), [1, 1, 1, 1].into());
+
+        assert_eq!(machine.run_table(&table, 100).steps, 5);
+        assert_eq!(strict_machine.run_table(&strict, 100).steps, 4 * 2 + 3);
+
+        let (tape, _, head) = machine.finish_trimmed();
+        let (strict_tape, state, strict_head) = strict_machine.finish_trimmed();
+
+        assert_eq!(tape, [2, 1, 2, 1, 3]);
+        assert_eq!((strict_tape, strict_head), (tape, head));
+        assert_eq!(state, State::Halt);
+    }
+}
@@ -0,0 +1,553 @@
+//! A universal machine, simulating machines encoded on its tape.
+//!
+//! An encoded machine numbers its states from 1, with 0 for halting, and
+//! its symbols from 0, the blank. Every number is a field of the same width
+//! in binary, least significant bit first. The tape of the universal
+//! machine reads
+//!
+//! ```text
+//! ,c,c[S:R];q a>q' b d;q a>q' b d|^c,c,c
+//! ```
+//!
+//! where `S` is the current state, `R` a scratch field, each `;q a>q' b d`
+//! a rule reading `a` in state `q` that moves to `q'` after writing `b` and
+//! moving by `d`, one of `L`, `R` or `N`, and each `,c` a cell of the
+//! simulated tape, the one under the head marked with `^` instead. Cells
+//! left of cell 0 are stored left of the register and grow to the left,
+//! the others right of the rules, so the simulated tape can grow both ways.
+//! A missing rule halts the simulation with `S` left as it is.
+
+use alloc::collections::BTreeSet;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::dsl::Definition;
+use crate::{asm, Char, Move, Snapshot, State, TransitionTable};
+
+const UNIVERSAL: &str = "
+    # copies the symbol under the head into R
+    fetch:
+        call to_register
+        move right until :
+        move right
+        call skip_marked
+        if ] goto lookup
+        call to_head
+        move right
+        call skip_marked
+        if 1 goto fetch1
+        write x
+        call to_register
+        move right until :
+        move right
+        call skip_marked
+        write x
+        goto fetch
+    fetch1:
+        write y
+        call to_register
+        move right until :
+        move right
+        call skip_marked
+        write y
+        goto fetch
+
+    # compares S and R with the first rule not rejected yet, bit by bit
+    lookup:
+        call unmark
+    compare:
+        call to_register
+        move right
+        call skip_marked
+        if : goto skip_colon
+        goto compare_bit
+    skip_colon:
+        move right
+        call skip_marked
+    compare_bit:
+        if ] goto matched
+        if 1 goto compare1
+        write x
+        call to_rule
+        if | goto stuck
+        move right
+        call skip_marked
+        if 1 goto reject
+        write x
+        goto compare
+    compare1:
+        write y
+        call to_rule
+        if | goto stuck
+        move right
+        call skip_marked
+        if 0 goto reject
+        write y
+        goto compare
+    reject:
+        move left until ;
+        write !
+        call unmark
+        goto compare
+
+    # copies the new state into S and the symbol to write under the head
+    matched:
+        call unmark
+    copy_state:
+        call to_register
+        move right
+        call skip_marked
+        if : goto copy_symbol
+        call source
+        if 1 goto state1
+        write x
+        call to_register
+        move right
+        call skip_marked
+        write x
+        goto copy_state
+    state1:
+        write y
+        call to_register
+        move right
+        call skip_marked
+        write y
+        goto copy_state
+    copy_symbol:
+        call to_head
+        move right
+        call skip_marked
+        if , [ _ goto move_head
+        call source
+        if 1 goto symbol1
+        write x
+        call to_head
+        move right
+        call skip_marked
+        write x
+        goto copy_symbol
+    symbol1:
+        write y
+        call to_head
+        move right
+        call skip_marked
+        write y
+        goto copy_symbol
+
+    move_head:
+        call source
+        if L goto left
+        if R goto right
+        goto next
+    right:
+        call cleanup
+        call to_head
+        write ,
+        move right
+        move right until , [ _
+        if , goto mark_head
+        if _ goto extend_right
+        # from cell -1 to cell 0
+        move right until |
+        move right
+        if , goto mark_head
+    extend_right:
+        write ^
+        call new_cell_right
+        goto next
+    mark_head:
+        write ^
+        goto next
+    left:
+        call cleanup
+        call to_head
+        write ,
+        move left
+        if _ goto extend_left
+        if | goto cross_left
+        move left until ,
+        write ^
+        goto next
+    cross_left:
+        move left until [
+        move left
+        if _ goto extend_left
+        move left until ,
+        write ^
+        goto next
+    extend_left:
+        call new_cell_left
+
+    # halts in state 0
+    next:
+        call cleanup
+        call to_register
+        move right until 1 :
+        if 1 goto fetch
+        halt
+    stuck:
+        call cleanup
+        halt
+
+    # onto the leftmost cell of the tape, from anywhere right of it
+    sub far_left
+        move left
+        move left until _
+        move right
+
+    sub to_head
+        call far_left
+        move right until ^
+
+    sub to_register
+        call far_left
+        move right until [
+
+    # onto the first rule not rejected, or the | after the rules
+    sub to_rule
+        call to_register
+        move right until ; |
+
+    # onto the first unmarked symbol of the new state, symbol and move of
+    # the matched rule
+    sub source
+        call to_rule
+        move right until >
+        move right
+        call skip_marked
+
+    sub skip_marked
+        move right until 0 1 , ^ [ : ] ; ! > | L R N _
+
+    sub mark
+        if 1 goto one
+        write x
+        return
+    one:
+        write y
+
+    sub unmark
+        call far_left
+    loop:
+        move right until x y _
+        if _ goto done
+        if x goto zero
+        write 1
+        goto loop
+    zero:
+        write 0
+        goto loop
+    done:
+
+    # unmarks and restores the rejected rules
+    sub cleanup
+        call far_left
+    loop:
+        move right until x y ! _
+        if _ goto done
+        if x goto zero
+        if y goto one
+        write ;
+        goto loop
+    zero:
+        write 0
+        goto loop
+    one:
+        write 1
+        goto loop
+    done:
+
+    # appends a blank cell, as wide as S, right of everything
+    sub new_cell_right
+    loop:
+        call to_register
+        move right
+        call skip_marked
+        if : goto done
+        call mark
+        move right until _
+        write 0
+        goto loop
+    done:
+        call unmark
+
+    # prepends a blank cell under the head, left of everything
+    sub new_cell_left
+    loop:
+        call to_register
+        move right
+        call skip_marked
+        if : goto done
+        call mark
+        call far_left
+        move left
+        write 0
+        goto loop
+    done:
+        call far_left
+        move left
+        write ^
+        call unmark
+";
+
+/// The universal machine, compiled from a program in the
+/// [`asm`](crate::asm) language.
+pub fn universal() -> Definition<Char> {
+    asm::compile(UNIVERSAL).unwrap()
+}
+
+/// How the states and symbols of a machine are numbered on the tape of the
+/// [`universal`] machine.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Encoding<S, Sym> {
+    /// The states numbered from 1.
+    pub states: Vec<S>,
+    /// The symbols numbered from 0, the blank first.
+    pub symbols: Vec<Sym>,
+    pub width: usize,
+}
+
+impl<S, Sym> Encoding<S, Sym>
+where
+    S: Ord + Clone,
+    Sym: Ord + Clone + Default,
+{
+    /// Numbers the states and symbols of `table`, `start` first among the
+    /// states.
+    pub fn new(table: &TransitionTable<S, Sym>, start: &S) -> Self {
+        let mut states = BTreeSet::new();
+        let mut symbols = BTreeSet::new();
+
+        for (state, read, rule) in table.iter() {
+            states.insert(state);
+            symbols.insert(read);
+            states.extend(match &rule.new_state {
+                Some(State::State(next)) => Some(next),
+                _ => None,
+            });
+            symbols.extend(&rule.write);
+        }
+
+        let blank = Sym::default();
+        states.remove(start);
+        symbols.remove(&blank);
+
+        let states: Vec<S> = [start].into_iter().chain(states).cloned().collect();
+        let symbols: Vec<Sym> = [&blank].into_iter().chain(symbols).cloned().collect();
+        let largest = states.len().max(symbols.len() - 1);
+
+        Self {
+            width: (usize::BITS - largest.leading_zeros()).max(1) as usize,
+            states,
+            symbols,
+        }
+    }
+
+    /// The tape of the universal machine simulating `table` in `state` on
+    /// `input`, with the head on the first cell, or `None` if `state` or a
+    /// symbol of `input` was not numbered or a rule moves more than one cell.
+    pub fn encode(
+        &self,
+        table: &TransitionTable<S, Sym>,
+        state: &S,
+        input: &[Sym],
+    ) -> Option<Vec<Char>> {
+        let mut out = String::from("[");
+        out.push_str(&self.field(self.state_code(state)?));
+        out.push(':');
+        out.push_str(&self.field(0));
+        out.push(']');
+
+        for (from, read, rule) in table.iter() {
+            let next = match &rule.new_state {
+                None => self.state_code(from)?,
+                Some(State::State(next)) => self.state_code(next)?,
+                Some(State::Halt) => 0,
+            };
+            let write = rule.write.as_ref().unwrap_or(read);
+
+            out.push(';');
+            out.push_str(&self.field(self.state_code(from)?));
+            out.push_str(&self.field(self.symbol_code(read)?));
+            out.push('>');
+            out.push_str(&self.field(next));
+            out.push_str(&self.field(self.symbol_code(write)?));
+            out.push(match rule.head_move.map_or(0, Move::offset) {
+                0 => 'N',
+                -1 => 'L',
+                1 => 'R',
+                _ => return None,
+            });
+        }
+
+        out.push('|');
+
+        let blank = [Sym::default()];
+        let input = match input.is_empty() {
+            true => &blank[..],
+            false => input,
+        };
+
+        for (index, symbol) in input.iter().enumerate() {
+            out.push(if index == 0 { '^' } else { ',' });
+            out.push_str(&self.field(self.symbol_code(symbol)?));
+        }
+
+        Some(out.chars().map(Char).collect())
+    }
+
+    /// The configuration of the simulated machine on the tape of the
+    /// universal machine, or `None` if it is not a valid encoding. The
+    /// simulated machine counts as halted only in state 0.
+    pub fn decode(&self, tape: &[Char]) -> Option<Snapshot<S, Sym>> {
+        let tape: String = tape.iter().map(|symbol| symbol.0).collect();
+        let tape = tape.trim_matches(Char::default().0);
+
+        let (left, rest) = tape.split_once('[')?;
+        let (state, rest) = rest.split_once(':')?;
+        let (_, right) = rest.split_once('|')?;
+
+        let mut cells = Vec::new();
+        let mut head = None;
+
+        for cell in left.chars().chain(right.chars()) {
+            match cell {
+                ',' => cells.push(String::new()),
+                '^' => {
+                    head = Some(cells.len());
+                    cells.push(String::new());
+                }
+                bit => cells.last_mut()?.push(bit),
+            }
+        }
+
+        let origin = left.chars().filter(|&c| c == ',' || c == '^').count();
+        let tape = cells
+            .iter()
+            .map(|cell| self.symbols.get(self.value(cell)?).cloned())
+            .collect::<Option<Vec<Sym>>>()?;
+        let state = match self.value(state)? {
+            0 => State::Halt,
+            code => State::State(self.states.get(code - 1)?.clone()),
+        };
+
+        Some(Snapshot {
+            state,
+            tape,
+            head: head?,
+            origin,
+        })
+    }
+
+    fn state_code(&self, state: &S) -> Option<usize> {
+        self.states
+            .iter()
+            .position(|other| other == state)
+            .map(|index| index + 1)
+    }
+
+    fn symbol_code(&self, symbol: &Sym) -> Option<usize> {
+        self.symbols.iter().position(|other| other == symbol)
+    }
+
+    fn field(&self, value: usize) -> String {
+        (0..self.width)
+            .map(|bit| match (value >> bit) & 1 {
+                1 => '1',
+                _ => '0',
+            })
+            .collect()
+    }
+
+    fn value(&self, field: &str) -> Option<usize> {
+        if field.len() != self.width {
+            return None;
+        }
+
+        field.chars().rev().try_fold(0, |value, bit| match bit {
+            '0' => Some(value * 2),
+            '1' => Some(value * 2 + 1),
+            _ => None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::borrow::ToOwned;
+
+    use super::*;
+    use crate::{dsl, Machine};
+
+    // runs `src` directly and on the universal machine, comparing the tapes
+    fn cosimulate(src: &str, start: &str, input: &str) -> Snapshot<String, Char> {
+        let table = dsl::parse::<Char>(src).unwrap();
+        let mut machine: Machine<String, Char> =
+            Machine::with_input(start.to_owned(), input).unwrap();
+        machine.run_table(&table, 1_000);
+
+        assert!(machine.halted());
+
+        let encoding = Encoding::new(&table, &start.to_owned());
+        let input: Vec<Char> = input.chars().map(Char).collect();
+        let tape = encoding.encode(&table, &start.to_owned(), &input).unwrap();
+
+        let utm = universal();
+        let mut universal: Machine<String, Char> = Machine::new(utm.start, tape.into());
+        universal.run_table(&utm.table, 10_000_000);
+
+        assert!(universal.halted());
+
+        let simulated = encoding.decode(&universal.tape_to_vec()).unwrap();
+        let expected = machine.snapshot();
+
+        assert_eq!(simulated.tape, expected.tape);
+        assert_eq!(
+            simulated.head as isize - simulated.origin as isize,
+            expected.head as isize - expected.origin as isize
+        );
+
+        simulated
+    }
+
+    #[test]
+    fn encoding_test() {
+        let table = dsl::parse::<Char>("a _ 1 R b\nb _ * L a\nb 1 _ N halt").unwrap();
+        let encoding = Encoding::new(&table, &"b".to_owned());
+
+        assert_eq!(encoding.states, ["b", "a"]);
+        assert_eq!(encoding.symbols, [Char('_'), Char('1')]);
+        assert_eq!(encoding.width, 2);
+
+        let tape = encoding
+            .encode(&table, &"b".to_owned(), &[Char('1')])
+            .unwrap();
+        let tape: String = tape.iter().map(|symbol| symbol.0).collect();
+
+        assert_eq!(tape, "[10:00];0100>1010R;1010>0000N;1000>0100L|^10");
+        assert_eq!(
+            encoding.decode(&tape.chars().map(Char).collect::<Vec<_>>()),
+            Some(Snapshot {
+                state: State::State("b".to_owned()),
+                tape: Vec::from([Char('1')]),
+                head: 0,
+                origin: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn universal_test() {
+        // the 2-state busy beaver, growing the tape both ways
+        let beaver = cosimulate("a _ 1 R b\na 1 1 L b\nb _ 1 L a\nb 1 1 R halt", "a", "");
+
+        assert_eq!(beaver.state, State::Halt);
+        assert_eq!(beaver.tape, [Char('1'); 4]);
+
+        // binary increment, halting on a missing rule
+        let inc = cosimulate("inc 1 0 R *\ninc 0 1 N done", "inc", "110");
+
+        assert_eq!(inc.state, State::State("done".to_owned()));
+        assert_eq!(inc.tape, [Char('0'), Char('0'), Char('1')]);
+    }
+}
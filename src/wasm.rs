@@ -0,0 +1,131 @@
+//! JavaScript bindings for browser playgrounds, built with e.g.
+//! `cargo rustc --lib --release --target wasm32-unknown-unknown --features wasm --crate-type cdylib`
+//! followed by `wasm-bindgen`.
+//!
+//! States and symbols are plain numbers on the JS side; symbol `0` is the
+//! blank. Moves are signed distances: `-1` (left), `1` (right), `0` (stay)
+//! or any other number of cells.
+
+use std::collections::VecDeque;
+
+use wasm_bindgen::prelude::*;
+
+use crate::{Machine, Move, Rule, State, TransitionTable};
+
+#[wasm_bindgen]
+pub struct WasmMachine {
+    machine: Machine<u32, u32>,
+    table: TransitionTable<u32, u32>,
+    steps: u64,
+}
+
+#[wasm_bindgen]
+impl WasmMachine {
+    #[wasm_bindgen(constructor)]
+    pub fn new(state: u32, tape: Vec<u32>) -> Self {
+        let mut tape: VecDeque<u32> = tape.into();
+
+        if tape.is_empty() {
+            tape.push_back(0);
+        }
+
+        Self {
+            machine: Machine::new(state, tape),
+            table: TransitionTable::new(),
+            steps: 0,
+        }
+    }
+
+    /// Adds the rule for reading `read` in `state`. A missing `next` halts
+    /// the machine; a missing `write` leaves the cell untouched.
+    pub fn add_rule(
+        &mut self,
+        state: u32,
+        read: u32,
+        write: Option<u32>,
+        head_move: i32,
+        next: Option<u32>,
+    ) {
+        let rule = Rule {
+            new_state: Some(next.map_or(State::Halt, State::State)),
+            write,
+            head_move: Move::by(head_move as isize),
+        };
+
+        self.table.insert(state, read, rule);
+    }
+
+    /// Performs a single step; returns `false` if the machine had already
+    /// halted.
+    pub fn step(&mut self) -> bool {
+        if self.machine.halted() {
+            return false;
+        }
+
+        self.machine.execute_table(&self.table);
+        self.steps += 1;
+
+        true
+    }
+
+    /// Steps until halting or until `limit` steps were taken; returns the
+    /// number of steps taken.
+    pub fn run(&mut self, limit: u32) -> u32 {
+        let mut taken = 0;
+
+        while taken < limit && self.step() {
+            taken += 1;
+        }
+
+        taken
+    }
+
+    pub fn halted(&self) -> bool {
+        self.machine.halted()
+    }
+
+    /// The current state, or `undefined` once halted.
+    pub fn state(&self) -> Option<u32> {
+        self.machine.state().copied()
+    }
+
+    pub fn head(&self) -> usize {
+        self.machine.head()
+    }
+
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    pub fn tape(&self) -> Vec<u32> {
+        self.machine.tape_to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wasm_machine_test() {
+        // increments a little-endian binary number
+        let mut machine = WasmMachine::new(0, vec![1, 1]);
+        machine.add_rule(0, 1, Some(0), 1, Some(0));
+        machine.add_rule(0, 0, Some(1), 0, None);
+
+        assert_eq!(machine.run(100), 3);
+        assert!(machine.halted());
+        assert!(!machine.step());
+        assert_eq!((machine.state(), machine.steps()), (None, 3));
+        assert_eq!(machine.tape(), [0, 0, 1]);
+
+        // moves keep their distance
+        let mut machine = WasmMachine::new(0, Vec::new());
+        machine.add_rule(0, 0, Some(1), 2, Some(1));
+        machine.add_rule(1, 0, Some(1), 0, None);
+
+        assert_eq!(machine.run(100), 2);
+        assert_eq!(machine.head(), 2);
+        assert_eq!(machine.tape(), [1, 0, 1]);
+    }
+}
@@ -0,0 +1,93 @@
+use alloc::vec::Vec;
+
+use crate::{Machine, Move, Rule, State, Tape};
+
+/// A rule writing a word of symbols from the head rightwards, then leaving
+/// the head `head` cells right of where it started, or left of it if
+/// negative. A [`Rule`] is the special case of a word of at most one symbol
+/// and a head offset of -1, 0 or 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WordRule<S, Sym> {
+    pub new_state: Option<State<S>>,
+    pub word: Vec<Sym>,
+    pub head: isize,
+}
+
+impl<S, Sym> From<Rule<S, Sym>> for WordRule<S, Sym> {
+    fn from(rule: Rule<S, Sym>) -> Self {
+        Self {
+            new_state: rule.new_state,
+            word: rule.write.into_iter().collect(),
+            head: rule.head_move.map_or(0, Move::offset),
+        }
+    }
+}
+
+impl<S, Sym, T> Machine<S, Sym, T>
+where
+    Sym: Default + Clone,
+    T: Tape<Sym>,
+{
+    /// Applies `rule` to the current configuration, regardless of whether
+    /// the machine has halted, growing the tape as needed.
+    pub fn apply_word(&mut self, rule: WordRule<S, Sym>) {
+        let WordRule {
+            new_state,
+            word,
+            head,
+        } = rule;
+
+        if let Some(new_state) = new_state {
+            self.state = new_state;
+        }
+
+        // the offset of the head from the start of the word
+        let mut at = 0;
+
+        for (index, symbol) in word.into_iter().enumerate() {
+            if index > 0 {
+                self.head_move_right();
+                at += 1;
+            }
+
+            self.write_tape(symbol);
+        }
+
+        while at < head {
+            self.head_move_right();
+            at += 1;
+        }
+
+        while at > head {
+            self.head_move_left();
+            at -= 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply_word_test() {
+        let mut machine: Machine<u8, u8> = Machine::new(0, [0].into());
+        machine.apply_word(WordRule {
+            new_state: Some(State::State(1)),
+            word: Vec::from([1, 2, 3]),
+            head: -1,
+        });
+
+        assert_eq!(machine.state(), Some(&1));
+        assert_eq!(machine.tape_to_vec(), [0, 1, 2, 3]);
+        assert_eq!(machine.head() as isize - machine.origin() as isize, -1);
+
+        // the same as applying the rule itself
+        let rule = Rule::halt().write(4).right();
+        let mut word = machine.clone();
+        machine.apply(rule.clone());
+        word.apply_word(rule.into());
+
+        assert_eq!(word, machine);
+    }
+}